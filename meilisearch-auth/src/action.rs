@@ -22,8 +22,24 @@ pub enum Action {
     IndexesUpdate = actions::INDEXES_UPDATE,
     #[serde(rename = "indexes.delete")]
     IndexesDelete = actions::INDEXES_DELETE,
+    #[serde(rename = "indexes.swap")]
+    IndexesSwap = actions::INDEXES_SWAP,
+    #[serde(rename = "aliases.get")]
+    AliasesGet = actions::ALIASES_GET,
+    #[serde(rename = "aliases.update")]
+    AliasesUpdate = actions::ALIASES_UPDATE,
+    #[serde(rename = "templates.get")]
+    TemplatesGet = actions::TEMPLATES_GET,
+    #[serde(rename = "templates.update")]
+    TemplatesUpdate = actions::TEMPLATES_UPDATE,
     #[serde(rename = "tasks.get")]
     TasksGet = actions::TASKS_GET,
+    #[serde(rename = "tasks.cancel")]
+    TasksCancel = actions::TASKS_CANCEL,
+    #[serde(rename = "tasks.delete")]
+    TasksDelete = actions::TASKS_DELETE,
+    #[serde(rename = "tasks.update")]
+    TasksUpdate = actions::TASKS_UPDATE,
     #[serde(rename = "settings.get")]
     SettingsGet = actions::SETTINGS_GET,
     #[serde(rename = "settings.update")]
@@ -32,6 +48,10 @@ pub enum Action {
     StatsGet = actions::STATS_GET,
     #[serde(rename = "dumps.create")]
     DumpsCreate = actions::DUMPS_CREATE,
+    #[serde(rename = "dumps.import")]
+    DumpsImport = actions::DUMPS_IMPORT,
+    #[serde(rename = "snapshots.create")]
+    SnapshotsCreate = actions::SNAPSHOTS_CREATE,
     #[serde(rename = "version")]
     Version = actions::VERSION,
     #[serde(rename = "keys.create")]
@@ -42,6 +62,48 @@ pub enum Action {
     KeysUpdate = actions::KEYS_UPDATE,
     #[serde(rename = "keys.delete")]
     KeysDelete = actions::KEYS_DELETE,
+    #[serde(rename = "keys.rotate")]
+    KeysRotate = actions::KEYS_ROTATE,
+    #[serde(rename = "webhooks.get")]
+    WebhooksGet = actions::WEBHOOKS_GET,
+    #[serde(rename = "webhooks.update")]
+    WebhooksUpdate = actions::WEBHOOKS_UPDATE,
+    #[serde(rename = "auditLog.get")]
+    AuditLogGet = actions::AUDIT_LOG_GET,
+    #[serde(rename = "maintenance.get")]
+    MaintenanceGet = actions::MAINTENANCE_GET,
+    #[serde(rename = "maintenance.update")]
+    MaintenanceUpdate = actions::MAINTENANCE_UPDATE,
+    #[serde(rename = "config.get")]
+    ConfigGet = actions::CONFIG_GET,
+    #[serde(rename = "config.update")]
+    ConfigUpdate = actions::CONFIG_UPDATE,
+    #[serde(rename = "experimental.get")]
+    ExperimentalFeaturesGet = actions::EXPERIMENTAL_FEATURES_GET,
+    #[serde(rename = "experimental.update")]
+    ExperimentalFeaturesUpdate = actions::EXPERIMENTAL_FEATURES_UPDATE,
+    #[serde(rename = "analytics.get")]
+    SearchAnalyticsGet = actions::SEARCH_ANALYTICS_GET,
+    #[serde(rename = "replication.get")]
+    ReplicationGet = actions::REPLICATION_GET,
+    #[serde(rename = "documents.*")]
+    DocumentsAll = actions::DOCUMENTS_ALL,
+    #[serde(rename = "indexes.*")]
+    IndexesAll = actions::INDEXES_ALL,
+    #[serde(rename = "tasks.*")]
+    TasksAll = actions::TASKS_ALL,
+    #[serde(rename = "settings.*")]
+    SettingsAll = actions::SETTINGS_ALL,
+    #[serde(rename = "dumps.*")]
+    DumpsAll = actions::DUMPS_ALL,
+    #[serde(rename = "keys.*")]
+    KeysAll = actions::KEYS_ALL,
+    #[serde(rename = "aliases.*")]
+    AliasesAll = actions::ALIASES_ALL,
+    #[serde(rename = "templates.*")]
+    TemplatesAll = actions::TEMPLATES_ALL,
+    #[serde(rename = "webhooks.*")]
+    WebhooksAll = actions::WEBHOOKS_ALL,
 }
 
 impl Action {
@@ -57,16 +119,47 @@ impl Action {
             INDEXES_GET => Some(Self::IndexesGet),
             INDEXES_UPDATE => Some(Self::IndexesUpdate),
             INDEXES_DELETE => Some(Self::IndexesDelete),
+            INDEXES_SWAP => Some(Self::IndexesSwap),
+            ALIASES_GET => Some(Self::AliasesGet),
+            ALIASES_UPDATE => Some(Self::AliasesUpdate),
+            TEMPLATES_GET => Some(Self::TemplatesGet),
+            TEMPLATES_UPDATE => Some(Self::TemplatesUpdate),
             TASKS_GET => Some(Self::TasksGet),
+            TASKS_CANCEL => Some(Self::TasksCancel),
+            TASKS_DELETE => Some(Self::TasksDelete),
+            TASKS_UPDATE => Some(Self::TasksUpdate),
             SETTINGS_GET => Some(Self::SettingsGet),
             SETTINGS_UPDATE => Some(Self::SettingsUpdate),
             STATS_GET => Some(Self::StatsGet),
             DUMPS_CREATE => Some(Self::DumpsCreate),
+            DUMPS_IMPORT => Some(Self::DumpsImport),
+            SNAPSHOTS_CREATE => Some(Self::SnapshotsCreate),
             VERSION => Some(Self::Version),
             KEYS_CREATE => Some(Self::KeysAdd),
             KEYS_GET => Some(Self::KeysGet),
             KEYS_UPDATE => Some(Self::KeysUpdate),
             KEYS_DELETE => Some(Self::KeysDelete),
+            KEYS_ROTATE => Some(Self::KeysRotate),
+            WEBHOOKS_GET => Some(Self::WebhooksGet),
+            WEBHOOKS_UPDATE => Some(Self::WebhooksUpdate),
+            AUDIT_LOG_GET => Some(Self::AuditLogGet),
+            MAINTENANCE_GET => Some(Self::MaintenanceGet),
+            MAINTENANCE_UPDATE => Some(Self::MaintenanceUpdate),
+            CONFIG_GET => Some(Self::ConfigGet),
+            CONFIG_UPDATE => Some(Self::ConfigUpdate),
+            EXPERIMENTAL_FEATURES_GET => Some(Self::ExperimentalFeaturesGet),
+            EXPERIMENTAL_FEATURES_UPDATE => Some(Self::ExperimentalFeaturesUpdate),
+            SEARCH_ANALYTICS_GET => Some(Self::SearchAnalyticsGet),
+            REPLICATION_GET => Some(Self::ReplicationGet),
+            DOCUMENTS_ALL => Some(Self::DocumentsAll),
+            INDEXES_ALL => Some(Self::IndexesAll),
+            TASKS_ALL => Some(Self::TasksAll),
+            SETTINGS_ALL => Some(Self::SettingsAll),
+            DUMPS_ALL => Some(Self::DumpsAll),
+            KEYS_ALL => Some(Self::KeysAll),
+            ALIASES_ALL => Some(Self::AliasesAll),
+            TEMPLATES_ALL => Some(Self::TemplatesAll),
+            WEBHOOKS_ALL => Some(Self::WebhooksAll),
             _otherwise => None,
         }
     }
@@ -83,18 +176,76 @@ impl Action {
             Self::IndexesGet => INDEXES_GET,
             Self::IndexesUpdate => INDEXES_UPDATE,
             Self::IndexesDelete => INDEXES_DELETE,
+            Self::IndexesSwap => INDEXES_SWAP,
+            Self::AliasesGet => ALIASES_GET,
+            Self::AliasesUpdate => ALIASES_UPDATE,
+            Self::TemplatesGet => TEMPLATES_GET,
+            Self::TemplatesUpdate => TEMPLATES_UPDATE,
             Self::TasksGet => TASKS_GET,
+            Self::TasksCancel => TASKS_CANCEL,
+            Self::TasksDelete => TASKS_DELETE,
+            Self::TasksUpdate => TASKS_UPDATE,
             Self::SettingsGet => SETTINGS_GET,
             Self::SettingsUpdate => SETTINGS_UPDATE,
             Self::StatsGet => STATS_GET,
             Self::DumpsCreate => DUMPS_CREATE,
+            Self::DumpsImport => DUMPS_IMPORT,
+            Self::SnapshotsCreate => SNAPSHOTS_CREATE,
             Self::Version => VERSION,
             Self::KeysAdd => KEYS_CREATE,
             Self::KeysGet => KEYS_GET,
             Self::KeysUpdate => KEYS_UPDATE,
             Self::KeysDelete => KEYS_DELETE,
+            Self::KeysRotate => KEYS_ROTATE,
+            Self::WebhooksGet => WEBHOOKS_GET,
+            Self::WebhooksUpdate => WEBHOOKS_UPDATE,
+            Self::AuditLogGet => AUDIT_LOG_GET,
+            Self::MaintenanceGet => MAINTENANCE_GET,
+            Self::MaintenanceUpdate => MAINTENANCE_UPDATE,
+            Self::ConfigGet => CONFIG_GET,
+            Self::ConfigUpdate => CONFIG_UPDATE,
+            Self::ExperimentalFeaturesGet => EXPERIMENTAL_FEATURES_GET,
+            Self::ExperimentalFeaturesUpdate => EXPERIMENTAL_FEATURES_UPDATE,
+            Self::SearchAnalyticsGet => SEARCH_ANALYTICS_GET,
+            Self::ReplicationGet => REPLICATION_GET,
+            Self::DocumentsAll => DOCUMENTS_ALL,
+            Self::IndexesAll => INDEXES_ALL,
+            Self::TasksAll => TASKS_ALL,
+            Self::SettingsAll => SETTINGS_ALL,
+            Self::DumpsAll => DUMPS_ALL,
+            Self::KeysAll => KEYS_ALL,
+            Self::AliasesAll => ALIASES_ALL,
+            Self::TemplatesAll => TEMPLATES_ALL,
+            Self::WebhooksAll => WEBHOOKS_ALL,
         }
     }
+
+    /// Returns the actions granted by this action: the action itself, along with every action of
+    /// its group if it is a wildcard like `documents.*`, or every action if it is `*`.
+    pub fn expand(self) -> Vec<Self> {
+        use Action::*;
+        let mut granted = match self {
+            All => return Self::into_enum_iter().collect(),
+            DocumentsAll => vec![DocumentsAdd, DocumentsGet, DocumentsDelete],
+            IndexesAll => vec![
+                IndexesAdd,
+                IndexesGet,
+                IndexesUpdate,
+                IndexesDelete,
+                IndexesSwap,
+            ],
+            TasksAll => vec![TasksGet, TasksCancel, TasksDelete, TasksUpdate],
+            SettingsAll => vec![SettingsGet, SettingsUpdate],
+            DumpsAll => vec![DumpsCreate, DumpsImport],
+            KeysAll => vec![KeysAdd, KeysGet, KeysUpdate, KeysDelete, KeysRotate],
+            AliasesAll => vec![AliasesGet, AliasesUpdate],
+            TemplatesAll => vec![TemplatesGet, TemplatesUpdate],
+            WebhooksAll => vec![WebhooksGet, WebhooksUpdate],
+            _ => Vec::new(),
+        };
+        granted.push(self);
+        granted
+    }
 }
 
 pub mod actions {
@@ -117,4 +268,35 @@ pub mod actions {
     pub const KEYS_GET: u8 = 17;
     pub const KEYS_UPDATE: u8 = 18;
     pub const KEYS_DELETE: u8 = 19;
+    pub const TASKS_CANCEL: u8 = 20;
+    pub const TASKS_DELETE: u8 = 21;
+    pub const WEBHOOKS_GET: u8 = 22;
+    pub const WEBHOOKS_UPDATE: u8 = 23;
+    pub const TASKS_UPDATE: u8 = 24;
+    pub const SNAPSHOTS_CREATE: u8 = 25;
+    pub const DUMPS_IMPORT: u8 = 26;
+    pub const INDEXES_SWAP: u8 = 27;
+    pub const ALIASES_GET: u8 = 28;
+    pub const ALIASES_UPDATE: u8 = 29;
+    pub const TEMPLATES_GET: u8 = 30;
+    pub const TEMPLATES_UPDATE: u8 = 31;
+    pub(crate) const DOCUMENTS_ALL: u8 = 32;
+    pub(crate) const INDEXES_ALL: u8 = 33;
+    pub(crate) const TASKS_ALL: u8 = 34;
+    pub(crate) const SETTINGS_ALL: u8 = 35;
+    pub(crate) const DUMPS_ALL: u8 = 36;
+    pub(crate) const KEYS_ALL: u8 = 37;
+    pub(crate) const ALIASES_ALL: u8 = 38;
+    pub(crate) const TEMPLATES_ALL: u8 = 39;
+    pub(crate) const WEBHOOKS_ALL: u8 = 40;
+    pub const AUDIT_LOG_GET: u8 = 41;
+    pub const MAINTENANCE_GET: u8 = 42;
+    pub const MAINTENANCE_UPDATE: u8 = 43;
+    pub const CONFIG_GET: u8 = 44;
+    pub const CONFIG_UPDATE: u8 = 45;
+    pub const EXPERIMENTAL_FEATURES_GET: u8 = 46;
+    pub const EXPERIMENTAL_FEATURES_UPDATE: u8 = 47;
+    pub const SEARCH_ANALYTICS_GET: u8 = 48;
+    pub const REPLICATION_GET: u8 = 49;
+    pub const KEYS_ROTATE: u8 = 50;
 }
@@ -0,0 +1,177 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The administrative actions recorded in the audit log.
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub enum AuditAction {
+    #[serde(rename = "key.created")]
+    KeyCreated,
+    #[serde(rename = "key.updated")]
+    KeyUpdated,
+    #[serde(rename = "key.rotated")]
+    KeyRotated,
+    #[serde(rename = "key.deleted")]
+    KeyDeleted,
+    #[serde(rename = "index.created")]
+    IndexCreated,
+    #[serde(rename = "index.deleted")]
+    IndexDeleted,
+    #[serde(rename = "settings.updated")]
+    SettingsUpdated,
+    #[serde(rename = "dump.created")]
+    DumpCreated,
+    #[serde(rename = "dump.imported")]
+    DumpImported,
+    #[serde(rename = "snapshot.created")]
+    SnapshotCreated,
+}
+
+impl AuditAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::KeyCreated => "key.created",
+            Self::KeyUpdated => "key.updated",
+            Self::KeyRotated => "key.rotated",
+            Self::KeyDeleted => "key.deleted",
+            Self::IndexCreated => "index.created",
+            Self::IndexDeleted => "index.deleted",
+            Self::SettingsUpdated => "settings.updated",
+            Self::DumpCreated => "dump.created",
+            Self::DumpImported => "dump.imported",
+            Self::SnapshotCreated => "snapshot.created",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug)]
+pub struct AuditActionError(String);
+
+impl fmt::Display for AuditActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not an audited action. Available actions are `key.created`, `key.updated`, \
+            `key.rotated`, `key.deleted`, `index.created`, `index.deleted`, `settings.updated`, \
+            `dump.created`, `dump.imported` and `snapshot.created`.",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for AuditActionError {}
+
+impl FromStr for AuditAction {
+    type Err = AuditActionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "key.created" => Ok(Self::KeyCreated),
+            "key.updated" => Ok(Self::KeyUpdated),
+            "key.rotated" => Ok(Self::KeyRotated),
+            "key.deleted" => Ok(Self::KeyDeleted),
+            "index.created" => Ok(Self::IndexCreated),
+            "index.deleted" => Ok(Self::IndexDeleted),
+            "settings.updated" => Ok(Self::SettingsUpdated),
+            "dump.created" => Ok(Self::DumpCreated),
+            "dump.imported" => Ok(Self::DumpImported),
+            "snapshot.created" => Ok(Self::SnapshotCreated),
+            _ => Err(AuditActionError(s.to_string())),
+        }
+    }
+}
+
+pub type AuditEntryId = u64;
+
+/// An entry of the audit log. The entries are never modified once recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: AuditEntryId,
+    pub action: AuditAction,
+    /// What the action was performed on, e.g. the uid of an index or of a key.
+    pub target: Option<String>,
+    /// The API key that performed the action, `None` for the master key.
+    pub api_key_uid: Option<Uuid>,
+    #[serde(default)]
+    pub details: Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// Selects the entries of the audit log, every entry is selected by default.
+#[derive(Debug, Default, Clone)]
+pub struct AuditFilter {
+    pub actions: Option<Vec<AuditAction>>,
+    pub api_key_uid: Option<Uuid>,
+    pub target: Option<String>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actions
+            .as_ref()
+            .map_or(true, |actions| actions.contains(&entry.action))
+            && self
+                .api_key_uid
+                .map_or(true, |uid| entry.api_key_uid == Some(uid))
+            && self
+                .target
+                .as_ref()
+                .map_or(true, |target| entry.target.as_ref() == Some(target))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_actions() {
+        for action in ["key.created", "settings.updated", "snapshot.created"] {
+            assert_eq!(action.parse::<AuditAction>().unwrap().name(), action);
+        }
+        assert!("keys.created".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn filter_entries() {
+        let uid = Uuid::new_v4();
+        let entry = AuditEntry {
+            id: 0,
+            action: AuditAction::IndexDeleted,
+            target: Some("movies".to_string()),
+            api_key_uid: Some(uid),
+            details: Value::Null,
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert!(AuditFilter::default().matches(&entry));
+        assert!(AuditFilter {
+            actions: Some(vec![AuditAction::IndexCreated, AuditAction::IndexDeleted]),
+            api_key_uid: Some(uid),
+            target: Some("movies".to_string()),
+        }
+        .matches(&entry));
+        assert!(!AuditFilter {
+            actions: Some(vec![AuditAction::IndexCreated]),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditFilter {
+            api_key_uid: Some(Uuid::new_v4()),
+            ..Default::default()
+        }
+        .matches(&entry));
+    }
+}
@@ -0,0 +1,110 @@
+//! Authentication of the client certificates verified by the TLS layer.
+//!
+//! Each accepted certificate is identified by the SHA-256 fingerprint of its DER encoding and
+//! gives access to a set of actions on a set of indexes, like an API key, e.g.
+//! `{ "fingerprint": "5E:1B:...", "actions": ["search"], "indexes": ["products*"] }`.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{Action, Identity};
+
+/// The rights given to a client certificate.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CertificateIdentity {
+    /// The SHA-256 fingerprint of the certificate, in hexadecimal with or without colons.
+    pub fingerprint: String,
+    pub actions: Vec<Action>,
+    pub indexes: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ClientCertificates {
+    identities: HashMap<String, Vec<CertificateIdentity>>,
+}
+
+impl ClientCertificates {
+    pub fn new(identities: Vec<CertificateIdentity>) -> Self {
+        let mut certificates = Self::default();
+        for identity in identities {
+            let fingerprint = normalize_fingerprint(&identity.fingerprint);
+            certificates
+                .identities
+                .entry(fingerprint)
+                .or_default()
+                .push(identity);
+        }
+        certificates
+    }
+
+    /// Returns the rights given to the certificate with the `fingerprint`, computed with
+    /// [`certificate_fingerprint`].
+    pub fn authenticate(&self, fingerprint: &str) -> Option<Identity> {
+        let identities = self.identities.get(fingerprint)?;
+        Some(Identity {
+            actions: identities
+                .iter()
+                .flat_map(|identity| identity.actions.iter().copied())
+                .collect(),
+            indexes: identities
+                .iter()
+                .flat_map(|identity| identity.indexes.iter().cloned())
+                .collect(),
+        })
+    }
+}
+
+/// Returns the SHA-256 fingerprint of the DER encoded `certificate`, in lowercase hexadecimal.
+pub fn certificate_fingerprint(certificate: &[u8]) -> String {
+    Sha256::digest(certificate)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| *c != ':')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn authenticate_a_certificate() {
+        let fingerprint = certificate_fingerprint(b"certificate");
+        let displayed = fingerprint
+            .as_bytes()
+            .chunks(2)
+            .map(|byte| std::str::from_utf8(byte).unwrap().to_uppercase())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        let certificates = ClientCertificates::new(vec![
+            CertificateIdentity {
+                fingerprint: displayed,
+                actions: vec![Action::Search],
+                indexes: vec!["products".to_string()],
+            },
+            CertificateIdentity {
+                fingerprint: fingerprint.clone(),
+                actions: vec![Action::DocumentsGet],
+                indexes: vec!["orders".to_string()],
+            },
+        ]);
+
+        let identity = certificates.authenticate(&fingerprint).unwrap();
+        assert_eq!(identity.actions, vec![Action::Search, Action::DocumentsGet]);
+        assert_eq!(identity.indexes, vec!["products", "orders"]);
+
+        let other = certificate_fingerprint(b"other certificate");
+        assert!(certificates.authenticate(&other).is_none());
+    }
+}
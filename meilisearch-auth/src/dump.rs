@@ -8,6 +8,7 @@ use std::path::Path;
 use crate::{AuthController, HeedAuthStore, Result};
 
 const KEYS_PATH: &str = "keys";
+const AUDIT_LOG_PATH: &str = "audit-log";
 
 impl AuthController {
     pub fn dump(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<()> {
@@ -25,6 +26,12 @@ impl AuthController {
             keys_file.write_all(b"\n")?;
         }
 
+        let mut audit_log_file = File::create(dst.as_ref().join(AUDIT_LOG_PATH))?;
+        for entry in store.audit_entries()? {
+            serde_json::to_writer(&mut audit_log_file, &entry)?;
+            audit_log_file.write_all(b"\n")?;
+        }
+
         Ok(())
     }
 
@@ -42,6 +49,15 @@ impl AuthController {
             store.put_api_key(key?)?;
         }
 
+        // the dumps created before the audit log don't contain it.
+        let audit_log_file_path = src.as_ref().join(AUDIT_LOG_PATH);
+        if audit_log_file_path.exists() {
+            let reader = BufReader::new(File::open(&audit_log_file_path)?);
+            for entry in Deserializer::from_reader(reader).into_iter() {
+                store.put_audit_entry(entry?)?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -24,6 +24,18 @@ pub enum AuthControllerError {
     InvalidApiKeyName(Value),
     #[error("`uid` field value `{0}` is invalid. It should be a valid UUID v4 string or omitted.")]
     InvalidApiKeyUid(Value),
+    #[error("`allowedIps` field value `{0}` is invalid. It should be an array of string representing IP addresses or CIDR ranges, or specified as a null value.")]
+    InvalidApiKeyAllowedIps(Value),
+    #[error("`allowedReferers` field value `{0}` is invalid. It should be an array of string representing host names, optionally starting with `*.`, or specified as a null value.")]
+    InvalidApiKeyAllowedReferers(Value),
+    #[error("`rateLimit` field value `{0}` is invalid. It should be an object with positive integer `requestsPerSecond` and `concurrentSearches` fields, or specified as a null value.")]
+    InvalidApiKeyRateLimit(Value),
+    #[error("`displayedAttributes` field value `{0}` is invalid. It should be an array of string representing attribute names, or specified as a null value.")]
+    InvalidApiKeyDisplayedAttributes(Value),
+    #[error("`forbiddenAttributes` field value `{0}` is invalid. It should be an array of string representing attribute names, or specified as a null value.")]
+    InvalidApiKeyForbiddenAttributes(Value),
+    #[error("`gracePeriod` field value `{0}` is invalid. It should be a positive integer representing a number of seconds or omitted.")]
+    InvalidApiKeyGracePeriod(Value),
     #[error("API key `{0}` not found.")]
     ApiKeyNotFound(String),
     #[error("`uid` field value `{0}` is already an existing API key.")]
@@ -50,6 +62,12 @@ impl ErrorCode for AuthControllerError {
             Self::InvalidApiKeyExpiresAt(_) => Code::InvalidApiKeyExpiresAt,
             Self::InvalidApiKeyDescription(_) => Code::InvalidApiKeyDescription,
             Self::InvalidApiKeyName(_) => Code::InvalidApiKeyName,
+            Self::InvalidApiKeyAllowedIps(_) => Code::InvalidApiKeyAllowedIps,
+            Self::InvalidApiKeyAllowedReferers(_) => Code::InvalidApiKeyAllowedReferers,
+            Self::InvalidApiKeyRateLimit(_) => Code::InvalidApiKeyRateLimit,
+            Self::InvalidApiKeyDisplayedAttributes(_) => Code::InvalidApiKeyDisplayedAttributes,
+            Self::InvalidApiKeyForbiddenAttributes(_) => Code::InvalidApiKeyForbiddenAttributes,
+            Self::InvalidApiKeyGracePeriod(_) => Code::InvalidApiKeyGracePeriod,
             Self::ApiKeyNotFound(_) => Code::ApiKeyNotFound,
             Self::InvalidApiKeyUid(_) => Code::InvalidApiKeyUid,
             Self::ApiKeyAlreadyExists(_) => Code::ApiKeyAlreadyExists,
@@ -0,0 +1,114 @@
+//! Authentication of the tokens issued by an external OpenID Connect identity provider.
+//!
+//! The tokens are JWTs signed with one of the keys the provider publishes as a JSON Web Key Set.
+//! Besides the standard issuer, audience and expiration claims, they list the actions and the
+//! indexes they give access to in two configurable claims, e.g.
+//! `"meilisearch_actions": ["search", "documents.*"]` and `"meilisearch_indexes": ["products*"]`.
+//! A claim may also be a string of space-separated values, like the OAuth `scope` claim.
+
+use std::sync::RwLock;
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
+use serde_json::Value;
+
+use crate::{Action, AuthFilter, SearchRules};
+
+pub struct IdentityProvider {
+    issuer: String,
+    audience: Option<String>,
+    actions_claim: String,
+    indexes_claim: String,
+    /// The keys of the provider, `None` until they are fetched.
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+/// The rights given by a token of the identity provider, or by a client certificate.
+#[derive(Debug)]
+pub struct Identity {
+    pub actions: Vec<Action>,
+    pub indexes: Vec<String>,
+}
+
+impl Identity {
+    /// Returns the filters of the identity if it is authorized to perform `action` on `index`,
+    /// or on any index if `index` is `None`.
+    pub fn authorize(self, action: Action, index: Option<&str>) -> Option<AuthFilter> {
+        let granted: Vec<_> = self.actions.iter().flat_map(|a| a.expand()).collect();
+        if !granted.contains(&action) {
+            return None;
+        }
+
+        let search_rules = SearchRules::Set(self.indexes.into_iter().collect());
+        if index.map_or(false, |index| !search_rules.is_index_authorized(index)) {
+            return None;
+        }
+
+        Some(AuthFilter {
+            search_rules,
+            allow_index_creation: granted.contains(&Action::IndexesAdd),
+            ..AuthFilter::default()
+        })
+    }
+}
+
+impl IdentityProvider {
+    pub fn new(
+        issuer: String,
+        audience: Option<String>,
+        actions_claim: String,
+        indexes_claim: String,
+    ) -> Self {
+        Self {
+            issuer,
+            audience,
+            actions_claim,
+            indexes_claim,
+            jwks: RwLock::new(None),
+        }
+    }
+
+    /// Replaces the keys of the provider, they are regularly fetched again to follow the key
+    /// rotations of the provider.
+    pub fn set_jwks(&self, jwks: JwkSet) {
+        *self.jwks.write().unwrap() = Some(jwks);
+    }
+
+    /// Returns the rights given by `token` if it is a valid token of the provider.
+    pub fn authenticate(&self, token: &str) -> Option<Identity> {
+        let header = decode_header(token).ok()?;
+        let kid = header.kid?;
+        let key = {
+            let jwks = self.jwks.read().unwrap();
+            DecodingKey::from_jwk(jwks.as_ref()?.find(&kid)?).ok()?
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        if let Some(ref audience) = self.audience {
+            validation.set_audience(&[audience]);
+        }
+        let claims = decode::<Value>(token, &key, &validation).ok()?.claims;
+
+        let actions = claim_values(&claims, &self.actions_claim)
+            .into_iter()
+            // the actions unknown to this version are ignored.
+            .filter_map(|action| serde_json::from_value(Value::String(action)).ok())
+            .collect();
+        let indexes = claim_values(&claims, &self.indexes_claim);
+
+        Some(Identity { actions, indexes })
+    }
+}
+
+fn claim_values(claims: &Value, name: &str) -> Vec<String> {
+    match claims.get(name) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        Some(Value::String(values)) => values.split_whitespace().map(String::from).collect(),
+        _ => Vec::new(),
+    }
+}
@@ -1,8 +1,12 @@
 use crate::action::Action;
 use crate::error::{AuthControllerError, Result};
+use crate::rate_limit::RateLimit;
 use crate::store::KeyId;
 
-use meilisearch_types::index_uid::IndexUid;
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+use meilisearch_types::index_uid_pattern::IndexUidPattern;
 use meilisearch_types::star_or::StarOr;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, Value};
@@ -11,6 +15,9 @@ use time::macros::{format_description, time};
 use time::{Date, OffsetDateTime, PrimitiveDateTime};
 use uuid::Uuid;
 
+/// How long a rotated key stays valid when no grace period is specified.
+const DEFAULT_GRACE_PERIOD_SECONDS: i64 = 24 * 60 * 60;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Key {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -19,9 +26,28 @@ pub struct Key {
     pub name: Option<String>,
     pub uid: KeyId,
     pub actions: Vec<Action>,
-    pub indexes: Vec<StarOr<IndexUid>>,
+    /// The indexes the key gives access to, an index uid may end with a `*` to match all the
+    /// indexes starting with it.
+    pub indexes: Vec<StarOr<IndexUidPattern>>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub expires_at: Option<OffsetDateTime>,
+    /// The IP addresses and ranges the key can be used from, any when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_ips: Option<Vec<IpNet>>,
+    /// The hosts the `Referer` or `Origin` of the requests must match, any when `None`. A host
+    /// starting with `*.` matches all its subdomains.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_referers: Option<Vec<String>>,
+    /// The limits of the requests made with the key, on top of the global limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// The only attributes the searches made with the key can return, all the displayed ones
+    /// when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub displayed_attributes: Option<Vec<String>>,
+    /// The attributes the searches made with the key can never return.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forbidden_attributes: Option<Vec<String>>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
@@ -73,6 +99,36 @@ impl Key {
             .map(parse_expiration_date)
             .ok_or(AuthControllerError::MissingParameter("expiresAt"))??;
 
+        let allowed_ips = value
+            .get("allowedIps")
+            .map_or(Ok(None), parse_allowed_ips)?;
+
+        let allowed_referers = value
+            .get("allowedReferers")
+            .map_or(Ok(None), parse_allowed_referers)?;
+
+        let rate_limit = value.get("rateLimit").map_or(Ok(None), parse_rate_limit)?;
+
+        let displayed_attributes =
+            value
+                .get("displayedAttributes")
+                .map_or(Ok(None), |attributes| {
+                    parse_attributes(
+                        attributes,
+                        AuthControllerError::InvalidApiKeyDisplayedAttributes,
+                    )
+                })?;
+
+        let forbidden_attributes =
+            value
+                .get("forbiddenAttributes")
+                .map_or(Ok(None), |attributes| {
+                    parse_attributes(
+                        attributes,
+                        AuthControllerError::InvalidApiKeyForbiddenAttributes,
+                    )
+                })?;
+
         let created_at = OffsetDateTime::now_utc();
         let updated_at = created_at;
 
@@ -83,6 +139,11 @@ impl Key {
             actions,
             indexes,
             expires_at,
+            allowed_ips,
+            allowed_referers,
+            rate_limit,
+            displayed_attributes,
+            forbidden_attributes,
             created_at,
             updated_at,
         })
@@ -101,6 +162,32 @@ impl Key {
             self.name = des?;
         }
 
+        if let Some(allowed_ips) = value.get("allowedIps") {
+            self.allowed_ips = parse_allowed_ips(allowed_ips)?;
+        }
+
+        if let Some(allowed_referers) = value.get("allowedReferers") {
+            self.allowed_referers = parse_allowed_referers(allowed_referers)?;
+        }
+
+        if let Some(rate_limit) = value.get("rateLimit") {
+            self.rate_limit = parse_rate_limit(rate_limit)?;
+        }
+
+        if let Some(attributes) = value.get("displayedAttributes") {
+            self.displayed_attributes = parse_attributes(
+                attributes,
+                AuthControllerError::InvalidApiKeyDisplayedAttributes,
+            )?;
+        }
+
+        if let Some(attributes) = value.get("forbiddenAttributes") {
+            self.forbidden_attributes = parse_attributes(
+                attributes,
+                AuthControllerError::InvalidApiKeyForbiddenAttributes,
+            )?;
+        }
+
         if value.get("uid").is_some() {
             return Err(AuthControllerError::ImmutableField("uid".to_string()));
         }
@@ -130,6 +217,27 @@ impl Key {
         Ok(())
     }
 
+    /// Returns a key with the same name, description, actions, indexes and expiration date, but
+    /// with a new uid, and thus a new value.
+    pub fn regenerate(&self) -> Self {
+        let now = OffsetDateTime::now_utc();
+        Self {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            uid: Uuid::new_v4(),
+            actions: self.actions.clone(),
+            indexes: self.indexes.clone(),
+            expires_at: self.expires_at,
+            allowed_ips: self.allowed_ips.clone(),
+            allowed_referers: self.allowed_referers.clone(),
+            rate_limit: self.rate_limit,
+            displayed_attributes: self.displayed_attributes.clone(),
+            forbidden_attributes: self.forbidden_attributes.clone(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
     pub(crate) fn default_admin() -> Self {
         let now = OffsetDateTime::now_utc();
         let uid = Uuid::new_v4();
@@ -140,6 +248,11 @@ impl Key {
             actions: vec![Action::All],
             indexes: vec![StarOr::Star],
             expires_at: None,
+            allowed_ips: None,
+            allowed_referers: None,
+            rate_limit: None,
+            displayed_attributes: None,
+            forbidden_attributes: None,
             created_at: now,
             updated_at: now,
         }
@@ -155,12 +268,120 @@ impl Key {
             actions: vec![Action::Search],
             indexes: vec![StarOr::Star],
             expires_at: None,
+            allowed_ips: None,
+            allowed_referers: None,
+            rate_limit: None,
+            displayed_attributes: None,
+            forbidden_attributes: None,
             created_at: now,
             updated_at: now,
         }
     }
 }
 
+/// Parses a list of IP addresses and CIDR ranges, a single address being a range of one address.
+fn parse_allowed_ips(value: &Value) -> Result<Option<Vec<IpNet>>> {
+    let invalid = || AuthControllerError::InvalidApiKeyAllowedIps(value.clone());
+    match value {
+        Value::Array(ips) => ips
+            .iter()
+            .map(|ip| {
+                let ip = ip.as_str().ok_or_else(invalid)?;
+                ip.parse::<IpNet>()
+                    .or_else(|_| ip.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| invalid())
+            })
+            .collect::<Result<_>>()
+            .map(Some),
+        Value::Null => Ok(None),
+        _otherwise => Err(invalid()),
+    }
+}
+
+/// Parses a list of attribute names, `invalid` builds the error returned for any other value.
+fn parse_attributes(
+    value: &Value,
+    invalid: fn(Value) -> AuthControllerError,
+) -> Result<Option<Vec<String>>> {
+    match value {
+        Value::Array(attributes) => attributes
+            .iter()
+            .map(|attribute| match attribute.as_str() {
+                Some(attribute) if !attribute.is_empty() => Ok(attribute.to_string()),
+                _ => Err(invalid(value.clone())),
+            })
+            .collect::<Result<_>>()
+            .map(Some),
+        Value::Null => Ok(None),
+        _otherwise => Err(invalid(value.clone())),
+    }
+}
+
+/// Parses a list of host names, which may start with `*.` to match all the subdomains of a host.
+fn parse_allowed_referers(value: &Value) -> Result<Option<Vec<String>>> {
+    let invalid = || AuthControllerError::InvalidApiKeyAllowedReferers(value.clone());
+    match value {
+        Value::Array(referers) => referers
+            .iter()
+            .map(|referer| {
+                let referer = referer.as_str().ok_or_else(invalid)?;
+                let host = referer.strip_prefix("*.").unwrap_or(referer);
+                if !host.is_empty()
+                    && host
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+                {
+                    Ok(referer.to_ascii_lowercase())
+                } else {
+                    Err(invalid())
+                }
+            })
+            .collect::<Result<_>>()
+            .map(Some),
+        Value::Null => Ok(None),
+        _otherwise => Err(invalid()),
+    }
+}
+
+/// Parses the `requestsPerSecond` and `concurrentSearches` limits of a key, which must be positive.
+fn parse_rate_limit(value: &Value) -> Result<Option<RateLimit>> {
+    let invalid = || AuthControllerError::InvalidApiKeyRateLimit(value.clone());
+    match value {
+        Value::Null => Ok(None),
+        value => {
+            let limit: RateLimit = from_value(value.clone()).map_err(|_| invalid())?;
+            let limits = [limit.requests_per_second, limit.concurrent_searches];
+            if limits.contains(&Some(0)) {
+                Err(invalid())
+            } else {
+                Ok(Some(limit))
+            }
+        }
+    }
+}
+
+/// Returns the date until which a rotated key stays valid, from the `gracePeriod` of `value` in
+/// seconds. It defaults to a day.
+pub(crate) fn parse_grace_period_end(value: &Value) -> Result<OffsetDateTime> {
+    let grace_period = match value.get("gracePeriod") {
+        None | Some(Value::Null) => Some(DEFAULT_GRACE_PERIOD_SECONDS),
+        Some(grace_period) => grace_period.as_i64().filter(|seconds| *seconds >= 0),
+    };
+
+    grace_period
+        .and_then(|seconds| {
+            let end = OffsetDateTime::now_utc()
+                .unix_timestamp()
+                .checked_add(seconds)?;
+            OffsetDateTime::from_unix_timestamp(end).ok()
+        })
+        .ok_or_else(|| {
+            AuthControllerError::InvalidApiKeyGracePeriod(
+                value.get("gracePeriod").cloned().unwrap_or_default(),
+            )
+        })
+}
+
 fn parse_expiration_date(value: &Value) -> Result<Option<OffsetDateTime>> {
     match value {
         Value::String(string) => OffsetDateTime::parse(string, &Rfc3339)
@@ -1,23 +1,36 @@
 mod action;
+mod audit;
+mod client_certificates;
 mod dump;
 pub mod error;
+mod identity_provider;
 mod key;
+mod rate_limit;
 mod store;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::net::IpAddr;
 use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
 
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub use action::{actions, Action};
+pub use audit::{AuditAction, AuditActionError, AuditEntry, AuditEntryId, AuditFilter};
+pub use client_certificates::{certificate_fingerprint, CertificateIdentity, ClientCertificates};
 use error::{AuthControllerError, Result};
+pub use identity_provider::{Identity, IdentityProvider};
+use key::parse_grace_period_end;
 pub use key::Key;
+use meilisearch_types::index_uid_pattern;
 use meilisearch_types::star_or::StarOr;
+use rate_limit::KeyRateLimits;
+pub use rate_limit::{RateLimit, RateLimitPermit, RateLimiter};
 use store::generate_key_as_base64;
 pub use store::open_auth_store_env;
 use store::HeedAuthStore;
@@ -26,6 +39,10 @@ use store::HeedAuthStore;
 pub struct AuthController {
     store: Arc<HeedAuthStore>,
     master_key: Option<String>,
+    identity_provider: Option<Arc<IdentityProvider>>,
+    client_certificates: Option<Arc<ClientCertificates>>,
+    rate_limiter: RateLimiter,
+    rate_limits: KeyRateLimits,
 }
 
 impl AuthController {
@@ -36,10 +53,64 @@ impl AuthController {
             generate_default_keys(&store)?;
         }
 
-        Ok(Self {
+        let controller = Self {
             store: Arc::new(store),
             master_key: master_key.clone(),
-        })
+            identity_provider: None,
+            client_certificates: None,
+            rate_limiter: RateLimiter::default(),
+            rate_limits: KeyRateLimits::default(),
+        };
+        for key in controller.store.list_api_keys()? {
+            controller.cache_rate_limit(&key);
+        }
+
+        Ok(controller)
+    }
+
+    /// Keeps the rate limits of `key` in memory, the keys can't be used without a master key.
+    fn cache_rate_limit(&self, key: &Key) {
+        if let Some(encoded_key) = self.generate_key(key.uid) {
+            self.rate_limits.set(key.uid, encoded_key, key.rate_limit);
+        }
+    }
+
+    /// Accepts the tokens of the `identity_provider` in addition to the API keys.
+    pub fn set_identity_provider(&mut self, identity_provider: Arc<IdentityProvider>) {
+        self.identity_provider = Some(identity_provider);
+    }
+
+    /// Returns the filters of `token` if it is a token of the identity provider authorized to
+    /// perform `action` on `index`, or on any index if `index` is `None`.
+    pub fn authenticate_identity(
+        &self,
+        token: &str,
+        action: Action,
+        index: Option<&str>,
+    ) -> Option<AuthFilter> {
+        let identity = self.identity_provider.as_ref()?.authenticate(token)?;
+        identity.authorize(action, index)
+    }
+
+    /// Accepts the client certificates listed in `client_certificates` in addition to the API
+    /// keys.
+    pub fn set_client_certificates(&mut self, client_certificates: Arc<ClientCertificates>) {
+        self.client_certificates = Some(client_certificates);
+    }
+
+    /// Returns the filters of the client certificate with the `fingerprint` if it is authorized
+    /// to perform `action` on `index`, or on any index if `index` is `None`.
+    pub fn authenticate_certificate(
+        &self,
+        fingerprint: &str,
+        action: Action,
+        index: Option<&str>,
+    ) -> Option<AuthFilter> {
+        let identity = self
+            .client_certificates
+            .as_ref()?
+            .authenticate(fingerprint)?;
+        identity.authorize(action, index)
     }
 
     pub fn create_key(&self, value: Value) -> Result<Key> {
@@ -48,14 +119,42 @@ impl AuthController {
             Some(_) => Err(AuthControllerError::ApiKeyAlreadyExists(
                 key.uid.to_string(),
             )),
-            None => self.store.put_api_key(key),
+            None => {
+                let key = self.store.put_api_key(key)?;
+                self.cache_rate_limit(&key);
+                Ok(key)
+            }
         }
     }
 
     pub fn update_key(&self, uid: Uuid, value: Value) -> Result<Key> {
         let mut key = self.get_key(uid)?;
         key.update_from_value(value)?;
-        self.store.put_api_key(key)
+        let key = self.store.put_api_key(key)?;
+        self.cache_rate_limit(&key);
+        Ok(key)
+    }
+
+    /// Replaces the key `uid` by a new key with the same rights. The replaced key stays valid
+    /// during the `gracePeriod` of `value`, and is deleted right away if it is zero.
+    pub fn rotate_key(&self, uid: Uuid, value: Value) -> Result<Key> {
+        let grace_period_end = parse_grace_period_end(&value)?;
+        let mut key = self.get_key(uid)?;
+        let new_key = self.store.put_api_key(key.regenerate())?;
+        self.cache_rate_limit(&new_key);
+
+        if grace_period_end <= OffsetDateTime::now_utc() {
+            self.store.delete_api_key(uid)?;
+            self.rate_limits.remove(uid);
+        } else {
+            key.expires_at = Some(key.expires_at.map_or(grace_period_end, |expires_at| {
+                expires_at.min(grace_period_end)
+            }));
+            key.updated_at = OffsetDateTime::now_utc();
+            self.store.put_api_key(key)?;
+        }
+
+        Ok(new_key)
     }
 
     pub fn get_key(&self, uid: Uuid) -> Result<Key> {
@@ -92,8 +191,12 @@ impl AuthController {
         if !key.indexes.iter().any(|i| i == &StarOr::Star) {
             filters.search_rules = match search_rules {
                 // Intersect search_rules with parent key authorized indexes.
-                Some(search_rules) => SearchRules::Map(
-                    key.indexes
+                Some(search_rules) => {
+                    let key_rules = SearchRules::Set(
+                        key.indexes.iter().map(|i| i.deref().to_string()).collect(),
+                    );
+                    let mut rules: HashMap<_, _> = key
+                        .indexes
                         .into_iter()
                         .filter_map(|index| {
                             search_rules.get_index_search_rules(index.deref()).map(
@@ -102,18 +205,35 @@ impl AuthController {
                                 },
                             )
                         })
-                        .collect(),
-                ),
+                        .collect();
+                    // the rules of the indexes matched by an index pattern of the parent key.
+                    for (index, index_search_rules) in search_rules {
+                        if index != "*" && key_rules.is_index_authorized(&index) {
+                            rules.insert(index, Some(index_search_rules));
+                        }
+                    }
+                    SearchRules::Map(rules)
+                }
                 None => SearchRules::Set(key.indexes.into_iter().map(String::from).collect()),
             };
         } else if let Some(search_rules) = search_rules {
             filters.search_rules = search_rules;
         }
 
+        if key.displayed_attributes.is_some() || key.forbidden_attributes.is_some() {
+            filters.search_rules = filters
+                .search_rules
+                .restrict_attributes(&key.displayed_attributes, &key.forbidden_attributes);
+        }
+
+        filters.key_uid = Some(uid);
+        filters.allowed_ips = key.allowed_ips;
+        filters.allowed_referers = key.allowed_referers;
+
         filters.allow_index_creation = key
             .actions
             .iter()
-            .any(|&action| action == Action::IndexesAdd || action == Action::All);
+            .any(|action| action.expand().contains(&Action::IndexesAdd));
 
         Ok(filters)
     }
@@ -124,12 +244,77 @@ impl AuthController {
 
     pub fn delete_key(&self, uid: Uuid) -> Result<()> {
         if self.store.delete_api_key(uid)? {
+            self.rate_limits.remove(uid);
             Ok(())
         } else {
             Err(AuthControllerError::ApiKeyNotFound(uid.to_string()))
         }
     }
 
+    /// Appends an entry to the audit log, recording that the key `api_key_uid`, or the master
+    /// key if it is `None`, performed `action` on `target`.
+    pub fn record_audit_entry(
+        &self,
+        action: AuditAction,
+        target: Option<String>,
+        api_key_uid: Option<Uuid>,
+        details: Value,
+    ) -> Result<AuditEntry> {
+        self.store.put_audit_entry(AuditEntry {
+            id: 0,
+            action,
+            target,
+            api_key_uid,
+            details,
+            timestamp: OffsetDateTime::now_utc(),
+        })
+    }
+
+    /// Returns at most `limit` entries of the audit log matching `filter`, from the most recent
+    /// to the oldest, starting at the entry `from`.
+    pub fn list_audit_entries(
+        &self,
+        filter: &AuditFilter,
+        from: Option<AuditEntryId>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>> {
+        self.store.list_audit_entries(filter, from, limit)
+    }
+
+    /// Returns the whole audit log, from the oldest entry to the most recent.
+    pub fn audit_entries(&self) -> Result<Vec<AuditEntry>> {
+        self.store.audit_entries()
+    }
+
+    /// The counters of the rate limits, shared by all the clones of the controller.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Returns the uid and the rate limits of the API key whose encoded value is `encoded_key`,
+    /// if it has some. Nothing is read from the store.
+    pub fn rate_limit_of_encoded_key(&self, encoded_key: &str) -> Option<(Uuid, RateLimit)> {
+        self.rate_limits.get_by_encoded_key(encoded_key)
+    }
+
+    /// Returns the rate limits of the API key `uid`, if it has some. Nothing is read from the
+    /// store.
+    pub fn rate_limit_of_key(&self, uid: Uuid) -> Option<RateLimit> {
+        self.rate_limits.get(uid)
+    }
+
+    /// Keeps in memory that the tenant `token` is signed by the key `uid`, until its `exp`
+    /// timestamp or until the key is deleted.
+    pub fn cache_verified_tenant_token(&self, token: &str, uid: Uuid, exp: Option<i64>) {
+        self.rate_limits.set_verified_token(token, uid, exp);
+    }
+
+    /// Returns the uid of the key that signed the tenant `token` if it was cached by
+    /// [`Self::cache_verified_tenant_token`] and hasn't expired. Nothing is read from the store.
+    pub fn verified_tenant_token(&self, token: &str) -> Option<Uuid> {
+        self.rate_limits.get_verified_token(token)
+    }
+
     pub fn get_master_key(&self) -> Option<&String> {
         self.master_key.as_ref()
     }
@@ -157,8 +342,14 @@ impl AuthController {
             .or(match index {
                 // else check if the key has access to the requested index.
                 Some(index) => {
-                    self.store
+                    match self
+                        .store
                         .get_expiration_date(uid, action, Some(index.as_bytes()))?
+                    {
+                        Some(expiration) => Some(expiration),
+                        // or to an index pattern matching the requested index.
+                        None => self.store.pattern_expiration_date(uid, action, index)?,
+                    }
                 }
                 // or to any index if no index has been requested.
                 None => self.store.prefix_first_expiration_date(uid, action)?,
@@ -176,6 +367,13 @@ impl AuthController {
 pub struct AuthFilter {
     pub search_rules: SearchRules,
     pub allow_index_creation: bool,
+    /// The uid of the API key the request is authenticated with, `None` for the master key, the
+    /// tokens of the identity provider and the client certificates.
+    pub key_uid: Option<Uuid>,
+    /// The IP ranges the requests must come from, any when `None`.
+    pub allowed_ips: Option<Vec<IpNet>>,
+    /// The hosts the referer of the requests must match, any when `None`.
+    pub allowed_referers: Option<Vec<String>>,
 }
 
 impl Default for AuthFilter {
@@ -183,10 +381,56 @@ impl Default for AuthFilter {
         Self {
             search_rules: SearchRules::default(),
             allow_index_creation: true,
+            key_uid: None,
+            allowed_ips: None,
+            allowed_referers: None,
         }
     }
 }
 
+impl AuthFilter {
+    /// Returns whether a request sent from the `ip` address with the `referer` may use the key.
+    /// The requests whose address or referer is unknown are refused when the key restricts it.
+    pub fn is_origin_allowed(&self, ip: Option<IpAddr>, referer: Option<&str>) -> bool {
+        let ip_allowed = self.allowed_ips.as_ref().map_or(true, |allowed_ips| {
+            ip.map_or(false, |ip| allowed_ips.iter().any(|net| net.contains(&ip)))
+        });
+
+        let referer_allowed = self
+            .allowed_referers
+            .as_ref()
+            .map_or(true, |allowed_referers| {
+                referer.and_then(referer_host).map_or(false, |host| {
+                    allowed_referers
+                        .iter()
+                        .any(|allowed| host_matches(allowed, &host))
+                })
+            });
+
+        ip_allowed && referer_allowed
+    }
+}
+
+/// Returns the lowercased host of a `Referer` or `Origin` header value, e.g. `example.com` for
+/// `https://user@example.com:8080/search`.
+fn referer_host(referer: &str) -> Option<String> {
+    let rest = referer.split_once("://").map_or(referer, |(_, rest)| rest);
+    let authority = rest.split(|c| matches!(c, '/' | '?' | '#')).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Returns whether the `host` is the `allowed` host, or one of its subdomains if `allowed`
+/// starts with `*.`.
+fn host_matches(allowed: &str, host: &str) -> bool {
+    match allowed.strip_prefix("*.") {
+        Some(domain) => host.strip_suffix(domain).map_or(false, |subdomain| {
+            subdomain.len() > 1 && subdomain.ends_with('.')
+        }),
+        None => allowed == host,
+    }
+}
+
 /// Transparent wrapper around a list of allowed indexes with the search rules to apply for each.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
@@ -204,26 +448,76 @@ impl Default for SearchRules {
 impl SearchRules {
     pub fn is_index_authorized(&self, index: &str) -> bool {
         match self {
-            Self::Set(set) => set.contains("*") || set.contains(index),
-            Self::Map(map) => map.contains_key("*") || map.contains_key(index),
+            Self::Set(set) => {
+                set.contains("*")
+                    || set.contains(index)
+                    || set.iter().any(|pattern| matches_pattern(pattern, index))
+            }
+            Self::Map(map) => {
+                map.contains_key("*")
+                    || map.contains_key(index)
+                    || map.keys().any(|pattern| matches_pattern(pattern, index))
+            }
         }
     }
 
     pub fn get_index_search_rules(&self, index: &str) -> Option<IndexSearchRules> {
         match self {
-            Self::Set(set) => {
-                if set.contains("*") || set.contains(index) {
+            Self::Set(_) => {
+                if self.is_index_authorized(index) {
                     Some(IndexSearchRules::default())
                 } else {
                     None
                 }
             }
+            // the rules of the index itself take precedence over the rules of the longest index
+            // pattern matching it, which take precedence over the rules of `*`.
             Self::Map(map) => map
                 .get(index)
+                .or_else(|| {
+                    map.iter()
+                        .filter(|(pattern, _)| matches_pattern(pattern, index))
+                        .max_by_key(|(pattern, _)| pattern.len())
+                        .map(|(_, isr)| isr)
+                })
                 .or_else(|| map.get("*"))
                 .map(|isr| isr.clone().unwrap_or_default()),
         }
     }
+
+    /// Gives the alias `alias` the rules of the authorized index `index` it points to, so that
+    /// an index reached through an alias is restricted as if it was reached directly.
+    pub fn alias(self, alias: &str, index: &str) -> Self {
+        let rules = self.get_index_search_rules(index).unwrap_or_default();
+        let mut map: HashMap<_, _> = match self {
+            Self::Set(set) => set.into_iter().map(|i| (i, None)).collect(),
+            Self::Map(map) => map,
+        };
+        map.insert(alias.to_string(), Some(rules));
+        Self::Map(map)
+    }
+
+    /// Restricts further the attributes the searches can return on every index, with the
+    /// restrictions of an API key.
+    fn restrict_attributes(
+        self,
+        displayed: &Option<Vec<String>>,
+        forbidden: &Option<Vec<String>>,
+    ) -> Self {
+        let restrict = |rules: Option<IndexSearchRules>| {
+            let mut rules = rules.unwrap_or_default();
+            rules.restrict_attributes(displayed, forbidden);
+            Some(rules)
+        };
+        match self {
+            Self::Set(set) => Self::Map(set.into_iter().map(|i| (i, restrict(None))).collect()),
+            Self::Map(map) => Self::Map(
+                map.into_iter()
+                    .map(|(i, rules)| (i, restrict(rules)))
+                    .collect(),
+            ),
+        }
+    }
 }
 
 impl IntoIterator for SearchRules {
@@ -242,12 +536,67 @@ impl IntoIterator for SearchRules {
     }
 }
 
+/// Returns whether `pattern` is an index pattern like `products*` matching `index`.
+fn matches_pattern(pattern: &str, index: &str) -> bool {
+    pattern.ends_with('*') && index_uid_pattern::matches(pattern, index)
+}
+
 /// Contains the rules to apply on the top of the search query for a specific index.
 ///
 /// filter: search filter to apply in addition to query filters.
+/// displayedAttributes: the only attributes the search can return, all the displayed ones when
+/// omitted.
+/// forbiddenAttributes: the attributes the search can never return.
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct IndexSearchRules {
     pub filter: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub displayed_attributes: Option<BTreeSet<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forbidden_attributes: Option<BTreeSet<String>>,
+}
+
+impl IndexSearchRules {
+    /// Keeps the attributes displayed by both the rules and `displayed`, and forbids the
+    /// attributes forbidden by either of them. An attribute is matched by its name or by the name
+    /// of an object containing it.
+    fn restrict_attributes(
+        &mut self,
+        displayed: &Option<Vec<String>>,
+        forbidden: &Option<Vec<String>>,
+    ) {
+        self.displayed_attributes = match (self.displayed_attributes.take(), displayed) {
+            (Some(own), Some(displayed)) => {
+                let allowed_by = |attributes: &[&String], attribute: &str| {
+                    attributes
+                        .iter()
+                        .any(|allowed| milli::is_faceted_by(attribute, allowed))
+                };
+                let own_attributes: Vec<_> = own.iter().collect();
+                let displayed_attributes: Vec<_> = displayed.iter().collect();
+                Some(
+                    own.iter()
+                        .filter(|a| allowed_by(&displayed_attributes, a.as_str()))
+                        .chain(
+                            displayed
+                                .iter()
+                                .filter(|a| allowed_by(&own_attributes, a.as_str())),
+                        )
+                        .cloned()
+                        .collect(),
+                )
+            }
+            (own, None) => own,
+            (None, Some(displayed)) => Some(displayed.iter().cloned().collect()),
+        };
+
+        if let Some(forbidden) = forbidden {
+            self.forbidden_attributes
+                .get_or_insert_with(BTreeSet::new)
+                .extend(forbidden.iter().cloned());
+        }
+    }
 }
 
 fn generate_default_keys(store: &HeedAuthStore) -> Result<()> {
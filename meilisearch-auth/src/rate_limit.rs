@@ -0,0 +1,333 @@
+//! Counting of the requests against the rate limits of the API keys and of the whole instance.
+//!
+//! The requests are counted in windows of one second starting at the first request of the
+//! window. The concurrent searches are counted from the start of a search until its response is
+//! sent, through the [`RateLimitPermit`] returned for the request.
+//!
+//! The limits of the API keys are kept in memory by the [`KeyRateLimits`], so that the requests
+//! are counted without reading the keys from the store. The tenant tokens whose signature was
+//! checked are kept along with them until they expire, so that a token is only verified once.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// The maximum number of verified tenant tokens kept in memory, they are all forgotten when it's
+/// reached and none of them has expired.
+const MAX_VERIFIED_TOKENS: usize = 10_000;
+
+/// The limits of the requests made with an API key, or of all the requests when they are set
+/// globally. Each limit is unlimited when `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimit {
+    #[serde(default)]
+    pub requests_per_second: Option<u32>,
+    #[serde(default)]
+    pub concurrent_searches: Option<u32>,
+}
+
+impl RateLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.requests_per_second.is_none() && self.concurrent_searches.is_none()
+    }
+}
+
+/// The rate limits of the API keys that have some, by uid and by encoded key.
+#[derive(Clone, Default)]
+pub(crate) struct KeyRateLimits {
+    inner: Arc<RwLock<KeyRateLimitsInner>>,
+}
+
+#[derive(Default)]
+struct KeyRateLimitsInner {
+    by_uid: HashMap<Uuid, RateLimit>,
+    by_encoded_key: HashMap<String, Uuid>,
+    /// The uid of the parent key and the expiration timestamp of the verified tenant tokens, by
+    /// hash of the token.
+    verified_tokens: HashMap<[u8; 32], (Uuid, Option<i64>)>,
+}
+
+impl KeyRateLimits {
+    /// Records the `rate_limit` of the key `uid` whose encoded value is `encoded_key`, or
+    /// forgets it if the key isn't limited anymore.
+    pub fn set(&self, uid: Uuid, encoded_key: String, rate_limit: Option<RateLimit>) {
+        let mut inner = self.inner.write().unwrap();
+        match rate_limit.filter(|limits| !limits.is_unlimited()) {
+            Some(limits) => {
+                inner.by_uid.insert(uid, limits);
+                inner.by_encoded_key.insert(encoded_key, uid);
+            }
+            None => {
+                inner.by_uid.remove(&uid);
+                inner.by_encoded_key.remove(&encoded_key);
+            }
+        }
+    }
+
+    pub fn remove(&self, uid: Uuid) {
+        let mut inner = self.inner.write().unwrap();
+        inner.by_uid.remove(&uid);
+        inner.by_encoded_key.retain(|_, key_uid| *key_uid != uid);
+        inner
+            .verified_tokens
+            .retain(|_, (key_uid, _)| *key_uid != uid);
+    }
+
+    pub fn get(&self, uid: Uuid) -> Option<RateLimit> {
+        self.inner.read().unwrap().by_uid.get(&uid).copied()
+    }
+
+    pub fn get_by_encoded_key(&self, encoded_key: &str) -> Option<(Uuid, RateLimit)> {
+        let inner = self.inner.read().unwrap();
+        let uid = *inner.by_encoded_key.get(encoded_key)?;
+        inner.by_uid.get(&uid).map(|limits| (uid, *limits))
+    }
+
+    /// Records that the tenant `token` is signed by the key `uid` and expires at the `exp`
+    /// timestamp, if any.
+    pub fn set_verified_token(&self, token: &str, uid: Uuid, exp: Option<i64>) {
+        let mut inner = self.inner.write().unwrap();
+        if inner.verified_tokens.len() >= MAX_VERIFIED_TOKENS {
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            inner
+                .verified_tokens
+                .retain(|_, (_, exp)| !is_expired(*exp, now));
+            if inner.verified_tokens.len() >= MAX_VERIFIED_TOKENS {
+                inner.verified_tokens.clear();
+            }
+        }
+        inner.verified_tokens.insert(token_hash(token), (uid, exp));
+    }
+
+    /// Returns the uid of the key that signed the tenant `token` if it was verified and hasn't
+    /// expired since.
+    pub fn get_verified_token(&self, token: &str) -> Option<Uuid> {
+        let inner = self.inner.read().unwrap();
+        let (uid, exp) = inner.verified_tokens.get(&token_hash(token))?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        (!is_expired(*exp, now)).then(|| *uid)
+    }
+}
+
+fn token_hash(token: &str) -> [u8; 32] {
+    Sha256::digest(token).into()
+}
+
+/// A tenant token is expired once the second of its `exp` timestamp has passed.
+fn is_expired(exp: Option<i64>, now: i64) -> bool {
+    matches!(exp, Some(exp) if now > exp)
+}
+
+/// The requests of a key, or of the whole instance for the `None` bucket.
+type Bucket = Option<Uuid>;
+
+#[derive(Default)]
+struct Counter {
+    window_start: Option<Instant>,
+    requests: u32,
+    searches: u32,
+}
+
+impl Counter {
+    /// Returns how long to wait before a request is accepted, `None` if it is accepted now.
+    fn retry_after(&mut self, limits: &RateLimit, search: bool, now: Instant) -> Option<Duration> {
+        let elapsed = self.window_start.map(|start| now.duration_since(start));
+        if elapsed.map_or(true, |elapsed| elapsed >= WINDOW) {
+            self.window_start = Some(now);
+            self.requests = 0;
+        }
+
+        if matches!(limits.requests_per_second, Some(max) if self.requests >= max) {
+            let elapsed = elapsed.unwrap_or_default();
+            return Some(WINDOW.saturating_sub(elapsed));
+        }
+        if search && matches!(limits.concurrent_searches, Some(max) if self.searches >= max) {
+            // we can't know when a search finishes, the client should retry soon.
+            return Some(WINDOW);
+        }
+
+        None
+    }
+}
+
+/// The counters of the requests, shared by all the workers of the server.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    counters: Arc<Mutex<HashMap<Bucket, Counter>>>,
+}
+
+impl RateLimiter {
+    /// Counts a request against the `global` limits and the limits of its `key`, if any. Returns
+    /// how long to wait before retrying if a limit is reached, in which case nothing is counted.
+    pub fn acquire(
+        &self,
+        global: RateLimit,
+        key: Option<(Uuid, RateLimit)>,
+        search: bool,
+    ) -> Result<RateLimitPermit, Duration> {
+        let mut buckets = Vec::new();
+        if !global.is_unlimited() {
+            buckets.push((None, global));
+        }
+        if let Some((uid, limits)) = key.filter(|(_, limits)| !limits.is_unlimited()) {
+            buckets.push((Some(uid), limits));
+        }
+
+        if buckets.is_empty() {
+            return Ok(RateLimitPermit::default());
+        }
+
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap();
+        let retry_after = buckets
+            .iter()
+            .filter_map(|(bucket, limits)| {
+                counters
+                    .entry(*bucket)
+                    .or_default()
+                    .retry_after(limits, search, now)
+            })
+            .max();
+        if let Some(retry_after) = retry_after {
+            return Err(retry_after);
+        }
+
+        for (bucket, _) in &buckets {
+            let counter = counters.entry(*bucket).or_default();
+            counter.requests += 1;
+            if search {
+                counter.searches += 1;
+            }
+        }
+
+        Ok(RateLimitPermit {
+            counters: search.then(|| self.counters.clone()),
+            buckets: buckets.into_iter().map(|(bucket, _)| bucket).collect(),
+        })
+    }
+}
+
+/// Releases the concurrent search it counted when dropped.
+#[derive(Default)]
+pub struct RateLimitPermit {
+    counters: Option<Arc<Mutex<HashMap<Bucket, Counter>>>>,
+    buckets: Vec<Bucket>,
+}
+
+impl Drop for RateLimitPermit {
+    fn drop(&mut self) {
+        if let Some(counters) = self.counters.take() {
+            let mut counters = counters.lock().unwrap();
+            for bucket in &self.buckets {
+                if let Some(counter) = counters.get_mut(bucket) {
+                    counter.searches = counter.searches.saturating_sub(1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn requests_per_second() {
+        let limiter = RateLimiter::default();
+        let uid = Uuid::new_v4();
+        let limits = RateLimit {
+            requests_per_second: Some(2),
+            concurrent_searches: None,
+        };
+
+        assert!(limiter
+            .acquire(RateLimit::default(), Some((uid, limits)), false)
+            .is_ok());
+        assert!(limiter
+            .acquire(RateLimit::default(), Some((uid, limits)), false)
+            .is_ok());
+        let retry_after = limiter
+            .acquire(RateLimit::default(), Some((uid, limits)), false)
+            .err()
+            .unwrap();
+        assert!(retry_after <= WINDOW);
+
+        // the other keys aren't limited.
+        let other = Some((Uuid::new_v4(), limits));
+        assert!(limiter.acquire(RateLimit::default(), other, false).is_ok());
+        // nor the requests without limits.
+        assert!(limiter.acquire(RateLimit::default(), None, false).is_ok());
+    }
+
+    #[test]
+    fn key_rate_limits() {
+        let rate_limits = KeyRateLimits::default();
+        let uid = Uuid::new_v4();
+        let limits = RateLimit {
+            requests_per_second: Some(2),
+            concurrent_searches: None,
+        };
+
+        rate_limits.set(uid, "encoded".to_string(), Some(limits));
+        assert_eq!(rate_limits.get(uid), Some(limits));
+        assert_eq!(
+            rate_limits.get_by_encoded_key("encoded"),
+            Some((uid, limits))
+        );
+        assert_eq!(rate_limits.get_by_encoded_key("other"), None);
+
+        // the keys without limits aren't kept.
+        rate_limits.set(uid, "encoded".to_string(), Some(RateLimit::default()));
+        assert_eq!(rate_limits.get(uid), None);
+        assert_eq!(rate_limits.get_by_encoded_key("encoded"), None);
+
+        rate_limits.set(uid, "encoded".to_string(), Some(limits));
+        rate_limits.remove(uid);
+        assert_eq!(rate_limits.get(uid), None);
+        assert_eq!(rate_limits.get_by_encoded_key("encoded"), None);
+    }
+
+    #[test]
+    fn verified_tokens() {
+        let rate_limits = KeyRateLimits::default();
+        let uid = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        rate_limits.set_verified_token("forever", uid, None);
+        rate_limits.set_verified_token("later", uid, Some(now + 3600));
+        rate_limits.set_verified_token("expired", uid, Some(now - 1));
+        assert_eq!(rate_limits.get_verified_token("forever"), Some(uid));
+        assert_eq!(rate_limits.get_verified_token("later"), Some(uid));
+        assert_eq!(rate_limits.get_verified_token("expired"), None);
+        assert_eq!(rate_limits.get_verified_token("unknown"), None);
+
+        // the tokens of a deleted key must be verified again.
+        rate_limits.remove(uid);
+        assert_eq!(rate_limits.get_verified_token("forever"), None);
+    }
+
+    #[test]
+    fn concurrent_searches() {
+        let limiter = RateLimiter::default();
+        let global = RateLimit {
+            requests_per_second: None,
+            concurrent_searches: Some(1),
+        };
+
+        let permit = limiter.acquire(global, None, true).unwrap();
+        assert!(limiter.acquire(global, None, true).is_err());
+        // the other requests aren't searches.
+        assert!(limiter.acquire(global, None, false).is_ok());
+
+        drop(permit);
+        assert!(limiter.acquire(global, None, true).is_ok());
+    }
+}
@@ -8,15 +8,16 @@ use std::path::Path;
 use std::str;
 use std::sync::Arc;
 
-use enum_iterator::IntoEnumIterator;
 use hmac::{Hmac, Mac};
+use meilisearch_types::index_uid_pattern;
 use meilisearch_types::star_or::StarOr;
-use milli::heed::types::{ByteSlice, DecodeIgnore, SerdeJson};
+use milli::heed::types::{ByteSlice, DecodeIgnore, OwnedType, SerdeJson};
 use milli::heed::{Database, Env, EnvOpenOptions, RwTxn};
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use super::audit::{AuditEntry, AuditEntryId, AuditFilter};
 use super::error::Result;
 use super::{Action, Key};
 
@@ -24,6 +25,9 @@ const AUTH_STORE_SIZE: usize = 1_073_741_824; //1GiB
 const AUTH_DB_PATH: &str = "auth";
 const KEY_DB_NAME: &str = "api-keys";
 const KEY_ID_ACTION_INDEX_EXPIRATION_DB_NAME: &str = "keyid-action-index-expiration";
+const AUDIT_LOG_DB_NAME: &str = "audit-log";
+
+type BEU64 = milli::heed::zerocopy::U64<milli::heed::byteorder::BE>;
 
 pub type KeyId = Uuid;
 
@@ -32,6 +36,7 @@ pub struct HeedAuthStore {
     env: Arc<Env>,
     keys: Database<ByteSlice, SerdeJson<Key>>,
     action_keyid_index_expiration: Database<KeyIdActionCodec, SerdeJson<Option<OffsetDateTime>>>,
+    audit_log: Database<OwnedType<BEU64>, SerdeJson<AuditEntry>>,
     should_close_on_drop: bool,
 }
 
@@ -46,7 +51,7 @@ impl Drop for HeedAuthStore {
 pub fn open_auth_store_env(path: &Path) -> milli::heed::Result<milli::heed::Env> {
     let mut options = EnvOpenOptions::new();
     options.map_size(AUTH_STORE_SIZE); // 1GB
-    options.max_dbs(2);
+    options.max_dbs(3);
     options.open(path)
 }
 
@@ -58,10 +63,12 @@ impl HeedAuthStore {
         let keys = env.create_database(Some(KEY_DB_NAME))?;
         let action_keyid_index_expiration =
             env.create_database(Some(KEY_ID_ACTION_INDEX_EXPIRATION_DB_NAME))?;
+        let audit_log = env.create_database(Some(AUDIT_LOG_DB_NAME))?;
         Ok(Self {
             env,
             keys,
             action_keyid_index_expiration,
+            audit_log,
             should_close_on_drop: true,
         })
     }
@@ -87,12 +94,10 @@ impl HeedAuthStore {
         // create inverted database.
         let db = self.action_keyid_index_expiration;
 
-        let actions = if key.actions.contains(&Action::All) {
-            // if key.actions contains All, we iterate over all actions.
-            Action::into_enum_iter().collect()
-        } else {
-            key.actions.clone()
-        };
+        // the wildcard actions are expanded into the actions they grant.
+        let mut actions: Vec<_> = key.actions.iter().flat_map(|a| a.expand()).collect();
+        actions.sort_unstable_by_key(Action::repr);
+        actions.dedup();
 
         let no_index_restriction = key.indexes.contains(&StarOr::Star);
         for action in actions {
@@ -176,6 +181,32 @@ impl HeedAuthStore {
         Ok(self.action_keyid_index_expiration.get(&rtxn, &tuple)?)
     }
 
+    /// Returns the expiration date of the action on the first index pattern of the key, like
+    /// `products*`, matching `index`.
+    pub fn pattern_expiration_date(
+        &self,
+        uid: Uuid,
+        action: Action,
+        index: &str,
+    ) -> Result<Option<Option<OffsetDateTime>>> {
+        let rtxn = self.env.read_txn()?;
+        let tuple = (&uid, &action, None);
+        for entry in self
+            .action_keyid_index_expiration
+            .prefix_iter(&rtxn, &tuple)?
+        {
+            let ((_, _, pattern), expiration) = entry?;
+            let pattern = pattern.and_then(|pattern| str::from_utf8(pattern).ok());
+            if let Some(pattern) = pattern.filter(|pattern| pattern.ends_with('*')) {
+                if index_uid_pattern::matches(pattern, index) {
+                    return Ok(Some(expiration));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     pub fn prefix_first_expiration_date(
         &self,
         uid: Uuid,
@@ -193,6 +224,57 @@ impl HeedAuthStore {
         Ok(exp)
     }
 
+    /// Appends `entry` to the audit log under the id following the last recorded entry, the id
+    /// of `entry` is ignored.
+    pub fn put_audit_entry(&self, mut entry: AuditEntry) -> Result<AuditEntry> {
+        let mut wtxn = self.env.write_txn()?;
+        entry.id = match self.audit_log.last(&wtxn)? {
+            Some((id, _)) => id.get() + 1,
+            None => 0,
+        };
+        self.audit_log
+            .put(&mut wtxn, &BEU64::new(entry.id), &entry)?;
+        wtxn.commit()?;
+
+        Ok(entry)
+    }
+
+    /// Returns at most `limit` entries matching `filter`, from the most recent to the oldest,
+    /// starting at the entry `from`.
+    pub fn list_audit_entries(
+        &self,
+        filter: &AuditFilter,
+        from: Option<AuditEntryId>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>> {
+        let rtxn = self.env.read_txn()?;
+        let range = ..=BEU64::new(from.unwrap_or(u64::MAX));
+        let mut entries = Vec::new();
+        for result in self.audit_log.rev_range(&rtxn, &range)? {
+            if entries.len() >= limit {
+                break;
+            }
+            let (_, entry) = result?;
+            if filter.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns every entry of the audit log, from the oldest to the most recent.
+    pub fn audit_entries(&self) -> Result<Vec<AuditEntry>> {
+        let rtxn = self.env.read_txn()?;
+        let mut entries = Vec::new();
+        for result in self.audit_log.iter(&rtxn)? {
+            let (_, entry) = result?;
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
     fn delete_key_from_inverted_db(&self, wtxn: &mut RwTxn, key: &KeyId) -> Result<()> {
         let mut iter = self
             .action_keyid_index_expiration
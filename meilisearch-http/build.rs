@@ -7,6 +7,10 @@ fn main() {
 
     #[cfg(feature = "mini-dashboard")]
     mini_dashboard::setup_mini_dashboard().expect("Could not load the mini-dashboard assets");
+
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/meilisearch.proto")
+        .expect("Could not compile the gRPC protocol");
 }
 
 #[cfg(feature = "mini-dashboard")]
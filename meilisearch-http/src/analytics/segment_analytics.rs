@@ -10,7 +10,7 @@ use http::header::CONTENT_TYPE;
 use meilisearch_auth::SearchRules;
 use meilisearch_lib::index::{
     SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
 };
 use meilisearch_lib::index_controller::Stats;
 use meilisearch_lib::MeiliSearch;
@@ -366,9 +366,34 @@ pub struct SearchAggregator {
     // The maximum number of terms in a q request
     max_terms_number: usize,
 
+    // vector
+    // The maximum number of dimensions of a vector request
+    max_vector_size: usize,
+    // Whether a request blended the keyword and vector rankings
+    hybrid: bool,
+
+    // matching strategy
+    matching_strategy: HashMap<String, usize>,
+
+    // attributes to search on
+    attributes_to_search_on: bool,
+
+    // distinct
+    distinct: bool,
+
+    // attribute weights
+    attribute_weights: bool,
+
+    // ranking profile
+    ranking_profile: bool,
+
+    // suggestions
+    show_suggestions: bool,
+
     // pagination
     max_limit: usize,
     max_offset: usize,
+    finite_pagination: usize,
 
     // formatting
     highlight_pre_tag: bool,
@@ -423,8 +448,32 @@ impl SearchAggregator {
             ret.max_terms_number = q.split_whitespace().count();
         }
 
-        ret.max_limit = query.limit;
-        ret.max_offset = query.offset.unwrap_or_default();
+        if let Some(ref vector) = query.vector {
+            ret.max_vector_size = vector.len();
+        }
+        ret.hybrid = query.hybrid.is_some();
+
+        ret.matching_strategy
+            .insert(format!("{:?}", query.matching_strategy), 1);
+
+        ret.attributes_to_search_on = query.attributes_to_search_on.is_some();
+        ret.distinct = query.distinct.is_some();
+        ret.attribute_weights = query.attribute_weights.is_some();
+        ret.ranking_profile = query.ranking_profile.is_some();
+        ret.show_suggestions = query.show_suggestions;
+
+        if query.page.is_some() || query.hits_per_page.is_some() {
+            ret.max_limit = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+            ret.max_offset = query
+                .page
+                .unwrap_or(1)
+                .saturating_sub(1)
+                .saturating_mul(ret.max_limit);
+            ret.finite_pagination = 1;
+        } else {
+            ret.max_limit = query.limit;
+            ret.max_offset = query.offset.unwrap_or_default();
+        }
 
         ret.highlight_pre_tag = query.highlight_pre_tag != DEFAULT_HIGHLIGHT_PRE_TAG();
         ret.highlight_post_tag = query.highlight_post_tag != DEFAULT_HIGHLIGHT_POST_TAG();
@@ -476,9 +525,30 @@ impl SearchAggregator {
         }
         // q
         self.max_terms_number = self.max_terms_number.max(other.max_terms_number);
+        // vector
+        self.max_vector_size = self.max_vector_size.max(other.max_vector_size);
+        self.hybrid |= other.hybrid;
+        // matching strategy
+        for (key, value) in other.matching_strategy.into_iter() {
+            let matching_strategy = self.matching_strategy.entry(key).or_insert(0);
+            *matching_strategy = matching_strategy.saturating_add(value);
+        }
+        // attributes to search on
+        self.attributes_to_search_on |= other.attributes_to_search_on;
+        // distinct
+        self.distinct |= other.distinct;
+        // attribute weights
+        self.attribute_weights |= other.attribute_weights;
+        // ranking profile
+        self.ranking_profile |= other.ranking_profile;
+        // suggestions
+        self.show_suggestions |= other.show_suggestions;
         // pagination
         self.max_limit = self.max_limit.max(other.max_limit);
         self.max_offset = self.max_offset.max(other.max_offset);
+        self.finite_pagination = self
+            .finite_pagination
+            .saturating_add(other.finite_pagination);
 
         self.highlight_pre_tag |= other.highlight_pre_tag;
         self.highlight_post_tag |= other.highlight_post_tag;
@@ -518,9 +588,32 @@ impl SearchAggregator {
                 "q": {
                    "max_terms_number": self.max_terms_number,
                 },
+                "vector": {
+                    "max_vector_size": self.max_vector_size,
+                    "hybrid": self.hybrid,
+                },
+                "attributes_to_search_on": {
+                    "used": self.attributes_to_search_on,
+                },
+                "distinct": {
+                    "used": self.distinct,
+                },
+                "attribute_weights": {
+                    "used": self.attribute_weights,
+                },
+                "ranking_profile": {
+                    "used": self.ranking_profile,
+                },
+                "suggestions": {
+                    "used": self.show_suggestions,
+                },
+                "matching_strategy": {
+                    "most_used_strategy": self.matching_strategy.iter().max_by_key(|(_, v)| *v).map(|(k, _)| json!(k)).unwrap_or_else(|| json!(null)),
+                },
                 "pagination": {
                    "max_limit": self.max_limit,
                    "max_offset": self.max_offset,
+                   "most_used_navigation": if self.finite_pagination > (self.total_received / 2) { "exhaustive" } else { "estimated" },
                 },
                 "formatting": {
                     "highlight_pre_tag": self.highlight_pre_tag,
@@ -0,0 +1,140 @@
+//! Decides which responses are compressed by the `Compress` middleware.
+//!
+//! The responses are compressed with the encoding negotiated from the `Accept-Encoding` header of
+//! the request. The ones smaller than the size threshold, where the compression wouldn't pay off,
+//! and the ones of the routes opted out are sent as is. They are marked with the `identity`
+//! content encoding, which the `Compress` middleware leaves untouched, so this middleware must be
+//! wrapped before it.
+
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, CONTENT_ENCODING};
+use actix_web::Error;
+use futures::future::LocalBoxFuture;
+
+use crate::option::Opt;
+
+/// Returns whether the `path` is one of the `route`, whose `*` segments match any segment, or is
+/// below it.
+fn matches_route(route: &str, path: &str) -> bool {
+    let mut path = path.split('/').filter(|segment| !segment.is_empty());
+    route
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .all(|expected| match path.next() {
+            Some(segment) => expected == "*" || expected == segment,
+            None => false,
+        })
+}
+
+struct CompressionConfig {
+    min_size: u64,
+    disabled_routes: Vec<String>,
+}
+
+impl CompressionConfig {
+    fn is_disabled(&self, path: &str) -> bool {
+        self.disabled_routes
+            .iter()
+            .any(|route| matches_route(route, path))
+    }
+}
+
+pub struct CompressionPolicy {
+    config: Rc<CompressionConfig>,
+}
+
+impl CompressionPolicy {
+    pub fn new(opt: &Opt) -> Self {
+        Self {
+            config: Rc::new(CompressionConfig {
+                min_size: opt.http_compression_min_size.get_bytes() as u64,
+                disabled_routes: opt.http_compression_disabled_routes.clone(),
+            }),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CompressionPolicy
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CompressionPolicyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CompressionPolicyMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct CompressionPolicyMiddleware<S> {
+    service: Rc<S>,
+    config: Rc<CompressionConfig>,
+}
+
+impl<S, B> Service<ServiceRequest> for CompressionPolicyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let disabled = self.config.is_disabled(req.path());
+        let min_size = self.config.min_size;
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            let mut response = service.call(req).await?;
+            // the size of the streamed responses isn't known, they are always compressed.
+            let too_small = match response.response().body().size() {
+                BodySize::Sized(size) => size < min_size,
+                BodySize::None | BodySize::Stream => false,
+            };
+            if (disabled || too_small) && !response.headers().contains_key(CONTENT_ENCODING) {
+                response
+                    .headers_mut()
+                    .insert(CONTENT_ENCODING, HeaderValue::from_static("identity"));
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_matches_route() {
+        assert!(matches_route("/metrics", "/metrics"));
+        assert!(matches_route(
+            "/indexes/*/documents",
+            "/indexes/movies/documents"
+        ));
+        assert!(matches_route(
+            "/indexes/*/documents",
+            "/indexes/movies/documents/42"
+        ));
+        assert!(!matches_route(
+            "/indexes/*/documents",
+            "/indexes/movies/search"
+        ));
+        assert!(!matches_route("/indexes/*/documents", "/indexes/movies"));
+        assert!(matches_route("/", "/health"));
+    }
+}
@@ -0,0 +1,29 @@
+use actix_cors::Cors;
+
+use crate::Opt;
+
+/// Returns the CORS policy of the `--cors-*` options. Any origin, method and header is allowed
+/// unless the options restrict them.
+pub fn cors_policy(opt: &Opt) -> Cors {
+    let mut cors = Cors::default().max_age(opt.cors_max_age_sec);
+
+    if opt.cors_allowed_origins.is_empty() || opt.cors_allowed_origins.iter().any(|o| o == "*") {
+        cors = cors.allow_any_origin().send_wildcard();
+    } else {
+        for origin in &opt.cors_allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+
+    cors = if opt.cors_allowed_methods.is_empty() {
+        cors.allow_any_method()
+    } else {
+        cors.allowed_methods(opt.cors_allowed_methods.iter().cloned())
+    };
+
+    if opt.cors_allowed_headers.is_empty() {
+        cors.allow_any_header()
+    } else {
+        cors.allowed_headers(opt.cors_allowed_headers.iter().cloned())
+    }
+}
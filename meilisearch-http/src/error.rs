@@ -1,3 +1,4 @@
+use actix_multipart::MultipartError;
 use actix_web as aweb;
 use aweb::error::{JsonPayloadError, QueryPayloadError};
 use meilisearch_types::error::{Code, ErrorCode, ResponseError};
@@ -12,6 +13,48 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error("The `{0}` value `{1}` is invalid. Only ASCII characters are accepted.")]
+    InvalidCsvParameter(&'static str, char),
+    #[error("The multipart payload provided is malformed. `{0}`.")]
+    MalformedMultipart(MultipartError),
+    #[error("The federation `weight` of query `{0}` is invalid. Expected a positive number, found `{1}`.")]
+    InvalidFederationWeight(usize, f64),
+    #[error("The `{1}` parameter of query `{0}` can't be used with the `federation`. The pagination of a federated search is set in the `federation`.")]
+    PaginationInFederatedQuery(usize, &'static str),
+    #[error("The cursor `{0}` is invalid. A cursor must be the `nextCursor` returned by a previous call.")]
+    InvalidDocumentCursor(String),
+    #[error("The `cursor` parameter can't be used with the `offset` or `sort` parameters.")]
+    CursorWithOffsetOrSort,
+    #[error("The `ids` parameter can't be used with the `offset`, `cursor`, `filter` or `sort` parameters.")]
+    IdsWithPagination,
+    #[error("The synonyms file is invalid at line {0}: {1}.")]
+    InvalidSynonymsFile(usize, String),
+    #[error("The synonyms of `{0}` are one-way synonyms, which can't be exported in the WordNet format. Use the Solr format instead.")]
+    UnsupportedSynonymsExport(String),
+    #[error("Query parameters to filter the tasks to {0} are missing. Available query parameters are: `uids`, `indexUids`, `statuses`, `types`.")]
+    MissingTaskFilters(&'static str),
+    #[error("The task priority `{0}` is invalid. Expected one of `low`, `normal` or `high`.")]
+    InvalidTaskPriority(String),
+    #[error("The webhook URL `{0}` is invalid. Expected an absolute `http` or `https` URL.")]
+    InvalidWebhookUrl(String),
+    #[error("The log level `{0}` is invalid. Expected comma separated `level` or `module=level` filters, where the level is one of `off`, `error`, `warn`, `info`, `debug` or `trace`.")]
+    InvalidLogLevel(String),
+    #[error("A batch must contain at least one operation.")]
+    EmptyBatch,
+    #[error("The operation `{0}` of the batch is invalid: {1}.")]
+    InvalidBatchOperation(usize, String),
+    #[error("Exactly one of `path` or `url` must be provided to import a dump.")]
+    InvalidDumpSource,
+    #[error("Too many requests. Retry after {0} seconds.")]
+    TooManyRequests(u64),
+    #[error("The Elasticsearch request is not supported: {0}.")]
+    InvalidElasticsearchRequest(String),
+    #[error("The {0} feature is experimental and must be enabled with the `{1}` option.")]
+    FeatureNotEnabled(&'static str, &'static str),
+    #[error("The instance is in read-only mode for maintenance, only the read requests and the searches are accepted.")]
+    ReadOnlyMode,
+    #[error("The instance is a read-only replica of `{0}`, the write requests must be sent to the primary instance.")]
+    ReplicaMode(String),
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -19,10 +62,40 @@ impl ErrorCode for MeilisearchHttpError {
         match self {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::InvalidCsvParameter(_, _) => Code::BadRequest,
+            MeilisearchHttpError::InvalidFederationWeight(_, _)
+            | MeilisearchHttpError::PaginationInFederatedQuery(_, _) => Code::BadRequest,
+            MeilisearchHttpError::InvalidDocumentCursor(_)
+            | MeilisearchHttpError::CursorWithOffsetOrSort => Code::InvalidDocumentCursor,
+            MeilisearchHttpError::InvalidSynonymsFile(_, _)
+            | MeilisearchHttpError::UnsupportedSynonymsExport(_)
+            | MeilisearchHttpError::IdsWithPagination => Code::BadRequest,
+            MeilisearchHttpError::MissingTaskFilters(_) => Code::MissingTaskFilters,
+            MeilisearchHttpError::InvalidTaskPriority(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidWebhookUrl(_) => Code::InvalidWebhookUrl,
+            MeilisearchHttpError::InvalidLogLevel(_) => Code::InvalidLogLevel,
+            MeilisearchHttpError::EmptyBatch
+            | MeilisearchHttpError::InvalidBatchOperation(_, _) => Code::InvalidBatchOperation,
+            MeilisearchHttpError::InvalidDumpSource => Code::BadRequest,
+            MeilisearchHttpError::InvalidElasticsearchRequest(_) => Code::BadRequest,
+            MeilisearchHttpError::TooManyRequests(_) => Code::TooManyRequests,
+            MeilisearchHttpError::FeatureNotEnabled(_, _) => Code::FeatureNotEnabled,
+            MeilisearchHttpError::ReadOnlyMode => Code::ReadOnlyMode,
+            MeilisearchHttpError::ReplicaMode(_) => Code::ReadOnlyMode,
+            MeilisearchHttpError::MalformedMultipart(MultipartError::Payload(
+                aweb::error::PayloadError::Overflow,
+            )) => Code::PayloadTooLarge,
+            MeilisearchHttpError::MalformedMultipart(_) => Code::MalformedPayload,
         }
     }
 }
 
+impl From<MultipartError> for MeilisearchHttpError {
+    fn from(other: MultipartError) -> Self {
+        Self::MalformedMultipart(other)
+    }
+}
+
 impl From<MeilisearchHttpError> for aweb::Error {
     fn from(other: MeilisearchHttpError) -> Self {
         aweb::Error::from(ResponseError::from(other))
@@ -0,0 +1,85 @@
+//! The `ETag` of the responses derived from the version of an index, and the conditional
+//! requests sent with the `If-None-Match` header.
+
+use std::hash::Hasher;
+
+use actix_web::http::header::{ETag, EntityTag, IfNoneMatch};
+use actix_web::{HttpRequest, HttpResponse};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use siphasher::sip::SipHasher;
+
+/// Returns the `ETag` of the response to `req` on the index `uid`. It changes with the documents
+/// and the settings of the index, and with the route and the parameters of the request, as they
+/// select what is returned.
+pub async fn index_etag(
+    meilisearch: &MeiliSearch,
+    uid: String,
+    req: &HttpRequest,
+) -> Result<EntityTag, ResponseError> {
+    let (uuid, updated_at) = meilisearch.index_version(uid).await?;
+
+    // the SipHasher keys are fixed, so the tags survive a restart.
+    let mut hasher = SipHasher::new();
+    hasher.write(uuid.as_bytes());
+    hasher.write(&updated_at.unix_timestamp_nanos().to_be_bytes());
+    hasher.write(req.path().as_bytes());
+    hasher.write(req.query_string().as_bytes());
+
+    Ok(EntityTag::new_strong(format!("{:016x}", hasher.finish())))
+}
+
+/// Returns whether the client already holds the response tagged `etag`, according to the
+/// `If-None-Match` header of `req`.
+fn is_fresh(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match req.get_header::<IfNoneMatch>() {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        None => false,
+    }
+}
+
+/// Returns a `304 Not Modified` response if the client already holds the response tagged `etag`,
+/// it is checked before the response is read from the index.
+pub fn not_modified(req: &HttpRequest, etag: &EntityTag) -> Option<HttpResponse> {
+    is_fresh(req, etag).then(|| {
+        HttpResponse::NotModified()
+            .insert_header(ETag(etag.clone()))
+            .finish()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[test]
+    fn test_is_fresh() {
+        let etag = EntityTag::new_strong("0123456789abcdef".to_string());
+
+        let req = TestRequest::default().to_http_request();
+        assert!(!is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", r#""other", "0123456789abcdef""#))
+            .to_http_request();
+        assert!(is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", r#"W/"0123456789abcdef""#))
+            .to_http_request();
+        assert!(is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", "*"))
+            .to_http_request();
+        assert!(is_fresh(&req, &etag));
+
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", r#""other""#))
+            .to_http_request();
+        assert!(!is_fresh(&req, &etag));
+    }
+}
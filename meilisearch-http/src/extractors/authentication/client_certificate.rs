@@ -0,0 +1,26 @@
+use std::any::Any;
+
+use actix_tls::accept::rustls::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use meilisearch_auth::certificate_fingerprint;
+
+/// The fingerprint of the certificate the client presented when the TLS connection was
+/// established, the certificate has been verified against the `--ssl-auth-path` roots.
+#[derive(Debug, Clone)]
+pub struct ClientCertificate {
+    pub fingerprint: String,
+}
+
+/// Stores the [`ClientCertificate`] of each TLS connection in its data, to be used with
+/// `HttpServer::on_connect`.
+pub fn extract_client_certificate(connection: &dyn Any, data: &mut Extensions) {
+    if let Some(stream) = connection.downcast_ref::<TlsStream<TcpStream>>() {
+        let (_, session) = stream.get_ref();
+        if let Some(certificate) = session.peer_certificates().and_then(|certs| certs.first()) {
+            data.insert(ClientCertificate {
+                fingerprint: certificate_fingerprint(&certificate.0),
+            });
+        }
+    }
+}
@@ -1,15 +1,20 @@
+mod client_certificate;
 mod error;
+mod origin;
 
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::pin::Pin;
 
 use actix_web::FromRequest;
-use error::AuthenticationError;
-use futures::future::err;
+pub use client_certificate::{extract_client_certificate, ClientCertificate};
+pub use error::AuthenticationError;
+use futures::future::{err, ok, Ready};
 use futures::Future;
-use meilisearch_auth::{AuthController, AuthFilter};
+use meilisearch_auth::{Action, AuthController, AuthFilter};
+use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::{Code, ResponseError};
+pub use origin::{RequestOrigin, TrustedProxies};
 
 pub struct GuardedData<P, D> {
     data: D,
@@ -26,16 +31,22 @@ impl<P, D> GuardedData<P, D> {
         auth: AuthController,
         token: String,
         index: Option<String>,
+        origin: RequestOrigin,
+        meilisearch: Option<MeiliSearch>,
         data: Option<D>,
     ) -> Result<Self, ResponseError>
     where
         P: Policy + 'static,
     {
-        match Self::authenticate(auth, token, index).await? {
+        let resolved = resolve_alias(meilisearch, index.clone()).await?;
+        match Self::authenticate(auth, token, resolved.clone()).await? {
+            Some(filters) if !filters.is_origin_allowed(origin.ip, origin.referer.as_deref()) => {
+                Err(AuthenticationError::InvalidToken.into())
+            }
             Some(filters) => match data {
                 Some(data) => Ok(Self {
                     data,
-                    filters,
+                    filters: through_alias(filters, index, resolved),
                     _marker: PhantomData,
                 }),
                 None => Err(AuthenticationError::IrretrievableState.into()),
@@ -61,6 +72,37 @@ impl<P, D> GuardedData<P, D> {
         }
     }
 
+    async fn auth_certificate(
+        auth: AuthController,
+        certificate: ClientCertificate,
+        index: Option<String>,
+        meilisearch: Option<MeiliSearch>,
+        data: Option<D>,
+    ) -> Result<Self, ResponseError>
+    where
+        P: Policy + 'static,
+    {
+        let resolved = resolve_alias(meilisearch, index.clone()).await?;
+        let authenticated = resolved.clone();
+        let filters = tokio::task::spawn_blocking(move || {
+            P::authenticate_certificate(auth, &certificate.fingerprint, authenticated.as_deref())
+        })
+        .await
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))?;
+
+        match filters {
+            Some(filters) => match data {
+                Some(data) => Ok(Self {
+                    data,
+                    filters: through_alias(filters, index, resolved),
+                    _marker: PhantomData,
+                }),
+                None => Err(AuthenticationError::IrretrievableState.into()),
+            },
+            None => Err(AuthenticationError::InvalidToken.into()),
+        }
+    }
+
     async fn authenticate(
         auth: AuthController,
         token: String,
@@ -107,6 +149,8 @@ impl<P: Policy + 'static, D: 'static + Clone> FromRequest for GuardedData<P, D>
                                 auth,
                                 token.to_string(),
                                 index.map(String::from),
+                                RequestOrigin::from_request(req),
+                                req.app_data::<MeiliSearch>().cloned(),
                                 req.app_data::<D>().cloned(),
                             )),
                             None => Box::pin(err(AuthenticationError::InvalidToken.into())),
@@ -116,15 +160,147 @@ impl<P: Policy + 'static, D: 'static + Clone> FromRequest for GuardedData<P, D>
                         Box::pin(err(AuthenticationError::MissingAuthorizationHeader.into()))
                     }
                 },
-                None => Box::pin(Self::auth_token(auth, req.app_data::<D>().cloned())),
+                // Without a key, the client certificate of the connection is authenticated.
+                None => match req.conn_data::<ClientCertificate>() {
+                    Some(certificate) if auth.get_master_key().is_some() => {
+                        Box::pin(Self::auth_certificate(
+                            auth,
+                            certificate.clone(),
+                            req.match_info().get("index_uid").map(String::from),
+                            req.app_data::<MeiliSearch>().cloned(),
+                            req.app_data::<D>().cloned(),
+                        ))
+                    }
+                    _ => Box::pin(Self::auth_token(auth, req.app_data::<D>().cloned())),
+                },
             },
             None => Box::pin(err(AuthenticationError::IrretrievableState.into())),
         }
     }
 }
 
+/// Returns the uid of the index the `index` of the route points to when it is an alias, the keys
+/// are authorized on the index actually reached.
+async fn resolve_alias(
+    meilisearch: Option<MeiliSearch>,
+    index: Option<String>,
+) -> Result<Option<String>, ResponseError> {
+    match (meilisearch, index) {
+        (Some(meilisearch), Some(index)) => Ok(Some(meilisearch.resolve_alias(index).await?)),
+        (_, index) => Ok(index),
+    }
+}
+
+/// Gives the alias of the route the search rules of the `resolved` index it points to, which
+/// the routes look up by the uid of their path.
+fn through_alias(
+    mut filters: AuthFilter,
+    alias: Option<String>,
+    resolved: Option<String>,
+) -> AuthFilter {
+    if let (Some(alias), Some(index)) = (alias, resolved) {
+        if alias != index {
+            filters.search_rules = filters.search_rules.alias(&alias, &index);
+        }
+    }
+    filters
+}
+
 pub trait Policy {
     fn authenticate(auth: AuthController, token: &str, index: Option<&str>) -> Option<AuthFilter>;
+
+    /// Authenticates the client certificate with the `fingerprint`, none is accepted by default.
+    fn authenticate_certificate(
+        _auth: AuthController,
+        _fingerprint: &str,
+        _index: Option<&str>,
+    ) -> Option<AuthFilter> {
+        None
+    }
+}
+
+/// Gives access to the data of the routes whose required actions depend on their payload, like
+/// the batch route. Nothing is checked at extraction, the route must call `authorize` for each
+/// action and index it targets before doing anything.
+pub struct ActionGuardedData<D> {
+    data: D,
+    auth: AuthController,
+    token: Option<String>,
+    certificate: Option<ClientCertificate>,
+    origin: RequestOrigin,
+}
+
+impl<D> ActionGuardedData<D> {
+    /// Returns the filters of the key if it is authorized to perform `action` on `index`, or on
+    /// any index if `index` is `None`.
+    pub fn authorize(
+        &self,
+        action: Action,
+        index: Option<&str>,
+    ) -> Result<AuthFilter, ResponseError> {
+        if let (None, Some(certificate)) = (&self.token, &self.certificate) {
+            if self.auth.get_master_key().is_some() && !policies::is_keys_action(action.repr()) {
+                return self
+                    .auth
+                    .authenticate_certificate(&certificate.fingerprint, action, index)
+                    .ok_or_else(|| AuthenticationError::InvalidToken.into());
+            }
+        }
+
+        let token = self.token.as_deref().unwrap_or_default();
+        match policies::authenticate_action(&self.auth, token, action, index) {
+            Some(filters)
+                if !filters.is_origin_allowed(self.origin.ip, self.origin.referer.as_deref()) =>
+            {
+                Err(AuthenticationError::InvalidToken.into())
+            }
+            Some(filters) => Ok(filters),
+            None if self.token.is_none() => {
+                Err(AuthenticationError::MissingAuthorizationHeader.into())
+            }
+            None => Err(AuthenticationError::InvalidToken.into()),
+        }
+    }
+}
+
+impl<D> Deref for ActionGuardedData<D> {
+    type Target = D;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<D: 'static + Clone> FromRequest for ActionGuardedData<D> {
+    type Error = ResponseError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        _payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let token = req
+            .headers()
+            .get("Authorization")
+            .and_then(|type_token| type_token.to_str().ok())
+            .and_then(|type_token| type_token.strip_prefix("Bearer "))
+            .map(String::from);
+
+        match (
+            req.app_data::<AuthController>().cloned(),
+            req.app_data::<D>().cloned(),
+        ) {
+            (Some(auth), Some(data)) => ok(Self {
+                data,
+                auth,
+                token,
+                certificate: req.conn_data::<ClientCertificate>().cloned(),
+                origin: RequestOrigin::from_request(req),
+            }),
+            _ => err(AuthenticationError::IrretrievableState.into()),
+        }
+    }
 }
 
 pub mod policies {
@@ -147,7 +323,7 @@ pub mod policies {
     }
 
     /// Extracts the key id used to sign the payload, without performing any validation.
-    fn extract_key_id(token: &str) -> Option<Uuid> {
+    pub(crate) fn extract_key_id(token: &str) -> Option<Uuid> {
         let mut validation = tenant_token_validation();
         validation.insecure_disable_signature_validation();
         let dummy_key = DecodingKey::from_secret(b"secret");
@@ -158,9 +334,57 @@ pub mod policies {
         Some(api_key_uid)
     }
 
-    fn is_keys_action(action: u8) -> bool {
+    /// Decodes the tenant token after checking that it is signed by its parent key, whose uid is
+    /// returned along with the claims. The expiration and the rules of the token aren't checked.
+    fn decode_tenant_token(auth: &AuthController, token: &str) -> Option<(Uuid, Claims)> {
+        let uid = extract_key_id(token)?;
+        let key = auth.generate_key(uid)?;
+        let data = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(key.as_bytes()),
+            &tenant_token_validation(),
+        )
+        .ok()?;
+        Some((uid, data.claims))
+    }
+
+    /// Returns the uid of the parent key of the tenant token if the token is signed by it and
+    /// hasn't expired. The signature of a token is only checked once, the token is then cached
+    /// by the [`AuthController`] until it expires.
+    pub(crate) fn verified_key_id(auth: &AuthController, token: &str) -> Option<Uuid> {
+        if let Some(uid) = auth.verified_tenant_token(token) {
+            return Some(uid);
+        }
+
+        let (uid, claims) = decode_tenant_token(auth, token)?;
+        if matches!(claims.exp, Some(exp) if OffsetDateTime::now_utc().unix_timestamp() > exp) {
+            return None;
+        }
+        auth.cache_verified_tenant_token(token, uid, claims.exp);
+        Some(uid)
+    }
+
+    pub(crate) fn is_keys_action(action: u8) -> bool {
         use actions::*;
-        matches!(action, KEYS_GET | KEYS_CREATE | KEYS_UPDATE | KEYS_DELETE)
+        matches!(
+            action,
+            KEYS_GET | KEYS_CREATE | KEYS_UPDATE | KEYS_DELETE | KEYS_ROTATE
+        )
+    }
+
+    /// Only authorizes the master key, or any request when there is no master key.
+    pub struct MasterPolicy;
+
+    impl Policy for MasterPolicy {
+        fn authenticate(
+            auth: AuthController,
+            token: &str,
+            _index: Option<&str>,
+        ) -> Option<AuthFilter> {
+            auth.get_master_key()
+                .map_or(true, |mk| mk == token)
+                .then(AuthFilter::default)
+        }
     }
 
     pub struct ActionPolicy<const A: u8>;
@@ -171,31 +395,62 @@ pub mod policies {
             token: &str,
             index: Option<&str>,
         ) -> Option<AuthFilter> {
-            // authenticate if token is the master key.
-            // master key can only have access to keys routes.
-            // if master key is None only keys routes are inaccessible.
-            if auth
-                .get_master_key()
-                .map_or_else(|| !is_keys_action(A), |mk| mk == token)
-            {
-                return Some(AuthFilter::default());
-            }
-
             // Tenant token
             if let Some(filters) = ActionPolicy::<A>::authenticate_tenant_token(&auth, token, index)
             {
+                Some(filters)
+            } else {
+                let action = Action::from_repr(A)?;
+                authenticate_action(&auth, token, action, index)
+            }
+        }
+
+        fn authenticate_certificate(
+            auth: AuthController,
+            fingerprint: &str,
+            index: Option<&str>,
+        ) -> Option<AuthFilter> {
+            // like the tokens of the identity provider, the certificates can't manage the keys.
+            if is_keys_action(A) {
+                return None;
+            }
+            let action = Action::from_repr(A)?;
+            auth.authenticate_certificate(fingerprint, action, index)
+        }
+    }
+
+    /// Authenticates the master key or an API key for the `action` on the `index`.
+    pub fn authenticate_action(
+        auth: &AuthController,
+        token: &str,
+        action: Action,
+        index: Option<&str>,
+    ) -> Option<AuthFilter> {
+        // authenticate if token is the master key.
+        // master key can only have access to keys routes.
+        // if master key is None only keys routes are inaccessible.
+        if auth
+            .get_master_key()
+            .map_or_else(|| !is_keys_action(action.repr()), |mk| mk == token)
+        {
+            return Some(AuthFilter::default());
+        }
+
+        // Token of the identity provider, which can't manage the API keys.
+        if !is_keys_action(action.repr()) {
+            if let Some(filters) = auth.authenticate_identity(token, action, index) {
                 return Some(filters);
-            } else if let Some(action) = Action::from_repr(A) {
-                // API key
-                if let Ok(Some(uid)) = auth.get_optional_uid_from_encoded_key(token.as_bytes()) {
-                    if let Ok(true) = auth.is_key_authorized(uid, action, index) {
-                        return auth.get_key_filters(uid, None).ok();
-                    }
-                }
             }
+        }
 
-            None
+        // API key
+        if let Ok(Some(uid)) = auth.get_optional_uid_from_encoded_key(token.as_bytes()) {
+            if let Ok(true) = auth.is_key_authorized(uid, action, index) {
+                return auth.get_key_filters(uid, None).ok();
+            }
         }
+
+        None
     }
 
     impl<const A: u8> ActionPolicy<A> {
@@ -213,31 +468,23 @@ pub mod policies {
             // check if parent key is authorized to do the action.
             if auth.is_key_authorized(uid, Action::Search, index).ok()? {
                 // Check if tenant token is valid.
-                let key = auth.generate_key(uid)?;
-                let data = decode::<Claims>(
-                    token,
-                    &DecodingKey::from_secret(key.as_bytes()),
-                    &tenant_token_validation(),
-                )
-                .ok()?;
+                let (_, claims) = decode_tenant_token(auth, token)?;
 
                 // Check index access if an index restriction is provided.
                 if let Some(index) = index {
-                    if !data.claims.search_rules.is_index_authorized(index) {
+                    if !claims.search_rules.is_index_authorized(index) {
                         return None;
                     }
                 }
 
                 // Check if token is expired.
-                if let Some(exp) = data.claims.exp {
+                if let Some(exp) = claims.exp {
                     if OffsetDateTime::now_utc().unix_timestamp() > exp {
                         return None;
                     }
                 }
 
-                return auth
-                    .get_key_filters(uid, Some(data.claims.search_rules))
-                    .ok();
+                return auth.get_key_filters(uid, Some(claims.search_rules)).ok();
             }
 
             None
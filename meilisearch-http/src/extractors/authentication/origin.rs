@@ -0,0 +1,69 @@
+use std::net::IpAddr;
+
+use actix_web::http::header::{ORIGIN, REFERER};
+use actix_web::HttpRequest;
+use ipnet::IpNet;
+
+/// The reverse proxies whose `X-Forwarded-For` header is trusted to find the address of the
+/// clients.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(pub Vec<IpNet>);
+
+impl TrustedProxies {
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Where a request comes from, checked against the restrictions of the API keys.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOrigin {
+    pub ip: Option<IpAddr>,
+    pub referer: Option<String>,
+}
+
+impl RequestOrigin {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        let trusted_proxies = req
+            .app_data::<TrustedProxies>()
+            .cloned()
+            .unwrap_or_default();
+        let referer = req
+            .headers()
+            .get(REFERER)
+            .or_else(|| req.headers().get(ORIGIN))
+            .and_then(|referer| referer.to_str().ok())
+            .map(String::from);
+
+        Self {
+            ip: client_ip(req, &trusted_proxies),
+            referer,
+        }
+    }
+}
+
+/// Returns the address of the client. The `X-Forwarded-For` header is only read when the request
+/// is sent by a trusted proxy: the client is then the last address of the header that isn't a
+/// trusted proxy, since the addresses before it can be forged by the client.
+fn client_ip(req: &HttpRequest, trusted_proxies: &TrustedProxies) -> Option<IpAddr> {
+    let peer = req.peer_addr()?.ip();
+    if !trusted_proxies.is_trusted(&peer) {
+        return Some(peer);
+    }
+
+    let forwarded: Vec<IpAddr> = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(','))
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect();
+
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !trusted_proxies.is_trusted(ip))
+        .or_else(|| forwarded.first())
+        .copied()
+        .or(Some(peer))
+}
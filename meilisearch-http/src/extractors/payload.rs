@@ -1,29 +1,36 @@
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
+use actix_web::dev::Decompress;
 use actix_web::error::PayloadError;
 use actix_web::{dev, web, FromRequest, HttpRequest};
 use futures::future::{ready, Ready};
 use futures::Stream;
 
+use crate::Opt;
+
 pub struct Payload {
-    payload: dev::Payload,
+    payload: Decompress<dev::Payload>,
     limit: usize,
 }
 
+/// The limit of the payloads, read from the options on each request since it can be changed at
+/// runtime.
+#[derive(Default)]
 pub struct PayloadConfig {
-    limit: usize,
+    opt: Option<Opt>,
 }
 
 impl PayloadConfig {
-    pub fn new(limit: usize) -> Self {
-        Self { limit }
+    pub fn new(opt: Opt) -> Self {
+        Self { opt: Some(opt) }
     }
-}
 
-impl Default for PayloadConfig {
-    fn default() -> Self {
-        Self { limit: 256 * 1024 }
+    fn limit(&self) -> usize {
+        match &self.opt {
+            Some(opt) => opt.current_http_payload_size_limit().get_bytes() as usize,
+            None => 256 * 1024,
+        }
     }
 }
 
@@ -36,10 +43,12 @@ impl FromRequest for Payload {
     fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
         let limit = req
             .app_data::<PayloadConfig>()
-            .map(|c| c.limit)
-            .unwrap_or(PayloadConfig::default().limit);
+            .map(|c| c.limit())
+            .unwrap_or_else(|| PayloadConfig::default().limit());
+        // The payload is decompressed according to its `Content-Encoding` header before the
+        // limit is applied, so the limit bounds the size of the decompressed payload.
         ready(Ok(Payload {
-            payload: payload.take(),
+            payload: Decompress::from_headers(payload.take(), req.headers()),
             limit,
         }))
     }
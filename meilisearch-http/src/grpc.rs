@@ -0,0 +1,601 @@
+//! The gRPC API described by `proto/meilisearch.proto`, served on `--grpc-addr` for the
+//! backend-to-backend integrations. It mirrors the search and the documents routes, and the
+//! documents are uploaded as a stream of chunks.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+
+use actix_web::error::PayloadError;
+use actix_web::web::Bytes;
+use actix_web::ResponseError as _;
+use anyhow::Context;
+use futures::Stream;
+use log::error;
+use meilisearch_auth::{AuthController, AuthFilter, RateLimit, RateLimitPermit};
+use meilisearch_lib::document_formats::CsvOptions;
+use meilisearch_lib::index::SearchQuery;
+use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
+use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use meilisearch_lib::tasks::TaskFilter;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::{json, Map, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::policies::{actions, ActionPolicy, Policy};
+use crate::extractors::authentication::AuthenticationError;
+use crate::rate_limit::key_rate_limit;
+use crate::routes::indexes::search::add_search_rules;
+use crate::task::{SummarizedTaskView, TaskView};
+
+pub mod proto {
+    tonic::include_proto!("meilisearch.v1");
+}
+
+use proto::add_documents_header::Format;
+use proto::add_documents_request::Content;
+use proto::meilisearch_server::{Meilisearch as MeilisearchGrpc, MeilisearchServer};
+
+/// The number of documents read at once by `GetDocuments`.
+const DOCUMENTS_PAGE_SIZE: usize = 1000;
+
+/// Binds `addr` and serves the gRPC API on it until the process stops. The requests are counted
+/// against the `global` rate limits like the HTTP requests.
+pub fn serve(
+    addr: &str,
+    meilisearch: MeiliSearch,
+    auth: AuthController,
+    global: RateLimit,
+) -> anyhow::Result<()> {
+    let addr: SocketAddr = addr
+        .to_socket_addrs()?
+        .next()
+        .with_context(|| format!("The gRPC address `{}` can't be resolved.", addr))?;
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let incoming = TcpListenerStream::new(tokio::net::TcpListener::from_std(listener)?);
+
+    let service = MeilisearchServer::new(GrpcService::new(meilisearch, auth, global));
+    tokio::spawn(async move {
+        if let Err(e) = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(incoming)
+            .await
+        {
+            error!("The gRPC server stopped: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+pub struct GrpcService {
+    meilisearch: MeiliSearch,
+    auth: AuthController,
+    global: RateLimit,
+}
+
+impl GrpcService {
+    pub fn new(meilisearch: MeiliSearch, auth: AuthController, global: RateLimit) -> Self {
+        Self {
+            meilisearch,
+            auth,
+            global,
+        }
+    }
+
+    /// Authenticates the `authorization` metadata of a request like the `Authorization` header
+    /// of the HTTP requests, and counts it against the rate limits. A `search` is counted until
+    /// the returned permit is dropped.
+    async fn authorize<P: Policy + 'static>(
+        &self,
+        metadata: &MetadataMap,
+        remote_addr: Option<SocketAddr>,
+        index: Option<&str>,
+        search: bool,
+    ) -> Result<(AuthFilter, RateLimitPermit), Status> {
+        let token = metadata
+            .get("authorization")
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .ok_or_else(|| status(AuthenticationError::InvalidToken))
+            })
+            .transpose()?
+            .map(String::from);
+
+        let key = token
+            .as_deref()
+            .and_then(|token| key_rate_limit(&self.auth, token));
+        let permit = self
+            .auth
+            .rate_limiter()
+            .acquire(self.global, key, search)
+            .map_err(|retry_after| {
+                // rounded up like the `Retry-After` header of the HTTP responses.
+                let seconds = retry_after.as_secs() + (retry_after.subsec_nanos() > 0) as u64;
+                let mut error = status(MeilisearchHttpError::TooManyRequests(seconds));
+                error.metadata_mut().insert("retry-after", seconds.into());
+                error
+            })?;
+
+        let auth = self.auth.clone();
+        let authenticated = token.clone();
+        let index = index.map(String::from);
+        let filters = tokio::task::spawn_blocking(move || {
+            P::authenticate(
+                auth,
+                authenticated.as_deref().unwrap_or_default(),
+                index.as_deref(),
+            )
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        match filters {
+            Some(filters) if !filters.is_origin_allowed(remote_addr.map(|a| a.ip()), None) => {
+                Err(status(AuthenticationError::InvalidToken))
+            }
+            Some(filters) => Ok((filters, permit)),
+            None if token.is_none() => Err(status(AuthenticationError::MissingAuthorizationHeader)),
+            None => Err(status(AuthenticationError::InvalidToken)),
+        }
+    }
+
+    fn check_not_read_only(&self) -> Result<(), Status> {
+        if self.meilisearch.is_read_only() {
+            return Err(status(MeilisearchHttpError::ReadOnlyMode));
+        }
+        if let Some(primary) = self.meilisearch.replica_of() {
+            return Err(status(MeilisearchHttpError::ReplicaMode(
+                primary.to_string(),
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl MeilisearchGrpc for GrpcService {
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> Result<Response<proto::SearchResponse>, Status> {
+        let index_uid = request.get_ref().index_uid.clone();
+        // the permit is held until the response is built, the search is counted meanwhile.
+        let (filters, _permit) = self
+            .authorize::<ActionPolicy<{ actions::SEARCH }>>(
+                request.metadata(),
+                request.remote_addr(),
+                Some(&index_uid),
+                true,
+            )
+            .await?;
+
+        let mut query = search_query(request.into_inner())?;
+        if let Some(search_rules) = filters.search_rules.get_index_search_rules(&index_uid) {
+            add_search_rules(&mut query, search_rules).map_err(status)?;
+        }
+
+        let result = self
+            .meilisearch
+            .search(index_uid, query)
+            .await
+            .map_err(status)?;
+        let result = json!(result);
+
+        Ok(Response::new(proto::SearchResponse {
+            hits: result["hits"]
+                .as_array()
+                .map(|hits| hits.iter().map(Value::to_string).collect())
+                .unwrap_or_default(),
+            query: result["query"].as_str().unwrap_or_default().to_string(),
+            processing_time_ms: result["processingTimeMs"].as_u64().unwrap_or_default(),
+            estimated_total_hits: result["estimatedTotalHits"].as_u64(),
+            total_hits: result["totalHits"].as_u64(),
+            total_pages: result["totalPages"].as_u64(),
+            facet_distribution: result.get("facetDistribution").map(Value::to_string),
+            facet_stats: result.get("facetStats").map(Value::to_string),
+        }))
+    }
+
+    async fn get_document(
+        &self,
+        request: Request<proto::GetDocumentRequest>,
+    ) -> Result<Response<proto::Document>, Status> {
+        self.authorize::<ActionPolicy<{ actions::DOCUMENTS_GET }>>(
+            request.metadata(),
+            request.remote_addr(),
+            Some(&request.get_ref().index_uid),
+            false,
+        )
+        .await?;
+
+        let proto::GetDocumentRequest {
+            index_uid,
+            document_id,
+            fields,
+        } = request.into_inner();
+        let document = self
+            .meilisearch
+            .document(index_uid, document_id, non_empty(fields))
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::Document {
+            json: Value::Object(document).to_string(),
+        }))
+    }
+
+    type GetDocumentsStream = Pin<Box<dyn Stream<Item = Result<proto::Document, Status>> + Send>>;
+
+    async fn get_documents(
+        &self,
+        request: Request<proto::GetDocumentsRequest>,
+    ) -> Result<Response<Self::GetDocumentsStream>, Status> {
+        self.authorize::<ActionPolicy<{ actions::DOCUMENTS_GET }>>(
+            request.metadata(),
+            request.remote_addr(),
+            Some(&request.get_ref().index_uid),
+            false,
+        )
+        .await?;
+
+        let proto::GetDocumentsRequest {
+            index_uid,
+            offset,
+            limit,
+            fields,
+            filter,
+        } = request.into_inner();
+        let fields = non_empty(fields);
+        let filter = json_filter(filter)?;
+
+        let meilisearch = self.meilisearch.clone();
+        let documents = async_stream::try_stream! {
+            let mut offset = offset as usize;
+            let mut remaining = limit.map_or(usize::MAX, |limit| limit as usize);
+            while remaining > 0 {
+                let page = remaining.min(DOCUMENTS_PAGE_SIZE);
+                let (_, documents) = meilisearch
+                    .documents(index_uid.clone(), offset, page, fields.clone(), filter.clone(), None)
+                    .await
+                    .map_err(status)?;
+
+                let count = documents.len();
+                for document in documents {
+                    yield proto::Document { json: Value::Object(document).to_string() };
+                }
+                if count < page {
+                    break;
+                }
+                offset += count;
+                remaining -= count;
+            }
+        };
+
+        Ok(Response::new(
+            Box::pin(documents) as Self::GetDocumentsStream
+        ))
+    }
+
+    async fn add_documents(
+        &self,
+        request: Request<Streaming<proto::AddDocumentsRequest>>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let metadata = request.metadata().clone();
+        let remote_addr = request.remote_addr();
+        let mut messages = request.into_inner();
+
+        let header = match messages.message().await? {
+            Some(proto::AddDocumentsRequest {
+                content: Some(Content::Header(header)),
+            }) => header,
+            _ => {
+                return Err(Status::invalid_argument(
+                    "The first message must be the header of the documents.",
+                ))
+            }
+        };
+        let (filters, _) = self
+            .authorize::<ActionPolicy<{ actions::DOCUMENTS_ADD }>>(
+                &metadata,
+                remote_addr,
+                Some(&header.index_uid),
+                false,
+            )
+            .await?;
+        self.check_not_read_only()?;
+
+        let format = match header.format() {
+            Format::Json => DocumentAdditionFormat::Json,
+            Format::Ndjson => DocumentAdditionFormat::Ndjson,
+            Format::Csv => {
+                let mut options = CsvOptions::default();
+                if let Some(delimiter) = &header.csv_delimiter {
+                    options.delimiter = csv_delimiter(delimiter)?;
+                }
+                DocumentAdditionFormat::Csv(options)
+            }
+        };
+        let method = if header.replace {
+            IndexDocumentsMethod::ReplaceDocuments
+        } else {
+            IndexDocumentsMethod::UpdateDocuments
+        };
+
+        // The chunks are forwarded as they are received, the update file store reads them as
+        // the payload of an HTTP request.
+        let (sender, receiver) = mpsc::channel(1);
+        tokio::spawn(async move {
+            loop {
+                let chunk = match messages.message().await {
+                    Ok(Some(proto::AddDocumentsRequest {
+                        content: Some(Content::Chunk(chunk)),
+                    })) => Ok(Bytes::from(chunk)),
+                    Ok(Some(_)) => Err(PayloadError::Io(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "only the first message can be a header",
+                    ))),
+                    Ok(None) => break,
+                    Err(status) => Err(PayloadError::Io(io::Error::new(
+                        io::ErrorKind::Other,
+                        status.message().to_string(),
+                    ))),
+                };
+                let failed = chunk.is_err();
+                if sender.send(chunk).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        let update = Update::DocumentAddition {
+            payload: Box::new(ReceiverStream::new(receiver)),
+            primary_key: header.primary_key,
+            method,
+            merge_patch: false,
+            format,
+            allow_index_creation: filters.allow_index_creation,
+        };
+        let task: SummarizedTaskView = self
+            .meilisearch
+            .register_update(header.index_uid, update)
+            .await
+            .map_err(status)?
+            .into();
+
+        Ok(Response::new(task_message(json!(task))))
+    }
+
+    async fn delete_documents(
+        &self,
+        request: Request<proto::DeleteDocumentsRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        self.authorize::<ActionPolicy<{ actions::DOCUMENTS_DELETE }>>(
+            request.metadata(),
+            request.remote_addr(),
+            Some(&request.get_ref().index_uid),
+            false,
+        )
+        .await?;
+        self.check_not_read_only()?;
+
+        let proto::DeleteDocumentsRequest {
+            index_uid,
+            document_ids,
+        } = request.into_inner();
+        let update = Update::DeleteDocuments(document_ids);
+        let task: SummarizedTaskView = self
+            .meilisearch
+            .register_update(index_uid, update)
+            .await
+            .map_err(status)?
+            .into();
+
+        Ok(Response::new(task_message(json!(task))))
+    }
+
+    async fn get_task(
+        &self,
+        request: Request<proto::GetTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let (filters, _) = self
+            .authorize::<ActionPolicy<{ actions::TASKS_GET }>>(
+                request.metadata(),
+                request.remote_addr(),
+                None,
+                false,
+            )
+            .await?;
+
+        let search_rules = &filters.search_rules;
+        let filters = if search_rules.is_index_authorized("*") {
+            None
+        } else {
+            let mut filters = TaskFilter::default();
+            for (index, _policy) in search_rules.clone() {
+                filters.filter_index(index);
+            }
+            Some(filters)
+        };
+
+        let task: TaskView = self
+            .meilisearch
+            .get_task(request.into_inner().task_uid, filters)
+            .await
+            .map_err(status)?
+            .into();
+
+        Ok(Response::new(task_message(json!(task))))
+    }
+}
+
+/// Turns an error of Meilisearch into a gRPC status, with the error code in the
+/// `meilisearch-error-code` metadata.
+fn status(error: impl Into<ResponseError>) -> Status {
+    let error: ResponseError = error.into();
+    let code = match error.status_code().as_u16() {
+        400 | 413 | 415 => tonic::Code::InvalidArgument,
+        401 => tonic::Code::Unauthenticated,
+        403 => tonic::Code::PermissionDenied,
+        404 => tonic::Code::NotFound,
+        409 => tonic::Code::AlreadyExists,
+        429 => tonic::Code::ResourceExhausted,
+        503 => tonic::Code::Unavailable,
+        _ => tonic::Code::Internal,
+    };
+
+    let mut status = Status::new(code, error.to_string());
+    if let Some(Ok(value)) = json!(error)["code"].as_str().map(str::parse) {
+        status
+            .metadata_mut()
+            .insert("meilisearch-error-code", value);
+    }
+    status
+}
+
+fn search_query(request: proto::SearchRequest) -> Result<SearchQuery, Status> {
+    let mut query = Map::new();
+    let mut set = |name: &str, value: Value| {
+        if !value.is_null() {
+            query.insert(name.to_string(), value);
+        }
+    };
+
+    set("q", json!(request.q));
+    set("offset", json!(request.offset));
+    set("limit", json!(request.limit));
+    set("page", json!(request.page));
+    set("hitsPerPage", json!(request.hits_per_page));
+    set("filter", json!(json_filter(request.filter)?));
+    set("sort", json!(non_empty(request.sort)));
+    set("facets", json!(non_empty(request.facets)));
+    set(
+        "attributesToRetrieve",
+        json!(non_empty(request.attributes_to_retrieve)),
+    );
+    set(
+        "attributesToHighlight",
+        json!(non_empty(request.attributes_to_highlight)),
+    );
+    set(
+        "attributesToCrop",
+        json!(non_empty(request.attributes_to_crop)),
+    );
+    set("cropLength", json!(request.crop_length));
+    set("showMatchesPosition", json!(request.show_matches_position));
+    set("matchingStrategy", json!(request.matching_strategy));
+    set(
+        "attributesToSearchOn",
+        json!(non_empty(request.attributes_to_search_on)),
+    );
+    set("distinct", json!(request.distinct));
+
+    serde_json::from_value(Value::Object(query))
+        .map_err(|e| Status::invalid_argument(format!("The search request is invalid: {}.", e)))
+}
+
+/// Parses a filter given in JSON.
+fn json_filter(filter: Option<String>) -> Result<Option<Value>, Status> {
+    filter
+        .map(|filter| serde_json::from_str(&filter))
+        .transpose()
+        .map_err(|e| Status::invalid_argument(format!("The filter is not valid JSON: {}.", e)))
+}
+
+fn csv_delimiter(delimiter: &str) -> Result<u8, Status> {
+    let mut chars = delimiter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii() => Ok(c as u8),
+        (Some(c), _) => Err(status(MeilisearchHttpError::InvalidCsvParameter(
+            "csvDelimiter",
+            c,
+        ))),
+        (None, _) => Err(Status::invalid_argument(
+            "The CSV delimiter can't be empty.",
+        )),
+    }
+}
+
+/// The repeated fields are empty when missing.
+fn non_empty(values: Vec<String>) -> Option<Vec<String>> {
+    (!values.is_empty()).then(|| values)
+}
+
+/// Returns the message of a task serialized like the HTTP API does.
+fn task_message(task: Value) -> proto::Task {
+    let string = |name: &str| task[name].as_str().map(String::from);
+    let json = |name: &str| {
+        task.get(name)
+            .filter(|v| !v.is_null())
+            .map(Value::to_string)
+    };
+
+    proto::Task {
+        task_uid: task["taskUid"]
+            .as_u64()
+            .or_else(|| task["uid"].as_u64())
+            .unwrap_or_default() as u32,
+        index_uid: string("indexUid"),
+        status: string("status").unwrap_or_default(),
+        r#type: string("type").unwrap_or_default(),
+        enqueued_at: string("enqueuedAt").unwrap_or_default(),
+        started_at: string("startedAt"),
+        finished_at: string("finishedAt"),
+        details: json("details"),
+        error: json("error"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn convert_a_search_request() {
+        let request = proto::SearchRequest {
+            index_uid: "movies".to_string(),
+            q: Some("carol".to_string()),
+            limit: Some(5),
+            filter: Some(r#"["genre = drama"]"#.to_string()),
+            sort: vec!["year:desc".to_string()],
+            ..Default::default()
+        };
+        let query = search_query(request).unwrap();
+        assert_eq!(query.q.as_deref(), Some("carol"));
+        assert_eq!(query.limit, 5);
+        assert_eq!(query.filter, Some(json!(["genre = drama"])));
+        assert_eq!(query.sort, Some(vec!["year:desc".to_string()]));
+        assert_eq!(query.facets, None);
+
+        let request = proto::SearchRequest {
+            filter: Some("genre = drama".to_string()),
+            ..Default::default()
+        };
+        let error = search_query(request).unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn convert_a_task() {
+        let task = task_message(json!({
+            "taskUid": 4,
+            "indexUid": "movies",
+            "status": "enqueued",
+            "type": "documentAdditionOrUpdate",
+            "enqueuedAt": "2022-06-01T00:00:00Z",
+        }));
+        assert_eq!(task.task_uid, 4);
+        assert_eq!(task.index_uid.as_deref(), Some("movies"));
+        assert_eq!(task.r#type, "documentAdditionOrUpdate");
+        assert_eq!(task.error, None);
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use jsonwebtoken::jwk::JwkSet;
+use log::{error, info};
+use meilisearch_auth::IdentityProvider;
+use reqwest::Client;
+use tokio::time::sleep;
+
+/// Fetches the JSON Web Key Set of the identity `provider` from `url` every `interval`, so the
+/// rotations of its keys are followed. When a fetch fails the previous keys are kept.
+pub async fn refresh_jwks(provider: Arc<IdentityProvider>, url: String, interval: Duration) {
+    let client = Client::new();
+
+    loop {
+        match fetch_jwks(&client, &url).await {
+            Ok(jwks) => {
+                info!(
+                    "Fetched {} keys of the identity provider from {}.",
+                    jwks.keys.len(),
+                    url
+                );
+                provider.set_jwks(jwks);
+            }
+            Err(e) => error!(
+                "Could not fetch the keys of the identity provider from {}: {}",
+                url, e
+            ),
+        }
+
+        sleep(interval).await;
+    }
+}
+
+async fn fetch_jwks(client: &Client, url: &str) -> anyhow::Result<JwkSet> {
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())?
+        .bytes()
+        .await?;
+    Ok(serde_json::from_slice(&body)?)
+}
@@ -2,12 +2,29 @@
 #[macro_use]
 pub mod error;
 pub mod analytics;
+pub mod compression;
+pub mod cors;
+pub mod etag;
+pub mod event_sink;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod task;
 #[macro_use]
 pub mod extractors;
 pub mod helpers;
+pub mod identity_provider;
+pub mod logging;
+pub mod maintenance;
+pub mod metrics;
 pub mod option;
+pub mod rate_limit;
+pub mod request_id;
 pub mod routes;
+pub mod runtime_config;
+pub mod shutdown;
+pub mod task_priority;
+pub mod telemetry;
+pub mod webhook;
 
 use std::sync::{atomic::AtomicBool, Arc};
 use std::time::Duration;
@@ -21,9 +38,14 @@ pub use option::Opt;
 
 use actix_web::{web, HttpRequest};
 
+use extractors::authentication::TrustedProxies;
 use extractors::payload::PayloadConfig;
-use meilisearch_auth::AuthController;
-use meilisearch_lib::MeiliSearch;
+use meilisearch_auth::{AuthController, CertificateIdentity, ClientCertificates, IdentityProvider};
+use meilisearch_lib::index_controller::ReplicaConfig;
+use meilisearch_lib::tasks::TaskWebhook;
+use meilisearch_lib::{MeiliSearch, SnapshotRetention};
+use metrics::MetricsConfig;
+use routes::health::HealthConfig;
 
 pub static AUTOBATCHING_ENABLED: AtomicBool = AtomicBool::new(false);
 
@@ -47,7 +69,8 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         // dump
         .set_ignore_missing_dump(opt.ignore_missing_dump)
         .set_ignore_dump_if_db_exists(opt.ignore_dump_if_db_exists)
-        .set_dump_dst(opt.dumps_dir.clone());
+        .set_dump_dst(opt.dumps_dir.clone())
+        .set_dump_import_allowed_hosts(opt.dump_import_allowed_hosts.clone());
 
     if let Some(ref path) = opt.import_snapshot {
         meilisearch.set_import_snapshot(path.clone());
@@ -55,17 +78,108 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
 
     if let Some(ref path) = opt.import_dump {
         meilisearch.set_dump_src(path.clone());
+        meilisearch.set_incremental_dumps(opt.import_incremental_dump.clone());
     }
 
     if opt.schedule_snapshot {
         meilisearch.set_schedule_snapshot();
     }
 
-    meilisearch.build(
+    if let Some(ref schedule) = opt.snapshot_schedule {
+        meilisearch.set_snapshot_schedule(schedule.clone());
+    }
+
+    meilisearch.set_snapshot_retention(SnapshotRetention {
+        keep_last: opt.snapshot_retention_count,
+        keep_days: opt.snapshot_retention_days,
+    });
+
+    if let Some(search_cutoff_ms) = opt.search_cutoff_ms {
+        meilisearch.set_search_cutoff(Duration::from_millis(search_cutoff_ms));
+    }
+
+    if let Some(threshold_ms) = opt.slow_query_threshold_ms {
+        meilisearch.set_slow_query_threshold(Duration::from_millis(threshold_ms));
+    }
+
+    if let Some(size) = opt.search_cache_size {
+        meilisearch.set_search_cache_size(size);
+    }
+
+    meilisearch.set_search_analytics(opt.experimental_enable_search_analytics);
+
+    if let Some(ref url) = opt.task_webhook_url {
+        meilisearch.set_webhook(TaskWebhook {
+            url: url.clone(),
+            authorization: opt.task_webhook_authorization_header.clone(),
+        });
+    }
+
+    if let Some(storage) = opt.object_storage_options.storage()? {
+        meilisearch.set_object_storage(storage);
+    }
+
+    if let Some(retention_sec) = opt.replication_log_retention_sec {
+        meilisearch.set_replication_log_retention(Duration::from_secs(retention_sec));
+    }
+
+    if let Some(ref url) = opt.replica_of {
+        meilisearch.set_replica(ReplicaConfig {
+            primary_url: url.clone(),
+            api_key: opt.replica_api_key.clone(),
+        });
+    }
+
+    let meilisearch = meilisearch.build(
         opt.db_path.clone(),
         opt.indexer_options.clone(),
         opt.scheduler_options.clone(),
-    )
+    )?;
+
+    // the webhook can be set at runtime, so its sender always runs.
+    tokio::task::spawn_local(webhook::run(meilisearch.clone()));
+
+    if let Some(sink) = event_sink::EventSink::from_opt(opt)? {
+        tokio::task::spawn_local(event_sink::run(meilisearch.clone(), sink));
+    }
+
+    // the metrics can be enabled at runtime, so the task durations are always recorded.
+    tokio::task::spawn_local(metrics::record_task_durations(meilisearch.clone()));
+
+    Ok(meilisearch)
+}
+
+/// Makes `auth` accept the tokens of the identity provider configured in `opt`, if any, and
+/// starts fetching its keys.
+pub fn setup_identity_provider(opt: &Opt, auth: &mut AuthController) {
+    if let (Some(url), Some(issuer)) = (&opt.oidc_jwks_url, &opt.oidc_issuer) {
+        let provider = Arc::new(IdentityProvider::new(
+            issuer.clone(),
+            opt.oidc_audience.clone(),
+            opt.oidc_actions_claim.clone(),
+            opt.oidc_indexes_claim.clone(),
+        ));
+        auth.set_identity_provider(provider.clone());
+
+        tokio::task::spawn_local(identity_provider::refresh_jwks(
+            provider,
+            url.clone(),
+            Duration::from_secs(opt.oidc_jwks_refresh_interval_sec),
+        ));
+    }
+}
+
+/// Accepts the client certificates of the `--ssl-client-identities-path` in addition to the API
+/// keys.
+pub fn setup_client_certificates(opt: &Opt, auth: &mut AuthController) -> anyhow::Result<()> {
+    if let Some(ref path) = opt.ssl_client_identities_path {
+        let content = std::fs::read(path)?;
+        let identities: Vec<CertificateIdentity> = serde_json::from_slice(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid client identities file {:?}: {}", path, e))?;
+        auth.set_client_certificates(Arc::new(ClientCertificates::new(identities)));
+    }
+
+    Ok(())
 }
 
 pub fn configure_data(
@@ -75,10 +189,12 @@ pub fn configure_data(
     opt: &Opt,
     analytics: Arc<dyn Analytics>,
 ) {
-    let http_payload_size_limit = opt.http_payload_size_limit.get_bytes() as usize;
     config
         .app_data(data)
         .app_data(auth)
+        .app_data(TrustedProxies(opt.trusted_proxies.clone()))
+        .app_data(web::Data::new(MetricsConfig::new(opt)))
+        .app_data(web::Data::new(HealthConfig::new(opt)))
         .app_data(web::Data::from(analytics))
         .app_data(
             web::JsonConfig::default()
@@ -98,7 +214,8 @@ pub fn configure_data(
                     err => PayloadError::from(err).into(),
                 }),
         )
-        .app_data(PayloadConfig::new(http_payload_size_limit))
+        .app_data(PayloadConfig::new(opt.clone()))
+        .app_data(web::Data::new(opt.clone()))
         .app_data(
             web::QueryConfig::default().error_handler(|err, _req| PayloadError::from(err).into()),
         );
@@ -144,7 +261,6 @@ pub fn dashboard(config: &mut web::ServiceConfig, _enable_frontend: bool) {
 #[macro_export]
 macro_rules! create_app {
     ($data:expr, $auth:expr, $enable_frontend:expr, $opt:expr, $analytics:expr) => {{
-        use actix_cors::Cors;
         use actix_web::middleware::TrailingSlash;
         use actix_web::App;
         use actix_web::{middleware, web};
@@ -157,16 +273,27 @@ macro_rules! create_app {
             .configure(|s| configure_data(s, $data.clone(), $auth.clone(), &$opt, $analytics))
             .configure(routes::configure)
             .configure(|s| dashboard(s, $enable_frontend))
-            .wrap(
-                Cors::default()
-                    .send_wildcard()
-                    .allow_any_header()
-                    .allow_any_origin()
-                    .allow_any_method()
-                    .max_age(86_400), // 24h
-            )
-            .wrap(middleware::Logger::default())
+            .wrap(meilisearch_http::maintenance::ReadOnlyMode::new(
+                $data.clone(),
+            ))
+            .wrap(meilisearch_http::task_priority::TaskPrioritization)
+            .wrap(meilisearch_http::rate_limit::RateLimiting::new(
+                $auth.clone(),
+                &$opt,
+            ))
+            .wrap(meilisearch_http::cors::cors_policy(&$opt))
+            .wrap(middleware::Logger::new(
+                meilisearch_http::request_id::LOGGER_FORMAT,
+            ))
+            .wrap(meilisearch_http::logging::JsonRequestLogger::new(&$opt))
+            .wrap(meilisearch_http::metrics::RequestMetrics::new(
+                $data.clone(),
+                &$opt,
+            ))
+            .wrap(meilisearch_http::telemetry::RequestTracing)
+            .wrap(meilisearch_http::compression::CompressionPolicy::new(&$opt))
             .wrap(middleware::Compress::default())
+            .wrap(meilisearch_http::request_id::RequestIdentifier)
             .wrap(middleware::NormalizePath::new(
                 middleware::TrailingSlash::Trim,
             ))
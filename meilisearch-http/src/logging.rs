@@ -0,0 +1,269 @@
+//! The formats of the logs. The human format is the default format of `env_logger`, the
+//! structured JSON logs are enabled with `--log-format json`.
+//!
+//! Each JSON record is written as an object on its own line. The requests are logged by
+//! [`JsonRequestLogger`] instead of the actix logger, with their fields as separate keys.
+//!
+//! Both formats include the id of the request the record was written for, if any.
+//!
+//! The log level can be changed at runtime, the logger is then rebuilt with the new filters.
+
+use std::future::{ready, Ready};
+use std::io::Write;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Instant;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use env_logger::fmt::Formatter;
+use futures::future::LocalBoxFuture;
+use log::{LevelFilter, Log, Metadata, Record};
+use meilisearch_auth::AuthController;
+use meilisearch_lib::request_id;
+use once_cell::sync::OnceCell;
+use serde_json::{json, Map, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::Opt;
+
+/// The target of the request logs, whose message is a JSON object of their fields.
+const REQUEST_TARGET: &str = "meilisearch_http::request";
+
+static LOGGER: OnceCell<ReloadableLogger> = OnceCell::new();
+
+pub fn is_json(opt: &Opt) -> bool {
+    opt.log_format == "json"
+}
+
+/// Installs the logger of the `log_level` and `log_format` options.
+pub fn init(opt: &Opt) -> anyhow::Result<()> {
+    let logger = LOGGER.get_or_init(|| ReloadableLogger {
+        json: is_json(opt),
+        inner: RwLock::new((
+            opt.log_level.clone(),
+            build_logger(&opt.log_level, is_json(opt)),
+        )),
+    });
+    log::set_logger(logger)?;
+    log::set_max_level(logger.max_level());
+
+    Ok(())
+}
+
+/// Returns the log level of the installed logger, `None` if no logger is installed.
+pub fn log_level() -> Option<String> {
+    LOGGER
+        .get()
+        .map(|logger| logger.inner.read().unwrap().0.clone())
+}
+
+/// Replaces the filters of the installed logger, if any, by `log_level`.
+pub fn set_log_level(log_level: &str) {
+    if let Some(logger) = LOGGER.get() {
+        let inner = build_logger(log_level, logger.json);
+        *logger.inner.write().unwrap() = (log_level.to_string(), inner);
+        log::set_max_level(logger.max_level());
+    }
+}
+
+/// Returns whether `log_level` is made of comma separated `level` or `module=level` filters,
+/// as accepted by `env_logger`.
+pub fn is_valid_log_level(log_level: &str) -> bool {
+    log_level.split(',').all(|directive| {
+        let level = directive.rsplit('=').next().unwrap_or(directive);
+        LevelFilter::from_str(level.trim()).is_ok()
+    })
+}
+
+fn build_logger(log_level: &str, json: bool) -> env_logger::Logger {
+    let mut log_builder = env_logger::Builder::new();
+    log_builder.parse_filters(log_level);
+    if log_level == "info" {
+        // if we are in info we only allow the warn log_level for milli
+        log_builder.filter_module("milli", LevelFilter::Warn);
+    }
+
+    if json {
+        // the requests are logged with their fields by the JSON request logger instead.
+        log_builder.filter_module("actix_web::middleware::logger", LevelFilter::Off);
+        log_builder.format(format_json);
+    } else {
+        log_builder.format(format_human);
+    }
+
+    log_builder.build()
+}
+
+/// The logger of `env_logger`, along with the filters it was built with, behind a lock so it
+/// can be replaced.
+struct ReloadableLogger {
+    json: bool,
+    inner: RwLock<(String, env_logger::Logger)>,
+}
+
+impl ReloadableLogger {
+    fn max_level(&self) -> LevelFilter {
+        self.inner.read().unwrap().1.filter()
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.read().unwrap().1.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.inner.read().unwrap().1.log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.read().unwrap().1.flush()
+    }
+}
+
+/// Writes `record` like the default format of `env_logger`, with the id of the current request
+/// after the target.
+pub fn format_human(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let level = buf.default_styled_level(record.level());
+    match request_id::current() {
+        Some(id) => writeln!(
+            buf,
+            "[{} {:<5} {} {}] {}",
+            buf.timestamp(),
+            level,
+            record.target(),
+            id,
+            record.args()
+        ),
+        None => writeln!(
+            buf,
+            "[{} {:<5} {}] {}",
+            buf.timestamp(),
+            level,
+            record.target(),
+            record.args()
+        ),
+    }
+}
+
+/// Writes `record` as a line of JSON, for the `format` of `env_logger`.
+pub fn format_json(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = Map::new();
+    line.insert(
+        "timestamp".to_string(),
+        json!(OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default()),
+    );
+    line.insert("level".to_string(), json!(record.level().as_str()));
+    line.insert("target".to_string(), json!(record.target()));
+    if let Some(id) = request_id::current() {
+        line.insert("requestId".to_string(), json!(id));
+    }
+
+    let message = record.args().to_string();
+    match serde_json::from_str(&message) {
+        Ok(Value::Object(fields)) if record.target() == REQUEST_TARGET => line.extend(fields),
+        _ => {
+            line.insert("message".to_string(), json!(message));
+        }
+    }
+
+    serde_json::to_writer(&mut *buf, &line)?;
+    writeln!(buf)
+}
+
+/// Logs each request with its method, path, status code, latency, and the uids of its index
+/// and API key, when the logs are in JSON.
+pub struct JsonRequestLogger {
+    enabled: bool,
+}
+
+impl JsonRequestLogger {
+    pub fn new(opt: &Opt) -> Self {
+        Self {
+            enabled: is_json(opt),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for JsonRequestLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = JsonRequestLoggerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(JsonRequestLoggerMiddleware {
+            service: Rc::new(service),
+            enabled: self.enabled,
+        }))
+    }
+}
+
+pub struct JsonRequestLoggerMiddleware<S> {
+    service: Rc<S>,
+    enabled: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for JsonRequestLoggerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        if !self.enabled {
+            return Box::pin(service.call(req));
+        }
+
+        let start = Instant::now();
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let api_key_uid = api_key_uid(&req);
+
+        Box::pin(async move {
+            let response = service.call(req).await?;
+            let index_uid = response.request().match_info().get("index_uid");
+            let fields = json!({
+                "message": format!("{} {} {}", method, path, response.status().as_u16()),
+                "method": method,
+                "path": path,
+                "status": response.status().as_u16(),
+                "latencyMs": start.elapsed().as_secs_f64() * 1000.0,
+                "apiKeyUid": api_key_uid,
+                "indexUid": index_uid,
+            });
+            log::info!(target: REQUEST_TARGET, "{}", fields);
+            Ok(response)
+        })
+    }
+}
+
+/// Returns the uid of the API key of `req`, the master key and the tenant tokens having none.
+fn api_key_uid(req: &ServiceRequest) -> Option<String> {
+    let auth = req.app_data::<AuthController>()?;
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|type_token| type_token.to_str().ok())
+        .and_then(|type_token| type_token.strip_prefix("Bearer "))?;
+    let uid = auth
+        .get_optional_uid_from_encoded_key(token.as_bytes())
+        .ok()??;
+    Some(uid.to_string())
+}
@@ -1,5 +1,10 @@
 use std::env;
+#[cfg(unix)]
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+#[cfg(unix)]
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use actix_web::http::KeepAlive;
 use actix_web::HttpServer;
@@ -7,7 +12,11 @@ use clap::Parser;
 use meilisearch_auth::AuthController;
 use meilisearch_http::analytics;
 use meilisearch_http::analytics::Analytics;
-use meilisearch_http::{create_app, setup_meilisearch, Opt};
+use meilisearch_http::extractors::authentication::extract_client_certificate;
+use meilisearch_http::{
+    create_app, setup_client_certificates, setup_identity_provider, setup_meilisearch, Opt,
+};
+use meilisearch_http::{logging, runtime_config, shutdown, telemetry};
 use meilisearch_lib::MeiliSearch;
 
 #[cfg(target_os = "linux")]
@@ -16,14 +25,7 @@ static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 /// does all the setup before meilisearch is launched
 fn setup(opt: &Opt) -> anyhow::Result<()> {
-    let mut log_builder = env_logger::Builder::new();
-    log_builder.parse_filters(&opt.log_level);
-    if opt.log_level == "info" {
-        // if we are in info we only allow the warn log_level for milli
-        log_builder.filter_module("milli", log::LevelFilter::Warn);
-    }
-
-    log_builder.init();
+    logging::init(opt)?;
 
     Ok(())
 }
@@ -33,6 +35,7 @@ async fn main() -> anyhow::Result<()> {
     let opt = Opt::parse();
 
     setup(&opt)?;
+    telemetry::init(&opt)?;
 
     match opt.env.as_ref() {
         "production" => {
@@ -48,7 +51,9 @@ async fn main() -> anyhow::Result<()> {
 
     let meilisearch = setup_meilisearch(&opt)?;
 
-    let auth_controller = AuthController::new(&opt.db_path, &opt.master_key)?;
+    let mut auth_controller = AuthController::new(&opt.db_path, &opt.master_key)?;
+    setup_identity_provider(&opt, &mut auth_controller);
+    setup_client_certificates(&opt, &mut auth_controller)?;
 
     #[cfg(all(not(debug_assertions), feature = "analytics"))]
     let (analytics, user) = if !opt.no_analytics {
@@ -61,8 +66,27 @@ async fn main() -> anyhow::Result<()> {
 
     print_launch_resume(&opt, &user);
 
+    runtime_config::reload_on_hangup(meilisearch.clone(), opt.clone())?;
+
+    if let Some(ref addr) = opt.grpc_addr {
+        #[cfg(feature = "grpc")]
+        meilisearch_http::grpc::serve(
+            addr,
+            meilisearch.clone(),
+            auth_controller.clone(),
+            opt.rate_limit(),
+        )?;
+        #[cfg(not(feature = "grpc"))]
+        anyhow::bail!(
+            "Can't serve the gRPC API on `{}`, this binary was built without the `grpc` feature",
+            addr
+        );
+    }
+
     run_http(meilisearch, auth_controller, opt, analytics).await?;
 
+    telemetry::shutdown();
+
     Ok(())
 }
 
@@ -74,6 +98,7 @@ async fn run_http(
 ) -> anyhow::Result<()> {
     let _enable_dashboard = &opt.env == "development";
     let opt_clone = opt.clone();
+    let meilisearch = data.clone();
     let http_server = HttpServer::new(move || {
         create_app!(
             data,
@@ -83,17 +108,76 @@ async fn run_http(
             analytics.clone()
         )
     })
-    // Disable signals allows the server to terminate immediately when a user enter CTRL-C
+    // The client certificates of the TLS connections are authenticated by the extractors.
+    .on_connect(extract_client_certificate)
+    // The signals are handled by the graceful shutdown, which also waits for the tasks.
     .disable_signals()
-    .keep_alive(KeepAlive::Os);
+    .shutdown_timeout(opt.shutdown_timeout_sec)
+    .keep_alive(match opt.http_keep_alive_sec {
+        Some(0) => KeepAlive::Disabled,
+        Some(sec) => KeepAlive::Timeout(Duration::from_secs(sec)),
+        None => KeepAlive::Os,
+    })
+    .client_request_timeout(Duration::from_millis(opt.http_client_request_timeout_ms));
 
-    if let Some(config) = opt.get_ssl_config()? {
-        http_server
-            .bind_rustls(opt.http_addr, config)?
-            .run()
-            .await?;
-    } else {
-        http_server.bind(&opt.http_addr)?.run().await?;
+    let http_server = match opt.http_workers {
+        Some(workers) => http_server.workers(workers),
+        None => http_server,
+    };
+
+    let server = match opt.listen_unix_socket {
+        #[cfg(unix)]
+        Some(ref path) => {
+            if opt.ssl_cert_path.is_some() {
+                anyhow::bail!(
+                    "TLS isn't supported on a Unix domain socket, it is left to the proxy."
+                );
+            }
+            remove_stale_unix_socket(path)?;
+            let http_server = http_server.bind_uds(path)?;
+            // the connections are only accepted once the server runs.
+            std::fs::set_permissions(
+                path,
+                std::fs::Permissions::from_mode(opt.unix_socket_permissions),
+            )?;
+            http_server.run()
+        }
+        #[cfg(not(unix))]
+        Some(_) => anyhow::bail!("Unix domain sockets are only supported on Unix platforms."),
+        // HTTP/2 is negotiated with ALPN over TLS, and detected from the first bytes of the
+        // connection otherwise.
+        None => match opt.get_ssl_config()? {
+            Some(config) => http_server
+                .bind_rustls(opt.http_addr.clone(), config)?
+                .run(),
+            None => http_server.bind_auto_h2c(&opt.http_addr)?.run(),
+        },
+    };
+
+    let shutdown = actix_web::rt::spawn(shutdown::shutdown_on_signal(
+        server.handle(),
+        meilisearch,
+        Duration::from_secs(opt.shutdown_timeout_sec),
+    ));
+    server.await?;
+    // the server only stops once the shutdown started, which is done once the tasks are drained.
+    let _ = shutdown.await;
+
+    if let Some(ref path) = opt.listen_unix_socket {
+        let _ = std::fs::remove_file(path);
+    }
+
+    Ok(())
+}
+
+/// Removes the socket left at `path` by a previous run. Any other file is kept, and fails the
+/// bind.
+#[cfg(unix)]
+fn remove_stale_unix_socket(path: &Path) -> anyhow::Result<()> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(path)?;
+        }
     }
     Ok(())
 }
@@ -116,7 +200,13 @@ pub fn print_launch_resume(opt: &Opt, user: &str) {
     eprintln!("{}", ascii_name);
 
     eprintln!("Database path:\t\t{:?}", opt.db_path);
-    eprintln!("Server listening on:\t\"http://{}\"", opt.http_addr);
+    match opt.listen_unix_socket {
+        Some(ref path) => eprintln!("Server listening on:\t{:?}", path),
+        None => eprintln!("Server listening on:\t\"http://{}\"", opt.http_addr),
+    }
+    if let Some(ref addr) = opt.grpc_addr {
+        eprintln!("gRPC listening on:\t{:?}", addr);
+    }
     eprintln!("Environment:\t\t{:?}", opt.env);
     eprintln!("Commit SHA:\t\t{:?}", commit_sha.to_string());
     eprintln!("Commit date:\t\t{:?}", commit_date.to_string());
@@ -0,0 +1,110 @@
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use actix_web::{Error, ResponseError as _};
+use futures::future::LocalBoxFuture;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+
+use crate::error::MeilisearchHttpError;
+
+/// The routes accepting a `POST` without writing anything.
+const READ_ONLY_POST_SUFFIXES: &[&str] =
+    &["/search", "/facet-search", "/suggest", "/fetch", "/_search"];
+
+/// Returns whether `req` is accepted while the instance is in read-only mode: the reads, the
+/// searches, the snapshots and the changes of the mode itself.
+fn is_allowed_in_read_only_mode(req: &ServiceRequest) -> bool {
+    let path = req.path();
+    match *req.method() {
+        Method::GET | Method::HEAD | Method::OPTIONS => true,
+        Method::POST => {
+            READ_ONLY_POST_SUFFIXES
+                .iter()
+                .any(|suffix| path.ends_with(suffix))
+                || path == "/multi-search"
+                || path == "/graphql"
+                || path == "/snapshots"
+        }
+        _ => path == "/maintenance",
+    }
+}
+
+/// Returns whether `req` is accepted by a follower: the requests accepted in read-only mode, and
+/// the management of the API keys which aren't replicated.
+fn is_allowed_on_replica(req: &ServiceRequest) -> bool {
+    is_allowed_in_read_only_mode(req) || req.path().starts_with("/keys")
+}
+
+/// Rejects the write requests with a `503 Service Unavailable` response while the instance is
+/// in read-only mode, or if it is a follower replicating a primary instance.
+pub struct ReadOnlyMode {
+    meilisearch: MeiliSearch,
+}
+
+impl ReadOnlyMode {
+    pub fn new(meilisearch: MeiliSearch) -> Self {
+        Self { meilisearch }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ReadOnlyMode
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ReadOnlyModeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ReadOnlyModeMiddleware {
+            service: Rc::new(service),
+            meilisearch: self.meilisearch.clone(),
+        }))
+    }
+}
+
+pub struct ReadOnlyModeMiddleware<S> {
+    service: Rc<S>,
+    meilisearch: MeiliSearch,
+}
+
+impl<S, B> Service<ServiceRequest> for ReadOnlyModeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.meilisearch.is_read_only() && !is_allowed_in_read_only_mode(&req) {
+            let error = ResponseError::from(MeilisearchHttpError::ReadOnlyMode);
+            let response = error.error_response();
+            return Box::pin(ready(Ok(req.into_response(response).map_into_right_body())));
+        }
+        if let Some(primary) = self.meilisearch.replica_of() {
+            if !is_allowed_on_replica(&req) {
+                let error =
+                    ResponseError::from(MeilisearchHttpError::ReplicaMode(primary.to_string()));
+                let response = error.error_response();
+                return Box::pin(ready(Ok(req.into_response(response).map_into_right_body())));
+            }
+        }
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            let response = service.call(req).await?;
+            Ok(response.map_into_left_body())
+        })
+    }
+}
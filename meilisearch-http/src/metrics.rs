@@ -0,0 +1,383 @@
+//! The metrics exposed in the Prometheus text format by the `/metrics` route.
+//!
+//! The requests and the task durations are recorded as they happen in a registry shared by all
+//! the workers, the state of the tasks queue and of the indexes is read when the metrics are
+//! scraped.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures::future::LocalBoxFuture;
+use log::warn;
+use meilisearch_auth::SearchRules;
+use meilisearch_lib::tasks::TaskFilter;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::Opt;
+
+/// The upper bounds of the buckets of the response times, in seconds.
+const RESPONSE_TIME_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+/// The upper bounds of the buckets of the task durations, in seconds.
+const TASK_DURATION_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0, 3600.0];
+
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+/// Whether the `/metrics` route is enabled at startup, and the options reported by the metrics.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub max_index_size: u64,
+}
+
+impl MetricsConfig {
+    pub fn new(opt: &Opt) -> Self {
+        Self {
+            enabled: opt.experimental_enable_metrics,
+            max_index_size: opt.max_index_size.get_bytes() as u64,
+        }
+    }
+
+    /// Whether the metrics are enabled, at startup or with the `/experimental-features` route.
+    pub fn is_enabled(&self, meilisearch: &MeiliSearch) -> bool {
+        self.enabled || meilisearch.experimental_features().metrics
+    }
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    /// The number of requests by method, route and status code.
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    /// The response times by method and route.
+    response_times: Mutex<BTreeMap<(String, String), Histogram>>,
+    task_durations: Mutex<Option<Histogram>>,
+}
+
+impl Metrics {
+    fn record_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let key = (method.to_string(), route.to_string());
+        *self
+            .requests
+            .lock()
+            .unwrap()
+            .entry((key.0.clone(), key.1.clone(), status))
+            .or_default() += 1;
+        self.response_times
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| Histogram::new(RESPONSE_TIME_BUCKETS))
+            .observe(seconds);
+    }
+
+    fn record_task_duration(&self, seconds: f64) {
+        self.task_durations
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| Histogram::new(TASK_DURATION_BUCKETS))
+            .observe(seconds);
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub async fn render(
+        &self,
+        meilisearch: &MeiliSearch,
+        config: &MetricsConfig,
+    ) -> Result<String, ResponseError> {
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "meilisearch_http_requests_total",
+            "counter",
+            "The number of HTTP requests received.",
+        );
+        for ((method, route, status), count) in self.requests.lock().unwrap().iter() {
+            let labels = labels(&[
+                ("method", method),
+                ("route", route),
+                ("status", &status.to_string()),
+            ]);
+            let _ = writeln!(
+                out,
+                "meilisearch_http_requests_total{{{}}} {}",
+                labels, count
+            );
+        }
+
+        header(
+            &mut out,
+            "meilisearch_http_response_time_seconds",
+            "histogram",
+            "The response times of the HTTP requests.",
+        );
+        for ((method, route), histogram) in self.response_times.lock().unwrap().iter() {
+            let labels = labels(&[("method", method), ("route", route)]);
+            histogram.render(&mut out, "meilisearch_http_response_time_seconds", &labels);
+        }
+
+        header(
+            &mut out,
+            "meilisearch_task_duration_seconds",
+            "histogram",
+            "The processing durations of the finished tasks.",
+        );
+        if let Some(ref histogram) = *self.task_durations.lock().unwrap() {
+            histogram.render(&mut out, "meilisearch_task_duration_seconds", "");
+        }
+
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(|task| !task.is_finished());
+        let queued = meilisearch
+            .list_tasks(Some(filter), None, None)
+            .await?
+            .len();
+        header(
+            &mut out,
+            "meilisearch_tasks_queued",
+            "gauge",
+            "The number of enqueued and processing tasks.",
+        );
+        let _ = writeln!(out, "meilisearch_tasks_queued {}", queued);
+
+        let stats = meilisearch.get_all_stats(&SearchRules::default()).await?;
+        header(
+            &mut out,
+            "meilisearch_database_size_bytes",
+            "gauge",
+            "The size of all the indexes on disk.",
+        );
+        let _ = writeln!(
+            out,
+            "meilisearch_database_size_bytes {}",
+            stats.database_size
+        );
+
+        header(
+            &mut out,
+            "meilisearch_index_max_size_bytes",
+            "gauge",
+            "The maximum size of the LMDB map of an index.",
+        );
+        let _ = writeln!(
+            out,
+            "meilisearch_index_max_size_bytes {}",
+            config.max_index_size
+        );
+
+        header(
+            &mut out,
+            "meilisearch_index_size_bytes",
+            "gauge",
+            "The size of the LMDB map of an index used on disk.",
+        );
+        for (uid, index) in &stats.indexes {
+            let labels = labels(&[("index", uid)]);
+            let _ = writeln!(
+                out,
+                "meilisearch_index_size_bytes{{{}}} {}",
+                labels, index.size
+            );
+        }
+
+        header(
+            &mut out,
+            "meilisearch_index_documents",
+            "gauge",
+            "The number of documents of an index.",
+        );
+        for (uid, index) in &stats.indexes {
+            let labels = labels(&[("index", uid)]);
+            let _ = writeln!(
+                out,
+                "meilisearch_index_documents{{{}}} {}",
+                labels, index.number_of_documents
+            );
+        }
+
+        Ok(out)
+    }
+}
+
+struct Histogram {
+    bounds: &'static [f64],
+    /// The number of observations in each bucket, not cumulated.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if let Some(bucket) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.counts[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        let mut cumulated = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulated += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}{}le=\"{}\"}} {}",
+                name, labels, separator, bound, cumulated
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}{}le=\"+Inf\"}} {}",
+            name, labels, separator, self.count
+        );
+
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", labels)
+        };
+        let _ = writeln!(out, "{}_sum{} {}", name, labels, self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, labels, self.count);
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', r"\\")
+                .replace('"', r#"\""#)
+                .replace('\n', r"\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Records the durations of the tasks of `meilisearch` as they finish, until the scheduler stops.
+pub async fn record_task_durations(meilisearch: MeiliSearch) {
+    let mut receiver = meilisearch.subscribe_finished_tasks().await;
+
+    loop {
+        match receiver.recv().await {
+            Ok(tasks) => {
+                for task in tasks {
+                    if let (Some(started), Some(finished)) = (task.started_at(), task.finished_at())
+                    {
+                        METRICS.record_task_duration((finished - started).as_seconds_f64());
+                    }
+                }
+            }
+            Err(RecvError::Lagged(missed)) => {
+                warn!("The metrics missed the durations of {} tasks.", missed);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Records the number and the response time of the requests by route, when the metrics are
+/// enabled.
+pub struct RequestMetrics {
+    config: MetricsConfig,
+    meilisearch: MeiliSearch,
+}
+
+impl RequestMetrics {
+    pub fn new(meilisearch: MeiliSearch, opt: &Opt) -> Self {
+        Self {
+            config: MetricsConfig::new(opt),
+            meilisearch,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware {
+            service: Rc::new(service),
+            config: self.config,
+            meilisearch: self.meilisearch.clone(),
+        }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+    config: MetricsConfig,
+    meilisearch: MeiliSearch,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        if !self.config.is_enabled(&self.meilisearch) {
+            return Box::pin(service.call(req));
+        }
+
+        let start = Instant::now();
+        let method = req.method().to_string();
+        Box::pin(async move {
+            let response = service.call(req).await?;
+            // the routes are recorded by pattern, to not create a time series per index.
+            let route = response
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| "unmatched".to_string());
+            METRICS.record_request(
+                &method,
+                &route,
+                response.status().as_u16(),
+                start.elapsed().as_secs_f64(),
+            );
+            Ok(response)
+        })
+    }
+}
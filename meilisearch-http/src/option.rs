@@ -3,9 +3,14 @@ use std::io::{BufReader, Read};
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use actix_web::http::header::HeaderName;
+use actix_web::http::Method;
 use byte_unit::Byte;
 use clap::Parser;
-use meilisearch_lib::options::{IndexerOpts, SchedulerConfig};
+use ipnet::IpNet;
+use meilisearch_auth::RateLimit;
+use meilisearch_lib::options::{IndexerOpts, ObjectStorageOpts, SchedulerConfig};
+use meilisearch_lib::CronSchedule;
 use rustls::{
     server::{
         AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient,
@@ -16,7 +21,12 @@ use rustls::{
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 use serde::Serialize;
 
+use crate::runtime_config::RuntimeOptions;
+
 const POSSIBLE_ENV: [&str; 2] = ["development", "production"];
+const POSSIBLE_LOG_FORMATS: [&str; 2] = ["human", "json"];
+const POSSIBLE_EVENT_SINKS: [&str; 2] = ["nats", "kafka"];
+const POSSIBLE_EVENT_SINK_FORMATS: [&str; 2] = ["json", "ndjson"];
 
 #[derive(Debug, Clone, Parser, Serialize)]
 #[clap(version)]
@@ -29,6 +39,30 @@ pub struct Opt {
     #[clap(long, env = "MEILI_HTTP_ADDR", default_value = "127.0.0.1:7700")]
     pub http_addr: String,
 
+    /// Listen on this Unix domain socket instead of the `--http-addr`, to only be reachable
+    /// through a local reverse proxy. A socket left at this path by a previous run is replaced.
+    #[clap(
+        long,
+        env = "MEILI_LISTEN_UNIX_SOCKET",
+        parse(from_os_str),
+        conflicts_with = "http-addr"
+    )]
+    pub listen_unix_socket: Option<PathBuf>,
+
+    /// The permissions of the `--listen-unix-socket`, in octal. Only the owner and the group of
+    /// the process can connect to it by default.
+    #[clap(
+        long,
+        env = "MEILI_UNIX_SOCKET_PERMISSIONS",
+        default_value = "660",
+        parse(try_from_str = parse_unix_socket_permissions)
+    )]
+    pub unix_socket_permissions: u32,
+
+    /// Also serve the gRPC API on this address. Requires a binary built with the `grpc` feature.
+    #[clap(long, env = "MEILI_GRPC_ADDR")]
+    pub grpc_addr: Option<String>,
+
     /// The master key allowing you to do everything on the server.
     #[serde(skip)]
     #[clap(long, env = "MEILI_MASTER_KEY")]
@@ -59,6 +93,30 @@ pub struct Opt {
     #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT", default_value = "100 MB")]
     pub http_payload_size_limit: Byte,
 
+    /// The minimum size, in bytes, of the compressed responses. The responses are compressed
+    /// with gzip, brotli or zstd when the client accepts it with the `Accept-Encoding` header.
+    #[clap(long, env = "MEILI_HTTP_COMPRESSION_MIN_SIZE", default_value = "1 KiB")]
+    pub http_compression_min_size: Byte,
+
+    /// The routes whose responses are never compressed, separated by commas, e.g.
+    /// `/indexes/*/documents/export`. The `*` segments match any segment and the routes below the
+    /// ones listed are matched too.
+    #[clap(
+        long,
+        env = "MEILI_HTTP_COMPRESSION_DISABLED_ROUTES",
+        use_value_delimiter = true
+    )]
+    pub http_compression_disabled_routes: Vec<String>,
+
+    /// The minimum free space, in bytes, of the disk holding the database. Below it, the
+    /// `/health/ready` route reports the instance as unavailable.
+    #[clap(
+        long,
+        env = "MEILI_HEALTH_MIN_FREE_DISK_SPACE",
+        default_value = "100 MiB"
+    )]
+    pub health_min_free_disk_space: Byte,
+
     /// Read server certificates from CERTFILE.
     /// This should contain PEM-format certificates
     /// in the right order (the first certificate should
@@ -79,6 +137,19 @@ pub struct Opt {
     #[serde(skip)]
     pub ssl_auth_path: Option<PathBuf>,
 
+    /// Read the rights of the client certificates from this JSON file, a list of
+    /// `{ "fingerprint": "<SHA-256 fingerprint>", "actions": [...], "indexes": [...] }`. The
+    /// requests without an API key are authenticated with the certificate of their connection,
+    /// verified against the `--ssl-auth-path` roots.
+    #[clap(
+        long,
+        env = "MEILI_SSL_CLIENT_IDENTITIES_PATH",
+        parse(from_os_str),
+        requires = "ssl-auth-path"
+    )]
+    #[serde(skip)]
+    pub ssl_client_identities_path: Option<PathBuf>,
+
     /// Read DER-encoded OCSP response from OCSPFILE and staple to certificate.
     /// Optional
     #[serde(skip)]
@@ -122,14 +193,60 @@ pub struct Opt {
     #[clap(long, env = "MEILI_SCHEDULE_SNAPSHOT")]
     pub schedule_snapshot: bool,
 
+    /// On `SIGTERM` or `SIGINT`, the time given, in seconds, to the requests being processed and
+    /// to the batch of tasks being processed to finish before the process exits.
+    #[clap(long, env = "MEILI_SHUTDOWN_TIMEOUT_SEC", default_value = "30")]
+    pub shutdown_timeout_sec: u64,
+
+    /// The time, in seconds, an idle connection is kept open to receive the next request, `0`
+    /// closes the connections after each request. The keep-alive of the operating system is used
+    /// by default.
+    #[clap(long, env = "MEILI_HTTP_KEEP_ALIVE_SEC")]
+    pub http_keep_alive_sec: Option<u64>,
+
+    /// The time, in milliseconds, given to the clients to send the headers of a request before
+    /// it is rejected with a `408 Request Timeout`, `0` disables it.
+    #[clap(
+        long,
+        env = "MEILI_HTTP_CLIENT_REQUEST_TIMEOUT_MS",
+        default_value = "5000"
+    )]
+    pub http_client_request_timeout_ms: u64,
+
+    /// The number of threads accepting the HTTP requests, the number of physical CPU cores by
+    /// default.
+    #[clap(long, env = "MEILI_HTTP_WORKERS")]
+    pub http_workers: Option<usize>,
+
     /// Defines time interval, in seconds, between each snapshot creation.
     #[clap(long, env = "MEILI_SNAPSHOT_INTERVAL_SEC", default_value = "86400")] // 24h
     pub snapshot_interval_sec: u64,
 
+    /// Schedules the snapshots with a cron expression evaluated in UTC, e.g. `0 3 * * *` for
+    /// every day at 3am, instead of every `snapshot-interval-sec`. Implies `schedule-snapshot`.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_SNAPSHOT_SCHEDULE")]
+    pub snapshot_schedule: Option<CronSchedule>,
+
+    /// Keeps the given number of snapshots in the snapshot directory instead of overwriting the
+    /// previous snapshot.
+    #[clap(long, env = "MEILI_SNAPSHOT_RETENTION_COUNT")]
+    pub snapshot_retention_count: Option<usize>,
+
+    /// Keeps the snapshots for the given number of days in the snapshot directory instead of
+    /// overwriting the previous snapshot. The most recent snapshot is always kept.
+    #[clap(long, env = "MEILI_SNAPSHOT_RETENTION_DAYS")]
+    pub snapshot_retention_days: Option<u64>,
+
     /// Import a dump from the specified path, must be a `.dump` file.
     #[clap(long, conflicts_with = "import-snapshot")]
     pub import_dump: Option<PathBuf>,
 
+    /// Replays an incremental dump on top of the imported dump. Can be repeated to replay a
+    /// chain of incremental dumps, in the order they were created.
+    #[clap(long, requires = "import-dump", multiple_occurrences = true)]
+    pub import_incremental_dump: Vec<PathBuf>,
+
     /// If the dump doesn't exists, load or create the database specified by `db-path` instead.
     #[clap(long, requires = "import-dump")]
     pub ignore_missing_dump: bool,
@@ -142,10 +259,206 @@ pub struct Opt {
     #[clap(long, env = "MEILI_DUMPS_DIR", default_value = "dumps/")]
     pub dumps_dir: PathBuf,
 
+    /// The hosts the dumps imported at runtime can be downloaded from, separated by commas. No
+    /// dump can be imported from a URL when empty.
+    #[clap(
+        long,
+        env = "MEILI_DUMP_IMPORT_ALLOWED_HOSTS",
+        use_value_delimiter = true
+    )]
+    pub dump_import_allowed_hosts: Vec<String>,
+
     /// Set the log level
     #[clap(long, env = "MEILI_LOG_LEVEL", default_value = "info")]
     pub log_level: String,
 
+    /// Set the format of the logs. With `json`, each log is a JSON object on its own line, and
+    /// the requests are logged with their method, path, status, latency, API key and index.
+    #[clap(long, env = "MEILI_LOG_FORMAT", default_value = "human", possible_values = &POSSIBLE_LOG_FORMATS)]
+    pub log_format: String,
+
+    /// The maximum duration of a search, in milliseconds, for the indexes that don't define their
+    /// own `searchCutoffMs` setting. Once it is reached, the hits found so far are returned.
+    #[clap(long, env = "MEILI_SEARCH_CUTOFF_MS")]
+    pub search_cutoff_ms: Option<u64>,
+
+    /// Log the searches taking longer than this duration, in milliseconds, with their parameters
+    /// and the time spent in each phase of the search.
+    #[clap(long, env = "MEILI_SLOW_QUERY_THRESHOLD_MS")]
+    pub slow_query_threshold_ms: Option<u64>,
+
+    /// Keep the results of this number of searches in memory, to answer the identical searches
+    /// without performing them again. The results of an index are dropped as soon as a task
+    /// touching it is finished. Disabled by default.
+    #[clap(long, env = "MEILI_SEARCH_CACHE_SIZE")]
+    pub search_cache_size: Option<usize>,
+
+    /// The URL notified with the tasks that reach a terminal state, the tasks are sent as an
+    /// NDJSON payload. The webhook can also be changed at runtime with the `/webhook` route.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_TASK_WEBHOOK_URL")]
+    pub task_webhook_url: Option<String>,
+
+    /// The value of the `Authorization` header sent with the task webhook notifications.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_TASK_WEBHOOK_AUTHORIZATION_HEADER",
+        requires = "task-webhook-url"
+    )]
+    pub task_webhook_authorization_header: Option<String>,
+
+    /// Publish the finished tasks, and the document changes when the changes feed is enabled, to
+    /// an event sink: a NATS server, or a Kafka cluster through its REST proxy.
+    #[clap(long, env = "MEILI_EVENT_SINK", possible_values = &POSSIBLE_EVENT_SINKS, requires = "event-sink-url")]
+    pub event_sink: Option<String>,
+
+    /// The URL of the event sink, e.g. `nats://localhost:4222`, with the credentials of the NATS
+    /// server as `user:password@` or `token@`, or `http://localhost:8082` for the Kafka REST proxy.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_EVENT_SINK_URL", requires = "event-sink")]
+    pub event_sink_url: Option<String>,
+
+    /// The NATS subject or the Kafka topic the events are published to.
+    #[clap(long, env = "MEILI_EVENT_SINK_TOPIC", default_value = "meilisearch")]
+    pub event_sink_topic: String,
+
+    /// The serialization of the events. With `json` each event is published as its own message,
+    /// with `ndjson` the events of a batch of tasks are published as one message, one per line.
+    #[clap(long, env = "MEILI_EVENT_SINK_FORMAT", default_value = "json", possible_values = &POSSIBLE_EVENT_SINK_FORMATS)]
+    pub event_sink_format: String,
+
+    /// Serve the log of the finished tasks to the followers, with the `/replication` routes.
+    /// The update files of the document additions are kept for this number of seconds once
+    /// processed, the followers lagging further behind, or behind the deleted tasks, must be
+    /// restored from a new snapshot of this instance.
+    #[clap(long, env = "MEILI_REPLICATION_LOG_RETENTION_SEC")]
+    pub replication_log_retention_sec: Option<u64>,
+
+    /// The URL of a primary instance to follow, e.g. `http://primary:7700`. Its tasks are
+    /// replayed as soon as they are finished and the write requests are rejected, except for the
+    /// API keys that aren't replicated. A follower is started empty, or from a snapshot of the
+    /// primary.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_REPLICA_OF")]
+    pub replica_of: Option<String>,
+
+    /// The API key sent to the primary instance, it must grant the `replication.get` action.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_REPLICA_API_KEY", requires = "replica-of")]
+    pub replica_api_key: Option<String>,
+
+    /// The addresses or CIDR ranges of the reverse proxies in front of the server, separated by
+    /// commas. The address of the clients is read from the `X-Forwarded-For` header of the
+    /// requests they send, to enforce the `allowedIps` of the API keys.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_TRUSTED_PROXIES", use_value_delimiter = true)]
+    pub trusted_proxies: Vec<IpNet>,
+
+    /// The origins allowed to send cross-origin requests, separated by commas, e.g.
+    /// `https://example.com`. Any origin is allowed when empty or `*`.
+    #[clap(long, env = "MEILI_CORS_ALLOWED_ORIGINS", use_value_delimiter = true)]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// The methods allowed in the cross-origin requests, separated by commas. Any method is
+    /// allowed when empty.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_CORS_ALLOWED_METHODS", use_value_delimiter = true)]
+    pub cors_allowed_methods: Vec<Method>,
+
+    /// The headers allowed in the cross-origin requests, separated by commas. Any header is
+    /// allowed when empty.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_CORS_ALLOWED_HEADERS", use_value_delimiter = true)]
+    pub cors_allowed_headers: Vec<HeaderName>,
+
+    /// The time, in seconds, the browsers may cache the result of a preflight request.
+    #[clap(long, env = "MEILI_CORS_MAX_AGE_SEC", default_value = "86400")]
+    pub cors_max_age_sec: usize,
+
+    /// The URL of the JSON Web Key Set of an OpenID Connect identity provider. The tokens signed
+    /// by one of its keys are accepted besides the API keys.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_OIDC_JWKS_URL", requires = "oidc-issuer")]
+    pub oidc_jwks_url: Option<String>,
+
+    /// The `iss` claim expected in the tokens of the identity provider.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_OIDC_ISSUER")]
+    pub oidc_issuer: Option<String>,
+
+    /// The `aud` claim expected in the tokens of the identity provider, it isn't checked by default.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_OIDC_AUDIENCE")]
+    pub oidc_audience: Option<String>,
+
+    /// The claim listing the actions allowed to the tokens of the identity provider.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OIDC_ACTIONS_CLAIM",
+        default_value = "meilisearch_actions"
+    )]
+    pub oidc_actions_claim: String,
+
+    /// The claim listing the indexes accessible to the tokens of the identity provider.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OIDC_INDEXES_CLAIM",
+        default_value = "meilisearch_indexes"
+    )]
+    pub oidc_indexes_claim: String,
+
+    /// The interval, in seconds, at which the keys of the identity provider are fetched again.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OIDC_JWKS_REFRESH_INTERVAL_SEC",
+        default_value = "600"
+    )]
+    pub oidc_jwks_refresh_interval_sec: u64,
+
+    /// The maximum number of requests per second accepted by the instance. The API keys can
+    /// define their own limits with their `rateLimit` field.
+    #[clap(long, env = "MEILI_RATE_LIMIT_REQUESTS_PER_SECOND")]
+    pub rate_limit_requests_per_second: Option<u32>,
+
+    /// The maximum number of searches processed concurrently by the instance.
+    #[clap(long, env = "MEILI_RATE_LIMIT_CONCURRENT_SEARCHES")]
+    pub rate_limit_concurrent_searches: Option<u32>,
+
+    /// Enables the `/metrics` route, exposing metrics in the Prometheus format to the master key.
+    /// It can also be enabled at runtime with the `/experimental-features` route.
+    #[clap(long, env = "MEILI_EXPERIMENTAL_ENABLE_METRICS")]
+    pub experimental_enable_metrics: bool,
+
+    /// Enables the `/es` routes, accepting a subset of the Elasticsearch search API. It can also be
+    /// enabled at runtime with the `/experimental-features` route.
+    #[clap(long, env = "MEILI_EXPERIMENTAL_ENABLE_ELASTICSEARCH_COMPAT")]
+    pub experimental_enable_elasticsearch_compat: bool,
+
+    /// Counts the searches made on each index, in memory, and exposes the most frequent and the
+    /// zero-result queries on the `/indexes/{index_uid}/analytics` route. It can also be enabled
+    /// at runtime with the `/experimental-features` route.
+    #[clap(long, env = "MEILI_EXPERIMENTAL_ENABLE_SEARCH_ANALYTICS")]
+    pub experimental_enable_search_analytics: bool,
+
+    /// The OTLP endpoint of an OpenTelemetry collector, e.g. `http://localhost:4317`. The traces
+    /// of the requests and of the tasks are exported to it.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_EXPERIMENTAL_OTLP_ENDPOINT")]
+    pub experimental_otlp_endpoint: Option<String>,
+
+    /// The `service.name` of the exported traces.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_EXPERIMENTAL_OTLP_SERVICE_NAME",
+        default_value = "meilisearch"
+    )]
+    pub experimental_otlp_service_name: String,
+
     #[serde(flatten)]
     #[clap(flatten)]
     pub indexer_options: IndexerOpts,
@@ -153,9 +466,32 @@ pub struct Opt {
     #[serde(flatten)]
     #[clap(flatten)]
     pub scheduler_options: SchedulerConfig,
+
+    #[serde(flatten)]
+    #[clap(flatten)]
+    pub object_storage_options: ObjectStorageOpts,
+
+    /// A JSON file of the options to reload when the process receives a `SIGHUP`, with the
+    /// fields accepted by the `PATCH /config` route.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_RUNTIME_CONFIG_FILE")]
+    pub runtime_config_file: Option<PathBuf>,
+
+    /// The options changed at runtime, they override the options above.
+    #[serde(skip)]
+    #[clap(skip)]
+    pub runtime: RuntimeOptions,
 }
 
 impl Opt {
+    /// The limits applying to all the requests together.
+    pub fn rate_limit(&self) -> RateLimit {
+        RateLimit {
+            requests_per_second: self.rate_limit_requests_per_second,
+            concurrent_searches: self.rate_limit_concurrent_searches,
+        }
+    }
+
     /// Wether analytics should be enabled or not.
     #[cfg(all(not(debug_assertions), feature = "analytics"))]
     pub fn analytics(&self) -> bool {
@@ -209,6 +545,17 @@ impl Opt {
     }
 }
 
+fn parse_unix_socket_permissions(permissions: &str) -> Result<u32, String> {
+    let digits = permissions.trim_start_matches("0o");
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if mode <= 0o777 => Ok(mode),
+        _ => Err(format!(
+            "`{}` isn't a valid permission, expected an octal mode such as `660`.",
+            permissions
+        )),
+    }
+}
+
 fn load_certs(filename: PathBuf) -> anyhow::Result<Vec<rustls::Certificate>> {
     let certfile =
         fs::File::open(filename).map_err(|_| anyhow::anyhow!("cannot open certificate file"))?;
@@ -268,4 +615,12 @@ mod test {
     fn test_valid_opt() {
         assert!(Opt::try_parse_from(Some("")).is_ok());
     }
+
+    #[test]
+    fn test_unix_socket_permissions() {
+        assert_eq!(parse_unix_socket_permissions("660").unwrap(), 0o660);
+        assert_eq!(parse_unix_socket_permissions("0o600").unwrap(), 0o600);
+        assert!(parse_unix_socket_permissions("999").is_err());
+        assert!(parse_unix_socket_permissions("1777").is_err());
+    }
 }
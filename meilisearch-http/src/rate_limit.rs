@@ -0,0 +1,157 @@
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
+use actix_web::{Error, ResponseError as _};
+use futures::future::LocalBoxFuture;
+use meilisearch_auth::{AuthController, RateLimit};
+use meilisearch_types::error::ResponseError;
+use uuid::Uuid;
+
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::policies::{extract_key_id, verified_key_id};
+use crate::Opt;
+
+/// Rejects the requests exceeding the global rate limits or the rate limits of their API key
+/// with a `429 Too Many Requests` response.
+///
+/// The counters and the rate limits of the keys are kept in memory by the [`AuthController`], so
+/// they are shared by all the workers and no key is read from the store to count a request.
+pub struct RateLimiting {
+    auth: AuthController,
+    global: RateLimit,
+}
+
+impl RateLimiting {
+    pub fn new(auth: AuthController, opt: &Opt) -> Self {
+        Self {
+            auth,
+            global: opt.rate_limit(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimiting
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitingMiddleware {
+            service: Rc::new(service),
+            auth: self.auth.clone(),
+            global: self.global,
+        }))
+    }
+}
+
+pub struct RateLimitingMiddleware<S> {
+    service: Rc<S>,
+    auth: AuthController,
+    global: RateLimit,
+}
+
+/// Returns the uid and the rate limits of the API key `token`, if it has some. The tenant tokens
+/// are limited like their parent key, once their signature is checked: the key they claim is
+/// only charged if they were signed by it.
+///
+/// The HTTP and the gRPC requests are charged alike through this function.
+pub fn key_rate_limit(auth: &AuthController, token: &str) -> Option<(Uuid, RateLimit)> {
+    if let Some(key) = auth.rate_limit_of_encoded_key(token) {
+        return Some(key);
+    }
+
+    // the signature is only checked for the tokens claiming a limited key.
+    let uid = extract_key_id(token)?;
+    let rate_limit = auth.rate_limit_of_key(uid)?;
+    verified_key_id(auth, token)?;
+    Some((uid, rate_limit))
+}
+
+/// Whether the request on `path` is a search, counted against the concurrent searches. The
+/// aliases are searched through the routes of the indexes, and the Elasticsearch compatible
+/// searches through `/es/{index_uid}/_search`. The trailing slashes are already trimmed.
+fn is_search(path: &str) -> bool {
+    let mut segments = path.split('/').skip(1);
+    match (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) {
+        (Some("multi-search"), None, None, None) => true,
+        (Some("indexes"), Some(_), Some("search"), None) => true,
+        (Some("es"), Some(_), Some("_search"), None) => true,
+        _ => false,
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let key = req
+            .headers()
+            .get("Authorization")
+            .and_then(|type_token| type_token.to_str().ok())
+            .and_then(|type_token| type_token.strip_prefix("Bearer "))
+            .and_then(|token| key_rate_limit(&self.auth, token));
+        let search = is_search(req.path());
+
+        match self.auth.rate_limiter().acquire(self.global, key, search) {
+            Ok(permit) => {
+                let service = self.service.clone();
+                Box::pin(async move {
+                    let response = service.call(req).await?;
+                    drop(permit);
+                    Ok(response.map_into_left_body())
+                })
+            }
+            Err(retry_after) => {
+                // rounded up, the header only accepts whole seconds.
+                let seconds = retry_after.as_secs() + (retry_after.subsec_nanos() > 0) as u64;
+                let error = ResponseError::from(MeilisearchHttpError::TooManyRequests(seconds));
+                let mut response = error.error_response();
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(seconds));
+                Box::pin(ready(Ok(req.into_response(response).map_into_right_body())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn search_paths() {
+        assert!(is_search("/indexes/movies/search"));
+        // an alias looks like an index in the path.
+        assert!(is_search("/indexes/movies-production/search"));
+        assert!(is_search("/es/movies/_search"));
+        assert!(is_search("/multi-search"));
+
+        assert!(!is_search("/indexes/movies/facet-search"));
+        assert!(!is_search("/indexes/movies/documents/search"));
+        assert!(!is_search("/indexes/movies/settings"));
+        assert!(!is_search("/search"));
+    }
+}
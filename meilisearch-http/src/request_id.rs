@@ -0,0 +1,103 @@
+//! Identifies each request with the `X-Request-Id` header.
+//!
+//! The id sent by the client is kept if it is valid, otherwise a new one is generated. It is
+//! added to the headers of the request, so the logger can print it, and to the headers of the
+//! response. The logs written and the tasks registered while the request is processed record it.
+
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::Error;
+use futures::future::LocalBoxFuture;
+use meilisearch_lib::request_id;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The format of the actix logger, its default format followed by the request id.
+pub const LOGGER_FORMAT: &str =
+    r#"%a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T %{x-request-id}i"#;
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Returns whether `id`, sent by a client, can be used as a request id.
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+pub struct RequestIdentifier;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdentifier
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdentifierMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdentifierMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestIdentifierMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdentifierMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| is_valid(id))
+            .map(String::from)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let name = HeaderName::from_static(REQUEST_ID_HEADER);
+        // the id is made of visible ASCII characters, it is a valid header value.
+        let value = HeaderValue::from_str(&id).unwrap();
+        req.headers_mut().insert(name.clone(), value.clone());
+
+        let service = self.service.clone();
+        Box::pin(request_id::scope(id, async move {
+            let mut response = service.call(req).await?;
+            response.headers_mut().insert(name, value);
+            Ok(response)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate_request_ids() {
+        assert!(is_valid("7b9f4c1e-2f0a-4a43-89a4-3cbf1a0e2d5b"));
+        assert!(is_valid("client-42"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("with space"));
+        assert!(!is_valid("é"));
+        assert!(!is_valid(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)));
+    }
+}
@@ -0,0 +1,115 @@
+//! The aliases are names resolving to an index in the search, document and settings routes, e.g.
+//! `/indexes/movies-production/search` searches the index `movies-production` points to.
+//!
+//! An alias is authorized like an index: a key restricted to the alias can use it whatever index
+//! it points to. Deleting an index doesn't delete its aliases, they fail to resolve until they
+//! are retargeted.
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, AuthenticationError, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+use super::Pagination;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(list_aliases))))
+        .service(
+            web::resource("/{alias}")
+                .route(web::get().to(SeqHandler(get_alias)))
+                .route(web::put().to(SeqHandler(set_alias)))
+                .route(web::delete().to(SeqHandler(delete_alias))),
+        );
+}
+
+pub async fn list_aliases(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALIASES_GET }>, MeiliSearch>,
+    paginate: web::Query<Pagination>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = &meilisearch.filters().search_rules;
+    let aliases: Vec<_> = meilisearch
+        .list_aliases()
+        .await?
+        .into_iter()
+        .filter(|alias| search_rules.is_index_authorized(&alias.alias))
+        .collect();
+    let ret = paginate
+        .into_inner()
+        .auto_paginate_sized(aliases.into_iter());
+
+    debug!("returns: {:?}", ret);
+    Ok(HttpResponse::Ok().json(ret))
+}
+
+pub async fn get_alias(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALIASES_GET }>, MeiliSearch>,
+    alias: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let alias = alias.into_inner();
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&alias)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let res = meilisearch.get_alias(alias).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SetAliasPayload {
+    index_uid: String,
+}
+
+pub async fn set_alias(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALIASES_UPDATE }>, MeiliSearch>,
+    alias: web::Path<String>,
+    body: web::Json<SetAliasPayload>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let alias = alias.into_inner();
+    let SetAliasPayload { index_uid } = body.into_inner();
+
+    // Pointing an alias to an index gives access to the index to every key allowed to use the
+    // alias, so both must be accessible to the key.
+    let search_rules = &meilisearch.filters().search_rules;
+    if !search_rules.is_index_authorized(&alias) || !search_rules.is_index_authorized(&index_uid) {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    analytics.publish("Alias Updated".to_string(), json!({}), Some(&req));
+
+    let res = meilisearch.set_alias(alias, index_uid).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn delete_alias(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALIASES_UPDATE }>, MeiliSearch>,
+    alias: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let alias = alias.into_inner();
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&alias)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    meilisearch.delete_alias(alias).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -2,18 +2,20 @@ use std::str;
 
 use actix_web::{web, HttpRequest, HttpResponse};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use meilisearch_auth::{error::AuthControllerError, Action, AuthController, Key};
+use meilisearch_auth::{
+    error::AuthControllerError, Action, AuditAction, AuthController, Key, RateLimit,
+};
 use meilisearch_types::error::{Code, ResponseError};
 
 use crate::extractors::{
     authentication::{policies::*, GuardedData},
     sequential_extractor::SeqHandler,
 };
-use crate::routes::Pagination;
+use crate::routes::{audit_log, Pagination};
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -26,15 +28,17 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route(web::get().to(SeqHandler(get_api_key)))
             .route(web::patch().to(SeqHandler(patch_api_key)))
             .route(web::delete().to(SeqHandler(delete_api_key))),
-    );
+    )
+    .service(web::resource("/{key}/rotate").route(web::post().to(SeqHandler(rotate_api_key))));
 }
 
 pub async fn create_api_key(
     auth_controller: GuardedData<ActionPolicy<{ actions::KEYS_CREATE }>, AuthController>,
     body: web::Json<Value>,
-    _req: HttpRequest,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let v = body.into_inner();
+    let api_key_uid = auth_controller.filters().key_uid;
     let res = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
         let key = auth_controller.create_key(v)?;
         Ok(KeyView::from_key(key, &auth_controller))
@@ -42,6 +46,15 @@ pub async fn create_api_key(
     .await
     .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
 
+    audit_log::record(
+        &req,
+        api_key_uid,
+        AuditAction::KeyCreated,
+        Some(res.uid.to_string()),
+        json!({ "name": res.name, "actions": res.actions, "indexes": res.indexes }),
+    )
+    .await;
+
     Ok(HttpResponse::Created().json(res))
 }
 
@@ -87,9 +100,16 @@ pub async fn patch_api_key(
     auth_controller: GuardedData<ActionPolicy<{ actions::KEYS_UPDATE }>, AuthController>,
     body: web::Json<Value>,
     path: web::Path<AuthParam>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let key = path.into_inner().key;
     let body = body.into_inner();
+    let api_key_uid = auth_controller.filters().key_uid;
+    // only the names of the updated fields are recorded.
+    let fields: Vec<_> = body
+        .as_object()
+        .map(|fields| fields.keys().cloned().collect())
+        .unwrap_or_default();
     let res = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
         let uid =
             Uuid::parse_str(&key).or_else(|_| auth_controller.get_uid_from_encoded_key(&key))?;
@@ -100,22 +120,74 @@ pub async fn patch_api_key(
     .await
     .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
 
+    audit_log::record(
+        &req,
+        api_key_uid,
+        AuditAction::KeyUpdated,
+        Some(res.uid.to_string()),
+        json!({ "fields": fields }),
+    )
+    .await;
+
     Ok(HttpResponse::Ok().json(res))
 }
 
+pub async fn rotate_api_key(
+    auth_controller: GuardedData<ActionPolicy<{ actions::KEYS_ROTATE }>, AuthController>,
+    body: Option<web::Json<Value>>,
+    path: web::Path<AuthParam>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ResponseError> {
+    let key = path.into_inner().key;
+    let body = body.map_or(Value::Null, web::Json::into_inner);
+    let api_key_uid = auth_controller.filters().key_uid;
+    let (rotated, res) = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
+        let uid =
+            Uuid::parse_str(&key).or_else(|_| auth_controller.get_uid_from_encoded_key(&key))?;
+        let key = auth_controller.rotate_key(uid, body)?;
+
+        Ok((uid, KeyView::from_key(key, &auth_controller)))
+    })
+    .await
+    .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    audit_log::record(
+        &req,
+        api_key_uid,
+        AuditAction::KeyRotated,
+        Some(rotated.to_string()),
+        json!({ "newUid": res.uid }),
+    )
+    .await;
+
+    Ok(HttpResponse::Created().json(res))
+}
+
 pub async fn delete_api_key(
     auth_controller: GuardedData<ActionPolicy<{ actions::KEYS_DELETE }>, AuthController>,
     path: web::Path<AuthParam>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let key = path.into_inner().key;
-    tokio::task::spawn_blocking(move || {
+    let api_key_uid = auth_controller.filters().key_uid;
+    let deleted = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
         let uid =
             Uuid::parse_str(&key).or_else(|_| auth_controller.get_uid_from_encoded_key(&key))?;
-        auth_controller.delete_key(uid)
+        auth_controller.delete_key(uid)?;
+        Ok(uid)
     })
     .await
     .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
 
+    audit_log::record(
+        &req,
+        api_key_uid,
+        AuditAction::KeyDeleted,
+        Some(deleted.to_string()),
+        json!({}),
+    )
+    .await;
+
     Ok(HttpResponse::NoContent().finish())
 }
 
@@ -135,6 +207,11 @@ struct KeyView {
     indexes: Vec<String>,
     #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
     expires_at: Option<OffsetDateTime>,
+    allowed_ips: Option<Vec<String>>,
+    allowed_referers: Option<Vec<String>>,
+    rate_limit: Option<RateLimit>,
+    displayed_attributes: Option<Vec<String>>,
+    forbidden_attributes: Option<Vec<String>>,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
     created_at: OffsetDateTime,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
@@ -153,6 +230,13 @@ impl KeyView {
             actions: key.actions,
             indexes: key.indexes.into_iter().map(String::from).collect(),
             expires_at: key.expires_at,
+            allowed_ips: key
+                .allowed_ips
+                .map(|ips| ips.iter().map(ToString::to_string).collect()),
+            allowed_referers: key.allowed_referers,
+            rate_limit: key.rate_limit,
+            displayed_attributes: key.displayed_attributes,
+            forbidden_attributes: key.forbidden_attributes,
             created_at: key.created_at,
             updated_at: key.updated_at,
         }
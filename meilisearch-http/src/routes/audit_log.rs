@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{debug, error};
+use meilisearch_auth::error::AuthControllerError;
+use meilisearch_auth::{AuditAction, AuditEntry, AuditEntryId, AuditFilter, AuthController};
+use meilisearch_types::error::{Code, ResponseError};
+use serde::{Deserialize, Serialize};
+use serde_cs::vec::CS;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+const DEFAULT_LIMIT: fn() -> usize = || 20;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_audit_log))))
+        .service(web::resource("/export").route(web::get().to(SeqHandler(export_audit_log))));
+}
+
+/// Records that the key `api_key_uid`, or the master key if it is `None`, performed `action` on
+/// `target`. The action has already been performed, so a failure to record it is only logged.
+pub async fn record(
+    req: &HttpRequest,
+    api_key_uid: Option<Uuid>,
+    action: AuditAction,
+    target: Option<String>,
+    details: Value,
+) {
+    let auth = match req.app_data::<AuthController>() {
+        Some(auth) => auth.clone(),
+        None => return,
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        auth.record_audit_entry(action, target, api_key_uid, details)
+    })
+    .await;
+    match result {
+        Ok(Ok(entry)) => debug!("recorded audit entry: {:?}", entry),
+        Ok(Err(e)) => error!("Failed to record the `{}` audit entry: {}", action, e),
+        Err(e) => error!("Failed to record the `{}` audit entry: {}", action, e),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuditLogFilterQuery {
+    actions: Option<CS<AuditAction>>,
+    api_key_uid: Option<Uuid>,
+    target: Option<String>,
+}
+
+impl From<AuditLogFilterQuery> for AuditFilter {
+    fn from(query: AuditLogFilterQuery) -> Self {
+        Self {
+            actions: query.actions.map(|actions| actions.into_iter().collect()),
+            api_key_uid: query.api_key_uid,
+            target: query.target,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuditLogQuery {
+    actions: Option<CS<AuditAction>>,
+    api_key_uid: Option<Uuid>,
+    target: Option<String>,
+    #[serde(default = "DEFAULT_LIMIT")]
+    limit: usize,
+    from: Option<AuditEntryId>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditLogView {
+    results: Vec<AuditEntry>,
+    limit: usize,
+    from: Option<AuditEntryId>,
+    next: Option<AuditEntryId>,
+}
+
+pub async fn get_audit_log(
+    auth_controller: GuardedData<ActionPolicy<{ actions::AUDIT_LOG_GET }>, AuthController>,
+    params: web::Query<AuditLogQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let AuditLogQuery {
+        actions,
+        api_key_uid,
+        target,
+        limit,
+        from,
+    } = params.into_inner();
+    let filter = AuditFilter::from(AuditLogFilterQuery {
+        actions,
+        api_key_uid,
+        target,
+    });
+
+    let view = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
+        // We +1 just to know if there is more after this "page" or not.
+        let mut results =
+            auth_controller.list_audit_entries(&filter, from, limit.saturating_add(1))?;
+        let next = if results.len() > limit {
+            results.pop().map(|entry| entry.id)
+        } else {
+            None
+        };
+        let from = results.first().map(|entry| entry.id);
+
+        Ok(AuditLogView {
+            results,
+            limit,
+            from,
+            next,
+        })
+    })
+    .await
+    .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::Ok().json(view))
+}
+
+/// Exports the entries of the audit log matching the filters as NDJSON, from the oldest entry
+/// to the most recent.
+pub async fn export_audit_log(
+    auth_controller: GuardedData<ActionPolicy<{ actions::AUDIT_LOG_GET }>, AuthController>,
+    params: web::Query<AuditLogFilterQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let filter = AuditFilter::from(params.into_inner());
+
+    let body = tokio::task::spawn_blocking(move || -> Result<_, AuthControllerError> {
+        let mut body = Vec::new();
+        for entry in auth_controller.audit_entries()? {
+            if filter.matches(&entry) {
+                serde_json::to_writer(&mut body, &entry)?;
+                body.write_all(b"\n")?;
+            }
+        }
+        Ok(body)
+    })
+    .await
+    .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))??;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .body(body))
+}
@@ -0,0 +1,284 @@
+use actix_web::error::PayloadError;
+use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use log::debug;
+use meilisearch_auth::Action;
+use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
+use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use meilisearch_lib::tasks::task::GroupUid;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::ActionGuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::task::{SummarizedTaskView, TaskStatus, TaskView};
+
+use super::tasks::indexes_filter;
+
+pub fn configure_batch(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(register_group))));
+}
+
+pub fn configure_batches(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/{group_uid}").route(web::get().to(SeqHandler(get_group))));
+}
+
+/// An operation of a batch, it is registered as a task on the index `index_uid`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum BatchOperation {
+    #[serde(rename_all = "camelCase")]
+    AddDocuments {
+        index_uid: String,
+        documents: Vec<Map<String, Value>>,
+        primary_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    UpdateDocuments {
+        index_uid: String,
+        documents: Vec<Map<String, Value>>,
+        primary_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DeleteDocuments {
+        index_uid: String,
+        ids: Option<Vec<Value>>,
+        filter: Option<Value>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ClearDocuments { index_uid: String },
+    #[serde(rename_all = "camelCase")]
+    UpdateSettings {
+        index_uid: String,
+        settings: Settings<Unchecked>,
+    },
+}
+
+impl BatchOperation {
+    fn index_uid(&self) -> &str {
+        match self {
+            BatchOperation::AddDocuments { index_uid, .. }
+            | BatchOperation::UpdateDocuments { index_uid, .. }
+            | BatchOperation::DeleteDocuments { index_uid, .. }
+            | BatchOperation::ClearDocuments { index_uid }
+            | BatchOperation::UpdateSettings { index_uid, .. } => index_uid,
+        }
+    }
+
+    fn action(&self) -> Action {
+        match self {
+            BatchOperation::AddDocuments { .. } | BatchOperation::UpdateDocuments { .. } => {
+                Action::DocumentsAdd
+            }
+            BatchOperation::DeleteDocuments { .. } | BatchOperation::ClearDocuments { .. } => {
+                Action::DocumentsDelete
+            }
+            BatchOperation::UpdateSettings { .. } => Action::SettingsUpdate,
+        }
+    }
+
+    /// Converts the operation to the update registering it, the `position` of the operation in
+    /// the batch is used in the error messages.
+    fn into_update(
+        self,
+        position: usize,
+        allow_index_creation: bool,
+    ) -> Result<(String, Update), MeilisearchHttpError> {
+        let documents_update = |documents: Vec<Map<String, Value>>, primary_key, method| {
+            // the documents were checked when deserializing the payload.
+            let documents = Bytes::from(serde_json::to_vec(&documents).unwrap());
+            let chunk: Result<_, PayloadError> = Ok(documents);
+            Update::DocumentAddition {
+                payload: Box::new(futures::stream::iter(vec![chunk])),
+                primary_key,
+                method,
+                merge_patch: false,
+                format: DocumentAdditionFormat::Json,
+                allow_index_creation,
+            }
+        };
+
+        let update = match self {
+            BatchOperation::AddDocuments {
+                index_uid,
+                documents,
+                primary_key,
+            } => (
+                index_uid,
+                documents_update(
+                    documents,
+                    primary_key,
+                    IndexDocumentsMethod::ReplaceDocuments,
+                ),
+            ),
+            BatchOperation::UpdateDocuments {
+                index_uid,
+                documents,
+                primary_key,
+            } => (
+                index_uid,
+                documents_update(
+                    documents,
+                    primary_key,
+                    IndexDocumentsMethod::UpdateDocuments,
+                ),
+            ),
+            BatchOperation::DeleteDocuments {
+                index_uid,
+                ids: Some(ids),
+                filter: None,
+            } => {
+                let ids = ids
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| v.to_string())
+                    })
+                    .collect();
+                (index_uid, Update::DeleteDocuments(ids))
+            }
+            BatchOperation::DeleteDocuments {
+                index_uid,
+                ids: None,
+                filter: Some(filter),
+            } => (index_uid, Update::DeleteDocumentsByFilter(filter)),
+            BatchOperation::DeleteDocuments { .. } => {
+                return Err(MeilisearchHttpError::InvalidBatchOperation(
+                    position,
+                    "a `deleteDocuments` operation must contain either `ids` or `filter`"
+                        .to_string(),
+                ))
+            }
+            BatchOperation::ClearDocuments { index_uid } => (index_uid, Update::ClearDocuments),
+            BatchOperation::UpdateSettings {
+                index_uid,
+                settings,
+            } => (
+                index_uid,
+                Update::Settings {
+                    settings,
+                    is_deletion: false,
+                    allow_index_creation,
+                },
+            ),
+        };
+
+        Ok(update)
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GroupView {
+    group_uid: GroupUid,
+    tasks: Vec<SummarizedTaskView>,
+}
+
+pub async fn register_group(
+    meilisearch: ActionGuardedData<MeiliSearch>,
+    body: web::Json<Vec<BatchOperation>>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let operations = body.into_inner();
+    debug!("called with params: {:?}", operations);
+
+    if operations.is_empty() {
+        return Err(MeilisearchHttpError::EmptyBatch.into());
+    }
+
+    // Every operation must be authorized before anything is registered.
+    let mut filters = Vec::with_capacity(operations.len());
+    for operation in &operations {
+        let index_uid = operation.index_uid();
+        filters.push(meilisearch.authorize(operation.action(), Some(index_uid))?);
+    }
+
+    analytics.publish(
+        "Batch Registered".to_string(),
+        json!({ "total_operations": operations.len() }),
+        Some(&req),
+    );
+
+    let updates = operations
+        .into_iter()
+        .zip(filters)
+        .enumerate()
+        .map(|(position, (operation, filters))| {
+            operation.into_update(position, filters.allow_index_creation)
+        })
+        .collect::<Result<_, _>>()?;
+
+    let (group_uid, tasks) = meilisearch.register_group(updates).await?;
+    let res = GroupView {
+        group_uid,
+        tasks: tasks.into_iter().map(SummarizedTaskView::from).collect(),
+    };
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Accepted().json(res))
+}
+
+/// The number of tasks of a group in each status.
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct GroupProgress {
+    enqueued: usize,
+    processing: usize,
+    succeeded: usize,
+    failed: usize,
+    canceled: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GroupStatusView {
+    uid: GroupUid,
+    total_tasks: usize,
+    progress: GroupProgress,
+    tasks: Vec<TaskView>,
+}
+
+pub async fn get_group(
+    meilisearch: ActionGuardedData<MeiliSearch>,
+    group_uid: web::Path<GroupUid>,
+) -> Result<HttpResponse, ResponseError> {
+    let group_uid = group_uid.into_inner();
+    // A group can be followed by any key allowed to see the tasks of all its indexes.
+    let filters = meilisearch.authorize(Action::TasksGet, None)?;
+    let filter = indexes_filter(&filters.search_rules, None);
+
+    let tasks: Vec<TaskView> = meilisearch
+        .get_group(group_uid, filter)
+        .await?
+        .into_iter()
+        .map(TaskView::from)
+        .collect();
+
+    let mut progress = GroupProgress::default();
+    for task in &tasks {
+        match task.status() {
+            TaskStatus::Enqueued => progress.enqueued += 1,
+            TaskStatus::Processing => progress.processing += 1,
+            TaskStatus::Succeeded => progress.succeeded += 1,
+            TaskStatus::Failed => progress.failed += 1,
+            TaskStatus::Canceled => progress.canceled += 1,
+        }
+    }
+
+    let res = GroupStatusView {
+        uid: group_uid,
+        total_tasks: tasks.len(),
+        progress,
+        tasks,
+    };
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
@@ -0,0 +1,60 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{debug, info};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::runtime_config::{RuntimeConfig, RuntimeConfigUpdate};
+use crate::Opt;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_config)))
+            .route(web::patch().to(SeqHandler(update_config))),
+    );
+}
+
+pub async fn get_config(
+    meilisearch: GuardedData<ActionPolicy<{ actions::CONFIG_GET }>, MeiliSearch>,
+    opt: web::Data<Opt>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = RuntimeConfig::current(&meilisearch, &opt).await;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn update_config(
+    meilisearch: GuardedData<ActionPolicy<{ actions::CONFIG_UPDATE }>, MeiliSearch>,
+    opt: web::Data<Opt>,
+    body: web::Json<RuntimeConfigUpdate>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let update = body.into_inner();
+
+    analytics.publish(
+        "Config Updated".to_string(),
+        json!({
+            "log_level": update.log_level.is_some(),
+            "http_payload_size_limit": update.http_payload_size_limit.is_some(),
+            "search_cutoff_ms": !update.search_cutoff_ms.is_not_set(),
+            "task_webhook_url": !update.task_webhook_url.is_not_set(),
+            "max_indexing_memory": !update.max_indexing_memory.is_not_set(),
+            "max_indexing_threads": update.max_indexing_threads.is_some(),
+        }),
+        Some(&req),
+    );
+
+    update.apply(&meilisearch, &opt).await?;
+    info!("Updated the runtime configuration.");
+
+    let res = RuntimeConfig::current(&meilisearch, &opt).await;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
@@ -1,27 +1,179 @@
-use actix_web::{web, HttpRequest, HttpResponse};
+use std::path::PathBuf;
+
+use actix_web::http::header;
+use actix_web::{guard, web, HttpRequest, HttpResponse};
 use log::debug;
+use meilisearch_auth::AuditAction;
+use meilisearch_lib::index_controller::{DumpSource, ImportConflictPolicy};
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
+use mime::Mime;
+use serde::Deserialize;
 use serde_json::json;
 
 use crate::analytics::Analytics;
+use crate::error::{MeilisearchHttpError, PayloadError};
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::payload::Payload;
 use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::audit_log;
+use crate::routes::indexes::documents::payload_to_stream;
 use crate::task::SummarizedTaskView;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("").route(web::post().to(SeqHandler(create_dump))));
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(create_dump))))
+        .service(
+            web::resource("/import")
+                // A json body gives the location of the dump, any other body is the dump itself.
+                .route(
+                    web::post()
+                        .guard(guard::fn_guard(is_json))
+                        .to(SeqHandler(import_dump_from_location)),
+                )
+                .route(web::post().to(SeqHandler(import_uploaded_dump))),
+        );
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateDumpRequest {
+    /// The uid of the dump an incremental dump is based on.
+    incremental_from: Option<String>,
 }
 
 pub async fn create_dump(
     meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_CREATE }>, MeiliSearch>,
+    body: web::Bytes,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
-    analytics.publish("Dump Created".to_string(), json!({}), Some(&req));
+    // The body is optional, a full dump is created when it's missing.
+    let params: CreateDumpRequest = if body.is_empty() {
+        CreateDumpRequest::default()
+    } else {
+        serde_json::from_slice(&body).map_err(PayloadError::MalformedPayload)?
+    };
+
+    analytics.publish(
+        "Dump Created".to_string(),
+        json!({ "incremental": params.incremental_from.is_some() }),
+        Some(&req),
+    );
+
+    let res: SummarizedTaskView = meilisearch
+        .register_dump_task(params.incremental_from.clone())
+        .await?
+        .into();
 
-    let res: SummarizedTaskView = meilisearch.register_dump_task().await?.into();
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::DumpCreated,
+        None,
+        json!({ "taskUid": res.task_uid(), "incrementalFrom": params.incremental_from }),
+    )
+    .await;
 
     debug!("returns: {:?}", res);
     Ok(HttpResponse::Accepted().json(res))
 }
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ImportDumpQuery {
+    #[serde(default)]
+    conflict: ImportConflictPolicy,
+}
+
+/// The location of a dump to import, either a `path` on the server or an `url` it is downloaded
+/// from.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ImportDumpRequest {
+    path: Option<PathBuf>,
+    url: Option<String>,
+    conflict: Option<ImportConflictPolicy>,
+}
+
+pub async fn import_dump_from_location(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_IMPORT }>, MeiliSearch>,
+    params: web::Query<ImportDumpQuery>,
+    body: web::Json<ImportDumpRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let body = body.into_inner();
+    debug!("called with params: {:?}", body);
+    // The conflict policy of the body takes precedence over the one of the query.
+    let conflict = body.conflict.unwrap_or(params.conflict);
+
+    let (source, kind) = match (body.path, body.url) {
+        (Some(path), None) => (DumpSource::Path(path), "path"),
+        (None, Some(url)) => (DumpSource::Url(url), "url"),
+        _ => return Err(MeilisearchHttpError::InvalidDumpSource.into()),
+    };
+
+    analytics.publish(
+        "Dump Imported".to_string(),
+        json!({ "source": kind, "conflict": conflict }),
+        Some(&req),
+    );
+
+    import_dump(meilisearch, source, conflict, &req, kind).await
+}
+
+pub async fn import_uploaded_dump(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_IMPORT }>, MeiliSearch>,
+    params: web::Query<ImportDumpQuery>,
+    body: Payload,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let conflict = params.into_inner().conflict;
+
+    analytics.publish(
+        "Dump Imported".to_string(),
+        json!({ "source": "upload", "conflict": conflict }),
+        Some(&req),
+    );
+
+    let source = DumpSource::Payload(Box::new(payload_to_stream(body)));
+    import_dump(meilisearch, source, conflict, &req, "upload").await
+}
+
+async fn import_dump(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_IMPORT }>, MeiliSearch>,
+    source: DumpSource,
+    conflict: ImportConflictPolicy,
+    req: &HttpRequest,
+    kind: &str,
+) -> Result<HttpResponse, ResponseError> {
+    let res: SummarizedTaskView = meilisearch
+        .register_dump_import(source, conflict)
+        .await?
+        .into();
+
+    audit_log::record(
+        req,
+        meilisearch.filters().key_uid,
+        AuditAction::DumpImported,
+        None,
+        json!({ "taskUid": res.task_uid(), "source": kind, "conflict": conflict }),
+    )
+    .await;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Accepted().json(res))
+}
+
+/// Whether the body of the request is JSON, whatever the parameters of its content type.
+fn is_json(ctx: &guard::GuardContext) -> bool {
+    ctx.head()
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Mime>().ok())
+        .map_or(false, |mime| {
+            mime.type_() == mime::APPLICATION && mime.subtype() == mime::JSON
+        })
+}
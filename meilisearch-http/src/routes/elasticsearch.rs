@@ -0,0 +1,940 @@
+//! The `/es` routes, accepting a subset of the Elasticsearch API to ease the migrations of the
+//! applications that can't rewrite all their queries at once.
+//!
+//! The `query` of a `_search` is translated to a Meilisearch search: the `match`,
+//! `match_phrase` and `multi_match` clauses give the query words, the `term`, `terms` and `range`
+//! clauses give the filter, and the `bool` clauses combine them. The clauses without an
+//! equivalent, like `fuzzy` or `script`, are rejected.
+//!
+//! The `_bulk` lines are registered as document additions, updates and deletions, the
+//! consecutive operations of the same kind on the same index sharing a task.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use actix_web::error::{JsonPayloadError, PayloadError as ActixPayloadError};
+use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures::StreamExt;
+use log::debug;
+use meilisearch_auth::Action;
+use meilisearch_lib::index::SearchQuery;
+use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
+use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::{json, Map, Value};
+
+use crate::analytics::{Analytics, SearchAggregator};
+use crate::error::{MeilisearchHttpError, PayloadError};
+use crate::extractors::authentication::{policies::*, ActionGuardedData, GuardedData};
+use crate::extractors::payload::Payload;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::option::Opt;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/_bulk").route(web::post().to(SeqHandler(bulk))))
+        .service(web::resource("/{index_uid}/_search").route(web::post().to(SeqHandler(search))))
+        .service(web::resource("/{index_uid}/_bulk").route(web::post().to(SeqHandler(index_bulk))));
+}
+
+/// The number of hits returned by Elasticsearch when the `size` is missing.
+const DEFAULT_SIZE: usize = 10;
+
+pub async fn search(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<Value>,
+    opt: web::Data<Opt>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    check_enabled(&opt, &meilisearch)?;
+    let body = body.into_inner();
+    debug!("elasticsearch search called with body: {:?}", body);
+
+    let index_uid = path.into_inner();
+    let primary_key = meilisearch
+        .get_index(index_uid.clone())
+        .await?
+        .meta
+        .primary_key;
+
+    let source = Source::from_body(&body)?;
+    let mut query = translate(&body, &source, primary_key.as_deref())?;
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        add_search_rules(&mut query, search_rules)?;
+    }
+
+    let mut aggregate = SearchAggregator::from_query(&query, &req);
+    let result = meilisearch.search(index_uid.clone(), query).await;
+    if let Ok(ref result) = result {
+        aggregate.succeed(result);
+    }
+    analytics.post_search(aggregate);
+
+    let result = json!(result?);
+    let hits: Vec<_> = result["hits"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|hit| es_hit(&index_uid, hit, &source, primary_key.as_deref()))
+        .collect();
+    let total = result["estimatedTotalHits"]
+        .as_u64()
+        .or_else(|| result["totalHits"].as_u64())
+        .unwrap_or_default();
+
+    let response = json!({
+        "took": result["processingTimeMs"],
+        "timed_out": result.get("degraded").is_some(),
+        "_shards": { "total": 1, "successful": 1, "skipped": 0, "failed": 0 },
+        "hits": {
+            "total": { "value": total, "relation": "eq" },
+            "max_score": null,
+            "hits": hits,
+        },
+    });
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+pub(crate) fn check_enabled(
+    opt: &Opt,
+    meilisearch: &MeiliSearch,
+) -> Result<(), MeilisearchHttpError> {
+    if !opt.experimental_enable_elasticsearch_compat
+        && !meilisearch.experimental_features().elasticsearch_compat
+    {
+        return Err(MeilisearchHttpError::FeatureNotEnabled(
+            "Elasticsearch compatibility",
+            "--experimental-enable-elasticsearch-compat",
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(message: impl Into<String>) -> MeilisearchHttpError {
+    MeilisearchHttpError::InvalidElasticsearchRequest(message.into())
+}
+
+/// The `_source` of the request, the attributes of the documents to return.
+enum Source {
+    All,
+    None,
+    Only(Vec<String>),
+}
+
+impl Source {
+    fn from_body(body: &Value) -> Result<Self, MeilisearchHttpError> {
+        let fields = match body.get("_source") {
+            None | Some(Value::Bool(true)) => return Ok(Source::All),
+            Some(Value::Bool(false)) => return Ok(Source::None),
+            Some(Value::Object(source)) if source.keys().all(|key| key == "includes") => {
+                source.get("includes").cloned().unwrap_or_default()
+            }
+            Some(fields) => fields.clone(),
+        };
+        match fields {
+            Value::String(field) => Ok(Source::Only(vec![field])),
+            Value::Array(fields) => fields
+                .into_iter()
+                .map(|field| match field {
+                    Value::String(field) => Ok(field),
+                    _ => Err(invalid("`_source` must contain attribute names")),
+                })
+                .collect::<Result<_, _>>()
+                .map(Source::Only),
+            _ => Err(invalid(
+                "`_source` must be a boolean, an attribute or a list of attributes",
+            )),
+        }
+    }
+}
+
+/// What the clauses of the query translate to.
+#[derive(Default)]
+struct Translation {
+    words: Vec<String>,
+    /// The attributes the words are searched on.
+    attributes: Vec<String>,
+    /// Whether some words are searched on all the searchable attributes.
+    all_attributes: bool,
+    all_words: bool,
+}
+
+impl Translation {
+    fn add_words(&mut self, words: String, attributes: Option<Vec<String>>) {
+        match attributes {
+            Some(attributes) => self.attributes.extend(attributes),
+            None => self.all_attributes = true,
+        }
+        self.words.push(words);
+    }
+}
+
+/// Translates an Elasticsearch search request to a Meilisearch search.
+fn translate(
+    body: &Value,
+    source: &Source,
+    primary_key: Option<&str>,
+) -> Result<SearchQuery, MeilisearchHttpError> {
+    let mut translation = Translation::default();
+    let filter = match body.get("query") {
+        Some(query) => clause(query, &mut translation)?,
+        None => None,
+    };
+
+    let mut query = Map::new();
+    if !translation.words.is_empty() {
+        query.insert("q".to_string(), json!(translation.words.join(" ")));
+        if !translation.all_attributes {
+            query.insert(
+                "attributesToSearchOn".to_string(),
+                json!(translation.attributes),
+            );
+        }
+    }
+    if translation.all_words {
+        query.insert("matchingStrategy".to_string(), json!("all"));
+    }
+    if let Some(filter) = filter {
+        query.insert("filter".to_string(), json!(filter));
+    }
+
+    query.insert("offset".to_string(), json!(usize_param(body, "from")?));
+    let size = usize_param(body, "size")?.unwrap_or(DEFAULT_SIZE);
+    query.insert("limit".to_string(), json!(size));
+
+    if let Some(sort) = body.get("sort") {
+        query.insert("sort".to_string(), json!(sort_rules(sort)?));
+    }
+
+    match source {
+        Source::All => (),
+        Source::None => {
+            let fields: Vec<_> = primary_key.into_iter().collect();
+            query.insert("attributesToRetrieve".to_string(), json!(fields));
+        }
+        Source::Only(fields) => {
+            let mut fields = fields.clone();
+            fields.extend(primary_key.map(String::from));
+            query.insert("attributesToRetrieve".to_string(), json!(fields));
+        }
+    }
+
+    serde_json::from_value(Value::Object(query)).map_err(|e| invalid(e.to_string()))
+}
+
+fn usize_param(body: &Value, name: &str) -> Result<Option<usize>, MeilisearchHttpError> {
+    match body.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(|value| Some(value as usize))
+            .ok_or_else(|| invalid(format!("`{}` must be a positive integer", name))),
+    }
+}
+
+/// Translates a clause of the query, returning its filter. The query words are added to the
+/// `translation`.
+fn clause(
+    query: &Value,
+    translation: &mut Translation,
+) -> Result<Option<String>, MeilisearchHttpError> {
+    let (kind, params) = single_entry(query, "query clause")?;
+    match kind.as_str() {
+        "match_all" => Ok(None),
+        "match" | "match_phrase" => {
+            let (field, params) = single_entry(params, kind)?;
+            let (text, operator) = match params {
+                Value::Object(params) => (
+                    params.get("query").cloned().unwrap_or_default(),
+                    params.get("operator").and_then(Value::as_str),
+                ),
+                text => (text.clone(), None),
+            };
+            let mut text = scalar_text(&text, kind)?;
+            if kind == "match_phrase" {
+                text = format!("\"{}\"", text.replace('"', " "));
+            }
+            if operator.map_or(false, |operator| operator.eq_ignore_ascii_case("and")) {
+                translation.all_words = true;
+            }
+            translation.add_words(text, search_attributes(std::iter::once(field.as_str())));
+            Ok(None)
+        }
+        "multi_match" => {
+            let text = scalar_text(&params["query"], kind)?;
+            let fields = match &params["fields"] {
+                Value::Null => None,
+                Value::Array(fields) => {
+                    let fields: Option<Vec<&str>> = fields.iter().map(Value::as_str).collect();
+                    let fields =
+                        fields.ok_or_else(|| invalid("`multi_match.fields` must be strings"))?;
+                    search_attributes(fields.into_iter())
+                }
+                _ => return Err(invalid("`multi_match.fields` must be a list of attributes")),
+            };
+            if params["operator"]
+                .as_str()
+                .map_or(false, |operator| operator.eq_ignore_ascii_case("and"))
+            {
+                translation.all_words = true;
+            }
+            translation.add_words(text, fields);
+            Ok(None)
+        }
+        "term" => {
+            let (field, value) = single_entry(params, kind)?;
+            let value = match value {
+                Value::Object(params) => params.get("value").cloned().unwrap_or_default(),
+                value => value.clone(),
+            };
+            Ok(Some(format!("{} = {}", field, filter_value(&value)?)))
+        }
+        "terms" => {
+            let (field, values) = single_entry(params, kind)?;
+            let values = values
+                .as_array()
+                .filter(|values| !values.is_empty())
+                .ok_or_else(|| invalid("`terms` must contain a non-empty list of values"))?;
+            let conditions = values
+                .iter()
+                .map(|value| Ok(format!("{} = {}", field, filter_value(value)?)))
+                .collect::<Result<Vec<_>, MeilisearchHttpError>>()?;
+            Ok(Some(format!("({})", conditions.join(" OR "))))
+        }
+        "range" => {
+            let (field, bounds) = single_entry(params, kind)?;
+            let bounds = bounds
+                .as_object()
+                .ok_or_else(|| invalid("`range` bounds must be an object"))?;
+            let mut conditions = Vec::new();
+            for (bound, value) in bounds {
+                let operator = match bound.as_str() {
+                    "gt" => ">",
+                    "gte" => ">=",
+                    "lt" => "<",
+                    "lte" => "<=",
+                    "format" | "boost" => continue,
+                    bound => {
+                        return Err(invalid(format!(
+                            "the `range` bound `{}` is not supported",
+                            bound
+                        )))
+                    }
+                };
+                conditions.push(format!("{} {} {}", field, operator, filter_value(value)?));
+            }
+            if conditions.is_empty() {
+                return Err(invalid("`range` must contain at least one bound"));
+            }
+            Ok(Some(format!("({})", conditions.join(" AND "))))
+        }
+        "bool" => bool_clause(params, translation),
+        kind => Err(invalid(format!("the `{}` query is not supported", kind))),
+    }
+}
+
+fn bool_clause(
+    params: &Value,
+    translation: &mut Translation,
+) -> Result<Option<String>, MeilisearchHttpError> {
+    let params = params
+        .as_object()
+        .ok_or_else(|| invalid("`bool` must be an object"))?;
+    let clauses = |name: &str| match params.get(name) {
+        None => Vec::new(),
+        Some(Value::Array(clauses)) => clauses.iter().collect(),
+        Some(clause) => vec![clause],
+    };
+    if let Some(name) = params.keys().find(|name| {
+        !matches!(
+            name.as_str(),
+            "must" | "filter" | "should" | "must_not" | "minimum_should_match" | "boost"
+        )
+    }) {
+        return Err(invalid(format!(
+            "the `bool.{}` clause is not supported",
+            name
+        )));
+    }
+
+    let words = translation.words.len();
+    let mut filters = Vec::new();
+    for query in clauses("must").into_iter().chain(clauses("filter")) {
+        filters.extend(clause(query, translation)?);
+    }
+
+    // Without a `must` or a `filter`, at least one of the `should` clauses must match, otherwise
+    // they only change the ranking and their filters are ignored.
+    let should = clauses("should");
+    let should_match = filters.is_empty() && translation.words.len() == words
+        || params.get("minimum_should_match").map_or(false, |minimum| {
+            minimum != &json!(0) && minimum != &json!("0")
+        });
+    let mut alternatives = Vec::new();
+    for query in should {
+        alternatives.extend(clause(query, translation)?);
+    }
+    if should_match && !alternatives.is_empty() {
+        filters.push(format!("({})", alternatives.join(" OR ")));
+    }
+
+    for query in clauses("must_not") {
+        let mut excluded = Translation::default();
+        match clause(query, &mut excluded)? {
+            Some(filter) if excluded.words.is_empty() => filters.push(format!("NOT {}", filter)),
+            _ => {
+                return Err(invalid(
+                    "`bool.must_not` only supports the `term`, `terms`, `range` and `bool` queries",
+                ))
+            }
+        }
+    }
+
+    Ok(match filters.len() {
+        0 => None,
+        1 => filters.pop(),
+        _ => Some(format!("({})", filters.join(" AND "))),
+    })
+}
+
+/// Returns the only entry of an object, e.g. the field and the parameters of `{"title": "x"}`.
+fn single_entry<'a>(
+    value: &'a Value,
+    what: &str,
+) -> Result<(&'a String, &'a Value), MeilisearchHttpError> {
+    match value.as_object() {
+        Some(object) if object.len() == 1 => Ok(object.iter().next().unwrap()),
+        _ => Err(invalid(format!(
+            "a `{}` must be an object with a single entry",
+            what
+        ))),
+    }
+}
+
+fn scalar_text(value: &Value, kind: &str) -> Result<String, MeilisearchHttpError> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(boolean) => Ok(boolean.to_string()),
+        _ => Err(invalid(format!(
+            "the text of a `{}` must be a string",
+            kind
+        ))),
+    }
+}
+
+/// Returns the attributes to search on, without the boosts of the `title^2` syntax, or `None`
+/// to search on all the attributes.
+fn search_attributes<'a>(fields: impl Iterator<Item = &'a str>) -> Option<Vec<String>> {
+    let mut attributes = Vec::new();
+    for field in fields {
+        let field = field.split('^').next().unwrap_or_default();
+        if field == "*" || field == "_all" {
+            return None;
+        }
+        attributes.push(field.to_string());
+    }
+    Some(attributes)
+}
+
+/// Formats a value of a `term` or a `range` for a filter expression.
+fn filter_value(value: &Value) -> Result<String, MeilisearchHttpError> {
+    match value {
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(boolean) => Ok(boolean.to_string()),
+        Value::String(text) if !text.contains('"') => Ok(format!("\"{}\"", text)),
+        Value::String(text) if !text.contains('\'') => Ok(format!("'{}'", text)),
+        Value::String(_) => Err(invalid(
+            "a value can't contain both a single and a double quote",
+        )),
+        _ => Err(invalid("the values must be strings, numbers or booleans")),
+    }
+}
+
+/// Translates the `sort` of the request, e.g. `[{"year": {"order": "desc"}}, "title"]`.
+fn sort_rules(sort: &Value) -> Result<Vec<String>, MeilisearchHttpError> {
+    let rules = match sort {
+        Value::Array(rules) => rules.iter().collect(),
+        rule => vec![rule],
+    };
+
+    let mut sort = Vec::new();
+    for rule in rules {
+        let (field, order) = match rule {
+            Value::String(field) => (field, None),
+            rule => {
+                let (field, order) = single_entry(rule, "sort")?;
+                let order = match order {
+                    Value::Object(params) => params.get("order"),
+                    order => Some(order),
+                };
+                (field, order.and_then(Value::as_str))
+            }
+        };
+        // the documents are already ranked by relevancy.
+        if field == "_score" {
+            continue;
+        }
+        let order = match order {
+            None | Some("asc") => "asc",
+            Some("desc") => "desc",
+            Some(order) => return Err(invalid(format!("the sort order `{}` is invalid", order))),
+        };
+        sort.push(format!("{}:{}", field, order));
+    }
+    Ok(sort)
+}
+
+/// Formats a hit of Meilisearch like a hit of Elasticsearch.
+fn es_hit(index_uid: &str, hit: &Value, source: &Source, primary_key: Option<&str>) -> Value {
+    let mut document = hit.as_object().cloned().unwrap_or_default();
+    document.retain(|key, _| !key.starts_with('_'));
+    let id = primary_key
+        .and_then(|key| document.get(key))
+        .map(|id| match id {
+            Value::String(id) => id.clone(),
+            id => id.to_string(),
+        });
+
+    let mut hit = json!({ "_index": index_uid, "_id": id, "_score": null });
+    match source {
+        Source::All => hit["_source"] = Value::Object(document),
+        Source::None => (),
+        Source::Only(fields) => {
+            document.retain(|key, _| fields.contains(key));
+            hit["_source"] = Value::Object(document);
+        }
+    }
+    hit
+}
+
+/// The kinds of operations of a `_bulk` request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BulkAction {
+    /// `index` and `create`, adding or replacing a document.
+    Index,
+    /// `update`, changing some fields of a document.
+    Update,
+    Delete,
+}
+
+/// An operation of a `_bulk` request, made of an action line and a document line except for
+/// the deletions.
+#[derive(Debug)]
+struct BulkOperation {
+    /// The name of the action in the request, repeated in the response.
+    name: String,
+    action: BulkAction,
+    index_uid: String,
+    id: Option<String>,
+    document: Option<Map<String, Value>>,
+}
+
+pub async fn bulk(
+    meilisearch: ActionGuardedData<MeiliSearch>,
+    body: Payload,
+    opt: web::Data<Opt>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    register_bulk(meilisearch, None, body, opt, req, analytics).await
+}
+
+pub async fn index_bulk(
+    meilisearch: ActionGuardedData<MeiliSearch>,
+    path: web::Path<String>,
+    body: Payload,
+    opt: web::Data<Opt>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let index_uid = path.into_inner();
+    register_bulk(meilisearch, Some(index_uid), body, opt, req, analytics).await
+}
+
+async fn register_bulk(
+    meilisearch: ActionGuardedData<MeiliSearch>,
+    default_index: Option<String>,
+    mut body: Payload,
+    opt: web::Data<Opt>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    check_enabled(&opt, &meilisearch)?;
+    let started_at = Instant::now();
+
+    let mut buffer = Vec::new();
+    while let Some(bytes) = body.next().await {
+        let bytes = bytes.map_err(|e| PayloadError::from(JsonPayloadError::Payload(e)))?;
+        buffer.extend_from_slice(&bytes);
+    }
+    let operations = parse_bulk(&buffer, default_index.as_deref())?;
+    debug!(
+        "elasticsearch bulk called with {} operations",
+        operations.len()
+    );
+
+    // Every operation must be authorized before anything is registered.
+    let mut allow_index_creation = HashMap::new();
+    for operation in &operations {
+        let action = match operation.action {
+            BulkAction::Index | BulkAction::Update => Action::DocumentsAdd,
+            BulkAction::Delete => Action::DocumentsDelete,
+        };
+        let filters = meilisearch.authorize(action, Some(&operation.index_uid))?;
+        let allowed = allow_index_creation
+            .entry(operation.index_uid.clone())
+            .or_insert(true);
+        *allowed &= filters.allow_index_creation;
+    }
+
+    // The `_id` of the documents is set as their primary key, `id` for the new indexes.
+    let mut primary_keys = HashMap::new();
+    for uid in allow_index_creation.keys() {
+        let primary_key = match meilisearch.get_index(uid.clone()).await {
+            Ok(index) => index.meta.primary_key,
+            Err(_) => None,
+        };
+        primary_keys.insert(uid.clone(), primary_key);
+    }
+
+    analytics.publish(
+        "Elasticsearch Bulk".to_string(),
+        json!({ "total_operations": operations.len() }),
+        Some(&req),
+    );
+
+    let groups = group_operations(&operations);
+    let mut updates = Vec::with_capacity(groups.len());
+    for group in &groups {
+        let first = &group[0];
+        let primary_key = primary_keys[&first.index_uid].clone();
+        let update = match first.action {
+            BulkAction::Delete => Update::DeleteDocuments(
+                group
+                    .iter()
+                    .filter_map(|operation| operation.id.clone())
+                    .collect(),
+            ),
+            action => {
+                let key = primary_key.clone().unwrap_or_else(|| "id".to_string());
+                let has_ids = group.iter().any(|operation| operation.id.is_some());
+                let documents: Vec<_> = group
+                    .iter()
+                    .map(|operation| {
+                        let mut document = operation.document.clone().unwrap_or_default();
+                        if let Some(ref id) = operation.id {
+                            document.insert(key.clone(), Value::String(id.clone()));
+                        }
+                        document
+                    })
+                    .collect();
+                let chunk: Result<_, ActixPayloadError> =
+                    Ok(Bytes::from(serde_json::to_vec(&documents).unwrap()));
+                Update::DocumentAddition {
+                    payload: Box::new(futures::stream::iter(vec![chunk])),
+                    primary_key: match primary_key {
+                        None if has_ids => Some(key),
+                        primary_key => primary_key,
+                    },
+                    method: match action {
+                        BulkAction::Update => IndexDocumentsMethod::UpdateDocuments,
+                        _ => IndexDocumentsMethod::ReplaceDocuments,
+                    },
+                    merge_patch: false,
+                    format: DocumentAdditionFormat::Json,
+                    allow_index_creation: allow_index_creation[&first.index_uid],
+                }
+            }
+        };
+        updates.push((first.index_uid.clone(), update));
+    }
+
+    let (_, tasks) = meilisearch.register_group(updates).await?;
+
+    let mut items = Vec::with_capacity(operations.len());
+    for (group, task) in groups.iter().zip(tasks) {
+        for operation in group.iter() {
+            items.push(json!({
+                operation.name.as_str(): {
+                    "_index": operation.index_uid,
+                    "_id": operation.id,
+                    "status": 202,
+                    "result": "enqueued",
+                    "taskUid": task.id,
+                }
+            }));
+        }
+    }
+    let response = json!({
+        "took": started_at.elapsed().as_millis(),
+        "errors": false,
+        "items": items,
+    });
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Parses the NDJSON lines of a `_bulk` request, the operations without an `_index` target the
+/// `default_index`.
+fn parse_bulk(
+    body: &[u8],
+    default_index: Option<&str>,
+) -> Result<Vec<BulkOperation>, MeilisearchHttpError> {
+    let body = std::str::from_utf8(body).map_err(|_| invalid("the body must be UTF-8"))?;
+    let mut lines = body
+        .lines()
+        .enumerate()
+        .map(|(number, line)| (number + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+    let parse = |number: usize, line: &str| -> Result<Value, MeilisearchHttpError> {
+        serde_json::from_str(line)
+            .map_err(|e| invalid(format!("the line {} is not valid JSON: {}", number, e)))
+    };
+
+    let mut operations = Vec::new();
+    while let Some((number, line)) = lines.next() {
+        let line = parse(number, line)?;
+        let (name, metadata) = single_entry(&line, "bulk action")
+            .map_err(|_| invalid(format!("the line {} must be an action", number)))?;
+        let action = match name.as_str() {
+            "index" | "create" => BulkAction::Index,
+            "update" => BulkAction::Update,
+            "delete" => BulkAction::Delete,
+            name => {
+                return Err(invalid(format!(
+                    "the bulk action `{}` of the line {} is not supported",
+                    name, number
+                )))
+            }
+        };
+
+        let index_uid = match metadata["_index"].as_str().or(default_index) {
+            Some(index_uid) => index_uid.to_string(),
+            None => {
+                return Err(invalid(format!(
+                    "the action of the line {} must have an `_index`",
+                    number
+                )))
+            }
+        };
+        let id = match &metadata["_id"] {
+            Value::Null => None,
+            Value::String(id) => Some(id.clone()),
+            id => Some(id.to_string()),
+        };
+        if id.is_none() && action != BulkAction::Index {
+            return Err(invalid(format!(
+                "the `{}` action of the line {} must have an `_id`",
+                name, number
+            )));
+        }
+
+        let document = match action {
+            BulkAction::Delete => None,
+            action => {
+                let (number, line) = lines.next().ok_or_else(|| {
+                    invalid(format!("the action of the line {} has no document", number))
+                })?;
+                let mut document = match parse(number, line)? {
+                    Value::Object(document) => document,
+                    _ => return Err(invalid(format!("the line {} must be a document", number))),
+                };
+                if action == BulkAction::Update {
+                    if document.contains_key("script") {
+                        return Err(invalid("the `script` updates are not supported"));
+                    }
+                    document = match document.remove("doc") {
+                        Some(Value::Object(doc)) => doc,
+                        _ => {
+                            return Err(invalid(format!(
+                                "the update of the line {} must contain a `doc` object",
+                                number
+                            )))
+                        }
+                    };
+                }
+                Some(document)
+            }
+        };
+
+        operations.push(BulkOperation {
+            name: name.clone(),
+            action,
+            index_uid,
+            id,
+            document,
+        });
+    }
+
+    if operations.is_empty() {
+        return Err(invalid("the bulk request must contain at least one action"));
+    }
+    Ok(operations)
+}
+
+/// Groups the consecutive operations of the same kind on the same index, each group is
+/// registered as a task.
+fn group_operations(operations: &[BulkOperation]) -> Vec<&[BulkOperation]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for end in 1..=operations.len() {
+        if end == operations.len()
+            || operations[end].action != operations[start].action
+            || operations[end].index_uid != operations[start].index_uid
+        {
+            groups.push(&operations[start..end]);
+            start = end;
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod test {
+    use meilisearch_lib::index::MatchingStrategy;
+
+    use super::*;
+
+    fn translate_query(body: Value) -> Result<SearchQuery, MeilisearchHttpError> {
+        let source = Source::from_body(&body)?;
+        translate(&body, &source, Some("id"))
+    }
+
+    #[test]
+    fn translate_a_bool_query() {
+        let query = translate_query(json!({
+            "query": {
+                "bool": {
+                    "must": [{ "match": { "title": { "query": "dark knight", "operator": "and" } } }],
+                    "filter": [
+                        { "term": { "genre": "action" } },
+                        { "range": { "year": { "gte": 2000, "lt": 2010 } } },
+                    ],
+                    "must_not": { "terms": { "rating": ["R", "NC-17"] } },
+                }
+            },
+            "from": 10,
+            "size": 5,
+            "sort": [{ "year": { "order": "desc" } }, "_score"],
+            "_source": ["title"],
+        }))
+        .unwrap();
+
+        assert_eq!(query.q.as_deref(), Some("dark knight"));
+        assert_eq!(
+            query.attributes_to_search_on,
+            Some(vec!["title".to_string()])
+        );
+        assert_eq!(query.matching_strategy, MatchingStrategy::All);
+        assert_eq!(
+            query.filter,
+            Some(json!(
+                "(genre = \"action\" AND (year >= 2000 AND year < 2010) AND NOT (rating = \"R\" OR rating = \"NC-17\"))"
+            ))
+        );
+        assert_eq!(query.offset, Some(10));
+        assert_eq!(query.limit, 5);
+        assert_eq!(query.sort, Some(vec!["year:desc".to_string()]));
+        assert_eq!(
+            query.attributes_to_retrieve,
+            Some(
+                ["title".to_string(), "id".to_string()]
+                    .into_iter()
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    fn translate_the_should_clauses() {
+        let query = translate_query(json!({
+            "query": { "bool": { "should": [
+                { "term": { "genre": "drama" } },
+                { "term": { "genre": "comedy" } },
+            ] } }
+        }))
+        .unwrap();
+        assert_eq!(
+            query.filter,
+            Some(json!("(genre = \"drama\" OR genre = \"comedy\")"))
+        );
+        assert_eq!(query.limit, DEFAULT_SIZE);
+
+        let query = translate_query(json!({
+            "query": { "multi_match": { "query": "carol", "fields": ["title^3", "overview"] } }
+        }))
+        .unwrap();
+        assert_eq!(query.q.as_deref(), Some("carol"));
+        assert_eq!(
+            query.attributes_to_search_on,
+            Some(vec!["title".to_string(), "overview".to_string()])
+        );
+        assert_eq!(query.filter, None);
+    }
+
+    #[test]
+    fn reject_the_unsupported_queries() {
+        assert!(translate_query(json!({ "query": { "fuzzy": { "title": "carl" } } })).is_err());
+        assert!(translate_query(json!({ "query": { "term": { "a": "x", "b": "y" } } })).is_err());
+        assert!(translate_query(json!({
+            "query": { "bool": { "must_not": { "match": { "title": "carol" } } } }
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn parse_a_bulk_request() {
+        let body = br#"
+{ "index": { "_index": "movies", "_id": "1" } }
+{ "title": "Carol" }
+{ "create": { "_id": 2 } }
+{ "title": "Wonder Woman" }
+{ "update": { "_id": "1" } }
+{ "doc": { "year": 2015 } }
+{ "delete": { "_index": "books", "_id": "3" } }
+"#;
+        let operations = parse_bulk(body, Some("movies")).unwrap();
+        assert_eq!(operations.len(), 4);
+        assert_eq!(operations[1].name, "create");
+        assert_eq!(operations[1].action, BulkAction::Index);
+        assert_eq!(operations[1].id.as_deref(), Some("2"));
+        assert_eq!(
+            operations[2].document,
+            Some(json!({ "year": 2015 }).as_object().unwrap().clone())
+        );
+        assert_eq!(operations[3].index_uid, "books");
+        assert_eq!(operations[3].document, None);
+
+        let groups = group_operations(&operations);
+        let sizes: Vec<_> = groups.iter().map(|group| group.len()).collect();
+        assert_eq!(sizes, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn reject_the_invalid_bulk_requests() {
+        // without an index.
+        assert!(parse_bulk(br#"{ "delete": { "_id": "1" } }"#, None).is_err());
+        // without a document.
+        assert!(parse_bulk(br#"{ "index": { "_id": "1" } }"#, Some("movies")).is_err());
+        // an update without an id.
+        let body = b"{ \"update\": {} }\n{ \"doc\": {} }";
+        assert!(parse_bulk(body, Some("movies")).is_err());
+        // a scripted update.
+        let body = b"{ \"update\": { \"_id\": 1 } }\n{ \"script\": \"ctx\" }";
+        assert!(parse_bulk(body, Some("movies")).is_err());
+        assert!(parse_bulk(b"\n", Some("movies")).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::experimental_features::RuntimeFeaturesUpdate;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_features)))
+            .route(web::patch().to(SeqHandler(update_features))),
+    );
+}
+
+pub async fn get_features(
+    meilisearch: GuardedData<ActionPolicy<{ actions::EXPERIMENTAL_FEATURES_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = meilisearch.experimental_features();
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn update_features(
+    meilisearch: GuardedData<ActionPolicy<{ actions::EXPERIMENTAL_FEATURES_UPDATE }>, MeiliSearch>,
+    body: web::Json<RuntimeFeaturesUpdate>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let update = body.into_inner();
+
+    analytics.publish(
+        "Experimental Features Updated".to_string(),
+        json!({
+            "metrics": update.metrics,
+            "elasticsearch_compat": update.elasticsearch_compat,
+            "search_analytics": update.search_analytics,
+        }),
+        Some(&req),
+    );
+
+    let res = meilisearch.update_experimental_features(update).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
@@ -0,0 +1,534 @@
+//! The `/graphql` route, serving the search, the multi-search and the documents as a GraphQL
+//! schema. The schema is built for each request from the indexes the key can access: each index
+//! gets a document type with a field per attribute of its field distribution, e.g. the `movies`
+//! index is queried with `moviesSearch`, `moviesDocument` and `moviesDocuments`.
+
+use std::collections::HashSet;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Scalar, Schema, TypeRef,
+};
+use async_graphql::{ErrorExtensions, Request, Value as GraphqlValue};
+use futures::future::try_join_all;
+use log::{debug, warn};
+use meilisearch_auth::{Action, SearchRules};
+use meilisearch_lib::index::SearchQuery;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::{Code, ResponseError};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::ActionGuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(graphql))));
+}
+
+/// The scalar of the attributes and of the other values without a fixed type.
+const JSON: &str = "JSON";
+const MULTI_SEARCH_RESULT: &str = "MultiSearchResult";
+/// The deepest query accepted, the documents are nested twice at most.
+const MAX_QUERY_DEPTH: usize = 16;
+
+/// The data shared by the resolvers of a request.
+struct Context {
+    meilisearch: MeiliSearch,
+    /// The indexes the key can search, `None` if it can't search at all.
+    search_rules: Option<SearchRules>,
+}
+
+/// An index of the schema, with the operations the key can perform on it.
+struct IndexSchema {
+    uid: String,
+    attributes: Vec<String>,
+    search: bool,
+    documents: bool,
+}
+
+pub async fn graphql(
+    meilisearch: ActionGuardedData<MeiliSearch>,
+    request: web::Json<Request>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let request = request.into_inner();
+    debug!("graphql called with query: {:?}", request.query);
+
+    analytics.publish(
+        "GraphQL POST".to_string(),
+        json!({ "query_length": request.query.len() }),
+        Some(&req),
+    );
+
+    let search_rules = meilisearch
+        .authorize(Action::Search, None)
+        .ok()
+        .map(|filters| filters.search_rules);
+
+    let mut indexes = Vec::new();
+    for index in meilisearch.list_indexes().await? {
+        let search = search_rules
+            .as_ref()
+            .map_or(false, |rules| rules.is_index_authorized(&index.uid));
+        let documents = meilisearch
+            .authorize(Action::DocumentsGet, Some(&index.uid))
+            .is_ok();
+        if !search && !documents {
+            continue;
+        }
+
+        let stats = meilisearch
+            .get_index_stats(index.uid.clone(), false)
+            .await?;
+        indexes.push(IndexSchema {
+            uid: index.uid,
+            attributes: stats.field_distribution.into_keys().collect(),
+            search,
+            documents,
+        });
+    }
+
+    // a key that can neither search nor get the documents is refused like on the other routes.
+    if search_rules.is_none() && indexes.is_empty() {
+        meilisearch.authorize(Action::Search, None)?;
+    }
+
+    let context = Context {
+        meilisearch: MeiliSearch::clone(&meilisearch),
+        search_rules,
+    };
+    let schema = build_schema(indexes, context)
+        .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))?;
+    let response = schema.execute(request).await;
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+fn build_schema(
+    indexes: Vec<IndexSchema>,
+    context: Context,
+) -> Result<Schema, async_graphql::dynamic::SchemaError> {
+    let mut query = Object::new("Query").field(
+        Field::new(
+            "multiSearch",
+            TypeRef::named_nn_list_nn(MULTI_SEARCH_RESULT),
+            |ctx| FieldFuture::new(multi_search(ctx)),
+        )
+        .argument(InputValue::new("queries", TypeRef::named_nn_list_nn(JSON))),
+    );
+    let mut multi_search_result = Object::new(MULTI_SEARCH_RESULT)
+        .field(value_field("indexUid", TypeRef::named_nn(TypeRef::STRING)))
+        .field(value_field("hits", TypeRef::named_nn_list_nn(JSON)));
+    multi_search_result = result_fields(multi_search_result);
+
+    let mut types = Vec::new();
+    let mut names = HashSet::new();
+    for index in indexes {
+        let name = graphql_name(&index.uid);
+        let type_name = pascal_case(&name);
+        if !names.insert(type_name.clone()) {
+            warn!(
+                "The index `{}` is not exposed with GraphQL, its name collides with another index.",
+                index.uid
+            );
+            continue;
+        }
+
+        let document = format!("{}Document", type_name);
+        types.push(document_type(&document, &index.attributes));
+
+        if index.search {
+            let result = format!("{}SearchResult", type_name);
+            let hits = Object::new(&result).field(documents_field("hits", &document));
+            types.push(result_fields(hits));
+
+            let uid = index.uid.clone();
+            let field = Field::new(
+                format!("{}Search", name),
+                TypeRef::named_nn(&result),
+                move |ctx| FieldFuture::new(search(ctx, uid.clone())),
+            );
+            query = query.field(search_arguments(field));
+        }
+
+        if index.documents {
+            let page = format!("{}DocumentPage", type_name);
+            types.push(
+                Object::new(&page)
+                    .field(documents_field("results", &document))
+                    .field(value_field("offset", TypeRef::named_nn(TypeRef::INT)))
+                    .field(value_field("limit", TypeRef::named_nn(TypeRef::INT)))
+                    .field(value_field("total", TypeRef::named_nn(TypeRef::INT))),
+            );
+
+            let uid = index.uid.clone();
+            query = query.field(
+                Field::new(
+                    format!("{}Document", name),
+                    TypeRef::named(&document),
+                    move |ctx| FieldFuture::new(get_document(ctx, uid.clone())),
+                )
+                .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::ID)))
+                .argument(InputValue::new(
+                    "fields",
+                    TypeRef::named_nn_list(TypeRef::STRING),
+                )),
+            );
+
+            let uid = index.uid.clone();
+            query = query.field(
+                Field::new(
+                    format!("{}Documents", name),
+                    TypeRef::named_nn(&page),
+                    move |ctx| FieldFuture::new(get_documents(ctx, uid.clone())),
+                )
+                .argument(InputValue::new("offset", TypeRef::named(TypeRef::INT)))
+                .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+                .argument(InputValue::new(
+                    "fields",
+                    TypeRef::named_nn_list(TypeRef::STRING),
+                ))
+                .argument(InputValue::new("filter", TypeRef::named(JSON)))
+                .argument(InputValue::new(
+                    "sort",
+                    TypeRef::named_nn_list(TypeRef::STRING),
+                )),
+            );
+        }
+    }
+
+    let mut schema = Schema::build(query.type_name(), None, None)
+        .register(Scalar::new(JSON))
+        .register(multi_search_result)
+        .register(query);
+    for object in types {
+        schema = schema.register(object);
+    }
+    schema.data(context).limit_depth(MAX_QUERY_DEPTH).finish()
+}
+
+/// Returns the type of the documents of an index, with a field per attribute and the `_raw`
+/// document.
+fn document_type(name: &str, attributes: &[String]) -> Object {
+    let mut object = Object::new(name)
+        .field(Field::new("_raw", TypeRef::named_nn(JSON), |ctx| {
+            FieldFuture::new(async move {
+                let document = ctx.parent_value.try_downcast_ref::<Value>()?;
+                json_value(document.clone()).map(Some)
+            })
+        }))
+        .field(value_field("_formatted", TypeRef::named(JSON)));
+
+    let mut fields: HashSet<_> = ["_raw", "_formatted"]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+    for attribute in attributes {
+        let field = graphql_name(attribute);
+        if field.starts_with("__") || !fields.insert(field.clone()) {
+            warn!(
+                "The attribute `{}` is not exposed with GraphQL, its name is reserved or collides with another attribute.",
+                attribute
+            );
+            continue;
+        }
+
+        let attribute = attribute.clone();
+        object = object.field(Field::new(field, TypeRef::named(JSON), move |ctx| {
+            let attribute = attribute.clone();
+            FieldFuture::new(async move {
+                let document = ctx.parent_value.try_downcast_ref::<Value>()?;
+                match lookup(document, &attribute) {
+                    Some(value) => json_value(value.clone()).map(Some),
+                    None => Ok(None),
+                }
+            })
+        }));
+    }
+
+    object
+}
+
+/// Adds the fields of a search result besides its hits.
+fn result_fields(object: Object) -> Object {
+    object
+        .field(value_field("query", TypeRef::named_nn(TypeRef::STRING)))
+        .field(value_field(
+            "processingTimeMs",
+            TypeRef::named_nn(TypeRef::INT),
+        ))
+        .field(value_field("offset", TypeRef::named(TypeRef::INT)))
+        .field(value_field("limit", TypeRef::named(TypeRef::INT)))
+        .field(value_field(
+            "estimatedTotalHits",
+            TypeRef::named(TypeRef::INT),
+        ))
+        .field(value_field("page", TypeRef::named(TypeRef::INT)))
+        .field(value_field("hitsPerPage", TypeRef::named(TypeRef::INT)))
+        .field(value_field("totalHits", TypeRef::named(TypeRef::INT)))
+        .field(value_field("totalPages", TypeRef::named(TypeRef::INT)))
+        .field(value_field("facetDistribution", TypeRef::named(JSON)))
+}
+
+fn search_arguments(field: Field) -> Field {
+    let strings = || TypeRef::named_nn_list(TypeRef::STRING);
+    [
+        ("q", TypeRef::named(TypeRef::STRING)),
+        ("offset", TypeRef::named(TypeRef::INT)),
+        ("limit", TypeRef::named(TypeRef::INT)),
+        ("page", TypeRef::named(TypeRef::INT)),
+        ("hitsPerPage", TypeRef::named(TypeRef::INT)),
+        ("filter", TypeRef::named(JSON)),
+        ("sort", strings()),
+        ("facets", strings()),
+        ("attributesToRetrieve", strings()),
+        ("attributesToHighlight", strings()),
+        ("attributesToCrop", strings()),
+        ("cropLength", TypeRef::named(TypeRef::INT)),
+        ("cropMarker", TypeRef::named(TypeRef::STRING)),
+        ("highlightPreTag", TypeRef::named(TypeRef::STRING)),
+        ("highlightPostTag", TypeRef::named(TypeRef::STRING)),
+        ("showMatchesPosition", TypeRef::named(TypeRef::BOOLEAN)),
+        ("matchingStrategy", TypeRef::named(TypeRef::STRING)),
+        ("attributesToSearchOn", strings()),
+        ("distinct", TypeRef::named(TypeRef::STRING)),
+    ]
+    .into_iter()
+    .fold(field, |field, (name, ty)| {
+        field.argument(InputValue::new(name, ty))
+    })
+}
+
+/// Returns a field resolved with the value of the same name in its parent.
+fn value_field(name: &'static str, ty: TypeRef) -> Field {
+    Field::new(name, ty, move |ctx| {
+        FieldFuture::new(async move {
+            let parent = ctx.parent_value.try_downcast_ref::<Value>()?;
+            match parent.get(name) {
+                Some(Value::Null) | None => Ok(None),
+                Some(value) => json_value(value.clone()).map(Some),
+            }
+        })
+    })
+}
+
+/// Returns a field resolved with the documents of the same name in its parent.
+fn documents_field(name: &'static str, document: &str) -> Field {
+    Field::new(name, TypeRef::named_nn_list_nn(document), move |ctx| {
+        FieldFuture::new(async move {
+            let parent = ctx.parent_value.try_downcast_ref::<Value>()?;
+            let documents = parent[name].as_array().cloned().unwrap_or_default();
+            Ok(Some(FieldValue::list(
+                documents.into_iter().map(FieldValue::owned_any),
+            )))
+        })
+    })
+}
+
+async fn search(
+    ctx: ResolverContext<'_>,
+    uid: String,
+) -> async_graphql::Result<Option<FieldValue<'_>>> {
+    let mut query = Map::new();
+    for (name, value) in ctx.args.iter() {
+        query.insert(name.to_string(), value.as_value().clone().into_json()?);
+    }
+    let query: SearchQuery = serde_json::from_value(Value::Object(query))?;
+
+    let result = search_index(ctx.data::<Context>()?, uid, query).await?;
+    Ok(Some(FieldValue::owned_any(result)))
+}
+
+async fn multi_search(ctx: ResolverContext<'_>) -> async_graphql::Result<Option<FieldValue<'_>>> {
+    let context = ctx.data::<Context>()?;
+    let queries: Vec<Map<String, Value>> = argument(&ctx, "queries")?.unwrap_or_default();
+
+    let searches = queries.into_iter().map(|mut query| async move {
+        let uid = match query.remove("indexUid") {
+            Some(Value::String(uid)) => uid,
+            _ => return Err("Each query of `multiSearch` must have an `indexUid`.".into()),
+        };
+        let query: SearchQuery = serde_json::from_value(Value::Object(query))?;
+
+        let mut result = search_index(context, uid.clone(), query).await?;
+        result["indexUid"] = json!(uid);
+        async_graphql::Result::Ok(FieldValue::owned_any(result))
+    });
+
+    let results = try_join_all(searches).await?;
+    Ok(Some(FieldValue::list(results)))
+}
+
+/// Searches the index `uid` with the search rules of the key.
+async fn search_index(
+    context: &Context,
+    uid: String,
+    mut query: SearchQuery,
+) -> async_graphql::Result<Value> {
+    let rules = context
+        .search_rules
+        .as_ref()
+        .and_then(|rules| rules.get_index_search_rules(&uid))
+        .ok_or_else(|| {
+            graphql_error(ResponseError::from_msg(
+                format!("The key is not allowed to search the index `{}`.", uid),
+                Code::InvalidToken,
+            ))
+        })?;
+    add_search_rules(&mut query, rules).map_err(graphql_error)?;
+
+    let result = context
+        .meilisearch
+        .search(uid, query)
+        .await
+        .map_err(graphql_error)?;
+    Ok(serde_json::to_value(result)?)
+}
+
+async fn get_document(
+    ctx: ResolverContext<'_>,
+    uid: String,
+) -> async_graphql::Result<Option<FieldValue<'_>>> {
+    let context = ctx.data::<Context>()?;
+    // the ids given as numbers are accepted too.
+    let id = match argument::<Value>(&ctx, "id")? {
+        Some(Value::String(id)) => id,
+        Some(id) => id.to_string(),
+        None => return Err("The `id` argument is missing.".into()),
+    };
+    let fields = argument(&ctx, "fields")?;
+
+    let document = context
+        .meilisearch
+        .document(uid, id, fields)
+        .await
+        .map_err(graphql_error)?;
+    Ok(Some(FieldValue::owned_any(Value::Object(document))))
+}
+
+async fn get_documents(
+    ctx: ResolverContext<'_>,
+    uid: String,
+) -> async_graphql::Result<Option<FieldValue<'_>>> {
+    let context = ctx.data::<Context>()?;
+    let offset = argument(&ctx, "offset")?.unwrap_or(0);
+    let limit = argument(&ctx, "limit")?.unwrap_or(20);
+
+    let (total, documents) = context
+        .meilisearch
+        .documents(
+            uid,
+            offset,
+            limit,
+            argument(&ctx, "fields")?,
+            argument(&ctx, "filter")?,
+            argument(&ctx, "sort")?,
+        )
+        .await
+        .map_err(graphql_error)?;
+
+    let page = json!({
+        "results": documents,
+        "offset": offset,
+        "limit": limit,
+        "total": total,
+    });
+    Ok(Some(FieldValue::owned_any(page)))
+}
+
+/// Returns the argument `name` of the field, `None` if it is missing or `null`.
+fn argument<T: DeserializeOwned>(
+    ctx: &ResolverContext,
+    name: &str,
+) -> async_graphql::Result<Option<T>> {
+    match ctx.args.get(name) {
+        Some(value) => Ok(serde_json::from_value(
+            value.as_value().clone().into_json()?,
+        )?),
+        None => Ok(None),
+    }
+}
+
+fn json_value(value: Value) -> async_graphql::Result<FieldValue<'static>> {
+    Ok(FieldValue::value(GraphqlValue::from_json(value)?))
+}
+
+/// Returns the value of the `attribute` of the `document`, looking into the nested objects for
+/// the attributes like `author.name`.
+fn lookup<'a>(document: &'a Value, attribute: &str) -> Option<&'a Value> {
+    if let Some(value) = document.get(attribute) {
+        return Some(value);
+    }
+
+    let (parent, child) = attribute.split_once('.')?;
+    match document.get(parent)? {
+        Value::Array(values) => values.iter().find_map(|value| lookup(value, child)),
+        value => lookup(value, child),
+    }
+}
+
+/// Turns an error of Meilisearch into a GraphQL error, keeping its code in the extensions.
+fn graphql_error(error: impl Into<ResponseError>) -> async_graphql::Error {
+    let error: ResponseError = error.into();
+    let body = json!(error);
+    async_graphql::Error::new(error.to_string()).extend_with(|_, extensions| {
+        for key in ["code", "type", "link"] {
+            if let Some(value) = body[key].as_str() {
+                extensions.set(key, value.to_string());
+            }
+        }
+    })
+}
+
+/// Returns a valid GraphQL name, the characters other than ASCII letters, digits and `_` are
+/// replaced by `_`.
+fn graphql_name(name: &str) -> String {
+    let mut graphql: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !graphql.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        graphql.insert(0, '_');
+    }
+    graphql
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn graphql_names() {
+        assert_eq!(graphql_name("movies"), "movies");
+        assert_eq!(graphql_name("movies-2022"), "movies_2022");
+        assert_eq!(graphql_name("2022"), "_2022");
+        assert_eq!(graphql_name("author.name"), "author_name");
+        assert_eq!(pascal_case("movies_2022"), "Movies_2022");
+    }
+
+    #[test]
+    fn lookup_nested_attributes() {
+        let document = json!({
+            "title": "Carol",
+            "author": { "name": "Patricia" },
+            "cast": [{ "name": "Cate" }, { "name": "Rooney" }],
+        });
+        assert_eq!(lookup(&document, "title"), Some(&json!("Carol")));
+        assert_eq!(lookup(&document, "author.name"), Some(&json!("Patricia")));
+        assert_eq!(lookup(&document, "cast.name"), Some(&json!("Cate")));
+        assert_eq!(lookup(&document, "author.age"), None);
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use meilisearch_auth::AuthController;
+use meilisearch_lib::MeiliSearch;
+use serde::Serialize;
+use sysinfo::{DiskExt, RefreshKind, System, SystemExt};
+
+use crate::Opt;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(get_health)))
+        .service(web::resource("/live").route(web::get().to(get_health)))
+        .service(web::resource("/ready").route(web::get().to(get_readiness)));
+}
+
+/// The options of the readiness checks.
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    pub db_path: PathBuf,
+    pub min_free_disk_space: u64,
+}
+
+impl HealthConfig {
+    pub fn new(opt: &Opt) -> Self {
+        Self {
+            db_path: opt.db_path.clone(),
+            min_free_disk_space: opt.health_min_free_disk_space.get_bytes() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum CheckStatus {
+    Ok,
+    Failed,
+    /// The check couldn't be performed, it doesn't prevent the instance from being ready.
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Check {
+    status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl Check {
+    fn ok() -> Self {
+        Self {
+            status: CheckStatus::Ok,
+            message: None,
+        }
+    }
+
+    fn failed(message: impl ToString) -> Self {
+        Self {
+            status: CheckStatus::Failed,
+            message: Some(message.to_string()),
+        }
+    }
+
+    fn skipped(message: impl ToString) -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            message: Some(message.to_string()),
+        }
+    }
+
+    fn is_failed(&self) -> bool {
+        matches!(self.status, CheckStatus::Failed)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadinessChecks {
+    database: Check,
+    scheduler: Check,
+    disk_space: Check,
+}
+
+/// The liveness check, the instance is alive as long as it answers.
+pub async fn get_health() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "available" }))
+}
+
+/// The instance is ready when its databases can be read, its scheduler processes the tasks and
+/// its disk isn't full. An instance that isn't ready must not receive traffic.
+pub async fn get_readiness(req: HttpRequest, config: web::Data<HealthConfig>) -> HttpResponse {
+    let (database, scheduler) = match req.app_data::<MeiliSearch>() {
+        Some(meilisearch) => {
+            let database = match check_databases(&req, meilisearch).await {
+                Ok(()) => Check::ok(),
+                Err(message) => Check::failed(message),
+            };
+            let scheduler = if meilisearch.is_scheduler_running().await {
+                Check::ok()
+            } else {
+                Check::failed("The task scheduler stopped.")
+            };
+            (database, scheduler)
+        }
+        None => (
+            Check::failed("The database isn't opened."),
+            Check::failed("The task scheduler isn't started."),
+        ),
+    };
+
+    let db_path = config.db_path.clone();
+    let disk_space = match web::block(move || available_disk_space(&db_path)).await {
+        Ok(Some(available)) if available < config.min_free_disk_space => Check::failed(format!(
+            "Only {} bytes are available on the disk holding the database, the minimum is {} bytes.",
+            available, config.min_free_disk_space
+        )),
+        Ok(Some(_)) => Check::ok(),
+        Ok(None) => Check::skipped("The disk holding the database couldn't be found."),
+        Err(e) => Check::failed(e),
+    };
+
+    let checks = ReadinessChecks {
+        database,
+        scheduler,
+        disk_space,
+    };
+
+    if checks.database.is_failed() || checks.scheduler.is_failed() || checks.disk_space.is_failed()
+    {
+        HttpResponse::ServiceUnavailable()
+            .json(serde_json::json!({ "status": "unavailable", "checks": checks }))
+    } else {
+        HttpResponse::Ok().json(serde_json::json!({ "status": "available", "checks": checks }))
+    }
+}
+
+async fn check_databases(req: &HttpRequest, meilisearch: &MeiliSearch) -> Result<(), String> {
+    meilisearch
+        .check_databases()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(auth) = req.app_data::<AuthController>().cloned() {
+        web::block(move || auth.list_keys())
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Returns the space available on the disk holding `db_path`, the disk whose mount point is the
+/// longest prefix of the path.
+fn available_disk_space(db_path: &Path) -> Option<u64> {
+    let db_path = db_path.canonicalize().ok()?;
+    let system = System::new_with_specifics(RefreshKind::new().with_disks_list());
+
+    system
+        .disks()
+        .iter()
+        .filter(|disk| db_path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
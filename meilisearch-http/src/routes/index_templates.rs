@@ -0,0 +1,89 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index_controller::IndexTemplate;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+use super::Pagination;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(list_templates))))
+        .service(
+            web::resource("/{name}")
+                .route(web::get().to(SeqHandler(get_template)))
+                .route(web::put().to(SeqHandler(set_template)))
+                .route(web::delete().to(SeqHandler(delete_template))),
+        );
+}
+
+pub async fn list_templates(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TEMPLATES_GET }>, MeiliSearch>,
+    paginate: web::Query<Pagination>,
+) -> Result<HttpResponse, ResponseError> {
+    let templates = meilisearch.list_templates().await?;
+    let ret = paginate
+        .into_inner()
+        .auto_paginate_sized(templates.into_iter());
+
+    debug!("returns: {:?}", ret);
+    Ok(HttpResponse::Ok().json(ret))
+}
+
+pub async fn get_template(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TEMPLATES_GET }>, MeiliSearch>,
+    name: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = meilisearch.get_template(name.into_inner()).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SetTemplatePayload {
+    pattern: String,
+    #[serde(default)]
+    settings: Settings<Unchecked>,
+}
+
+pub async fn set_template(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TEMPLATES_UPDATE }>, MeiliSearch>,
+    name: web::Path<String>,
+    body: web::Json<SetTemplatePayload>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let SetTemplatePayload { pattern, settings } = body.into_inner();
+
+    analytics.publish(
+        "Index Template Updated".to_string(),
+        json!({ "wildcard": pattern.contains('*') }),
+        Some(&req),
+    );
+
+    let template = IndexTemplate {
+        name: name.into_inner(),
+        pattern,
+        settings,
+    };
+    let res = meilisearch.set_template(template).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn delete_template(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TEMPLATES_UPDATE }>, MeiliSearch>,
+    name: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    meilisearch.delete_template(name.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,49 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::DEFAULT_CHANGES_LIMIT;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_document_changes))));
+}
+
+/// The changes are listed from the first one by default.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentChangesQuery {
+    #[serde(default)]
+    since: u64,
+    #[serde(default = "DEFAULT_CHANGES_LIMIT")]
+    limit: usize,
+}
+
+pub async fn get_document_changes(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<DocumentChangesQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    if !meilisearch
+        .indexer_options()
+        .experimental_enable_changes_feed
+    {
+        return Err(MeilisearchHttpError::FeatureNotEnabled(
+            "changes feed",
+            "--experimental-enable-changes-feed",
+        )
+        .into());
+    }
+
+    let DocumentChangesQuery { since, limit } = params.into_inner();
+    let changes = meilisearch
+        .document_changes(path.into_inner(), since, limit)
+        .await?;
+
+    debug!("returns: {} changes since {}", changes.results.len(), since);
+    Ok(HttpResponse::Ok().json(changes))
+}
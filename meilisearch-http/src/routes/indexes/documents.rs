@@ -1,11 +1,14 @@
+use actix_multipart::Multipart;
 use actix_web::error::PayloadError;
-use actix_web::http::header::CONTENT_TYPE;
+use actix_web::http::header::{ETag, CONTENT_TYPE};
 use actix_web::web::Bytes;
 use actix_web::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse};
 use bstr::ByteSlice;
-use futures::{Stream, StreamExt};
+use futures::{stream, Stream, StreamExt};
 use log::debug;
+use meilisearch_lib::document_formats::CsvOptions;
+use meilisearch_lib::index::Document;
 use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
 use meilisearch_lib::milli::update::IndexDocumentsMethod;
 use meilisearch_lib::MeiliSearch;
@@ -13,29 +16,50 @@ use meilisearch_types::error::ResponseError;
 use meilisearch_types::star_or::StarOr;
 use mime::Mime;
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_cs::vec::CS;
 use serde_json::Value;
 use tokio::sync::mpsc;
 
 use crate::analytics::Analytics;
-use crate::error::MeilisearchHttpError;
+use crate::error::{MeilisearchHttpError, PayloadError as MeilisearchPayloadError};
+use crate::etag::{index_etag, not_modified};
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::payload::Payload;
 use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::fix_sort_query_parameters;
 use crate::routes::{fold_star_or, PaginationView};
 use crate::task::SummarizedTaskView;
 
+/// The number of documents read from the index at once by an export.
+const EXPORT_PAGE_SIZE: usize = 1000;
+
 static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
     vec![
         "application/json".to_string(),
         "application/x-ndjson".to_string(),
         "text/csv".to_string(),
+        "application/msgpack".to_string(),
+        "application/cbor".to_string(),
+        "multipart/form-data".to_string(),
+    ]
+});
+
+/// The content types accepted for each file part of a `multipart/form-data` payload.
+static ACCEPTED_PART_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
+    vec![
+        "application/json".to_string(),
+        "application/x-ndjson".to_string(),
+        "text/csv".to_string(),
+        "application/msgpack".to_string(),
+        "application/cbor".to_string(),
     ]
 });
 
 /// This is required because Payload is not Sync nor Send
-fn payload_to_stream(mut payload: Payload) -> impl Stream<Item = Result<Bytes, PayloadError>> {
+pub(crate) fn payload_to_stream(
+    mut payload: Payload,
+) -> impl Stream<Item = Result<Bytes, PayloadError>> {
     let (snd, recv) = mpsc::channel(1);
     tokio::task::spawn_local(async move {
         while let Some(data) = payload.next().await {
@@ -79,6 +103,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     )
     // this route needs to be before the /documents/{document_id} to match properly
     .service(web::resource("/delete-batch").route(web::post().to(SeqHandler(delete_documents))))
+    .service(web::resource("/delete").route(web::post().to(SeqHandler(delete_documents_by_filter))))
+    .service(web::resource("/fetch").route(web::post().to(SeqHandler(fetch_documents))))
+    .service(web::resource("/edit").route(web::post().to(SeqHandler(edit_documents))))
+    .service(web::resource("/restore").route(web::post().to(SeqHandler(restore_documents))))
+    .service(web::resource("/export").route(web::get().to(SeqHandler(export_documents))))
     .service(
         web::resource("/{document_id}")
             .route(web::get().to(SeqHandler(get_document)))
@@ -96,17 +125,23 @@ pub async fn get_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
     params: web::Query<GetDocument>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let index = path.index_uid.clone();
     let id = path.document_id.clone();
     let GetDocument { fields } = params.into_inner();
     let attributes_to_retrieve = fields.and_then(fold_star_or);
 
+    let etag = index_etag(&meilisearch, index.clone(), &req).await?;
+    if let Some(response) = not_modified(&req, &etag) {
+        return Ok(response);
+    }
+
     let document = meilisearch
         .document(index, id, attributes_to_retrieve)
         .await?;
     debug!("returns: {:?}", document);
-    Ok(HttpResponse::Ok().json(document))
+    Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(document))
 }
 
 pub async fn delete_document(
@@ -126,11 +161,62 @@ pub async fn delete_document(
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct BrowseQuery {
-    #[serde(default)]
-    offset: usize,
+    offset: Option<usize>,
     #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
     limit: usize,
     fields: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+    sort: Option<String>,
+    cursor: Option<String>,
+}
+
+/// The documents returned by a cursor pagination, `next_cursor` must be sent back to retrieve
+/// the next documents. It is `None` once all the documents have been returned.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPaginationView {
+    results: Vec<Document>,
+    limit: usize,
+    next_cursor: Option<String>,
+}
+
+/// Decodes a cursor returned by a previous call, an empty cursor starts from the first document.
+fn decode_cursor(cursor: &str) -> Result<Option<u32>, MeilisearchHttpError> {
+    if cursor.is_empty() {
+        return Ok(None);
+    }
+
+    base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(|bytes| Some(u32::from_be_bytes(bytes)))
+        .ok_or_else(|| MeilisearchHttpError::InvalidDocumentCursor(cursor.to_string()))
+}
+
+fn encode_cursor(document_id: u32) -> String {
+    base64::encode_config(document_id.to_be_bytes(), base64::URL_SAFE_NO_PAD)
+}
+
+/// Browses the documents in the order they are stored, starting right after the `cursor`. Unlike
+/// the `offset`, the cursor doesn't need to skip the previous documents.
+async fn documents_by_cursor(
+    meilisearch: &MeiliSearch,
+    index_uid: String,
+    cursor: &str,
+    limit: usize,
+    attributes_to_retrieve: Option<Vec<String>>,
+    filter: Option<Value>,
+) -> Result<CursorPaginationView, ResponseError> {
+    let after = decode_cursor(cursor)?;
+    let (results, next) = meilisearch
+        .documents_after(index_uid, after, limit, attributes_to_retrieve, filter)
+        .await?;
+
+    Ok(CursorPaginationView {
+        results,
+        limit,
+        next_cursor: next.map(encode_cursor),
+    })
 }
 
 pub async fn get_all_documents(
@@ -143,11 +229,46 @@ pub async fn get_all_documents(
         limit,
         offset,
         fields,
+        filter,
+        sort,
+        cursor,
     } = params.into_inner();
     let attributes_to_retrieve = fields.and_then(fold_star_or);
+    let filter = filter.map(|f| match serde_json::from_str(&f) {
+        Ok(v) => v,
+        _ => Value::String(f),
+    });
+
+    if let Some(cursor) = cursor {
+        if offset.is_some() || sort.is_some() {
+            return Err(MeilisearchHttpError::CursorWithOffsetOrSort.into());
+        }
+        let ret = documents_by_cursor(
+            &meilisearch,
+            path.into_inner(),
+            &cursor,
+            limit,
+            attributes_to_retrieve,
+            filter,
+        )
+        .await?;
+
+        debug!("returns: {:?}", ret);
+        return Ok(HttpResponse::Ok().json(ret));
+    }
+
+    let offset = offset.unwrap_or_default();
+    let sort = sort.map(|attr| fix_sort_query_parameters(&attr));
 
     let (total, documents) = meilisearch
-        .documents(path.into_inner(), offset, limit, attributes_to_retrieve)
+        .documents(
+            path.into_inner(),
+            offset,
+            limit,
+            attributes_to_retrieve,
+            filter,
+            sort,
+        )
         .await?;
 
     let ret = PaginationView::new(offset, limit, total as usize, documents);
@@ -156,10 +277,217 @@ pub async fn get_all_documents(
     Ok(HttpResponse::Ok().json(ret))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BrowseQueryPost {
+    offset: Option<usize>,
+    #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
+    limit: usize,
+    fields: Option<Vec<StarOr<String>>>,
+    filter: Option<Value>,
+    sort: Option<Vec<String>>,
+    cursor: Option<String>,
+    /// The ids of the documents to return, in this order.
+    ids: Option<Vec<Value>>,
+}
+
+/// The documents fetched by id, in the order of the ids, and the ids of the missing ones.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentsByIdsView {
+    results: Vec<Document>,
+    missing: Vec<String>,
+}
+
+pub async fn fetch_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<BrowseQueryPost>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let BrowseQueryPost {
+        limit,
+        offset,
+        fields,
+        filter,
+        sort,
+        cursor,
+        ids,
+    } = body.into_inner();
+    let attributes_to_retrieve = fields.and_then(fold_star_or);
+
+    if let Some(ids) = ids {
+        if offset.is_some() || cursor.is_some() || filter.is_some() || sort.is_some() {
+            return Err(MeilisearchHttpError::IdsWithPagination.into());
+        }
+        let ids = ids
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| v.to_string())
+            })
+            .collect();
+        let (results, missing) = meilisearch
+            .documents_by_ids(path.into_inner(), ids, attributes_to_retrieve)
+            .await?;
+
+        let ret = DocumentsByIdsView { results, missing };
+        debug!("returns: {:?}", ret);
+        return Ok(HttpResponse::Ok().json(ret));
+    }
+
+    if let Some(cursor) = cursor {
+        if offset.is_some() || sort.is_some() {
+            return Err(MeilisearchHttpError::CursorWithOffsetOrSort.into());
+        }
+        let ret = documents_by_cursor(
+            &meilisearch,
+            path.into_inner(),
+            &cursor,
+            limit,
+            attributes_to_retrieve,
+            filter,
+        )
+        .await?;
+
+        debug!("returns: {:?}", ret);
+        return Ok(HttpResponse::Ok().json(ret));
+    }
+
+    let offset = offset.unwrap_or_default();
+
+    let (total, documents) = meilisearch
+        .documents(
+            path.into_inner(),
+            offset,
+            limit,
+            attributes_to_retrieve,
+            filter,
+            sort,
+        )
+        .await?;
+
+    let ret = PaginationView::new(offset, limit, total as usize, documents);
+
+    debug!("returns: {:?}", ret);
+    Ok(HttpResponse::Ok().json(ret))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExportQuery {
+    fields: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+}
+
+/// The position of an export in the index.
+enum ExportState {
+    /// A page read but not sent yet, and the id of its last document if more follow.
+    Page(Vec<Document>, Option<u32>),
+    /// The documents following this one are still to read.
+    After(u32),
+    Done,
+}
+
+/// Serializes the `documents` as NDJSON, one document per line.
+fn ndjson_chunk(documents: &[Document]) -> Bytes {
+    let mut chunk = Vec::new();
+    for document in documents {
+        // serializing a `Document` into a `Vec` can't fail.
+        serde_json::to_writer(&mut chunk, document).unwrap();
+        chunk.push(b'\n');
+    }
+    Bytes::from(chunk)
+}
+
+/// Streams all the documents of the index, or the ones matching the filter, as NDJSON. The
+/// documents are read one page at a time, only once the client received the previous ones.
+pub async fn export_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let index_uid = path.into_inner();
+    let ExportQuery { fields, filter } = params.into_inner();
+    let attributes_to_retrieve = fields.and_then(fold_star_or);
+    let filter = filter.map(|f| match serde_json::from_str(&f) {
+        Ok(v) => v,
+        _ => Value::String(f),
+    });
+
+    // the first page is read before responding, so that a missing index or an invalid filter
+    // is reported with its status instead of an interrupted stream.
+    let meilisearch = (*meilisearch).clone();
+    let (documents, next) = meilisearch
+        .documents_after(
+            index_uid.clone(),
+            None,
+            EXPORT_PAGE_SIZE,
+            attributes_to_retrieve.clone(),
+            filter.clone(),
+        )
+        .await?;
+
+    let chunks = stream::try_unfold(ExportState::Page(documents, next), move |state| {
+        let meilisearch = meilisearch.clone();
+        let index_uid = index_uid.clone();
+        let attributes_to_retrieve = attributes_to_retrieve.clone();
+        let filter = filter.clone();
+        async move {
+            let (documents, next) = match state {
+                ExportState::Page(documents, next) => (documents, next),
+                ExportState::After(after) => {
+                    meilisearch
+                        .documents_after(
+                            index_uid,
+                            Some(after),
+                            EXPORT_PAGE_SIZE,
+                            attributes_to_retrieve,
+                            filter,
+                        )
+                        .await?
+                }
+                ExportState::Done => return Ok(None),
+            };
+            let state = next.map_or(ExportState::Done, ExportState::After);
+            Ok::<_, ResponseError>(Some((ndjson_chunk(&documents), state)))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(chunks))
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct UpdateDocumentsQuery {
     pub primary_key: Option<String>,
+    pub csv_delimiter: Option<char>,
+    pub csv_quote: Option<char>,
+}
+
+impl UpdateDocumentsQuery {
+    fn csv_options(&self) -> Result<CsvOptions, MeilisearchHttpError> {
+        fn ascii_byte(name: &'static str, c: char) -> Result<u8, MeilisearchHttpError> {
+            if c.is_ascii() {
+                Ok(c as u8)
+            } else {
+                Err(MeilisearchHttpError::InvalidCsvParameter(name, c))
+            }
+        }
+
+        let mut options = CsvOptions::default();
+        if let Some(delimiter) = self.csv_delimiter {
+            options.delimiter = ascii_byte("csvDelimiter", delimiter)?;
+        }
+        if let Some(quote) = self.csv_quote {
+            options.quote = ascii_byte("csvQuote", quote)?;
+        }
+        Ok(options)
+    }
 }
 
 pub async fn add_documents(
@@ -173,6 +501,7 @@ pub async fn add_documents(
     debug!("called with params: {:?}", params);
     let params = params.into_inner();
     let index_uid = path.into_inner();
+    let csv_options = params.csv_options()?;
 
     analytics.add_documents(
         &params,
@@ -181,13 +510,32 @@ pub async fn add_documents(
     );
 
     let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let mime_type = extract_mime_type(&req)?;
+    if is_multipart(&mime_type) {
+        let tasks = multipart_document_addition(
+            &req,
+            meilisearch,
+            index_uid,
+            params.primary_key,
+            body,
+            IndexDocumentsMethod::ReplaceDocuments,
+            csv_options,
+            allow_index_creation,
+        )
+        .await?;
+
+        return Ok(HttpResponse::Accepted().json(tasks));
+    }
+
     let task = document_addition(
-        extract_mime_type(&req)?,
-        meilisearch,
+        mime_type,
+        &ACCEPTED_CONTENT_TYPE,
+        &meilisearch,
         index_uid,
         params.primary_key,
-        body,
+        payload_to_stream(body),
         IndexDocumentsMethod::ReplaceDocuments,
+        csv_options,
         allow_index_creation,
     )
     .await?;
@@ -204,7 +552,9 @@ pub async fn update_documents(
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let params = params.into_inner();
     let index_uid = path.into_inner();
+    let csv_options = params.csv_options()?;
 
     analytics.update_documents(
         &params,
@@ -213,13 +563,49 @@ pub async fn update_documents(
     );
 
     let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let mime_type = extract_mime_type(&req)?;
+    if is_multipart(&mime_type) {
+        let tasks = multipart_document_addition(
+            &req,
+            meilisearch,
+            index_uid,
+            params.primary_key,
+            body,
+            IndexDocumentsMethod::UpdateDocuments,
+            csv_options,
+            allow_index_creation,
+        )
+        .await?;
+
+        return Ok(HttpResponse::Accepted().json(tasks));
+    }
+
+    if is_merge_patch(&mime_type) {
+        // The patches are merged with the stored documents by the indexing task, so that they
+        // apply on top of the additions enqueued before them.
+        let update = Update::DocumentAddition {
+            payload: Box::new(payload_to_stream(body)),
+            primary_key: params.primary_key,
+            method: IndexDocumentsMethod::ReplaceDocuments,
+            merge_patch: true,
+            format: DocumentAdditionFormat::Json,
+            allow_index_creation,
+        };
+        let task: SummarizedTaskView = meilisearch.register_update(index_uid, update).await?.into();
+
+        debug!("returns: {:?}", task);
+        return Ok(HttpResponse::Accepted().json(task));
+    }
+
     let task = document_addition(
-        extract_mime_type(&req)?,
-        meilisearch,
+        mime_type,
+        &ACCEPTED_CONTENT_TYPE,
+        &meilisearch,
         index_uid,
-        params.into_inner().primary_key,
-        body,
+        params.primary_key,
+        payload_to_stream(body),
         IndexDocumentsMethod::UpdateDocuments,
+        csv_options,
         allow_index_creation,
     )
     .await?;
@@ -227,13 +613,81 @@ pub async fn update_documents(
     Ok(HttpResponse::Accepted().json(task))
 }
 
-async fn document_addition(
-    mime_type: Option<Mime>,
+fn is_merge_patch(mime_type: &Option<Mime>) -> bool {
+    mime_type.as_ref().map_or(false, |m| {
+        m.type_() == mime::APPLICATION
+            && m.subtype() == "merge-patch"
+            && m.suffix() == Some(mime::JSON)
+    })
+}
+
+fn is_multipart(mime_type: &Option<Mime>) -> bool {
+    mime_type.as_ref().map_or(false, |m| {
+        m.type_() == mime::MULTIPART && m.subtype() == mime::FORM_DATA
+    })
+}
+
+/// Registers one document addition task for each file part of a `multipart/form-data` payload.
+/// Each part must define its own `Content-Type`.
+#[allow(clippy::too_many_arguments)]
+async fn multipart_document_addition(
+    req: &HttpRequest,
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     index_uid: String,
     primary_key: Option<String>,
     body: Payload,
     method: IndexDocumentsMethod,
+    csv_options: CsvOptions,
+    allow_index_creation: bool,
+) -> Result<Vec<SummarizedTaskView>, ResponseError> {
+    let mut multipart = Multipart::new(req.headers(), body);
+    let mut tasks = Vec::new();
+
+    while let Some(field) = multipart.next().await {
+        let mut field = field.map_err(MeilisearchHttpError::from)?;
+        let mime_type = Some(field.content_type().clone());
+
+        // The fields of a multipart payload can only be read sequentially, we need to read the
+        // whole part before moving on to the next one.
+        let mut buffer = Vec::new();
+        while let Some(bytes) = field.next().await {
+            buffer.extend_from_slice(&bytes.map_err(MeilisearchHttpError::from)?);
+        }
+        let payload = futures::stream::iter(vec![Ok(Bytes::from(buffer))]);
+
+        let task = document_addition(
+            mime_type,
+            &ACCEPTED_PART_CONTENT_TYPE,
+            &meilisearch,
+            index_uid.clone(),
+            primary_key.clone(),
+            payload,
+            method,
+            csv_options,
+            allow_index_creation,
+        )
+        .await?;
+        tasks.push(task);
+    }
+
+    if tasks.is_empty() {
+        return Err(MeilisearchPayloadError::MissingPayload.into());
+    }
+
+    debug!("returns: {:?}", tasks);
+    Ok(tasks)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn document_addition(
+    mime_type: Option<Mime>,
+    accepted_content_types: &[String],
+    meilisearch: &MeiliSearch,
+    index_uid: String,
+    primary_key: Option<String>,
+    payload: impl Stream<Item = Result<Bytes, PayloadError>> + Send + Sync + Unpin + 'static,
+    method: IndexDocumentsMethod,
+    csv_options: CsvOptions,
     allow_index_creation: bool,
 ) -> Result<SummarizedTaskView, ResponseError> {
     let format = match mime_type
@@ -242,25 +696,28 @@ async fn document_addition(
     {
         Some(("application", "json")) => DocumentAdditionFormat::Json,
         Some(("application", "x-ndjson")) => DocumentAdditionFormat::Ndjson,
-        Some(("text", "csv")) => DocumentAdditionFormat::Csv,
+        Some(("text", "csv")) => DocumentAdditionFormat::Csv(csv_options),
+        Some(("application", "msgpack")) => DocumentAdditionFormat::MessagePack,
+        Some(("application", "cbor")) => DocumentAdditionFormat::Cbor,
         Some((type_, subtype)) => {
             return Err(MeilisearchHttpError::InvalidContentType(
                 format!("{}/{}", type_, subtype),
-                ACCEPTED_CONTENT_TYPE.clone(),
+                accepted_content_types.to_vec(),
             )
             .into())
         }
         None => {
             return Err(
-                MeilisearchHttpError::MissingContentType(ACCEPTED_CONTENT_TYPE.clone()).into(),
+                MeilisearchHttpError::MissingContentType(accepted_content_types.to_vec()).into(),
             )
         }
     };
 
     let update = Update::DocumentAddition {
-        payload: Box::new(payload_to_stream(body)),
+        payload: Box::new(payload),
         primary_key,
         method,
+        merge_patch: false,
         format,
         allow_index_creation,
     };
@@ -296,6 +753,85 @@ pub async fn delete_documents(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentDeletionByFilter {
+    filter: Value,
+}
+
+pub async fn delete_documents_by_filter(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<DocumentDeletionByFilter>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let update = Update::DeleteDocumentsByFilter(body.into_inner().filter);
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentEdition {
+    filter: Option<Value>,
+    function: String,
+}
+
+pub async fn edit_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<DocumentEdition>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentEdition { filter, function } = body.into_inner();
+    let update = Update::EditDocuments { filter, function };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// The documents to restore, all the restorable documents when no `ids` are given.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentRestoration {
+    ids: Option<Vec<Value>>,
+}
+
+pub async fn restore_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<DocumentRestoration>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentRestoration { ids } = body.into_inner();
+    let ids = ids.map(|ids| {
+        ids.iter()
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| v.to_string())
+            })
+            .collect()
+    });
+    let update = Update::RestoreDocuments { ids };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
 pub async fn clear_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
@@ -0,0 +1,57 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::FacetSearchQuery;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::{attribute_restrictions, fuse_filters};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(search))));
+}
+
+pub async fn search(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Json<FacetSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("facet search called with params: {:?}", query);
+
+    analytics.publish(
+        "Facet Searched POST".to_string(),
+        json!({
+            "total_received": 1,
+            "facet_query": query.facet_query.is_some(),
+            "q": query.q.is_some(),
+            "filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index_uid = path.into_inner();
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        let restrictions = attribute_restrictions(&search_rules);
+        if let Some(ref restrictions) = restrictions {
+            restrictions.check_filter(query.filter.as_ref())?;
+        }
+        query.attribute_restrictions = restrictions;
+        query.filter = fuse_filters(query.filter.take(), search_rules.filter);
+    }
+
+    let search_result = meilisearch.facet_search(index_uid, query).await?;
+
+    debug!("returns: {:?}", search_result);
+    Ok(HttpResponse::Ok().json(search_result))
+}
@@ -1,5 +1,6 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use log::debug;
+use meilisearch_auth::AuditAction;
 use meilisearch_lib::index_controller::Update;
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
@@ -8,15 +9,21 @@ use serde_json::json;
 use time::OffsetDateTime;
 
 use crate::analytics::Analytics;
-use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::authentication::{policies::*, AuthenticationError, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
 use crate::task::SummarizedTaskView;
 
-use super::Pagination;
+use super::{audit_log, Pagination};
 
+pub mod changes;
 pub mod documents;
+pub mod facet_search;
 pub mod search;
+pub mod search_analytics;
 pub mod settings;
+pub mod similar;
+pub mod suggest;
+pub mod synonyms;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -33,7 +40,13 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .route(web::delete().to(SeqHandler(delete_index))),
             )
             .service(web::resource("/stats").route(web::get().to(SeqHandler(get_index_stats))))
+            .service(web::scope("/analytics").configure(search_analytics::configure))
+            .service(web::scope("/changes").configure(changes::configure))
+            .service(web::resource("/clone").route(web::post().to(SeqHandler(clone_index))))
             .service(web::scope("/documents").configure(documents::configure))
+            .service(web::scope("/facet-search").configure(facet_search::configure))
+            .service(web::scope("/suggest").configure(suggest::configure))
+            .service(web::scope("/similar").configure(similar::configure))
             .service(web::scope("/search").configure(search::configure))
             .service(web::scope("/settings").configure(settings::configure)),
     );
@@ -80,8 +93,22 @@ pub async fn create_index(
         Some(&req),
     );
 
-    let update = Update::CreateIndex { primary_key };
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let update = Update::CreateIndex {
+        primary_key: primary_key.clone(),
+    };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(uid.clone(), update)
+        .await?
+        .into();
+
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::IndexCreated,
+        Some(uid),
+        json!({ "taskUid": task.task_uid(), "primaryKey": primary_key }),
+    )
+    .await;
 
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -147,19 +174,103 @@ pub async fn update_index(
 pub async fn delete_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let uid = path.into_inner();
     let update = Update::DeleteIndex;
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch
+        .register_update(uid.clone(), update)
+        .await?
+        .into();
+
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::IndexDeleted,
+        Some(uid),
+        json!({ "taskUid": task.task_uid() }),
+    )
+    .await;
 
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CloneIndexRequest {
+    index_uid: String,
+    #[serde(default)]
+    settings_only: bool,
+}
+
+pub async fn clone_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_CREATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<CloneIndexRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let CloneIndexRequest {
+        index_uid,
+        settings_only,
+    } = body.into_inner();
+
+    // The key must also be allowed to create the copy.
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&index_uid)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    analytics.publish(
+        "Index Cloned".to_string(),
+        json!({ "settings_only": settings_only }),
+        Some(&req),
+    );
+
+    let source = path.into_inner();
+    let update = Update::CloneIndex {
+        destination: index_uid.clone(),
+        settings_only,
+    };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(source.clone(), update)
+        .await?
+        .into();
+
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::IndexCreated,
+        Some(index_uid),
+        json!({ "taskUid": task.task_uid(), "clonedFrom": source, "settingsOnly": settings_only }),
+    )
+    .await;
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexStatsQuery {
+    /// Also reports the size of each database, the number of terms and the average document
+    /// size, which requires reading the whole index.
+    #[serde(default)]
+    detailed: bool,
+}
+
 pub async fn get_index_stats(
     meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
     path: web::Path<String>,
+    params: web::Query<IndexStatsQuery>,
 ) -> Result<HttpResponse, ResponseError> {
-    let response = meilisearch.get_index_stats(path.into_inner()).await?;
+    let response = meilisearch
+        .get_index_stats(path.into_inner(), params.detailed)
+        .await?;
 
     debug!("returns: {:?}", response);
     Ok(HttpResponse::Ok().json(response))
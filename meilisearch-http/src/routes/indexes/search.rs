@@ -2,8 +2,9 @@ use actix_web::{web, HttpRequest, HttpResponse};
 use log::debug;
 use meilisearch_auth::IndexSearchRules;
 use meilisearch_lib::index::{
-    SearchQuery, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG,
-    DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    AttributeRestrictions, HybridQuery, MatchingStrategy, SearchQuery, DEFAULT_CROP_LENGTH,
+    DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+    DEFAULT_SEARCH_LIMIT,
 };
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
@@ -30,6 +31,8 @@ pub struct SearchQueryGet {
     q: Option<String>,
     offset: Option<usize>,
     limit: Option<usize>,
+    page: Option<usize>,
+    hits_per_page: Option<usize>,
     attributes_to_retrieve: Option<CS<StarOr<String>>>,
     attributes_to_crop: Option<CS<StarOr<String>>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
@@ -40,6 +43,15 @@ pub struct SearchQueryGet {
     #[serde(default = "Default::default")]
     show_matches_position: bool,
     facets: Option<CS<StarOr<String>>>,
+    vector: Option<CS<f32>>,
+    hybrid_semantic_ratio: Option<f32>,
+    #[serde(default)]
+    matching_strategy: MatchingStrategy,
+    attributes_to_search_on: Option<CS<String>>,
+    distinct: Option<String>,
+    ranking_profile: Option<String>,
+    #[serde(default)]
+    show_suggestions: bool,
     #[serde(default = "DEFAULT_HIGHLIGHT_PRE_TAG")]
     highlight_pre_tag: String,
     #[serde(default = "DEFAULT_HIGHLIGHT_POST_TAG")]
@@ -62,6 +74,8 @@ impl From<SearchQueryGet> for SearchQuery {
             q: other.q,
             offset: other.offset,
             limit: other.limit.unwrap_or_else(DEFAULT_SEARCH_LIMIT),
+            page: other.page,
+            hits_per_page: other.hits_per_page,
             attributes_to_retrieve: other.attributes_to_retrieve.and_then(fold_star_or),
             attributes_to_crop: other.attributes_to_crop.and_then(fold_star_or),
             crop_length: other.crop_length,
@@ -70,16 +84,54 @@ impl From<SearchQueryGet> for SearchQuery {
             sort: other.sort.map(|attr| fix_sort_query_parameters(&attr)),
             show_matches_position: other.show_matches_position,
             facets: other.facets.and_then(fold_star_or),
+            vector: other.vector.map(|vector| vector.into_iter().collect()),
+            hybrid: other
+                .hybrid_semantic_ratio
+                .map(|semantic_ratio| HybridQuery { semantic_ratio }),
+            matching_strategy: other.matching_strategy,
+            attributes_to_search_on: other
+                .attributes_to_search_on
+                .map(|attributes| attributes.into_iter().collect()),
+            distinct: other.distinct,
+            attribute_weights: None,
+            ranking_profile: other.ranking_profile,
+            show_suggestions: other.show_suggestions,
             highlight_pre_tag: other.highlight_pre_tag,
             highlight_post_tag: other.highlight_post_tag,
             crop_marker: other.crop_marker,
+            attribute_restrictions: None,
         }
     }
 }
 
-/// Incorporate search rules in search query
-fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
-    query.filter = match (query.filter.take(), rules.filter) {
+/// Incorporate search rules in search query, the query can't use the attributes they restrict.
+pub(crate) fn add_search_rules(
+    query: &mut SearchQuery,
+    rules: IndexSearchRules,
+) -> Result<(), ResponseError> {
+    query.attribute_restrictions = attribute_restrictions(&rules);
+    if let Some(ref restrictions) = query.attribute_restrictions {
+        restrictions.check_query(query)?;
+    }
+    query.filter = fuse_filters(query.filter.take(), rules.filter);
+    Ok(())
+}
+
+/// Returns the restrictions of the attributes a search can return, if the rules have any.
+pub(crate) fn attribute_restrictions(rules: &IndexSearchRules) -> Option<AttributeRestrictions> {
+    if rules.displayed_attributes.is_none() && rules.forbidden_attributes.is_none() {
+        return None;
+    }
+
+    Some(AttributeRestrictions {
+        displayed_attributes: rules.displayed_attributes.clone(),
+        forbidden_attributes: rules.forbidden_attributes.clone().unwrap_or_default(),
+    })
+}
+
+/// Fuse the filter of a query with the filter of the search rules, both must be satisfied.
+pub(crate) fn fuse_filters(filter: Option<Value>, rules_filter: Option<Value>) -> Option<Value> {
+    match (filter, rules_filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
         (Some(filter), Some(rules_filter)) => {
@@ -100,7 +152,7 @@ fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
 // TODO: TAMO: split on :asc, and :desc, instead of doing some weird things
 
 /// Transform the sort query parameter into something that matches the post expected format.
-fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
+pub(crate) fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
     let mut sort_parameters = Vec::new();
     let mut merge = false;
     for current_sort in sort_query.trim_matches('"').split(',').map(|s| s.trim()) {
@@ -140,7 +192,7 @@ pub async fn search_with_url_query(
         .search_rules
         .get_index_search_rules(&index_uid)
     {
-        add_search_rules(&mut query, search_rules);
+        add_search_rules(&mut query, search_rules)?;
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
@@ -174,7 +226,7 @@ pub async fn search_with_post(
         .search_rules
         .get_index_search_rules(&index_uid)
     {
-        add_search_rules(&mut query, search_rules);
+        add_search_rules(&mut query, search_rules)?;
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
@@ -0,0 +1,53 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime};
+
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+const DEFAULT_QUERIES_LIMIT: fn() -> usize = || 10;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_search_analytics))));
+}
+
+/// The window of the report, the last day by default, and the number of queries listed.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchAnalyticsQuery {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+    #[serde(default = "DEFAULT_QUERIES_LIMIT")]
+    limit: usize,
+}
+
+pub async fn get_search_analytics(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH_ANALYTICS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<SearchAnalyticsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    if !meilisearch.is_search_analytics_enabled() {
+        return Err(MeilisearchHttpError::FeatureNotEnabled(
+            "search analytics",
+            "--experimental-enable-search-analytics",
+        )
+        .into());
+    }
+
+    let SearchAnalyticsQuery { from, to, limit } = params.into_inner();
+    let to = to.unwrap_or_else(OffsetDateTime::now_utc);
+    let from = from.unwrap_or(to - Duration::days(1));
+
+    let response = meilisearch
+        .search_analytics(path.into_inner(), from, to, limit)
+        .await?;
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -1,6 +1,8 @@
 use log::debug;
 
+use actix_web::http::header::ETag;
 use actix_web::{web, HttpRequest, HttpResponse};
+use meilisearch_auth::AuditAction;
 use meilisearch_lib::index::{Settings, Unchecked};
 use meilisearch_lib::index_controller::Update;
 use meilisearch_lib::MeiliSearch;
@@ -8,7 +10,9 @@ use meilisearch_types::error::ResponseError;
 use serde_json::json;
 
 use crate::analytics::Analytics;
+use crate::etag::{index_etag, not_modified};
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::routes::audit_log;
 use crate::task::SummarizedTaskView;
 
 #[macro_export]
@@ -21,15 +25,19 @@ macro_rules! make_setting_route {
             use meilisearch_lib::milli::update::Setting;
             use meilisearch_lib::{index::Settings, index_controller::Update, MeiliSearch};
 
+            use meilisearch_auth::AuditAction;
             use meilisearch_types::error::ResponseError;
+            use serde_json::json;
             use $crate::analytics::Analytics;
             use $crate::extractors::authentication::{policies::*, GuardedData};
             use $crate::extractors::sequential_extractor::SeqHandler;
+            use $crate::routes::audit_log;
             use $crate::task::SummarizedTaskView;
 
             pub async fn delete(
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: web::Path<String>,
+                req: HttpRequest,
             ) -> Result<HttpResponse, ResponseError> {
                 let settings = Settings {
                     $attr: Setting::Reset,
@@ -42,11 +50,21 @@ macro_rules! make_setting_route {
                     is_deletion: true,
                     allow_index_creation,
                 };
+                let index_uid = index_uid.into_inner();
                 let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
+                    .register_update(index_uid.clone(), update)
                     .await?
                     .into();
 
+                audit_log::record(
+                    &req,
+                    meilisearch.filters().key_uid,
+                    AuditAction::SettingsUpdated,
+                    Some(index_uid),
+                    json!({ "taskUid": task.task_uid(), "settings": [$camelcase_attr], "reset": true }),
+                )
+                .await;
+
                 debug!("returns: {:?}", task);
                 Ok(HttpResponse::Accepted().json(task))
             }
@@ -76,11 +94,21 @@ macro_rules! make_setting_route {
                     is_deletion: false,
                     allow_index_creation,
                 };
+                let index_uid = index_uid.into_inner();
                 let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
+                    .register_update(index_uid.clone(), update)
                     .await?
                     .into();
 
+                audit_log::record(
+                    &req,
+                    meilisearch.filters().key_uid,
+                    AuditAction::SettingsUpdated,
+                    Some(index_uid),
+                    json!({ "taskUid": task.task_uid(), "settings": [$camelcase_attr], "reset": false }),
+                )
+                .await;
+
                 debug!("returns: {:?}", task);
                 Ok(HttpResponse::Accepted().json(task))
             }
@@ -297,6 +325,15 @@ make_setting_route!(
             json!({
                 "faceting": {
                     "max_values_per_facet": setting.as_ref().and_then(|s| s.max_values_per_facet.set()),
+                    "sort_facet_values_by_count": setting
+                        .as_ref()
+                        .and_then(|s| s.sort_facet_values_by.as_ref().set())
+                        .map(|sort_by| {
+                            sort_by
+                                .values()
+                                .filter(|sort| **sort == meilisearch_lib::index::updates::FacetValuesSort::Count)
+                                .count()
+                        }),
                 },
             }),
             Some(req),
@@ -319,6 +356,95 @@ make_setting_route!(
             json!({
                 "pagination": {
                     "limited_to": setting.as_ref().and_then(|s| s.limited_to.set()),
+                    "max_total_hits": setting.as_ref().and_then(|s| s.max_total_hits.set()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/search-cutoff-ms",
+    put,
+    u64,
+    search_cutoff_ms,
+    "searchCutoffMs",
+    analytics,
+    |setting: &Option<u64>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "Search Cutoff Updated".to_string(),
+            json!({
+                "search_cutoff_ms": {
+                    "search_cutoff_ms": setting,
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/ranking-profiles",
+    put,
+    std::collections::BTreeMap<String, meilisearch_lib::index::updates::RankingProfile>,
+    ranking_profiles,
+    "rankingProfiles",
+    analytics,
+    |setting: &Option<std::collections::BTreeMap<String, meilisearch_lib::index::updates::RankingProfile>>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "RankingProfiles Updated".to_string(),
+            json!({
+                "ranking_profiles": {
+                    "total": setting.as_ref().map(|profiles| profiles.len()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/expiry-attribute",
+    put,
+    String,
+    expiry_attribute,
+    "expiryAttribute",
+    analytics,
+    |setting: &Option<String>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "ExpiryAttribute Updated".to_string(),
+            json!({
+                "expiry_attribute": {
+                    "set": setting.is_some(),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/version-attribute",
+    put,
+    String,
+    version_attribute,
+    "versionAttribute",
+    analytics,
+    |setting: &Option<String>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "VersionAttribute Updated".to_string(),
+            json!({
+                "version_attribute": {
+                    "set": setting.is_some(),
                 },
             }),
             Some(req),
@@ -335,6 +461,7 @@ macro_rules! generate_configure {
                 .route(web::patch().to(SeqHandler(update_all)))
                 .route(web::get().to(SeqHandler(get_all)))
                 .route(web::delete().to(SeqHandler(delete_all))))
+                .configure(super::synonyms::configure)
                 $(.service($mod::resources()))*;
         }
     };
@@ -349,7 +476,11 @@ generate_configure!(
     stop_words,
     synonyms,
     ranking_rules,
-    typo_tolerance
+    typo_tolerance,
+    search_cutoff_ms,
+    ranking_profiles,
+    expiry_attribute,
+    version_attribute
 );
 
 pub async fn update_all(
@@ -409,21 +540,44 @@ pub async fn update_all(
                         .map(|s| s.two_typos.set()))
                     .flatten(),
             },
+            "search_cutoff_ms": {
+                "search_cutoff_ms": settings.search_cutoff_ms.as_ref().set(),
+            },
+            "ranking_profiles": {
+                "total": settings.ranking_profiles.as_ref().set().map(|profiles| profiles.len()),
+            },
+            "expiry_attribute": {
+                "set": settings.expiry_attribute.as_ref().set().is_some(),
+            },
+            "version_attribute": {
+                "set": settings.version_attribute.as_ref().set().is_some(),
+            },
         }),
         Some(&req),
     );
 
+    let names = setting_names(&settings);
     let allow_index_creation = meilisearch.filters().allow_index_creation;
     let update = Update::Settings {
         settings,
         is_deletion: false,
         allow_index_creation,
     };
+    let index_uid = index_uid.into_inner();
     let task: SummarizedTaskView = meilisearch
-        .register_update(index_uid.into_inner(), update)
+        .register_update(index_uid.clone(), update)
         .await?
         .into();
 
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::SettingsUpdated,
+        Some(index_uid),
+        json!({ "taskUid": task.task_uid(), "settings": names, "reset": false }),
+    )
+    .await;
+
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -431,29 +585,56 @@ pub async fn update_all(
 pub async fn get_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
-    let settings = data.settings(index_uid.into_inner()).await?;
+    let index_uid = index_uid.into_inner();
+    let etag = index_etag(&data, index_uid.clone(), &req).await?;
+    if let Some(response) = not_modified(&req, &etag) {
+        return Ok(response);
+    }
+
+    let settings = data.settings(index_uid).await?;
     debug!("returns: {:?}", settings);
-    Ok(HttpResponse::Ok().json(settings))
+    Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(settings))
 }
 
 pub async fn delete_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
     let settings = Settings::cleared().into_unchecked();
 
+    let names = setting_names(&settings);
     let allow_index_creation = data.filters().allow_index_creation;
     let update = Update::Settings {
         settings,
         is_deletion: true,
         allow_index_creation,
     };
+    let index_uid = index_uid.into_inner();
     let task: SummarizedTaskView = data
-        .register_update(index_uid.into_inner(), update)
+        .register_update(index_uid.clone(), update)
         .await?
         .into();
 
+    audit_log::record(
+        &req,
+        data.filters().key_uid,
+        AuditAction::SettingsUpdated,
+        Some(index_uid),
+        json!({ "taskUid": task.task_uid(), "settings": names, "reset": true }),
+    )
+    .await;
+
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
+
+/// Returns the names of the settings changed by `settings`, as they appear in the payloads.
+fn setting_names(settings: &Settings<Unchecked>) -> Vec<String> {
+    match serde_json::to_value(settings) {
+        Ok(serde_json::Value::Object(fields)) => fields.into_iter().map(|(name, _)| name).collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,91 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::{SimilarQuery, DEFAULT_SIMILAR_LIMIT};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_cs::vec::CS;
+use serde_json::{json, Value};
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::{attribute_restrictions, fuse_filters};
+use crate::routes::{fold_star_or, StarOr};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/{document_id}").route(web::get().to(SeqHandler(similar))));
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SimilarQueryGet {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "DEFAULT_SIMILAR_LIMIT")]
+    limit: usize,
+    attributes_to_retrieve: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+}
+
+impl From<SimilarQueryGet> for SimilarQuery {
+    fn from(other: SimilarQueryGet) -> Self {
+        let filter = match other.filter {
+            Some(f) => match serde_json::from_str(&f) {
+                Ok(v) => Some(v),
+                _ => Some(Value::String(f)),
+            },
+            None => None,
+        };
+
+        Self {
+            filter,
+            offset: other.offset,
+            limit: other.limit,
+            attributes_to_retrieve: other.attributes_to_retrieve.and_then(fold_star_or),
+            attribute_restrictions: None,
+        }
+    }
+}
+
+pub async fn similar(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    path: web::Path<(String, String)>,
+    params: web::Query<SimilarQueryGet>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = SimilarQuery::from(params.into_inner());
+    debug!("similar called with params: {:?}", query);
+
+    analytics.publish(
+        "Similar GET".to_string(),
+        json!({
+            "total_received": 1,
+            "limit": query.limit,
+            "offset": query.offset,
+            "filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let (index_uid, document_id) = path.into_inner();
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        let restrictions = attribute_restrictions(&search_rules);
+        if let Some(ref restrictions) = restrictions {
+            restrictions.check_filter(query.filter.as_ref())?;
+        }
+        query.attribute_restrictions = restrictions;
+        query.filter = fuse_filters(query.filter.take(), search_rules.filter);
+    }
+
+    let similar_result = meilisearch.similar(index_uid, document_id, query).await?;
+
+    debug!("returns: {:?}", similar_result);
+    Ok(HttpResponse::Ok().json(similar_result))
+}
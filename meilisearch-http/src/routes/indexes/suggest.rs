@@ -0,0 +1,51 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::SuggestQuery;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::fuse_filters;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(suggest))));
+}
+
+pub async fn suggest(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Json<SuggestQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("suggest called with params: {:?}", query);
+
+    analytics.publish(
+        "Suggestions POST".to_string(),
+        json!({
+            "total_received": 1,
+            "limit": query.limit,
+            "filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index_uid = path.into_inner();
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        query.filter = fuse_filters(query.filter.take(), search_rules.filter);
+    }
+
+    let suggest_result = meilisearch.suggest(index_uid, query).await?;
+
+    debug!("returns: {:?}", suggest_result);
+    Ok(HttpResponse::Ok().json(suggest_result))
+}
@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use actix_web::error::JsonPayloadError;
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::StreamExt;
+use log::debug;
+use meilisearch_auth::AuditAction;
+use meilisearch_lib::index::Settings;
+use meilisearch_lib::index_controller::Update;
+use meilisearch_lib::milli::update::Setting;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::error::{MeilisearchHttpError, PayloadError};
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::payload::Payload;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::audit_log;
+use crate::task::SummarizedTaskView;
+
+type Synonyms = BTreeMap<String, Vec<String>>;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/synonyms/export")
+            .route(web::get().to(SeqHandler(export_synonyms)))
+            .route(web::put().to(SeqHandler(import_synonyms))),
+    );
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SynonymsFormat {
+    /// The format of the Solr synonym files: one group of equivalent synonyms separated by
+    /// commas per line, or one-way synonyms with `a, b => c, d`.
+    Solr,
+    /// The prolog format of the WordNet synsets: `s(synset_id,w_num,'word',ss_type,...).`
+    Wordnet,
+}
+
+impl Default for SynonymsFormat {
+    fn default() -> Self {
+        Self::Solr
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SynonymsFormatQuery {
+    #[serde(default)]
+    format: SynonymsFormat,
+}
+
+pub async fn export_synonyms(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Query<SynonymsFormatQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let settings = meilisearch.settings(index_uid.into_inner()).await?;
+    let synonyms = match settings.synonyms {
+        Setting::Set(synonyms) => synonyms,
+        _ => Synonyms::new(),
+    };
+
+    let file = match params.format {
+        SynonymsFormat::Solr => to_solr(&synonyms),
+        SynonymsFormat::Wordnet => to_wordnet(&synonyms)?,
+    };
+
+    debug!("returns: {:?}", file);
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(file))
+}
+
+pub async fn import_synonyms(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Query<SynonymsFormatQuery>,
+    mut body: Payload,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut buffer = Vec::new();
+    while let Some(bytes) = body.next().await {
+        let bytes = bytes.map_err(|e| PayloadError::from(JsonPayloadError::Payload(e)))?;
+        buffer.extend_from_slice(&bytes);
+    }
+    let file = String::from_utf8(buffer)
+        .map_err(|_| MeilisearchHttpError::InvalidSynonymsFile(0, "not utf-8".to_string()))?;
+
+    let synonyms = match params.format {
+        SynonymsFormat::Solr => from_solr(&file)?,
+        SynonymsFormat::Wordnet => from_wordnet(&file)?,
+    };
+
+    analytics.publish(
+        "Synonyms Imported".to_string(),
+        json!({
+            "format": format!("{:?}", params.format),
+            "total": synonyms.len(),
+        }),
+        Some(&req),
+    );
+
+    let settings = Settings {
+        synonyms: Setting::Set(synonyms),
+        ..Default::default()
+    };
+
+    let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let update = Update::Settings {
+        settings,
+        is_deletion: false,
+        allow_index_creation,
+    };
+    let index_uid = index_uid.into_inner();
+    let task: SummarizedTaskView = meilisearch
+        .register_update(index_uid.clone(), update)
+        .await?
+        .into();
+
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::SettingsUpdated,
+        Some(index_uid),
+        json!({ "taskUid": task.task_uid(), "settings": ["synonyms"], "reset": false }),
+    )
+    .await;
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Merges the groups of equivalent synonyms and the one-way synonyms into a synonyms setting.
+#[derive(Default)]
+struct SynonymsBuilder(BTreeMap<String, BTreeSet<String>>);
+
+impl SynonymsBuilder {
+    fn add_equivalent(&mut self, words: &[String]) {
+        for word in words {
+            let synonyms = self.0.entry(word.clone()).or_default();
+            synonyms.extend(words.iter().filter(|w| *w != word).cloned());
+        }
+    }
+
+    fn add_one_way(&mut self, inputs: &[String], outputs: &[String]) {
+        for input in inputs {
+            let synonyms = self.0.entry(input.clone()).or_default();
+            synonyms.extend(outputs.iter().filter(|w| *w != input).cloned());
+        }
+    }
+
+    fn build(self) -> Synonyms {
+        self.0
+            .into_iter()
+            .filter(|(_, synonyms)| !synonyms.is_empty())
+            .map(|(word, synonyms)| (word, synonyms.into_iter().collect()))
+            .collect()
+    }
+}
+
+fn parse_solr_words(words: &str) -> Vec<String> {
+    words
+        .split(',')
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn from_solr(file: &str) -> Result<Synonyms, MeilisearchHttpError> {
+    let mut builder = SynonymsBuilder::default();
+
+    for (number, line) in file.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once("=>") {
+            Some((inputs, outputs)) => {
+                let inputs = parse_solr_words(inputs);
+                let outputs = parse_solr_words(outputs);
+                if inputs.is_empty() || outputs.is_empty() {
+                    return Err(MeilisearchHttpError::InvalidSynonymsFile(
+                        number + 1,
+                        "both sides of `=>` must contain at least one word".to_string(),
+                    ));
+                }
+                builder.add_one_way(&inputs, &outputs);
+            }
+            None => builder.add_equivalent(&parse_solr_words(line)),
+        }
+    }
+
+    Ok(builder.build())
+}
+
+/// Splits the synonyms into the groups of words that are all synonyms of each other, and the
+/// remaining one-way synonyms.
+fn equivalent_groups(synonyms: &Synonyms) -> (Vec<Vec<&str>>, Vec<(&str, &[String])>) {
+    let mut visited = BTreeSet::new();
+    let mut groups = Vec::new();
+    let mut one_way = Vec::new();
+
+    for (word, words) in synonyms {
+        if visited.contains(word.as_str()) {
+            continue;
+        }
+
+        let group: BTreeSet<&str> = std::iter::once(word.as_str())
+            .chain(words.iter().map(String::as_str))
+            .collect();
+        let is_equivalent = group.iter().all(|member| match synonyms.get(*member) {
+            Some(others) => {
+                others.len() == group.len() - 1
+                    && others
+                        .iter()
+                        .all(|other| other != member && group.contains(other.as_str()))
+            }
+            None => false,
+        });
+
+        if is_equivalent {
+            visited.extend(group.iter().copied());
+            groups.push(group.into_iter().collect());
+        } else {
+            one_way.push((word.as_str(), words.as_slice()));
+        }
+    }
+
+    (groups, one_way)
+}
+
+fn to_solr(synonyms: &Synonyms) -> String {
+    let (groups, one_way) = equivalent_groups(synonyms);
+
+    let mut file = String::new();
+    for group in groups {
+        file.push_str(&group.join(", "));
+        file.push('\n');
+    }
+    for (word, words) in one_way {
+        file.push_str(&format!("{} => {}\n", word, words.join(", ")));
+    }
+    file
+}
+
+fn from_wordnet(file: &str) -> Result<Synonyms, MeilisearchHttpError> {
+    let mut synsets: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+    for (number, line) in file.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+
+        let invalid = |reason: &str| {
+            MeilisearchHttpError::InvalidSynonymsFile(number + 1, reason.to_string())
+        };
+        let fields = line
+            .strip_prefix("s(")
+            .and_then(|line| line.strip_suffix(")."))
+            .ok_or_else(|| invalid("expected a `s(...).` clause"))?;
+        let (synset_id, rest) = fields
+            .split_once(',')
+            .ok_or_else(|| invalid("missing the synset id"))?;
+        // the word is quoted, and its quotes are escaped by doubling them.
+        let word = rest
+            .split_once('\'')
+            .map(|(_, word)| word)
+            .and_then(|word| word.rsplit_once('\''))
+            .map(|(word, _)| word.replace("''", "'"))
+            .ok_or_else(|| invalid("missing the quoted word"))?;
+
+        synsets.entry(synset_id.trim()).or_default().push(word);
+    }
+
+    let mut builder = SynonymsBuilder::default();
+    for words in synsets.values() {
+        builder.add_equivalent(words);
+    }
+    Ok(builder.build())
+}
+
+fn to_wordnet(synonyms: &Synonyms) -> Result<String, MeilisearchHttpError> {
+    let (groups, one_way) = equivalent_groups(synonyms);
+    if let Some((word, _)) = one_way.first() {
+        return Err(MeilisearchHttpError::UnsupportedSynonymsExport(
+            word.to_string(),
+        ));
+    }
+
+    let mut file = String::new();
+    for (i, group) in groups.into_iter().enumerate() {
+        for (w_num, word) in group.into_iter().enumerate() {
+            file.push_str(&format!(
+                "s({},{},'{}',n,1,0).\n",
+                100000001 + i,
+                w_num + 1,
+                word.replace('\'', "''")
+            ));
+        }
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn synonyms(entries: &[(&str, &[&str])]) -> Synonyms {
+        entries
+            .iter()
+            .map(|(word, words)| {
+                (
+                    word.to_string(),
+                    words.iter().map(|w| w.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn solr_round_trip() {
+        let file = "# a comment\n\nTV, television\ni-pod, ipod => ipod, i pod\n";
+        let parsed = from_solr(file).unwrap();
+        assert_eq!(
+            parsed,
+            synonyms(&[
+                ("TV", &["television"]),
+                ("i-pod", &["i pod", "ipod"]),
+                ("ipod", &["i pod"]),
+                ("television", &["TV"]),
+            ])
+        );
+        assert_eq!(
+            to_solr(&parsed),
+            "TV, television\ni-pod => i pod, ipod\nipod => i pod\n"
+        );
+        assert_eq!(from_solr(&to_solr(&parsed)).unwrap(), parsed);
+    }
+
+    #[test]
+    fn solr_invalid_line() {
+        assert!(matches!(
+            from_solr("tv =>"),
+            Err(MeilisearchHttpError::InvalidSynonymsFile(1, _))
+        ));
+    }
+
+    #[test]
+    fn wordnet_round_trip() {
+        let file = "s(100000001,1,'abstain',v,1,0).\ns(100000001,2,'refrain',v,1,0).\ns(100000002,1,'o''clock',n,1,0).\ns(100000002,2,'hour',n,1,0).\n";
+        let parsed = from_wordnet(file).unwrap();
+        assert_eq!(
+            parsed,
+            synonyms(&[
+                ("abstain", &["refrain"]),
+                ("hour", &["o'clock"]),
+                ("o'clock", &["hour"]),
+                ("refrain", &["abstain"]),
+            ])
+        );
+        assert_eq!(from_wordnet(&to_wordnet(&parsed).unwrap()).unwrap(), parsed);
+        assert!(to_wordnet(&synonyms(&[("tv", &["television"])])).is_err());
+    }
+}
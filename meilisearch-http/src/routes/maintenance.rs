@@ -0,0 +1,76 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{debug, info};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_maintenance)))
+            .route(web::patch().to(SeqHandler(update_maintenance))),
+    );
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MaintenanceView {
+    read_only: bool,
+    /// Whether a batch of tasks is still being processed, in read-only mode the data is only
+    /// left untouched once it is finished.
+    processing: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpdateMaintenance {
+    read_only: bool,
+}
+
+async fn maintenance_view(meilisearch: &MeiliSearch) -> Result<MaintenanceView, ResponseError> {
+    Ok(MaintenanceView {
+        read_only: meilisearch.is_read_only(),
+        processing: meilisearch.is_processing().await?,
+    })
+}
+
+pub async fn get_maintenance(
+    meilisearch: GuardedData<ActionPolicy<{ actions::MAINTENANCE_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = maintenance_view(&meilisearch).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn update_maintenance(
+    meilisearch: GuardedData<ActionPolicy<{ actions::MAINTENANCE_UPDATE }>, MeiliSearch>,
+    body: web::Json<UpdateMaintenance>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let UpdateMaintenance { read_only } = body.into_inner();
+
+    analytics.publish(
+        "Maintenance Updated".to_string(),
+        json!({ "read_only": read_only }),
+        Some(&req),
+    );
+
+    meilisearch.set_read_only(read_only).await;
+    if read_only {
+        info!("Entering the read-only mode, the write requests are rejected.");
+    } else {
+        info!("Leaving the read-only mode.");
+    }
+
+    let res = maintenance_view(&meilisearch).await?;
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
@@ -0,0 +1,31 @@
+use actix_web::http::header::ContentType;
+use actix_web::{web, HttpResponse};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::metrics::{MetricsConfig, METRICS};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(get_metrics)));
+}
+
+pub async fn get_metrics(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    config: web::Data<MetricsConfig>,
+) -> Result<HttpResponse, ResponseError> {
+    if !config.is_enabled(&meilisearch) {
+        return Err(MeilisearchHttpError::FeatureNotEnabled(
+            "metrics",
+            "--experimental-enable-metrics",
+        )
+        .into());
+    }
+
+    let metrics = METRICS.render(&meilisearch, &config).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType("text/plain; version=0.0.4".parse().unwrap()))
+        .body(metrics))
+}
@@ -11,19 +11,56 @@ use meilisearch_types::star_or::StarOr;
 
 use crate::extractors::authentication::{policies::*, GuardedData};
 
+mod aliases;
 mod api_key;
+mod audit_log;
+mod batch;
+mod config;
 mod dump;
+mod elasticsearch;
+mod experimental_features;
+#[cfg(feature = "graphql")]
+mod graphql;
+pub mod health;
+mod index_templates;
 pub mod indexes;
+mod maintenance;
+mod metrics;
+mod multi_search;
+mod openapi;
+mod replication;
+mod snapshot;
+mod swap_indexes;
 mod tasks;
+mod webhook;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::scope("/tasks").configure(tasks::configure))
-        .service(web::resource("/health").route(web::get().to(get_health)))
+        .service(web::scope("/health").configure(health::configure))
         .service(web::scope("/keys").configure(api_key::configure))
+        .service(web::scope("/audit-log").configure(audit_log::configure))
         .service(web::scope("/dumps").configure(dump::configure))
+        .service(web::scope("/snapshots").configure(snapshot::configure))
         .service(web::resource("/stats").route(web::get().to(get_stats)))
         .service(web::resource("/version").route(web::get().to(get_version)))
-        .service(web::scope("/indexes").configure(indexes::configure));
+        .service(web::scope("/metrics").configure(metrics::configure))
+        .service(web::scope("/indexes").configure(indexes::configure))
+        .service(web::scope("/swap-indexes").configure(swap_indexes::configure))
+        .service(web::scope("/aliases").configure(aliases::configure))
+        .service(web::scope("/index-templates").configure(index_templates::configure))
+        .service(web::scope("/multi-search").configure(multi_search::configure))
+        .service(web::scope("/config").configure(config::configure))
+        .service(web::scope("/maintenance").configure(maintenance::configure))
+        .service(web::scope("/replication").configure(replication::configure))
+        .service(web::scope("/experimental-features").configure(experimental_features::configure))
+        .service(web::scope("/webhook").configure(webhook::configure))
+        .service(web::scope("/batch").configure(batch::configure_batch))
+        .service(web::scope("/batches").configure(batch::configure_batches))
+        .service(web::scope("/openapi.json").configure(openapi::configure))
+        .service(web::scope("/es").configure(elasticsearch::configure));
+
+    #[cfg(feature = "graphql")]
+    cfg.service(web::scope("/graphql").configure(graphql::configure));
 }
 
 /// Extracts the raw values from the `StarOr` types and
@@ -265,7 +302,3 @@ struct KeysResponse {
     private: Option<String>,
     public: Option<String>,
 }
-
-pub async fn get_health() -> Result<HttpResponse, ResponseError> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({ "status": "available" })))
-}
@@ -0,0 +1,288 @@
+use std::cmp::Ordering;
+use std::time::Instant;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::future::join_all;
+use log::debug;
+use meilisearch_lib::index::{SearchHit, SearchQuery, SearchResult, DEFAULT_SEARCH_LIMIT};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, AuthenticationError, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(multi_search))));
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MultiSearchQuery {
+    queries: Vec<SearchQueryWithIndex>,
+    /// When set, the hits of every query are merged into a single result list.
+    federation: Option<Federation>,
+}
+
+/// The pagination parameters of a search query, which the federated search sets itself.
+const PAGINATION_PARAMETERS: [&str; 4] = ["offset", "limit", "page", "hitsPerPage"];
+
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "Map<String, Value>")]
+pub struct SearchQueryWithIndex {
+    index_uid: String,
+    federation_options: FederationOptions,
+    query: SearchQuery,
+    /// The first pagination parameter set in the query, the default `limit` of the query can't
+    /// be told apart from an explicit one once it is deserialized.
+    pagination_parameter: Option<&'static str>,
+}
+
+impl TryFrom<Map<String, Value>> for SearchQueryWithIndex {
+    type Error = serde_json::Error;
+
+    fn try_from(mut query: Map<String, Value>) -> Result<Self, Self::Error> {
+        let index_uid = query
+            .remove("indexUid")
+            .ok_or_else(|| serde::de::Error::missing_field("indexUid"))?;
+        let federation_options = match query.remove("federationOptions") {
+            Some(options) => serde_json::from_value(options)?,
+            None => FederationOptions::default(),
+        };
+        let pagination_parameter = PAGINATION_PARAMETERS
+            .into_iter()
+            .find(|parameter| query.contains_key(*parameter));
+
+        Ok(Self {
+            index_uid: serde_json::from_value(index_uid)?,
+            federation_options,
+            query: serde_json::from_value(Value::Object(query))?,
+            pagination_parameter,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Federation {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    limit: usize,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FederationOptions {
+    #[serde(default = "default_weight")]
+    weight: f64,
+}
+
+impl Default for FederationOptions {
+    fn default() -> Self {
+        Self {
+            weight: default_weight(),
+        }
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultWithIndex {
+    index_uid: String,
+    #[serde(flatten)]
+    result: SearchResult,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MultiSearchResult {
+    results: Vec<SearchResultWithIndex>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HitFederation {
+    index_uid: String,
+    queries_position: usize,
+    /// Derived from the rank of the hit in the results of its query, it tells nothing about
+    /// the relevancy of the hit.
+    weighted_rank_score: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FederatedHit {
+    #[serde(flatten)]
+    hit: SearchHit,
+    #[serde(rename = "_federation")]
+    federation: HitFederation,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedSearchResult {
+    hits: Vec<FederatedHit>,
+    estimated_total_hits: u64,
+    limit: usize,
+    offset: usize,
+    processing_time_ms: u128,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    degraded: bool,
+}
+
+pub async fn multi_search(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    params: web::Json<MultiSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let MultiSearchQuery {
+        queries,
+        federation,
+    } = params.into_inner();
+    debug!("multi-search called with params: {:?}", queries);
+
+    analytics.publish(
+        "Multi Search POST".to_string(),
+        json!({
+            "total_queries": queries.len(),
+            "federated": federation.is_some(),
+        }),
+        Some(&req),
+    );
+
+    // The route is not bound to an index, so we must check that each query targets an index
+    // the key is allowed to search, and apply the tenant token search rules of this index.
+    let mut checked_queries = Vec::with_capacity(queries.len());
+    for (
+        position,
+        SearchQueryWithIndex {
+            index_uid,
+            federation_options,
+            mut query,
+            pagination_parameter,
+        },
+    ) in queries.into_iter().enumerate()
+    {
+        match meilisearch
+            .filters()
+            .search_rules
+            .get_index_search_rules(&index_uid)
+        {
+            Some(search_rules) => add_search_rules(&mut query, search_rules)?,
+            None => return Err(AuthenticationError::InvalidToken.into()),
+        }
+
+        let weight = federation_options.weight;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(MeilisearchHttpError::InvalidFederationWeight(position, weight).into());
+        }
+
+        // In federated mode, every query must return enough hits to fill the whole
+        // pagination window, the window is applied once the hits are merged.
+        if let Some(Federation { offset, limit }) = federation {
+            if let Some(parameter) = pagination_parameter {
+                return Err(
+                    MeilisearchHttpError::PaginationInFederatedQuery(position, parameter).into(),
+                );
+            }
+            query.offset = Some(0);
+            query.limit = offset.saturating_add(limit);
+            query.page = None;
+            query.hits_per_page = None;
+        }
+
+        checked_queries.push((index_uid, weight, query));
+    }
+
+    let before_search = Instant::now();
+
+    let meilisearch: &MeiliSearch = &meilisearch;
+    let searches = checked_queries
+        .into_iter()
+        .map(|(index_uid, weight, query)| async move {
+            let result = meilisearch.search(index_uid.clone(), query).await?;
+            Ok((SearchResultWithIndex { index_uid, result }, weight))
+        });
+
+    let results = join_all(searches)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, ResponseError>>()?;
+
+    match federation {
+        Some(federation) => {
+            let ret = merge_results(results, federation, before_search);
+            debug!("returns: {:?}", ret);
+            Ok(HttpResponse::Ok().json(ret))
+        }
+        None => {
+            let results = results.into_iter().map(|(result, _)| result).collect();
+            let ret = MultiSearchResult { results };
+            debug!("returns: {:?}", ret);
+            Ok(HttpResponse::Ok().json(ret))
+        }
+    }
+}
+
+/// Merges the hits of every query into a single list ordered by their weighted rank.
+///
+/// The engine doesn't expose the ranking score of the hits, so the hits are only compared by
+/// their rank in their own result list: the first hit of a query scores `1.0`, the following
+/// ones decrease linearly. The score is then multiplied by the weight of the query. Hits with
+/// equal scores are ordered by the position of their query in the request.
+fn merge_results(
+    results: Vec<(SearchResultWithIndex, f64)>,
+    Federation { offset, limit }: Federation,
+    before_search: Instant,
+) -> FederatedSearchResult {
+    let window = offset.saturating_add(limit).max(1) as f64;
+    let mut estimated_total_hits = 0;
+    let mut degraded = false;
+    let mut hits = Vec::new();
+
+    for (queries_position, (SearchResultWithIndex { index_uid, result }, weight)) in
+        results.into_iter().enumerate()
+    {
+        estimated_total_hits += result.hits_info.total_hits();
+        degraded |= result.degraded;
+        for (rank, hit) in result.hits.into_iter().enumerate() {
+            let rank_score = 1.0 - rank as f64 / window;
+            hits.push(FederatedHit {
+                hit,
+                federation: HitFederation {
+                    index_uid: index_uid.clone(),
+                    queries_position,
+                    weighted_rank_score: rank_score * weight,
+                },
+            });
+        }
+    }
+
+    // `sort_by` is stable, hits with the same score keep the order of their query.
+    hits.sort_by(|left, right| {
+        right
+            .federation
+            .weighted_rank_score
+            .partial_cmp(&left.federation.weighted_rank_score)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let hits = hits.into_iter().skip(offset).take(limit).collect();
+
+    FederatedSearchResult {
+        hits,
+        estimated_total_hits,
+        limit,
+        offset,
+        processing_time_ms: before_search.elapsed().as_millis(),
+        degraded,
+    }
+}
@@ -0,0 +1,583 @@
+//! The OpenAPI specification of the routes, served on `GET /openapi.json` to generate the
+//! client SDKs and configure the API gateways.
+//!
+//! The operations listed in [`OPERATIONS`] must follow the `configure` functions of the routes,
+//! the error codes come from [`Code`].
+
+use actix_web::{web, HttpResponse};
+use meilisearch_auth::Action;
+use meilisearch_types::error::Code;
+use once_cell::sync::Lazy;
+use serde_json::{json, Map, Value};
+
+use crate::extractors::authentication::policies::actions;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(get_openapi)));
+}
+
+/// Who may call an operation.
+#[derive(Debug, Clone, Copy)]
+enum Access {
+    /// Anyone, even without a key.
+    Public,
+    /// Only the master key.
+    Master,
+    /// The keys allowed to perform the action.
+    Key(u8),
+    /// The keys allowed to perform the actions of each operation of the request.
+    PerOperation,
+}
+
+/// The body of a request.
+#[derive(Debug, Clone, Copy)]
+enum Body {
+    None,
+    Json(&'static str),
+    /// The documents in JSON, NDJSON or CSV.
+    Documents,
+    /// The raw bytes of a file.
+    Binary,
+    /// The NDJSON lines of an Elasticsearch `_bulk` request.
+    Ndjson,
+}
+
+/// The body of a successful response.
+#[derive(Debug, Clone, Copy)]
+enum Response {
+    /// `200 OK` with a JSON body.
+    Json(&'static str),
+    /// `201 Created` with a JSON body.
+    Created(&'static str),
+    /// `202 Accepted` with the summary of the enqueued task.
+    Task,
+    /// `202 Accepted` with a JSON body.
+    Accepted(&'static str),
+    /// `204 No Content`.
+    NoContent,
+    /// `200 OK` with a body of the given content type.
+    Raw(&'static str),
+}
+
+#[derive(Debug)]
+struct Operation {
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    access: Access,
+    body: Body,
+    response: Response,
+}
+
+const fn op(
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    access: Access,
+    body: Body,
+    response: Response,
+) -> Operation {
+    Operation {
+        method,
+        path,
+        summary,
+        access,
+        body,
+        response,
+    }
+}
+
+use Access::*;
+use Body::{Binary, Documents, Json as JsonBody, Ndjson, None as NoBody};
+use Response::{Accepted, Created, Json, NoContent, Raw, Task};
+
+const SEARCH_QUERY: &str = "SearchQuery";
+const SEARCH_RESULT: &str = "SearchResult";
+
+/// The operations, in the order of [`super::configure`].
+#[rustfmt::skip]
+const OPERATIONS: &[Operation] = &[
+    op("get", "/tasks", "List the tasks", Key(actions::TASKS_GET), NoBody, Json("TaskList")),
+    op("delete", "/tasks", "Delete the finished tasks matching the filters", Key(actions::TASKS_DELETE), NoBody, Task),
+    op("post", "/tasks/cancel", "Cancel the tasks matching the filters", Key(actions::TASKS_CANCEL), NoBody, Task),
+    op("get", "/tasks/{task_id}", "Get a task", Key(actions::TASKS_GET), NoBody, Json("Task")),
+    op("patch", "/tasks/{task_id}", "Change the priority of an enqueued task", Key(actions::TASKS_UPDATE), JsonBody("Object"), Json("Task")),
+    op("post", "/tasks/{task_id}/move-to-front", "Process an enqueued task first", Key(actions::TASKS_UPDATE), NoBody, Json("Task")),
+    op("get", "/health", "Check that the instance is running", Public, NoBody, Json("Object")),
+    op("get", "/health/live", "Check that the instance is running", Public, NoBody, Json("Object")),
+    op("get", "/health/ready", "Check that the instance can serve the requests", Public, NoBody, Json("Object")),
+    op("get", "/keys", "List the API keys", Key(actions::KEYS_GET), NoBody, Json("KeyList")),
+    op("post", "/keys", "Create an API key", Key(actions::KEYS_CREATE), JsonBody("Key"), Created("Key")),
+    op("get", "/keys/{key}", "Get an API key", Key(actions::KEYS_GET), NoBody, Json("Key")),
+    op("patch", "/keys/{key}", "Update an API key", Key(actions::KEYS_UPDATE), JsonBody("Key"), Json("Key")),
+    op("delete", "/keys/{key}", "Delete an API key", Key(actions::KEYS_DELETE), NoBody, NoContent),
+    op("post", "/keys/{key}/rotate", "Replace an API key by a new one", Key(actions::KEYS_ROTATE), NoBody, Created("Key")),
+    op("get", "/audit-log", "List the audit log entries", Key(actions::AUDIT_LOG_GET), NoBody, Json("Object")),
+    op("get", "/audit-log/export", "Export the audit log as NDJSON", Key(actions::AUDIT_LOG_GET), NoBody, Raw("application/x-ndjson")),
+    op("post", "/dumps", "Create a dump", Key(actions::DUMPS_CREATE), NoBody, Task),
+    op("post", "/dumps/import", "Import a dump, uploaded or from a location", Key(actions::DUMPS_IMPORT), Binary, Task),
+    op("post", "/snapshots", "Create a snapshot", Key(actions::SNAPSHOTS_CREATE), NoBody, Accepted("Object")),
+    op("get", "/stats", "Get the stats of the instance", Key(actions::STATS_GET), NoBody, Json("Object")),
+    op("get", "/version", "Get the version of the instance", Key(actions::VERSION), NoBody, Json("Version")),
+    op("get", "/metrics", "Get the Prometheus metrics", Master, NoBody, Raw("text/plain")),
+    op("get", "/indexes", "List the indexes", Key(actions::INDEXES_GET), NoBody, Json("IndexList")),
+    op("post", "/indexes", "Create an index", Key(actions::INDEXES_CREATE), JsonBody("Object"), Task),
+    op("get", "/indexes/{index_uid}", "Get an index", Key(actions::INDEXES_GET), NoBody, Json("Index")),
+    op("patch", "/indexes/{index_uid}", "Update an index", Key(actions::INDEXES_UPDATE), JsonBody("Object"), Task),
+    op("delete", "/indexes/{index_uid}", "Delete an index", Key(actions::INDEXES_DELETE), NoBody, Task),
+    op("get", "/indexes/{index_uid}/stats", "Get the stats of an index", Key(actions::STATS_GET), NoBody, Json("Object")),
+    op("get", "/indexes/{index_uid}/analytics", "Get the most frequent queries of an index", Key(actions::SEARCH_ANALYTICS_GET), NoBody, Json("Object")),
+    op("get", "/indexes/{index_uid}/changes", "List the changes of the documents since a sequence number", Key(actions::DOCUMENTS_GET), NoBody, Json("Object")),
+    op("post", "/indexes/{index_uid}/clone", "Clone an index", Key(actions::INDEXES_CREATE), JsonBody("Object"), Task),
+    op("get", "/indexes/{index_uid}/documents", "List the documents", Key(actions::DOCUMENTS_GET), NoBody, Json("DocumentPage")),
+    op("post", "/indexes/{index_uid}/documents", "Add or replace documents", Key(actions::DOCUMENTS_ADD), Documents, Task),
+    op("put", "/indexes/{index_uid}/documents", "Add or update documents", Key(actions::DOCUMENTS_ADD), Documents, Task),
+    op("delete", "/indexes/{index_uid}/documents", "Delete all the documents", Key(actions::DOCUMENTS_DELETE), NoBody, Task),
+    op("post", "/indexes/{index_uid}/documents/delete-batch", "Delete documents by id", Key(actions::DOCUMENTS_DELETE), JsonBody("Array"), Task),
+    op("post", "/indexes/{index_uid}/documents/delete", "Delete the documents matching a filter", Key(actions::DOCUMENTS_DELETE), JsonBody("Object"), Task),
+    op("post", "/indexes/{index_uid}/documents/fetch", "Fetch documents", Key(actions::DOCUMENTS_GET), JsonBody("Object"), Json("DocumentPage")),
+    op("post", "/indexes/{index_uid}/documents/edit", "Edit documents with a function", Key(actions::DOCUMENTS_ADD), JsonBody("Object"), Task),
+    op("post", "/indexes/{index_uid}/documents/restore", "Restore deleted documents", Key(actions::DOCUMENTS_ADD), JsonBody("Object"), Task),
+    op("get", "/indexes/{index_uid}/documents/export", "Export the documents as NDJSON", Key(actions::DOCUMENTS_GET), NoBody, Raw("application/x-ndjson")),
+    op("get", "/indexes/{index_uid}/documents/{document_id}", "Get a document", Key(actions::DOCUMENTS_GET), NoBody, Json("Document")),
+    op("delete", "/indexes/{index_uid}/documents/{document_id}", "Delete a document", Key(actions::DOCUMENTS_DELETE), NoBody, Task),
+    op("post", "/indexes/{index_uid}/facet-search", "Search the values of a facet", Key(actions::SEARCH), JsonBody("Object"), Json("Object")),
+    op("post", "/indexes/{index_uid}/suggest", "Suggest completions of a query", Key(actions::SEARCH), JsonBody("Object"), Json("Object")),
+    op("get", "/indexes/{index_uid}/similar/{document_id}", "Get the documents most similar to a document", Key(actions::SEARCH), NoBody, Json("Object")),
+    op("get", "/indexes/{index_uid}/search", "Search an index with the query parameters", Key(actions::SEARCH), NoBody, Json(SEARCH_RESULT)),
+    op("post", "/indexes/{index_uid}/search", "Search an index", Key(actions::SEARCH), JsonBody(SEARCH_QUERY), Json(SEARCH_RESULT)),
+    op("get", "/indexes/{index_uid}/settings", "Get the settings", Key(actions::SETTINGS_GET), NoBody, Json("Settings")),
+    op("patch", "/indexes/{index_uid}/settings", "Update the settings", Key(actions::SETTINGS_UPDATE), JsonBody("Settings"), Task),
+    op("delete", "/indexes/{index_uid}/settings", "Reset the settings", Key(actions::SETTINGS_UPDATE), NoBody, Task),
+    op("get", "/indexes/{index_uid}/settings/synonyms/export", "Export the synonyms", Key(actions::SETTINGS_GET), NoBody, Raw("text/plain")),
+    op("put", "/indexes/{index_uid}/settings/synonyms/export", "Import the synonyms", Key(actions::SETTINGS_UPDATE), Binary, Task),
+    op("post", "/swap-indexes", "Swap indexes", Key(actions::INDEXES_SWAP), JsonBody("Array"), Task),
+    op("get", "/aliases", "List the aliases", Key(actions::ALIASES_GET), NoBody, Json("Object")),
+    op("get", "/aliases/{alias}", "Get an alias", Key(actions::ALIASES_GET), NoBody, Json("Object")),
+    op("put", "/aliases/{alias}", "Point an alias to an index", Key(actions::ALIASES_UPDATE), JsonBody("Object"), Json("Object")),
+    op("delete", "/aliases/{alias}", "Delete an alias", Key(actions::ALIASES_UPDATE), NoBody, NoContent),
+    op("get", "/index-templates", "List the index templates", Key(actions::TEMPLATES_GET), NoBody, Json("Object")),
+    op("get", "/index-templates/{name}", "Get an index template", Key(actions::TEMPLATES_GET), NoBody, Json("Object")),
+    op("put", "/index-templates/{name}", "Create or replace an index template", Key(actions::TEMPLATES_UPDATE), JsonBody("Object"), Json("Object")),
+    op("delete", "/index-templates/{name}", "Delete an index template", Key(actions::TEMPLATES_UPDATE), NoBody, NoContent),
+    op("post", "/multi-search", "Search several indexes", Key(actions::SEARCH), JsonBody("Object"), Json("Object")),
+    op("get", "/config", "Get the runtime configuration", Key(actions::CONFIG_GET), NoBody, Json("Object")),
+    op("patch", "/config", "Update the runtime configuration", Key(actions::CONFIG_UPDATE), JsonBody("Object"), Json("Object")),
+    op("get", "/maintenance", "Get the maintenance mode", Key(actions::MAINTENANCE_GET), NoBody, Json("Object")),
+    op("patch", "/maintenance", "Enter or leave the read-only mode", Key(actions::MAINTENANCE_UPDATE), JsonBody("Object"), Json("Object")),
+    op("get", "/replication/tasks", "List the finished tasks to replay on the followers", Key(actions::REPLICATION_GET), NoBody, Json("Object")),
+    op("get", "/replication/update-files/{uuid}", "Get the documents of a replicated document addition", Key(actions::REPLICATION_GET), NoBody, Raw("application/x-ndjson")),
+    op("get", "/experimental-features", "Get the experimental features enabled at runtime", Key(actions::EXPERIMENTAL_FEATURES_GET), NoBody, Json("Object")),
+    op("patch", "/experimental-features", "Toggle the experimental features at runtime", Key(actions::EXPERIMENTAL_FEATURES_UPDATE), JsonBody("Object"), Json("Object")),
+    op("get", "/webhook", "Get the task webhook", Key(actions::WEBHOOKS_GET), NoBody, Json("Object")),
+    op("put", "/webhook", "Set the task webhook", Key(actions::WEBHOOKS_UPDATE), JsonBody("Object"), Json("Object")),
+    op("delete", "/webhook", "Delete the task webhook", Key(actions::WEBHOOKS_UPDATE), NoBody, NoContent),
+    op("post", "/batch", "Register several operations at once", PerOperation, JsonBody("Object"), Accepted("Object")),
+    op("get", "/batches/{group_uid}", "Get a group of operations", PerOperation, NoBody, Json("Object")),
+    op("get", "/openapi.json", "Get this specification", Public, NoBody, Json("Object")),
+    op("post", "/es/{index_uid}/_search", "Search an index with the Elasticsearch query DSL", Key(actions::SEARCH), JsonBody("Object"), Json("Object")),
+    op("post", "/es/_bulk", "Index or delete documents in the Elasticsearch bulk format", PerOperation, Ndjson, Json("Object")),
+    op("post", "/es/{index_uid}/_bulk", "Index or delete documents of an index in the Elasticsearch bulk format", PerOperation, Ndjson, Json("Object")),
+];
+
+/// The settings served on `/indexes/{index_uid}/settings/<route>`, with their update verb and
+/// the schema of their value.
+fn settings() -> Vec<(&'static str, &'static str, Value)> {
+    let strings = || json!({ "type": "array", "items": { "type": "string" } });
+    vec![
+        ("filterable-attributes", "put", strings()),
+        ("sortable-attributes", "put", strings()),
+        ("displayed-attributes", "put", strings()),
+        ("searchable-attributes", "put", strings()),
+        ("distinct-attribute", "put", json!({ "type": "string" })),
+        ("stop-words", "put", strings()),
+        (
+            "synonyms",
+            "put",
+            json!({ "type": "object", "additionalProperties": strings() }),
+        ),
+        ("ranking-rules", "put", strings()),
+        ("typo-tolerance", "patch", json!({ "type": "object" })),
+        ("search-cutoff-ms", "put", json!({ "type": "integer" })),
+        (
+            "ranking-profiles",
+            "put",
+            json!({ "type": "object", "additionalProperties": { "type": "object" } }),
+        ),
+        ("expiry-attribute", "put", json!({ "type": "string" })),
+        ("version-attribute", "put", json!({ "type": "string" })),
+    ]
+}
+
+static SPECIFICATION: Lazy<Value> = Lazy::new(specification);
+
+pub async fn get_openapi() -> HttpResponse {
+    HttpResponse::Ok().json(&*SPECIFICATION)
+}
+
+fn specification() -> Value {
+    let mut paths = Map::new();
+    for operation in OPERATIONS {
+        insert_operation(
+            &mut paths,
+            operation.path,
+            operation.method,
+            operation_object(operation),
+        );
+    }
+
+    #[cfg(feature = "graphql")]
+    {
+        let graphql = op(
+            "post",
+            "/graphql",
+            "Run a GraphQL query",
+            PerOperation,
+            JsonBody("Object"),
+            Json("Object"),
+        );
+        insert_operation(
+            &mut paths,
+            graphql.path,
+            graphql.method,
+            operation_object(&graphql),
+        );
+    }
+
+    for (route, verb, schema) in settings() {
+        let path = format!("/indexes/{{index_uid}}/settings/{}", route);
+        let read = op(
+            "get",
+            "",
+            "Get a setting",
+            Key(actions::SETTINGS_GET),
+            NoBody,
+            Json(""),
+        );
+        let mut get = operation_object(&read);
+        get["responses"]["200"]["content"]["application/json"]["schema"] = schema.clone();
+        insert_operation(&mut paths, &path, "get", get);
+
+        let write = op(
+            "",
+            "",
+            "Update a setting",
+            Key(actions::SETTINGS_UPDATE),
+            NoBody,
+            Task,
+        );
+        let mut update = operation_object(&write);
+        update["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema } },
+        });
+        insert_operation(&mut paths, &path, verb, update);
+
+        let reset = op(
+            "",
+            "",
+            "Reset a setting",
+            Key(actions::SETTINGS_UPDATE),
+            NoBody,
+            Task,
+        );
+        insert_operation(&mut paths, &path, "delete", operation_object(&reset));
+    }
+
+    for (path, item) in paths.iter_mut() {
+        let parameters = path_parameters(path);
+        if !parameters.is_empty() {
+            item["parameters"] = Value::Array(parameters);
+        }
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Meilisearch",
+            "version": env!("CARGO_PKG_VERSION"),
+            "license": { "name": "MIT" },
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "apiKey": { "type": "http", "scheme": "bearer" },
+            },
+            "schemas": schemas(),
+        },
+    })
+}
+
+fn insert_operation(paths: &mut Map<String, Value>, path: &str, method: &str, operation: Value) {
+    let item = paths.entry(path).or_insert_with(|| json!({}));
+    item[method] = operation;
+}
+
+fn operation_object(operation: &Operation) -> Value {
+    let mut object = json!({ "summary": operation.summary });
+
+    match operation.access {
+        Public => object["security"] = json!([]),
+        Master => {
+            object["security"] = json!([{ "apiKey": [] }]);
+            object["x-meilisearch-action"] = json!("master key");
+        }
+        Key(action) => {
+            object["security"] = json!([{ "apiKey": [] }]);
+            if let Some(action) = Action::from_repr(action) {
+                object["x-meilisearch-action"] = json!(action);
+            }
+        }
+        PerOperation => object["security"] = json!([{ "apiKey": [] }]),
+    }
+
+    match operation.body {
+        NoBody => (),
+        JsonBody(schema) => {
+            object["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema_ref(schema) } },
+            })
+        }
+        Documents => {
+            let documents = schema_ref("DocumentList");
+            object["requestBody"] = json!({
+                "required": true,
+                "content": {
+                    "application/json": { "schema": documents },
+                    "application/x-ndjson": { "schema": { "type": "string" } },
+                    "text/csv": { "schema": { "type": "string" } },
+                    "application/msgpack": { "schema": { "type": "string", "format": "binary" } },
+                    "application/cbor": { "schema": { "type": "string", "format": "binary" } },
+                },
+            })
+        }
+        Binary => {
+            object["requestBody"] = json!({
+                "required": true,
+                "content": {
+                    "application/octet-stream": {
+                        "schema": { "type": "string", "format": "binary" },
+                    },
+                },
+            })
+        }
+        Ndjson => {
+            object["requestBody"] = json!({
+                "required": true,
+                "content": { "application/x-ndjson": { "schema": { "type": "string" } } },
+            })
+        }
+    }
+
+    let (status, response) = match operation.response {
+        Json(schema) => ("200", json_response(schema)),
+        Created(schema) => ("201", json_response(schema)),
+        Task => ("202", json_response("SummarizedTask")),
+        Accepted(schema) => ("202", json_response(schema)),
+        NoContent => ("204", json!({ "description": "No Content" })),
+        Raw(content_type) => (
+            "200",
+            json!({
+                "description": "OK",
+                "content": { content_type: { "schema": { "type": "string" } } },
+            }),
+        ),
+    };
+    object["responses"] = json!({
+        status: response,
+        "default": json_response("Error"),
+    });
+
+    object
+}
+
+fn json_response(schema: &str) -> Value {
+    json!({
+        "description": "OK",
+        "content": { "application/json": { "schema": schema_ref(schema) } },
+    })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+/// Returns the parameters of a path, e.g. `index_uid` in `/indexes/{index_uid}`.
+fn path_parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| {
+            let schema = match name {
+                "task_id" | "batch_uid" => json!({ "type": "integer", "minimum": 0 }),
+                _ => json!({ "type": "string" }),
+            };
+            json!({ "name": name, "in": "path", "required": true, "schema": schema })
+        })
+        .collect()
+}
+
+fn schemas() -> Value {
+    let codes: Vec<_> = Code::all().map(|code| code.name()).collect();
+    let mut types: Vec<_> = Code::all().map(|code| code.type_()).collect();
+    types.sort_unstable();
+    types.dedup();
+
+    let statuses = ["enqueued", "processing", "succeeded", "failed", "canceled"];
+    let task_types = [
+        "indexCreation",
+        "indexUpdate",
+        "indexDeletion",
+        "indexSwap",
+        "indexClone",
+        "documentAdditionOrUpdate",
+        "documentDeletion",
+        "documentEdition",
+        "documentRestoration",
+        "settingsUpdate",
+        "dumpCreation",
+        "dumpImport",
+        "taskCancelation",
+        "taskDeletion",
+    ];
+    let list = |items: &str| {
+        json!({
+            "type": "object",
+            "properties": {
+                "results": { "type": "array", "items": schema_ref(items) },
+                "offset": { "type": "integer" },
+                "limit": { "type": "integer" },
+                "total": { "type": "integer" },
+            },
+        })
+    };
+    let actions: Vec<_> = (0..=u8::MAX)
+        .filter_map(Action::from_repr)
+        .map(|action| json!(action))
+        .collect();
+
+    json!({
+        "Object": { "type": "object" },
+        "Array": { "type": "array", "items": {} },
+        "Error": {
+            "type": "object",
+            "required": ["message", "code", "type", "link"],
+            "properties": {
+                "message": { "type": "string" },
+                "code": { "type": "string", "enum": codes },
+                "type": { "type": "string", "enum": types },
+                "link": { "type": "string", "format": "uri" },
+            },
+        },
+        "SummarizedTask": {
+            "type": "object",
+            "required": ["taskUid", "status", "type", "enqueuedAt"],
+            "properties": {
+                "taskUid": { "type": "integer" },
+                "indexUid": { "type": "string", "nullable": true },
+                "status": { "type": "string", "enum": statuses },
+                "type": { "type": "string", "enum": task_types },
+                "enqueuedAt": { "type": "string", "format": "date-time" },
+            },
+        },
+        "Task": {
+            "type": "object",
+            "properties": {
+                "uid": { "type": "integer" },
+                "indexUid": { "type": "string", "nullable": true },
+                "status": { "type": "string", "enum": statuses },
+                "type": { "type": "string", "enum": task_types },
+                "details": { "type": "object" },
+                "error": schema_ref("Error"),
+                "duration": { "type": "string", "nullable": true },
+                "enqueuedAt": { "type": "string", "format": "date-time" },
+                "startedAt": { "type": "string", "format": "date-time", "nullable": true },
+                "finishedAt": { "type": "string", "format": "date-time", "nullable": true },
+            },
+        },
+        "TaskList": list("Task"),
+        "Index": {
+            "type": "object",
+            "properties": {
+                "uid": { "type": "string" },
+                "primaryKey": { "type": "string", "nullable": true },
+                "createdAt": { "type": "string", "format": "date-time" },
+                "updatedAt": { "type": "string", "format": "date-time" },
+            },
+        },
+        "IndexList": list("Index"),
+        "Document": { "type": "object", "additionalProperties": true },
+        "DocumentList": { "type": "array", "items": schema_ref("Document") },
+        "DocumentPage": list("Document"),
+        "Key": {
+            "type": "object",
+            "properties": {
+                "uid": { "type": "string", "format": "uuid" },
+                "key": { "type": "string", "readOnly": true },
+                "name": { "type": "string", "nullable": true },
+                "description": { "type": "string", "nullable": true },
+                "actions": { "type": "array", "items": { "type": "string", "enum": actions } },
+                "indexes": { "type": "array", "items": { "type": "string" } },
+                "expiresAt": { "type": "string", "format": "date-time", "nullable": true },
+            },
+        },
+        "KeyList": list("Key"),
+        "Settings": {
+            "type": "object",
+            "properties": settings()
+                .into_iter()
+                .map(|(route, _, schema)| (camel_case(route), schema))
+                .collect::<Map<_, _>>(),
+        },
+        SEARCH_QUERY: {
+            "type": "object",
+            "properties": {
+                "q": { "type": "string", "nullable": true },
+                "offset": { "type": "integer" },
+                "limit": { "type": "integer" },
+                "page": { "type": "integer" },
+                "hitsPerPage": { "type": "integer" },
+                "filter": {},
+                "sort": { "type": "array", "items": { "type": "string" } },
+                "facets": { "type": "array", "items": { "type": "string" } },
+                "attributesToRetrieve": { "type": "array", "items": { "type": "string" } },
+                "attributesToHighlight": { "type": "array", "items": { "type": "string" } },
+                "attributesToCrop": { "type": "array", "items": { "type": "string" } },
+                "showMatchesPosition": { "type": "boolean" },
+            },
+        },
+        SEARCH_RESULT: {
+            "type": "object",
+            "properties": {
+                "hits": schema_ref("DocumentList"),
+                "query": { "type": "string" },
+                "processingTimeMs": { "type": "integer" },
+                "estimatedTotalHits": { "type": "integer" },
+                "totalHits": { "type": "integer" },
+                "facetDistribution": { "type": "object" },
+            },
+        },
+        "Version": {
+            "type": "object",
+            "properties": {
+                "commitSha": { "type": "string" },
+                "commitDate": { "type": "string" },
+                "pkgVersion": { "type": "string" },
+            },
+        },
+    })
+}
+
+/// `filterable-attributes` → `filterableAttributes`.
+fn camel_case(route: &str) -> String {
+    let mut words = route.split('-');
+    let mut name = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
@@ -0,0 +1,58 @@
+use actix_web::{web, HttpResponse};
+use futures::TryStreamExt;
+use log::debug;
+use meilisearch_lib::tasks::task::BatchSeq;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+/// The number of batches returned at once when the followers don't ask for a limit.
+const DEFAULT_REPLICATION_LIMIT: fn() -> usize = || 100;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/tasks").route(web::get().to(SeqHandler(get_replication_log))))
+        .service(
+            web::resource("/update-files/{uuid}").route(web::get().to(SeqHandler(get_update_file))),
+        );
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReplicationLogQuery {
+    after: Option<BatchSeq>,
+    #[serde(default = "DEFAULT_REPLICATION_LIMIT")]
+    limit: usize,
+}
+
+/// Returns the finished tasks to replay on the followers, processed after the `after` batch.
+pub async fn get_replication_log(
+    meilisearch: GuardedData<ActionPolicy<{ actions::REPLICATION_GET }>, MeiliSearch>,
+    params: web::Query<ReplicationLogQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let ReplicationLogQuery { after, limit } = params.into_inner();
+    let log = meilisearch.replication_log(after, limit).await?;
+
+    debug!(
+        "returns: {} tasks up to the batch {:?}",
+        log.tasks.len(),
+        log.last_batch_seq
+    );
+    Ok(HttpResponse::Ok().json(log))
+}
+
+/// Returns the documents of the update file of a document addition, as NDJSON. The file is
+/// streamed, it's never held in memory.
+pub async fn get_update_file(
+    meilisearch: GuardedData<ActionPolicy<{ actions::REPLICATION_GET }>, MeiliSearch>,
+    uuid: web::Path<Uuid>,
+) -> Result<HttpResponse, ResponseError> {
+    let chunks = meilisearch.replication_update_file(uuid.into_inner())?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(chunks.map_err(ResponseError::from)))
+}
@@ -0,0 +1,49 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_auth::AuditAction;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Serialize;
+use serde_json::json;
+use time::OffsetDateTime;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::audit_log;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(create_snapshot))));
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotView {
+    #[serde(with = "time::serde::rfc3339")]
+    enqueued_at: OffsetDateTime,
+}
+
+/// Schedules a snapshot, snapshots are not tasks and can't be followed with the tasks route.
+pub async fn create_snapshot(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SNAPSHOTS_CREATE }>, MeiliSearch>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Snapshot Created".to_string(), json!({}), Some(&req));
+
+    meilisearch.register_snapshot().await?;
+    audit_log::record(
+        &req,
+        meilisearch.filters().key_uid,
+        AuditAction::SnapshotCreated,
+        None,
+        json!({}),
+    )
+    .await;
+    let res = SnapshotView {
+        enqueued_at: OffsetDateTime::now_utc(),
+    };
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Accepted().json(res))
+}
@@ -0,0 +1,53 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, AuthenticationError, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::task::SummarizedTaskView;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(swap_indexes))));
+}
+
+/// A pair of indexes exchanging their documents and settings.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SwapIndexesPayload {
+    indexes: (String, String),
+}
+
+pub async fn swap_indexes(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_SWAP }>, MeiliSearch>,
+    body: web::Json<Vec<SwapIndexesPayload>>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let swaps = body.into_inner();
+    debug!("called with params: {:?}", swaps);
+
+    // The key must be allowed to access every index involved.
+    let search_rules = &meilisearch.filters().search_rules;
+    for swap in &swaps {
+        let (left, right) = &swap.indexes;
+        if !search_rules.is_index_authorized(left) || !search_rules.is_index_authorized(right) {
+            return Err(AuthenticationError::InvalidToken.into());
+        }
+    }
+
+    analytics.publish(
+        "Indexes Swapped".to_string(),
+        json!({ "swap_operation_number": swaps.len() }),
+        Some(&req),
+    );
+
+    let swaps = swaps.into_iter().map(|swap| swap.indexes).collect();
+    let task: SummarizedTaskView = meilisearch.register_index_swap(swaps).await?.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
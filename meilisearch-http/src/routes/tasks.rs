@@ -1,49 +1,268 @@
 use actix_web::{web, HttpRequest, HttpResponse};
-use meilisearch_lib::tasks::task::{TaskContent, TaskEvent, TaskId};
+use log::debug;
+use meilisearch_auth::SearchRules;
+use meilisearch_lib::tasks::task::{Task, TaskContent, TaskEvent, TaskId, TaskPriority};
 use meilisearch_lib::tasks::TaskFilter;
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::index_uid::IndexUid;
 use meilisearch_types::star_or::StarOr;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_cs::vec::CS;
 use serde_json::json;
+use time::OffsetDateTime;
 
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::{TaskListView, TaskStatus, TaskType, TaskView};
+use crate::task::{SummarizedTaskView, TaskListView, TaskStatus, TaskType, TaskView};
 
 use super::fold_star_or;
 
 const DEFAULT_LIMIT: fn() -> usize = || 20;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_tasks))))
-        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_tasks)))
+            .route(web::delete().to(SeqHandler(delete_tasks))),
+    )
+    .service(web::resource("/cancel").route(web::post().to(SeqHandler(cancel_tasks))))
+    .service(
+        web::resource("/{task_id}")
+            .route(web::get().to(SeqHandler(get_task)))
+            .route(web::patch().to(SeqHandler(update_task))),
+    )
+    .service(
+        web::resource("/{task_id}/move-to-front").route(web::post().to(SeqHandler(move_to_front))),
+    );
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct TaskFilterQuery {
-    #[serde(rename = "type")]
-    type_: Option<CS<StarOr<TaskType>>>,
-    status: Option<CS<StarOr<TaskStatus>>>,
-    index_uid: Option<CS<StarOr<IndexUid>>>,
+    uids: Option<CS<TaskId>>,
+    #[serde(alias = "type")]
+    types: Option<CS<StarOr<TaskType>>>,
+    #[serde(alias = "status")]
+    statuses: Option<CS<StarOr<TaskStatus>>>,
+    #[serde(alias = "indexUid")]
+    index_uids: Option<CS<StarOr<IndexUid>>>,
+    canceled_by: Option<CS<TaskId>>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_started_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_started_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_finished_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_finished_at: Option<OffsetDateTime>,
     #[serde(default = "DEFAULT_LIMIT")]
     limit: usize,
     from: Option<TaskId>,
 }
 
+/// The filters of the routes canceling or deleting tasks. They are the same as the ones of the
+/// task list, without the pagination.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TaskSelectionQuery {
+    uids: Option<CS<TaskId>>,
+    types: Option<CS<StarOr<TaskType>>>,
+    statuses: Option<CS<StarOr<TaskStatus>>>,
+    index_uids: Option<CS<StarOr<IndexUid>>>,
+    canceled_by: Option<CS<TaskId>>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_started_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_started_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_finished_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_finished_at: Option<OffsetDateTime>,
+}
+
+/// The filters that can be combined to select tasks, a `None` filter matches every task.
+struct TaskMatcher {
+    uids: Option<Vec<TaskId>>,
+    types: Option<Vec<TaskType>>,
+    statuses: Option<Vec<TaskStatus>>,
+    index_uids: Option<Vec<IndexUid>>,
+    canceled_by: Option<Vec<TaskId>>,
+    before_enqueued_at: Option<OffsetDateTime>,
+    after_enqueued_at: Option<OffsetDateTime>,
+    before_started_at: Option<OffsetDateTime>,
+    after_started_at: Option<OffsetDateTime>,
+    before_finished_at: Option<OffsetDateTime>,
+    after_finished_at: Option<OffsetDateTime>,
+}
+
+impl From<TaskSelectionQuery> for TaskMatcher {
+    fn from(query: TaskSelectionQuery) -> Self {
+        // A `*` value is transformed into a "not specified" filter.
+        Self {
+            uids: query.uids.map(|uids| uids.into_iter().collect()),
+            types: query.types.and_then(fold_star_or),
+            statuses: query.statuses.and_then(fold_star_or),
+            index_uids: query.index_uids.and_then(fold_star_or),
+            canceled_by: query.canceled_by.map(|ids| ids.into_iter().collect()),
+            before_enqueued_at: query.before_enqueued_at,
+            after_enqueued_at: query.after_enqueued_at,
+            before_started_at: query.before_started_at,
+            after_started_at: query.after_started_at,
+            before_finished_at: query.before_finished_at,
+            after_finished_at: query.after_finished_at,
+        }
+    }
+}
+
+impl TaskMatcher {
+    fn from_list_query(query: TaskFilterQuery) -> (Self, usize, Option<TaskId>) {
+        let TaskFilterQuery {
+            uids,
+            types,
+            statuses,
+            index_uids,
+            canceled_by,
+            before_enqueued_at,
+            after_enqueued_at,
+            before_started_at,
+            after_started_at,
+            before_finished_at,
+            after_finished_at,
+            limit,
+            from,
+        } = query;
+
+        let selection = TaskSelectionQuery {
+            uids,
+            types,
+            statuses,
+            index_uids,
+            canceled_by,
+            before_enqueued_at,
+            after_enqueued_at,
+            before_started_at,
+            after_started_at,
+            before_finished_at,
+            after_finished_at,
+        };
+
+        (selection.into(), limit, from)
+    }
+
+    /// Returns whether no filter was specified.
+    fn is_empty(&self) -> bool {
+        self.uids.is_none() && self.index_uids.is_none() && !self.filters_content()
+    }
+
+    /// Returns whether one of the filters must look at the content or the events of the tasks.
+    fn filters_content(&self) -> bool {
+        self.types.is_some()
+            || self.statuses.is_some()
+            || self.canceled_by.is_some()
+            || self.before_enqueued_at.is_some()
+            || self.after_enqueued_at.is_some()
+            || self.before_started_at.is_some()
+            || self.after_started_at.is_some()
+            || self.before_finished_at.is_some()
+            || self.after_finished_at.is_some()
+    }
+
+    /// Builds the task filter matching these filters, restricted to the indexes the search rules
+    /// authorize. Returns `None` when every task matches.
+    fn into_task_filter(self, search_rules: &SearchRules) -> Option<TaskFilter> {
+        let indexes_filters = indexes_filter(search_rules, self.index_uids.clone());
+        if self.uids.is_none() && !self.filters_content() {
+            return indexes_filters;
+        }
+
+        let mut filters = indexes_filters.unwrap_or_default();
+        filters.filter_fn(move |task| self.matches(task));
+        Some(filters)
+    }
+
+    fn matches(&self, task: &Task) -> bool {
+        let enqueued_at = match task.events.first() {
+            Some(TaskEvent::Created(at)) => Some(*at),
+            _ => None,
+        };
+        let started_at = task.events.iter().find_map(|event| match event {
+            TaskEvent::Processing(at) => Some(*at),
+            _ => None,
+        });
+        let canceled_by = match task.events.last() {
+            Some(TaskEvent::Canceled { canceled_by, .. }) => Some(*canceled_by),
+            _ => None,
+        };
+
+        self.uids
+            .as_ref()
+            .map_or(true, |uids| uids.contains(&task.id))
+            && self.types.as_ref().map_or(true, |types| {
+                types
+                    .iter()
+                    .any(|t| task_type_matches_content(t, &task.content))
+            })
+            && self.statuses.as_ref().map_or(true, |statuses| {
+                statuses
+                    .iter()
+                    .any(|s| task_status_matches_events(s, &task.events))
+            })
+            && self.canceled_by.as_ref().map_or(true, |ids| {
+                canceled_by.map_or(false, |canceled_by| ids.contains(&canceled_by))
+            })
+            && date_matches(enqueued_at, self.before_enqueued_at, self.after_enqueued_at)
+            && date_matches(started_at, self.before_started_at, self.after_started_at)
+            && date_matches(
+                task.finished_at(),
+                self.before_finished_at,
+                self.after_finished_at,
+            )
+    }
+}
+
+/// Returns whether the `date` is strictly between the `after` and `before` bounds. A task
+/// without a date never matches a bound.
+fn date_matches(
+    date: Option<OffsetDateTime>,
+    before: Option<OffsetDateTime>,
+    after: Option<OffsetDateTime>,
+) -> bool {
+    match date {
+        Some(date) => {
+            before.map_or(true, |before| date < before) && after.map_or(true, |after| date > after)
+        }
+        None => before.is_none() && after.is_none(),
+    }
+}
+
 #[rustfmt::skip]
 fn task_type_matches_content(type_: &TaskType, content: &TaskContent) -> bool {
     matches!((type_, content),
           (TaskType::IndexCreation, TaskContent::IndexCreation { .. })
         | (TaskType::IndexUpdate, TaskContent::IndexUpdate { .. })
         | (TaskType::IndexDeletion, TaskContent::IndexDeletion { .. })
+        | (TaskType::IndexSwap, TaskContent::IndexSwap { .. })
+        | (TaskType::IndexClone, TaskContent::IndexClone { .. })
         | (TaskType::DocumentAdditionOrUpdate, TaskContent::DocumentAddition { .. })
         | (TaskType::DocumentDeletion, TaskContent::DocumentDeletion{ .. })
+        | (TaskType::DocumentEdition, TaskContent::DocumentEdition { .. })
+        | (TaskType::DocumentRestoration, TaskContent::DocumentRestoration { .. })
         | (TaskType::SettingsUpdate, TaskContent::SettingsUpdate { .. })
+        | (TaskType::DumpCreation, TaskContent::Dump { .. })
+        | (TaskType::DumpImport, TaskContent::DumpImport { .. })
+        | (TaskType::TaskCancelation, TaskContent::TaskCancelation { .. })
+        | (TaskType::TaskDeletion, TaskContent::TaskDeletion { .. })
     )
 }
 
@@ -54,7 +273,8 @@ fn task_status_matches_events(status: &TaskStatus, events: &[TaskEvent]) -> bool
               (TaskStatus::Enqueued, TaskEvent::Created(_))
             | (TaskStatus::Processing, TaskEvent::Processing(_) | TaskEvent::Batched { .. })
             | (TaskStatus::Succeeded, TaskEvent::Succeeded { .. })
-            | (TaskStatus::Failed, TaskEvent::Failed { .. }),
+            | (TaskStatus::Failed, TaskEvent::Failed { .. })
+            | (TaskStatus::Canceled, TaskEvent::Canceled { .. }),
         )
     })
 }
@@ -71,25 +291,49 @@ async fn get_tasks(
         Some(&req),
     );
 
-    let TaskFilterQuery {
-        type_,
-        status,
-        index_uid,
-        limit,
-        from,
-    } = params.into_inner();
+    let (matcher, limit, from) = TaskMatcher::from_list_query(params.into_inner());
 
+    // The filters are restricted to the indexes the search rules authorize.
     let search_rules = &meilisearch.filters().search_rules;
+    let filters = matcher.into_task_filter(search_rules);
 
-    // We first transform a potential indexUid=* into a "not specified indexUid filter"
-    // for every one of the filters: type, status, and indexUid.
-    let type_: Option<Vec<_>> = type_.and_then(fold_star_or);
-    let status: Option<Vec<_>> = status.and_then(fold_star_or);
-    let index_uid: Option<Vec<_>> = index_uid.and_then(fold_star_or);
+    // We +1 just to know if there is more after this "page" or not.
+    let limit = limit.saturating_add(1);
 
-    // Then we filter on potential indexes and make sure that the search filter
-    // restrictions are also applied.
-    let indexes_filters = match index_uid {
+    let mut tasks_results: Vec<_> = meilisearch
+        .list_tasks(filters, Some(limit), from)
+        .await?
+        .into_iter()
+        .map(TaskView::from)
+        .collect();
+
+    // If we were able to fetch the number +1 tasks we asked
+    // it means that there is more to come.
+    let next = if tasks_results.len() == limit {
+        tasks_results.pop().map(|t| t.uid)
+    } else {
+        None
+    };
+
+    let from = tasks_results.first().map(|t| t.uid);
+
+    let tasks = TaskListView {
+        results: tasks_results,
+        limit: limit.saturating_sub(1),
+        from,
+        next,
+    };
+
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+/// Returns the filter restricting the tasks to the given indexes, and to the indexes the search
+/// rules authorize.
+pub(super) fn indexes_filter(
+    search_rules: &SearchRules,
+    index_uid: Option<Vec<IndexUid>>,
+) -> Option<TaskFilter> {
+    match index_uid {
         Some(indexes) => {
             let mut filters = TaskFilter::default();
             for name in indexes {
@@ -110,61 +354,76 @@ async fn get_tasks(
                 Some(filters)
             }
         }
-    };
+    }
+}
 
-    // Then we complete the task filter with other potential status and types filters.
-    let filters = if type_.is_some() || status.is_some() {
-        let mut filters = indexes_filters.unwrap_or_default();
-        filters.filter_fn(move |task| {
-            let matches_type = match &type_ {
-                Some(types) => types
-                    .iter()
-                    .any(|t| task_type_matches_content(t, &task.content)),
-                None => true,
-            };
+async fn cancel_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_CANCEL }>, MeiliSearch>,
+    params: web::Query<TaskSelectionQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let matcher = TaskMatcher::from(params.into_inner());
 
-            let matches_status = match &status {
-                Some(statuses) => statuses
-                    .iter()
-                    .any(|t| task_status_matches_events(t, &task.events)),
-                None => true,
-            };
+    // Canceling every task by mistake is too easy, at least one filter must be given.
+    if matcher.is_empty() {
+        return Err(MeilisearchHttpError::MissingTaskFilters("cancel").into());
+    }
+    analytics.publish(
+        "Tasks Canceled".to_string(),
+        json!({
+            "filtered_by_uid": matcher.uids.is_some(),
+            "filtered_by_index_uid": matcher.index_uids.is_some(),
+            "filtered_by_type": matcher.types.is_some(),
+            "filtered_by_status": matcher.statuses.is_some(),
+        }),
+        Some(&req),
+    );
 
-            matches_type && matches_status
-        });
-        Some(filters)
-    } else {
-        indexes_filters
-    };
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = matcher.into_task_filter(search_rules).unwrap_or_default();
 
-    // We +1 just to know if there is more after this "page" or not.
-    let limit = limit.saturating_add(1);
+    let query = req.query_string().to_string();
+    let task: SummarizedTaskView = meilisearch.cancel_tasks(query, filters).await?.into();
 
-    let mut tasks_results: Vec<_> = meilisearch
-        .list_tasks(filters, Some(limit), from)
-        .await?
-        .into_iter()
-        .map(TaskView::from)
-        .collect();
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
 
-    // If we were able to fetch the number +1 tasks we asked
-    // it means that there is more to come.
-    let next = if tasks_results.len() == limit {
-        tasks_results.pop().map(|t| t.uid)
-    } else {
-        None
-    };
+async fn delete_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_DELETE }>, MeiliSearch>,
+    params: web::Query<TaskSelectionQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let matcher = TaskMatcher::from(params.into_inner());
 
-    let from = tasks_results.first().map(|t| t.uid);
+    // Deleting the whole task history by mistake is too easy, at least one filter must be given.
+    if matcher.is_empty() {
+        return Err(MeilisearchHttpError::MissingTaskFilters("delete").into());
+    }
 
-    let tasks = TaskListView {
-        results: tasks_results,
-        limit: limit.saturating_sub(1),
-        from,
-        next,
-    };
+    analytics.publish(
+        "Tasks Deleted".to_string(),
+        json!({
+            "filtered_by_uid": matcher.uids.is_some(),
+            "filtered_by_index_uid": matcher.index_uids.is_some(),
+            "filtered_by_type": matcher.types.is_some(),
+            "filtered_by_status": matcher.statuses.is_some(),
+            "filtered_by_before_enqueued_at": matcher.before_enqueued_at.is_some(),
+            "filtered_by_before_finished_at": matcher.before_finished_at.is_some(),
+        }),
+        Some(&req),
+    );
 
-    Ok(HttpResponse::Ok().json(tasks))
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = matcher.into_task_filter(search_rules).unwrap_or_default();
+
+    let query = req.query_string().to_string();
+    let task: SummarizedTaskView = meilisearch.delete_tasks(query, filters).await?.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
 }
 
 async fn get_task(
@@ -197,3 +456,84 @@ async fn get_task(
 
     Ok(HttpResponse::Ok().json(task))
 }
+
+/// The priorities a client can give to a task, `urgent` is reserved to the tasks moved to the
+/// front of the queue.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+impl From<Priority> for TaskPriority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => TaskPriority::Low,
+            Priority::Normal => TaskPriority::Normal,
+            Priority::High => TaskPriority::High,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpdateTask {
+    priority: Priority,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TaskPriorityView {
+    task_uid: TaskId,
+    priority: TaskPriority,
+}
+
+async fn update_task(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_UPDATE }>, MeiliSearch>,
+    task_id: web::Path<TaskId>,
+    body: web::Json<UpdateTask>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let priority = TaskPriority::from(body.into_inner().priority);
+
+    analytics.publish(
+        "Task Updated".to_string(),
+        json!({ "priority": priority }),
+        Some(&req),
+    );
+
+    set_task_priority(&meilisearch, task_id.into_inner(), priority).await
+}
+
+async fn move_to_front(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_UPDATE }>, MeiliSearch>,
+    task_id: web::Path<TaskId>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Task Moved To Front".to_string(), json!({}), Some(&req));
+
+    set_task_priority(&meilisearch, task_id.into_inner(), TaskPriority::Urgent).await
+}
+
+async fn set_task_priority(
+    meilisearch: &GuardedData<ActionPolicy<{ actions::TASKS_UPDATE }>, MeiliSearch>,
+    task_id: TaskId,
+    priority: TaskPriority,
+) -> Result<HttpResponse, ResponseError> {
+    let filters = indexes_filter(&meilisearch.filters().search_rules, None);
+    let task = meilisearch
+        .set_task_priority(task_id, priority, filters)
+        .await?;
+
+    let res = TaskPriorityView {
+        task_uid: task.id,
+        priority,
+    };
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
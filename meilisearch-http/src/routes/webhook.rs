@@ -0,0 +1,89 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::tasks::TaskWebhook;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_webhook)))
+            .route(web::put().to(SeqHandler(update_webhook)))
+            .route(web::delete().to(SeqHandler(delete_webhook))),
+    );
+}
+
+/// The webhook as it is returned to the clients, the authorization header is a secret of the
+/// receiving service and is never sent back.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookView {
+    url: Option<String>,
+    has_authorization: bool,
+}
+
+impl From<Option<TaskWebhook>> for WebhookView {
+    fn from(webhook: Option<TaskWebhook>) -> Self {
+        Self {
+            has_authorization: webhook
+                .as_ref()
+                .map_or(false, |w| w.authorization.is_some()),
+            url: webhook.map(|w| w.url),
+        }
+    }
+}
+
+pub async fn get_webhook(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = WebhookView::from(meilisearch.webhook().await);
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn update_webhook(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_UPDATE }>, MeiliSearch>,
+    body: web::Json<TaskWebhook>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let webhook = body.into_inner();
+
+    match Url::parse(&webhook.url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => (),
+        _ => return Err(MeilisearchHttpError::InvalidWebhookUrl(webhook.url).into()),
+    }
+
+    analytics.publish(
+        "Webhook Updated".to_string(),
+        json!({ "authorization": webhook.authorization.is_some() }),
+        Some(&req),
+    );
+
+    meilisearch.set_webhook(Some(webhook)).await;
+    let res = WebhookView::from(meilisearch.webhook().await);
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn delete_webhook(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_UPDATE }>, MeiliSearch>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Webhook Deleted".to_string(), json!({}), Some(&req));
+
+    meilisearch.set_webhook(None).await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
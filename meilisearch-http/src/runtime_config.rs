@@ -0,0 +1,216 @@
+//! The options that can be changed without restarting the process, with the `PATCH /config`
+//! route or by sending a `SIGHUP` to reload the `--runtime-config-file`. The task queue and the
+//! connections are left untouched.
+
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use byte_unit::Byte;
+use log::{error, info};
+use meilisearch_lib::milli::update::Setting;
+use meilisearch_lib::options::{MaxMemory, MaxThreads};
+use meilisearch_lib::tasks::TaskWebhook;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::error::MeilisearchHttpError;
+use crate::logging;
+use crate::Opt;
+
+/// The values of the options changed at runtime. They are shared by the clones of the
+/// [`Opt`], so every worker sees the changes.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeOptions {
+    log_level: Arc<RwLock<Option<String>>>,
+    http_payload_size_limit: Arc<RwLock<Option<Byte>>>,
+}
+
+impl Opt {
+    pub fn current_log_level(&self) -> String {
+        self.runtime
+            .log_level
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| self.log_level.clone())
+    }
+
+    pub fn current_http_payload_size_limit(&self) -> Byte {
+        self.runtime
+            .http_payload_size_limit
+            .read()
+            .unwrap()
+            .unwrap_or(self.http_payload_size_limit)
+    }
+}
+
+/// The current values of the reloadable options.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeConfig {
+    log_level: String,
+    http_payload_size_limit: u64,
+    search_cutoff_ms: Option<u64>,
+    task_webhook_url: Option<String>,
+    /// The memory limit of the indexer in bytes, `None` if it is unlimited.
+    max_indexing_memory: Option<u64>,
+    max_indexing_threads: usize,
+}
+
+impl RuntimeConfig {
+    pub async fn current(meilisearch: &MeiliSearch, opt: &Opt) -> Self {
+        let indexer_options = meilisearch.indexer_options();
+        Self {
+            log_level: opt.current_log_level(),
+            http_payload_size_limit: opt.current_http_payload_size_limit().get_bytes() as u64,
+            search_cutoff_ms: meilisearch
+                .search_cutoff()
+                .await
+                .map(|cutoff| cutoff.as_millis() as u64),
+            task_webhook_url: meilisearch.webhook().await.map(|webhook| webhook.url),
+            max_indexing_memory: indexer_options
+                .max_indexing_memory
+                .map(|memory| memory.get_bytes() as u64),
+            max_indexing_threads: *indexer_options.max_indexing_threads,
+        }
+    }
+}
+
+/// The options to change, the missing options are left untouched and the `null` ones are
+/// removed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuntimeConfigUpdate {
+    #[serde(default)]
+    pub log_level: Option<String>,
+    #[serde(default)]
+    pub http_payload_size_limit: Option<Byte>,
+    #[serde(default)]
+    pub search_cutoff_ms: Setting<u64>,
+    #[serde(default)]
+    pub task_webhook_url: Setting<String>,
+    /// `null` removes the memory limit of the indexer.
+    #[serde(default)]
+    pub max_indexing_memory: Setting<Byte>,
+    #[serde(default)]
+    pub max_indexing_threads: Option<usize>,
+}
+
+impl RuntimeConfigUpdate {
+    /// Checks the options, nothing is applied unless they are all valid.
+    fn validate(&self) -> Result<(), MeilisearchHttpError> {
+        if let Some(log_level) = &self.log_level {
+            if !logging::is_valid_log_level(log_level) {
+                return Err(MeilisearchHttpError::InvalidLogLevel(log_level.clone()));
+            }
+        }
+
+        if let Setting::Set(url) = &self.task_webhook_url {
+            match Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => (),
+                _ => return Err(MeilisearchHttpError::InvalidWebhookUrl(url.clone())),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn apply(self, meilisearch: &MeiliSearch, opt: &Opt) -> Result<(), ResponseError> {
+        self.validate()?;
+
+        if !self.max_indexing_memory.is_not_set() || self.max_indexing_threads.is_some() {
+            let mut indexer_options = meilisearch.indexer_options();
+            match self.max_indexing_memory {
+                Setting::Set(memory) => {
+                    indexer_options.max_indexing_memory = MaxMemory::new(memory)
+                }
+                Setting::Reset => indexer_options.max_indexing_memory = MaxMemory::unlimited(),
+                Setting::NotSet => (),
+            }
+            if let Some(threads) = self.max_indexing_threads {
+                indexer_options.max_indexing_threads = MaxThreads::new(threads);
+            }
+            meilisearch.set_indexer_options(indexer_options)?;
+        }
+
+        if let Some(log_level) = self.log_level {
+            logging::set_log_level(&log_level);
+            *opt.runtime.log_level.write().unwrap() = Some(log_level);
+        }
+
+        if let Some(limit) = self.http_payload_size_limit {
+            *opt.runtime.http_payload_size_limit.write().unwrap() = Some(limit);
+        }
+
+        match self.search_cutoff_ms {
+            Setting::Set(ms) => {
+                meilisearch
+                    .set_search_cutoff(Some(Duration::from_millis(ms)))
+                    .await
+            }
+            Setting::Reset => meilisearch.set_search_cutoff(None).await,
+            Setting::NotSet => (),
+        }
+
+        match self.task_webhook_url {
+            Setting::Set(url) => {
+                // the authorization is a secret of the receiving service, it is kept.
+                let authorization = meilisearch
+                    .webhook()
+                    .await
+                    .and_then(|webhook| webhook.authorization);
+                meilisearch
+                    .set_webhook(Some(TaskWebhook { url, authorization }))
+                    .await;
+            }
+            Setting::Reset => meilisearch.set_webhook(None).await,
+            Setting::NotSet => (),
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads and applies the options of the `--runtime-config-file`.
+async fn reload(path: &Path, meilisearch: &MeiliSearch, opt: &Opt) -> anyhow::Result<()> {
+    let content = tokio::fs::read(path).await?;
+    let update: RuntimeConfigUpdate = serde_json::from_slice(&content)?;
+    update
+        .apply(meilisearch, opt)
+        .await
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
+/// Reloads the `--runtime-config-file` each time the process receives a `SIGHUP`.
+#[cfg(unix)]
+pub fn reload_on_hangup(meilisearch: MeiliSearch, opt: Opt) -> anyhow::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    actix_web::rt::spawn(async move {
+        while hangups.recv().await.is_some() {
+            match &opt.runtime_config_file {
+                Some(path) => match reload(path, &meilisearch, &opt).await {
+                    Ok(()) => info!("Reloaded the runtime configuration from {:?}.", path),
+                    Err(e) => error!(
+                        "Failed to reload the runtime configuration from {:?}: {}",
+                        path, e
+                    ),
+                },
+                None => error!(
+                    "Received a SIGHUP but no configuration file to reload was provided with `--runtime-config-file`."
+                ),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn reload_on_hangup(_meilisearch: MeiliSearch, _opt: Opt) -> anyhow::Result<()> {
+    Ok(())
+}
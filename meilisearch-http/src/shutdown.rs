@@ -0,0 +1,49 @@
+//! Stops the process gracefully on `SIGTERM` or `SIGINT`: the server stops accepting new
+//! connections, the requests being processed are answered and the batch of tasks being processed
+//! is finished, all within the `--shutdown-timeout-sec`.
+
+use std::time::Duration;
+
+use actix_web::dev::ServerHandle;
+use log::{error, info, warn};
+use meilisearch_lib::MeiliSearch;
+
+/// Waits for a `SIGTERM` or a `SIGINT`.
+#[cfg(unix)]
+async fn termination_signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = terminate.recv() => Ok(()),
+        result = tokio::signal::ctrl_c() => result,
+    }
+}
+
+#[cfg(not(unix))]
+async fn termination_signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
+
+/// Stops the server of `handle` and the processing of the tasks once a termination signal is
+/// received. The server must have been built with the same shutdown timeout.
+pub async fn shutdown_on_signal(handle: ServerHandle, meilisearch: MeiliSearch, timeout: Duration) {
+    if let Err(e) = termination_signal().await {
+        error!("Failed to listen to the termination signals: {}", e);
+        return;
+    }
+
+    info!(
+        "Shutting down, waiting up to {:?} for the requests and the tasks being processed.",
+        timeout
+    );
+
+    let (_, drained) = tokio::join!(handle.stop(true), meilisearch.drain_tasks(timeout));
+    match drained {
+        Ok(true) => info!("The tasks being processed are finished."),
+        Ok(false) => warn!(
+            "The batch of tasks being processed didn't finish in time, it will be processed again on the next start."
+        ),
+        Err(e) => error!("Failed to wait for the tasks being processed: {}", e),
+    }
+}
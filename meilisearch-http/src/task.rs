@@ -4,12 +4,14 @@ use std::str::FromStr;
 use std::write;
 
 use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index_controller::{DumpImportSource, ImportConflictPolicy, ImportedIndex};
 use meilisearch_lib::tasks::batch::BatchId;
 use meilisearch_lib::tasks::task::{
-    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult,
+    DocumentDeletion, GroupUid, Task, TaskContent, TaskEvent, TaskId, TaskPriority, TaskResult,
 };
 use meilisearch_types::error::ResponseError;
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 use time::{Duration, OffsetDateTime};
 
 use crate::AUTOBATCHING_ENABLED;
@@ -20,10 +22,17 @@ pub enum TaskType {
     IndexCreation,
     IndexUpdate,
     IndexDeletion,
+    IndexSwap,
+    IndexClone,
     DocumentAdditionOrUpdate,
     DocumentDeletion,
+    DocumentEdition,
+    DocumentRestoration,
     SettingsUpdate,
     DumpCreation,
+    DumpImport,
+    TaskCancelation,
+    TaskDeletion,
 }
 
 impl From<TaskContent> for TaskType {
@@ -32,10 +41,17 @@ impl From<TaskContent> for TaskType {
             TaskContent::IndexCreation { .. } => TaskType::IndexCreation,
             TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
             TaskContent::IndexDeletion { .. } => TaskType::IndexDeletion,
+            TaskContent::IndexSwap { .. } => TaskType::IndexSwap,
+            TaskContent::IndexClone { .. } => TaskType::IndexClone,
             TaskContent::DocumentAddition { .. } => TaskType::DocumentAdditionOrUpdate,
             TaskContent::DocumentDeletion { .. } => TaskType::DocumentDeletion,
+            TaskContent::DocumentEdition { .. } => TaskType::DocumentEdition,
+            TaskContent::DocumentRestoration { .. } => TaskType::DocumentRestoration,
             TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
             TaskContent::Dump { .. } => TaskType::DumpCreation,
+            TaskContent::DumpImport { .. } => TaskType::DumpImport,
+            TaskContent::TaskCancelation { .. } => TaskType::TaskCancelation,
+            TaskContent::TaskDeletion { .. } => TaskType::TaskDeletion,
         }
     }
 }
@@ -50,8 +66,8 @@ impl fmt::Display for TaskTypeError {
         write!(
             f,
             "invalid task type `{}`, expecting one of: \
-            indexCreation, indexUpdate, indexDeletion, documentAdditionOrUpdate, \
-            documentDeletion, settingsUpdate, dumpCreation",
+            indexCreation, indexUpdate, indexDeletion, indexSwap, indexClone, documentAdditionOrUpdate, \
+            documentDeletion, documentEdition, documentRestoration, settingsUpdate, dumpCreation, dumpImport, taskCancelation, taskDeletion",
             self.invalid_type
         )
     }
@@ -69,14 +85,28 @@ impl FromStr for TaskType {
             Ok(TaskType::IndexUpdate)
         } else if type_.eq_ignore_ascii_case("indexDeletion") {
             Ok(TaskType::IndexDeletion)
+        } else if type_.eq_ignore_ascii_case("indexSwap") {
+            Ok(TaskType::IndexSwap)
+        } else if type_.eq_ignore_ascii_case("indexClone") {
+            Ok(TaskType::IndexClone)
         } else if type_.eq_ignore_ascii_case("documentAdditionOrUpdate") {
             Ok(TaskType::DocumentAdditionOrUpdate)
         } else if type_.eq_ignore_ascii_case("documentDeletion") {
             Ok(TaskType::DocumentDeletion)
+        } else if type_.eq_ignore_ascii_case("documentEdition") {
+            Ok(TaskType::DocumentEdition)
+        } else if type_.eq_ignore_ascii_case("documentRestoration") {
+            Ok(TaskType::DocumentRestoration)
         } else if type_.eq_ignore_ascii_case("settingsUpdate") {
             Ok(TaskType::SettingsUpdate)
         } else if type_.eq_ignore_ascii_case("dumpCreation") {
             Ok(TaskType::DumpCreation)
+        } else if type_.eq_ignore_ascii_case("dumpImport") {
+            Ok(TaskType::DumpImport)
+        } else if type_.eq_ignore_ascii_case("taskCancelation") {
+            Ok(TaskType::TaskCancelation)
+        } else if type_.eq_ignore_ascii_case("taskDeletion") {
+            Ok(TaskType::TaskDeletion)
         } else {
             Err(TaskTypeError {
                 invalid_type: type_.to_string(),
@@ -92,6 +122,7 @@ pub enum TaskStatus {
     Processing,
     Succeeded,
     Failed,
+    Canceled,
 }
 
 #[derive(Debug)]
@@ -104,7 +135,7 @@ impl fmt::Display for TaskStatusError {
         write!(
             f,
             "invalid task status `{}`, expecting one of: \
-            enqueued, processing, succeeded, or failed",
+            enqueued, processing, succeeded, failed, or canceled",
             self.invalid_status,
         )
     }
@@ -124,6 +155,8 @@ impl FromStr for TaskStatus {
             Ok(TaskStatus::Succeeded)
         } else if status.eq_ignore_ascii_case("failed") {
             Ok(TaskStatus::Failed)
+        } else if status.eq_ignore_ascii_case("canceled") {
+            Ok(TaskStatus::Canceled)
         } else {
             Err(TaskStatusError {
                 invalid_status: status.to_string(),
@@ -140,6 +173,9 @@ enum TaskDetails {
     DocumentAddition {
         received_documents: usize,
         indexed_documents: Option<u64>,
+        /// The documents left unchanged because they weren't newer than the stored ones.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        rejected_documents: Vec<String>,
     },
     #[serde(rename_all = "camelCase")]
     Settings {
@@ -154,9 +190,61 @@ enum TaskDetails {
         deleted_documents: Option<u64>,
     },
     #[serde(rename_all = "camelCase")]
+    DocumentDeletionByFilter {
+        original_filter: Value,
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentEdition {
+        function: String,
+        original_filter: Option<Value>,
+        edited_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentRestoration {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        received_document_ids: Option<usize>,
+        restored_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
     ClearAll { deleted_documents: Option<u64> },
     #[serde(rename_all = "camelCase")]
-    Dump { dump_uid: String },
+    IndexSwap { swaps: Vec<IndexSwapView> },
+    #[serde(rename_all = "camelCase")]
+    IndexClone {
+        destination_index_uid: String,
+        settings_only: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Dump {
+        dump_uid: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        incremental_from: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DumpImport {
+        source: &'static str,
+        conflict: ImportConflictPolicy,
+        group_uid: Option<GroupUid>,
+        indexes: Option<Vec<ImportedIndex>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TaskCancelation {
+        canceled_tasks: usize,
+        original_filter: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TaskDeletion {
+        deleted_tasks: usize,
+        original_filter: String,
+    },
+}
+
+/// A pair of swapped indexes, in the format of the `POST /swap-indexes` payload.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSwapView {
+    indexes: [String; 2],
 }
 
 /// Serialize a `time::Duration` as a best effort ISO 8601 while waiting for
@@ -222,6 +310,8 @@ pub struct TaskView {
     details: Option<TaskDetails>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<ResponseError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    canceled_by: Option<TaskId>,
     #[serde(serialize_with = "serialize_duration")]
     duration: Option<Duration>,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
@@ -232,6 +322,20 @@ pub struct TaskView {
     finished_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     batch_uid: Option<Option<BatchId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    /// Only shown while the task is enqueued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<TaskPriority>,
+    /// The group registered through the batch route the task belongs to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_uid: Option<GroupUid>,
+}
+
+impl TaskView {
+    pub fn status(&self) -> &TaskStatus {
+        &self.status
+    }
 }
 
 impl From<Task> for TaskView {
@@ -241,6 +345,10 @@ impl From<Task> for TaskView {
             id,
             content,
             events,
+            request_id,
+            priority,
+            group_uid,
+            batch_seq: _,
         } = task;
 
         let (task_type, mut details) = match content {
@@ -250,6 +358,7 @@ impl From<Task> for TaskView {
                 let details = TaskDetails::DocumentAddition {
                     received_documents: documents_count,
                     indexed_documents: None,
+                    rejected_documents: Vec::new(),
                 };
 
                 (TaskType::DocumentAdditionOrUpdate, Some(details))
@@ -264,6 +373,16 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentDeletion {
+                deletion: DocumentDeletion::Filter(filter),
+                ..
+            } => (
+                TaskType::DocumentDeletion,
+                Some(TaskDetails::DocumentDeletionByFilter {
+                    original_filter: filter,
+                    deleted_documents: None,
+                }),
+            ),
             TaskContent::DocumentDeletion {
                 deletion: DocumentDeletion::Clear,
                 ..
@@ -273,6 +392,23 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentEdition {
+                filter, function, ..
+            } => (
+                TaskType::DocumentEdition,
+                Some(TaskDetails::DocumentEdition {
+                    function,
+                    original_filter: filter,
+                    edited_documents: None,
+                }),
+            ),
+            TaskContent::DocumentRestoration { ids, .. } => (
+                TaskType::DocumentRestoration,
+                Some(TaskDetails::DocumentRestoration {
+                    received_document_ids: ids.map(|ids| ids.len()),
+                    restored_documents: None,
+                }),
+            ),
             TaskContent::IndexDeletion { .. } => (
                 TaskType::IndexDeletion,
                 Some(TaskDetails::ClearAll {
@@ -291,9 +427,64 @@ impl From<Task> for TaskView {
                 TaskType::IndexUpdate,
                 Some(TaskDetails::IndexInfo { primary_key }),
             ),
-            TaskContent::Dump { uid } => (
+            TaskContent::IndexSwap { swaps } => (
+                TaskType::IndexSwap,
+                Some(TaskDetails::IndexSwap {
+                    swaps: swaps
+                        .into_iter()
+                        .map(|(left, right)| IndexSwapView {
+                            indexes: [left.into_inner(), right.into_inner()],
+                        })
+                        .collect(),
+                }),
+            ),
+            TaskContent::IndexClone {
+                destination,
+                settings_only,
+                ..
+            } => (
+                TaskType::IndexClone,
+                Some(TaskDetails::IndexClone {
+                    destination_index_uid: destination.into_inner(),
+                    settings_only,
+                }),
+            ),
+            TaskContent::Dump {
+                uid,
+                incremental_from,
+            } => (
                 TaskType::DumpCreation,
-                Some(TaskDetails::Dump { dump_uid: uid }),
+                Some(TaskDetails::Dump {
+                    dump_uid: uid,
+                    incremental_from,
+                }),
+            ),
+            TaskContent::DumpImport { source, conflict } => (
+                TaskType::DumpImport,
+                Some(TaskDetails::DumpImport {
+                    source: match source {
+                        DumpImportSource::Path(_) => "path",
+                        DumpImportSource::Url(_) => "url",
+                        DumpImportSource::Upload(_) => "upload",
+                    },
+                    conflict,
+                    group_uid: None,
+                    indexes: None,
+                }),
+            ),
+            TaskContent::TaskCancelation { query, tasks } => (
+                TaskType::TaskCancelation,
+                Some(TaskDetails::TaskCancelation {
+                    canceled_tasks: tasks.len(),
+                    original_filter: query,
+                }),
+            ),
+            TaskContent::TaskDeletion { query, tasks } => (
+                TaskType::TaskDeletion,
+                Some(TaskDetails::TaskDeletion {
+                    deleted_tasks: tasks.len(),
+                    original_filter: query,
+                }),
             ),
         };
 
@@ -307,14 +498,40 @@ impl From<Task> for TaskView {
                     (
                         TaskResult::DocumentAddition {
                             indexed_documents: num,
-                            ..
+                            rejected_documents: rejected,
                         },
                         Some(TaskDetails::DocumentAddition {
                             ref mut indexed_documents,
+                            ref mut rejected_documents,
                             ..
                         }),
                     ) => {
                         indexed_documents.replace(*num);
+                        *rejected_documents = rejected.clone();
+                    }
+                    (
+                        TaskResult::DocumentAddition {
+                            indexed_documents: num,
+                            ..
+                        },
+                        Some(TaskDetails::DocumentEdition {
+                            ref mut edited_documents,
+                            ..
+                        }),
+                    ) => {
+                        edited_documents.replace(*num);
+                    }
+                    (
+                        TaskResult::DocumentAddition {
+                            indexed_documents: num,
+                            ..
+                        },
+                        Some(TaskDetails::DocumentRestoration {
+                            ref mut restored_documents,
+                            ..
+                        }),
+                    ) => {
+                        restored_documents.replace(*num);
                     }
                     (
                         TaskResult::DocumentDeletion {
@@ -328,6 +545,17 @@ impl From<Task> for TaskView {
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::DocumentDeletion {
+                            deleted_documents: docs,
+                        },
+                        Some(TaskDetails::DocumentDeletionByFilter {
+                            ref mut deleted_documents,
+                            ..
+                        }),
+                    ) => {
+                        deleted_documents.replace(*docs);
+                    }
                     (
                         TaskResult::ClearAll {
                             deleted_documents: docs,
@@ -338,6 +566,20 @@ impl From<Task> for TaskView {
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::DumpImport {
+                            group_uid: uid,
+                            indexes: imported,
+                        },
+                        Some(TaskDetails::DumpImport {
+                            ref mut group_uid,
+                            ref mut indexes,
+                            ..
+                        }),
+                    ) => {
+                        *group_uid = *uid;
+                        indexes.replace(imported.clone());
+                    }
                     _ => (),
                 }
                 (TaskStatus::Succeeded, None, Some(*timestamp))
@@ -350,6 +592,12 @@ impl From<Task> for TaskView {
                     }) => {
                         deleted_documents.replace(0);
                     }
+                    Some(TaskDetails::DocumentDeletionByFilter {
+                        ref mut deleted_documents,
+                        ..
+                    }) => {
+                        deleted_documents.replace(0);
+                    }
                     Some(TaskDetails::ClearAll {
                         ref mut deleted_documents,
                         ..
@@ -362,12 +610,30 @@ impl From<Task> for TaskView {
                     }) => {
                         indexed_documents.replace(0);
                     }
+                    Some(TaskDetails::DocumentEdition {
+                        ref mut edited_documents,
+                        ..
+                    }) => {
+                        edited_documents.replace(0);
+                    }
+                    Some(TaskDetails::DocumentRestoration {
+                        ref mut restored_documents,
+                        ..
+                    }) => {
+                        restored_documents.replace(0);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Failed, Some(error.clone()), Some(*timestamp))
             }
+            TaskEvent::Canceled { timestamp, .. } => (TaskStatus::Canceled, None, Some(*timestamp)),
         };
 
+        let canceled_by = events.last().and_then(|e| match e {
+            TaskEvent::Canceled { canceled_by, .. } => Some(*canceled_by),
+            _ => None,
+        });
+
         let enqueued_at = match events.first() {
             Some(TaskEvent::Created(ts)) => *ts,
             _ => unreachable!("A task must always have a creation event."),
@@ -390,6 +656,8 @@ impl From<Task> for TaskView {
             None
         };
 
+        let priority = matches!(status, TaskStatus::Enqueued).then(|| priority);
+
         Self {
             uid: id,
             index_uid,
@@ -397,11 +665,15 @@ impl From<Task> for TaskView {
             task_type,
             details,
             error,
+            canceled_by,
             duration,
             enqueued_at,
             started_at,
             finished_at,
             batch_uid,
+            request_id,
+            priority,
+            group_uid,
         }
     }
 }
@@ -426,6 +698,12 @@ pub struct SummarizedTaskView {
     enqueued_at: OffsetDateTime,
 }
 
+impl SummarizedTaskView {
+    pub fn task_uid(&self) -> TaskId {
+        self.task_uid
+    }
+}
+
 impl From<Task> for SummarizedTaskView {
     fn from(mut other: Task) -> Self {
         let created_event = other
@@ -0,0 +1,106 @@
+//! Gives a priority to the tasks registered by a request with the `X-Task-Priority` header.
+//!
+//! The priority is one of `low`, `normal` or `high`, the tasks are registered with the `normal`
+//! priority when the header is missing. The requests with another priority are rejected.
+
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, ResponseError as _};
+use futures::future::LocalBoxFuture;
+use meilisearch_lib::task_priority;
+use meilisearch_lib::tasks::task::TaskPriority;
+use meilisearch_types::error::ResponseError;
+
+use crate::error::MeilisearchHttpError;
+
+pub const TASK_PRIORITY_HEADER: &str = "x-task-priority";
+
+/// Returns the priority named `name`, `urgent` is reserved to the tasks moved to the front of
+/// the queue.
+fn parse(name: &str) -> Option<TaskPriority> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "low" => Some(TaskPriority::Low),
+        "normal" => Some(TaskPriority::Normal),
+        "high" => Some(TaskPriority::High),
+        _ => None,
+    }
+}
+
+pub struct TaskPrioritization;
+
+impl<S, B> Transform<S, ServiceRequest> for TaskPrioritization
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = TaskPrioritizationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TaskPrioritizationMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TaskPrioritizationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TaskPrioritizationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let priority = match req.headers().get(TASK_PRIORITY_HEADER) {
+            Some(value) => {
+                let name = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                match parse(&name) {
+                    Some(priority) => priority,
+                    None => {
+                        let error =
+                            ResponseError::from(MeilisearchHttpError::InvalidTaskPriority(name));
+                        let response = error.error_response();
+                        return Box::pin(ready(Ok(req
+                            .into_response(response)
+                            .map_into_right_body())));
+                    }
+                }
+            }
+            None => TaskPriority::default(),
+        };
+
+        let service = self.service.clone();
+        Box::pin(task_priority::scope(priority, async move {
+            let response = service.call(req).await?;
+            Ok(response.map_into_left_body())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_task_priorities() {
+        assert_eq!(parse("low"), Some(TaskPriority::Low));
+        assert_eq!(parse("Normal"), Some(TaskPriority::Normal));
+        assert_eq!(parse(" high "), Some(TaskPriority::High));
+        assert_eq!(parse("urgent"), None);
+        assert_eq!(parse(""), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! Export of the traces of the requests and of the tasks to an OpenTelemetry collector.
+//!
+//! The spans are created with `tracing`, and exported with the OTLP protocol when an endpoint
+//! is configured. The logs are still written by `env_logger`, the subscriber installed here only
+//! exports the spans. The `traceparent` header of the requests is honored, so the spans of a
+//! request are attached to the trace of the service that sent it.
+
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::HeaderMap;
+use actix_web::Error;
+use futures::future::LocalBoxFuture;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use tracing::field::Empty;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::Opt;
+
+/// Starts exporting the spans to the OTLP endpoint of `opt`, if any. It must be called from the
+/// runtime of the server, which sends the spans in batches.
+pub fn init(opt: &Opt) -> anyhow::Result<()> {
+    let endpoint = match opt.experimental_otlp_endpoint {
+        Some(ref endpoint) => endpoint,
+        None => return Ok(()),
+    };
+
+    let resource = Resource::new(vec![KeyValue::new(
+        "service.name",
+        opt.experimental_otlp_service_name.clone(),
+    )]);
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(trace::config().with_resource(resource))
+        .install_batch(opentelemetry::runtime::Tokio)?;
+
+    let subscriber =
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+    tracing::subscriber::set_global_default(subscriber)?;
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Ok(())
+}
+
+/// Sends the spans that are not exported yet, before the server exits.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// Processes each request in a span, whose parent is the trace context of the request headers.
+pub struct RequestTracing;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTracing
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTracingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTracingMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestTracingMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTracingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(req.headers()))
+        });
+        let span = tracing::info_span!(
+            "HTTP request",
+            otel.kind = "server",
+            http.method = %req.method(),
+            http.target = %req.uri(),
+            http.route = Empty,
+            http.status_code = Empty,
+        );
+        span.set_parent(parent);
+
+        let future = span.in_scope(|| self.service.call(req));
+        Box::pin(
+            async move {
+                let response = future.await?;
+                let span = tracing::Span::current();
+                if let Some(route) = response.request().match_pattern() {
+                    span.record("http.route", &route.as_str());
+                }
+                span.record("http.status_code", &response.status().as_u16());
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use log::{error, warn};
+use meilisearch_lib::tasks::task::Task;
+use meilisearch_lib::tasks::TaskWebhook;
+use meilisearch_lib::MeiliSearch;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::Client;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::sleep;
+
+use crate::task::TaskView;
+
+/// The number of times a notification is sent before giving up on it.
+const MAX_ATTEMPTS: u32 = 5;
+/// The delay before the first retry of a notification, it doubles after each failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Sends the tasks that reach a terminal state to the webhook of `meilisearch`, until the
+/// scheduler stops.
+///
+/// The webhook is read again before each notification, so it can be changed at runtime. When no
+/// webhook is set the finished tasks are ignored.
+pub async fn run(meilisearch: MeiliSearch) {
+    let mut receiver = meilisearch.subscribe_finished_tasks().await;
+    let client = Client::new();
+
+    loop {
+        let tasks = match receiver.recv().await {
+            Ok(tasks) => tasks,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("The task webhook missed {} notifications.", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        if let Some(webhook) = meilisearch.webhook().await {
+            notify(&client, &webhook, ndjson_payload(tasks)).await;
+        }
+    }
+}
+
+/// Posts the `payload` to the `webhook`, retrying with an exponential backoff on failure.
+async fn notify(client: &Client, webhook: &TaskWebhook, payload: Vec<u8>) {
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client
+            .post(&webhook.url)
+            .header(CONTENT_TYPE, "application/x-ndjson")
+            .body(payload.clone());
+        if let Some(ref authorization) = webhook.authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(_) => return,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                warn!(
+                    "Failed to notify the task webhook (attempt {}/{}): {}",
+                    attempt, MAX_ATTEMPTS, e
+                );
+                sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => error!(
+                "Giving up notifying the task webhook after {} attempts: {}",
+                MAX_ATTEMPTS, e
+            ),
+        }
+    }
+}
+
+/// Serializes the `tasks` as they are returned by the tasks route, one task per line.
+fn ndjson_payload(tasks: Vec<Task>) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for task in tasks {
+        // serializing a `TaskView` into a `Vec` can't fail.
+        serde_json::to_writer(&mut payload, &TaskView::from(task)).unwrap();
+        payload.push(b'\n');
+    }
+    payload
+}
+
+#[cfg(test)]
+mod test {
+    use meilisearch_lib::tasks::task::{TaskContent, TaskEvent, TaskPriority, TaskResult};
+    use serde_json::Value;
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    #[test]
+    fn test_ndjson_payload() {
+        let tasks = (0..2)
+            .map(|id| Task {
+                id,
+                content: TaskContent::Dump {
+                    uid: format!("dump-{}", id),
+                    incremental_from: None,
+                },
+                events: vec![
+                    TaskEvent::Created(OffsetDateTime::now_utc()),
+                    TaskEvent::Succeeded {
+                        result: TaskResult::Other,
+                        timestamp: OffsetDateTime::now_utc(),
+                    },
+                ],
+                request_id: None,
+                priority: TaskPriority::default(),
+                group_uid: None,
+                batch_seq: None,
+            })
+            .collect();
+
+        let payload = String::from_utf8(ndjson_payload(tasks)).unwrap();
+        let lines: Vec<Value> = payload
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["uid"], 0);
+        assert_eq!(lines[1]["uid"], 1);
+        assert_eq!(lines[1]["status"], "succeeded");
+        assert!(payload.ends_with('\n'));
+    }
+}
@@ -0,0 +1,108 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn search_and_update_through_an_alias() {
+    let server = Server::new().await;
+    let blue = server.index("movies-blue");
+    let green = server.index("movies-green");
+    blue.add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    blue.wait_task(0).await;
+    green
+        .add_documents(json!([{ "id": 2, "title": "Dune" }]), None)
+        .await;
+    green.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .put("/aliases/movies", json!({ "indexUid": "movies-blue" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "alias": "movies", "indexUid": "movies-blue" })
+    );
+
+    let movies = server.index("movies");
+    let (response, code) = movies.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1, "title": "Carol" }]));
+
+    // The updates registered on the alias are applied to the index it points to.
+    let (response, _) = movies
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    assert_eq!(response["indexUid"], "movies-blue");
+    movies.wait_task(2).await;
+    let (response, _) = blue.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["title"]));
+
+    // Retargeting the alias.
+    let (_, code) = server
+        .service
+        .put("/aliases/movies", json!({ "indexUid": "movies-green" }))
+        .await;
+    assert_eq!(code, 200);
+    let (response, _) = movies
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"], json!([{ "id": 2, "title": "Dune" }]));
+
+    let (response, code) = server.service.get("/aliases").await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["results"],
+        json!([{ "alias": "movies", "indexUid": "movies-green" }])
+    );
+
+    let (_, code) = server.service.delete("/aliases/movies").await;
+    assert_eq!(code, 204);
+    let (response, code) = movies.search_post(json!({ "q": "dune" })).await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn error_alias() {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .put("/aliases/movies", json!({ "indexUid": "movies" }))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_alias");
+
+    let (response, code) = server
+        .service
+        .put("/aliases/production", json!({ "indexUid": "books" }))
+        .await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "index_not_found");
+
+    let (response, code) = server.service.get("/aliases/production").await;
+    let expected_response = json!({
+        "message": "Alias `production` not found.",
+        "code": "alias_not_found",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#alias_not_found"
+    });
+    assert_eq!(code, 404);
+    assert_eq!(response, expected_response);
+
+    // An index can't be created under the name of an alias.
+    server
+        .service
+        .put("/aliases/production", json!({ "indexUid": "movies" }))
+        .await;
+    let (_, code) = server.index("production").create(None).await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_already_exists");
+}
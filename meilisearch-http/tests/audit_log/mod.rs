@@ -0,0 +1,79 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn administrative_actions_are_recorded() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let (response, code) = server
+        .add_api_key(json!({ "actions": ["*"], "indexes": ["*"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+    let key_uid = response["uid"].as_str().unwrap().to_string();
+
+    // the following actions are performed by the key.
+    server.use_api_key(&key);
+    let index = server.index("movies");
+    let (response, code) = index.create(None).await;
+    assert_eq!(code, 202, "{}", response);
+    let (response, code) = index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    // reading something isn't recorded.
+    index.get().await;
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.service.get("/audit-log").await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3, "{}", response);
+
+    // the most recent entries come first.
+    assert_eq!(results[0]["action"], "settings.updated");
+    assert_eq!(results[0]["target"], "movies");
+    assert_eq!(results[0]["apiKeyUid"], key_uid);
+    assert_eq!(
+        results[0]["details"]["settings"],
+        json!(["filterableAttributes"])
+    );
+    assert_eq!(results[1]["action"], "index.created");
+    assert_eq!(results[1]["apiKeyUid"], key_uid);
+    assert_eq!(results[2]["action"], "key.created");
+    assert_eq!(results[2]["target"], key_uid);
+    assert_eq!(results[2]["apiKeyUid"], json!(null));
+
+    let (response, code) = server
+        .service
+        .get("/audit-log?actions=key.created,index.created&limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"][0]["action"], "index.created");
+    assert_eq!(response["next"], 0);
+
+    let (response, code) = server.service.get("/audit-log?actions=keys.created").await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (export, code) = server.service.get_str("/audit-log/export").await;
+    assert_eq!(code, 200, "{}", export);
+    let actions: Vec<String> = export
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["action"].to_string())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            r#""key.created""#,
+            r#""index.created""#,
+            r#""settings.updated""#
+        ]
+    );
+
+    server.delete_api_key(&key).await;
+    let (response, _) = server.service.get("/audit-log?limit=1").await;
+    assert_eq!(response["results"][0]["action"], "key.deleted");
+    assert_eq!(response["results"][0]["target"], key_uid);
+}
@@ -819,6 +819,246 @@ async fn delete_api_key() {
     assert_eq!(404, code, "{:?}", &response);
 }
 
+#[actix_rt::test]
+async fn rotate_api_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "name": "search-key",
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": "2050-11-13T00:00:00Z"
+    });
+
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    let old_uid = response["uid"].as_str().unwrap().to_string();
+    let old_key = response["key"].as_str().unwrap().to_string();
+
+    let (response, code) = server
+        .rotate_api_key(&old_key, json!({ "gracePeriod": 3600 }))
+        .await;
+    assert_eq!(201, code, "{:?}", &response);
+    assert_ne!(response["uid"], old_uid.as_str());
+    assert_ne!(response["key"], old_key.as_str());
+    assert_eq!(response["name"], "search-key");
+    assert_eq!(response["indexes"], json!(["products"]));
+    assert_eq!(response["actions"], json!(["search"]));
+    assert_eq!(response["expiresAt"], "2050-11-13T00:00:00Z");
+    let new_uid = response["uid"].as_str().unwrap().to_string();
+
+    // the old key stays valid during the grace period only.
+    let (response, code) = server.get_api_key(&old_uid).await;
+    assert_eq!(200, code, "{:?}", &response);
+    assert_ne!(response["expiresAt"], "2050-11-13T00:00:00Z");
+
+    // without grace period, the old key is deleted right away.
+    let (response, code) = server
+        .rotate_api_key(&new_uid, json!({ "gracePeriod": 0 }))
+        .await;
+    assert_eq!(201, code, "{:?}", &response);
+    let (response, code) = server.get_api_key(&new_uid).await;
+    assert_eq!(404, code, "{:?}", &response);
+}
+
+#[actix_rt::test]
+async fn error_rotate_api_key_invalid_grace_period() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null
+    });
+
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    let uid = response["uid"].as_str().unwrap();
+
+    let (response, code) = server
+        .rotate_api_key(uid, json!({ "gracePeriod": -1 }))
+        .await;
+    assert_eq!(400, code, "{:?}", &response);
+
+    let expected_response = json!({
+        "message": "`gracePeriod` field value `-1` is invalid. It should be a positive integer representing a number of seconds or omitted.",
+        "code": "invalid_api_key_grace_period",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_grace_period"
+    });
+
+    assert_eq!(response, expected_response);
+}
+
+#[actix_rt::test]
+async fn api_key_allowed_ips_and_referers() {
+    let mut server = Server::new_auth().await;
+    server.service.options.trusted_proxies = vec!["127.0.0.1".parse().unwrap()];
+    server.use_api_key("MASTER_KEY");
+
+    let index = server.index("products");
+    let (response, code) = index.create(None).await;
+    assert_eq!(202, code, "{:?}", &response);
+    index.wait_task(0).await;
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "allowedIps": ["10.0.0.0/8", "192.168.1.12"],
+        "allowedReferers": ["*.example.com"]
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    assert_eq!(
+        response["allowedIps"],
+        json!(["10.0.0.0/8", "192.168.1.12/32"])
+    );
+    assert_eq!(response["allowedReferers"], json!(["*.example.com"]));
+
+    let key = response["key"].as_str().unwrap();
+    server.use_api_key(&key);
+
+    let url = "/indexes/products/search";
+    let referer = ("Referer", "https://shop.example.com/search?q=shoes");
+    let cases = [
+        // an allowed client and referer.
+        ("10.1.2.3:4000", vec![referer], 200),
+        (
+            "192.168.1.12:4000",
+            vec![("Origin", "https://shop.example.com")],
+            200,
+        ),
+        // a client outside of the allowed ranges.
+        ("192.168.1.13:4000", vec![referer], 403),
+        // a referer that doesn't match.
+        (
+            "10.1.2.3:4000",
+            vec![("Referer", "https://example.org/")],
+            403,
+        ),
+        (
+            "10.1.2.3:4000",
+            vec![("Referer", "https://example.com/")],
+            403,
+        ),
+        // no referer at all.
+        ("10.1.2.3:4000", vec![], 403),
+        // a client behind the trusted proxy.
+        (
+            "127.0.0.1:4000",
+            vec![referer, ("X-Forwarded-For", "192.168.0.1, 10.0.0.5")],
+            200,
+        ),
+        // the addresses before the last untrusted one can be forged.
+        (
+            "127.0.0.1:4000",
+            vec![referer, ("X-Forwarded-For", "10.0.0.5, 192.168.0.1")],
+            403,
+        ),
+        // the header of an untrusted peer is ignored.
+        (
+            "192.168.0.1:4000",
+            vec![referer, ("X-Forwarded-For", "10.0.0.5")],
+            403,
+        ),
+    ];
+
+    for (peer_addr, headers, expected_code) in cases {
+        let (response, code) = server
+            .service
+            .get_from(url, peer_addr.parse().unwrap(), &headers)
+            .await;
+        assert_eq!(
+            expected_code, code,
+            "from {} with {:?}: {:?}",
+            peer_addr, headers, response
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_allowed_ips() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "allowedIps": ["10.0.0.0/33"]
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(400, code, "{:?}", &response);
+    assert_eq!(response["code"], "invalid_api_key_allowed_ips");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "allowedReferers": ["https://example.com/"]
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(400, code, "{:?}", &response);
+    assert_eq!(response["code"], "invalid_api_key_allowed_referers");
+}
+
+#[actix_rt::test]
+async fn api_key_rate_limit() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "rateLimit": { "requestsPerSecond": 1 }
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    assert_eq!(response["rateLimit"]["requestsPerSecond"], 1);
+    let key = response["key"].as_str().unwrap().to_string();
+
+    server.use_api_key(&key);
+    let (response, code) = server.index("products").search_post(json!({})).await;
+    assert_ne!(429, code, "{:?}", &response);
+
+    // a second request within the same second is rejected.
+    let (response, code) = server.index("products").search_post(json!({})).await;
+    assert_eq!(429, code, "{:?}", &response);
+    assert_eq!(response["code"], "too_many_requests");
+
+    // the other keys aren't limited.
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.index("products").search_post(json!({})).await;
+    assert_ne!(429, code, "{:?}", &response);
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_rate_limit() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    for rate_limit in [
+        json!({ "requestsPerSecond": 0 }),
+        json!({ "concurrentSearches": -1 }),
+        json!({ "requests": 10 }),
+        json!(10),
+    ] {
+        let content = json!({
+            "indexes": ["products"],
+            "actions": ["search"],
+            "expiresAt": null,
+            "rateLimit": rate_limit
+        });
+        let (response, code) = server.add_api_key(content).await;
+        assert_eq!(400, code, "{:?}", &response);
+        assert_eq!(response["code"], "invalid_api_key_rate_limit");
+    }
+}
+
 #[actix_rt::test]
 async fn error_delete_api_key_no_header() {
     let server = Server::new_auth().await;
@@ -1458,3 +1698,29 @@ async fn error_access_api_key_routes_no_master_key_set() {
     assert_eq!(expected_code, code, "{:?}", &response);
     assert_eq!(response, expected_response);
 }
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_attributes() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "displayedAttributes": "title"
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(400, code, "{:?}", &response);
+    assert_eq!(response["code"], "invalid_api_key_displayed_attributes");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "forbiddenAttributes": ["email", 12]
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(400, code, "{:?}", &response);
+    assert_eq!(response["code"], "invalid_api_key_forbidden_attributes");
+}
@@ -11,13 +11,24 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
         hashmap! {
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/indexes/products/facet-search") =>                   hashset!{"search", "*"},
+            ("POST",    "/indexes/products/suggest") =>                        hashset!{"search", "*"},
+            ("GET",     "/indexes/products/similar/0") =>                      hashset!{"search", "*"},
+            ("POST",    "/multi-search") =>                                    hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "*"},
             ("GET",     "/indexes/products/documents/0") =>                    hashset!{"documents.get", "*"},
+            ("GET",     "/indexes/products/documents/export") =>               hashset!{"documents.get", "*"},
+            ("GET",     "/indexes/products/changes") =>                        hashset!{"documents.get", "*"},
             ("DELETE",  "/indexes/products/documents/0") =>                    hashset!{"documents.delete", "*"},
+            ("POST",    "/indexes/products/documents/restore") =>              hashset!{"documents.add", "*"},
             ("GET",     "/tasks") =>                                           hashset!{"tasks.get", "*"},
             ("GET",     "/tasks?indexUid=products") =>                         hashset!{"tasks.get", "*"},
             ("GET",     "/tasks/0") =>                                         hashset!{"tasks.get", "*"},
+            ("POST",    "/tasks/cancel?uids=0") =>                             hashset!{"tasks.cancel", "*"},
+            ("DELETE",  "/tasks?uids=0") =>                                    hashset!{"tasks.delete", "*"},
+            ("PATCH",   "/tasks/0") =>                                         hashset!{"tasks.update", "*"},
+            ("POST",    "/tasks/0/move-to-front") =>                           hashset!{"tasks.update", "*"},
             ("PATCH",   "/indexes/products/") =>                               hashset!{"indexes.update", "*"},
             ("GET",     "/indexes/products/") =>                               hashset!{"indexes.get", "*"},
             ("DELETE",  "/indexes/products/") =>                               hashset!{"indexes.delete", "*"},
@@ -32,6 +43,7 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("GET",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/synonyms/export") =>       hashset!{"settings.get", "*"},
             ("DELETE",  "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/settings/typo-tolerance") =>        hashset!{"settings.update", "*"},
@@ -43,15 +55,42 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("PUT",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/synonyms/export") =>       hashset!{"settings.update", "*"},
             ("GET",     "/indexes/products/stats") =>                          hashset!{"stats.get", "*"},
+            ("GET",     "/indexes/products/analytics") =>                      hashset!{"analytics.get", "*"},
+            ("GET",     "/replication/tasks") =>                               hashset!{"replication.get", "*"},
+            ("POST",    "/indexes/products/clone") =>                          hashset!{"indexes.create", "*"},
             ("GET",     "/stats") =>                                           hashset!{"stats.get", "*"},
             ("POST",    "/dumps") =>                                           hashset!{"dumps.create", "*"},
+            ("POST",    "/dumps/import") =>                                    hashset!{"dumps.import", "*"},
+            ("POST",    "/snapshots") =>                                       hashset!{"snapshots.create", "*"},
+            ("POST",    "/swap-indexes") =>                                    hashset!{"indexes.swap", "*"},
+            ("GET",     "/aliases") =>                                         hashset!{"aliases.get", "*"},
+            ("GET",     "/aliases/products") =>                                hashset!{"aliases.get", "*"},
+            ("PUT",     "/aliases/products") =>                                hashset!{"aliases.update", "*"},
+            ("DELETE",  "/aliases/products") =>                                hashset!{"aliases.update", "*"},
+            ("GET",     "/index-templates") =>                                 hashset!{"templates.get", "*"},
+            ("GET",     "/index-templates/logs") =>                            hashset!{"templates.get", "*"},
+            ("PUT",     "/index-templates/logs") =>                            hashset!{"templates.update", "*"},
+            ("DELETE",  "/index-templates/logs") =>                            hashset!{"templates.update", "*"},
             ("GET",     "/version") =>                                         hashset!{"version", "*"},
             ("PATCH",   "/keys/mykey/") =>                                     hashset!{"keys.update", "*"},
             ("GET",     "/keys/mykey/") =>                                     hashset!{"keys.get", "*"},
             ("DELETE",  "/keys/mykey/") =>                                     hashset!{"keys.delete", "*"},
+            ("POST",    "/keys/mykey/rotate") =>                               hashset!{"keys.rotate", "*"},
             ("POST",    "/keys") =>                                            hashset!{"keys.create", "*"},
             ("GET",     "/keys") =>                                            hashset!{"keys.get", "*"},
+            ("GET",     "/audit-log") =>                                       hashset!{"auditLog.get", "*"},
+            ("GET",     "/audit-log/export") =>                                hashset!{"auditLog.get", "*"},
+            ("GET",     "/config") =>                                          hashset!{"config.get", "*"},
+            ("PATCH",   "/config") =>                                          hashset!{"config.update", "*"},
+            ("GET",     "/experimental-features") =>                           hashset!{"experimental.get", "*"},
+            ("PATCH",   "/experimental-features") =>                           hashset!{"experimental.update", "*"},
+            ("GET",     "/maintenance") =>                                     hashset!{"maintenance.get", "*"},
+            ("PATCH",   "/maintenance") =>                                     hashset!{"maintenance.update", "*"},
+            ("GET",     "/webhook") =>                                         hashset!{"webhooks.get", "*"},
+            ("PUT",     "/webhook") =>                                         hashset!{"webhooks.update", "*"},
+            ("DELETE",  "/webhook") =>                                         hashset!{"webhooks.update", "*"},
         }
     });
 
@@ -478,6 +517,79 @@ async fn list_authorized_tasks_restricted_index() {
     assert!(!response.iter().any(|task| task["indexUid"] == "test"));
 }
 
+#[actix_rt::test]
+async fn access_authorized_index_pattern_and_action_group() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+
+    // create the indexes `products-eu` and `sales`
+    let index = server.index("products-eu");
+    let (response, code) = index.create(Some("id")).await;
+    assert_eq!(202, code, "{:?}", &response);
+    let index = server.index("sales");
+    let (response, code) = index.create(Some("id")).await;
+    assert_eq!(202, code, "{:?}", &response);
+    index.wait_task(1).await;
+
+    // create key with access on the indexes starting with `products`.
+    let content = json!({
+        "indexes": ["products*"],
+        "actions": ["documents.*", "settings.get", "tasks.get"],
+        "expiresAt": (OffsetDateTime::now_utc() + Duration::hours(1)).format(&Rfc3339).unwrap(),
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    assert_eq!(response["indexes"], json!(["products*"]));
+
+    // use created key.
+    let key = response["key"].as_str().unwrap();
+    server.use_api_key(&key);
+
+    let (response, code) = server.service.get("/indexes/products-eu/documents").await;
+    assert_eq!(200, code, "{:?}", &response);
+    let (response, code) = server
+        .service
+        .delete("/indexes/products-eu/documents")
+        .await;
+    assert_eq!(202, code, "{:?}", &response);
+    let (response, code) = server.service.get("/indexes/products-eu/settings").await;
+    assert_eq!(200, code, "{:?}", &response);
+
+    // the key has no access on the `settings.update` action.
+    let (response, code) = server
+        .service
+        .patch("/indexes/products-eu/settings", json!({}))
+        .await;
+    assert_eq!(403, code, "{:?}", &response);
+
+    // the key has no access on `sales` index.
+    let (response, code) = server.service.get("/indexes/sales/documents").await;
+    assert_eq!(403, code, "{:?}", &response);
+
+    let (response, code) = server.service.get("/tasks").await;
+    assert_eq!(200, code, "{:?}", &response);
+    let response = response["results"].as_array().unwrap();
+    assert!(response
+        .iter()
+        .any(|task| task["indexUid"] == "products-eu"));
+    assert!(!response.iter().any(|task| task["indexUid"] == "sales"));
+}
+
+#[actix_rt::test]
+async fn error_create_key_invalid_index_pattern() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["pro*ducts"],
+        "actions": ["search"],
+        "expiresAt": null,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(400, code, "{:?}", &response);
+    assert_eq!(response["code"], "invalid_api_key_indexes");
+}
+
 #[actix_rt::test]
 async fn list_authorized_tasks_no_index_restriction() {
     let mut server = Server::new_auth().await;
@@ -650,3 +762,35 @@ async fn lazy_create_index() {
     assert_eq!(200, code, "{:?}", &response);
     assert_eq!(response["status"], "succeeded");
 }
+
+#[actix_rt::test]
+async fn index_patterns_are_authorized_on_the_index_behind_an_alias() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    for (task, uid) in ["secret", "products"].into_iter().enumerate() {
+        let index = server.index(uid);
+        index.add_documents(json!([{ "id": 1 }]), None).await;
+        index.wait_task(task as u64).await;
+    }
+    for (alias, uid) in [("products-x", "secret"), ("catalog", "products")] {
+        let (response, code) = server
+            .service
+            .put(&format!("/aliases/{}", alias), json!({ "indexUid": uid }))
+            .await;
+        assert_eq!(code, 200, "{}", response);
+    }
+
+    let (response, code) = server
+        .add_api_key(json!({ "indexes": ["products*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+    server.use_api_key(&key);
+
+    // the alias matches the pattern of the key, the index it points to doesn't.
+    let (response, code) = server.index("products-x").search_post(json!({})).await;
+    assert_eq!(code, 403, "{}", response);
+
+    let (response, code) = server.index("catalog").search_post(json!({})).await;
+    assert_eq!(code, 200, "{}", response);
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use meilisearch_auth::IdentityProvider;
+use serde_json::{json, Value};
+use time::{Duration, OffsetDateTime};
+
+use crate::common::Server;
+
+const ISSUER: &str = "https://idp.example.com";
+const SECRET: &str = "identity-provider-secret-key01";
+
+fn generate_token(kid: &str, mut claims: Value) -> String {
+    let exp = OffsetDateTime::now_utc() + Duration::hours(1);
+    claims["exp"] = json!(exp.unix_timestamp());
+
+    let mut header = Header::default();
+    header.kid = Some(kid.to_string());
+    encode(
+        &header,
+        &claims,
+        &EncodingKey::from_secret(SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+async fn server_with_identity_provider() -> Server {
+    let mut server = Server::new_auth().await;
+
+    let provider = IdentityProvider::new(
+        ISSUER.to_string(),
+        Some("meilisearch".to_string()),
+        "meilisearch_actions".to_string(),
+        "meilisearch_indexes".to_string(),
+    );
+    let jwks: JwkSet = serde_json::from_value(json!({
+        "keys": [{
+            "kty": "oct",
+            "kid": "test-key",
+            "alg": "HS256",
+            // the secret encoded in base64.
+            "k": "aWRlbnRpdHktcHJvdmlkZXItc2VjcmV0LWtleTAx"
+        }]
+    }))
+    .unwrap();
+    provider.set_jwks(jwks);
+    server
+        .service
+        .auth
+        .set_identity_provider(Arc::new(provider));
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.index("products").create(None).await;
+    assert_eq!(202, code, "{:?}", response);
+    server.index("products").wait_task(0).await;
+
+    server
+}
+
+#[actix_rt::test]
+async fn identity_provider_token_is_authorized() {
+    let mut server = server_with_identity_provider().await;
+
+    let token = generate_token(
+        "test-key",
+        json!({
+            "iss": ISSUER,
+            "aud": "meilisearch",
+            "meilisearch_actions": "search documents.*",
+            "meilisearch_indexes": ["products*"]
+        }),
+    );
+    server.use_api_key(&token);
+
+    let (response, code) = server.index("products").search_post(json!({})).await;
+    assert_eq!(200, code, "{:?}", response);
+    let (response, code) = server
+        .index("products")
+        .get_all_documents(Default::default())
+        .await;
+    assert_eq!(200, code, "{:?}", response);
+
+    // neither the actions nor the indexes of the token.
+    let (response, code) = server.index("products").settings().await;
+    assert_eq!(403, code, "{:?}", response);
+    let (response, code) = server.index("movies").search_post(json!({})).await;
+    assert_eq!(403, code, "{:?}", response);
+    // the keys can't be managed with a token.
+    let (response, code) = server.list_api_keys().await;
+    assert_eq!(403, code, "{:?}", response);
+}
+
+#[actix_rt::test]
+async fn invalid_identity_provider_token() {
+    let mut server = server_with_identity_provider().await;
+
+    let claims = json!({
+        "iss": ISSUER,
+        "aud": "meilisearch",
+        "meilisearch_actions": ["*"],
+        "meilisearch_indexes": ["*"]
+    });
+
+    let mut wrong_issuer = claims.clone();
+    wrong_issuer["iss"] = json!("https://other.example.com");
+    let mut wrong_audience = claims.clone();
+    wrong_audience["aud"] = json!("other");
+
+    let tokens = [
+        generate_token("unknown-key", claims),
+        generate_token("test-key", wrong_issuer),
+        generate_token("test-key", wrong_audience),
+    ];
+
+    for token in tokens.iter() {
+        server.use_api_key(&token);
+        let (response, code) = server.index("products").search_post(json!({})).await;
+        assert_eq!(403, code, "{:?}", response);
+    }
+}
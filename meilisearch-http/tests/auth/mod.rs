@@ -1,5 +1,6 @@
 mod api_keys;
 mod authorization;
+mod identity_provider;
 mod payload;
 mod tenant_token;
 
@@ -37,6 +38,15 @@ impl Server {
         self.service.patch(url, content).await
     }
 
+    pub async fn rotate_api_key(
+        &self,
+        key: impl AsRef<str>,
+        content: Value,
+    ) -> (Value, StatusCode) {
+        let url = format!("/keys/{}/rotate", key.as_ref());
+        self.service.post(url, content).await
+    }
+
     pub async fn list_api_keys(&self) -> (Value, StatusCode) {
         let url = "/keys";
         self.service.get(url).await
@@ -582,3 +582,178 @@ async fn error_access_modified_token() {
     assert_eq!(response, INVALID_RESPONSE.clone());
     assert_eq!(code, 403);
 }
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn search_restricted_attributes() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+    let index = server.index("sales");
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index.wait_task(1).await;
+    drop(index);
+
+    server.use_api_key("MASTER_KEY");
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "forbiddenAttributes": ["color"],
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["forbiddenAttributes"], json!(["color"]));
+    let key = response["key"].as_str().unwrap().to_string();
+    let uid = response["uid"].as_str().unwrap().to_string();
+
+    // the forbidden attributes of the key are never returned, even when asked for.
+    server.use_api_key(&key);
+    let index = server.index("sales");
+    let (response, code) = index
+        .search_post(json!({
+            "q": "glass",
+            "attributesToRetrieve": ["*"],
+            "attributesToHighlight": ["color"],
+            "showMatchesPosition": true,
+            "facets": ["color"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["title"], "Glass");
+    assert!(response["hits"][0].get("color").is_none(), "{}", response);
+    assert!(response["hits"][0]["_formatted"].get("color").is_none());
+    assert_eq!(response["facetDistribution"], json!({}));
+
+    let (response, code) = index
+        .facet_search(json!({"facetName": "color", "facetQuery": "bl"}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+
+    // nor can they be used by the query.
+    for (query, error_code) in [
+        (json!({"filter": "color = blue"}), "invalid_filter"),
+        (
+            json!({"filter": [["title = Glass", "color EXISTS"]]}),
+            "invalid_filter",
+        ),
+        (json!({"sort": ["color:asc"]}), "invalid_sort"),
+        (
+            json!({"q": "blue", "attributesToSearchOn": ["color"]}),
+            "invalid_search_attributes_to_search_on",
+        ),
+        (json!({"distinct": "color"}), "invalid_search_distinct"),
+    ] {
+        let (response, code) = index.search_post(query.clone()).await;
+        assert_eq!(code, 400, "{} {}", query, response);
+        assert_eq!(response["code"], error_code, "{} {}", query, response);
+    }
+
+    // and the query string doesn't match the documents through them.
+    let (response, code) = index.search_post(json!({"q": "blue"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+    let (response, code) = index.search_post(json!({"q": "glass -blue"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["title"], "Glass");
+    drop(index);
+
+    // the displayed attributes of a tenant token restrict the ones of its parent key further,
+    // while its filter can still use the restricted attributes.
+    let tenant_token = hashmap! {
+        "searchRules" => json!({"sales": {
+            "displayedAttributes": ["id", "color"],
+            "filter": "color = blue",
+        }}),
+        "exp" => json!((OffsetDateTime::now_utc() + Duration::hours(1)).unix_timestamp())
+    };
+    let web_token = generate_tenant_token(&uid, &key, tenant_token);
+    server.use_api_key(&web_token);
+    let index = server.index("sales");
+    let (response, code) = index.search_post(json!({"q": "450465"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": "450465" }]));
+
+    // the attributes left out of the displayed ones are restricted too.
+    let (response, code) = index.search_post(json!({"q": "glass"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+    let (response, code) = index.search_post(json!({"filter": "title = Glass"})).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn forged_tenant_token_is_not_rate_limited_as_its_claimed_key() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["search"],
+        "expiresAt": null,
+        "rateLimit": { "requestsPerSecond": 1 }
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+    let uid = response["uid"].as_str().unwrap().to_string();
+
+    // a token claiming the key without being signed by it doesn't use its quota.
+    let tenant_token = hashmap! { "searchRules" => json!(["*"]) };
+    let forged_token = generate_tenant_token(&uid, "not the parent key", tenant_token);
+    server.use_api_key(&forged_token);
+    for _ in 0..2 {
+        let (response, code) = server.index("sales").search_post(json!({})).await;
+        assert_eq!(code, 403, "{}", response);
+    }
+
+    server.use_api_key(&key);
+    let (response, code) = server.index("sales").search_post(json!({})).await;
+    assert_ne!(code, 429, "{}", response);
+}
+
+#[actix_rt::test]
+async fn tenant_token_rules_apply_through_an_alias() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("sales");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index.wait_task(1).await;
+    drop(index);
+    let (response, code) = server
+        .service
+        .put("/aliases/shop", json!({ "indexUid": "sales" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let (response, code) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap().to_string();
+    let uid = response["uid"].as_str().unwrap().to_string();
+
+    // the filter of the index applies when it is reached through an alias, rather than the
+    // rules of `*` matching the alias.
+    let tenant_token = hashmap! {
+        "searchRules" => json!({ "*": {}, "sales": { "filter": "color = blue" } }),
+    };
+    let web_token = generate_tenant_token(&uid, &key, tenant_token);
+    server.use_api_key(&web_token);
+    let (response, code) = server.index("shop").search_post(json!({})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"].as_array().unwrap().len(),
+        3,
+        "{}",
+        response
+    );
+}
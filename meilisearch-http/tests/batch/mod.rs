@@ -0,0 +1,97 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn register_and_get_batch() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/batch",
+            json!([
+                {
+                    "action": "addDocuments",
+                    "indexUid": "movies",
+                    "documents": [{ "id": 1, "title": "Carol" }, { "id": 2, "title": "Wonder Woman" }],
+                    "primaryKey": "id",
+                },
+                {
+                    "action": "updateSettings",
+                    "indexUid": "books",
+                    "settings": { "searchableAttributes": ["title"] },
+                },
+                {
+                    "action": "deleteDocuments",
+                    "indexUid": "movies",
+                    "ids": [2],
+                },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["groupUid"], 0);
+    let uids: Vec<_> = response["tasks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["taskUid"].as_u64().unwrap())
+        .collect();
+    assert_eq!(uids, vec![0, 1, 2]);
+
+    server.index("movies").wait_task(2).await;
+    server.index("books").wait_task(1).await;
+
+    let (response, code) = server.service.get("/batches/0").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], 0);
+    assert_eq!(response["totalTasks"], 3);
+    assert_eq!(response["tasks"][0]["groupUid"], 0);
+    assert_eq!(
+        response["progress"],
+        json!({ "enqueued": 0, "processing": 0, "succeeded": 3, "failed": 0, "canceled": 0 })
+    );
+
+    let (response, code) = server
+        .index("movies")
+        .get_all_documents(Default::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"], json!([{ "id": 1, "title": "Carol" }]));
+}
+
+#[actix_rt::test]
+async fn get_unexisting_batch() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/batches/12").await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "group_not_found");
+}
+
+#[actix_rt::test]
+async fn register_invalid_batch() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.post("/batch", json!([])).await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_batch_operation");
+
+    let (response, code) = server
+        .service
+        .post(
+            "/batch",
+            json!([
+                { "action": "clearDocuments", "indexUid": "movies" },
+                { "action": "deleteDocuments", "indexUid": "movies" },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_batch_operation");
+
+    // nothing was registered.
+    let (response, _code) = server.tasks().await;
+    assert_eq!(response["results"], json!([]));
+}
@@ -155,6 +155,21 @@ impl Index<'_> {
         self.service.get(url).await
     }
 
+    pub async fn fetch_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/fetch", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
+    pub async fn edit_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/edit", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
+    pub async fn restore_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/restore", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
     pub async fn delete_document(&self, id: u64) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/documents/{}", encode(self.uid.as_ref()), id);
         self.service.delete(url).await
@@ -175,6 +190,11 @@ impl Index<'_> {
             .await
     }
 
+    pub async fn delete_documents_by_filter(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/delete", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
     pub async fn settings(&self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/settings", encode(self.uid.as_ref()));
         self.service.get(url).await
@@ -220,6 +240,16 @@ impl Index<'_> {
         self.service.post(url, query).await
     }
 
+    pub async fn facet_search(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/facet-search", encode(self.uid.as_ref()));
+        self.service.post(url, query).await
+    }
+
+    pub async fn suggest(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/suggest", encode(self.uid.as_ref()));
+        self.service.post(url, query).await
+    }
+
     pub async fn search_get(&self, query: Value) -> (Value, StatusCode) {
         let params = serde_url_params::to_string(&query).unwrap();
         let url = format!("/indexes/{}/search?{}", encode(self.uid.as_ref()), params);
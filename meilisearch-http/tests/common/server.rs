@@ -9,7 +9,7 @@ use meilisearch_auth::AuthController;
 use meilisearch_http::setup_meilisearch;
 use meilisearch_lib::options::{IndexerOpts, MaxMemory};
 use once_cell::sync::Lazy;
-use serde_json::Value;
+use serde_json::{json, Value};
 use tempfile::TempDir;
 
 use meilisearch_http::option::Opt;
@@ -138,9 +138,27 @@ impl Server {
         self.service.get("/tasks").await
     }
 
+    pub async fn cancel_tasks(&self, query: &str) -> (Value, StatusCode) {
+        self.service
+            .post(format!("/tasks/cancel?{}", query), json!(null))
+            .await
+    }
+
+    pub async fn delete_tasks(&self, query: &str) -> (Value, StatusCode) {
+        self.service.delete(format!("/tasks?{}", query)).await
+    }
+
     pub async fn get_dump_status(&self, uid: &str) -> (Value, StatusCode) {
         self.service.get(format!("/dumps/{}/status", uid)).await
     }
+
+    pub async fn multi_search(&self, queries: Value) -> (Value, StatusCode) {
+        self.service.post("/multi-search", queries).await
+    }
+
+    pub async fn swap_indexes(&self, swaps: Value) -> (Value, StatusCode) {
+        self.service.post("/swap-indexes", swaps).await
+    }
 }
 
 pub fn default_settings(dir: impl AsRef<Path>) -> Opt {
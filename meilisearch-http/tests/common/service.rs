@@ -1,3 +1,6 @@
+use std::net::SocketAddr;
+
+use actix_web::http::header::HeaderMap;
 use actix_web::{http::StatusCode, test};
 use meilisearch_auth::AuthController;
 use meilisearch_lib::MeiliSearch;
@@ -90,6 +93,86 @@ impl Service {
         (response, status_code)
     }
 
+    /// Send a test get request whose response body isn't JSON, it is returned as text.
+    pub async fn get_str(&self, url: impl AsRef<str>) -> (String, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get().uri(url.as_ref());
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let body = test::read_body(res).await;
+        (String::from_utf8_lossy(&body).into_owned(), status_code)
+    }
+
+    /// Send a test get request sent from the `peer_addr` address, with the additional `headers`.
+    /// Sends `req` with the API key, and returns the headers of the response with its body.
+    pub async fn request(&self, mut req: test::TestRequest) -> (Value, StatusCode, HeaderMap) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let res = test::call_service(&app, req.to_request()).await;
+        let status_code = res.status();
+        let headers = res.headers().clone();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code, headers)
+    }
+
+    pub async fn get_from(
+        &self,
+        url: impl AsRef<str>,
+        peer_addr: SocketAddr,
+        headers: &[(&str, &str)],
+    ) -> (Value, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get()
+            .uri(url.as_ref())
+            .peer_addr(peer_addr);
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        for header in headers {
+            req = req.append_header(*header);
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code)
+    }
+
     pub async fn put(&self, url: impl AsRef<str>, body: Value) -> (Value, StatusCode) {
         let app = test::init_service(create_app!(
             &self.meilisearch,
@@ -0,0 +1,73 @@
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+/// Adds documents large enough for the responses listing them to be compressed.
+async fn server_with_documents(server: &Server) {
+    let index = server.index("test");
+    let documents = json!((0..100)
+        .map(|id| json!({ "id": id, "description": "a rather long and repetitive description" }))
+        .collect::<Vec<_>>());
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+}
+
+#[actix_rt::test]
+async fn large_responses_are_compressed() {
+    let server = Server::new().await;
+    server_with_documents(&server).await;
+
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/documents?limit=100")
+                .insert_header(("Accept-Encoding", "gzip")),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(headers.get("content-encoding").unwrap(), "gzip");
+
+    // the small responses aren't worth compressing.
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/documents/0")
+                .insert_header(("Accept-Encoding", "gzip")),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(headers.get("content-encoding").unwrap(), "identity");
+
+    // nor the ones of the clients not accepting it.
+    let (response, code, headers) = server
+        .service
+        .request(TestRequest::get().uri("/indexes/test/documents?limit=100"))
+        .await;
+    assert_eq!(code, 200);
+    assert!(!headers.contains_key("content-encoding"));
+    assert_eq!(response["results"].as_array().unwrap().len(), 100);
+}
+
+#[actix_rt::test]
+async fn compression_can_be_disabled_by_route() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.http_compression_disabled_routes = vec!["/indexes/*/documents".to_string()];
+    let server = Server::new_with_options(options).await.unwrap();
+    server_with_documents(&server).await;
+
+    let (response, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/documents?limit=100")
+                .insert_header(("Accept-Encoding", "gzip")),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(headers.get("content-encoding").unwrap(), "identity");
+    assert_eq!(response["results"].as_array().unwrap().len(), 100);
+}
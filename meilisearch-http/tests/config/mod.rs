@@ -0,0 +1,130 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn get_config() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/config").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["logLevel"], "info");
+    assert_eq!(response["httpPayloadSizeLimit"], 10 * 1024 * 1024);
+    assert_eq!(response["searchCutoffMs"], json!(null));
+    assert_eq!(response["taskWebhookUrl"], json!(null));
+    // the memory of the indexer is unlimited in the tests.
+    assert_eq!(response["maxIndexingMemory"], json!(null));
+    assert!(response["maxIndexingThreads"].is_u64());
+}
+
+#[actix_rt::test]
+async fn update_config() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(Some("id")).await;
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/config",
+            json!({
+                "logLevel": "info,milli=debug",
+                "httpPayloadSizeLimit": "100 B",
+                "searchCutoffMs": 50,
+                "taskWebhookUrl": "http://localhost:7777/tasks",
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["logLevel"], "info,milli=debug");
+    assert_eq!(response["httpPayloadSizeLimit"], 100);
+    assert_eq!(response["searchCutoffMs"], 50);
+    assert_eq!(response["taskWebhookUrl"], "http://localhost:7777/tasks");
+
+    // the new payload size limit applies right away.
+    let document = json!({ "id": 1, "content": "a".repeat(200) });
+    let (response, code) = index.add_documents(json!([document]), None).await;
+    assert_eq!(code, 413, "{}", response);
+    assert_eq!(response["code"], "payload_too_large");
+
+    let (response, code) = server.service.get("/webhook").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["url"], "http://localhost:7777/tasks");
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/config",
+            json!({ "searchCutoffMs": null, "taskWebhookUrl": null }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchCutoffMs"], json!(null));
+    assert_eq!(response["taskWebhookUrl"], json!(null));
+    assert_eq!(response["httpPayloadSizeLimit"], 100);
+}
+
+#[actix_rt::test]
+async fn update_config_invalid_options() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/config",
+            json!({ "logLevel": "verbose", "searchCutoffMs": 50 }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_log_level");
+
+    let (response, code) = server
+        .service
+        .patch("/config", json!({ "taskWebhookUrl": "ftp://localhost" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_webhook_url");
+
+    // nothing was applied.
+    let (response, _) = server.service.get("/config").await;
+    assert_eq!(response["logLevel"], "info");
+    assert_eq!(response["searchCutoffMs"], json!(null));
+
+    let (response, code) = server
+        .service
+        .patch("/config", json!({ "masterKey": "secret" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn update_indexing_limits() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/config",
+            json!({ "maxIndexingMemory": "512 MiB", "maxIndexingThreads": 2 }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["maxIndexingMemory"], 512 * 1024 * 1024);
+    assert_eq!(response["maxIndexingThreads"], 2);
+
+    // the documents are indexed with the new limits.
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = server
+        .service
+        .patch("/config", json!({ "maxIndexingMemory": null }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["maxIndexingMemory"], json!(null));
+    assert_eq!(response["maxIndexingThreads"], 2);
+}
@@ -0,0 +1,71 @@
+use actix_web::http::Method;
+use actix_web::test::TestRequest;
+use meilisearch_http::Opt;
+
+use crate::common::{default_settings, Server};
+
+#[actix_rt::test]
+async fn any_origin_is_allowed_by_default() {
+    let server = Server::new().await;
+
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/health")
+                .insert_header(("Origin", "https://example.com")),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(headers.get("access-control-allow-origin").unwrap(), "*");
+}
+
+#[actix_rt::test]
+async fn only_the_allowed_origins_are_accepted() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        cors_allowed_origins: vec!["https://example.com".to_string()],
+        cors_allowed_methods: vec![Method::GET, Method::POST],
+        cors_max_age_sec: 600,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/health")
+                .insert_header(("Origin", "https://example.com")),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        headers.get("access-control-allow-origin").unwrap(),
+        "https://example.com"
+    );
+
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::default()
+                .method(Method::OPTIONS)
+                .uri("/indexes")
+                .insert_header(("Origin", "https://example.com"))
+                .insert_header(("Access-Control-Request-Method", "POST")),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(headers.get("access-control-max-age").unwrap(), "600");
+
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/health")
+                .insert_header(("Origin", "https://evil.com")),
+        )
+        .await;
+    assert_eq!(code, 400);
+    assert!(!headers.contains_key("access-control-allow-origin"));
+}
@@ -51,6 +51,152 @@ async fn add_documents_test_json_content_types() {
     assert_eq!(response["taskUid"], 1);
 }
 
+/// gzip compressed payloads must be decompressed before being indexed
+#[actix_rt::test]
+async fn add_documents_test_gzip_content_encoding() {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let document = json!([
+        {
+            "id": 1,
+            "content": "Bouvier Bernois",
+        }
+    ]);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(document.to_string().as_bytes()).unwrap();
+    let payload = encoder.finish().unwrap();
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(payload)
+        .insert_header(("content-type", "application/json"))
+        .insert_header(("content-encoding", "gzip"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202);
+    assert_eq!(response["taskUid"], 0);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+}
+
+/// each file part of a multipart payload must create its own task
+#[actix_rt::test]
+async fn add_documents_test_multipart_form_data() {
+    let boundary = "------------------------meilisearch";
+    let payload = format!(
+        "--{boundary}\r\n\
+        Content-Disposition: form-data; name=\"documents\"; filename=\"documents.json\"\r\n\
+        Content-Type: application/json\r\n\r\n\
+        [{{\"id\": 1, \"content\": \"Bouvier Bernois\"}}]\r\n\
+        --{boundary}\r\n\
+        Content-Disposition: form-data; name=\"documents\"; filename=\"documents.csv\"\r\n\
+        Content-Type: text/csv\r\n\r\n\
+        id,content\n2,Leonberg\r\n\
+        --{boundary}--\r\n",
+        boundary = boundary
+    );
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(payload)
+        .insert_header((
+            "content-type",
+            format!("multipart/form-data; boundary={}", boundary),
+        ))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202);
+    assert_eq!(response[0]["taskUid"], 0);
+    assert_eq!(response[1]["taskUid"], 1);
+
+    let index = server.index("dog");
+    index.wait_task(1).await;
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn add_documents_test_msgpack_content_type() {
+    let documents = json!([
+        { "id": 1, "content": "Bouvier Bernois" },
+        { "id": 2, "content": "Leonberg" },
+    ]);
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(rmp_serde::to_vec_named(&documents).unwrap())
+        .insert_header(("content-type", "application/msgpack"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    index.wait_task(0).await;
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"], documents);
+
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(&b"\xc1"[..])
+        .insert_header(("content-type", "application/msgpack"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(response["code"], json!("malformed_payload"));
+}
+
 /// any other content-type is must be refused
 #[actix_rt::test]
 async fn error_add_documents_test_bad_content_types() {
@@ -84,7 +230,7 @@ async fn error_add_documents_test_bad_content_types() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/msgpack`, `application/cbor`, `multipart/form-data`"#
         )
     );
     assert_eq!(response["code"], "invalid_content_type");
@@ -108,7 +254,7 @@ async fn error_add_documents_test_bad_content_types() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/msgpack`, `application/cbor`, `multipart/form-data`"#
         )
     );
     assert_eq!(response["code"], "invalid_content_type");
@@ -151,7 +297,7 @@ async fn error_add_documents_test_no_content_type() {
     assert_eq!(
         response["message"],
         json!(
-            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/msgpack`, `application/cbor`, `multipart/form-data`"#
         )
     );
     assert_eq!(response["code"], "missing_content_type");
@@ -174,7 +320,7 @@ async fn error_add_documents_test_no_content_type() {
     assert_eq!(
         response["message"],
         json!(
-            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/msgpack`, `application/cbor`, `multipart/form-data`"#
         )
     );
     assert_eq!(response["code"], "missing_content_type");
@@ -185,6 +331,68 @@ async fn error_add_documents_test_no_content_type() {
     );
 }
 
+#[actix_rt::test]
+async fn add_csv_documents_with_custom_delimiter_and_quote() {
+    let document = "id;content;price:number\n1;'hello; world';12.5\n2;hello world;3";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvDelimiter=%3B&csvQuote=%27")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": "1", "content": "hello; world", "price": 12.5 })
+    );
+}
+
+#[actix_rt::test]
+async fn error_add_csv_documents_with_non_ascii_delimiter() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvDelimiter=%C3%A9")
+        .set_payload("id\n1")
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!("The `csvDelimiter` value `é` is invalid. Only ASCII characters are accepted.")
+    );
+    assert_eq!(response["code"], json!("bad_request"));
+}
+
 #[actix_rt::test]
 async fn error_add_malformed_csv_documents() {
     let document = "id, content\n1234, hello, world\n12, hello world";
@@ -388,7 +596,7 @@ async fn error_add_malformed_ndjson_documents() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 1 column 2`."#
+            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`."#
         )
     );
     assert_eq!(response["code"], json!("malformed_payload"));
@@ -412,7 +620,7 @@ async fn error_add_malformed_ndjson_documents() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 1 column 2`."#
+            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`."#
         )
     );
     assert_eq!(response["code"], json!("malformed_payload"));
@@ -810,6 +1018,100 @@ async fn update_document() {
     );
 }
 
+#[actix_rt::test]
+async fn update_document_with_merge_patch() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        {
+            "doc_id": 1,
+            "content": "foo",
+            "author": { "name": "John", "age": 32 },
+        }
+    ]);
+
+    let (_response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 202);
+
+    index.wait_task(0).await;
+
+    let patch = json!({ "doc_id": 1, "content": null, "author": { "age": 33 } });
+
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::put()
+        .uri("/indexes/test/documents")
+        .set_payload(patch.to_string())
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    index.wait_task(1).await;
+
+    let (response, code) = index.get_task(1).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "doc_id": 1, "author": { "name": "John", "age": 33 } })
+    );
+}
+
+#[actix_rt::test]
+async fn merge_patch_applies_on_top_of_enqueued_additions() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([{ "doc_id": 1, "content": "foo", "tags": ["a"] }]);
+    let (_response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 202);
+
+    // the patch is sent before the addition is processed.
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let patches = json!([
+        { "doc_id": 1, "tags": null, "other": "bar" },
+        { "doc_id": 1, "content": "baz" },
+    ]);
+    let req = test::TestRequest::put()
+        .uri("/indexes/test/documents")
+        .set_payload(patches.to_string())
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    index.wait_task(1).await;
+
+    let (response, code) = index.get_task(1).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "doc_id": 1, "content": "baz", "other": "bar" })
+    );
+}
+
 #[actix_rt::test]
 async fn add_larger_dataset() {
     let server = Server::new().await;
@@ -1099,3 +1401,103 @@ async fn add_documents_with_primary_key_twice() {
     let (response, _code) = index.get_task(1).await;
     assert_eq!(response["status"], "succeeded");
 }
+
+#[actix_rt::test]
+async fn add_documents_with_stale_versions() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "versionAttribute": "version" }))
+        .await;
+    index
+        .add_documents(json!([{ "id": 1, "version": 2, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let documents = json!([
+        { "id": 1, "version": 1, "title": "Moonlight" },
+        { "id": 2, "version": 1, "title": "Pan" },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+    assert_eq!(response["details"]["rejectedDocuments"], json!(["1"]));
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["title"], "Carol");
+
+    // a newer version replaces the stored document.
+    index
+        .update_documents(
+            json!([{ "id": 1, "version": 3, "title": "Moonlight" }]),
+            None,
+        )
+        .await;
+    let response = index.wait_task(3).await;
+    assert!(response["details"].get("rejectedDocuments").is_none());
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(response["title"], "Moonlight");
+}
+
+#[actix_rt::test]
+async fn partial_updates_without_version_keep_the_stored_one() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "versionAttribute": "version" }))
+        .await;
+    index
+        .add_documents(json!([{ "id": 1, "version": 2, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    // the partial updates that don't set the version are accepted.
+    index
+        .update_documents(json!([{ "id": 1, "title": "Moonlight" }]), None)
+        .await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["details"]["indexedDocuments"], 1, "{}", response);
+    assert!(response["details"].get("rejectedDocuments").is_none());
+
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::put()
+        .uri("/indexes/test/documents")
+        .set_payload(json!({ "id": 1, "genre": "drama" }).to_string())
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+    let response = index.wait_task(3).await;
+    assert!(response["details"].get("rejectedDocuments").is_none());
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(
+        response,
+        json!({ "id": 1, "version": 2, "title": "Moonlight", "genre": "drama" })
+    );
+
+    // the ones that set it must bump it, like the replacements.
+    index
+        .update_documents(json!([{ "id": 1, "version": 2, "title": "Pan" }]), None)
+        .await;
+    let response = index.wait_task(4).await;
+    assert_eq!(response["details"]["rejectedDocuments"], json!(["1"]));
+    index
+        .add_documents(json!([{ "id": 1, "title": "Pan" }]), None)
+        .await;
+    let response = index.wait_task(5).await;
+    assert_eq!(response["details"]["rejectedDocuments"], json!(["1"]));
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(response["title"], "Moonlight");
+}
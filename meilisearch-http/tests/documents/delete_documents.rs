@@ -145,3 +145,62 @@ async fn delete_no_document_batch() {
     assert_eq!(code, 200);
     assert_eq!(response["results"].as_array().unwrap().len(), 3);
 }
+
+#[actix_rt::test]
+async fn delete_documents_by_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["status"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "status": "archived" },
+                { "id": 1, "status": "published" },
+                { "id": 2, "status": "archived" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .delete_documents_by_filter(json!({ "filter": "status = archived" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["type"], "documentDeletion");
+    assert_eq!(response["details"]["originalFilter"], "status = archived");
+    assert_eq!(response["details"]["deletedDocuments"], 2);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["results"],
+        json!([{ "id": 1, "status": "published" }])
+    );
+}
+
+#[actix_rt::test]
+async fn delete_documents_by_filter_on_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 0, "status": "archived" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (_response, code) = index
+        .delete_documents_by_filter(json!({ "filter": "status = archived" }))
+        .await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["details"]["deletedDocuments"], 0);
+}
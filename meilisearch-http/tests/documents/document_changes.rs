@@ -0,0 +1,86 @@
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+async fn server() -> Server {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.indexer_options.experimental_enable_changes_feed = true;
+    Server::new_with_options(options).await.unwrap()
+}
+
+#[actix_rt::test]
+async fn changes_feed_is_disabled_by_default() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/indexes/test/changes").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "feature_not_enabled");
+}
+
+#[actix_rt::test]
+async fn list_document_changes() {
+    let server = server().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol" },
+                { "id": 2, "title": "Wonder Woman" },
+                { "id": 3, "title": "Dune" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+    index.delete_document(2).await;
+    index
+        .update_documents(json!([{ "id": 1, "title": "Carol (2015)" }]), None)
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = server.service.get("/indexes/test/changes").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["lastSequence"], 5);
+    // only the latest change of each document is kept.
+    let changes: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| {
+            (
+                change["sequence"].clone(),
+                change["kind"].clone(),
+                change["id"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (json!(3), json!("upsert"), json!("3")),
+            (json!(4), json!("deletion"), json!("2")),
+            (json!(5), json!("upsert"), json!("1")),
+        ]
+    );
+    assert_eq!(
+        response["results"][2]["document"],
+        json!({ "id": 1, "title": "Carol (2015)" })
+    );
+    assert!(response["results"][1].get("document").is_none());
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/changes?since=3&limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["sequence"], 4);
+
+    let (response, code) = server.service.get("/indexes/test/changes?since=5").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([]));
+}
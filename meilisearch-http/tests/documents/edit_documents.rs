@@ -0,0 +1,64 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn edit_documents_with_function() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["status"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "status": "draft", "views": 1 },
+                { "id": 1, "status": "published", "views": 10 },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .edit_documents(json!({
+            "filter": "status = published",
+            "function": "doc.views += 1; doc.title = `doc ${doc.id}`;",
+        }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentEdition");
+    assert_eq!(response["details"]["editedDocuments"], 1);
+    assert_eq!(response["details"]["originalFilter"], "status = published");
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["results"],
+        json!([
+            { "id": 0, "status": "draft", "views": 1 },
+            { "id": 1, "status": "published", "views": 11, "title": "doc 1" },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn error_edit_documents_invalid_function() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 0, "views": 1 }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.views +=" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_document_edition_function");
+}
@@ -1,6 +1,8 @@
+use actix_web::test::TestRequest;
+
 use crate::common::{GetAllDocumentsOptions, GetDocumentOptions, Server};
 
-use serde_json::json;
+use serde_json::{json, Value};
 
 // TODO: partial test since we are testing error, amd error is not yet fully implemented in
 // transplant
@@ -400,3 +402,265 @@ async fn get_documents_displayed_attributes_is_ignored() {
     assert_eq!(response.as_object().unwrap().keys().count(), 16);
     assert!(response.as_object().unwrap().get("gender").is_some());
 }
+
+#[actix_rt::test]
+async fn get_all_documents_with_filter_and_sort() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["gender"],
+            "sortableAttributes": ["age"],
+        }))
+        .await;
+    index.load_test_set().await;
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/documents?filter=gender%20%3D%20male&sort=age:asc&limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["id"], json!(5));
+    assert_eq!(response["results"][0]["age"], json!(20));
+    assert_eq!(response["total"], json!(37));
+
+    let (response, code) = index
+        .fetch_documents(json!({
+            "filter": "gender = male",
+            "sort": ["age:asc"],
+            "fields": ["id", "age"],
+            "limit": 1,
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([{ "id": 5, "age": 20 }]));
+    assert_eq!(response["total"], json!(37));
+
+    let (response, code) = index
+        .fetch_documents(json!({ "filter": "gender = male" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
+    assert_eq!(response["total"], json!(37));
+}
+
+#[actix_rt::test]
+async fn error_get_all_documents_with_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (response, code) = index
+        .fetch_documents(json!({ "filter": "gender = male" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], json!("invalid_filter"));
+}
+
+#[actix_rt::test]
+async fn get_all_documents_with_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!((0..5)
+        .map(|id| json!({ "id": id, "even": id % 2 == 0 }))
+        .collect::<Vec<_>>());
+    index
+        .update_settings(json!({ "filterableAttributes": ["even"] }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let mut ids = Vec::new();
+    let mut cursor = String::new();
+    let mut pages = 0;
+    loop {
+        let (response, code) = server
+            .service
+            .get(format!("/indexes/test/documents?limit=2&cursor={}", cursor))
+            .await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["limit"], 2);
+        assert!(response.get("total").is_none());
+        pages += 1;
+        for document in response["results"].as_array().unwrap() {
+            ids.push(document["id"].as_u64().unwrap());
+        }
+        match response["nextCursor"].as_str() {
+            Some(next) => cursor = next.to_string(),
+            None => break,
+        }
+    }
+    ids.sort_unstable();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    assert_eq!(pages, 3);
+
+    let (response, code) = index
+        .fetch_documents(
+            json!({ "filter": "even = true", "cursor": "", "limit": 2, "fields": ["id"] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    let cursor = response["nextCursor"].as_str().unwrap();
+
+    let (response, code) = index
+        .fetch_documents(json!({ "filter": "even = true", "cursor": cursor, "limit": 2 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["nextCursor"], json!(null));
+}
+
+#[actix_rt::test]
+async fn error_get_all_documents_with_invalid_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/documents?cursor=notacursor")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], json!("invalid_document_cursor"));
+
+    let (response, code) = index
+        .fetch_documents(json!({ "cursor": "", "offset": 2 }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], json!("invalid_document_cursor"));
+}
+
+#[actix_rt::test]
+async fn export_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!((0..2500)
+        .map(|id| json!({ "id": id, "even": id % 2 == 0 }))
+        .collect::<Vec<_>>());
+    index
+        .update_settings(json!({ "filterableAttributes": ["even"] }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (export, code) = server
+        .service
+        .get_str("/indexes/test/documents/export")
+        .await;
+    assert_eq!(code, 200, "{}", export);
+    let mut ids: Vec<u64> = export
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<Value>(line).unwrap()["id"]
+                .as_u64()
+                .unwrap()
+        })
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (0..2500).collect::<Vec<_>>());
+
+    let (export, code) = server
+        .service
+        .get_str("/indexes/test/documents/export?filter=even%20%3D%20true&fields=id")
+        .await;
+    assert_eq!(code, 200, "{}", export);
+    let documents: Vec<Value> = export
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(documents.len(), 1250);
+    assert!(documents
+        .iter()
+        .all(|document| document.as_object().unwrap().len() == 1
+            && document["id"].as_u64().unwrap() % 2 == 0));
+
+    let (_, code) = server
+        .service
+        .get_str("/indexes/unknown/documents/export")
+        .await;
+    assert_eq!(code, 404);
+}
+
+#[actix_rt::test]
+async fn get_document_with_etag() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (_, code, headers) = server
+        .service
+        .request(TestRequest::get().uri("/indexes/test/documents/1"))
+        .await;
+    assert_eq!(code, 200);
+    let etag = headers.get("etag").unwrap().clone();
+
+    let (_, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/documents/1")
+                .insert_header(("If-None-Match", etag.clone())),
+        )
+        .await;
+    assert_eq!(code, 304);
+    assert_eq!(headers.get("etag").unwrap(), &etag);
+
+    // the selected fields are another representation of the document.
+    let (_, _, headers) = server
+        .service
+        .request(TestRequest::get().uri("/indexes/test/documents/1?fields=id"))
+        .await;
+    assert_ne!(headers.get("etag").unwrap(), &etag);
+
+    index
+        .update_documents(json!([{ "id": 1, "title": "Moonlight" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code, headers) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/documents/1")
+                .insert_header(("If-None-Match", etag.clone())),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["title"], "Moonlight");
+    assert_ne!(headers.get("etag").unwrap(), &etag);
+}
+
+#[actix_rt::test]
+async fn fetch_documents_by_ids() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!((0..5)
+        .map(|id| json!({ "id": id, "title": format!("title {}", id) }))
+        .collect::<Vec<_>>());
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .fetch_documents(json!({ "ids": [3, "1", 42, 0], "fields": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "results": [{ "id": 3 }, { "id": 1 }, { "id": 0 }],
+            "missing": ["42"],
+        })
+    );
+
+    let (response, code) = index
+        .fetch_documents(json!({ "ids": [1], "offset": 2 }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], json!("bad_request"));
+}
@@ -1,3 +1,6 @@
 mod add_documents;
 mod delete_documents;
+mod document_changes;
+mod edit_documents;
 mod get_documents;
+mod restore_documents;
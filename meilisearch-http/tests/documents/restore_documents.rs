@@ -0,0 +1,117 @@
+use serde_json::json;
+
+use crate::common::{default_settings, GetAllDocumentsOptions, Server};
+
+async fn server() -> Server {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.indexer_options.soft_deletion_retention_sec = Some(3600);
+    Server::new_with_options(options).await.unwrap()
+}
+
+#[actix_rt::test]
+async fn restore_deleted_documents() {
+    let server = server().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol", "genre": "drama" },
+                { "id": 2, "title": "Wonder Woman", "genre": "action" },
+                { "id": 3, "title": "Dune", "genre": "action" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    index.delete_document(1).await;
+    index
+        .delete_documents_by_filter(json!({ "filter": "genre = action" }))
+        .await;
+    index.wait_task(3).await;
+
+    let (response, _) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"], json!([]));
+    // the deleted documents are invisible to the search.
+    let (response, _) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(response["hits"], json!([]));
+
+    let (response, code) = index.restore_documents(json!({ "ids": [1] })).await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(4).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentRestoration");
+    assert_eq!(response["details"]["receivedDocumentIds"], 1);
+    assert_eq!(response["details"]["restoredDocuments"], 1);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["title"], "Carol");
+    let (_, code) = index.get_document(2, None).await;
+    assert_eq!(code, 404);
+
+    // a document added back after its deletion isn't replaced by the deleted version.
+    index
+        .add_documents(json!([{ "id": 3, "title": "Dune: Part Two" }]), None)
+        .await;
+    index.wait_task(5).await;
+
+    index.restore_documents(json!({})).await;
+    let response = index.wait_task(6).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, _) = index.get_document(2, None).await;
+    assert_eq!(
+        response,
+        json!({ "id": 2, "title": "Wonder Woman", "genre": "action" })
+    );
+    let (response, _) = index.get_document(3, None).await;
+    assert_eq!(response, json!({ "id": 3, "title": "Dune: Part Two" }));
+}
+
+#[actix_rt::test]
+async fn restore_documents_deleted_by_an_enqueued_task() {
+    let server = server().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    // the restoration is processed after the deletion enqueued before it.
+    index.clear_all_documents().await;
+    index.restore_documents(json!({})).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["restoredDocuments"], 1);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["title"], "Carol");
+}
+
+#[actix_rt::test]
+async fn deletions_are_definitive_by_default() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    index.clear_all_documents().await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.restore_documents(json!({})).await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+
+    let (_, code) = index.get_document(1, None).await;
+    assert_eq!(code, 404);
+}
@@ -1,6 +1,7 @@
 mod data;
 
 use crate::common::{default_settings, Server};
+use actix_web::test;
 use meilisearch_http::Opt;
 use serde_json::json;
 
@@ -61,7 +62,7 @@ async fn import_dump_v2_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -125,7 +126,7 @@ async fn import_dump_v2_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -189,7 +190,7 @@ async fn import_dump_v2_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 }})
+        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -253,7 +254,7 @@ async fn import_dump_v3_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -317,7 +318,7 @@ async fn import_dump_v3_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -381,7 +382,7 @@ async fn import_dump_v3_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -445,7 +446,7 @@ async fn import_dump_v4_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({ "displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -509,7 +510,7 @@ async fn import_dump_v4_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -573,7 +574,7 @@ async fn import_dump_v4_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "limitedTo": 1000 } })
+        json!({ "displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "limitedTo": 1000, "maxTotalHits": null }, "searchCutoffMs": null, "rankingProfiles": {}, "expiryAttribute": null, "versionAttribute": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -605,3 +606,268 @@ async fn import_dump_v4_rubygems_with_settings() {
         json!({ "name": "vortex-of-agony", "summary": "You dont need to use nodejs or go, just install this plugin. It will crash your application at random", "description": "You dont need to use nodejs or go, just install this plugin. It will crash your application at random", "id": "159227", "version": "0.1.0", "total_downloads": "1007"})
     );
 }
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn import_incremental_dump_chain() {
+    let temp = tempfile::tempdir().unwrap();
+    let dumps_dir = tempfile::tempdir().unwrap();
+    let options = Opt {
+        dumps_dir: dumps_dir.path().to_owned(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (_, code) = server
+        .service
+        .post("/dumps", json!({ "incrementalFrom": "unknown" }))
+        .await;
+    assert_eq!(code, 404);
+
+    let movies = server.index("movies");
+    let books = server.index("books");
+    movies
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), Some("id"))
+        .await;
+    books.add_documents(json!([{ "id": 1 }]), Some("id")).await;
+    books.wait_task(1).await;
+
+    let (_, code) = server.service.post("/dumps", json!({})).await;
+    assert_eq!(code, 202);
+    let base_uid = movies.wait_task(2).await["details"]["dumpUid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    movies.add_documents(json!([{ "id": 3 }]), None).await;
+    books.delete().await;
+    let comics = server.index("comics");
+    comics.add_documents(json!([{ "id": 1 }]), Some("id")).await;
+    comics.wait_task(5).await;
+
+    let (_, code) = server
+        .service
+        .post("/dumps", json!({ "incrementalFrom": base_uid }))
+        .await;
+    assert_eq!(code, 202);
+    let task = movies.wait_task(6).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+    assert_eq!(task["details"]["incrementalFrom"], base_uid);
+    let incremental_uid = task["details"]["dumpUid"].as_str().unwrap().to_string();
+
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        import_dump: Some(dumps_dir.path().join(format!("{}.dump", base_uid))),
+        import_incremental_dump: vec![dumps_dir.path().join(format!("{}.dump", incremental_uid))],
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (indexes, code) = server.list_indexes(None, None).await;
+    assert_eq!(code, 200);
+    let uids: Vec<_> = indexes["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|index| index["uid"].as_str().unwrap())
+        .collect();
+    assert_eq!(uids, ["comics", "movies"]);
+
+    let (documents, code) = server
+        .index("movies")
+        .get_all_documents(Default::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(documents["results"].as_array().unwrap().len(), 3);
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn refuse_incremental_dump_after_task_deletion() {
+    let temp = tempfile::tempdir().unwrap();
+    let dumps_dir = tempfile::tempdir().unwrap();
+    let options = Opt {
+        dumps_dir: dumps_dir.path().to_owned(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let movies = server.index("movies");
+    movies.add_documents(json!([{ "id": 1 }]), Some("id")).await;
+    movies.wait_task(0).await;
+
+    let (_, code) = server.service.post("/dumps", json!({})).await;
+    assert_eq!(code, 202);
+    let base_uid = movies.wait_task(1).await["details"]["dumpUid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    movies.add_documents(json!([{ "id": 2 }]), None).await;
+    movies.wait_task(2).await;
+
+    // deleting the tasks registered before the base doesn't matter.
+    let (_, code) = server.delete_tasks("uids=0").await;
+    assert_eq!(code, 202);
+    let (_, code) = server
+        .service
+        .post("/dumps", json!({ "incrementalFrom": base_uid }))
+        .await;
+    assert_eq!(code, 202);
+    let task = movies.wait_task(4).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+
+    // the index modified by a deleted task wouldn't be dumped.
+    let (_, code) = server.delete_tasks("uids=2").await;
+    assert_eq!(code, 202);
+    let (response, code) = server
+        .service
+        .post("/dumps", json!({ "incrementalFrom": base_uid }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn import_dump_at_runtime() {
+    let temp = tempfile::tempdir().unwrap();
+    let dumps_dir = tempfile::tempdir().unwrap();
+    let options = Opt {
+        dumps_dir: dumps_dir.path().to_owned(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let movies = server.index("movies");
+    movies
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), Some("id"))
+        .await;
+    movies
+        .update_settings(json!({ "filterableAttributes": ["id"] }))
+        .await;
+    let books = server.index("books");
+    books.add_documents(json!([{ "id": 1 }]), Some("id")).await;
+    books.wait_task(2).await;
+
+    let (_, code) = server.service.post("/dumps", json!({})).await;
+    assert_eq!(code, 202);
+    let dump_uid = movies.wait_task(3).await["details"]["dumpUid"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let dump_path = dumps_dir.path().join(format!("{}.dump", dump_uid));
+
+    // the dumps can only be imported from the dump directory.
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        dumps_dir: dumps_dir.path().to_owned(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let movies = server.index("movies");
+    movies
+        .add_documents(json!([{ "id": 10 }]), Some("id"))
+        .await;
+    movies.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post("/dumps/import", json!({ "path": dump_path }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "dumpImport");
+    let task = movies.wait_task(1).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+    assert_eq!(
+        task["details"]["indexes"],
+        json!([{ "uid": "books", "importedAs": "books" }, { "uid": "movies", "importedAs": null }])
+    );
+
+    // the body is read as json whatever the parameters of its content type.
+    let req = test::TestRequest::post()
+        .uri("/dumps/import")
+        .set_payload(
+            json!({ "path": dump_uid.clone() + ".dump", "conflict": "rename" }).to_string(),
+        )
+        .insert_header(("content-type", "application/json; charset=utf-8"));
+    let (response, code, _) = server.service.request(req).await;
+    assert_eq!(code, 202, "{}", response);
+    let task_uid = response["taskUid"].as_u64().unwrap();
+    let task = movies.wait_task(task_uid).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+    assert_eq!(
+        task["details"]["indexes"],
+        json!([{ "uid": "books", "importedAs": "books_1" }, { "uid": "movies", "importedAs": "movies_1" }])
+    );
+    let (group, code) = server
+        .service
+        .get(format!("/batches/{}", task["details"]["groupUid"]))
+        .await;
+    assert_eq!(code, 200, "{}", group);
+    let last_task = group["tasks"].as_array().unwrap().last().unwrap()["uid"]
+        .as_u64()
+        .unwrap();
+    let task = movies.wait_task(last_task).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+
+    let renamed = server.index("movies_1");
+    let (documents, code) = renamed.get_all_documents(Default::default()).await;
+    assert_eq!(code, 200);
+    assert_eq!(documents["results"].as_array().unwrap().len(), 2);
+    let (settings, code) = renamed.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(settings["filterableAttributes"], json!(["id"]));
+
+    // the existing index is left untouched.
+    let (documents, _) = movies.get_all_documents(Default::default()).await;
+    assert_eq!(documents["results"], json!([{ "id": 10 }]));
+
+    // an unsupported dump fails when the import is processed.
+    std::fs::copy(
+        GetDump::MoviesRawV4.path(),
+        dumps_dir.path().join("v4.dump"),
+    )
+    .unwrap();
+    let (response, code) = server
+        .service
+        .post("/dumps/import", json!({ "path": "v4.dump" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let task = movies
+        .wait_task(response["taskUid"].as_u64().unwrap())
+        .await;
+    assert_eq!(task["status"], "failed", "{}", task);
+    assert_eq!(task["error"]["code"], "invalid_dump");
+
+    let (response, code) = server
+        .service
+        .post(
+            "/dumps/import",
+            json!({ "path": GetDump::MoviesRawV4.path() }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    let (response, code) = server
+        .service
+        .post("/dumps/import", json!({ "path": "../v4.dump" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+
+    // no host is allowed by default.
+    let (response, code) = server
+        .service
+        .post(
+            "/dumps/import",
+            json!({ "url": "http://127.0.0.1:7700/dump.dump" }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    let (response, code) = server.service.post("/dumps/import", json!({})).await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "bad_request");
+}
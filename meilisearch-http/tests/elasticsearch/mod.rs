@@ -0,0 +1,160 @@
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use crate::common::{default_settings, GetAllDocumentsOptions, Server};
+
+async fn server() -> Server {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        experimental_enable_elasticsearch_compat: true,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("movies");
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre", "year"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol", "genre": "drama", "year": 2015 },
+                { "id": 2, "title": "Wonder Woman", "genre": "action", "year": 2017 },
+                { "id": 3, "title": "Carol of the Bells", "genre": "comedy", "year": 1999 },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+    server
+}
+
+#[actix_rt::test]
+async fn elasticsearch_compat_is_disabled_by_default() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/es/movies/_search",
+            json!({ "query": { "match_all": {} } }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "feature_not_enabled");
+}
+
+#[actix_rt::test]
+async fn elasticsearch_search() {
+    let server = server().await;
+
+    let query = json!({
+        "query": {
+            "bool": {
+                "must": { "match": { "title": "carol" } },
+                "filter": { "range": { "year": { "gte": 2000 } } },
+            }
+        },
+        "_source": ["title"],
+    });
+    let (response, code) = server.service.post("/es/movies/_search", query).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"]["total"],
+        json!({ "value": 1, "relation": "eq" })
+    );
+    assert_eq!(
+        response["hits"]["hits"],
+        json!([{ "_index": "movies", "_id": "1", "_score": null, "_source": { "title": "Carol" } }])
+    );
+
+    let query = json!({
+        "query": { "bool": { "must_not": { "term": { "genre": "drama" } } } },
+        "sort": [{ "year": "desc" }],
+        "size": 1,
+    });
+    let (response, code) = server.service.post("/es/movies/_search", query).await;
+    assert_eq!(code, 400, "{}", response);
+
+    server
+        .index("movies")
+        .update_settings(json!({ "sortableAttributes": ["year"] }))
+        .await;
+    server.index("movies").wait_task(2).await;
+
+    let query = json!({
+        "query": { "bool": { "must_not": { "term": { "genre": "drama" } } } },
+        "sort": [{ "year": "desc" }],
+        "size": 1,
+    });
+    let (response, code) = server.service.post("/es/movies/_search", query).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"]["hits"][0]["_id"], "2");
+    assert_eq!(response["hits"]["hits"][0]["_source"]["year"], 2017);
+}
+
+#[actix_rt::test]
+async fn elasticsearch_unsupported_query() {
+    let server = server().await;
+
+    let query = json!({ "query": { "fuzzy": { "title": "carl" } } });
+    let (response, code) = server.service.post("/es/movies/_search", query).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+    assert_eq!(
+        response["message"],
+        "The Elasticsearch request is not supported: the `fuzzy` query is not supported."
+    );
+}
+
+#[actix_rt::test]
+async fn elasticsearch_bulk() {
+    let server = server().await;
+
+    let body = r#"{ "index": { "_index": "books", "_id": "1" } }
+{ "title": "Dune" }
+{ "index": { "_index": "books", "_id": "2" } }
+{ "title": "Hyperion" }
+{ "update": { "_index": "movies", "_id": 1 } }
+{ "doc": { "year": 2016 } }
+{ "delete": { "_index": "movies", "_id": "3" } }
+"#;
+    let req = TestRequest::post()
+        .uri("/es/_bulk")
+        .insert_header(("content-type", "application/x-ndjson"))
+        .set_payload(body);
+    let (response, code, _) = server.service.request(req).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["errors"], false);
+    let items = response["items"].as_array().unwrap();
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0]["index"]["_id"], "1");
+    assert_eq!(items[0]["index"]["taskUid"], items[1]["index"]["taskUid"]);
+    assert_eq!(items[2]["update"]["_index"], "movies");
+    assert_eq!(items[3]["delete"]["status"], 202);
+
+    server.index("movies").wait_task(4).await;
+
+    let (books, _) = server
+        .index("books")
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(
+        books["results"],
+        json!([{ "title": "Dune", "id": "1" }, { "title": "Hyperion", "id": "2" }])
+    );
+    let (movies, _) = server
+        .index("movies")
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(movies["results"].as_array().unwrap().len(), 2);
+    assert_eq!(movies["results"][0]["year"], 2016);
+
+    // the request is rejected as a whole.
+    let req = TestRequest::post()
+        .uri("/es/movies/_bulk")
+        .set_payload("{ \"update\": {} }\n{ \"doc\": {} }\n");
+    let (response, code, _) = server.service.request(req).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
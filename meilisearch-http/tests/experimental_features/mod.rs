@@ -0,0 +1,76 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn toggle_the_experimental_features() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/experimental-features").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "metrics": false, "elasticsearchCompat": false, "searchAnalytics": false })
+    );
+
+    let (response, code) = server.service.get("/metrics").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "feature_not_enabled");
+
+    let (response, code) = server
+        .service
+        .patch("/experimental-features", json!({ "metrics": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "metrics": true, "elasticsearchCompat": false, "searchAnalytics": false })
+    );
+
+    let (metrics, code) = server.service.get_str("/metrics").await;
+    assert_eq!(code, 200, "{}", metrics);
+    assert!(metrics.contains("meilisearch_tasks_queued 0"));
+
+    let query = json!({ "query": { "match_all": {} } });
+    let (response, code) = server
+        .service
+        .post("/es/movies/_search", query.clone())
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "feature_not_enabled");
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/experimental-features",
+            json!({ "elasticsearchCompat": true }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "metrics": true, "elasticsearchCompat": true, "searchAnalytics": false })
+    );
+
+    // the feature is enabled, the index is still missing.
+    let (response, code) = server.service.post("/es/movies/_search", query).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn unknown_experimental_feature() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .patch("/experimental-features", json!({ "vectorStore": true }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, _) = server.service.get("/experimental-features").await;
+    assert_eq!(
+        response,
+        json!({ "metrics": false, "elasticsearchCompat": false, "searchAnalytics": false })
+    );
+}
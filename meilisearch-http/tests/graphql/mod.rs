@@ -0,0 +1,70 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn graphql_search_and_documents() {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol", "author": { "name": "Patricia" } },
+                { "id": 2, "title": "Wonder Woman", "author": { "name": "William" } },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let query = r#"{
+        moviesSearch(q: "carol") {
+            hits { id title }
+            estimatedTotalHits
+        }
+        moviesDocument(id: 2) { title author }
+        moviesDocuments(limit: 1) { results { id } total }
+        multiSearch(queries: [{ indexUid: "movies", q: "wonder" }]) { indexUid hits }
+    }"#;
+    let (response, code) = server
+        .service
+        .post("/graphql", json!({ "query": query }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("errors").is_none(), "{}", response);
+
+    let data = &response["data"];
+    assert_eq!(
+        data["moviesSearch"],
+        json!({ "hits": [{ "id": 1, "title": "Carol" }], "estimatedTotalHits": 1 })
+    );
+    assert_eq!(
+        data["moviesDocument"],
+        json!({ "title": "Wonder Woman", "author": { "name": "William" } })
+    );
+    assert_eq!(
+        data["moviesDocuments"],
+        json!({ "results": [{ "id": 1 }], "total": 2 })
+    );
+    assert_eq!(data["multiSearch"][0]["indexUid"], "movies");
+    assert_eq!(data["multiSearch"][0]["hits"][0]["id"], 2);
+}
+
+#[actix_rt::test]
+async fn graphql_errors_carry_the_error_code() {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let query = r#"{ moviesDocument(id: 42) { id } }"#;
+    let (response, code) = server
+        .service
+        .post("/graphql", json!({ "query": query }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["errors"][0]["extensions"]["code"],
+        "document_not_found"
+    );
+}
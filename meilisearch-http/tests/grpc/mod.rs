@@ -0,0 +1,122 @@
+use meilisearch_auth::RateLimit;
+use meilisearch_http::grpc::proto::meilisearch_server::Meilisearch;
+use meilisearch_http::grpc::{proto, GrpcService};
+use serde_json::{json, Value};
+use tonic::Request;
+
+use crate::common::Server;
+
+async fn movies() -> (Server, GrpcService) {
+    movies_with_rate_limit(RateLimit::default()).await
+}
+
+async fn movies_with_rate_limit(global: RateLimit) -> (Server, GrpcService) {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol" },
+                { "id": 2, "title": "Wonder Woman" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let service = GrpcService::new(
+        server.service.meilisearch.clone(),
+        server.service.auth.clone(),
+        global,
+    );
+    (server, service)
+}
+
+#[actix_rt::test]
+async fn grpc_search_and_get_document() {
+    let (_server, service) = movies().await;
+
+    let request = proto::SearchRequest {
+        index_uid: "movies".to_string(),
+        q: Some("carol".to_string()),
+        ..Default::default()
+    };
+    let response = service.search(Request::new(request)).await.unwrap();
+    let response = response.into_inner();
+    assert_eq!(response.query, "carol");
+    assert_eq!(response.estimated_total_hits, Some(1));
+    let hit: Value = serde_json::from_str(&response.hits[0]).unwrap();
+    assert_eq!(hit, json!({ "id": 1, "title": "Carol" }));
+
+    let request = proto::GetDocumentRequest {
+        index_uid: "movies".to_string(),
+        document_id: "2".to_string(),
+        fields: vec!["title".to_string()],
+    };
+    let document = service.get_document(Request::new(request)).await.unwrap();
+    let document: Value = serde_json::from_str(&document.into_inner().json).unwrap();
+    assert_eq!(document, json!({ "title": "Wonder Woman" }));
+
+    let request = proto::GetDocumentRequest {
+        index_uid: "movies".to_string(),
+        document_id: "42".to_string(),
+        fields: Vec::new(),
+    };
+    let error = service
+        .get_document(Request::new(request))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), tonic::Code::NotFound);
+    assert_eq!(
+        error.metadata().get("meilisearch-error-code").unwrap(),
+        "document_not_found"
+    );
+}
+
+#[actix_rt::test]
+async fn grpc_delete_documents() {
+    let (server, service) = movies().await;
+
+    let request = proto::DeleteDocumentsRequest {
+        index_uid: "movies".to_string(),
+        document_ids: vec!["1".to_string()],
+    };
+    let task = service
+        .delete_documents(Request::new(request))
+        .await
+        .unwrap();
+    let task = task.into_inner();
+    assert_eq!(task.task_uid, 1);
+    assert_eq!(task.index_uid.as_deref(), Some("movies"));
+    assert_eq!(task.status, "enqueued");
+
+    server.index("movies").wait_task(1).await;
+    let request = proto::GetTaskRequest { task_uid: 1 };
+    let task = service.get_task(Request::new(request)).await.unwrap();
+    assert_eq!(task.into_inner().status, "succeeded");
+}
+
+#[actix_rt::test]
+async fn grpc_requests_are_rate_limited() {
+    let global = RateLimit {
+        requests_per_second: Some(1),
+        concurrent_searches: None,
+    };
+    let (_server, service) = movies_with_rate_limit(global).await;
+
+    let request = || {
+        Request::new(proto::SearchRequest {
+            index_uid: "movies".to_string(),
+            ..Default::default()
+        })
+    };
+    service.search(request()).await.unwrap();
+
+    let error = service.search(request()).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::ResourceExhausted);
+    assert_eq!(
+        error.metadata().get("meilisearch-error-code").unwrap(),
+        "too_many_requests"
+    );
+    assert_eq!(error.metadata().get("retry-after").unwrap(), "1");
+}
@@ -0,0 +1,101 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn clone_index() {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({ "filterableAttributes": ["title"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/movies/clone",
+            json!({ "indexUid": "movies-staging" }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "indexClone");
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(
+        response["details"],
+        json!({ "destinationIndexUid": "movies-staging", "settingsOnly": false })
+    );
+
+    let staging = server.index("movies-staging");
+    let (response, _) = staging
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"], json!([{ "id": 1, "title": "Carol" }]));
+    let (response, _) = staging.settings().await;
+    assert_eq!(response["filterableAttributes"], json!(["title"]));
+
+    // The copy is independent of its source.
+    staging.delete_document(1).await;
+    staging.wait_task(3).await;
+    let (response, _) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn clone_index_settings_only() {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (_, code) = server
+        .service
+        .post(
+            "/indexes/movies/clone",
+            json!({ "indexUid": "movies-staging", "settingsOnly": true }),
+        )
+        .await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let staging = server.index("movies-staging");
+    let (response, _) = staging
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"], json!([]));
+    let (response, _) = staging.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["title"]));
+}
+
+#[actix_rt::test]
+async fn error_clone_to_existing_index() {
+    let server = Server::new().await;
+    server.index("movies").create(None).await;
+    let index = server.index("books");
+    index.create(None).await;
+    index.wait_task(1).await;
+
+    let (_, code) = server
+        .service
+        .post("/indexes/movies/clone", json!({ "indexUid": "books" }))
+        .await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_already_exists");
+}
@@ -1,5 +1,7 @@
+mod clone_index;
 mod create_index;
 mod delete_index;
 mod get_index;
 mod stats;
+mod swap_indexes;
 mod update_index;
@@ -49,6 +49,37 @@ async fn stats() {
     assert_eq!(response["fieldDistribution"]["age"], 1);
 }
 
+#[actix_rt::test]
+async fn detailed_stats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Carol" },
+        { "id": 2, "title": "Wonder Woman" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200);
+    assert!(response.get("databaseSizes").is_none());
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/stats?detailed=true")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["numberOfDocuments"], 2);
+    assert!(response["numberOfDistinctTerms"].as_u64().unwrap() >= 3);
+    assert!(response["databaseSize"].as_u64().unwrap() > 0);
+    assert!(response["databaseSizes"]["documents"].as_u64().unwrap() > 0);
+    assert!(response["databaseSizes"]["word-docids"].as_u64().unwrap() > 0);
+    assert!(response["avgDocumentSize"].as_u64().unwrap() > 0);
+    assert!(response["createdAt"].is_string());
+    assert!(response["updatedAt"].is_string());
+}
+
 #[actix_rt::test]
 async fn error_get_stats_unexisting_index() {
     let server = Server::new().await;
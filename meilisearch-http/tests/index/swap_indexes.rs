@@ -0,0 +1,97 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn swap_indexes() {
+    let server = Server::new().await;
+    let movies = server.index("movies");
+    let books = server.index("books");
+
+    movies
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    movies.wait_task(0).await;
+    books
+        .add_documents(json!([{ "id": 1, "title": "Dune" }]), None)
+        .await;
+    books.wait_task(1).await;
+    books
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    books.wait_task(2).await;
+
+    let (response, code) = server
+        .swap_indexes(json!([{ "indexes": ["movies", "books"] }]))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "indexSwap");
+
+    let response = movies.wait_task(3).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["indexUid"], json!(null));
+    assert_eq!(
+        response["details"],
+        json!({ "swaps": [{ "indexes": ["movies", "books"] }] })
+    );
+
+    let (response, _) = movies
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"], json!([{ "id": 1, "title": "Dune" }]));
+    let (response, _) = movies.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["title"]));
+
+    let (response, _) = books
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(response["results"], json!([{ "id": 1, "title": "Carol" }]));
+    let (response, _) = books.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["*"]));
+}
+
+#[actix_rt::test]
+async fn swap_index_twice() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .swap_indexes(json!([
+            { "indexes": ["movies", "books"] },
+            { "indexes": ["books", "series"] },
+        ]))
+        .await;
+
+    let expected_response = json!({
+        "message": "The index `books` is present in several swaps.",
+        "code": "invalid_swap_indexes",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_swap_indexes"
+    });
+    assert_eq!(code, 400);
+    assert_eq!(response, expected_response);
+
+    let (response, code) = server
+        .swap_indexes(json!([{ "indexes": ["movies", "movies"] }]))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_swap_indexes");
+}
+
+#[actix_rt::test]
+async fn swap_unexisting_index() {
+    let server = Server::new().await;
+    let movies = server.index("movies");
+    movies.create(None).await;
+    movies.wait_task(0).await;
+
+    let (_, code) = server
+        .swap_indexes(json!([{ "indexes": ["movies", "books"] }]))
+        .await;
+    assert_eq!(code, 202);
+
+    let response = movies.wait_task(1).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_not_found");
+    // Nothing was swapped.
+    assert_eq!(movies.get().await.1, 200);
+}
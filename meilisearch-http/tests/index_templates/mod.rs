@@ -0,0 +1,73 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn template_applied_to_new_indexes() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .put(
+            "/index-templates/logs",
+            json!({
+                "pattern": "logs-*",
+                "settings": {
+                    "filterableAttributes": ["level"],
+                    "typoTolerance": { "enabled": false },
+                },
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["name"], "logs");
+    assert_eq!(response["pattern"], "logs-*");
+
+    // The index is created implicitly by the document addition.
+    let index = server.index("logs-2022");
+    index
+        .add_documents(json!([{ "id": 1, "level": "error" }]), None)
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["filterableAttributes"], json!(["level"]));
+    assert_eq!(response["typoTolerance"]["enabled"], json!(false));
+
+    let index = server.index("movies");
+    index.create(None).await;
+    index.wait_task(1).await;
+    let (response, _) = index.settings().await;
+    assert_eq!(response["filterableAttributes"], json!([]));
+
+    let (response, code) = server.service.get("/index-templates").await;
+    assert_eq!(code, 200);
+    assert_eq!(response["total"], 1);
+    assert_eq!(response["results"][0]["name"], "logs");
+
+    let (_, code) = server.service.delete("/index-templates/logs").await;
+    assert_eq!(code, 204);
+    let (response, code) = server.service.get("/index-templates/logs").await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "index_template_not_found");
+}
+
+#[actix_rt::test]
+async fn error_invalid_template() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .put("/index-templates/logs", json!({ "pattern": "logs.*" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_template");
+
+    let (response, code) = server
+        .service
+        .put("/index-templates/my.logs", json!({ "pattern": "logs-*" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_template");
+}
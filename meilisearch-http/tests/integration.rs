@@ -1,10 +1,29 @@
+mod aliases;
+mod audit_log;
 mod auth;
+mod batch;
 mod common;
+mod compression;
+mod config;
+mod cors;
 mod dashboard;
 mod documents;
 mod dumps;
+mod elasticsearch;
+mod experimental_features;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "grpc")]
+mod grpc;
 mod index;
+mod index_templates;
+mod maintenance;
+mod metrics;
+mod openapi;
+mod replication;
+mod request_id;
 mod search;
+mod search_analytics;
 mod settings;
 mod snapshot;
 mod stats;
@@ -0,0 +1,67 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn read_only_mode_rejects_the_writes() {
+    let server = Server::new().await;
+    let index = server.index("movies");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .patch("/maintenance", json!({ "readOnly": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["readOnly"], true);
+
+    let (response, code) = server.service.get("/maintenance").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["readOnly"], true);
+
+    // the reads and the searches are still accepted.
+    let (response, code) = index.get().await;
+    assert_eq!(code, 200, "{}", response);
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+
+    let (response, code) = index
+        .add_documents(json!([{ "id": 2, "title": "Parasite" }]), None)
+        .await;
+    assert_eq!(code, 503, "{}", response);
+    assert_eq!(response["code"], "read_only_mode");
+    let (response, code) = server.index("books").create(None).await;
+    assert_eq!(code, 503, "{}", response);
+    let (response, code) = index.delete().await;
+    assert_eq!(code, 503, "{}", response);
+
+    let (response, code) = server
+        .service
+        .patch("/maintenance", json!({ "readOnly": false }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["readOnly"], false);
+
+    let (response, code) = index
+        .add_documents(json!([{ "id": 2, "title": "Parasite" }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+}
+
+#[actix_rt::test]
+async fn update_maintenance_requires_the_mode() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.patch("/maintenance", json!({})).await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) = server.service.get("/maintenance").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "readOnly": false, "processing": false }));
+}
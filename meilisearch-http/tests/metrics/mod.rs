@@ -0,0 +1,48 @@
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+#[actix_rt::test]
+async fn metrics_are_disabled_by_default() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/metrics").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "feature_not_enabled");
+}
+
+#[actix_rt::test]
+async fn metrics() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        experimental_enable_metrics: true,
+        master_key: Some("MASTER_KEY".to_string()),
+        ..default_settings(temp.path())
+    };
+    let mut server = Server::new_with_options(options).await.unwrap();
+    server.use_api_key("MASTER_KEY");
+
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), None)
+        .await;
+    index.wait_task(0).await;
+    index.search_post(json!({ "q": "1" })).await;
+
+    let (metrics, code) = server.service.get_str("/metrics").await;
+    assert_eq!(code, 200, "{}", metrics);
+    assert!(metrics.contains(r#"meilisearch_index_documents{index="test"} 2"#));
+    assert!(metrics.contains("meilisearch_tasks_queued 0"));
+    assert!(metrics.contains(
+        r#"meilisearch_http_requests_total{method="POST",route="/indexes/{index_uid}/search",status="200"} 1"#
+    ));
+
+    // only the master key can read the metrics.
+    let (response, code) = server
+        .add_api_key(json!({ "actions": ["*"], "indexes": ["*"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    server.use_api_key(response["key"].as_str().unwrap());
+    let (_, code) = server.service.get_str("/metrics").await;
+    assert_eq!(code, 403);
+}
@@ -0,0 +1,70 @@
+use actix_web::test::TestRequest;
+use serde_json::Value;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn openapi_specification_is_served() {
+    let server = Server::new().await;
+
+    let (response, code, _) = server
+        .service
+        .request(TestRequest::get().uri("/openapi.json"))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["openapi"], "3.0.3");
+
+    let search = &response["paths"]["/indexes/{index_uid}/search"];
+    assert_eq!(search["post"]["x-meilisearch-action"], "search");
+    assert_eq!(search["parameters"][0]["name"], "index_uid");
+    assert_eq!(
+        search["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/SearchQuery"
+    );
+
+    let settings = &response["paths"]["/indexes/{index_uid}/settings/filterable-attributes"];
+    assert_eq!(settings["put"]["x-meilisearch-action"], "settings.update");
+
+    let health = &response["paths"]["/health"]["get"];
+    assert_eq!(health["security"], serde_json::json!([]));
+
+    let codes = response["components"]["schemas"]["Error"]["properties"]["code"]["enum"]
+        .as_array()
+        .unwrap();
+    assert!(codes.iter().any(|code| code == "index_not_found"));
+    assert!(codes.iter().any(|code| code == "read_only_mode"));
+}
+
+#[actix_rt::test]
+async fn openapi_paths_are_routed() {
+    let server = Server::new().await;
+
+    let (response, _, _) = server
+        .service
+        .request(TestRequest::get().uri("/openapi.json"))
+        .await;
+
+    for (path, item) in response["paths"].as_object().unwrap() {
+        if item.get("get").is_none() {
+            continue;
+        }
+
+        let uri: Vec<_> = path
+            .split('/')
+            .map(|segment| match segment {
+                "{task_id}" | "{batch_uid}" | "{document_id}" => "0",
+                segment if segment.starts_with('{') => "test",
+                segment => segment,
+            })
+            .collect();
+        let uri = uri.join("/");
+
+        // the unknown routes are answered with an empty 404 or 405, unlike the handlers.
+        let (body, code, _) = server.service.request(TestRequest::get().uri(&uri)).await;
+        assert!(
+            !(matches!(code.as_u16(), 404 | 405) && body == Value::Null),
+            "{} is not routed",
+            uri
+        );
+    }
+}
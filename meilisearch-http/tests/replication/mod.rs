@@ -0,0 +1,240 @@
+use std::net::TcpListener;
+use std::time::Duration;
+
+use actix_web::test::TestRequest;
+use actix_web::HttpServer;
+use meilisearch_http::{analytics, create_app};
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+#[actix_rt::test]
+async fn replication_is_disabled_by_default() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/replication/tasks").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn replication_log() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        replication_log_retention_sec: Some(3600),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("movies");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+    // the failed tasks are skipped.
+    server.index("books").delete().await;
+    index.wait_task(1).await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = server.service.get("/replication/tasks").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["lastBatchSeq"], 2);
+    let tasks = response["tasks"].as_array().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["uid"], 0);
+    assert_eq!(tasks[0]["batchSeq"], 0);
+    assert_eq!(tasks[1]["uid"], 2);
+    assert_eq!(tasks[1]["batchSeq"], 2);
+
+    // the update files of the processed document additions are kept for the followers.
+    let content_uuid = tasks[0]["content"]["DocumentAddition"]["content_uuid"]
+        .as_str()
+        .unwrap();
+    let (content, code) = server
+        .service
+        .get_str(format!("/replication/update-files/{}", content_uuid))
+        .await;
+    assert_eq!(code, 200, "{}", content);
+    let document: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+    assert_eq!(document, json!({ "id": 1, "title": "Carol" }));
+
+    let (response, code) = server
+        .service
+        .get("/replication/tasks?after=0&limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["lastBatchSeq"], 1);
+    assert!(response["tasks"].as_array().unwrap().is_empty());
+
+    let (response, code) = server.service.get("/replication/tasks?after=2").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["lastBatchSeq"], 2);
+    assert!(response["tasks"].as_array().unwrap().is_empty());
+
+    let (response, code) = server
+        .service
+        .get("/replication/update-files/ed3f2d6a-9fa2-4a2c-8ce6-2d2c6b0e2a3e")
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "update_file_not_found");
+}
+
+#[actix_rt::test]
+async fn replication_log_of_deleted_tasks() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        replication_log_retention_sec: Some(3600),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("movies");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index.wait_task(1).await;
+    let (response, code) = server.delete_tasks("uids=0").await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+
+    // the followers that didn't replay the deleted task can't catch up anymore.
+    let (response, code) = server.service.get("/replication/tasks").await;
+    assert_eq!(code, 410, "{}", response);
+    assert_eq!(response["code"], "replication_position_unavailable");
+
+    let (response, code) = server.service.get("/replication/tasks?after=0").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["lastBatchSeq"], 2);
+    let tasks = response["tasks"].as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["uid"], 1);
+}
+
+#[actix_rt::test]
+async fn replication_follows_the_processing_order() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        replication_log_retention_sec: Some(3600),
+        ..default_settings(temp.path())
+    };
+    let primary = Server::new_with_options(options).await.unwrap();
+
+    // the scheduler is paused while the tasks are registered, so the high priority one is
+    // processed before the one registered first.
+    let meilisearch = &primary.service.meilisearch;
+    meilisearch
+        .drain_tasks(Duration::from_secs(10))
+        .await
+        .unwrap();
+    primary
+        .index("movies")
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    let request = TestRequest::post()
+        .uri("/indexes/books/documents")
+        .insert_header(("X-Task-Priority", "high"))
+        .set_json(&json!([{ "id": 1, "title": "Dune" }]));
+    let (response, code, _) = primary.service.request(request).await;
+    assert_eq!(code, 202, "{}", response);
+    // restoring the read-write mode resumes the scheduler.
+    meilisearch.set_read_only(false).await;
+    primary.index("movies").wait_task(0).await;
+    primary.index("books").wait_task(1).await;
+
+    let (response, code) = primary.service.get("/replication/tasks").await;
+    assert_eq!(code, 200, "{}", response);
+    let uids: Vec<_> = response["tasks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["uid"].clone())
+        .collect();
+    assert_eq!(uids, vec![json!(1), json!(0)]);
+
+    // the follower replays the log of the primary served over HTTP.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let primary_url = format!("http://{}", listener.local_addr().unwrap());
+    let (auth, options) = (
+        primary.service.auth.clone(),
+        primary.service.options.clone(),
+    );
+    let meilisearch = meilisearch.clone();
+    let http_server = HttpServer::new(move || {
+        create_app!(
+            meilisearch,
+            auth,
+            false,
+            options,
+            analytics::MockAnalytics::new(&options).0
+        )
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_rt::spawn(http_server);
+
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        replica_of: Some(primary_url),
+        ..default_settings(temp.path())
+    };
+    let follower = Server::new_with_options(options).await.unwrap();
+    for _ in 0..100 {
+        let (response, _) = follower.tasks().await;
+        let results = response["results"].as_array().unwrap();
+        if results.len() == 2 && results.iter().all(|task| task["status"] == "succeeded") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // the follower registered the tasks in the order the primary processed them.
+    let (response, _) = follower.index("books").get_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["indexUid"], "books");
+    let (response, _) = follower.index("movies").get_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["indexUid"], "movies");
+
+    for (uid, title) in [("movies", "Carol"), ("books", "Dune")] {
+        let (response, code) = follower.index(uid).get_document(1, None).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["title"], title);
+    }
+}
+
+#[actix_rt::test]
+async fn replica_rejects_the_writes() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        replica_of: Some("http://127.0.0.1:1".to_string()),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("movies");
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    assert_eq!(code, 503, "{}", response);
+    assert_eq!(response["code"], "read_only_mode");
+    let (response, code) = index.create(None).await;
+    assert_eq!(code, 503, "{}", response);
+
+    // the reads and the searches are still accepted.
+    let (response, code) = server.service.get("/indexes").await;
+    assert_eq!(code, 200, "{}", response);
+    let (response, code) = server
+        .service
+        .post("/multi-search", json!({ "queries": [] }))
+        .await;
+    assert_ne!(code, 503, "{}", response);
+}
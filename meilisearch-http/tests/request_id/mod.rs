@@ -0,0 +1,54 @@
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn request_ids_are_generated() {
+    let server = Server::new().await;
+
+    let (_, code, headers) = server
+        .service
+        .request(TestRequest::get().uri("/health"))
+        .await;
+    assert_eq!(code, 200);
+    let first = headers.get("x-request-id").unwrap().to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(first).is_ok(), "{}", first);
+
+    let (_, _, headers) = server
+        .service
+        .request(TestRequest::get().uri("/health"))
+        .await;
+    assert_ne!(headers.get("x-request-id").unwrap(), first);
+
+    // the error responses carry it too.
+    let (_, code, headers) = server
+        .service
+        .request(TestRequest::get().uri("/indexes/unknown"))
+        .await;
+    assert_eq!(code, 404);
+    assert!(headers.contains_key("x-request-id"));
+}
+
+#[actix_rt::test]
+async fn request_ids_are_propagated_to_the_tasks() {
+    let server = Server::new().await;
+
+    let request = TestRequest::post()
+        .uri("/indexes")
+        .insert_header(("X-Request-Id", "client-42"))
+        .set_json(&json!({ "uid": "movies" }));
+    let (response, code, headers) = server.service.request(request).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(headers.get("x-request-id").unwrap(), "client-42");
+
+    let (task, _) = server.index("movies").get_task(0).await;
+    assert_eq!(task["requestId"], "client-42");
+
+    // an invalid id is replaced by a generated one.
+    let request = TestRequest::get()
+        .uri("/health")
+        .insert_header(("X-Request-Id", "not valid"));
+    let (_, _, headers) = server.service.request(request).await;
+    assert_ne!(headers.get("x-request-id").unwrap(), "not valid");
+}
@@ -0,0 +1,111 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "Shazam!", "genres": ["action", "comedy"] },
+        { "id": 2, "title": "Captain Marvel", "genres": ["action", "science fiction"] },
+        { "id": 3, "title": "Escape Room", "genres": ["horror", "thriller"] },
+        { "id": 4, "title": "How to Train Your Dragon", "genres": ["animation", "adventure"] },
+        { "id": 5, "title": "Glass", "genres": ["thriller", "science fiction"] },
+    ])
+});
+
+#[actix_rt::test]
+async fn simple_facet_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["genres"] }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "genres", "facetQuery": "a" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "action", "count": 2 },
+            { "value": "adventure", "count": 1 },
+            { "value": "animation", "count": 1 },
+        ])
+    );
+    assert_eq!(response["facetQuery"], "a");
+}
+
+#[actix_rt::test]
+async fn facet_search_with_typo() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["genres"] }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "genres", "facetQuery": "thriler" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "thriller", "count": 2 }])
+    );
+}
+
+#[actix_rt::test]
+async fn facet_search_with_query_and_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["genres", "id"] }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "genres", "q": "glass" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "science fiction", "count": 1 },
+            { "value": "thriller", "count": 1 },
+        ])
+    );
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "genres", "facetQuery": "sci", "filter": "id < 3" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "science fiction", "count": 1 }])
+    );
+}
+
+#[actix_rt::test]
+async fn facet_search_not_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["genres"] }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "title", "facetQuery": "a" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_facet_search_facet_name");
+}
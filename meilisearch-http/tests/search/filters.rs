@@ -0,0 +1,254 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "genre": "horror", "release_date": 1999, "description": "A haunted house" },
+        { "id": 2, "genre": "comedy", "release_date": null, "description": "" },
+        { "id": 3, "genre": "drama", "description": null },
+        { "id": 4, "genre": ["horror", "drama"], "release_date": 2010, "description": [] },
+        { "id": 5, "release_date": 2022 },
+    ])
+});
+
+fn ids(response: &Value) -> Vec<u64> {
+    let mut ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+async fn server() -> Server {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(
+            json!({ "filterableAttributes": ["genre", "release_date", "description"] }),
+        )
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+    server
+}
+
+#[actix_rt::test]
+async fn filter_in() {
+    let server = server().await;
+    let index = server.index("test");
+
+    for (filter, expected) in [
+        (json!("genre IN [horror, comedy]"), vec![1, 2, 4]),
+        (json!("genre in ['horror', \"comedy\"]"), vec![1, 2, 4]),
+        (json!("genre NOT IN [horror, comedy]"), vec![3, 5]),
+        (json!("genre IN [drama] AND release_date > 2000"), vec![4]),
+        (
+            json!(["genre IN [comedy]", "release_date IS NULL"]),
+            vec![2],
+        ),
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), expected, "{}", filter);
+            })
+            .await;
+    }
+}
+
+#[actix_rt::test]
+async fn filter_exists_null_and_empty() {
+    let server = server().await;
+    let index = server.index("test");
+
+    for (filter, expected) in [
+        ("release_date EXISTS", vec![1, 2, 4, 5]),
+        ("release_date NOT EXISTS", vec![3]),
+        ("release_date IS NULL", vec![2]),
+        ("release_date IS NOT NULL", vec![1, 3, 4, 5]),
+        ("description IS EMPTY", vec![2, 4]),
+        ("description IS NOT EMPTY", vec![1, 3, 5]),
+        ("description IS NULL OR genre = comedy", vec![2, 3]),
+        ("NOT description EXISTS", vec![5]),
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), expected, "{}", filter);
+            })
+            .await;
+    }
+}
+
+#[actix_rt::test]
+async fn filter_operators_on_other_routes() {
+    let server = server().await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .fetch_documents(json!({ "filter": "release_date NOT EXISTS" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["results"],
+        json!([{ "id": 3, "genre": "drama", "description": null }])
+    );
+
+    let (response, code) = index
+        .delete_documents_by_filter(json!({ "filter": "genre IN [horror, comedy]" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+
+    index
+        .search(
+            json!({ "filter": "description EXISTS" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![3]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn filter_operators_on_non_filterable_attribute() {
+    let server = server().await;
+    let index = server.index("test");
+
+    index
+        .search(json!({ "filter": "title EXISTS" }), |response, code| {
+            assert_eq!(code, 400, "{}", response);
+            assert_eq!(
+                response["message"],
+                "Attribute `title` is not filterable. Available filterable attributes are: `description, genre, release_date`."
+            );
+            assert_eq!(response["code"], "invalid_filter");
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn filter_contains_and_starts_with() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["sku", "path"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "sku": "ABC-001", "path": "/admin/users" },
+                { "id": 2, "sku": "abc-002", "path": "/home/admin" },
+                { "id": 3, "sku": "XABC-003", "path": ["/docs", "/site/admin/panel"] },
+                { "id": 4, "sku": "DEF-004", "path": "/ad" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    for (filter, expected) in [
+        ("sku STARTS WITH \"ABC-\"", vec![1, 2]),
+        ("sku NOT STARTS WITH 'abc'", vec![3, 4]),
+        ("path CONTAINS \"/admin/\"", vec![1, 3]),
+        ("path contains ADMIN", vec![1, 2, 3]),
+        ("path CONTAINS ad AND sku STARTS WITH def", vec![4]),
+        ("path CONTAINS /nowhere", vec![]),
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), expected, "{}", filter);
+            })
+            .await;
+    }
+
+    index.delete_document(1).await;
+    index.wait_task(2).await;
+    index
+        .search(
+            json!({ "filter": "path CONTAINS \"/admin/\"" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![3]);
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "filter": "id CONTAINS 1" }), |response, code| {
+            assert_eq!(code, 400, "{}", response);
+            assert_eq!(response["code"], "invalid_filter");
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn filter_geo_bounding_box_and_polygon() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["_geo", "genre"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "genre": "pizza", "_geo": { "lat": 45.4777599, "lng": 9.1967508 } },
+                { "id": 2, "genre": "pizza", "_geo": { "lat": 45.4632046, "lng": 9.1719421 } },
+                { "id": 3, "genre": "sushi", "_geo": { "lat": 45.4687, "lng": 9.1893 } },
+                { "id": 4, "genre": "pizza", "_geo": { "lat": 48.8566, "lng": 2.3522 } },
+                { "id": 5, "genre": "pizza" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    for (filter, expected) in [
+        (
+            "_geoBoundingBox([45.49, 9.20], [45.46, 9.17])",
+            vec![1, 2, 3],
+        ),
+        ("_geoBoundingBox([45.465, 9.19], [45.46, 9.17])", vec![2]),
+        (
+            "_geoBoundingBox([45.49, 9.20], [45.46, 9.17]) AND genre = pizza",
+            vec![1, 2],
+        ),
+        (
+            "NOT _geoBoundingBox([45.49, 9.20], [45.46, 9.17])",
+            vec![4, 5],
+        ),
+        (
+            "_geoPolygon([45.48, 9.19], [45.48, 9.20], [45.46, 9.20], [45.46, 9.17])",
+            vec![1, 3],
+        ),
+        ("_geoPolygon([49, 2], [49, 3], [48, 3])", vec![4]),
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), expected, "{}", filter);
+            })
+            .await;
+    }
+
+    for filter in [
+        "_geoBoundingBox([45.49, 9.20])",
+        "_geoPolygon([45.48, 9.19], [45.48, 9.20])",
+        "_geoBoundingBox([91, 9.20], [45.46, 9.17])",
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_filter");
+            })
+            .await;
+    }
+}
@@ -2,9 +2,17 @@
 // should be tested in its own module to isolate tests and keep the tests readable.
 
 mod errors;
+mod facet_search;
+mod filters;
 mod formatted;
-
-use crate::common::Server;
+mod multi;
+mod pagination;
+mod similar;
+mod suggest;
+mod vector;
+
+use crate::common::{default_settings, Server};
+use meilisearch_http::Opt;
 use once_cell::sync::Lazy;
 use serde_json::{json, Value};
 
@@ -510,6 +518,122 @@ async fn search_facet_distribution() {
         .await;
 }
 
+#[actix_rt::test]
+async fn search_facet_stats() {
+    let server = Server::new().await;
+    let index = server.index("nested");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["father", "doggos.age"]}))
+        .await;
+
+    let documents = NESTED_DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({
+                "facets": ["father", "doggos.age"]
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                // only the numeric facets have statistics.
+                assert_eq!(
+                    response["facetStats"],
+                    json!({ "doggos.age": { "min": 2.0, "max": 8.0 } })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({
+                "filter": "doggos.age < 5",
+                "facets": ["doggos.age"]
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetStats"],
+                    json!({ "doggos.age": { "min": 2.0, "max": 4.0 } })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "q": "jean" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("facetStats").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_sort_facet_values_by() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["genre", "color"],
+            "faceting": { "maxValuesPerFacet": 2, "sortFacetValuesBy": { "genre": "count" } },
+        }))
+        .await;
+    let documents = json!([
+        { "id": 1, "genre": "drama", "color": "red" },
+        { "id": 2, "genre": "horror", "color": "blue" },
+        { "id": 3, "genre": "horror", "color": "green" },
+        { "id": 4, "genre": "thriller", "color": "green" },
+        { "id": 5, "genre": "thriller", "color": "blue" },
+        { "id": 6, "genre": "thriller", "color": "red" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (settings, _) = index.settings().await;
+    assert_eq!(
+        settings["faceting"],
+        json!({ "maxValuesPerFacet": 2, "sortFacetValuesBy": { "genre": "count" } })
+    );
+
+    let (response, code) = index
+        .search_post(json!({ "facets": ["genre", "color"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    // the keys are compared in order.
+    assert_eq!(
+        response["facetDistribution"]["genre"].to_string(),
+        r#"{"thriller":3,"horror":2}"#
+    );
+    assert_eq!(
+        response["facetDistribution"]["color"].to_string(),
+        r#"{"blue":2,"green":2}"#
+    );
+
+    index
+        .update_settings(
+            json!({ "faceting": { "sortFacetValuesBy": { "*": "count", "genre": "alpha" } } }),
+        )
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = index
+        .search_post(json!({ "facets": ["genre", "color"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetDistribution"]["genre"].to_string(),
+        r#"{"drama":1,"horror":2}"#
+    );
+    assert_eq!(
+        response["facetDistribution"]["color"].to_string(),
+        r#"{"blue":2,"green":2}"#
+    );
+}
+
 #[actix_rt::test]
 async fn displayed_attributes() {
     let server = Server::new().await;
@@ -716,3 +840,593 @@ async fn faceting_max_values_per_facet() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn search_with_matching_strategy() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({"q": "captain xyz", "matchingStrategy": "last"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "captain xyz", "matchingStrategy": "all"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "captain marvel", "matchingStrategy": "all"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_frequency_matching_strategy() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "the dragon" },
+        { "id": 2, "title": "the captain" },
+        { "id": 3, "title": "the glass" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    // the last strategy drops `glass` then `dragon`, leaving `the` which matches everything.
+    index
+        .search(
+            json!({"q": "the dragon glass", "matchingStrategy": "last"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+            },
+        )
+        .await;
+
+    // the frequency strategy drops `glass`, which is still being typed, then `the`, the most
+    // frequent word, and never `dragon`, the rarest one.
+    index
+        .search(
+            json!({"q": "the dragon glass", "matchingStrategy": "frequency"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let hits = response["hits"].as_array().unwrap();
+                assert_eq!(hits.len(), 1);
+                assert_eq!(hits[0]["id"], 1);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_attributes_to_search_on() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "the lord of the rings", "author": "tolkien", "details": { "tags": ["fantasy"] } },
+        { "id": 2, "title": "tolkien, a biography", "author": "carpenter", "details": { "tags": ["biography"] } },
+        { "id": 3, "title": "the hobbit", "author": "tolkien", "details": { "tags": ["fantasy"] } },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"q": "tolkien"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "tolkien", "attributesToSearchOn": ["title"], "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([{ "id": 2 }]));
+                assert_eq!(response["estimatedTotalHits"], 1);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "fantasy", "attributesToSearchOn": ["details"], "attributesToRetrieve": ["id"], "limit": 1, "offset": 1}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["estimatedTotalHits"], 2);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "carpenter", "attributesToSearchOn": ["title"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_attributes_to_search_on_beyond_the_pagination_limit() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "pagination": { "limitedTo": 1 } }))
+        .await;
+    let documents = json!([
+        { "id": 1, "title": "the lord of the rings", "author": "tolkien" },
+        { "id": 2, "title": "tolkien, a biography", "author": "carpenter" },
+        { "id": 3, "title": "the hobbit", "author": "tolkien" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    // the document the engine ranks first doesn't match in the author, the restriction applies
+    // to all the matching documents.
+    index
+        .search(
+            json!({"q": "tolkien", "attributesToSearchOn": ["author"], "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let hits = response["hits"].as_array().unwrap();
+                assert_eq!(hits.len(), 1);
+                assert_ne!(hits[0]["id"], 2);
+                assert_eq!(response["estimatedTotalHits"], 2);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_attributes_to_search_on_not_searchable() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({"q": "glass", "attributesToSearchOn": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_search_attributes_to_search_on");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_distinct() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "product": "shirt", "color": "red" },
+        { "id": 2, "product": "shirt", "color": "blue" },
+        { "id": 3, "product": "pants", "color": "red" },
+        { "id": 4, "product": "shirt", "color": "green" },
+        { "id": 5, "color": "blue" },
+    ]);
+    index
+        .update_settings(json!({ "filterableAttributes": ["product", "color"] }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({"distinct": "product", "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["hits"],
+                    json!([{ "id": 1 }, { "id": 3 }, { "id": 5 }])
+                );
+                assert_eq!(response["estimatedTotalHits"], 3);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"distinct": "color", "filter": "product = shirt", "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["hits"],
+                    json!([{ "id": 1 }, { "id": 2 }, { "id": 4 }])
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(json!({"distinct": "id"}), |response, code| {
+            assert_eq!(code, 400, "{}", response);
+            assert_eq!(response["code"], "invalid_search_distinct");
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_cutoff() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"q": "glass"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert!(response.get("degraded").is_none());
+        })
+        .await;
+
+    // a cutoff of zero is reached as soon as the engine ranked the documents, the hits it
+    // ranked are still returned.
+    index.update_settings(json!({ "searchCutoffMs": 0 })).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({"q": "glass"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(
+                response["hits"],
+                json!([{ "title": "Glass", "id": "450465" }])
+            );
+            assert_eq!(response["degraded"], true);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "glass", "attributeWeights": { "title": 2.0 }, "showSuggestions": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["degraded"], true);
+                assert!(response.get("suggestions").is_none());
+            },
+        )
+        .await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchCutoffMs"], 0);
+}
+
+#[actix_rt::test]
+async fn search_with_suggestions() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({"q": "capcain marvel", "showSuggestions": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["suggestions"], json!(["captain marvel"]));
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "captain marvel", "showSuggestions": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["suggestions"], json!([]));
+            },
+        )
+        .await;
+
+    index
+        .search(json!({"q": "capcain marvel"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("suggestions").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_negated_terms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"q": "-shazam -Room"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["query"], "-shazam -Room");
+            assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+            assert_eq!(response["estimatedTotalHits"], 3);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "the -\"hidden world\"", "hitsPerPage": 10}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([]));
+                assert_eq!(response["totalHits"], 0);
+            },
+        )
+        .await;
+
+    // the words of a negated phrase must follow each other.
+    index
+        .search(
+            json!({"q": "dragon -\"world hidden\""}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"][0]["id"], "166428");
+            },
+        )
+        .await;
+
+    index
+        .search(json!({"q": "captain -marvel"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_ignores_the_expired_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"expiryAttribute": "expiresAt"}))
+        .await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "id": 1, "title": "fresh milk", "expiresAt": "2100-01-01T00:00:00Z" },
+        { "id": 2, "title": "sour milk", "expiresAt": "2000-01-01T00:00:00+02:00" },
+        { "id": 3, "title": "old milk", "expiresAt": 946684800 },
+        { "id": 4, "title": "powdered milk" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({"q": "milk", "hitsPerPage": 10}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let mut ids: Vec<_> = response["hits"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|hit| hit["id"].as_u64().unwrap())
+                .collect();
+            ids.sort_unstable();
+            assert_eq!(ids, vec![1, 4]);
+            assert_eq!(response["totalHits"], 2);
+        })
+        .await;
+
+    // the expired documents are still stored until they are swept.
+    let (_, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200);
+
+    // without an expiry attribute, no document expires.
+    index
+        .update_settings(json!({"expiryAttribute": null}))
+        .await;
+    index.wait_task(2).await;
+
+    index
+        .search(json!({"q": "milk"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 4);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_attribute_weights() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Batman Begins", "overview": "The dark knight rises" },
+        { "id": 2, "title": "The Dark Knight", "overview": "Batman fights the joker" },
+    ]);
+    index
+        .update_settings(json!({ "searchableAttributes": ["title", "overview"] }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({"q": "dark knight", "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([{ "id": 2 }, { "id": 1 }]));
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "dark knight", "attributeWeights": { "title": 0.5, "overview": 2 }, "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([{ "id": 1 }, { "id": 2 }]));
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "dark", "attributeWeights": { "id": 2 }}),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_search_attribute_weights");
+                assert_eq!(
+                    response["message"],
+                    "Invalid attribute weights: attribute `id` is not searchable. Available searchable attributes are: `title, overview`."
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "dark", "attributeWeights": { "title": -1 }}),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_search_attribute_weights");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_ranking_profile() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Carol", "price": 10 },
+        { "id": 2, "title": "Wonder Woman", "price": 5 },
+        { "id": 3, "title": "Carol of the Bells", "price": 20 },
+    ]);
+    index
+        .update_settings(json!({
+            "sortableAttributes": ["price"],
+            "rankingProfiles": {
+                "cheap": { "sort": ["price:asc"] },
+                "expensive": { "sort": ["price:desc"] },
+            },
+        }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (settings, _) = index.settings().await;
+    assert_eq!(
+        settings["rankingProfiles"],
+        json!({
+            "cheap": { "sort": ["price:asc"] },
+            "expensive": { "sort": ["price:desc"] },
+        })
+    );
+
+    index
+        .search(
+            json!({"rankingProfile": "cheap", "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["hits"],
+                    json!([{ "id": 2 }, { "id": 1 }, { "id": 3 }])
+                );
+            },
+        )
+        .await;
+
+    // the profile replaces the sort of the query.
+    index
+        .search(
+            json!({"q": "carol", "sort": ["price:asc"], "rankingProfile": "expensive", "attributesToRetrieve": ["id"]}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([{ "id": 3 }, { "id": 1 }]));
+            },
+        )
+        .await;
+
+    index
+        .search(json!({"rankingProfile": "experiment-b"}), |response, code| {
+            assert_eq!(code, 400, "{}", response);
+            assert_eq!(response["code"], "invalid_search_ranking_profile");
+            assert_eq!(
+                response["message"],
+                "Ranking profile `experiment-b` not found. Available ranking profiles are: `cheap, expensive`."
+            );
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_cache_is_invalidated_by_the_tasks() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        search_cache_size: Some(10),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    for _ in 0..2 {
+        let (response, code) = index.search_post(json!({"q": "glass"})).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    }
+
+    index
+        .add_documents(json!([{ "id": "1", "title": "Glass Onion" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({"q": "glass"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    index.delete().await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.search_post(json!({"q": "glass"})).await;
+    assert_eq!(code, 404, "{}", response);
+}
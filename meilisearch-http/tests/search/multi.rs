@@ -0,0 +1,172 @@
+use super::*;
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn multi_search_empty_list() {
+    let server = Server::new().await;
+
+    let (response, code) = server.multi_search(json!({ "queries": [] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "results": [] }));
+}
+
+#[actix_rt::test]
+async fn multi_search_several_indexes() {
+    let server = Server::new().await;
+
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let nested_index = server.index("nested");
+    nested_index
+        .add_documents(NESTED_DOCUMENTS.clone(), None)
+        .await;
+    nested_index.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({ "queries": [
+            { "indexUid": "test", "q": "glass" },
+            { "indexUid": "nested", "q": "pesti" },
+            { "indexUid": "test", "q": "captain", "limit": 1 },
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+
+    assert_eq!(results[0]["indexUid"], "test");
+    assert_eq!(results[0]["query"], "glass");
+    assert_eq!(results[0]["hits"][0]["id"], "450465");
+
+    assert_eq!(results[1]["indexUid"], "nested");
+    assert_eq!(results[1]["query"], "pesti");
+    assert_eq!(results[1]["hits"].as_array().unwrap().len(), 2);
+
+    assert_eq!(results[2]["indexUid"], "test");
+    assert_eq!(results[2]["limit"], 1);
+    assert_eq!(results[2]["hits"][0]["id"], "299537");
+}
+
+#[actix_rt::test]
+async fn multi_search_missing_index() {
+    let server = Server::new().await;
+
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .multi_search(json!({ "queries": [
+            { "indexUid": "test", "q": "glass" },
+            { "indexUid": "unknown", "q": "glass" },
+        ]}))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn federated_search_merges_hits() {
+    let server = Server::new().await;
+
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let nested_index = server.index("nested");
+    nested_index
+        .add_documents(NESTED_DOCUMENTS.clone(), None)
+        .await;
+    nested_index.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({
+            "federation": { "limit": 3 },
+            "queries": [
+                { "indexUid": "test", "q": "glass" },
+                { "indexUid": "nested", "q": "pesti", "federationOptions": { "weight": 2.0 } },
+            ]
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 3);
+    assert_eq!(response["limit"], 3);
+    assert_eq!(response["offset"], 0);
+    assert_eq!(response["estimatedTotalHits"], 3);
+
+    // the weight of the second query puts its hits first.
+    assert_eq!(hits[0]["_federation"]["indexUid"], "nested");
+    assert_eq!(hits[0]["_federation"]["queriesPosition"], 1);
+    assert_eq!(hits[1]["_federation"]["indexUid"], "nested");
+    assert_eq!(hits[2]["_federation"]["indexUid"], "test");
+    assert_eq!(hits[2]["id"], "450465");
+}
+
+#[actix_rt::test]
+async fn federated_search_pagination() {
+    let server = Server::new().await;
+
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .multi_search(json!({
+            "federation": { "offset": 1, "limit": 1 },
+            "queries": [
+                { "indexUid": "test", "q": "glass" },
+                { "indexUid": "test", "q": "captain" },
+            ]
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["id"], "299537");
+    assert_eq!(hits[0]["_federation"]["queriesPosition"], 1);
+}
+
+#[actix_rt::test]
+async fn federated_search_invalid_weight() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .multi_search(json!({
+            "federation": {},
+            "queries": [
+                { "indexUid": "test", "q": "glass", "federationOptions": { "weight": -1.0 } },
+            ]
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn federated_search_rejects_the_pagination_of_the_queries() {
+    let server = Server::new().await;
+
+    for parameter in ["offset", "limit", "page", "hitsPerPage"] {
+        let mut query = json!({ "indexUid": "test", "q": "captain" });
+        query[parameter] = json!(1);
+        let (response, code) = server
+            .multi_search(json!({
+                "federation": {},
+                "queries": [{ "indexUid": "test", "q": "glass" }, query]
+            }))
+            .await;
+        assert_eq!(code, 400, "{}", response);
+        assert_eq!(response["code"], "bad_request");
+        assert!(
+            response["message"].as_str().unwrap().contains(parameter),
+            "{}",
+            response
+        );
+    }
+}
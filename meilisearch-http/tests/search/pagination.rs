@@ -0,0 +1,137 @@
+use serde_json::json;
+
+use super::DOCUMENTS;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_with_hits_per_page_and_page() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"hitsPerPage": 2, "page": 1}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            assert_eq!(response["hitsPerPage"], 2);
+            assert_eq!(response["page"], 1);
+            assert_eq!(response["totalHits"], 5);
+            assert_eq!(response["totalPages"], 3);
+            assert!(response.get("estimatedTotalHits").is_none());
+            assert!(response.get("limit").is_none());
+            assert!(response.get("offset").is_none());
+        })
+        .await;
+
+    index
+        .search(json!({"hitsPerPage": 2, "page": 3}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert_eq!(response["page"], 3);
+        })
+        .await;
+
+    index
+        .search(json!({"hitsPerPage": 2, "page": 4}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_zero_or_no_hits_per_page() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"page": 0}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalHits"], 5);
+        })
+        .await;
+
+    index
+        .search(json!({"hitsPerPage": 0}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["page"], 1);
+            assert_eq!(response["totalHits"], 5);
+            assert_eq!(response["totalPages"], 0);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_counts_matching_documents_exhaustively() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({"q": "the", "hitsPerPage": 1, "matchingStrategy": "all"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["totalHits"], 1);
+                assert_eq!(response["totalPages"], 1);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_bounds_total_hits_by_max_total_hits() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 3 } }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(
+        response["pagination"],
+        json!({ "limitedTo": 1000, "maxTotalHits": 3 })
+    );
+
+    index
+        .search(json!({"hitsPerPage": 2, "page": 1}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["totalHits"], 3);
+            assert_eq!(response["totalPages"], 2);
+        })
+        .await;
+
+    let (response, code) = index
+        .update_settings(json!({ "pagination": { "maxTotalHits": null } }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+
+    index
+        .search(json!({"hitsPerPage": 2, "page": 1}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["totalHits"], 5);
+        })
+        .await;
+}
@@ -0,0 +1,87 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn similar_documents_by_terms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Harry Potter and the Sorcerer's Stone", "genre": "fantasy" },
+        { "id": 2, "title": "Harry Potter and the Chamber of Secrets", "genre": "fantasy" },
+        { "id": 3, "title": "The Lord of the Rings", "genre": "fantasy" },
+        { "id": 4, "title": "Harold and the Purple Crayon", "genre": "children" },
+    ]);
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server.service.get("/indexes/test/similar/1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["id"], "1");
+    assert_eq!(response["hits"][0]["id"], 2);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+    assert_eq!(response["estimatedTotalHits"], 3);
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/similar/1?filter=genre%20%3D%20children&attributesToRetrieve=title")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([{ "title": "Harold and the Purple Crayon" }])
+    );
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/similar/1?limit=1&offset=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_ne!(response["hits"][0]["id"], 2);
+    assert_eq!(response["limit"], 1);
+    assert_eq!(response["offset"], 1);
+}
+
+#[actix_rt::test]
+async fn similar_documents_by_vectors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "kefir", "_vectors": [1.0, 0.0, 0.0] },
+        { "id": 2, "title": "intel", "_vectors": [[0.0, 1.0, 0.0], [0.7, 0.7, 0.0]] },
+        { "id": 3, "title": "echo", "_vectors": [0.0, 0.0, 1.0] },
+        { "id": 4, "title": "patou" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/similar/1?attributesToRetrieve=id")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    // the documents without an embedding are left out.
+    assert_eq!(response["hits"], json!([{ "id": 2 }, { "id": 3 }]));
+}
+
+#[actix_rt::test]
+async fn similar_documents_of_a_missing_document() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/indexes/test/similar/42").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "document_not_found");
+
+    let (response, code) = server.service.get("/indexes/unknown/similar/1").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
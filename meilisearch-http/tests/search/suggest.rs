@@ -0,0 +1,63 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn suggest_completes_the_last_word() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Harry Potter and the Sorcerer's Stone", "genre": "fantasy" },
+        { "id": 2, "title": "Harry Potter and the Chamber of Secrets", "genre": "fantasy" },
+        { "id": 3, "title": "Harold and the Purple Crayon", "genre": "children" },
+        { "id": 4, "title": "The Hare with Amber Eyes", "genre": "biography" },
+    ]);
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.suggest(json!({ "q": "har" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([
+            { "suggestion": "harry", "count": 2 },
+            { "suggestion": "hare", "count": 1 },
+            { "suggestion": "harold", "count": 1 },
+        ])
+    );
+    assert_eq!(response["query"], "har");
+
+    let (response, code) = index.suggest(json!({ "q": "harry po" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([{ "suggestion": "harry potter", "count": 2 }])
+    );
+
+    let (response, code) = index
+        .suggest(json!({ "q": "har", "filter": "genre = children", "limit": 1 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([{ "suggestion": "harold", "count": 1 }])
+    );
+
+    let (response, code) = index.suggest(json!({ "q": "" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["suggestions"], json!([]));
+}
+
+#[actix_rt::test]
+async fn error_suggest_unexisting_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index.suggest(json!({ "q": "har" })).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
@@ -0,0 +1,215 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "kefir", "color": "white", "_vectors": [1.0, 0.0, 0.0] },
+        { "id": 2, "title": "intel", "color": "black", "_vectors": [[0.0, 1.0, 0.0], [0.7, 0.7, 0.0]] },
+        { "id": 3, "title": "echo", "color": "white", "_vectors": [0.0, 0.0, 1.0] },
+        { "id": 4, "title": "patou", "color": "black" },
+    ])
+});
+
+#[actix_rt::test]
+async fn search_with_vector() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [0.0, 0.1, 1.0], "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([{ "id": 3 }, { "id": 2 }, { "id": 1 }, { "id": 4 }])
+    );
+    assert_eq!(response["estimatedTotalHits"], 4);
+
+    let (response, code) = index
+        .search_post(
+            json!({ "vector": [1.0, 0.9, 0.0], "limit": 2, "attributesToRetrieve": ["id"] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 2 }, { "id": 1 }]));
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.9, 0.0], "offset": 1, "limit": 1, "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1 }]));
+}
+
+#[actix_rt::test]
+async fn search_with_vector_and_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["color"] }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0], "filter": "color = black", "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 2 }, { "id": 4 }]));
+    assert_eq!(response["estimatedTotalHits"], 2);
+}
+
+#[actix_rt::test]
+async fn hybrid_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    // only the keyword ranking is used.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "echo",
+            "vector": [1.0, 0.0, 0.0],
+            "hybrid": { "semanticRatio": 0.0 },
+            "attributesToRetrieve": ["id"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0], json!({ "id": 3 }));
+
+    // only the vector ranking is used, the semantic results are not restricted by the query.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "echo",
+            "vector": [1.0, 0.0, 0.0],
+            "hybrid": { "semanticRatio": 1.0 },
+            "attributesToRetrieve": ["id"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0], json!({ "id": 1 }));
+    assert_eq!(response["estimatedTotalHits"], 4);
+
+    // a blend of both rankings.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "echo",
+            "vector": [1.0, 0.0, 0.0],
+            "attributesToRetrieve": ["id"],
+            "hybrid": {},
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0], json!({ "id": 3 }));
+    assert_eq!(response["hits"][1], json!({ "id": 1 }));
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "echo",
+            "vector": [1.0, 0.0, 0.0],
+            "attributesToRetrieve": ["id"],
+            "hybrid": { "semanticRatio": 0.8 },
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }])
+    );
+}
+
+#[actix_rt::test]
+async fn hybrid_search_bad_parameters() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "echo", "hybrid": { "semanticRatio": 0.5 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_hybrid_query");
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0], "hybrid": { "semanticRatio": 1.5 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_hybrid_query");
+}
+
+#[actix_rt::test]
+async fn vectors_are_neither_searchable_nor_returned() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "q": "0.7" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [0.0, 0.0, 1.0], "limit": 1 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([{ "id": 3, "title": "echo", "color": "white" }])
+    );
+}
+
+#[actix_rt::test]
+async fn vectors_follow_the_updates_of_the_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    // a partial update without `_vectors` keeps the embeddings of the document.
+    index
+        .update_documents(json!([{ "id": 3, "title": "echoes" }]), None)
+        .await;
+    index.wait_task(1).await;
+    let (response, code) = index
+        .search_post(
+            json!({ "vector": [0.0, 0.0, 1.0], "limit": 1, "attributesToRetrieve": ["id"] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 3 }]));
+
+    // a replacement without `_vectors` forgets them.
+    index
+        .add_documents(json!([{ "id": 3, "title": "echo" }]), None)
+        .await;
+    index.wait_task(2).await;
+    // the document added in place of a deleted one doesn't get its embeddings.
+    index.delete_document(1).await;
+    index.wait_task(3).await;
+    index
+        .add_documents(json!([{ "id": 5, "title": "kefir" }]), None)
+        .await;
+    index.wait_task(4).await;
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [0.0, 0.0, 1.0], "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0], json!({ "id": 2 }));
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0], "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0], json!({ "id": 2 }));
+    assert_eq!(response["estimatedTotalHits"], 4);
+}
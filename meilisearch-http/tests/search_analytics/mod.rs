@@ -0,0 +1,121 @@
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+#[actix_rt::test]
+async fn search_analytics_are_disabled_by_default() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/indexes/movies/analytics").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "feature_not_enabled");
+}
+
+#[actix_rt::test]
+async fn search_analytics() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        experimental_enable_search_analytics: true,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("movies");
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol" },
+                { "id": 2, "title": "Wonder Woman" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    for q in ["carol", "Wonder  woman", "carol", "cinderella", "", "carol"] {
+        index.search_post(json!({ "q": q })).await;
+    }
+
+    let (response, code) = server.service.get("/indexes/movies/analytics").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalSearches"], 6);
+    assert_eq!(response["zeroResultSearches"], 1);
+    assert_eq!(
+        response["topQueries"],
+        json!([
+            { "query": "carol", "count": 3 },
+            { "query": "cinderella", "count": 1 },
+            { "query": "wonder woman", "count": 1 },
+        ])
+    );
+    assert_eq!(
+        response["zeroResultQueries"],
+        json!([{ "query": "cinderella", "count": 1 }])
+    );
+
+    let (response, code) = server
+        .service
+        .get("/indexes/movies/analytics?limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["topQueries"],
+        json!([{ "query": "carol", "count": 3 }])
+    );
+
+    // the searches made before the window aren't counted.
+    let (response, code) = server
+        .service
+        .get("/indexes/movies/analytics?from=2000-01-01T00:00:00Z&to=2000-01-02T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalSearches"], 0);
+    assert_eq!(response["averageProcessingTimeMs"], json!(null));
+
+    assert_eq!(response["profiles"], json!({}));
+
+    let (response, code) = server.service.get("/indexes/books/analytics").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn search_analytics_by_ranking_profile() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        experimental_enable_search_analytics: true,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("movies");
+    index
+        .update_settings(json!({
+            "rankingProfiles": {
+                "strict": { "matchingStrategy": "all" },
+                "loose": { "matchingStrategy": "last" },
+            },
+        }))
+        .await;
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    index
+        .search_post(json!({ "q": "carol bells", "rankingProfile": "strict" }))
+        .await;
+    index
+        .search_post(json!({ "q": "carol bells", "rankingProfile": "loose" }))
+        .await;
+    index.search_post(json!({ "q": "carol" })).await;
+
+    let (response, code) = server.service.get("/indexes/movies/analytics").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalSearches"], 3);
+    assert_eq!(response["profiles"]["strict"]["totalSearches"], 1);
+    assert_eq!(response["profiles"]["strict"]["zeroResultSearches"], 1);
+    assert_eq!(response["profiles"]["loose"]["totalSearches"], 1);
+    assert_eq!(response["profiles"]["loose"]["zeroResultSearches"], 0);
+    assert_eq!(response["profiles"].as_object().unwrap().len(), 2);
+}
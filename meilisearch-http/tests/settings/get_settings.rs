@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use actix_web::test::TestRequest;
 use once_cell::sync::Lazy;
 use serde_json::{json, Value};
 
@@ -30,6 +31,10 @@ static DEFAULT_SETTINGS_VALUES: Lazy<HashMap<&'static str, Value>> = Lazy::new(|
             "maxValuesByFacet": json!(100),
         }),
     );
+    map.insert("search_cutoff_ms", json!(Value::Null));
+    map.insert("ranking_profiles", json!({}));
+    map.insert("expiry_attribute", json!(Value::Null));
+    map.insert("version_attribute", json!(Value::Null));
     map
 });
 
@@ -49,7 +54,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 11);
+    assert_eq!(settings.keys().len(), 15);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -71,14 +76,20 @@ async fn get_settings() {
         settings["faceting"],
         json!({
             "maxValuesPerFacet": 100,
+            "sortFacetValuesBy": { "*": "alpha" },
         })
     );
     assert_eq!(
         settings["pagination"],
         json!({
             "limitedTo": 1000,
+            "maxTotalHits": null,
         })
     );
+    assert_eq!(settings["searchCutoffMs"], json!(null));
+    assert_eq!(settings["rankingProfiles"], json!({}));
+    assert_eq!(settings["expiryAttribute"], json!(null));
+    assert_eq!(settings["versionAttribute"], json!(null));
 }
 
 #[actix_rt::test]
@@ -282,7 +293,11 @@ test_setting_routes!(
     distinct_attribute,
     stop_words,
     ranking_rules,
-    synonyms
+    synonyms,
+    search_cutoff_ms,
+    ranking_profiles,
+    expiry_attribute,
+    version_attribute
 );
 
 #[actix_rt::test]
@@ -330,3 +345,44 @@ async fn set_and_reset_distinct_attribute_with_dedicated_route() {
 
     assert_eq!(response, json!(null));
 }
+
+#[actix_rt::test]
+async fn get_settings_with_etag() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (_, code, headers) = server
+        .service
+        .request(TestRequest::get().uri("/indexes/test/settings"))
+        .await;
+    assert_eq!(code, 200);
+    let etag = headers.get("etag").unwrap().clone();
+
+    let (_, code, _) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/settings")
+                .insert_header(("If-None-Match", etag.clone())),
+        )
+        .await;
+    assert_eq!(code, 304);
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code, _) = server
+        .service
+        .request(
+            TestRequest::get()
+                .uri("/indexes/test/settings")
+                .insert_header(("If-None-Match", etag)),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["filterableAttributes"], json!(["genre"]));
+}
@@ -1,2 +1,3 @@
 mod distinct;
 mod get_settings;
+mod synonyms;
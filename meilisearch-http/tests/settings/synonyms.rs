@@ -0,0 +1,119 @@
+use actix_web::test;
+use meilisearch_http::{analytics, create_app};
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn import_and_export_solr_synonyms() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let req = test::TestRequest::put()
+        .uri("/indexes/test/settings/synonyms/export")
+        .set_payload("# movies\nsuperhero, hero\nmarvel => marvel comics\n")
+        .insert_header(("content-type", "text/plain"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("test");
+    index.wait_task(0).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(
+        response["synonyms"],
+        json!({
+            "hero": ["superhero"],
+            "marvel": ["marvel comics"],
+            "superhero": ["hero"],
+        })
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/indexes/test/settings/synonyms/export")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 200);
+    let body = test::read_body(res).await;
+    assert_eq!(body, "hero, superhero\nmarvel => marvel comics\n");
+
+    let req = test::TestRequest::get()
+        .uri("/indexes/test/settings/synonyms/export?format=wordnet")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn import_wordnet_synonyms() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let req = test::TestRequest::put()
+        .uri("/indexes/test/settings/synonyms/export?format=wordnet")
+        .set_payload("s(100000001,1,'film',n,1,0).\ns(100000001,2,'movie',n,1,0).\n")
+        .insert_header(("content-type", "text/plain"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("test");
+    index.wait_task(0).await;
+
+    let req = test::TestRequest::get()
+        .uri("/indexes/test/settings/synonyms/export?format=wordnet")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 200);
+    let body = test::read_body(res).await;
+    assert_eq!(
+        body,
+        "s(100000001,1,'film',n,1,0).\ns(100000001,2,'movie',n,1,0).\n"
+    );
+}
+
+#[actix_rt::test]
+async fn import_invalid_synonyms_file() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let req = test::TestRequest::put()
+        .uri("/indexes/test/settings/synonyms/export")
+        .set_payload("tv, television\n => tv\n")
+        .insert_header(("content-type", "text/plain"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(
+        response["message"],
+        "The synonyms file is invalid at line 2: both sides of `=>` must contain at least one word."
+    );
+    assert_eq!(response["code"], "bad_request");
+}
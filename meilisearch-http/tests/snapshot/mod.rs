@@ -81,3 +81,43 @@ async fn perform_snapshot() {
         server.index("test1").settings(),
     );
 }
+
+#[actix_rt::test]
+async fn perform_snapshot_on_demand() {
+    let temp = tempfile::tempdir().unwrap();
+    let snapshot_dir = tempfile::tempdir().unwrap();
+
+    let options = Opt {
+        snapshot_dir: snapshot_dir.path().to_owned(),
+        snapshot_retention_count: Some(1),
+        ..default_settings(temp.path())
+    };
+
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (response, code) = server
+        .service
+        .post("/snapshots", serde_json::json!({}))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert!(response["enqueuedAt"].is_string());
+
+    sleep(Duration::from_secs(2)).await;
+
+    let (_response, code) = server
+        .service
+        .post("/snapshots", serde_json::json!({}))
+        .await;
+    assert_eq!(code, 202);
+
+    sleep(Duration::from_secs(2)).await;
+
+    // With a retention, the snapshots are timestamped and only the most recent one is kept.
+    let snapshots: Vec<_> = std::fs::read_dir(snapshot_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".snapshot"))
+        .collect();
+    assert_eq!(snapshots.len(), 1, "{:?}", snapshots);
+    assert!(snapshots[0].starts_with("db-"));
+}
@@ -1,6 +1,9 @@
+use byte_unit::{Byte, ByteUnit};
+use meilisearch_http::Opt;
 use serde_json::json;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
+use crate::common::server::default_settings;
 use crate::common::Server;
 
 #[actix_rt::test]
@@ -23,6 +26,49 @@ async fn test_healthyness() {
     assert_eq!(response["status"], "available");
 }
 
+#[actix_rt::test]
+async fn liveness() {
+    let server = Server::new().await;
+
+    let (response, status_code) = server.service.get("/health/live").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["status"], "available");
+}
+
+#[actix_rt::test]
+async fn readiness() {
+    let server = Server::new().await;
+    server.index("test").create(None).await;
+    server.index("test").wait_task(0).await;
+
+    let (response, status_code) = server.service.get("/health/ready").await;
+    assert_eq!(status_code, 200, "{}", response);
+    assert_eq!(response["status"], "available");
+    assert_eq!(response["checks"]["database"]["status"], "ok");
+    assert_eq!(response["checks"]["scheduler"]["status"], "ok");
+    assert_ne!(response["checks"]["diskSpace"]["status"], "failed");
+}
+
+#[actix_rt::test]
+async fn not_ready_when_the_disk_is_full() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        health_min_free_disk_space: Byte::from_unit(1024.0, ByteUnit::TiB).unwrap(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (response, status_code) = server.service.get("/health/ready").await;
+    if response["checks"]["diskSpace"]["status"] == "skipped" {
+        // the disk holding the database couldn't be found on this platform.
+        return;
+    }
+    assert_eq!(status_code, 503, "{}", response);
+    assert_eq!(response["status"], "unavailable");
+    assert_eq!(response["checks"]["diskSpace"]["status"], "failed");
+    assert_eq!(response["checks"]["database"]["status"], "ok");
+}
+
 #[actix_rt::test]
 async fn stats() {
     let server = Server::new().await;
@@ -1,4 +1,5 @@
 use crate::common::Server;
+use actix_web::test::TestRequest;
 use serde_json::json;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
@@ -189,6 +190,48 @@ async fn list_tasks_status_and_type_filtered() {
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
 }
 
+#[actix_rt::test]
+async fn list_tasks_with_rich_filters() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index.update(Some("id")).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .get("/tasks?uids=0,1&types=indexCreation")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 0);
+
+    let (task, _) = index.get_task(0).await;
+    let enqueued_at = task["enqueuedAt"].as_str().unwrap();
+    let (response, code) = server
+        .service
+        .get(format!("/tasks?afterEnqueuedAt={}", enqueued_at))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 1);
+
+    let (response, code) = server
+        .service
+        .get("/tasks?statuses=succeeded&beforeFinishedAt=2050-01-01T00:00:00Z&indexUids=test")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    let (response, code) = server.service.get("/tasks?canceledBy=5").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+
+    let (response, code) = server.service.get("/tasks?afterStartedAt=yesterday").await;
+    assert_eq!(code, 400, "{}", response);
+}
+
 macro_rules! assert_valid_summarized_task {
     ($response:expr, $task_type:literal, $index:literal) => {{
         assert_eq!($response.as_object().unwrap().len(), 5);
@@ -231,3 +274,232 @@ async fn test_summarized_task_view() {
     let (response, _) = index.delete().await;
     assert_valid_summarized_task!(response, "indexDeletion", "test");
 }
+
+#[actix_rt::test]
+async fn cancel_tasks_without_filters() {
+    let server = Server::new().await;
+    let (response, code) = server.cancel_tasks("").await;
+
+    let expected_response = json!({
+        "message": "Query parameters to filter the tasks to cancel are missing. Available query parameters are: `uids`, `indexUids`, `statuses`, `types`.",
+        "code": "missing_task_filters",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#missing_task_filters"
+    });
+
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 400);
+}
+
+#[actix_rt::test]
+async fn cancel_processing_tasks() {
+    let server = Server::new().await;
+    let (response, code) = server.cancel_tasks("statuses=enqueued,processing").await;
+    assert_eq!(code, 202, "{}", response);
+
+    let (response, code) = server
+        .service
+        .get(format!("/tasks/{}", response["taskUid"]))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["canceledTasks"], 0);
+}
+
+#[actix_rt::test]
+async fn cancel_finished_task() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.cancel_tasks("uids=0").await;
+    assert_eq!(code, 202);
+    assert_eq!(response["taskUid"], 1);
+    assert_eq!(response["type"], "taskCancelation");
+
+    // the cancelation task is registered already processed.
+    let (response, code) = server.service.get("/tasks/1").await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["indexUid"], json!(null));
+    assert_eq!(response["details"]["canceledTasks"], 0);
+    assert_eq!(response["details"]["originalFilter"], "uids=0");
+
+    // a finished task can't be canceled.
+    let (response, _) = index.get_task(0).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response.get("canceledBy"), None);
+
+    let (response, code) = server
+        .service
+        .get("/tasks?type=taskCancelation&status=succeeded")
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn delete_tasks_without_filters() {
+    let server = Server::new().await;
+    let (response, code) = server.delete_tasks("").await;
+
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "missing_task_filters");
+}
+
+#[actix_rt::test]
+async fn delete_finished_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index.update(Some("id")).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server.delete_tasks("uids=0&statuses=succeeded").await;
+    assert_eq!(code, 202);
+    assert_eq!(response["taskUid"], 2);
+    assert_eq!(response["type"], "taskDeletion");
+
+    let (response, code) = server.service.get("/tasks/2").await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["deletedTasks"], 1);
+    assert_eq!(
+        response["details"]["originalFilter"],
+        "uids=0&statuses=succeeded"
+    );
+
+    let (_, code) = index.get_task(0).await;
+    assert_eq!(code, 404);
+    let (_, code) = index.get_task(1).await;
+    assert_eq!(code, 200);
+
+    // the task ids keep increasing after a deletion.
+    let (response, code) = server.delete_tasks("uids=2").await;
+    assert_eq!(code, 202);
+    assert_eq!(response["taskUid"], 3);
+}
+
+#[actix_rt::test]
+async fn update_and_delete_webhook() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/webhook").await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "url": null, "hasAuthorization": false }));
+
+    let (response, code) = server
+        .service
+        .put("/webhook", json!({ "url": "ftp://localhost/tasks" }))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_webhook_url");
+
+    let (response, code) = server
+        .service
+        .put(
+            "/webhook",
+            json!({ "url": "http://localhost:3000/tasks", "authorization": "Bearer secret" }),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "url": "http://localhost:3000/tasks", "hasAuthorization": true })
+    );
+
+    let (_response, code) = server.service.delete("/webhook").await;
+    assert_eq!(code, 204);
+
+    let (response, _code) = server.service.get("/webhook").await;
+    assert_eq!(response, json!({ "url": null, "hasAuthorization": false }));
+}
+
+#[actix_rt::test]
+async fn prioritize_finished_task() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .patch("/tasks/0", json!({ "priority": "high" }))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "task_not_enqueued");
+
+    let (response, code) = server
+        .service
+        .post("/tasks/0/move-to-front", json!(null))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "task_not_enqueued");
+
+    let (response, code) = server
+        .service
+        .post("/tasks/1/move-to-front", json!(null))
+        .await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "task_not_found");
+
+    // `urgent` is reserved to the tasks moved to the front of the queue.
+    let (response, code) = server
+        .service
+        .patch("/tasks/0", json!({ "priority": "urgent" }))
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn register_tasks_with_a_priority() {
+    let server = Server::new().await;
+
+    let request = TestRequest::post()
+        .uri("/indexes")
+        .insert_header(("X-Task-Priority", "high"))
+        .set_json(&json!({ "uid": "test" }));
+    let (response, code, _) = server.service.request(request).await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = server.index("test").wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    // the priority is only shown while the task is enqueued.
+    assert!(response.get("priority").is_none(), "{}", response);
+
+    // `urgent` is reserved to the tasks moved to the front of the queue.
+    let request = TestRequest::post()
+        .uri("/indexes")
+        .insert_header(("X-Task-Priority", "urgent"))
+        .set_json(&json!({ "uid": "other" }));
+    let (response, code, _) = server.service.request(request).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn drain_tasks_before_shutdown() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+
+    let drained = server
+        .service
+        .meilisearch
+        .drain_tasks(std::time::Duration::from_secs(10))
+        .await
+        .unwrap();
+    assert!(drained);
+    // the batch was either finished or never started.
+    let (response, _) = index.get_task(0).await;
+    assert_ne!(response["status"], "processing", "{}", response);
+
+    // no new batch is started once the tasks are drained.
+    index.add_documents(json!([{ "id": 2 }]), None).await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let (response, _) = index.get_task(1).await;
+    assert_eq!(response["status"], "enqueued", "{}", response);
+}
@@ -1,18 +1,25 @@
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug, Display};
 use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::thread;
 
+use bytes::{Buf, Bytes};
+use crossbeam_channel::bounded;
 use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::internal_error;
 use milli::documents::DocumentBatchBuilder;
+use tokio::sync::mpsc;
 
 type Result<T> = std::result::Result<T, DocumentFormatError>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum PayloadType {
     Ndjson,
     Json,
     Csv,
+    MessagePack,
+    Cbor,
 }
 
 impl fmt::Display for PayloadType {
@@ -21,6 +28,8 @@ impl fmt::Display for PayloadType {
             PayloadType::Ndjson => write!(f, "ndjson"),
             PayloadType::Json => write!(f, "json"),
             PayloadType::Csv => write!(f, "csv"),
+            PayloadType::MessagePack => write!(f, "msgpack"),
+            PayloadType::Cbor => write!(f, "cbor"),
         }
     }
 }
@@ -29,6 +38,20 @@ impl fmt::Display for PayloadType {
 pub enum DocumentFormatError {
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
     MalformedPayload(Box<milli::documents::Error>, PayloadType),
+    /// A line of an ndjson payload is malformed, the line number starts at 1.
+    MalformedNdjsonLine(Box<milli::documents::Error>, usize),
+    /// A binary payload couldn't be decoded.
+    UndecodablePayload(String, PayloadType),
+}
+
+/// Truncates the serde error message, the user input maybe insanely long.
+/// See https://github.com/meilisearch/meilisearch/issues/2107
+fn truncate_serde_message(mut serde_msg: String) -> String {
+    let ellipsis = "...";
+    if serde_msg.len() > 100 + ellipsis.len() {
+        serde_msg.replace_range(50..serde_msg.len() - 85, ellipsis);
+    }
+    serde_msg
 }
 
 impl Display for DocumentFormatError {
@@ -37,13 +60,7 @@ impl Display for DocumentFormatError {
             Self::Internal(e) => write!(f, "An internal error has occurred: `{}`.", e),
             Self::MalformedPayload(me, b) => match me.borrow() {
                 milli::documents::Error::JsonError(se) => {
-                    // https://github.com/meilisearch/meilisearch/issues/2107
-                    // The user input maybe insanely long. We need to truncate it.
-                    let mut serde_msg = se.to_string();
-                    let ellipsis = "...";
-                    if serde_msg.len() > 100 + ellipsis.len() {
-                        serde_msg.replace_range(50..serde_msg.len() - 85, ellipsis);
-                    }
+                    let serde_msg = truncate_serde_message(se.to_string());
 
                     write!(
                         f,
@@ -53,6 +70,38 @@ impl Display for DocumentFormatError {
                 }
                 _ => write!(f, "The `{}` payload provided is malformed: `{}`.", b, me),
             },
+            Self::MalformedNdjsonLine(me, line) => match me.borrow() {
+                milli::documents::Error::JsonError(se) => {
+                    // Each line is deserialized on its own, so serde always reports the first
+                    // line. We replace the position with the one in the whole payload.
+                    let serde_msg = se.to_string();
+                    let position = format!(" at line {} column {}", se.line(), se.column());
+                    let serde_msg = match serde_msg.strip_suffix(&position) {
+                        Some(msg) => format!("{} at line {} column {}", msg, line, se.column()),
+                        None => serde_msg,
+                    };
+                    let serde_msg = truncate_serde_message(serde_msg);
+
+                    write!(
+                        f,
+                        "The `{}` payload provided is malformed. `Couldn't serialize document value: {}`.",
+                        PayloadType::Ndjson, serde_msg
+                    )
+                }
+                _ => write!(
+                    f,
+                    "The `{}` payload provided is malformed at line {}: `{}`.",
+                    PayloadType::Ndjson,
+                    line,
+                    me
+                ),
+            },
+            Self::UndecodablePayload(message, b) => write!(
+                f,
+                "The `{}` payload provided is malformed. `Couldn't decode document value: {}`.",
+                b,
+                truncate_serde_message(message.clone())
+            ),
         }
     }
 }
@@ -73,32 +122,145 @@ impl ErrorCode for DocumentFormatError {
         match self {
             DocumentFormatError::Internal(_) => Code::Internal,
             DocumentFormatError::MalformedPayload(_, _) => Code::MalformedPayload,
+            DocumentFormatError::MalformedNdjsonLine(_, _) => Code::MalformedPayload,
+            DocumentFormatError::UndecodablePayload(_, _) => Code::MalformedPayload,
         }
     }
 }
 
 internal_error!(DocumentFormatError: io::Error);
 
+/// A reader over the chunks of a payload that are sent from an async context. It allows
+/// the payload to be read by a blocking document reader while it is still being received.
+pub struct ChunksReader {
+    receiver: mpsc::Receiver<std::result::Result<Bytes, ()>>,
+    current: Bytes,
+}
+
+impl ChunksReader {
+    pub fn new(receiver: mpsc::Receiver<std::result::Result<Bytes, ()>>) -> Self {
+        Self {
+            receiver,
+            current: Bytes::new(),
+        }
+    }
+
+    /// Waits for the first non-empty chunk and returns whether the payload is empty.
+    pub fn is_empty(&mut self) -> io::Result<bool> {
+        self.fill()
+    }
+
+    /// Fills the current chunk if it has been consumed, returns `true` if there is nothing
+    /// left to read.
+    fn fill(&mut self) -> io::Result<bool> {
+        while self.current.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(Ok(bytes)) => self.current = bytes,
+                Some(Err(())) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "the payload stream has been interrupted",
+                    ))
+                }
+                None => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Read for ChunksReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.fill()? {
+            return Ok(0);
+        }
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current[..len]);
+        self.current.advance(len);
+        Ok(len)
+    }
+}
+
+/// The options used to parse a csv payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+}
+
 /// reads csv from input and write an obkv batch to writer.
-pub fn read_csv(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+pub fn read_csv(input: impl Read, writer: impl Write + Seek, options: CsvOptions) -> Result<usize> {
     let writer = BufWriter::new(writer);
-    let builder =
-        DocumentBatchBuilder::from_csv(input, writer).map_err(|e| (PayloadType::Csv, e))?;
+    let builder = if options == CsvOptions::default() {
+        DocumentBatchBuilder::from_csv(input, writer).map_err(|e| (PayloadType::Csv, e))?
+    } else {
+        // milli only reads the standard csv dialect, so we first need to rewrite the payload
+        // with the default delimiter and quote.
+        let input = normalize_csv(input, options)?;
+        DocumentBatchBuilder::from_csv(input, writer).map_err(|e| (PayloadType::Csv, e))?
+    };
 
     let count = builder.finish().map_err(|e| (PayloadType::Csv, e))?;
 
     Ok(count)
 }
 
+/// Rewrites a csv using a custom delimiter and quote into a temporary file using the default ones.
+fn normalize_csv(input: impl Read, options: CsvOptions) -> Result<impl Read> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .from_reader(input);
+    let mut writer = csv::Writer::from_writer(tempfile::tempfile()?);
+
+    let mut record = csv::ByteRecord::new();
+    while reader
+        .read_byte_record(&mut record)
+        .map_err(|e| (PayloadType::Csv, milli::documents::Error::from(e)))?
+    {
+        writer
+            .write_byte_record(&record)
+            .map_err(|e| (PayloadType::Csv, milli::documents::Error::from(e)))?;
+    }
+
+    let mut file = writer
+        .into_inner()
+        .map_err(|e| DocumentFormatError::Internal(Box::new(e.into_error())))?;
+    file.seek(io::SeekFrom::Start(0))?;
+
+    Ok(BufReader::new(file))
+}
+
 /// reads jsonl from input and write an obkv batch to writer.
 pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
-    let mut reader = BufReader::new(input);
+    write_ndjson_lines(BufReader::new(input), writer, 1)
+}
+
+/// Writes the lines of a jsonl payload to an obkv batch, `first_line` is the number of the first
+/// line of `reader` in the whole payload.
+fn write_ndjson_lines(
+    mut reader: impl BufRead,
+    writer: impl Write + Seek,
+    first_line: usize,
+) -> Result<usize> {
     let writer = BufWriter::new(writer);
 
     let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (PayloadType::Ndjson, e))?;
     let mut buf = String::new();
+    let mut line = first_line - 1;
 
     while reader.read_line(&mut buf)? > 0 {
+        line += 1;
         // skip empty lines
         if buf == "\n" {
             buf.clear();
@@ -106,7 +268,10 @@ pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize>
         }
         builder
             .extend_from_json(Cursor::new(&buf.as_bytes()))
-            .map_err(|e| (PayloadType::Ndjson, e))?;
+            .map_err(|e| match e {
+                milli::documents::Error::Io(e) => DocumentFormatError::Internal(Box::new(e)),
+                e => DocumentFormatError::MalformedNdjsonLine(Box::new(e), line),
+            })?;
         buf.clear();
     }
 
@@ -115,6 +280,141 @@ pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize>
     Ok(count)
 }
 
+/// The options of the parallel jsonl reader.
+#[derive(Debug, Clone, Copy)]
+pub struct NdjsonPipelineOptions {
+    /// The number of threads writing the batches.
+    pub threads: usize,
+    /// The size of the chunks of the payload written to each batch, a chunk is extended to the
+    /// end of its last line.
+    pub chunk_size: usize,
+}
+
+impl Default for NdjsonPipelineOptions {
+    fn default() -> Self {
+        Self {
+            threads: num_cpus::get(),
+            chunk_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Reads jsonl from input and writes it to several obkv batches in parallel.
+///
+/// The payload is split into chunks of whole lines that are sent, through bounded channels, to
+/// a pool of threads writing each chunk to its own batch, created with `new_part`. Only a few
+/// chunks are held in memory at once. The batches are returned in the order of the payload,
+/// there is at least one, and the first malformed line of the payload is reported.
+pub fn read_ndjson_parallel<W, F>(
+    input: impl Read,
+    new_part: F,
+    options: NdjsonPipelineOptions,
+) -> Result<(usize, Vec<W>)>
+where
+    W: Write + Seek + Send + 'static,
+    F: Fn() -> io::Result<W> + Clone + Send + 'static,
+{
+    let threads = options.threads.max(1);
+    // (chunk index, number of its first line, content)
+    let (chunk_sender, chunk_receiver) = bounded::<(usize, usize, Vec<u8>)>(threads);
+    let (part_sender, part_receiver) = bounded::<(usize, Result<(usize, W)>)>(threads);
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let chunk_receiver = chunk_receiver.clone();
+            let part_sender = part_sender.clone();
+            let new_part = new_part.clone();
+            thread::spawn(move || {
+                for (index, first_line, chunk) in chunk_receiver {
+                    let result =
+                        new_part()
+                            .map_err(DocumentFormatError::from)
+                            .and_then(|mut part| {
+                                let count = write_ndjson_lines(&chunk[..], &mut part, first_line)?;
+                                Ok((count, part))
+                            });
+                    // the collector stops at the first error.
+                    if part_sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(chunk_receiver);
+    drop(part_sender);
+
+    // The batches are written out of order, they are put back in the order of the payload.
+    let collector = thread::spawn(move || -> Result<(usize, Vec<W>)> {
+        let mut pending = BTreeMap::new();
+        let mut parts = Vec::new();
+        let mut count = 0;
+        for (index, result) in part_receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&parts.len()) {
+                let (part_count, part) = result?;
+                count += part_count;
+                parts.push(part);
+            }
+        }
+        Ok((count, parts))
+    });
+
+    let read_result = send_ndjson_chunks(input, options.chunk_size, |chunk| {
+        chunk_sender.send(chunk).is_ok()
+    });
+    drop(chunk_sender);
+
+    let pipeline_panicked =
+        || DocumentFormatError::Internal("a thread of the ndjson pipeline panicked".into());
+    let collected = collector.join().map_err(|_| pipeline_panicked())?;
+    for worker in workers {
+        worker.join().map_err(|_| pipeline_panicked())?;
+    }
+
+    let (count, mut parts) = collected?;
+    read_result?;
+
+    if parts.is_empty() {
+        let mut part = new_part()?;
+        write_ndjson_lines(io::empty(), &mut part, 1)?;
+        parts.push(part);
+    }
+
+    Ok((count, parts))
+}
+
+/// Splits `input` into chunks of whole lines and gives them to `send`, until it returns `false`.
+fn send_ndjson_chunks(
+    input: impl Read,
+    chunk_size: usize,
+    mut send: impl FnMut((usize, usize, Vec<u8>)) -> bool,
+) -> Result<()> {
+    let mut reader = BufReader::new(input);
+    let mut line = 1;
+
+    for index in 0.. {
+        let mut chunk = Vec::with_capacity(chunk_size);
+        (&mut reader)
+            .take(chunk_size.max(1) as u64)
+            .read_to_end(&mut chunk)?;
+        if chunk.is_empty() {
+            break;
+        }
+        if chunk.last() != Some(&b'\n') {
+            reader.read_until(b'\n', &mut chunk)?;
+        }
+
+        let lines = chunk.iter().filter(|byte| **byte == b'\n').count();
+        if !send((index, line, chunk)) {
+            break;
+        }
+        line += lines;
+    }
+
+    Ok(())
+}
+
 /// reads json from input and write an obkv batch to writer.
 pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
     let writer = BufWriter::new(writer);
@@ -127,3 +427,152 @@ pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
 
     Ok(count)
 }
+
+/// reads MessagePack from input and write an obkv batch to writer.
+pub fn read_msgpack(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    let documents = rmp_serde::from_read(input).map_err(|e| {
+        DocumentFormatError::UndecodablePayload(e.to_string(), PayloadType::MessagePack)
+    })?;
+    read_decoded(documents, writer, PayloadType::MessagePack)
+}
+
+/// reads CBOR from input and write an obkv batch to writer.
+pub fn read_cbor(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    let documents = ciborium::de::from_reader(input)
+        .map_err(|e| DocumentFormatError::UndecodablePayload(e.to_string(), PayloadType::Cbor))?;
+    read_decoded(documents, writer, PayloadType::Cbor)
+}
+
+/// Writes the documents decoded from a binary payload, which have the same shape as a json
+/// payload, to an obkv batch.
+fn read_decoded(
+    documents: serde_json::Value,
+    writer: impl Write + Seek,
+    payload_type: PayloadType,
+) -> Result<usize> {
+    let json =
+        serde_json::to_vec(&documents).map_err(|e| DocumentFormatError::Internal(e.into()))?;
+
+    let writer = BufWriter::new(writer);
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (payload_type, e))?;
+    builder
+        .extend_from_json(Cursor::new(json))
+        .map_err(|e| (payload_type, e))?;
+
+    let count = builder.finish().map_err(|e| (payload_type, e))?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use milli::documents::DocumentBatchReader;
+
+    use super::*;
+
+    fn small_chunks() -> NdjsonPipelineOptions {
+        NdjsonPipelineOptions {
+            threads: 3,
+            chunk_size: 16,
+        }
+    }
+
+    fn document_ids(parts: Vec<Cursor<Vec<u8>>>) -> Vec<serde_json::Value> {
+        let mut ids = Vec::new();
+        for mut part in parts {
+            part.set_position(0);
+            let mut reader = DocumentBatchReader::from_reader(part).unwrap();
+            while let Some((index, document)) = reader.next_document_with_index().unwrap() {
+                for (field_id, content) in document.iter() {
+                    if matches!(index.name(field_id), Some(name) if name == "id") {
+                        ids.push(serde_json::from_slice(content).unwrap());
+                    }
+                }
+            }
+        }
+        ids
+    }
+
+    #[test]
+    fn read_ndjson_in_parallel_keeps_the_order() {
+        let payload: String = (0..100)
+            .map(|i| format!("{{ \"id\": {}, \"title\": \"document {}\" }}\n\n", i, i))
+            .collect();
+
+        let (count, parts) = read_ndjson_parallel(
+            payload.as_bytes(),
+            || Ok(Cursor::new(Vec::new())),
+            small_chunks(),
+        )
+        .unwrap();
+
+        assert_eq!(count, 100);
+        assert!(parts.len() > 1);
+        let expected: Vec<_> = (0..100).map(serde_json::Value::from).collect();
+        assert_eq!(document_ids(parts), expected);
+    }
+
+    #[test]
+    fn read_ndjson_in_parallel_reports_the_first_malformed_line() {
+        let mut payload: String = (0..50).map(|i| format!("{{ \"id\": {} }}\n", i)).collect();
+        payload.push_str("{ \"id\": 50\n");
+        payload.push_str(
+            &(51..100)
+                .map(|i| format!("{{ \"id\": {} }}\n", i))
+                .collect::<String>(),
+        );
+        payload.push_str("{ \"id\": 100\n");
+
+        let error = read_ndjson_parallel(
+            payload.as_bytes(),
+            || Ok(Cursor::new(Vec::new())),
+            small_chunks(),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            DocumentFormatError::MalformedNdjsonLine(_, 51)
+        ));
+    }
+
+    #[test]
+    fn read_msgpack_and_cbor_documents() {
+        let documents = serde_json::json!([
+            { "id": 1, "title": "Carol", "tags": ["romance", "drama"] },
+            { "id": 2, "title": "Wonder Woman", "rating": 4.5 },
+        ]);
+        let expected = vec![serde_json::Value::from(1), serde_json::Value::from(2)];
+
+        let msgpack = rmp_serde::to_vec_named(&documents).unwrap();
+        let mut batch = Cursor::new(Vec::new());
+        assert_eq!(read_msgpack(&msgpack[..], &mut batch).unwrap(), 2);
+        assert_eq!(document_ids(vec![batch]), expected.clone());
+
+        let mut cbor = Vec::new();
+        ciborium::ser::into_writer(&documents, &mut cbor).unwrap();
+        let mut batch = Cursor::new(Vec::new());
+        assert_eq!(read_cbor(&cbor[..], &mut batch).unwrap(), 2);
+        assert_eq!(document_ids(vec![batch]), expected);
+    }
+
+    #[test]
+    fn read_malformed_msgpack() {
+        let error = read_msgpack(&b"\xc1"[..], Cursor::new(Vec::new())).unwrap_err();
+        assert!(matches!(
+            error,
+            DocumentFormatError::UndecodablePayload(_, PayloadType::MessagePack)
+        ));
+    }
+
+    #[test]
+    fn read_empty_ndjson_in_parallel() {
+        let (count, parts) =
+            read_ndjson_parallel(&b"\n\n"[..], || Ok(Cursor::new(Vec::new())), small_chunks())
+                .unwrap();
+
+        assert_eq!(count, 0);
+        assert_eq!(parts.len(), 1);
+        assert!(document_ids(parts).is_empty());
+    }
+}
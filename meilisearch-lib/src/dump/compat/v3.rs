@@ -37,6 +37,7 @@ impl From<v2::UpdateResult> for TaskResult {
         match other {
             v2::UpdateResult::DocumentsAddition(result) => TaskResult::DocumentAddition {
                 indexed_documents: result.nb_documents as u64,
+                rejected_documents: Vec::new(),
             },
             v2::UpdateResult::DocumentDeletion { deleted } => TaskResult::DocumentDeletion {
                 deleted_documents: deleted,
@@ -72,6 +73,7 @@ impl From<Update> for super::v4::TaskContent {
             } => TaskContent::DocumentAddition {
                 content_uuid: Uuid::default(),
                 merge_strategy: method,
+                merge_patch: false,
                 primary_key,
                 // document count is unknown for legacy updates
                 documents_count: 0,
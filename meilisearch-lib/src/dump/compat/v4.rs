@@ -8,7 +8,8 @@ use uuid::Uuid;
 use crate::index::{Settings, Unchecked};
 use crate::tasks::batch::BatchId;
 use crate::tasks::task::{
-    DocumentDeletion, TaskContent as NewTaskContent, TaskEvent as NewTaskEvent, TaskId, TaskResult,
+    DocumentDeletion, TaskContent as NewTaskContent, TaskEvent as NewTaskEvent, TaskId,
+    TaskPriority, TaskResult,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +26,10 @@ impl From<Task> for crate::tasks::task::Task {
             id: other.id,
             content: NewTaskContent::from((other.index_uid, other.content)),
             events: other.events.into_iter().map(Into::into).collect(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         }
     }
 }
@@ -112,6 +117,7 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 index_uid,
                 content_uuid,
                 merge_strategy,
+                merge_patch: false,
                 primary_key,
                 documents_count,
                 allow_index_creation,
@@ -139,7 +145,10 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 index_uid,
                 primary_key,
             },
-            TaskContent::Dump { uid } => NewTaskContent::Dump { uid },
+            TaskContent::Dump { uid } => NewTaskContent::Dump {
+                uid,
+                incremental_from: None,
+            },
         }
     }
 }
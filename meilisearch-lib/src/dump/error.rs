@@ -2,6 +2,8 @@ use meilisearch_auth::error::AuthControllerError;
 use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::internal_error;
 
+use crate::storage::ObjectStorageError;
+use crate::update_file_store::UpdateFileStoreError;
 use crate::{index_resolver::error::IndexResolverError, tasks::error::TaskError};
 
 pub type Result<T> = std::result::Result<T, DumpError>;
@@ -12,6 +14,22 @@ pub enum DumpError {
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
     #[error("{0}")]
     IndexResolver(#[from] IndexResolverError),
+    #[error("Dump `{0}` not found. An incremental dump must follow a dump that succeeded and is still in the task history.")]
+    BaseDumpNotFound(String),
+    #[error("Tasks registered after the dump `{0}` were deleted from the task history, the indexes they modified can't be known. Create a full dump instead.")]
+    PrunedTaskHistory(String),
+    #[error("The dump is invalid: {0}.")]
+    InvalidDump(String),
+    #[error("Only the dumps of version V5 can be imported through the API, the dump is of version {0}. Use the `--import-dump` option instead.")]
+    UnsupportedImportVersion(&'static str),
+    #[error(
+        "The dump `{0}` can't be imported, only the dumps of the dump directory can be imported."
+    )]
+    ForbiddenDumpPath(String),
+    #[error("The dump can't be downloaded from `{0}`, only the `http` and `https` URLs of the hosts given to `--dump-import-allowed-hosts` are accepted.")]
+    ForbiddenDumpUrl(String),
+    #[error("The dump couldn't be downloaded from `{0}`: {1}.")]
+    DumpDownload(String, String),
 }
 
 internal_error!(
@@ -23,7 +41,9 @@ internal_error!(
     tempfile::PersistError,
     fs_extra::error::Error,
     AuthControllerError,
-    TaskError
+    TaskError,
+    ObjectStorageError,
+    UpdateFileStoreError
 );
 
 impl ErrorCode for DumpError {
@@ -31,6 +51,12 @@ impl ErrorCode for DumpError {
         match self {
             DumpError::Internal(_) => Code::Internal,
             DumpError::IndexResolver(e) => e.error_code(),
+            DumpError::BaseDumpNotFound(_) => Code::DumpNotFound,
+            DumpError::PrunedTaskHistory(_) => Code::BadRequest,
+            DumpError::InvalidDump(_)
+            | DumpError::UnsupportedImportVersion(_)
+            | DumpError::DumpDownload(_, _) => Code::InvalidDump,
+            DumpError::ForbiddenDumpPath(_) | DumpError::ForbiddenDumpUrl(_) => Code::BadRequest,
         }
     }
 }
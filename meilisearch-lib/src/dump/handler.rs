@@ -16,6 +16,7 @@ pub fn generate_uid() -> String {
 }
 
 mod real {
+    use std::collections::HashSet;
     use std::path::PathBuf;
     use std::sync::Arc;
 
@@ -28,10 +29,17 @@ mod real {
     use crate::analytics;
     use crate::compression::to_tar_gz;
     use crate::dump::error::{DumpError, Result};
-    use crate::dump::{MetadataVersion, META_FILE_NAME};
+    use crate::dump::incremental::changed_indexes;
+    use crate::dump::{
+        download_dump, import_indexes, read_dump, upload_path, DumpImportSource,
+        ImportConflictPolicy, MetadataVersion, META_FILE_NAME,
+    };
+    use crate::experimental_features;
     use crate::index_resolver::{
         index_store::IndexStore, meta_store::IndexMetaStore, IndexResolver,
     };
+    use crate::storage::{ArchiveKind, ObjectStorage};
+    use crate::tasks::task::{TaskContent, TaskResult};
     use crate::tasks::TaskStore;
     use crate::update_file_store::UpdateFileStore;
 
@@ -43,6 +51,10 @@ mod real {
         index_db_size: usize,
         env: Arc<Env>,
         index_resolver: Arc<IndexResolver<U, I>>,
+        /// When set, the dumps are uploaded to the object storage and removed locally.
+        object_storage: Option<ObjectStorage>,
+        /// The hosts the imported dumps can be downloaded from.
+        import_allowed_hosts: Vec<String>,
     }
 
     impl<U, I> DumpHandler<U, I>
@@ -50,6 +62,7 @@ mod real {
         U: IndexMetaStore + Sync + Send + 'static,
         I: IndexStore + Sync + Send + 'static,
     {
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             dump_path: PathBuf,
             db_path: PathBuf,
@@ -58,6 +71,8 @@ mod real {
             index_db_size: usize,
             env: Arc<Env>,
             index_resolver: Arc<IndexResolver<U, I>>,
+            object_storage: Option<ObjectStorage>,
+            import_allowed_hosts: Vec<String>,
         ) -> Self {
             Self {
                 dump_path,
@@ -67,18 +82,36 @@ mod real {
                 index_db_size,
                 env,
                 index_resolver,
+                object_storage,
+                import_allowed_hosts,
             }
         }
 
-        pub async fn run(&self, uid: String) -> Result<()> {
+        /// Performs the dump `uid`. When `incremental_from` is set, only the indexes modified since
+        /// this dump are dumped.
+        pub async fn run(&self, uid: String, incremental_from: Option<String>) -> Result<()> {
             trace!("Performing dump.");
 
+            // Fails early if the base of an incremental dump can't be found.
+            let changed_indexes = match incremental_from {
+                Some(ref base) => {
+                    let store = TaskStore::new(self.env.clone())?;
+                    Some(changed_indexes(&store, base).await?)
+                }
+                None => None,
+            };
+
             create_dir_all(&self.dump_path).await?;
 
             let temp_dump_dir = tokio::task::spawn_blocking(tempfile::TempDir::new).await??;
             let temp_dump_path = temp_dump_dir.path().to_owned();
 
-            let meta = MetadataVersion::new_v5(self.index_db_size, self.task_store_size);
+            let meta = MetadataVersion::new_v5(
+                self.index_db_size,
+                self.task_store_size,
+                uid.clone(),
+                incremental_from,
+            );
             let meta_path = temp_dump_path.join(META_FILE_NAME);
 
             let meta_bytes = serde_json::to_vec(&meta)?;
@@ -92,6 +125,7 @@ mod real {
             let db_path = self.db_path.clone();
             let temp_dump_path_clone = temp_dump_path.clone();
             tokio::task::spawn_blocking(move || -> Result<()> {
+                experimental_features::copy_features(&db_path, &temp_dump_path_clone)?;
                 AuthController::dump(db_path, temp_dump_path_clone)?;
                 Ok(())
             })
@@ -102,7 +136,14 @@ mod real {
                 self.update_file_store.clone(),
             )
             .await?;
-            self.index_resolver.dump(&temp_dump_path).await?;
+            match changed_indexes {
+                Some(uids) => {
+                    self.index_resolver
+                        .dump_indexes(&temp_dump_path, &uids)
+                        .await?
+                }
+                None => self.index_resolver.dump(&temp_dump_path).await?,
+            }
 
             let dump_path = self.dump_path.clone();
             let dump_path = tokio::task::spawn_blocking(move || -> Result<PathBuf> {
@@ -123,8 +164,93 @@ mod real {
 
             info!("Created dump in {:?}.", dump_path);
 
+            if let Some(ref storage) = self.object_storage {
+                let name = dump_path.file_name().unwrap_or_default().to_string_lossy();
+                storage
+                    .upload(&storage.key(ArchiveKind::Dump, &name), &dump_path)
+                    .await?;
+                tokio::fs::remove_file(&dump_path).await?;
+            }
+
             Ok(())
         }
+
+        /// Imports the dump read from `source`: registers, in a single group, the tasks
+        /// recreating its indexes. The indexes conflicting with an existing index are handled
+        /// according to the `conflict` policy.
+        pub async fn import(
+            &self,
+            source: &DumpImportSource,
+            conflict: ImportConflictPolicy,
+        ) -> Result<TaskResult> {
+            trace!("Importing dump.");
+
+            // the downloaded archive is removed once the dump is read.
+            let mut downloaded = None;
+            let archive = match source {
+                DumpImportSource::Path(path) => path.clone(),
+                DumpImportSource::Upload(uuid) => upload_path(&self.dump_path, *uuid),
+                DumpImportSource::Url(url) => {
+                    let file = download_dump(url, &self.import_allowed_hosts).await?;
+                    downloaded.insert(file).path().to_path_buf()
+                }
+            };
+
+            let dir = tokio::task::spawn_blocking(tempfile::TempDir::new).await??;
+            let dir_path = dir.path().to_path_buf();
+            let indexes =
+                tokio::task::spawn_blocking(move || read_dump(&archive, &dir_path)).await?;
+            drop(downloaded);
+            // an uploaded archive is only imported once, even if it is invalid.
+            if let DumpImportSource::Upload(uuid) = source {
+                tokio::fs::remove_file(upload_path(&self.dump_path, *uuid)).await?;
+            }
+            let indexes = indexes?;
+
+            let existing: HashSet<String> = self
+                .index_resolver
+                .list()
+                .await?
+                .into_iter()
+                .map(|(uid, _)| uid)
+                .collect();
+            let update_file_store = self.update_file_store.clone();
+            let (contents, imported) = tokio::task::spawn_blocking(move || {
+                let mut contents = Vec::new();
+                let imported = import_indexes(
+                    indexes,
+                    conflict,
+                    existing,
+                    &update_file_store,
+                    &mut contents,
+                );
+                // the extracted dump is no longer needed once the documents are copied.
+                drop(dir);
+                (contents, imported)
+            })
+            .await?;
+
+            let store = TaskStore::new(self.env.clone())?;
+            let registered = match imported {
+                Ok(imported) if contents.is_empty() => Ok((None, imported)),
+                Ok(imported) => match store.register_group(contents.clone()).await {
+                    Ok((group_uid, _)) => Ok((Some(group_uid), imported)),
+                    Err(e) => Err(DumpError::from(e)),
+                },
+                Err(e) => Err(e),
+            };
+
+            match registered {
+                Ok((group_uid, indexes)) => Ok(TaskResult::DumpImport { group_uid, indexes }),
+                Err(e) => {
+                    // the documents are only kept once the tasks adding them are registered.
+                    for uuid in contents.iter().filter_map(TaskContent::get_content_uuid) {
+                        let _ = self.update_file_store.delete(uuid).await;
+                    }
+                    Err(e)
+                }
+            }
+        }
     }
 }
 
@@ -137,8 +263,11 @@ mod test {
     use nelson::Mocker;
 
     use crate::dump::error::Result;
+    use crate::dump::{DumpImportSource, ImportConflictPolicy};
     use crate::index_resolver::IndexResolver;
     use crate::index_resolver::{index_store::IndexStore, meta_store::IndexMetaStore};
+    use crate::storage::ObjectStorage;
+    use crate::tasks::task::TaskResult;
     use crate::update_file_store::UpdateFileStore;
 
     use super::*;
@@ -159,6 +288,7 @@ mod test {
         U: IndexMetaStore + Sync + Send + 'static,
         I: IndexStore + Sync + Send + 'static,
     {
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             dump_path: PathBuf,
             db_path: PathBuf,
@@ -167,6 +297,8 @@ mod test {
             index_db_size: usize,
             env: Arc<Env>,
             index_resolver: Arc<IndexResolver<U, I>>,
+            object_storage: Option<ObjectStorage>,
+            import_allowed_hosts: Vec<String>,
         ) -> Self {
             Self::Real(super::real::DumpHandler::new(
                 dump_path,
@@ -176,12 +308,29 @@ mod test {
                 index_db_size,
                 env,
                 index_resolver,
+                object_storage,
+                import_allowed_hosts,
             ))
         }
-        pub async fn run(&self, uid: String) -> Result<()> {
+        pub async fn run(&self, uid: String, incremental_from: Option<String>) -> Result<()> {
+            match self {
+                DumpHandler::Real(real) => real.run(uid, incremental_from).await,
+                DumpHandler::Mock(mocker) => unsafe {
+                    mocker.get("run").call((uid, incremental_from))
+                },
+            }
+        }
+
+        pub async fn import(
+            &self,
+            source: &DumpImportSource,
+            conflict: ImportConflictPolicy,
+        ) -> Result<TaskResult> {
             match self {
-                DumpHandler::Real(real) => real.run(uid).await,
-                DumpHandler::Mock(mocker) => unsafe { mocker.get("run").call(uid) },
+                DumpHandler::Real(real) => real.import(source, conflict).await,
+                DumpHandler::Mock(mocker) => unsafe {
+                    mocker.get("import").call((source.clone(), conflict))
+                },
             }
         }
     }
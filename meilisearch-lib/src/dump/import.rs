@@ -0,0 +1,340 @@
+//! Import of a dump while the engine is running.
+//!
+//! Unlike `--import-dump`, which replaces the whole database at startup, importing a dump at
+//! runtime registers, for each index of the dump, the tasks recreating it: its creation, the
+//! update of its settings and the addition of its documents. The tasks and the keys of the dump
+//! are not imported.
+//!
+//! The import is itself a task: the archive is only read, and the tasks of its indexes
+//! registered, when the import is processed. The archives on the local filesystem must be in the
+//! dump directory, and the ones downloaded must be served by one of the allowed hosts.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use futures::StreamExt;
+use meilisearch_types::index_uid::IndexUid;
+use milli::update::IndexDocumentsMethod;
+use reqwest::{redirect, Url};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use super::error::{DumpError, Result};
+use super::{MetadataVersion, META_FILE_NAME};
+use crate::compression::from_tar_gz;
+use crate::document_formats::read_ndjson;
+use crate::index::DumpMeta;
+use crate::index_controller::Payload;
+use crate::index_resolver::meta_store::HeedMetaStore;
+use crate::tasks::task::TaskContent;
+use crate::update_file_store::UpdateFileStore;
+
+/// The directory, in the dump directory, where the uploaded archives are kept until they are
+/// imported.
+const UPLOADS_DIR: &str = "uploads";
+
+/// Where the archive of a dump to import is read from, as given when the import is registered.
+pub enum DumpSource {
+    /// A dump archive in the dump directory, the path is relative to it unless it is absolute.
+    Path(PathBuf),
+    /// A dump archive downloaded when the import is processed.
+    Url(String),
+    /// A dump archive streamed to the engine, it is kept in the dump directory until the import
+    /// is processed.
+    Payload(Payload),
+}
+
+/// Where the archive of a dump import task is read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum DumpImportSource {
+    /// The canonical path of an archive of the dump directory.
+    Path(#[cfg_attr(test, proptest(value = "PathBuf::from(\"dumps/dump.dump\")"))] PathBuf),
+    Url(String),
+    /// An uploaded archive, see [`upload_path`].
+    Upload(#[cfg_attr(test, proptest(value = "Uuid::new_v4()"))] Uuid),
+}
+
+/// What to do with an index of the imported dump when an index with the same uid exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum ImportConflictPolicy {
+    /// The index of the dump isn't imported.
+    Skip,
+    /// The existing index is deleted and replaced by the index of the dump.
+    Overwrite,
+    /// The index of the dump is imported under the first free uid `<uid>_<n>`.
+    Rename,
+}
+
+impl Default for ImportConflictPolicy {
+    fn default() -> Self {
+        Self::Skip
+    }
+}
+
+/// An index of an imported dump, `imported_as` is `None` when the index was skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedIndex {
+    pub uid: String,
+    pub imported_as: Option<String>,
+}
+
+/// An index read from the dump, along with the directory containing its documents.
+pub struct DumpedIndex {
+    pub uid: String,
+    pub path: PathBuf,
+    pub meta: DumpMeta,
+}
+
+/// Returns the path of the uploaded archive `uuid` in the dump directory `dump_path`.
+pub fn upload_path(dump_path: &Path, uuid: Uuid) -> PathBuf {
+    dump_path
+        .join(UPLOADS_DIR)
+        .join(uuid.to_string())
+        .with_extension("dump")
+}
+
+/// Returns the canonical path of the archive `path`, which must be in the dump directory
+/// `dump_path`. A relative `path` is relative to the dump directory.
+pub fn resolve_dump_path(dump_path: &Path, path: &Path) -> Result<PathBuf> {
+    let forbidden = || DumpError::ForbiddenDumpPath(path.display().to_string());
+    let dump_path = dump_path.canonicalize().map_err(|_| forbidden())?;
+    let resolved = dump_path
+        .join(path)
+        .canonicalize()
+        .map_err(|e| DumpError::InvalidDump(format!("`{}`: {}", path.display(), e)))?;
+    // the path is canonical, it can't go out of the dump directory through `..` or a symlink.
+    if resolved.starts_with(&dump_path) && resolved.is_file() {
+        Ok(resolved)
+    } else {
+        Err(forbidden())
+    }
+}
+
+/// Returns whether the host of `url` is one of the `allowed_hosts`, only the `http` and `https`
+/// URLs can be allowed.
+fn is_allowed_url(url: &Url, allowed_hosts: &[String]) -> bool {
+    matches!(url.scheme(), "http" | "https")
+        && url.host_str().map_or(false, |host| {
+            allowed_hosts
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(host))
+        })
+}
+
+/// Checks that the dump at `url` can be downloaded from one of the `allowed_hosts`.
+pub fn check_dump_url(url: &str, allowed_hosts: &[String]) -> Result<()> {
+    match Url::parse(url) {
+        Ok(parsed) if is_allowed_url(&parsed, allowed_hosts) => Ok(()),
+        _ => Err(DumpError::ForbiddenDumpUrl(url.to_string())),
+    }
+}
+
+/// Downloads the dump at `url` to a temporary file. The redirections are only followed to the
+/// `allowed_hosts`.
+pub async fn download_dump(url: &str, allowed_hosts: &[String]) -> Result<NamedTempFile> {
+    check_dump_url(url, allowed_hosts)?;
+    let error = |e: &dyn std::fmt::Display| DumpError::DumpDownload(url.to_string(), e.to_string());
+
+    let allowed = allowed_hosts.to_vec();
+    let client = reqwest::Client::builder()
+        .redirect(redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= 10 {
+                attempt.error("too many redirections")
+            } else if is_allowed_url(attempt.url(), &allowed) {
+                attempt.follow()
+            } else {
+                attempt.stop()
+            }
+        }))
+        .build()
+        .map_err(|e| error(&e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| error(&e))?;
+    // a redirection to a forbidden host stops with the response of the redirection.
+    if response.status().is_redirection() {
+        return Err(DumpError::ForbiddenDumpUrl(url.to_string()));
+    }
+
+    let archive = NamedTempFile::new()?;
+    let mut file = tokio::fs::File::from_std(archive.reopen()?);
+    let mut stream = response.bytes_stream();
+    while let Some(bytes) = stream.next().await {
+        file.write_all(&bytes.map_err(|e| error(&e))?).await?;
+    }
+    file.flush().await?;
+
+    Ok(archive)
+}
+
+/// Extracts the dump `archive` in `dst`, and returns the indexes it contains.
+pub fn read_dump(archive: &Path, dst: &Path) -> Result<Vec<DumpedIndex>> {
+    let invalid = |e: &dyn std::fmt::Display| DumpError::InvalidDump(e.to_string());
+
+    from_tar_gz(archive, dst).map_err(|e| invalid(&e))?;
+    let meta_file = File::open(dst.join(META_FILE_NAME)).map_err(|e| invalid(&e))?;
+    let meta: MetadataVersion = serde_json::from_reader(meta_file).map_err(|e| invalid(&e))?;
+    match meta {
+        MetadataVersion::V5(meta) if meta.incremental_from.is_some() => {
+            return Err(DumpError::InvalidDump(
+                "an incremental dump can't be imported on its own".to_string(),
+            ))
+        }
+        MetadataVersion::V5(_) => (),
+        meta => return Err(DumpError::UnsupportedImportVersion(meta.version())),
+    }
+
+    let mut indexes = Vec::new();
+    for entry in HeedMetaStore::dumped_indexes(dst).map_err(|e| invalid(&e))? {
+        let path = dst.join("indexes").join(entry.index_meta.uuid.to_string());
+        let meta = DumpMeta::read(&path).map_err(|e| invalid(&e))?;
+        indexes.push(DumpedIndex {
+            uid: entry.uid,
+            path,
+            meta,
+        });
+    }
+
+    Ok(indexes)
+}
+
+/// Pushes to `contents` the tasks recreating the `indexes` of an imported dump, and returns what
+/// became of each index. The documents of the indexes are written to the update file store, the
+/// update files of the `contents` must be removed if they aren't registered.
+pub fn import_indexes(
+    indexes: Vec<DumpedIndex>,
+    conflict: ImportConflictPolicy,
+    mut existing: HashSet<String>,
+    update_file_store: &UpdateFileStore,
+    contents: &mut Vec<TaskContent>,
+) -> Result<Vec<ImportedIndex>> {
+    let invalid = |e: &dyn std::fmt::Display| DumpError::InvalidDump(e.to_string());
+
+    let mut imported = Vec::with_capacity(indexes.len());
+    for index in indexes {
+        let (target, overwrite) = match resolve_conflict(&index.uid, conflict, &mut existing) {
+            Some(target) => target,
+            None => {
+                imported.push(ImportedIndex {
+                    uid: index.uid,
+                    imported_as: None,
+                });
+                continue;
+            }
+        };
+        let index_uid = IndexUid::from_str(&target).map_err(|e| invalid(&e))?;
+
+        if overwrite {
+            contents.push(TaskContent::IndexDeletion {
+                index_uid: index_uid.clone(),
+            });
+        }
+        contents.push(TaskContent::IndexCreation {
+            index_uid: index_uid.clone(),
+            primary_key: index.meta.primary_key.clone(),
+        });
+        contents.push(TaskContent::SettingsUpdate {
+            index_uid: index_uid.clone(),
+            settings: index.meta.settings,
+            is_deletion: false,
+            allow_index_creation: false,
+        });
+
+        let documents = File::open(DumpMeta::documents_path(&index.path))?;
+        if documents.metadata()?.len() > 0 {
+            let (content_uuid, mut update_file) = update_file_store.new_update()?;
+            let documents_count =
+                read_ndjson(documents, &mut *update_file).map_err(|e| invalid(&e))?;
+            update_file.persist()?;
+            contents.push(TaskContent::DocumentAddition {
+                index_uid,
+                content_uuid,
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                merge_patch: false,
+                primary_key: index.meta.primary_key,
+                documents_count,
+                allow_index_creation: false,
+            });
+        }
+
+        imported.push(ImportedIndex {
+            uid: index.uid,
+            imported_as: Some(target),
+        });
+    }
+
+    Ok(imported)
+}
+
+/// Returns the uid under which the index `uid` of a dump is imported, and whether the existing
+/// index must be deleted first. Returns `None` if the index is skipped. The `existing` indexes
+/// are updated with the returned uid.
+pub fn resolve_conflict(
+    uid: &str,
+    policy: ImportConflictPolicy,
+    existing: &mut HashSet<String>,
+) -> Option<(String, bool)> {
+    if !existing.contains(uid) {
+        existing.insert(uid.to_string());
+        return Some((uid.to_string(), false));
+    }
+
+    match policy {
+        ImportConflictPolicy::Skip => None,
+        ImportConflictPolicy::Overwrite => Some((uid.to_string(), true)),
+        ImportConflictPolicy::Rename => {
+            let renamed = (1..)
+                .map(|n| format!("{}_{}", uid, n))
+                .find(|renamed| !existing.contains(renamed))?;
+            existing.insert(renamed.clone());
+            Some((renamed, false))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn resolve_conflicts() {
+        let mut existing: HashSet<_> = ["movies", "movies_1"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            resolve_conflict("books", ImportConflictPolicy::Skip, &mut existing),
+            Some(("books".to_string(), false))
+        );
+        assert_eq!(
+            resolve_conflict("movies", ImportConflictPolicy::Skip, &mut existing),
+            None
+        );
+        assert_eq!(
+            resolve_conflict("movies", ImportConflictPolicy::Overwrite, &mut existing),
+            Some(("movies".to_string(), true))
+        );
+        assert_eq!(
+            resolve_conflict("movies", ImportConflictPolicy::Rename, &mut existing),
+            Some(("movies_2".to_string(), false))
+        );
+        assert_eq!(
+            resolve_conflict("movies", ImportConflictPolicy::Rename, &mut existing),
+            Some(("movies_3".to_string(), false))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Incremental dumps only contain the indexes modified since a previous dump, called their base.
+//!
+//! An incremental dump has the same layout as a full dump, except that its `indexes` directory
+//! only contains the indexes on which a task succeeded since its base was performed. The list of
+//! the indexes, the tasks, the update files and the keys are always dumped entirely.
+//!
+//! At import, a chain of incremental dumps is replayed on top of a full dump: each dump of the
+//! chain replaces the indexes it contains, and removes the indexes it doesn't list anymore.
+//!
+//! The modified indexes are found from the task history, so an incremental dump is refused once
+//! a task registered after its base was deleted. The retention of the finished tasks deletes
+//! the base before the tasks that follow it, which is detected as well.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use log::info;
+use meilisearch_auth::AuthController;
+use milli::heed::EnvOpenOptions;
+use milli::update::IndexerConfig;
+use tempfile::TempDir;
+use time::OffsetDateTime;
+
+use super::error::{DumpError, Result};
+use super::MetadataVersion;
+use crate::analytics;
+use crate::experimental_features;
+use crate::index::Index;
+use crate::index_resolver::meta_store::HeedMetaStore;
+use crate::options::IndexerOpts;
+use crate::tasks::task::{TaskContent, TaskEvent};
+use crate::tasks::{TaskFilter, TaskStore};
+use crate::update_file_store::UpdateFileStore;
+
+/// Returns the date at which the processing of the dump `uid` started. The dump must have
+/// succeeded, and none of the tasks registered after it can have been deleted since.
+pub async fn find_dump_start(store: &TaskStore, uid: &str) -> Result<OffsetDateTime> {
+    let searched = uid.to_string();
+    let mut filter = TaskFilter::default();
+    filter.filter_fn(move |task| {
+        matches!(&task.content, TaskContent::Dump { uid, .. } if *uid == searched)
+            && matches!(task.events.last(), Some(TaskEvent::Succeeded { .. }))
+    });
+
+    let (base_id, started_at) = store
+        .list_tasks(None, Some(filter), None)
+        .await?
+        .iter()
+        .find_map(|task| Some((task.id, task.started_at()?)))
+        .ok_or_else(|| DumpError::BaseDumpNotFound(uid.to_string()))?;
+
+    let mut filter = TaskFilter::default();
+    filter.filter_fn(move |task| {
+        matches!(
+            &task.content,
+            TaskContent::TaskDeletion { tasks, .. } if tasks.iter().any(|id| *id > base_id)
+        )
+    });
+    if !store.list_tasks(None, Some(filter), None).await?.is_empty() {
+        return Err(DumpError::PrunedTaskHistory(uid.to_string()));
+    }
+
+    Ok(started_at)
+}
+
+/// Returns the uids of the indexes on which a task succeeded since the dump `base` started.
+pub async fn changed_indexes(store: &TaskStore, base: &str) -> Result<HashSet<String>> {
+    let since = find_dump_start(store, base).await?;
+
+    let mut filter = TaskFilter::default();
+    filter.filter_fn(move |task| {
+        matches!(
+            task.events.last(),
+            Some(TaskEvent::Succeeded { timestamp, .. }) if *timestamp >= since
+        )
+    });
+
+    let mut changed = HashSet::new();
+    for task in store.list_tasks(None, Some(filter), None).await? {
+        match task.content {
+            // The swapped indexes are listed under a new uid, they must be dumped again.
+            TaskContent::IndexSwap { swaps } => {
+                for (left, right) in swaps {
+                    changed.insert(left.into_inner());
+                    changed.insert(right.into_inner());
+                }
+            }
+            TaskContent::IndexClone { destination, .. } => {
+                changed.insert(destination.into_inner());
+            }
+            _ => changed.extend(task.index_uid().map(String::from)),
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Loads the full dump extracted at `src` with its `meta`, and then replays the incremental
+/// dumps extracted in `incremental_dumps` on top of it.
+pub fn load_dump_chain(
+    meta: MetadataVersion,
+    src: &Path,
+    incremental_dumps: &[(TempDir, MetadataVersion)],
+    dst: &Path,
+    index_db_size: usize,
+    meta_env_size: usize,
+    indexing_options: &IndexerOpts,
+) -> anyhow::Result<()> {
+    let mut previous_uid = match meta {
+        MetadataVersion::V5(meta) if meta.incremental_from.is_none() => meta.dump_uid,
+        MetadataVersion::V5(_) => bail!("The dump to import is itself an incremental dump. Incremental dumps must be replayed on top of a full dump."),
+        meta => bail!(
+            "Incremental dumps can only be replayed on top of a dump of version V5, the dump to import is of version {}.",
+            meta.version()
+        ),
+    };
+
+    // The dumped indexes are stored in directories named after their uuid. We keep, for each
+    // index, the directory of the most recent dump containing it.
+    let mut indexes = dumped_indexes(src)?;
+
+    for (incremental_src, meta) in incremental_dumps {
+        let meta = match meta {
+            MetadataVersion::V5(meta) => meta,
+            meta => bail!(
+                "Incremental dumps must be of version V5, found an incremental dump of version {}.",
+                meta.version()
+            ),
+        };
+
+        let dump_uid = meta.dump_uid.as_deref().unwrap_or("unknown");
+        match (meta.incremental_from.as_deref(), previous_uid.as_deref()) {
+            (None, _) => bail!("The dump `{}` is not an incremental dump.", dump_uid),
+            (Some(from), Some(previous)) if from == previous => (),
+            (Some(from), previous) => bail!(
+                "The incremental dump `{}` must be replayed on top of the dump `{}`, but follows the dump `{}`.",
+                dump_uid,
+                from,
+                previous.unwrap_or("unknown"),
+            ),
+        }
+
+        info!("Replaying incremental dump {}.", dump_uid);
+
+        let listed: HashSet<OsString> = HeedMetaStore::dumped_uuids(incremental_src.path())?
+            .into_iter()
+            .map(|uuid| uuid.to_string().into())
+            .collect();
+        indexes.retain(|uuid, _| listed.contains(uuid));
+        indexes.extend(dumped_indexes(incremental_src.path())?);
+
+        if let Some(missing) = listed.iter().find(|uuid| !indexes.contains_key(*uuid)) {
+            bail!(
+                "The index `{}` listed in the incremental dump `{}` isn't present in any dump of the chain.",
+                missing.to_string_lossy(),
+                dump_uid
+            );
+        }
+
+        previous_uid = meta.dump_uid.clone();
+    }
+
+    // Everything but the indexes is loaded from the most recent dump.
+    let last_src = incremental_dumps
+        .last()
+        .map_or(src, |(incremental_src, _)| incremental_src.path());
+
+    let mut options = EnvOpenOptions::new();
+    options.map_size(meta_env_size);
+    options.max_dbs(100);
+    let env = Arc::new(options.open(&dst)?);
+
+    HeedMetaStore::load_dump(last_src, env.clone())?;
+    let indexer_config = IndexerConfig::try_from(indexing_options)?;
+    for index_path in indexes.values() {
+        Index::load_dump(index_path, &dst, index_db_size, &indexer_config)?;
+    }
+    UpdateFileStore::load_dump(last_src, &dst)?;
+    TaskStore::load_dump(last_src, env)?;
+    AuthController::load_dump(last_src, &dst)?;
+    analytics::copy_user_id(last_src, dst);
+    experimental_features::copy_features(last_src, dst)?;
+
+    info!("Loading indexes.");
+
+    Ok(())
+}
+
+/// Returns the directories of the indexes dumped in the dump extracted at `src`, by uuid.
+fn dumped_indexes(src: &Path) -> anyhow::Result<HashMap<OsString, PathBuf>> {
+    let indexes_path = src.join("indexes");
+    if !indexes_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let mut indexes = HashMap::new();
+    for entry in indexes_path
+        .read_dir()
+        .with_context(|| format!("invalid dump indexes: {}", indexes_path.display()))?
+    {
+        let entry = entry?;
+        indexes.insert(entry.file_name(), entry.path());
+    }
+
+    Ok(indexes)
+}
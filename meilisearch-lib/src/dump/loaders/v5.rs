@@ -6,6 +6,7 @@ use milli::heed::EnvOpenOptions;
 
 use crate::analytics;
 use crate::dump::Metadata;
+use crate::experimental_features;
 use crate::index_resolver::IndexResolver;
 use crate::options::IndexerOpts;
 use crate::tasks::TaskStore;
@@ -40,6 +41,7 @@ pub fn load_dump(
     TaskStore::load_dump(&src, env)?;
     AuthController::load_dump(&src, &dst)?;
     analytics::copy_user_id(src.as_ref(), dst.as_ref());
+    experimental_features::copy_features(src.as_ref(), dst.as_ref())?;
 
     info!("Loading indexes.");
 
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::bail;
 use log::info;
@@ -14,10 +14,17 @@ use crate::options::IndexerOpts;
 use self::loaders::{v2, v3, v4, v5};
 
 pub use handler::{generate_uid, DumpHandler};
+pub(crate) use import::{
+    check_dump_url, download_dump, import_indexes, read_dump, resolve_dump_path, upload_path,
+};
+pub use import::{DumpImportSource, DumpSource, ImportConflictPolicy, ImportedIndex};
+pub(crate) use incremental::find_dump_start;
 
 mod compat;
 pub mod error;
 mod handler;
+mod import;
+mod incremental;
 mod loaders;
 
 const META_FILE_NAME: &str = "metadata.json";
@@ -30,15 +37,28 @@ pub struct Metadata {
     update_db_size: usize,
     #[serde(with = "time::serde::rfc3339")]
     dump_date: OffsetDateTime,
+    /// The uid of the dump, only known for the dumps that can be followed by incremental dumps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dump_uid: Option<String>,
+    /// The uid of the dump an incremental dump must be applied on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    incremental_from: Option<String>,
 }
 
 impl Metadata {
-    pub fn new(index_db_size: usize, update_db_size: usize) -> Self {
+    pub fn new(
+        index_db_size: usize,
+        update_db_size: usize,
+        dump_uid: String,
+        incremental_from: Option<String>,
+    ) -> Self {
         Self {
             db_version: env!("CARGO_PKG_VERSION").to_string(),
             index_db_size,
             update_db_size,
             dump_date: OffsetDateTime::now_utc(),
+            dump_uid: Some(dump_uid),
+            incremental_from,
         }
     }
 }
@@ -110,8 +130,13 @@ impl MetadataVersion {
         Ok(())
     }
 
-    pub fn new_v5(index_db_size: usize, update_db_size: usize) -> Self {
-        let meta = Metadata::new(index_db_size, update_db_size);
+    pub fn new_v5(
+        index_db_size: usize,
+        update_db_size: usize,
+        dump_uid: String,
+        incremental_from: Option<String>,
+    ) -> Self {
+        let meta = Metadata::new(index_db_size, update_db_size, dump_uid, incremental_from);
         Self::V5(meta)
     }
 
@@ -151,9 +176,13 @@ pub enum DumpStatus {
     Failed,
 }
 
+/// Loads the dump at `src_path` in `dst_path`, and then replays the `incremental_dumps` on top
+/// of it, in order.
+#[allow(clippy::too_many_arguments)]
 pub fn load_dump(
     dst_path: impl AsRef<Path>,
     src_path: impl AsRef<Path>,
+    incremental_dumps: &[PathBuf],
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
     index_db_size: usize,
@@ -165,13 +194,29 @@ pub fn load_dump(
 
     if empty_db && src_path_exists {
         let (tmp_src, tmp_dst, meta) = extract_dump(&dst_path, &src_path)?;
-        meta.load_dump(
-            tmp_src.path(),
-            tmp_dst.path(),
-            index_db_size,
-            update_db_size,
-            indexer_opts,
-        )?;
+        if incremental_dumps.is_empty() {
+            meta.load_dump(
+                tmp_src.path(),
+                tmp_dst.path(),
+                index_db_size,
+                update_db_size,
+                indexer_opts,
+            )?;
+        } else {
+            let incremental_dumps = incremental_dumps
+                .iter()
+                .map(|path| extract_archive(&dst_path, path))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            incremental::load_dump_chain(
+                meta,
+                tmp_src.path(),
+                &incremental_dumps,
+                tmp_dst.path(),
+                index_db_size,
+                update_db_size,
+                indexer_opts,
+            )?;
+        }
         persist_dump(&dst_path, tmp_dst)?;
         Ok(())
     } else if !empty_db && !ignore_dump_if_db_exists {
@@ -194,22 +239,7 @@ fn extract_dump(
     dst_path: impl AsRef<Path>,
     src_path: impl AsRef<Path>,
 ) -> anyhow::Result<(TempDir, TempDir, MetadataVersion)> {
-    // Setup a temp directory path in the same path as the database, to prevent cross devices
-    // references.
-    let temp_path = dst_path
-        .as_ref()
-        .parent()
-        .map(ToOwned::to_owned)
-        .unwrap_or_else(|| ".".into());
-
-    let tmp_src = tempfile::tempdir_in(temp_path)?;
-    let tmp_src_path = tmp_src.path();
-
-    from_tar_gz(&src_path, tmp_src_path)?;
-
-    let meta_path = tmp_src_path.join(META_FILE_NAME);
-    let mut meta_file = File::open(&meta_path)?;
-    let meta: MetadataVersion = serde_json::from_reader(&mut meta_file)?;
+    let (tmp_src, meta) = extract_archive(&dst_path, src_path)?;
 
     if !dst_path.as_ref().exists() {
         std::fs::create_dir_all(dst_path.as_ref())?;
@@ -229,6 +259,31 @@ fn extract_dump(
     Ok((tmp_src, tmp_dst, meta))
 }
 
+/// Extracts the dump archive at `src_path` next to `dst_path`, and reads its metadata.
+fn extract_archive(
+    dst_path: impl AsRef<Path>,
+    src_path: impl AsRef<Path>,
+) -> anyhow::Result<(TempDir, MetadataVersion)> {
+    // Setup a temp directory path in the same path as the database, to prevent cross devices
+    // references.
+    let temp_path = dst_path
+        .as_ref()
+        .parent()
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| ".".into());
+
+    let tmp_src = tempfile::tempdir_in(temp_path)?;
+    let tmp_src_path = tmp_src.path();
+
+    from_tar_gz(&src_path, tmp_src_path)?;
+
+    let meta_path = tmp_src_path.join(META_FILE_NAME);
+    let mut meta_file = File::open(&meta_path)?;
+    let meta: MetadataVersion = serde_json::from_reader(&mut meta_file)?;
+
+    Ok((tmp_src, meta))
+}
+
 fn persist_dump(dst_path: impl AsRef<Path>, tmp_dst: TempDir) -> anyhow::Result<()> {
     let persisted_dump = tmp_dst.into_path();
 
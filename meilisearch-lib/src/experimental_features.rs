@@ -0,0 +1,126 @@
+//! The experimental features enabled at runtime with the `/experimental-features` route, in
+//! addition to the ones enabled by the startup options. They are persisted in the database
+//! directory and carried by the dumps and the snapshots.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+pub const FEATURES_FILE_NAME: &str = "experimental-features.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuntimeFeatures {
+    /// The `/metrics` route.
+    pub metrics: bool,
+    /// The `/es` routes, accepting a subset of the Elasticsearch API.
+    pub elasticsearch_compat: bool,
+    /// The counting of the searches by index, exposed by the `/indexes/{uid}/analytics` route.
+    pub search_analytics: bool,
+}
+
+/// The features to toggle, the missing ones are left untouched.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuntimeFeaturesUpdate {
+    pub metrics: Option<bool>,
+    pub elasticsearch_compat: Option<bool>,
+    pub search_analytics: Option<bool>,
+}
+
+impl RuntimeFeatures {
+    fn apply(mut self, update: RuntimeFeaturesUpdate) -> Self {
+        if let Some(metrics) = update.metrics {
+            self.metrics = metrics;
+        }
+        if let Some(elasticsearch_compat) = update.elasticsearch_compat {
+            self.elasticsearch_compat = elasticsearch_compat;
+        }
+        if let Some(search_analytics) = update.search_analytics {
+            self.search_analytics = search_analytics;
+        }
+        self
+    }
+}
+
+pub struct FeatureStore {
+    path: PathBuf,
+    features: RwLock<RuntimeFeatures>,
+}
+
+impl FeatureStore {
+    /// Reads the features persisted in the database at `db_path`, none are enabled in a new
+    /// database.
+    pub fn new(db_path: &Path) -> std::io::Result<Self> {
+        let path = db_path.join(FEATURES_FILE_NAME);
+        let features = match fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => RuntimeFeatures::default(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            path,
+            features: RwLock::new(features),
+        })
+    }
+
+    pub fn get(&self) -> RuntimeFeatures {
+        *self.features.read().unwrap()
+    }
+
+    /// Applies and persists the `update`, returns the new features.
+    pub fn update(&self, update: RuntimeFeaturesUpdate) -> std::io::Result<RuntimeFeatures> {
+        let mut features = self.features.write().unwrap();
+        let updated = features.apply(update);
+
+        // the file is replaced at once, to not be left half written.
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec(&updated)?)?;
+        fs::rename(&temp, &self.path)?;
+
+        *features = updated;
+        Ok(updated)
+    }
+}
+
+/// Copies the features persisted in the database or the dump at `src` to `dst`, if any.
+pub fn copy_features(src: &Path, dst: &Path) -> std::io::Result<()> {
+    match fs::copy(src.join(FEATURES_FILE_NAME), dst.join(FEATURES_FILE_NAME)) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn persist_the_features() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FeatureStore::new(dir.path()).unwrap();
+        assert_eq!(store.get(), RuntimeFeatures::default());
+
+        let update = RuntimeFeaturesUpdate {
+            metrics: Some(true),
+            ..Default::default()
+        };
+        let features = store.update(update).unwrap();
+        assert!(features.metrics);
+        assert!(!features.elasticsearch_compat);
+
+        let store = FeatureStore::new(dir.path()).unwrap();
+        assert_eq!(store.get(), features);
+
+        let dst = tempfile::tempdir().unwrap();
+        copy_features(dir.path(), dst.path()).unwrap();
+        assert_eq!(FeatureStore::new(dst.path()).unwrap().get(), features);
+        // nothing is copied from a database without features.
+        copy_features(tempfile::tempdir().unwrap().path(), dst.path()).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom};
+
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::error::Result;
+use super::index::Document;
+use crate::update_file_store::UpdateFileStore;
+
+/// A batch of documents written to a temporary file, so that the documents meilisearch derives
+/// from an addition or from the stored documents are never all kept in memory.
+pub(crate) struct BatchFile {
+    file: File,
+    builder: DocumentBatchBuilder<BufWriter<File>>,
+}
+
+impl BatchFile {
+    pub fn new() -> Result<Self> {
+        let file = tempfile::tempfile()?;
+        let builder = DocumentBatchBuilder::new(BufWriter::new(file.try_clone()?))?;
+        Ok(Self { file, builder })
+    }
+
+    pub fn push(&mut self, document: &impl Serialize) -> Result<()> {
+        self.builder
+            .extend_from_json(Cursor::new(serde_json::to_vec(document)?))?;
+        Ok(())
+    }
+
+    /// Returns the number of documents of the batch, along with a reader over them.
+    pub fn finish(self) -> Result<(usize, DocumentBatchReader<File>)> {
+        let count = self.builder.finish()?;
+        let mut file = self.file;
+        file.seek(SeekFrom::Start(0))?;
+        Ok((count, DocumentBatchReader::from_reader(file)?))
+    }
+}
+
+/// Calls `f` with each document of the update files `contents`, in the order they were sent.
+pub(crate) fn for_each_document(
+    file_store: &UpdateFileStore,
+    contents: &[Uuid],
+    mut f: impl FnMut(Document) -> Result<()>,
+) -> Result<()> {
+    for content_uuid in contents {
+        for content_file in file_store.get_update(*content_uuid)? {
+            let reader = DocumentBatchReader::from_reader(content_file)?;
+            for_each_document_of(reader, &mut f)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Calls `f` with each document of `reader`, in order.
+pub(crate) fn for_each_document_of<R: Read + Seek>(
+    mut reader: DocumentBatchReader<R>,
+    mut f: impl FnMut(Document) -> Result<()>,
+) -> Result<()> {
+    while let Some((index, document)) = reader.next_document_with_index()? {
+        let mut object = Document::new();
+        for (field_id, bytes) in document.iter() {
+            if let Some(name) = index.name(field_id) {
+                object.insert(name.to_string(), serde_json::from_slice(bytes)?);
+            }
+        }
+        f(object)?;
+    }
+
+    Ok(())
+}
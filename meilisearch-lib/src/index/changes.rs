@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+use std::ops::Bound;
+
+use milli::documents::DocumentBatchReader;
+use milli::heed::types::{ByteSlice, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::obkv_to_json;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::error::Result;
+use super::index::Index;
+use super::Document;
+use crate::update_file_store::UpdateFileStore;
+
+/// The prefix of the keys of the main database under which the changes of the documents are
+/// kept, by sequence number. Only the latest change of each document is kept. The engine doesn't
+/// know about these entries.
+const DOCUMENT_CHANGE_PREFIX: &str = "document-change-";
+/// The prefix of the keys holding the sequence number of the latest change of each document, by
+/// external id.
+const DOCUMENT_SEQUENCE_PREFIX: &str = "document-sequence-";
+/// The key holding the sequence number of the latest change of the index.
+const LAST_SEQUENCE_KEY: &str = "document-changes-last-sequence";
+
+pub const DEFAULT_CHANGES_LIMIT: fn() -> usize = || 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Upsert,
+    Deletion,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredChange {
+    kind: ChangeKind,
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    changed_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentChange {
+    pub sequence: u64,
+    pub kind: ChangeKind,
+    pub id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub changed_at: OffsetDateTime,
+    /// The current version of an upserted document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<Document>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentChanges {
+    pub results: Vec<DocumentChange>,
+    pub since: u64,
+    pub limit: usize,
+    /// The sequence number of the latest change of the index, the feed is exhausted once it is
+    /// reached.
+    pub last_sequence: u64,
+}
+
+/// The changes are sorted by sequence number when the keys are sorted lexicographically.
+fn change_key(sequence: u64) -> String {
+    format!("{}{:020}", DOCUMENT_CHANGE_PREFIX, sequence)
+}
+
+/// Returns the document id held by `id` if it is a string or a number, like the engine accepts.
+pub(super) fn document_id(id: Option<Value>) -> Option<String> {
+    match id? {
+        Value::String(id) => Some(id),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+impl Index {
+    fn changes_feed_enabled(&self) -> bool {
+        self.indexer_config.opts().experimental_enable_changes_feed
+    }
+
+    fn last_change_sequence(&self, rtxn: &RoTxn) -> Result<u64> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<u64>>(rtxn, LAST_SEQUENCE_KEY)?
+            .unwrap_or(0))
+    }
+
+    /// Returns at most `limit` changes of the documents made after the change `since`, in the
+    /// order they were made, along with the current version of the upserted documents.
+    pub fn document_changes(&self, since: u64, limit: usize) -> Result<DocumentChanges> {
+        let rtxn = self.read_txn()?;
+        let start = change_key(since.saturating_add(1));
+        let range = (Bound::Included(start.as_str()), Bound::Unbounded);
+
+        let mut changes = Vec::new();
+        // the values are decoded once their keys are checked, the entries following the changes
+        // hold other data.
+        for entry in self.main.range::<_, Str, ByteSlice, _>(&rtxn, &range)? {
+            let (key, value) = entry?;
+            let sequence = match key.strip_prefix(DOCUMENT_CHANGE_PREFIX) {
+                Some(sequence) if changes.len() < limit => sequence.parse().unwrap_or_default(),
+                _ => break,
+            };
+            let change: StoredChange = serde_json::from_slice(value)?;
+            changes.push((sequence, change));
+        }
+
+        let fields_ids_map = self.fields_ids_map(&rtxn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_ids = self.external_documents_ids(&rtxn)?;
+
+        let mut results = Vec::with_capacity(changes.len());
+        for (sequence, change) in changes {
+            let document = match external_ids.get(change.id.as_bytes()) {
+                Some(docid) if change.kind == ChangeKind::Upsert => self
+                    .documents(&rtxn, std::iter::once(docid))?
+                    .into_iter()
+                    .next()
+                    .map(|(_, obkv)| obkv_to_json(&all_fields, &fields_ids_map, obkv))
+                    .transpose()?,
+                _ => None,
+            };
+            results.push(DocumentChange {
+                sequence,
+                kind: change.kind,
+                id: change.id,
+                changed_at: change.changed_at,
+                document,
+            });
+        }
+
+        Ok(DocumentChanges {
+            results,
+            since,
+            limit,
+            last_sequence: self.last_change_sequence(&rtxn)?,
+        })
+    }
+
+    /// Appends the changes of the documents `ids` to the feed, forgetting their previous ones.
+    fn record_changes(&self, txn: &mut RwTxn, kind: ChangeKind, ids: Vec<String>) -> Result<()> {
+        let mut sequence = self.last_change_sequence(txn)?;
+        let changed_at = OffsetDateTime::now_utc();
+
+        for id in ids {
+            let sequence_key = format!("{}{}", DOCUMENT_SEQUENCE_PREFIX, id);
+            if let Some(previous) = self
+                .main
+                .get::<_, Str, SerdeJson<u64>>(txn, &sequence_key)?
+            {
+                self.main.delete::<_, Str>(txn, &change_key(previous))?;
+            }
+
+            sequence += 1;
+            let change = StoredChange {
+                kind,
+                id,
+                changed_at,
+            };
+            self.main
+                .put::<_, Str, SerdeJson<_>>(txn, &change_key(sequence), &change)?;
+            self.main
+                .put::<_, Str, SerdeJson<u64>>(txn, &sequence_key, &sequence)?;
+        }
+
+        self.main
+            .put::<_, Str, SerdeJson<u64>>(txn, LAST_SEQUENCE_KEY, &sequence)?;
+
+        Ok(())
+    }
+
+    /// Records the deletion of the documents `docids`, it must be called before they are deleted.
+    pub(crate) fn record_deletions(&self, txn: &mut RwTxn, docids: &RoaringBitmap) -> Result<()> {
+        if !self.changes_feed_enabled() || docids.is_empty() {
+            return Ok(());
+        }
+
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let primary_key = match self
+            .primary_key(txn)?
+            .and_then(|primary_key| fields_ids_map.id(primary_key))
+        {
+            Some(primary_key) => primary_key,
+            None => return Ok(()),
+        };
+
+        let mut ids = Vec::with_capacity(docids.len() as usize);
+        for (_, obkv) in self.documents(txn, docids.iter())? {
+            let id = obkv
+                .get(primary_key)
+                .and_then(|bytes| serde_json::from_slice(bytes).ok());
+            ids.extend(document_id(id));
+        }
+
+        self.record_changes(txn, ChangeKind::Deletion, ids)
+    }
+
+    /// Records the upsert of the documents of the update files `contents`, except the `rejected`
+    /// ones, it must be called once they are added.
+    pub(crate) fn record_upserts(
+        &self,
+        txn: &mut RwTxn,
+        file_store: &UpdateFileStore,
+        contents: &[Uuid],
+        rejected: &[String],
+    ) -> Result<()> {
+        if !self.changes_feed_enabled() {
+            return Ok(());
+        }
+
+        let mut ids = self.content_document_ids(txn, file_store, contents)?;
+        if !rejected.is_empty() {
+            let rejected: HashSet<_> = rejected.iter().collect();
+            ids.retain(|id| !rejected.contains(id));
+        }
+
+        self.record_upserted_ids(txn, ids)
+    }
+
+    /// Returns the ids of the documents of the update files `contents`.
+    pub(crate) fn content_document_ids(
+        &self,
+        txn: &RoTxn,
+        file_store: &UpdateFileStore,
+        contents: &[Uuid],
+    ) -> Result<Vec<String>> {
+        let primary_key = match self.primary_key(txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut ids = Vec::new();
+        for content_uuid in contents {
+            for content_file in file_store.get_update(*content_uuid)? {
+                let mut reader = DocumentBatchReader::from_reader(content_file)?;
+                while let Some((index, document)) = reader.next_document_with_index()? {
+                    let id = document
+                        .iter()
+                        .find(|(field_id, _)| index.name(*field_id) == Some(&primary_key))
+                        .and_then(|(_, bytes)| serde_json::from_slice(bytes).ok());
+                    ids.extend(document_id(id));
+                }
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Records the upsert of the documents `ids`, it must be called once they are added.
+    pub(crate) fn record_upserted_ids(&self, txn: &mut RwTxn, ids: Vec<String>) -> Result<()> {
+        if !self.changes_feed_enabled() || ids.is_empty() {
+            return Ok(());
+        }
+
+        self.record_changes(txn, ChangeKind::Upsert, ids)
+    }
+}
@@ -0,0 +1,169 @@
+use fst::IntoStreamer;
+use milli::heed::RoTxn;
+use milli::tokenizer::TokenizerBuilder;
+use roaring::RoaringBitmap;
+
+use super::error::Result;
+use super::index::Index;
+
+/// The maximum number of corrected queries returned in a search result.
+const MAX_SUGGESTIONS: usize = 3;
+
+impl Index {
+    /// Returns up to three corrected versions of the query `q`, in which the words that are not
+    /// indexed are replaced by indexed words within the typo tolerance of the index.
+    ///
+    /// Only the corrections whose words all appear together in one of the `candidates` are
+    /// returned. For each word, the closest corrections are preferred, then the most frequent.
+    pub(crate) fn query_corrections(
+        &self,
+        rtxn: &RoTxn,
+        q: &str,
+        candidates: &RoaringBitmap,
+    ) -> Result<Vec<String>> {
+        if !self.authorize_typos(rtxn)? {
+            return Ok(Vec::new());
+        }
+
+        let words_fst = self.words_fst(rtxn)?;
+
+        let tokenizer = TokenizerBuilder::default().build();
+        let words: Vec<_> = tokenizer
+            .tokenize(q)
+            .filter(|token| token.is_word())
+            .map(|token| token.lemma().to_string())
+            .collect();
+
+        let mut misspelled = false;
+        let mut corrections = Vec::with_capacity(words.len());
+        for word in words {
+            if words_fst.contains(&word) {
+                corrections.push(vec![word]);
+                continue;
+            }
+            misspelled = true;
+
+            let max_typos = self.max_typos(rtxn, &word)?;
+            let mut closest = Vec::new();
+            for indexed in same_first_letter(&words_fst, &word)? {
+                let distance = levenshtein(&word, &indexed);
+                if distance > max_typos {
+                    continue;
+                }
+                let count = match self.word_docids.get(rtxn, &indexed)? {
+                    Some(docids) => (&docids & candidates).len(),
+                    None => 0,
+                };
+                if count > 0 {
+                    closest.push((distance, count, indexed));
+                }
+            }
+
+            if closest.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            closest.sort_by(|(ld, lc, _), (rd, rc, _)| ld.cmp(rd).then(rc.cmp(lc)));
+            corrections.push(
+                closest
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|(_, _, word)| word)
+                    .collect(),
+            );
+        }
+
+        if !misspelled {
+            return Ok(Vec::new());
+        }
+
+        let mut suggestions = Vec::new();
+        for i in 0..MAX_SUGGESTIONS {
+            let words: Vec<&str> = corrections
+                .iter()
+                .map(|words| words[i.min(words.len() - 1)].as_str())
+                .collect();
+
+            let mut docids = candidates.clone();
+            for word in &words {
+                docids &= self.word_docids.get(rtxn, word)?.unwrap_or_default();
+            }
+
+            let suggestion = words.join(" ");
+            if !docids.is_empty() && !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+        }
+
+        Ok(suggestions)
+    }
+}
+
+impl Index {
+    /// Returns how many typos the engine accepts in the `word` of a query.
+    pub(crate) fn max_typos(&self, rtxn: &RoTxn, word: &str) -> Result<usize> {
+        if !self.authorize_typos(rtxn)? {
+            return Ok(0);
+        }
+
+        let len = word.chars().count();
+        Ok(if len >= self.min_word_len_two_typos(rtxn)? as usize {
+            2
+        } else if len >= self.min_word_len_one_typo(rtxn)? as usize {
+            1
+        } else {
+            0
+        })
+    }
+}
+
+/// Returns the indexed words starting with the same letter as the `word`. The typos on the first
+/// letter are too costly to look for, like the engine does.
+pub(crate) fn same_first_letter<D: AsRef<[u8]>>(
+    words_fst: &fst::Set<D>,
+    word: &str,
+) -> Result<Vec<String>> {
+    let first = match word.chars().next() {
+        Some(first) => first,
+        None => return Ok(Vec::new()),
+    };
+    let mut range = words_fst.range().ge(first.to_string());
+    if let Some(next) = char::from_u32(first as u32 + 1) {
+        range = range.lt(next.to_string());
+    }
+    Ok(range.into_stream().into_strs()?)
+}
+
+/// Computes the levenshtein distance between two words.
+pub(crate) fn levenshtein(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    // `row[j]` is the distance between the current prefix of `left` and the first `j` chars of
+    // `right`.
+    let mut row: Vec<usize> = (0..=right.len()).collect();
+
+    for (i, lc) in left.chars().enumerate() {
+        let mut previous_diagonal = row[0];
+        row[0] = i + 1;
+        for (j, rc) in right.iter().enumerate() {
+            let substitution = previous_diagonal + (lc != *rc) as usize;
+            previous_diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[right.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("hello", "hello"), 0);
+        assert_eq!(levenshtein("helo", "hello"), 1);
+        assert_eq!(levenshtein("hlelo", "hello"), 2);
+        assert_eq!(levenshtein("world", "hello"), 4);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+}
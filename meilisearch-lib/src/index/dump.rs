@@ -1,6 +1,6 @@
 use std::fs::{create_dir_all, File};
 use std::io::{BufReader, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use indexmap::IndexMap;
@@ -10,15 +10,34 @@ use milli::update::{IndexDocumentsConfig, IndexerConfig};
 use serde::{Deserialize, Serialize};
 
 use crate::document_formats::read_ndjson;
-use crate::index::updates::apply_settings_to_builder;
+use crate::index::expiration::all_document_expirations;
+use crate::index::facet_trigrams::index_facet_trigrams;
+use crate::index::field_presence::index_field_presence;
+use crate::index::updates::{apply_settings_to_builder, apply_settings_to_index};
+use crate::index::vector::{take_vectors, AddedVectors, VECTORS_FIELD_NAME};
 
 use super::error::Result;
-use super::{index::Index, Settings, Unchecked};
+use super::{index::Index, Settings, SideStore, Unchecked};
 
+/// The settings and primary key of a dumped index.
 #[derive(Serialize, Deserialize)]
-struct DumpMeta {
-    settings: Settings<Unchecked>,
-    primary_key: Option<String>,
+pub struct DumpMeta {
+    pub settings: Settings<Unchecked>,
+    pub primary_key: Option<String>,
+}
+
+impl DumpMeta {
+    /// Reads the meta of the index dumped in the directory `index_dump`.
+    pub fn read(index_dump: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let meta_file = File::open(index_dump.as_ref().join(META_FILE_NAME))?;
+        Ok(serde_json::from_reader(meta_file)?)
+    }
+
+    /// Returns the path of the NDJSON file containing the documents of the index dumped in the
+    /// directory `index_dump`.
+    pub fn documents_path(index_dump: impl AsRef<Path>) -> PathBuf {
+        index_dump.as_ref().join(DATA_FILE_NAME)
+    }
 }
 
 const META_FILE_NAME: &str = "meta.json";
@@ -44,17 +63,23 @@ impl Index {
 
         let documents = self.all_documents(txn)?;
         let fields_ids_map = self.fields_ids_map(txn)?;
+        let side_txn = self.side.env.read_txn()?;
 
         // dump documents
         let mut json_map = IndexMap::new();
         for document in documents {
-            let (_, reader) = document?;
+            let (docid, reader) = document?;
 
             for (fid, bytes) in reader.iter() {
                 if let Some(name) = fields_ids_map.name(fid) {
                     json_map.insert(name, serde_json::from_slice::<serde_json::Value>(bytes)?);
                 }
             }
+            // the documents are dumped with their embeddings, as they were sent.
+            let embeddings = self.document_embeddings(&side_txn, docid)?;
+            if !embeddings.is_empty() {
+                json_map.insert(VECTORS_FIELD_NAME, serde_json::json!(embeddings));
+            }
 
             serde_json::to_writer(&mut document_file, &json_map)?;
             document_file.write_all(b"\n")?;
@@ -106,6 +131,7 @@ impl Index {
         let mut options = EnvOpenOptions::new();
         options.map_size(size);
         let index = milli::Index::new(options, &dst_dir_path)?;
+        let side = SideStore::open(&dst_dir_path, size)?;
 
         let mut txn = index.write_txn()?;
 
@@ -120,6 +146,8 @@ impl Index {
 
         builder.execute(|_| ())?;
 
+        apply_settings_to_index(&settings, &index, &mut txn)?;
+
         let document_file_path = src.as_ref().join(DATA_FILE_NAME);
         let reader = BufReader::new(File::open(&document_file_path)?);
 
@@ -132,10 +160,15 @@ impl Index {
             Err(e) => return Err(e.into()),
         };
 
+        let mut vectors = AddedVectors::default();
         if !empty {
             tmp_doc_file.seek(SeekFrom::Start(0))?;
 
             let documents_reader = DocumentBatchReader::from_reader(tmp_doc_file)?;
+            let primary_key = index.primary_key(&txn)?.map(String::from);
+            let (readers, taken) =
+                take_vectors(primary_key.as_deref(), vec![documents_reader], false)?;
+            vectors = taken;
 
             //If the document file is empty, we don't perform the document addition, to prevent
             //a primary key error to be thrown.
@@ -147,11 +180,19 @@ impl Index {
                 config,
                 |_| (),
             )?;
-            builder.add_documents(documents_reader)?;
+            for reader in readers {
+                builder.add_documents(reader)?;
+            }
             builder.execute()?;
         }
 
+        index_field_presence(&index, &mut txn)?;
+        index_facet_trigrams(&index, &mut txn)?;
+        let expirations = all_document_expirations(&index, &txn)?;
+
         txn.commit()?;
+        vectors.store(&index, &side)?;
+        expirations.store(&side)?;
         index.prepare_for_closing().wait();
 
         Ok(())
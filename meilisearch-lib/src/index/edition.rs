@@ -0,0 +1,94 @@
+use log::info;
+use milli::obkv_to_json;
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde_json::Value;
+
+use super::batch_file::BatchFile;
+use super::changes::document_id;
+use super::error::{IndexError, Result};
+use super::expiration::document_expirations;
+use super::filter::parse_filter;
+use super::index::{Document, Index};
+
+/// The maximum number of operations a function can perform on a single document, this prevents
+/// a function from looping forever.
+const MAX_OPERATIONS_PER_DOCUMENT: u64 = 1_000_000;
+
+fn compile(function: &str) -> Result<(Engine, AST)> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS_PER_DOCUMENT);
+    let ast = engine
+        .compile(function)
+        .map_err(|e| IndexError::DocumentEdition(e.to_string()))?;
+    Ok((engine, ast))
+}
+
+/// Checks that the rhai `function` compiles, so that an invalid function is rejected before its
+/// edition is registered.
+pub fn check_edition_function(function: &str) -> Result<()> {
+    compile(function).map(drop)
+}
+
+impl Index {
+    /// Applies the rhai `function` to every document matching `filter`, and replaces the
+    /// documents that it modified, in a single transaction. The function reads and modifies the
+    /// current document through the `doc` variable. Returns the number of modified documents.
+    pub fn edit_documents(&self, filter: Option<&Value>, function: &str) -> Result<u64> {
+        let (engine, ast) = compile(function)?;
+
+        let mut txn = self.write_txn()?;
+        let candidates = match filter.map(parse_filter).transpose()?.flatten() {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => self.documents_ids(&txn)?,
+        };
+
+        let primary_key = self.primary_key(&txn)?.map(String::from);
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut edited = BatchFile::new()?;
+        let mut ids = Vec::new();
+        for (_id, obkv) in self.documents(&txn, candidates.iter())? {
+            let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+
+            let mut scope = Scope::new();
+            scope.push("doc", to_dynamic(&document).map_err(edition_error)?);
+            engine
+                .run_ast_with_scope(&mut scope, &ast)
+                .map_err(edition_error)?;
+
+            let new_document: Dynamic = scope.get_value("doc").unwrap_or_default();
+            let new_document: Document = from_dynamic(&new_document).map_err(edition_error)?;
+            if new_document != document {
+                let id = primary_key
+                    .as_ref()
+                    .and_then(|primary_key| new_document.get(primary_key).cloned());
+                ids.extend(document_id(id));
+                edited.push(&new_document)?;
+            }
+        }
+
+        let (count, reader) = edited.finish()?;
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let (addition, vectors) = self.replace_documents_txn(&mut txn, reader)?;
+        let expirations = document_expirations(self, &txn, &ids)?;
+        self.record_upserted_ids(&mut txn, ids)?;
+
+        txn.commit()?;
+        vectors.store(self, &self.side)?;
+        expirations.store(&self.side)?;
+        self.retain_trash()?;
+
+        info!("document edition done: {:?}", addition);
+
+        Ok(addition.indexed_documents)
+    }
+}
+
+fn edition_error(error: Box<rhai::EvalAltResult>) -> IndexError {
+    IndexError::DocumentEdition(error.to_string())
+}
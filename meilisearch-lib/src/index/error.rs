@@ -18,6 +18,26 @@ pub enum IndexError {
     Facet(#[from] FacetError),
     #[error("{0}")]
     Milli(#[from] milli::Error),
+    #[error("The document edition function failed: `{0}`.")]
+    DocumentEdition(String),
+    #[error("Attribute `{0}` is not filterable. Available filterable attributes are: `{}`.", .1.join(", "))]
+    InvalidFacetSearchFacetName(String, Vec<String>),
+    #[error("Invalid hybrid search: {0}.")]
+    InvalidHybridQuery(String),
+    #[error("Attribute `{0}` is not searchable. Available searchable attributes are: `{}`.", .1.join(", "))]
+    InvalidAttributesToSearchOn(String, Vec<String>),
+    #[error("Attribute `{0}` is not filterable and thus, cannot be used as distinct attribute. Available filterable attributes are: `{}`.", .1.join(", "))]
+    InvalidSearchDistinct(String, Vec<String>),
+    #[error("Invalid attribute weights: {0}.")]
+    InvalidSearchAttributeWeights(String),
+    #[error("Ranking profile `{0}` not found. Available ranking profiles are: `{}`.", .1.join(", "))]
+    InvalidSearchRankingProfile(String, Vec<String>),
+    /// An attribute the search rules of the API key restrict, used by the search parameter.
+    #[error("Attribute `{0}` can't be used in `{1}` with this API key.")]
+    RestrictedSearchAttribute(String, &'static str),
+    /// The update was stopped before committing its changes, because its task was canceled.
+    #[error("The update was aborted because its task was canceled.")]
+    Aborted,
 }
 
 internal_error!(
@@ -32,10 +52,27 @@ internal_error!(
 impl ErrorCode for IndexError {
     fn error_code(&self) -> Code {
         match self {
-            IndexError::Internal(_) => Code::Internal,
+            IndexError::Internal(_) | IndexError::Aborted => Code::Internal,
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
             IndexError::Facet(e) => e.error_code(),
             IndexError::Milli(e) => MilliError(e).error_code(),
+            IndexError::DocumentEdition(_) => Code::InvalidDocumentEditionFunction,
+            IndexError::InvalidFacetSearchFacetName(_, _) => Code::InvalidFacetSearchFacetName,
+            IndexError::InvalidHybridQuery(_) => Code::InvalidSearchHybridQuery,
+            IndexError::InvalidAttributesToSearchOn(_, _) => {
+                Code::InvalidSearchAttributesToSearchOn
+            }
+            IndexError::InvalidSearchDistinct(_, _) => Code::InvalidSearchDistinct,
+            IndexError::InvalidSearchAttributeWeights(_) => Code::InvalidSearchAttributeWeights,
+            IndexError::InvalidSearchRankingProfile(_, _) => Code::InvalidSearchRankingProfile,
+            IndexError::RestrictedSearchAttribute(_, parameter) => match *parameter {
+                "filter" => Code::Filter,
+                "sort" => Code::Sort,
+                "attributesToSearchOn" => Code::InvalidSearchAttributesToSearchOn,
+                "distinct" => Code::InvalidSearchDistinct,
+                "attributeWeights" => Code::InvalidSearchAttributeWeights,
+                _ => Code::BadRequest,
+            },
         }
     }
 }
@@ -0,0 +1,416 @@
+use milli::heed::byteorder::BE;
+use milli::heed::types::{DecodeIgnore, SerdeJson, Str};
+use milli::heed::zerocopy::U32;
+use milli::heed::RoTxn;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::error::Result;
+use super::index::{Index, EXPIRY_ATTRIBUTE_KEY};
+use super::side_store::{ExpirationKey, SideStore};
+use crate::update_file_store::UpdateFileStore;
+
+type BEU32 = U32<BE>;
+
+/// The key of the main database under which the previous versions stored the expiry dates of all
+/// the documents, in a single entry. They are moved to the side store when the index is opened.
+const LEGACY_DOCUMENT_EXPIRATIONS_KEY: &str = "document-expirations";
+
+/// Returns the key of the document `docid` expiring at the `expires_at` timestamp in the
+/// expirations database. The keys sort by expiry date, then by internal id.
+fn expiration_key(expires_at: i64, docid: u32) -> ExpirationKey {
+    let mut key = [0; 12];
+    // the sign bit is flipped so that the dates before the epoch sort first.
+    key[..8].copy_from_slice(&((expires_at as u64) ^ (1 << 63)).to_be_bytes());
+    key[8..].copy_from_slice(&docid.to_be_bytes());
+    key
+}
+
+fn decode_expiration_key(key: &ExpirationKey) -> (i64, u32) {
+    let mut expires_at = [0; 8];
+    expires_at.copy_from_slice(&key[..8]);
+    let mut docid = [0; 4];
+    docid.copy_from_slice(&key[8..]);
+    (
+        (u64::from_be_bytes(expires_at) ^ (1 << 63)) as i64,
+        u32::from_be_bytes(docid),
+    )
+}
+
+/// The expiry dates of the documents changed by an update, read in its transaction. They are
+/// stored in the side store once the transaction is committed, like the embeddings.
+#[derive(Debug, Default)]
+pub(crate) struct ChangedExpirations {
+    /// Whether the expiry dates of all the documents are given, the other ones are forgotten.
+    all: bool,
+    /// The expiry date of each changed document, `None` if it doesn't expire anymore.
+    expirations: Vec<(u32, Option<i64>)>,
+}
+
+impl ChangedExpirations {
+    /// Stores the expiry dates in the `side` store, only the keys of the changed documents are
+    /// written.
+    pub fn store(self, side: &SideStore) -> Result<()> {
+        let mut side_txn = side.env.write_txn()?;
+        if self.all {
+            side.expirations.clear(&mut side_txn)?;
+            side.expiration_dates.clear(&mut side_txn)?;
+        } else if self.expirations.is_empty() {
+            return Ok(());
+        }
+
+        for (docid, expires_at) in self.expirations {
+            let id = BEU32::new(docid);
+            if let Some(previous) = side.expiration_dates.get(&side_txn, &id)? {
+                side.expirations
+                    .delete(&mut side_txn, &expiration_key(previous, docid))?;
+            }
+            match expires_at {
+                Some(expires_at) => {
+                    side.expirations
+                        .put(&mut side_txn, &expiration_key(expires_at, docid), &())?;
+                    side.expiration_dates.put(&mut side_txn, &id, &expires_at)?;
+                }
+                None => {
+                    side.expiration_dates.delete(&mut side_txn, &id)?;
+                }
+            }
+        }
+        side_txn.commit()?;
+
+        Ok(())
+    }
+}
+
+/// Returns the field holding the expiry date of the documents of the `index`, if any.
+fn expiry_field_id(index: &milli::Index, txn: &RoTxn) -> Result<Option<u16>> {
+    let attribute = index
+        .main
+        .get::<_, Str, SerdeJson<String>>(txn, EXPIRY_ATTRIBUTE_KEY)?;
+    match attribute {
+        Some(attribute) => Ok(index.fields_ids_map(txn)?.id(&attribute)),
+        None => Ok(None),
+    }
+}
+
+/// Reads the expiry date of the stored document `docid`.
+fn document_expiration(
+    index: &milli::Index,
+    txn: &RoTxn,
+    field_id: u16,
+    docid: u32,
+) -> Result<Option<i64>> {
+    Ok(index
+        .documents(txn, Some(docid))?
+        .first()
+        .and_then(|(_, obkv)| obkv.get(field_id))
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+        .and_then(|value| parse_expiry(&value)))
+}
+
+/// Reads the expiry dates of all the documents of the `index`, it must be called once the
+/// expiry attribute changed.
+pub(crate) fn all_document_expirations(
+    index: &milli::Index,
+    txn: &RoTxn,
+) -> Result<ChangedExpirations> {
+    let mut changed = ChangedExpirations {
+        all: true,
+        expirations: Vec::new(),
+    };
+    if let Some(field_id) = expiry_field_id(index, txn)? {
+        for entry in index.all_documents(txn)? {
+            let (docid, obkv) = entry?;
+            let expires_at = obkv
+                .get(field_id)
+                .and_then(|bytes| serde_json::from_slice(bytes).ok())
+                .and_then(|value| parse_expiry(&value));
+            if let Some(expires_at) = expires_at {
+                changed.expirations.push((docid, Some(expires_at)));
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Reads the expiry dates of the documents `ids` of `txn`, once they are indexed.
+pub(crate) fn document_expirations<I>(
+    index: &milli::Index,
+    txn: &RoTxn,
+    ids: I,
+) -> Result<ChangedExpirations>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut changed = ChangedExpirations::default();
+    let field_id = match expiry_field_id(index, txn)? {
+        Some(field_id) => field_id,
+        None => return Ok(changed),
+    };
+
+    let external_ids = index.external_documents_ids(txn)?;
+    for id in ids {
+        // the documents rejected by the engine have no internal id.
+        if let Some(docid) = external_ids.get(id.as_ref().as_bytes()) {
+            let expires_at = document_expiration(index, txn, field_id, docid)?;
+            changed.expirations.push((docid, expires_at));
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Moves the expiry dates stored by the previous versions in the main database of the `index`
+/// to its `side` store.
+pub(crate) fn migrate_document_expirations(index: &milli::Index, side: &SideStore) -> Result<()> {
+    let rtxn = index.read_txn()?;
+    if index
+        .main
+        .get::<_, Str, DecodeIgnore>(&rtxn, LEGACY_DOCUMENT_EXPIRATIONS_KEY)?
+        .is_none()
+    {
+        return Ok(());
+    }
+    all_document_expirations(index, &rtxn)?.store(side)?;
+    drop(rtxn);
+
+    let mut txn = index.write_txn()?;
+    index
+        .main
+        .delete::<_, Str>(&mut txn, LEGACY_DOCUMENT_EXPIRATIONS_KEY)?;
+    txn.commit()?;
+
+    Ok(())
+}
+
+impl Index {
+    /// Returns the documents whose expiry date is reached at `now`. They are ignored by the
+    /// searches until they are swept from the index.
+    pub(crate) fn expired_documents(&self, now: OffsetDateTime) -> Result<RoaringBitmap> {
+        let side_txn = self.side.env.read_txn()?;
+        let end = expiration_key(now.unix_timestamp().saturating_add(1), 0);
+        let mut expired = RoaringBitmap::new();
+        for entry in self.side.expirations.range(&side_txn, &(..end))? {
+            let (key, ()) = entry?;
+            expired.insert(decode_expiration_key(&key).1);
+        }
+        Ok(expired)
+    }
+
+    /// Returns when the next document of the index expires, after `now`.
+    pub fn next_document_expiration(&self, now: OffsetDateTime) -> Result<Option<OffsetDateTime>> {
+        let side_txn = self.side.env.read_txn()?;
+        let start = expiration_key(now.unix_timestamp().saturating_add(1), 0);
+        let next = match self.side.expirations.range(&side_txn, &(start..))?.next() {
+            Some(entry) => decode_expiration_key(&entry?.0).0,
+            None => return Ok(None),
+        };
+        Ok(OffsetDateTime::from_unix_timestamp(next).ok())
+    }
+
+    /// Forgets the expiry dates of the documents `docids`, which are about to be deleted. They
+    /// are forgotten before the deletion is committed, so that the ids the engine reuses never
+    /// get the expiry date of a deleted document.
+    pub(crate) fn forget_expirations(&self, docids: &RoaringBitmap) -> Result<()> {
+        ChangedExpirations {
+            all: false,
+            expirations: docids.iter().map(|docid| (docid, None)).collect(),
+        }
+        .store(&self.side)
+    }
+
+    /// Reads the expiry dates of the documents of the update files `contents`, once they are
+    /// indexed in `txn`. Nothing is read when the index has no expiry attribute.
+    pub(crate) fn content_expirations(
+        &self,
+        txn: &RoTxn,
+        file_store: &UpdateFileStore,
+        contents: &[Uuid],
+    ) -> Result<ChangedExpirations> {
+        if expiry_field_id(self, txn)?.is_none() {
+            return Ok(ChangedExpirations::default());
+        }
+        let ids = self.content_document_ids(txn, file_store, contents)?;
+        document_expirations(self, txn, ids)
+    }
+
+    /// Returns the external ids of the documents that expired, to delete them.
+    pub fn expired_documents_ids(&self) -> Result<Vec<String>> {
+        let rtxn = self.read_txn()?;
+        let expired = self.expired_documents(OffsetDateTime::now_utc())?;
+        if expired.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let fields_ids_map = self.fields_ids_map(&rtxn)?;
+        let primary_key = match self
+            .primary_key(&rtxn)?
+            .and_then(|primary_key| fields_ids_map.id(primary_key))
+        {
+            Some(primary_key) => primary_key,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut ids = Vec::with_capacity(expired.len() as usize);
+        // the engine may already have reused the id of a deleted document.
+        let documents_ids = self.documents_ids(&rtxn)?;
+        for (_, obkv) in self.documents(&rtxn, (expired & documents_ids).iter())? {
+            let id = obkv
+                .get(primary_key)
+                .and_then(|bytes| serde_json::from_slice(bytes).ok());
+            match id {
+                Some(Value::String(id)) => ids.push(id),
+                Some(Value::Number(id)) => ids.push(id.to_string()),
+                _ => (),
+            }
+        }
+
+        Ok(ids)
+    }
+}
+
+/// Reads an expiry date given either as an RFC 3339 string or as a number of seconds since the
+/// unix epoch. The documents with any other value never expire.
+fn parse_expiry(value: &Value) -> Option<i64> {
+    match value {
+        Value::String(date) => OffsetDateTime::parse(date, &Rfc3339)
+            .ok()
+            .map(|date| date.unix_timestamp()),
+        Value::Number(number) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|secs| secs.floor() as i64)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Seek, SeekFrom};
+
+    use milli::documents::DocumentBatchReader;
+    use milli::heed::{EnvOpenOptions, RwTxn};
+    use milli::update::{IndexDocuments, IndexDocumentsConfig, IndexerConfig};
+    use serde_json::json;
+
+    use super::*;
+    use crate::document_formats::read_ndjson;
+
+    #[test]
+    fn test_parse_expiry() {
+        assert_eq!(
+            parse_expiry(&json!("2022-06-01T00:00:00Z")),
+            Some(1654041600)
+        );
+        assert_eq!(
+            parse_expiry(&json!("2022-06-01T02:00:00+02:00")),
+            Some(1654041600)
+        );
+        assert_eq!(parse_expiry(&json!(1654041600)), Some(1654041600));
+        assert_eq!(parse_expiry(&json!(1654041600.9)), Some(1654041600));
+        assert_eq!(parse_expiry(&json!("tomorrow")), None);
+        assert_eq!(parse_expiry(&json!(null)), None);
+        assert_eq!(parse_expiry(&json!([1654041600])), None);
+    }
+
+    #[test]
+    fn expiration_keys_sort_by_date() {
+        let keys = [
+            expiration_key(-10, 3),
+            expiration_key(0, 7),
+            expiration_key(1654041600, 1),
+            expiration_key(1654041600, 2),
+        ];
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(decode_expiration_key(&keys[0]), (-10, 3));
+        assert_eq!(decode_expiration_key(&keys[2]), (1654041600, 1));
+    }
+
+    fn add_documents(index: &milli::Index, txn: &mut RwTxn, documents: Vec<Value>) {
+        let ndjson: String = documents.iter().map(|d| format!("{}\n", d)).collect();
+        let mut file = tempfile::tempfile().unwrap();
+        read_ndjson(Cursor::new(ndjson), &mut file).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let config = IndexerConfig::default();
+        let mut builder =
+            IndexDocuments::new(txn, index, &config, IndexDocumentsConfig::default(), |_| ())
+                .unwrap();
+        builder
+            .add_documents(DocumentBatchReader::from_reader(file).unwrap())
+            .unwrap();
+        builder.execute().unwrap();
+    }
+
+    #[test]
+    fn only_the_changed_documents_are_touched() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(100 * 1024 * 1024);
+        let index = milli::Index::new(options, dir.path()).unwrap();
+        let side = SideStore::open(dir.path(), 100 * 1024 * 1024).unwrap();
+
+        let mut txn = index.write_txn().unwrap();
+        index
+            .main
+            .put::<_, Str, SerdeJson<_>>(&mut txn, EXPIRY_ATTRIBUTE_KEY, &"expiresAt")
+            .unwrap();
+        // the even documents expired, the odd ones expire in the future.
+        let documents = (0..10_000)
+            .map(|id| {
+                let expires_at: i64 = if id % 2 == 0 { 1000 } else { 4000000000 };
+                json!({ "id": id, "expiresAt": expires_at })
+            })
+            .collect();
+        add_documents(&index, &mut txn, documents);
+        let changed = all_document_expirations(&index, &txn).unwrap();
+        txn.commit().unwrap();
+        assert_eq!(changed.expirations.len(), 10_000);
+        changed.store(&side).unwrap();
+
+        // a second batch only reads the expiry dates of its documents.
+        let mut txn = index.write_txn().unwrap();
+        add_documents(
+            &index,
+            &mut txn,
+            vec![
+                json!({ "id": 0, "expiresAt": 4000000000i64 }),
+                json!({ "id": 1, "expiresAt": "2022-06-01T00:00:00Z" }),
+                json!({ "id": 2 }),
+            ],
+        );
+        let changed = document_expirations(&index, &txn, ["0", "1", "2"]).unwrap();
+        txn.commit().unwrap();
+        assert_eq!(changed.expirations.len(), 3);
+        changed.store(&side).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let external_ids = index.external_documents_ids(&rtxn).unwrap();
+        let docid = |id: &str| external_ids.get(id).unwrap();
+        let side_txn = side.env.read_txn().unwrap();
+        let end = expiration_key(OffsetDateTime::now_utc().unix_timestamp() + 1, 0);
+        let expired: RoaringBitmap = side
+            .expirations
+            .range(&side_txn, &(..end))
+            .unwrap()
+            .map(|entry| decode_expiration_key(&entry.unwrap().0).1)
+            .collect();
+
+        // 5000 expired documents, minus the 0 and the 2, plus the 1.
+        assert_eq!(expired.len(), 4999);
+        assert!(!expired.contains(docid("0")));
+        assert!(expired.contains(docid("1")));
+        assert!(!expired.contains(docid("2")));
+        assert_eq!(
+            side.expiration_dates
+                .get(&side_txn, &BEU32::new(docid("1")))
+                .unwrap(),
+            Some(1654041600)
+        );
+        assert_eq!(side.expirations.len(&side_txn).unwrap(), 9_999);
+    }
+}
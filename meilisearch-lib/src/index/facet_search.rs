@@ -0,0 +1,182 @@
+use std::time::Instant;
+
+use milli::DEFAULT_VALUES_PER_FACET;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::error::{IndexError, Result};
+use super::filter::parse_filter;
+use super::index::Index;
+use super::search::AttributeRestrictions;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FacetSearchQuery {
+    pub facet_name: String,
+    pub facet_query: Option<String>,
+    pub q: Option<String>,
+    pub filter: Option<Value>,
+    /// The attributes whose values may be returned, set from the search rules.
+    #[serde(skip)]
+    pub attribute_restrictions: Option<AttributeRestrictions>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetValueHit {
+    pub value: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetSearchResult {
+    pub facet_hits: Vec<FacetValueHit>,
+    pub facet_query: Option<String>,
+    pub processing_time_ms: u128,
+}
+
+impl Index {
+    /// Searches the values of the `facet_name` facet that start with the `facet_query`, allowing
+    /// typos according to the typo tolerance settings of the index. Only the values of the
+    /// documents matching the main query and the filter are considered, and they are returned
+    /// sorted by decreasing number of documents.
+    pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let mut filterable_fields = self.filterable_fields(&rtxn)?;
+        // the restricted attributes are handled as if they weren't filterable.
+        if let Some(ref restrictions) = query.attribute_restrictions {
+            filterable_fields.retain(|field| restrictions.allows(field));
+        }
+        if !filterable_fields.contains(&query.facet_name) {
+            return Err(IndexError::InvalidFacetSearchFacetName(
+                query.facet_name,
+                filterable_fields.into_iter().collect(),
+            ));
+        }
+
+        let filter = match query.filter {
+            Some(ref filter) => parse_filter(filter)?,
+            None => None,
+        };
+
+        let mut search = self.search(&rtxn);
+        if let Some(ref q) = query.q {
+            search.query(q);
+        }
+        if let Some(ref filter) = filter {
+            if let Some(engine_filter) = filter.engine_filter()? {
+                search.filter(engine_filter);
+            }
+        }
+        search.limit(0);
+        let mut candidates = search.execute()?.candidates;
+        if let Some(filter) = filter.as_ref().filter(|filter| filter.is_extended()) {
+            candidates &= filter.evaluate(&rtxn, self)?;
+        }
+        // the query can't match the documents through the restricted attributes.
+        if let (Some(restrictions), Some(q)) = (&query.attribute_restrictions, &query.q) {
+            if !q.trim().is_empty() {
+                if let Some(fields) = self.allowed_searched_fields(&rtxn, None, restrictions)? {
+                    candidates &= self.documents_matching_in_attributes(&rtxn, q, &fields)?;
+                }
+            }
+        }
+
+        let mut distribution = self
+            .facets_distribution(&rtxn)
+            .facets(std::iter::once(&query.facet_name))
+            .max_values_per_facet(usize::MAX)
+            .candidates(candidates)
+            .execute()?;
+        let values = distribution.remove(&query.facet_name).unwrap_or_default();
+
+        let max_typos = match query.facet_query {
+            Some(ref facet_query) if self.authorize_typos(&rtxn)? => {
+                let len = facet_query.chars().count();
+                if len >= self.min_word_len_two_typos(&rtxn)? as usize {
+                    2
+                } else if len >= self.min_word_len_one_typo(&rtxn)? as usize {
+                    1
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        };
+
+        let facet_query = query.facet_query.as_ref().map(|q| q.to_lowercase());
+        let mut facet_hits: Vec<_> = values
+            .into_iter()
+            .filter(|(value, _)| match facet_query {
+                Some(ref facet_query) => matches_facet_query(value, facet_query, max_typos),
+                None => true,
+            })
+            .map(|(value, count)| FacetValueHit { value, count })
+            .collect();
+
+        // `sort_by` is stable, values with the same count stay in lexicographic order.
+        facet_hits.sort_by(|left, right| right.count.cmp(&left.count));
+
+        let max_values_per_facet = self
+            .max_values_per_facet(&rtxn)?
+            .unwrap_or(DEFAULT_VALUES_PER_FACET);
+        facet_hits.truncate(max_values_per_facet);
+
+        Ok(FacetSearchResult {
+            facet_hits,
+            facet_query: query.facet_query,
+            processing_time_ms: before_search.elapsed().as_millis(),
+        })
+    }
+}
+
+/// Returns whether one of the words of the facet value starts with the (lowercased)
+/// `facet_query`, with at most `max_typos` typos.
+fn matches_facet_query(value: &str, facet_query: &str, max_typos: usize) -> bool {
+    let value = value.to_lowercase();
+    let query: Vec<char> = facet_query.chars().collect();
+    // the whole value is tried too, so queries containing several words can match.
+    std::iter::once(value.as_str())
+        .chain(value.split_whitespace())
+        .any(|word| prefix_distance(&query, word) <= max_typos)
+}
+
+/// Computes the smallest levenshtein distance between the `query` and any prefix of `word`.
+fn prefix_distance(query: &[char], word: &str) -> usize {
+    // `row[i]` is the distance between the first `i` chars of the query and the current prefix.
+    let mut row: Vec<usize> = (0..=query.len()).collect();
+    let mut best = row[query.len()];
+
+    for (j, wc) in word.chars().enumerate() {
+        let mut previous_diagonal = row[0];
+        row[0] = j + 1;
+        for (i, qc) in query.iter().enumerate() {
+            let substitution = previous_diagonal + (*qc != wc) as usize;
+            previous_diagonal = row[i + 1];
+            row[i + 1] = substitution.min(row[i] + 1).min(row[i + 1] + 1);
+        }
+        best = best.min(row[query.len()]);
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_matches_facet_query() {
+        assert!(matches_facet_query("Science Fiction", "sci", 0));
+        assert!(matches_facet_query("Science Fiction", "fict", 0));
+        assert!(matches_facet_query("Science Fiction", "science fi", 0));
+        assert!(!matches_facet_query("Science Fiction", "scr", 0));
+        assert!(matches_facet_query("Science Fiction", "scr", 1));
+        assert!(matches_facet_query("Adventure", "adventrue", 2));
+        assert!(!matches_facet_query("Adventure", "horror", 2));
+        assert!(matches_facet_query("Adventure", "", 0));
+    }
+}
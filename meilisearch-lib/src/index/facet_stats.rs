@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use milli::heed::types::ByteSlice;
+use milli::heed::{BytesDecode, RoTxn};
+use milli::heed_codec::facet::FacetLevelValueF64Codec;
+use roaring::RoaringBitmap;
+use serde::Serialize;
+
+use super::error::Result;
+use super::index::Index;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FacetStats {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Index {
+    /// Returns the smallest and the largest numbers of the `fields` in the `candidates`. The
+    /// fields without any number in the candidates are left out.
+    pub(crate) fn facet_stats<'a>(
+        &self,
+        rtxn: &RoTxn,
+        fields: impl IntoIterator<Item = &'a String>,
+        candidates: &RoaringBitmap,
+    ) -> Result<BTreeMap<String, FacetStats>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let numbers = self.facet_id_f64_docids.remap_key_type::<ByteSlice>();
+
+        let mut stats = BTreeMap::new();
+        for field in fields {
+            let field_id = match fields_ids_map.id(field) {
+                Some(field_id) => field_id,
+                None => continue,
+            };
+            // the level 0 of the facet database holds every number of the field, in order.
+            let mut prefix = field_id.to_be_bytes().to_vec();
+            prefix.push(0);
+
+            let mut min = None;
+            for entry in numbers.prefix_iter(rtxn, &prefix)? {
+                let (key, docids) = entry?;
+                if !docids.is_disjoint(candidates) {
+                    min = FacetLevelValueF64Codec::bytes_decode(key).map(|(_, _, value, _)| value);
+                    break;
+                }
+            }
+            let mut max = None;
+            for entry in numbers.rev_prefix_iter(rtxn, &prefix)? {
+                let (key, docids) = entry?;
+                if !docids.is_disjoint(candidates) {
+                    max = FacetLevelValueF64Codec::bytes_decode(key).map(|(_, _, value, _)| value);
+                    break;
+                }
+            }
+
+            if let (Some(min), Some(max)) = (min, max) {
+                stats.insert(field.clone(), FacetStats { min, max });
+            }
+        }
+
+        Ok(stats)
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use milli::heed::types::{ByteSlice, DecodeIgnore, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::{is_faceted_by, FieldId};
+use roaring::RoaringBitmap;
+
+use super::error::Result;
+use super::field_presence::ensure_filterable;
+
+/// The prefix of the keys of the main database under which the string values of the filterable
+/// attributes are stored by trigram. The engine doesn't know about these entries, they are
+/// handled by meilisearch.
+const FACET_TRIGRAM_PREFIX: &str = "facet-trigram-";
+/// The key of the main database under which the attributes whose trigrams are indexed are stored.
+const FACET_TRIGRAMS_FIELDS_KEY: &str = "facet-trigrams-fields";
+
+fn trigram_key(field: &str, trigram: &str) -> String {
+    format!("{}{}-{}", FACET_TRIGRAM_PREFIX, field, trigram)
+}
+
+fn trigrams(value: &str) -> BTreeSet<String> {
+    let chars: Vec<_> = value.chars().collect();
+    chars
+        .windows(3)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+fn indexed_fields(index: &milli::Index, rtxn: &RoTxn) -> Result<BTreeSet<String>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<BTreeSet<String>>>(rtxn, FACET_TRIGRAMS_FIELDS_KEY)?
+        .unwrap_or_default())
+}
+
+/// Calls `f`, in order, with the string values of the field starting with `prefix` and the
+/// documents containing them. The values are normalized by the engine.
+fn for_each_value(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    field_id: FieldId,
+    prefix: &str,
+    mut f: impl FnMut(&str, RoaringBitmap),
+) -> Result<()> {
+    // the level 0 of the facet database holds every string of the field, in order.
+    let mut key_prefix = field_id.to_be_bytes().to_vec();
+    key_prefix.push(0);
+    let value_start = key_prefix.len();
+    key_prefix.extend_from_slice(prefix.as_bytes());
+
+    let strings = index.facet_id_string_docids.remap_key_type::<ByteSlice>();
+    for entry in strings.prefix_iter(rtxn, &key_prefix)? {
+        let (key, (_original, docids)) = entry?;
+        if let Ok(value) = std::str::from_utf8(&key[value_start..]) {
+            f(value, docids);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the documents in which a string of the filterable `field` starts with `prefix`. Like
+/// for the equality, the comparison is case insensitive.
+pub(crate) fn starts_with(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    field: &str,
+    prefix: &str,
+) -> Result<RoaringBitmap> {
+    ensure_filterable(index, rtxn, field)?;
+    let field_id = match index.fields_ids_map(rtxn)?.id(field) {
+        Some(field_id) => field_id,
+        None => return Ok(RoaringBitmap::new()),
+    };
+
+    let mut docids = RoaringBitmap::new();
+    for_each_value(
+        index,
+        rtxn,
+        field_id,
+        &prefix.to_lowercase(),
+        |_, value_docids| docids |= value_docids,
+    )?;
+
+    Ok(docids)
+}
+
+/// Returns the documents in which a string of the filterable `field` contains `needle`. Like for
+/// the equality, the comparison is case insensitive.
+///
+/// The values sharing all the trigrams of the needle are checked, the whole vocabulary of the
+/// field is only read for the needles shorter than a trigram and the indexes created before the
+/// trigrams were indexed.
+pub(crate) fn contains(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    field: &str,
+    needle: &str,
+) -> Result<RoaringBitmap> {
+    ensure_filterable(index, rtxn, field)?;
+    let field_id = match index.fields_ids_map(rtxn)?.id(field) {
+        Some(field_id) => field_id,
+        None => return Ok(RoaringBitmap::new()),
+    };
+    let needle = needle.to_lowercase();
+    let needle_trigrams = trigrams(&needle);
+
+    let mut docids = RoaringBitmap::new();
+    if needle_trigrams.is_empty() || !indexed_fields(index, rtxn)?.contains(field) {
+        for_each_value(index, rtxn, field_id, "", |value, value_docids| {
+            if value.contains(&needle) {
+                docids |= value_docids;
+            }
+        })?;
+        return Ok(docids);
+    }
+
+    let mut candidates: Option<BTreeSet<String>> = None;
+    for trigram in &needle_trigrams {
+        let values = index
+            .main
+            .get::<_, Str, SerdeJson<BTreeSet<String>>>(rtxn, &trigram_key(field, trigram))?
+            .unwrap_or_default();
+        let values = match candidates {
+            Some(candidates) => candidates.intersection(&values).cloned().collect(),
+            None => values,
+        };
+        let done = values.is_empty();
+        candidates = Some(values);
+        if done {
+            break;
+        }
+    }
+
+    // sharing the trigrams doesn't mean containing the needle, and the values of the deleted
+    // documents are only removed from the trigrams on the next indexation.
+    for value in candidates.iter().flatten() {
+        if !value.contains(&needle) {
+            continue;
+        }
+        if let Some((_original, value_docids)) = index
+            .facet_id_string_docids
+            .get(rtxn, &(field_id, value.as_str()))?
+        {
+            docids |= value_docids;
+        }
+    }
+
+    Ok(docids)
+}
+
+/// Indexes the trigrams of the string values of every filterable attribute of the `index`, it
+/// must be called once the documents or the filterable attributes changed. The vocabulary of the
+/// filterable attributes is read, not the documents.
+pub fn index_facet_trigrams(index: &milli::Index, txn: &mut RwTxn) -> Result<()> {
+    let keys = index
+        .main
+        .prefix_iter::<_, Str, DecodeIgnore>(txn, FACET_TRIGRAM_PREFIX)?
+        .map(|entry| entry.map(|(key, _)| key.to_string()))
+        .collect::<milli::heed::Result<Vec<_>>>()?;
+    for key in keys {
+        index.main.delete::<_, Str>(txn, &key)?;
+    }
+
+    let filterable = index.filterable_fields(txn)?;
+    let fields_ids_map = index.fields_ids_map(txn)?;
+    let mut fields = BTreeSet::new();
+    for (field_id, field) in fields_ids_map.iter() {
+        if !filterable.iter().any(|f| is_faceted_by(field, f)) {
+            continue;
+        }
+
+        let mut trigram_values: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for_each_value(index, txn, field_id, "", |value, _| {
+            for trigram in trigrams(value) {
+                trigram_values
+                    .entry(trigram)
+                    .or_default()
+                    .insert(value.to_string());
+            }
+        })?;
+        for (trigram, values) in &trigram_values {
+            index
+                .main
+                .put::<_, Str, SerdeJson<_>>(txn, &trigram_key(field, trigram), values)?;
+        }
+        fields.insert(field.to_string());
+    }
+    index
+        .main
+        .put::<_, Str, SerdeJson<_>>(txn, FACET_TRIGRAMS_FIELDS_KEY, &fields)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn value_trigrams() {
+        assert_eq!(
+            trigrams("/admin/"),
+            BTreeSet::from(["/ad", "adm", "dmi", "min", "in/"].map(String::from))
+        );
+        assert_eq!(
+            trigrams("né-é"),
+            BTreeSet::from(["né-", "é-é"].map(String::from))
+        );
+        assert!(trigrams("ab").is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use milli::heed::types::{SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::heed_codec::RoaringBitmapCodec;
+use milli::{is_faceted_by, UserError};
+use roaring::RoaringBitmap;
+use serde_json::{Map, Value};
+
+use super::error::Result;
+
+/// The key of the main database under which the filterable attributes whose presence is indexed
+/// are stored. The engine doesn't know about these entries, they are handled by meilisearch.
+const FIELD_PRESENCE_FIELDS_KEY: &str = "field-presence-fields";
+
+/// The documents in which a filterable attribute is present, `null` or empty.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct FieldPresence {
+    /// The documents containing the attribute, whatever its value.
+    pub exists: RoaringBitmap,
+    /// The documents in which the attribute is `null`.
+    pub null: RoaringBitmap,
+    /// The documents in which the attribute is an empty string, array or object.
+    pub empty: RoaringBitmap,
+}
+
+impl FieldPresence {
+    fn read(index: &milli::Index, rtxn: &RoTxn, field: &str) -> Result<Self> {
+        let get = |kind| -> Result<RoaringBitmap> {
+            Ok(index
+                .main
+                .get::<_, Str, RoaringBitmapCodec>(rtxn, &presence_key(kind, field))?
+                .unwrap_or_default())
+        };
+        Ok(Self {
+            exists: get("exists")?,
+            null: get("null")?,
+            empty: get("empty")?,
+        })
+    }
+
+    fn write(&self, index: &milli::Index, txn: &mut RwTxn, field: &str) -> Result<()> {
+        for (kind, docids) in [
+            ("exists", &self.exists),
+            ("null", &self.null),
+            ("empty", &self.empty),
+        ] {
+            index.main.put::<_, Str, RoaringBitmapCodec>(
+                txn,
+                &presence_key(kind, field),
+                docids,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn delete(index: &milli::Index, txn: &mut RwTxn, field: &str) -> Result<()> {
+        for kind in ["exists", "null", "empty"] {
+            index
+                .main
+                .delete::<_, Str>(txn, &presence_key(kind, field))?;
+        }
+        Ok(())
+    }
+}
+
+fn presence_key(kind: &str, field: &str) -> String {
+    format!("field-presence-{}-{}", kind, field)
+}
+
+fn indexed_fields(index: &milli::Index, rtxn: &RoTxn) -> Result<BTreeSet<String>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<BTreeSet<String>>>(rtxn, FIELD_PRESENCE_FIELDS_KEY)?
+        .unwrap_or_default())
+}
+
+/// Returns an invalid filter error when the `field` isn't filterable.
+pub(crate) fn ensure_filterable(index: &milli::Index, rtxn: &RoTxn, field: &str) -> Result<()> {
+    let filterable = index.filterable_fields(rtxn)?;
+    if !filterable.iter().any(|f| is_faceted_by(field, f)) {
+        let mut filterable: Vec<_> = filterable.into_iter().collect();
+        filterable.sort_unstable();
+        let message = format!(
+            "Attribute `{}` is not filterable. Available filterable attributes are: `{}`.",
+            field,
+            filterable.join(", ")
+        );
+        return Err(milli::Error::from(UserError::InvalidFilter(message)).into());
+    }
+
+    Ok(())
+}
+
+/// Returns the documents in which the filterable `field` is present, `null` or empty.
+///
+/// The presence of the filterable attributes is indexed, the attributes nested in a filterable
+/// object and the indexes created before the presence was indexed are read from the documents.
+pub(crate) fn field_presence(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    field: &str,
+) -> Result<FieldPresence> {
+    ensure_filterable(index, rtxn, field)?;
+
+    if indexed_fields(index, rtxn)?.contains(field) {
+        FieldPresence::read(index, rtxn, field)
+    } else {
+        let fields = BTreeSet::from([field.to_string()]);
+        let mut presences = compute_field_presence(index, rtxn, &fields)?;
+        Ok(presences.remove(field).unwrap_or_default())
+    }
+}
+
+/// Indexes the presence of every filterable attribute of the `index`, it must be called once the
+/// documents or the filterable attributes changed. All the documents are read.
+pub fn index_field_presence(index: &milli::Index, txn: &mut RwTxn) -> Result<()> {
+    let fields: BTreeSet<_> = index.filterable_fields(txn)?.into_iter().collect();
+    let presences = compute_field_presence(index, txn, &fields)?;
+
+    for field in indexed_fields(index, txn)? {
+        FieldPresence::delete(index, txn, &field)?;
+    }
+    for (field, presence) in &presences {
+        presence.write(index, txn, field)?;
+    }
+    index
+        .main
+        .put::<_, Str, SerdeJson<_>>(txn, FIELD_PRESENCE_FIELDS_KEY, &fields)?;
+
+    Ok(())
+}
+
+/// Removes the deleted documents from the indexed presence of the attributes.
+pub fn retain_field_presence(index: &milli::Index, txn: &mut RwTxn) -> Result<()> {
+    let documents_ids = index.documents_ids(txn)?;
+    for field in indexed_fields(index, txn)? {
+        let mut presence = FieldPresence::read(index, txn, &field)?;
+        presence.exists &= &documents_ids;
+        presence.null &= &documents_ids;
+        presence.empty &= &documents_ids;
+        presence.write(index, txn, &field)?;
+    }
+
+    Ok(())
+}
+
+fn compute_field_presence(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    fields: &BTreeSet<String>,
+) -> Result<BTreeMap<String, FieldPresence>> {
+    let mut presences: BTreeMap<_, _> = fields
+        .iter()
+        .map(|field| (field.clone(), FieldPresence::default()))
+        .collect();
+    if fields.is_empty() {
+        return Ok(presences);
+    }
+
+    let fields_ids_map = index.fields_ids_map(rtxn)?;
+    // only the top-level attributes containing one of the fields are read.
+    let read_fields: BTreeSet<_> = fields_ids_map
+        .iter()
+        .filter(|(_, name)| fields.iter().any(|field| is_faceted_by(field, name)))
+        .map(|(id, _)| id)
+        .collect();
+
+    let mut document = Map::new();
+    for entry in index.all_documents(rtxn)? {
+        let (docid, obkv) = entry?;
+        document.clear();
+        for (fid, bytes) in obkv.iter() {
+            if let Some(name) = fields_ids_map
+                .name(fid)
+                .filter(|_| read_fields.contains(&fid))
+            {
+                document.insert(name.to_string(), serde_json::from_slice(bytes)?);
+            }
+        }
+
+        for (field, presence) in presences.iter_mut() {
+            let mut values = Vec::new();
+            field_values(&document, field, &mut values);
+            if !values.is_empty() {
+                presence.exists.insert(docid);
+            }
+            if values.iter().any(|value| value.is_null()) {
+                presence.null.insert(docid);
+            }
+            if values.iter().any(|value| is_empty(value)) {
+                presence.empty.insert(docid);
+            }
+        }
+    }
+
+    Ok(presences)
+}
+
+/// Collects the values of the `field` in the `object`, the nested attributes are reached through
+/// the objects and the arrays of objects, like for the filters.
+fn field_values<'a>(object: &'a Map<String, Value>, field: &str, values: &mut Vec<&'a Value>) {
+    for (key, value) in object {
+        if key == field {
+            values.push(value);
+        } else if let Some(rest) = field
+            .strip_prefix(key.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            nested_values(value, rest, values);
+        }
+    }
+}
+
+fn nested_values<'a>(value: &'a Value, field: &str, values: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(object) => field_values(object, field, values),
+        Value::Array(array) => array
+            .iter()
+            .for_each(|value| nested_values(value, field, values)),
+        _ => (),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.is_empty(),
+        Value::Array(array) => array.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn nested_field_values() {
+        let document = json!({
+            "title": "",
+            "doggos": [{ "name": "bobby", "age": null }, { "name": "buddy" }],
+            "info.tags": [],
+        });
+        let document = document.as_object().unwrap();
+
+        let mut values = Vec::new();
+        field_values(document, "doggos.age", &mut values);
+        assert_eq!(values, vec![&Value::Null]);
+
+        let mut values = Vec::new();
+        field_values(document, "doggos.name", &mut values);
+        assert_eq!(values, vec![&json!("bobby"), &json!("buddy")]);
+
+        let mut values = Vec::new();
+        field_values(document, "info.tags", &mut values);
+        assert!(is_empty(values[0]));
+        assert!(is_empty(&document["title"]));
+
+        let mut values = Vec::new();
+        field_values(document, "doggos.color", &mut values);
+        assert!(values.is_empty());
+    }
+}
@@ -0,0 +1,854 @@
+use either::Either;
+use milli::heed::RoTxn;
+use milli::{Filter, UserError};
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use crate::index::error::{FacetError, IndexError};
+
+use super::error::Result;
+use super::facet_trigrams::{contains, starts_with};
+use super::field_presence::{ensure_filterable, field_presence};
+
+/// A filter expression, in the syntax of the engine extended with the operators it doesn't
+/// support.
+#[derive(Debug, Clone, PartialEq)]
+enum FilterExpr {
+    /// A condition evaluated by the engine, as written in the filter.
+    Condition(String),
+    /// `field IN [values]`, the field and the values are kept as written.
+    In(String, Vec<String>),
+    Exists(String),
+    IsNull(String),
+    IsEmpty(String),
+    /// `field CONTAINS value`, the value is unquoted.
+    Contains(String, String),
+    /// `field STARTS WITH value`, the value is unquoted.
+    StartsWith(String, String),
+    /// A `_geoBoundingBox([lat, lng], [lat, lng])` expression, as written.
+    GeoBoundingBox(String),
+    /// A `_geoPolygon([lat, lng], [lat, lng], [lat, lng], ..)` expression, as written.
+    GeoPolygon(String),
+    Not(Box<FilterExpr>),
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+}
+
+impl FilterExpr {
+    /// Whether the engine understands the expression as written.
+    fn is_native(&self) -> bool {
+        match self {
+            Self::Condition(_) => true,
+            Self::In(_, _)
+            | Self::Exists(_)
+            | Self::IsNull(_)
+            | Self::IsEmpty(_)
+            | Self::Contains(_, _)
+            | Self::StartsWith(_, _)
+            | Self::GeoBoundingBox(_)
+            | Self::GeoPolygon(_) => false,
+            Self::Not(expr) => expr.is_native(),
+            Self::And(exprs) | Self::Or(exprs) => exprs.iter().all(Self::is_native),
+        }
+    }
+
+    /// Rewrites the expression in the syntax of the engine, if it can evaluate it.
+    fn to_engine(&self) -> Option<String> {
+        let join = |exprs: &[FilterExpr], operator| {
+            let exprs = exprs
+                .iter()
+                .map(|expr| Some(format!("({})", expr.to_engine()?)))
+                .collect::<Option<Vec<_>>>()?;
+            Some(exprs.join(operator))
+        };
+
+        match self {
+            Self::Condition(condition) => Some(condition.clone()),
+            Self::In(field, values) if !values.is_empty() => {
+                let equalities: Vec<_> = values
+                    .iter()
+                    .map(|value| format!("{} = {}", field, value))
+                    .collect();
+                Some(equalities.join(" OR "))
+            }
+            Self::In(_, _)
+            | Self::Exists(_)
+            | Self::IsNull(_)
+            | Self::IsEmpty(_)
+            | Self::Contains(_, _)
+            | Self::StartsWith(_, _)
+            | Self::GeoBoundingBox(_)
+            | Self::GeoPolygon(_) => None,
+            Self::Not(expr) => Some(format!("NOT ({})", expr.to_engine()?)),
+            Self::And(exprs) => join(exprs, " AND "),
+            Self::Or(exprs) => join(exprs, " OR "),
+        }
+    }
+
+    /// Returns the names of the attributes the expression filters on.
+    fn fields(&self) -> Vec<&str> {
+        match self {
+            // a condition starts with its field, or is a geo radius.
+            Self::Condition(condition) => {
+                match tokenize(condition).and_then(|t| t.first().copied()) {
+                    Some(Token::Value(field)) => vec![unquote(field)],
+                    Some(Token::Geo(_)) => vec!["_geo"],
+                    _ => Vec::new(),
+                }
+            }
+            Self::GeoBoundingBox(_) | Self::GeoPolygon(_) => vec!["_geo"],
+            Self::In(field, _) => vec![unquote(field)],
+            Self::Exists(field)
+            | Self::IsNull(field)
+            | Self::IsEmpty(field)
+            | Self::Contains(field, _)
+            | Self::StartsWith(field, _) => vec![field.as_str()],
+            Self::Not(expr) => expr.fields(),
+            Self::And(exprs) | Self::Or(exprs) => exprs.iter().flat_map(Self::fields).collect(),
+        }
+    }
+
+    /// Returns the part of the expression the engine can evaluate, the documents it matches
+    /// include all the documents the whole expression matches.
+    fn engine_part(&self) -> Option<String> {
+        match self {
+            Self::And(exprs) => {
+                let exprs: Vec<_> = exprs
+                    .iter()
+                    .filter_map(Self::to_engine)
+                    .map(|expr| format!("({})", expr))
+                    .collect();
+                (!exprs.is_empty()).then(|| exprs.join(" AND "))
+            }
+            expr => expr.to_engine(),
+        }
+    }
+
+    fn evaluate(&self, rtxn: &RoTxn, index: &milli::Index) -> Result<RoaringBitmap> {
+        if let Some(filter) = self.to_engine() {
+            return match Filter::from_str(&filter)? {
+                Some(filter) => Ok(filter.evaluate(rtxn, index)?),
+                None => Ok(index.documents_ids(rtxn)?),
+            };
+        }
+
+        match self {
+            Self::And(exprs) => {
+                let mut docids = index.documents_ids(rtxn)?;
+                for expr in exprs {
+                    docids &= expr.evaluate(rtxn, index)?;
+                }
+                Ok(docids)
+            }
+            Self::Or(exprs) => {
+                let mut docids = RoaringBitmap::new();
+                for expr in exprs {
+                    docids |= expr.evaluate(rtxn, index)?;
+                }
+                Ok(docids)
+            }
+            Self::Not(expr) => Ok(index.documents_ids(rtxn)? - expr.evaluate(rtxn, index)?),
+            Self::Exists(field) => Ok(field_presence(index, rtxn, field)?.exists),
+            Self::IsNull(field) => Ok(field_presence(index, rtxn, field)?.null),
+            Self::IsEmpty(field) => Ok(field_presence(index, rtxn, field)?.empty),
+            Self::Contains(field, needle) => contains(index, rtxn, field, needle),
+            Self::StartsWith(field, prefix) => starts_with(index, rtxn, field, prefix),
+            Self::GeoBoundingBox(expr) => match geo_points(expr)?[..] {
+                [top_right, bottom_left] => geo_documents(index, rtxn, |point| {
+                    in_bounding_box(top_right, bottom_left, point)
+                }),
+                _ => Err(invalid_geo_filter(expr, "two")),
+            },
+            Self::GeoPolygon(expr) => match geo_points(expr)? {
+                polygon if polygon.len() >= 3 => {
+                    geo_documents(index, rtxn, |point| in_polygon(&polygon, point))
+                }
+                _ => Err(invalid_geo_filter(expr, "at least three")),
+            },
+            // the conditions are evaluated by the engine, only an empty `IN` list is left.
+            Self::Condition(_) | Self::In(_, _) => Ok(RoaringBitmap::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// A word or a quoted value, as written.
+    Value(&'a str),
+    Operator(&'a str),
+    /// A `_geoRadius(..)`, `_geoBoundingBox(..)` or `_geoPolygon(..)` expression, as written.
+    Geo(&'a str),
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+const GEO_OPERATORS: [&str; 3] = ["_geoRadius", "_geoBoundingBox", "_geoPolygon"];
+
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = match c {
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            '[' => (Token::OpenBracket, 1),
+            ']' => (Token::CloseBracket, 1),
+            ',' => (Token::Comma, 1),
+            '\'' | '"' => {
+                let len = rest[1..].find(c)? + 2;
+                (Token::Value(&rest[..len]), len)
+            }
+            '=' => (Token::Operator("="), 1),
+            '!' if rest.starts_with("!=") => (Token::Operator("!="), 2),
+            '<' | '>' => {
+                let len = if rest[1..].starts_with('=') { 2 } else { 1 };
+                (Token::Operator(&rest[..len]), len)
+            }
+            '!' => return None,
+            _ => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || "()[],'\"=!<>".contains(c))
+                    .unwrap_or(rest.len());
+                if GEO_OPERATORS.contains(&&rest[..len]) && rest[len..].starts_with('(') {
+                    let len = rest.find(')')? + 1;
+                    (Token::Geo(&rest[..len]), len)
+                } else {
+                    (Token::Value(&rest[..len]), len)
+                }
+            }
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+
+    Some(tokens)
+}
+
+fn unquote(value: &str) -> &str {
+    match value.chars().next() {
+        Some(quote @ ('\'' | '"')) => value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+            .unwrap_or(value),
+        _ => value,
+    }
+}
+
+/// Parses a filter, the operators keywords are case insensitive. `None` is returned when the
+/// filter isn't valid, the engine then reports the error.
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(input: &'a str) -> Option<FilterExpr> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+        };
+        let expr = parser.expression()?;
+        (parser.position == parser.tokens.len()).then(|| expr)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.tokens.get(self.position) {
+            Some(Token::Value(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consumes the `keywords` only if they all follow.
+    fn keywords(&mut self, keywords: &[&str]) -> bool {
+        let follow = keywords.iter().enumerate().all(|(i, keyword)| {
+            matches!(
+                self.tokens.get(self.position + i),
+                Some(Token::Value(word)) if word.eq_ignore_ascii_case(keyword)
+            )
+        });
+        if follow {
+            self.position += keywords.len();
+        }
+        follow
+    }
+
+    fn expression(&mut self) -> Option<FilterExpr> {
+        let mut exprs = vec![self.and()?];
+        while self.keyword("OR") {
+            exprs.push(self.and()?);
+        }
+        Some(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            FilterExpr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Option<FilterExpr> {
+        let mut exprs = vec![self.not()?];
+        while self.keyword("AND") {
+            exprs.push(self.not()?);
+        }
+        Some(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            FilterExpr::And(exprs)
+        })
+    }
+
+    fn not(&mut self) -> Option<FilterExpr> {
+        if self.keyword("NOT") {
+            Some(FilterExpr::Not(Box::new(self.not()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Option<FilterExpr> {
+        match self.next()? {
+            Token::Open => {
+                let expr = self.expression()?;
+                (self.next()? == Token::Close).then(|| expr)
+            }
+            Token::Geo(geo) if geo.starts_with("_geoBoundingBox") => {
+                Some(FilterExpr::GeoBoundingBox(geo.to_string()))
+            }
+            Token::Geo(geo) if geo.starts_with("_geoPolygon") => {
+                Some(FilterExpr::GeoPolygon(geo.to_string()))
+            }
+            Token::Geo(geo_radius) => Some(FilterExpr::Condition(geo_radius.to_string())),
+            Token::Value(field) => self.condition(field),
+            _ => None,
+        }
+    }
+
+    fn value(&mut self) -> Option<&'a str> {
+        match self.next()? {
+            Token::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn condition(&mut self, field: &'a str) -> Option<FilterExpr> {
+        let name = unquote(field).to_string();
+
+        if let Some(Token::Operator(operator)) = self.tokens.get(self.position).copied() {
+            self.position += 1;
+            let value = self.value()?;
+            return Some(FilterExpr::Condition(format!(
+                "{} {} {}",
+                field, operator, value
+            )));
+        }
+
+        let negated = self.keyword("NOT");
+        let expr = if self.keyword("IN") {
+            FilterExpr::In(field.to_string(), self.list()?)
+        } else if self.keyword("EXISTS") {
+            FilterExpr::Exists(name)
+        } else if self.keyword("CONTAINS") {
+            FilterExpr::Contains(name, unquote(self.value()?).to_string())
+        } else if self.keywords(&["STARTS", "WITH"]) {
+            FilterExpr::StartsWith(name, unquote(self.value()?).to_string())
+        } else if negated {
+            return None;
+        } else if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            let expr = if self.keyword("NULL") {
+                FilterExpr::IsNull(name)
+            } else if self.keyword("EMPTY") {
+                FilterExpr::IsEmpty(name)
+            } else {
+                return None;
+            };
+            return Some(negate(expr, negated));
+        } else {
+            let from = self.value()?;
+            if !self.keyword("TO") {
+                return None;
+            }
+            let to = self.value()?;
+            FilterExpr::Condition(format!("{} {} TO {}", field, from, to))
+        };
+
+        Some(negate(expr, negated))
+    }
+
+    fn list(&mut self) -> Option<Vec<String>> {
+        if self.next()? != Token::OpenBracket {
+            return None;
+        }
+        let mut values = Vec::new();
+        loop {
+            match self.next()? {
+                Token::CloseBracket if values.is_empty() => return Some(values),
+                Token::Value(value) => values.push(value.to_string()),
+                _ => return None,
+            }
+            match self.next()? {
+                Token::Comma => (),
+                Token::CloseBracket => return Some(values),
+                _ => return None,
+            }
+        }
+    }
+}
+
+fn negate(expr: FilterExpr, negated: bool) -> FilterExpr {
+    if negated {
+        FilterExpr::Not(Box::new(expr))
+    } else {
+        expr
+    }
+}
+
+fn invalid_geo_filter(expr: &str, expected: &str) -> IndexError {
+    let message = format!(
+        "The geo filter `{}` expects {} `[latitude, longitude]` points, with a latitude between \
+         -90 and 90 and a longitude between -180 and 180.",
+        expr, expected
+    );
+    milli::Error::from(UserError::InvalidFilter(message)).into()
+}
+
+/// Parses the `[latitude, longitude]` points given to a `_geoBoundingBox` or `_geoPolygon`
+/// expression.
+fn geo_points(expr: &str) -> Result<Vec<[f64; 2]>> {
+    fn parse(args: &str) -> Option<Vec<[f64; 2]>> {
+        let mut points = Vec::new();
+        let mut rest = args.trim_start();
+        while !rest.is_empty() {
+            let point = rest.strip_prefix('[')?;
+            let end = point.find(']')?;
+            let coordinates = point[..end]
+                .split(',')
+                .map(|coordinate| coordinate.trim().parse().ok())
+                .collect::<Option<Vec<f64>>>()?;
+            match coordinates[..] {
+                [lat, lng] if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lng) => {
+                    points.push([lat, lng])
+                }
+                _ => return None,
+            }
+            rest = point[end + 1..].trim_start();
+            if let Some(next) = rest.strip_prefix(',') {
+                rest = next.trim_start();
+            } else if !rest.is_empty() {
+                return None;
+            }
+        }
+        Some(points)
+    }
+
+    // the tokenizer only keeps the expressions ending with their closing parenthesis.
+    let args = expr
+        .find('(')
+        .and_then(|start| expr.get(start + 1..expr.len() - 1));
+    args.and_then(parse)
+        .ok_or_else(|| invalid_geo_filter(expr, "valid"))
+}
+
+/// Whether the `[lat, lng]` point lies in the box between the `top_right` and `bottom_left`
+/// corners. The box crosses the antimeridian when its left side is east of its right side.
+fn in_bounding_box(top_right: [f64; 2], bottom_left: [f64; 2], [lat, lng]: [f64; 2]) -> bool {
+    let [top, right] = top_right;
+    let [bottom, left] = bottom_left;
+    let in_longitudes = if left <= right {
+        left <= lng && lng <= right
+    } else {
+        left <= lng || lng <= right
+    };
+    bottom <= lat && lat <= top && in_longitudes
+}
+
+/// Whether the `[lat, lng]` point lies in the `polygon`, by counting how many of its edges a ray
+/// cast from the point crosses.
+fn in_polygon(polygon: &[[f64; 2]], [lat, lng]: [f64; 2]) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &[vertex_lat, vertex_lng] in polygon {
+        let [previous_lat, previous_lng] = previous;
+        if (vertex_lat > lat) != (previous_lat > lat)
+            && lng
+                < (previous_lng - vertex_lng) * (lat - vertex_lat) / (previous_lat - vertex_lat)
+                    + vertex_lng
+        {
+            inside = !inside;
+        }
+        previous = [vertex_lat, vertex_lng];
+    }
+    inside
+}
+
+/// Returns the documents whose `_geo` point is matched by `matches`. Like for the `_geoRadius`
+/// expressions of the engine, the points are read from the geo rtree of the index.
+fn geo_documents(
+    index: &milli::Index,
+    rtxn: &RoTxn,
+    matches: impl Fn([f64; 2]) -> bool,
+) -> Result<RoaringBitmap> {
+    ensure_filterable(index, rtxn, "_geo")?;
+    let rtree = match index.geo_rtree(rtxn)? {
+        Some(rtree) => rtree,
+        None => return Ok(RoaringBitmap::new()),
+    };
+
+    Ok(rtree
+        .iter()
+        .filter(|point| matches(point.data.1))
+        .map(|point| point.data.0)
+        .collect())
+}
+
+/// A filter of the documents, either a string expression or an array of expressions in which
+/// the nested arrays are `OR`ed and the other elements `AND`ed.
+///
+/// The engine evaluates the filters written in its syntax. The `IN` operators are rewritten as
+/// equalities for the engine, while the `EXISTS`, `IS NULL` and `IS EMPTY` operators are
+/// evaluated on the presence of the filterable attributes, the `CONTAINS` and `STARTS WITH`
+/// operators on their string values, indexed by meilisearch, and the `_geoBoundingBox` and
+/// `_geoPolygon` operators on the geo points of the documents.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexFilter {
+    /// The filter, or the part of it, evaluated by the engine.
+    engine: Option<Value>,
+    /// The whole filter, when the engine can't evaluate it.
+    extended: Option<FilterExpr>,
+}
+
+impl IndexFilter {
+    /// Returns the filter to give to the engine. When the filter [`Self::is_extended`], the
+    /// documents the engine returns are a superset of the ones the whole filter matches.
+    pub fn engine_filter(&self) -> Result<Option<Filter>> {
+        match self.engine {
+            Some(ref filter) => engine_filter(filter),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether the engine can't evaluate the whole filter, its results must be restricted with
+    /// [`Self::evaluate`].
+    pub fn is_extended(&self) -> bool {
+        self.extended.is_some()
+    }
+
+    /// Returns the documents matching the filter.
+    pub fn evaluate(&self, rtxn: &RoTxn, index: &milli::Index) -> Result<RoaringBitmap> {
+        match self.extended {
+            Some(ref expr) => expr.evaluate(rtxn, index),
+            None => match self.engine_filter()? {
+                Some(filter) => Ok(filter.evaluate(rtxn, index)?),
+                None => Ok(index.documents_ids(rtxn)?),
+            },
+        }
+    }
+}
+
+pub(crate) fn parse_filter(filter: &Value) -> Result<Option<IndexFilter>> {
+    let expr = match filter {
+        Value::String(expr) => Parser::parse(expr),
+        Value::Array(arr) => parse_filter_array(arr)?,
+        v => return Err(FacetError::InvalidExpression(&["Array"], v.clone()).into()),
+    };
+
+    // the filters written in the syntax of the engine, and the invalid ones, are given as is.
+    let filter = match expr {
+        Some(expr) if !expr.is_native() => match expr.to_engine() {
+            Some(engine) => IndexFilter {
+                engine: Some(Value::String(engine)),
+                extended: None,
+            },
+            None => IndexFilter {
+                engine: expr.engine_part().map(Value::String),
+                extended: Some(expr),
+            },
+        },
+        _ => match engine_filter(filter)? {
+            Some(_) => IndexFilter {
+                engine: Some(filter.clone()),
+                extended: None,
+            },
+            None => return Ok(None),
+        },
+    };
+
+    Ok(Some(filter))
+}
+
+/// Parses the expressions of a filter array, `None` is returned when one of them is invalid.
+fn parse_filter_array(arr: &[Value]) -> Result<Option<FilterExpr>> {
+    let mut ands = Vec::new();
+    for value in arr {
+        match value {
+            Value::String(s) => match Parser::parse(s) {
+                Some(expr) => ands.push(expr),
+                None => return Ok(None),
+            },
+            Value::Array(arr) => {
+                let mut ors = Vec::new();
+                for value in arr {
+                    match value {
+                        Value::String(s) => match Parser::parse(s) {
+                            Some(expr) => ors.push(expr),
+                            None => return Ok(None),
+                        },
+                        v => {
+                            return Err(FacetError::InvalidExpression(&["String"], v.clone()).into())
+                        }
+                    }
+                }
+                ands.push(FilterExpr::Or(ors));
+            }
+            v => {
+                return Err(
+                    FacetError::InvalidExpression(&["String", "[String]"], v.clone()).into(),
+                )
+            }
+        }
+    }
+
+    Ok(Some(FilterExpr::And(ands)))
+}
+
+/// Returns the names of the attributes the `filter` is evaluated on. When the filter can't be
+/// parsed, all its words are returned, so that no attribute it may use is missed.
+pub(crate) fn filter_attributes(filter: &Value) -> Vec<String> {
+    let exprs: Vec<&str> = match filter {
+        Value::String(expr) => vec![expr],
+        Value::Array(arr) => arr
+            .iter()
+            .flat_map(|value| match value {
+                Value::Array(arr) => arr.iter().filter_map(Value::as_str).collect(),
+                value => value.as_str().into_iter().collect::<Vec<_>>(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut attributes = Vec::new();
+    for expr in exprs {
+        match Parser::parse(expr) {
+            Some(expr) => attributes.extend(expr.fields().into_iter().map(String::from)),
+            None => attributes.extend(tokenize(expr).into_iter().flatten().filter_map(|token| {
+                match token {
+                    Token::Value(word) => Some(unquote(word).to_string()),
+                    Token::Geo(_) => Some("_geo".to_string()),
+                    _ => None,
+                }
+            })),
+        }
+    }
+    attributes
+}
+
+fn engine_filter(filter: &Value) -> Result<Option<Filter>> {
+    match filter {
+        Value::String(expr) => Ok(Filter::from_str(expr)?),
+        Value::Array(arr) => engine_filter_array(arr),
+        v => Err(FacetError::InvalidExpression(&["Array"], v.clone()).into()),
+    }
+}
+
+fn engine_filter_array(arr: &[Value]) -> Result<Option<Filter>> {
+    let mut ands = Vec::new();
+    for value in arr {
+        match value {
+            Value::String(s) => ands.push(Either::Right(s.as_str())),
+            Value::Array(arr) => {
+                let mut ors = Vec::new();
+                for value in arr {
+                    match value {
+                        Value::String(s) => ors.push(s.as_str()),
+                        v => {
+                            return Err(FacetError::InvalidExpression(&["String"], v.clone()).into())
+                        }
+                    }
+                }
+                ands.push(Either::Left(ors));
+            }
+            v => {
+                return Err(
+                    FacetError::InvalidExpression(&["String", "[String]"], v.clone()).into(),
+                )
+            }
+        }
+    }
+
+    Ok(Filter::from_array(ands)?)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn engine(filter: &str) -> Option<String> {
+        Parser::parse(filter).and_then(|expr| expr.to_engine())
+    }
+
+    #[test]
+    fn parse_native_filters() {
+        for filter in [
+            "genre = horror",
+            "(genre = 'science fiction' OR year >= 2000) AND NOT id != 3",
+            "year 1990 TO 2000",
+            "_geoRadius(45.47, 9.18, 2000) AND \"release date\" < 10",
+        ] {
+            assert!(Parser::parse(filter).unwrap().is_native(), "{}", filter);
+        }
+        assert_eq!(engine("genre = horror").unwrap(), "genre = horror");
+        assert_eq!(
+            engine("genre=horror AND year 1 TO 2").unwrap(),
+            "(genre = horror) AND (year 1 TO 2)"
+        );
+    }
+
+    #[test]
+    fn parse_extended_filters() {
+        assert_eq!(
+            engine("genre IN [horror, 'science fiction']").unwrap(),
+            "genre = horror OR genre = 'science fiction'"
+        );
+        assert_eq!(
+            engine("year > 2000 AND genre not in [horror]").unwrap(),
+            "(year > 2000) AND (NOT (genre = horror))"
+        );
+        assert_eq!(
+            Parser::parse("release_date EXISTS OR NOT \"poster\" EXISTS").unwrap(),
+            FilterExpr::Or(vec![
+                FilterExpr::Exists("release_date".to_string()),
+                FilterExpr::Not(Box::new(FilterExpr::Exists("poster".to_string()))),
+            ])
+        );
+        assert_eq!(
+            Parser::parse("description IS NOT EMPTY AND year IS NULL").unwrap(),
+            FilterExpr::And(vec![
+                FilterExpr::Not(Box::new(FilterExpr::IsEmpty("description".to_string()))),
+                FilterExpr::IsNull("year".to_string()),
+            ])
+        );
+        assert_eq!(Parser::parse("genre IN []").unwrap().to_engine(), None);
+        assert_eq!(
+            Parser::parse("path CONTAINS \"/admin/\" OR sku NOT starts with 'ABC-'").unwrap(),
+            FilterExpr::Or(vec![
+                FilterExpr::Contains("path".to_string(), "/admin/".to_string()),
+                FilterExpr::Not(Box::new(FilterExpr::StartsWith(
+                    "sku".to_string(),
+                    "ABC-".to_string()
+                ))),
+            ])
+        );
+        // without `WITH`, `STARTS` is the lower bound of a range.
+        assert_eq!(
+            Parser::parse("sku STARTS TO end").unwrap(),
+            FilterExpr::Condition("sku STARTS TO end".to_string())
+        );
+
+        assert_eq!(
+            Parser::parse("_geoBoundingBox([45.49, 9.20], [45.46, 9.17]) OR _geoPolygon([1, 1], [1, 2], [2, 2])").unwrap(),
+            FilterExpr::Or(vec![
+                FilterExpr::GeoBoundingBox("_geoBoundingBox([45.49, 9.20], [45.46, 9.17])".to_string()),
+                FilterExpr::GeoPolygon("_geoPolygon([1, 1], [1, 2], [2, 2])".to_string()),
+            ])
+        );
+
+        let expr = Parser::parse("year > 2000 AND poster EXISTS AND genre IN [horror]").unwrap();
+        assert_eq!(expr.to_engine(), None);
+        assert_eq!(
+            expr.engine_part().unwrap(),
+            "(year > 2000) AND (genre = horror)"
+        );
+    }
+
+    #[test]
+    fn filtered_attributes() {
+        let filter = json!([
+            "(genre = horror OR 'release date' 1 TO 2) AND NOT poster EXISTS",
+            ["tags IN [a, b]", "_geoRadius(45.47, 9.18, 2000)"],
+            "_geoBoundingBox([1, 2], [0, 1])",
+        ]);
+        assert_eq!(
+            filter_attributes(&filter),
+            ["genre", "release date", "poster", "tags", "_geo", "_geo"]
+        );
+        // all the words of an invalid filter are kept.
+        assert_eq!(
+            filter_attributes(&json!("secret IN horror")),
+            ["secret", "IN", "horror"]
+        );
+    }
+
+    #[test]
+    fn geo_shapes() {
+        assert_eq!(
+            geo_points("_geoPolygon([1, 2.5],[-3,4] , [5, -6.25])").unwrap(),
+            [[1.0, 2.5], [-3.0, 4.0], [5.0, -6.25]]
+        );
+        for expr in [
+            "_geoBoundingBox([1, 2], 3)",
+            "_geoBoundingBox([1, 2] [3, 4])",
+            "_geoBoundingBox([1, 2, 3])",
+            "_geoBoundingBox([91, 2], [3, 4])",
+            "_geoBoundingBox([1, 181], [3, 4])",
+            "_geoPolygon([a, 2])",
+        ] {
+            assert!(geo_points(expr).is_err(), "{}", expr);
+        }
+
+        assert!(in_bounding_box([2.0, 2.0], [0.0, 0.0], [1.0, 1.0]));
+        assert!(!in_bounding_box([2.0, 2.0], [0.0, 0.0], [1.0, 3.0]));
+        assert!(!in_bounding_box([2.0, 2.0], [0.0, 0.0], [3.0, 1.0]));
+        // a box crossing the antimeridian.
+        assert!(in_bounding_box([2.0, -170.0], [0.0, 170.0], [1.0, 179.0]));
+        assert!(in_bounding_box([2.0, -170.0], [0.0, 170.0], [1.0, -175.0]));
+        assert!(!in_bounding_box([2.0, -170.0], [0.0, 170.0], [1.0, 0.0]));
+
+        // a concave polygon, shaped like a `C` opened to the east.
+        let polygon = [
+            [0.0, 0.0],
+            [0.0, 3.0],
+            [1.0, 3.0],
+            [1.0, 1.0],
+            [2.0, 1.0],
+            [2.0, 3.0],
+            [3.0, 3.0],
+            [3.0, 0.0],
+        ];
+        assert!(in_polygon(&polygon, [0.5, 2.0]));
+        assert!(in_polygon(&polygon, [1.5, 0.5]));
+        assert!(!in_polygon(&polygon, [1.5, 2.0]));
+        assert!(!in_polygon(&polygon, [4.0, 1.0]));
+    }
+
+    #[test]
+    fn invalid_filters_are_left_to_the_engine() {
+        for filter in [
+            "genre",
+            "genre IN horror",
+            "genre IN [horror",
+            "genre IS",
+            "path CONTAINS",
+            "sku STARTS WITH (abc)",
+            "genre NOT = horror",
+            "(genre = horror",
+            "genre = horror AND",
+            "genre = 'horror",
+        ] {
+            assert_eq!(Parser::parse(filter), None, "{}", filter);
+        }
+    }
+}
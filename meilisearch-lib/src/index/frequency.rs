@@ -0,0 +1,87 @@
+use milli::heed::RoTxn;
+use milli::tokenizer::TokenizerBuilder;
+
+use super::error::Result;
+use super::index::Index;
+
+/// Splits the query `q` into its terms, the words and the `"exact phrases"`, as written.
+pub(crate) fn split_terms(q: &str) -> Vec<&str> {
+    let mut terms = Vec::new();
+    let mut rest = q.trim_start();
+
+    while !rest.is_empty() {
+        let end = match rest.strip_prefix('"') {
+            // an unterminated phrase lasts until the end of the query.
+            Some(phrase) => phrase.find('"').map_or(rest.len(), |end| end + 2),
+            None => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+        terms.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+
+    terms
+}
+
+impl Index {
+    /// Reorders the terms of the query `q` from the one contained in the fewest documents to the
+    /// one contained in the most, so that the engine, which drops the terms from the last one
+    /// until enough documents match, drops the most frequent ones first. A phrase is as frequent
+    /// as its rarest word.
+    ///
+    /// The engine only matches the last word of a query as a prefix, so when the query doesn't
+    /// end with a space, its last term is still being typed and stays the last one.
+    pub(crate) fn order_terms_by_frequency(&self, rtxn: &RoTxn, q: &str) -> Result<String> {
+        let mut terms = split_terms(q);
+        let prefix = match terms.last() {
+            Some(_) if !q.ends_with(char::is_whitespace) => terms.pop(),
+            _ => None,
+        };
+
+        let tokenizer = TokenizerBuilder::default().build();
+        let mut counted = Vec::with_capacity(terms.len());
+        for term in terms {
+            let mut count = None;
+            for token in tokenizer.tokenize(term).filter(|token| token.is_word()) {
+                let word_count = self
+                    .word_docids
+                    .get(rtxn, token.lemma())?
+                    .map_or(0, |docids| docids.len());
+                count = Some(count.map_or(word_count, |count: u64| count.min(word_count)));
+            }
+            counted.push((count.unwrap_or(0), term));
+        }
+
+        // `sort_by_key` is stable, the terms as frequent as each other keep the query order.
+        counted.sort_by_key(|(count, _)| *count);
+
+        let mut ordered: Vec<_> = counted.into_iter().map(|(_, term)| term).collect();
+        ordered.extend(prefix);
+        let mut ordered = ordered.join(" ");
+        if q.ends_with(char::is_whitespace) {
+            ordered.push(' ');
+        }
+        Ok(ordered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_terms() {
+        assert_eq!(
+            split_terms("  the   hidden world "),
+            vec!["the", "hidden", "world"]
+        );
+        assert_eq!(
+            split_terms("how \"to train\" your dragon"),
+            vec!["how", "\"to train\"", "your", "dragon"]
+        );
+        assert_eq!(
+            split_terms("glass \"unterminated phrase"),
+            vec!["glass", "\"unterminated phrase"]
+        );
+        assert!(split_terms("   ").is_empty());
+    }
+}
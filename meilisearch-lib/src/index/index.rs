@@ -1,29 +1,55 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::create_dir_all;
 use std::marker::PhantomData;
+use std::ops::Bound;
 use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
 
 use fst::IntoStreamer;
-use milli::heed::{EnvOpenOptions, RoTxn};
-use milli::update::{IndexerConfig, Setting};
-use milli::{obkv_to_json, FieldDistribution, DEFAULT_VALUES_PER_FACET};
+use milli::heed::types::{ByteSlice, SerdeJson, Str};
+use milli::heed::{Database, EnvOpenOptions, RoTxn};
+use milli::update::Setting;
+use milli::{obkv_to_json, DocumentId, FieldDistribution, BEU32, DEFAULT_VALUES_PER_FACET};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::index::search::DEFAULT_PAGINATION_LIMITED_TO;
+use crate::index::filter::parse_filter;
+use crate::index::search::{parse_sort, DEFAULT_PAGINATION_LIMITED_TO};
+use crate::options::IndexerConfigHandle;
 use crate::EnvSizer;
 
 use super::error::IndexError;
 use super::error::Result;
-use super::updates::{FacetingSettings, MinWordSizeTyposSetting, PaginationSettings, TypoSettings};
+use super::expiration::migrate_document_expirations;
+use super::side_store::SideStore;
+use super::updates::{
+    FacetValuesSort, FacetingSettings, MinWordSizeTyposSetting, PaginationSettings, RankingProfile,
+    TypoSettings,
+};
 use super::{Checked, Settings};
 
 pub type Document = Map<String, Value>;
 
+/// The key of the main database under which the search cutoff of the index is stored. The engine
+/// doesn't know about this setting, it is handled by meilisearch.
+pub(crate) const SEARCH_CUTOFF_MS_KEY: &str = "search-cutoff-ms";
+/// The key of the main database under which the largest `totalHits` of the exhaustive
+/// pagination is stored.
+pub(crate) const MAX_TOTAL_HITS_KEY: &str = "max-total-hits";
+/// The key of the main database under which the order of the facet values is stored.
+pub(crate) const SORT_FACET_VALUES_BY_KEY: &str = "sort-facet-values-by";
+/// The key of the main database under which the ranking profiles are stored.
+pub(crate) const RANKING_PROFILES_KEY: &str = "ranking-profiles";
+/// The key of the main database under which the attribute holding the expiry date of the
+/// documents is stored.
+pub(crate) const EXPIRY_ATTRIBUTE_KEY: &str = "expiry-attribute";
+/// The key of the main database under which the attribute holding the version of the documents
+/// is stored.
+pub(crate) const VERSION_ATTRIBUTE_KEY: &str = "version-attribute";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexMeta {
@@ -63,6 +89,25 @@ pub struct IndexStats {
     /// later set to either true or false, we we retrieve the information from the `UpdateStore`
     pub is_indexing: Option<bool>,
     pub field_distribution: FieldDistribution,
+    /// Only computed on demand, since the whole index must be read.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub details: Option<DetailedIndexStats>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedIndexStats {
+    /// The size of the files of the index on disk, free pages included.
+    pub database_size: u64,
+    /// The size of the keys and values stored in each database of the index, by database name.
+    pub database_sizes: BTreeMap<String, u64>,
+    pub number_of_distinct_terms: u64,
+    /// The size of the documents database divided by the number of documents.
+    pub avg_document_size: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
 }
 
 #[derive(Clone, derivative::Derivative)]
@@ -72,7 +117,9 @@ pub struct Index {
     #[derivative(Debug = "ignore")]
     pub inner: Arc<milli::Index>,
     #[derivative(Debug = "ignore")]
-    pub indexer_config: Arc<IndexerConfig>,
+    pub indexer_config: IndexerConfigHandle,
+    #[derivative(Debug = "ignore")]
+    pub(crate) side: SideStore,
 }
 
 impl Deref for Index {
@@ -88,23 +135,27 @@ impl Index {
         path: impl AsRef<Path>,
         size: usize,
         uuid: Uuid,
-        update_handler: Arc<IndexerConfig>,
+        update_handler: IndexerConfigHandle,
     ) -> Result<Self> {
         log::debug!("opening index in {}", path.as_ref().display());
         create_dir_all(&path)?;
         let mut options = EnvOpenOptions::new();
         options.map_size(size);
         let inner = Arc::new(milli::Index::new(options, &path)?);
+        let side = SideStore::open(&path, size)?;
+        migrate_document_expirations(&inner, &side)?;
         Ok(Index {
             inner,
             uuid,
             indexer_config: update_handler,
+            side,
         })
     }
 
     /// Asynchronously close the underlying index
     pub fn close(self) {
         self.inner.as_ref().clone().prepare_for_closing();
+        self.side.env.clone().prepare_for_closing();
     }
 
     pub fn stats(&self) -> Result<IndexStats> {
@@ -115,6 +166,91 @@ impl Index {
             number_of_documents: self.number_of_documents(&rtxn)?,
             is_indexing: None,
             field_distribution: self.field_distribution(&rtxn)?,
+            details: None,
+        })
+    }
+
+    pub fn detailed_stats(&self) -> Result<DetailedIndexStats> {
+        let rtxn = self.read_txn()?;
+
+        let mut main_size = 0;
+        for entry in self.main.iter::<_, ByteSlice, ByteSlice>(&rtxn)? {
+            let (key, value) = entry?;
+            main_size += (key.len() + value.len()) as u64;
+        }
+
+        let documents_size = payload_size(&rtxn, self.documents)?;
+        let database_sizes: BTreeMap<_, _> = [
+            ("main", main_size),
+            ("word-docids", payload_size(&rtxn, self.word_docids)?),
+            (
+                "exact-word-docids",
+                payload_size(&rtxn, self.exact_word_docids)?,
+            ),
+            (
+                "word-prefix-docids",
+                payload_size(&rtxn, self.word_prefix_docids)?,
+            ),
+            (
+                "exact-word-prefix-docids",
+                payload_size(&rtxn, self.exact_word_prefix_docids)?,
+            ),
+            (
+                "docid-word-positions",
+                payload_size(&rtxn, self.docid_word_positions)?,
+            ),
+            (
+                "word-pair-proximity-docids",
+                payload_size(&rtxn, self.word_pair_proximity_docids)?,
+            ),
+            (
+                "word-prefix-pair-proximity-docids",
+                payload_size(&rtxn, self.word_prefix_pair_proximity_docids)?,
+            ),
+            (
+                "word-position-docids",
+                payload_size(&rtxn, self.word_position_docids)?,
+            ),
+            (
+                "word-prefix-position-docids",
+                payload_size(&rtxn, self.word_prefix_position_docids)?,
+            ),
+            (
+                "field-id-word-count-docids",
+                payload_size(&rtxn, self.field_id_word_count_docids)?,
+            ),
+            (
+                "facet-id-f64-docids",
+                payload_size(&rtxn, self.facet_id_f64_docids)?,
+            ),
+            (
+                "facet-id-string-docids",
+                payload_size(&rtxn, self.facet_id_string_docids)?,
+            ),
+            (
+                "field-id-docid-facet-f64s",
+                payload_size(&rtxn, self.field_id_docid_facet_f64s)?,
+            ),
+            (
+                "field-id-docid-facet-strings",
+                payload_size(&rtxn, self.field_id_docid_facet_strings)?,
+            ),
+            ("documents", documents_size),
+        ]
+        .into_iter()
+        .map(|(name, size)| (name.to_string(), size))
+        .collect();
+
+        let number_of_documents = self.number_of_documents(&rtxn)?;
+        let avg_document_size = documents_size.checked_div(number_of_documents).unwrap_or(0);
+
+        Ok(DetailedIndexStats {
+            database_size: self.size(),
+            database_sizes,
+            number_of_distinct_terms: self.words_fst(&rtxn)?.len() as u64,
+            avg_document_size,
+            created_at: self.created_at(&rtxn)?,
+            updated_at: self.updated_at(&rtxn)?,
         })
     }
 
@@ -130,6 +266,53 @@ impl Index {
         self.uuid
     }
 
+    /// Returns the maximum duration of a search on this index, in milliseconds.
+    pub fn search_cutoff_ms(&self, txn: &RoTxn) -> Result<Option<u64>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<u64>>(txn, SEARCH_CUTOFF_MS_KEY)?)
+    }
+
+    /// Returns the largest `totalHits` reported by the `page`/`hitsPerPage` pagination, `None`
+    /// when it is unbounded.
+    pub fn max_total_hits(&self, txn: &RoTxn) -> Result<Option<usize>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<usize>>(txn, MAX_TOTAL_HITS_KEY)?)
+    }
+
+    /// Returns the order of the values of each facet, the values of the facets that are not
+    /// listed are sorted in lexicographic order unless `*` says otherwise.
+    pub fn sort_facet_values_by(&self, txn: &RoTxn) -> Result<BTreeMap<String, FacetValuesSort>> {
+        let sort_by = self
+            .main
+            .get::<_, Str, SerdeJson<_>>(txn, SORT_FACET_VALUES_BY_KEY)?;
+        Ok(sort_by.unwrap_or_else(|| BTreeMap::from([("*".to_string(), FacetValuesSort::Alpha)])))
+    }
+
+    /// Returns the ranking profiles of the index, by name.
+    pub fn ranking_profiles(&self, txn: &RoTxn) -> Result<BTreeMap<String, RankingProfile>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<_>>(txn, RANKING_PROFILES_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the attribute holding the date after which a document expires, if any.
+    pub fn expiry_attribute(&self, txn: &RoTxn) -> Result<Option<String>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<String>>(txn, EXPIRY_ATTRIBUTE_KEY)?)
+    }
+
+    /// Returns the attribute holding the version of the documents, the additions of documents
+    /// that aren't newer than the stored ones are rejected.
+    pub fn version_attribute(&self, txn: &RoTxn) -> Result<Option<String>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<String>>(txn, VERSION_ATTRIBUTE_KEY)?)
+    }
+
     pub fn settings_txn(&self, txn: &RoTxn) -> Result<Settings<Checked>> {
         let displayed_attributes = self
             .displayed_fields(txn)?
@@ -199,6 +382,7 @@ impl Index {
                 self.max_values_per_facet(txn)?
                     .unwrap_or(DEFAULT_VALUES_PER_FACET),
             ),
+            sort_facet_values_by: Setting::Set(self.sort_facet_values_by(txn)?),
         };
 
         let pagination = PaginationSettings {
@@ -206,8 +390,20 @@ impl Index {
                 self.pagination_limited_to(txn)?
                     .unwrap_or(DEFAULT_PAGINATION_LIMITED_TO),
             ),
+            max_total_hits: match self.max_total_hits(txn)? {
+                Some(max_total_hits) => Setting::Set(max_total_hits),
+                None => Setting::Reset,
+            },
         };
 
+        let search_cutoff_ms = self.search_cutoff_ms(txn)?;
+
+        let ranking_profiles = self.ranking_profiles(txn)?;
+
+        let expiry_attribute = self.expiry_attribute(txn)?;
+
+        let version_attribute = self.version_attribute(txn)?;
+
         Ok(Settings {
             displayed_attributes: match displayed_attributes {
                 Some(attrs) => Setting::Set(attrs),
@@ -229,30 +425,164 @@ impl Index {
             typo_tolerance: Setting::Set(typo_tolerance),
             faceting: Setting::Set(faceting),
             pagination: Setting::Set(pagination),
+            search_cutoff_ms: match search_cutoff_ms {
+                Some(cutoff) => Setting::Set(cutoff),
+                None => Setting::Reset,
+            },
+            ranking_profiles: Setting::Set(ranking_profiles),
+            expiry_attribute: match expiry_attribute {
+                Some(attribute) => Setting::Set(attribute),
+                None => Setting::Reset,
+            },
+            version_attribute: match version_attribute {
+                Some(attribute) => Setting::Set(attribute),
+                None => Setting::Reset,
+            },
             _kind: PhantomData,
         })
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
+    /// Return the total number of documents matching the filter + the selected documents.
+    /// When neither a filter nor a sort is given, all the documents of the index are matched and
+    /// returned in the order of their internal ids.
     pub fn retrieve_documents<S: AsRef<str>>(
         &self,
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<S>>,
+        filter: Option<Value>,
+        sort: Option<Vec<String>>,
     ) -> Result<(u64, Vec<Document>)> {
         let txn = self.read_txn()?;
 
-        let fields_ids_map = self.fields_ids_map(&txn)?;
-        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let filter = match filter {
+            Some(ref filter) => parse_filter(filter)?,
+            None => None,
+        };
+
+        let (number_of_documents, documents_ids) = match (filter, sort) {
+            (None, None) => {
+                let documents = self
+                    .documents
+                    .range(&txn, &(..))?
+                    .skip(offset)
+                    .take(limit)
+                    .map(|entry| Ok(entry?.0.get()))
+                    .collect::<Result<Vec<_>>>()?;
+                (self.number_of_documents(&txn)?, documents)
+            }
+            (Some(filter), None) => {
+                let candidates = filter.evaluate(&txn, self)?;
+                let documents = candidates.iter().skip(offset).take(limit).collect();
+                (candidates.len(), documents)
+            }
+            (filter, Some(sort)) => {
+                let mut search = self.search(&txn);
+                search.offset(offset);
+                search.limit(limit);
+                if let Some(ref filter) = filter {
+                    if let Some(engine_filter) = filter.engine_filter()? {
+                        search.filter(engine_filter);
+                    }
+                }
+                search.sort_criteria(parse_sort(&sort)?);
+
+                match filter.as_ref().filter(|filter| filter.is_extended()) {
+                    // all the documents are sorted before being restricted by the filter.
+                    Some(filter) => {
+                        search.offset(0);
+                        search.limit(self.number_of_documents(&txn)? as usize);
+                        let matching = filter.evaluate(&txn, self)?;
+                        let documents_ids: Vec<_> = search
+                            .execute()?
+                            .documents_ids
+                            .into_iter()
+                            .filter(|id| matching.contains(*id))
+                            .collect();
+                        let documents = documents_ids.into_iter().skip(offset).take(limit);
+                        (matching.len(), documents.collect())
+                    }
+                    None => {
+                        let milli::SearchResult {
+                            documents_ids,
+                            candidates,
+                            ..
+                        } = search.execute()?;
+                        (candidates.len(), documents_ids)
+                    }
+                }
+            }
+        };
+
+        let documents = self.documents_to_json(&txn, documents_ids, &attributes_to_retrieve)?;
+
+        Ok((number_of_documents, documents))
+    }
 
-        let iter = self.documents.range(&txn, &(..))?.skip(offset).take(limit);
+    /// Returns at most `limit` documents matching the filter, in the order of their internal ids,
+    /// starting right after the document `after`. The returned id is the one of the last returned
+    /// document when there are more documents to retrieve, and must be used as the next `after`.
+    pub fn retrieve_documents_after<S: AsRef<str>>(
+        &self,
+        after: Option<DocumentId>,
+        limit: usize,
+        attributes_to_retrieve: Option<Vec<S>>,
+        filter: Option<Value>,
+    ) -> Result<(Vec<Document>, Option<DocumentId>)> {
+        let txn = self.read_txn()?;
+
+        let filter = match filter {
+            Some(ref filter) => parse_filter(filter)?,
+            None => None,
+        };
+
+        // one more document is fetched to know whether there are documents left.
+        let mut documents_ids = match filter {
+            Some(filter) => filter
+                .evaluate(&txn, self)?
+                .iter()
+                .skip_while(|id| after.map_or(false, |after| *id <= after))
+                .take(limit.saturating_add(1))
+                .collect(),
+            None => {
+                let start = match after {
+                    Some(after) => Bound::Excluded(BEU32::new(after)),
+                    None => Bound::Unbounded,
+                };
+                self.documents
+                    .range(&txn, &(start, Bound::Unbounded))?
+                    .take(limit.saturating_add(1))
+                    .map(|entry| Ok(entry?.0.get()))
+                    .collect::<Result<Vec<_>>>()?
+            }
+        };
+
+        let next = if documents_ids.len() > limit {
+            documents_ids.truncate(limit);
+            documents_ids.last().copied()
+        } else {
+            None
+        };
+
+        let documents = self.documents_to_json(&txn, documents_ids, &attributes_to_retrieve)?;
+
+        Ok((documents, next))
+    }
+
+    fn documents_to_json<S: AsRef<str>>(
+        &self,
+        txn: &RoTxn,
+        documents_ids: Vec<DocumentId>,
+        attributes_to_retrieve: &Option<Vec<S>>,
+    ) -> Result<Vec<Document>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
 
         let mut documents = Vec::new();
 
-        for entry in iter {
-            let (_id, obkv) = entry?;
+        for (_id, obkv) in self.documents(txn, documents_ids)? {
             let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
-            let document = match &attributes_to_retrieve {
+            let document = match attributes_to_retrieve {
                 Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
                     &document,
                     attributes_to_retrieve.iter().map(|s| s.as_ref()),
@@ -262,9 +592,7 @@ impl Index {
             documents.push(document);
         }
 
-        let number_of_documents = self.number_of_documents(&txn)?;
-
-        Ok((number_of_documents, documents))
+        Ok(documents)
     }
 
     pub fn retrieve_document<S: AsRef<str>>(
@@ -301,20 +629,82 @@ impl Index {
         Ok(document)
     }
 
+    /// Returns the documents `ids` in the order they are requested, and the ids of the ones that
+    /// don't exist.
+    pub fn retrieve_documents_by_ids<S: AsRef<str>>(
+        &self,
+        ids: Vec<String>,
+        attributes_to_retrieve: Option<Vec<S>>,
+    ) -> Result<(Vec<Document>, Vec<String>)> {
+        let txn = self.read_txn()?;
+
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_ids = self.external_documents_ids(&txn)?;
+
+        let mut documents = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        for id in ids {
+            let obkv = match external_ids.get(id.as_bytes()) {
+                Some(internal_id) => self
+                    .documents(&txn, std::iter::once(internal_id))?
+                    .into_iter()
+                    .next()
+                    .map(|(_, obkv)| obkv),
+                None => None,
+            };
+            let obkv = match obkv {
+                Some(obkv) => obkv,
+                None => {
+                    missing.push(id);
+                    continue;
+                }
+            };
+
+            let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            documents.push(match &attributes_to_retrieve {
+                Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
+                    &document,
+                    attributes_to_retrieve.iter().map(|s| s.as_ref()),
+                ),
+                None => document,
+            });
+        }
+
+        Ok((documents, missing))
+    }
+
     pub fn size(&self) -> u64 {
         self.env.size()
     }
 
-    pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
-        let mut dst = path.as_ref().join(format!("indexes/{}/", self.uuid));
-        create_dir_all(&dst)?;
-        dst.push("data.mdb");
+    /// Copies the documents, the settings and the side store of the index to the index
+    /// directory `dst`.
+    pub fn copy_to(&self, dst: impl AsRef<Path>) -> Result<()> {
         let _txn = self.write_txn()?;
-        self.inner
-            .env
-            .copy_to_path(dst, milli::heed::CompactionOption::Enabled)?;
+        self.inner.env.copy_to_path(
+            dst.as_ref().join("data.mdb"),
+            milli::heed::CompactionOption::Enabled,
+        )?;
+        self.side.copy_to(dst)?;
         Ok(())
     }
+
+    pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
+        let dst = path.as_ref().join(format!("indexes/{}/", self.uuid));
+        create_dir_all(&dst)?;
+        self.copy_to(dst)
+    }
+}
+
+/// Returns the size of the keys and values stored in `db`.
+fn payload_size<KC, DC>(rtxn: &RoTxn, db: Database<KC, DC>) -> Result<u64> {
+    let mut size = 0;
+    for entry in db.remap_types::<ByteSlice, ByteSlice>().iter(rtxn)? {
+        let (key, value) = entry?;
+        size += (key.len() + value.len()) as u64;
+    }
+    Ok(size)
 }
 
 /// When running tests, when a server instance is dropped, the environment is not actually closed,
@@ -327,6 +717,7 @@ impl Drop for Index {
 
         if Arc::strong_count(&self.inner) == 1 {
             self.inner.as_ref().clone().prepare_for_closing();
+            self.side.env.clone().prepare_for_closing();
         }
     }
 }
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use milli::heed::RoTxn;
+use milli::obkv_to_json;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::batch_file::{for_each_document, BatchFile};
+use super::changes::document_id;
+use super::error::Result;
+use super::index::{Document, Index};
+use super::vector::AddedVectors;
+use crate::update_file_store::UpdateFileStore;
+
+/// Applies a JSON Merge Patch to a value following the RFC 7386.
+fn merge_patch(target: &mut Value, patch: Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(Document::new());
+            }
+            if let Value::Object(target) = target {
+                for (key, value) in patch {
+                    if value.is_null() {
+                        target.remove(&key);
+                    } else {
+                        merge_patch(target.entry(key).or_insert(Value::Null), value);
+                    }
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
+
+impl Index {
+    /// Applies the JSON Merge Patches of the update files `contents`, in the order they were
+    /// sent, to the documents they target as stored in `txn`, and returns the patched documents.
+    /// The patches of a document are applied on top of each other, and the ones targeting a
+    /// missing document are applied to an empty document. Also returns the ids of the documents
+    /// left unchanged because none of their patches was newer, when the index has a version
+    /// attribute, and the embeddings the patches set, which are taken out of the documents.
+    pub(crate) fn merge_patches(
+        &self,
+        txn: &RoTxn,
+        file_store: &UpdateFileStore,
+        contents: &[Uuid],
+    ) -> Result<(BatchFile, Vec<String>, AddedVectors)> {
+        let primary_key = self.primary_key(txn)?.map(String::from);
+        let patch_id = |patch: &Document| {
+            primary_key
+                .as_ref()
+                .and_then(|primary_key| document_id(patch.get(primary_key).cloned()))
+        };
+
+        // the documents patched several times are kept in memory until their last patch.
+        let mut remaining: HashMap<String, usize> = HashMap::new();
+        for_each_document(file_store, contents, |patch| {
+            if let Some(id) = patch_id(&patch) {
+                *remaining.entry(id).or_default() += 1;
+            }
+            Ok(())
+        })?;
+
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_ids = self.external_documents_ids(txn)?;
+        let mut check = self.version_check(txn, true)?;
+
+        let mut patched = HashMap::new();
+        let mut batch = BatchFile::new()?;
+        let mut vectors = AddedVectors::default();
+        for_each_document(file_store, contents, |mut patch| {
+            let id = match patch_id(&patch) {
+                Some(id) => id,
+                // the patches without a valid id are left to the engine, which rejects them.
+                None => {
+                    vectors.take(None, &mut patch, true);
+                    let mut document = Value::Object(Document::new());
+                    merge_patch(&mut document, Value::Object(patch));
+                    return batch.push(&document);
+                }
+            };
+
+            let accepted = match check.as_mut() {
+                Some(check) => check.accept(&patch)?,
+                None => true,
+            };
+            let document = match patched.remove(&id) {
+                Some(document) => Some(document),
+                // a rejected patch leaves the stored document as is.
+                None if !accepted => None,
+                None => match external_ids.get(id.as_bytes()) {
+                    Some(docid) => match self.documents(txn, std::iter::once(docid))?.pop() {
+                        Some((_, obkv)) => Some(Value::Object(obkv_to_json(
+                            &all_fields,
+                            &fields_ids_map,
+                            obkv,
+                        )?)),
+                        None => Some(Value::Object(Document::new())),
+                    },
+                    None => Some(Value::Object(Document::new())),
+                },
+            };
+            let document = document.map(|mut document| {
+                if accepted {
+                    // a patch replaces the embeddings of the document as a whole.
+                    vectors.take(Some(id.clone()), &mut patch, true);
+                    merge_patch(&mut document, Value::Object(patch));
+                }
+                document
+            });
+
+            let last = match remaining.get_mut(&id) {
+                Some(left) if *left > 1 => {
+                    *left -= 1;
+                    false
+                }
+                _ => true,
+            };
+            match document {
+                Some(document) if !last => {
+                    patched.insert(id, document);
+                    Ok(())
+                }
+                Some(document) => batch.push(&document),
+                None => Ok(()),
+            }
+        })?;
+
+        let rejected = check.map(|check| check.into_rejected()).unwrap_or_default();
+        Ok((batch, rejected, vectors))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_patch() {
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (
+                json!({"a": "b", "b": "c"}),
+                json!({"a": null}),
+                json!({"b": "c"}),
+            ),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (
+                json!({"a": [{"b": "c"}]}),
+                json!({"a": [1]}),
+                json!({"a": [1]}),
+            ),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!([1, 2]),
+                json!({"a": "b", "c": null}),
+                json!({"a": "b"}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+
+        for (mut target, patch, expected) in cases {
+            merge_patch(&mut target, patch);
+            assert_eq!(target, expected);
+        }
+    }
+}
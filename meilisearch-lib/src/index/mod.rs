@@ -1,18 +1,48 @@
+pub use changes::{ChangeKind, DocumentChange, DocumentChanges, DEFAULT_CHANGES_LIMIT};
+pub use dump::DumpMeta;
+pub use edition::check_edition_function;
+pub use facet_search::{FacetSearchQuery, FacetSearchResult};
+pub use facet_stats::FacetStats;
 pub use search::{
-    SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
+    AttributeRestrictions, HitsInfo, HybridQuery, MatchingStrategy, SearchHit, SearchQuery,
+    SearchResult, SearchTimings, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
     DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    DEFAULT_SEMANTIC_RATIO,
 };
+pub(crate) use side_store::SideStore;
+pub use similar::{SimilarQuery, SimilarResult, DEFAULT_SIMILAR_LIMIT};
+pub use suggest::{SuggestQuery, SuggestResult, DEFAULT_SUGGEST_LIMIT};
 pub use updates::{apply_settings_to_builder, Checked, Facets, Settings, Unchecked};
 
+mod batch_file;
+mod changes;
+mod did_you_mean;
 mod dump;
+mod edition;
 pub mod error;
+mod expiration;
+mod facet_search;
+mod facet_stats;
+mod facet_trigrams;
+mod field_presence;
+mod filter;
+mod frequency;
+mod merge_patch;
+mod negations;
 mod search;
+mod searched_attributes;
+mod side_store;
+mod similar;
+mod suggest;
+mod trash;
 pub mod updates;
+mod vector;
+mod versioning;
 
 #[allow(clippy::module_inception)]
 mod index;
 
-pub use index::{Document, IndexMeta, IndexStats};
+pub use index::{DetailedIndexStats, Document, IndexMeta, IndexStats};
 
 #[cfg(not(test))]
 pub use index::Index;
@@ -27,16 +57,25 @@ pub mod test {
     use std::path::Path;
     use std::path::PathBuf;
     use std::sync::Arc;
+    use std::time::Duration;
 
     use milli::update::IndexerConfig;
+
+    use crate::options::IndexerConfigHandle;
     use milli::update::{DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsMethod};
+    use milli::DocumentId;
     use nelson::Mocker;
+    use time::OffsetDateTime;
     use uuid::Uuid;
 
     use super::error::Result;
     use super::index::Index;
     use super::Document;
-    use super::{Checked, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings};
+    use super::{
+        Checked, DetailedIndexStats, FacetSearchQuery, FacetSearchResult, IndexMeta, IndexStats,
+        SearchQuery, SearchResult, Settings, SimilarQuery, SimilarResult, SuggestQuery,
+        SuggestResult,
+    };
     use crate::update_file_store::UpdateFileStore;
 
     #[derive(Clone)]
@@ -54,7 +93,7 @@ pub mod test {
             path: impl AsRef<Path>,
             size: usize,
             uuid: Uuid,
-            update_handler: Arc<IndexerConfig>,
+            update_handler: IndexerConfigHandle,
         ) -> Result<Self> {
             let index = Index::open(path, size, uuid, update_handler)?;
             Ok(Self::Real(index))
@@ -83,6 +122,13 @@ pub mod test {
             }
         }
 
+        pub fn detailed_stats(&self) -> Result<DetailedIndexStats> {
+            match self {
+                MockIndex::Real(index) => index.detailed_stats(),
+                MockIndex::Mock(m) => unsafe { m.get("detailed_stats").call(()) },
+            }
+        }
+
         pub fn meta(&self) -> Result<IndexMeta> {
             match self {
                 MockIndex::Real(index) => index.meta(),
@@ -101,10 +147,27 @@ pub mod test {
             offset: usize,
             limit: usize,
             attributes_to_retrieve: Option<Vec<S>>,
+            filter: Option<serde_json::Value>,
+            sort: Option<Vec<String>>,
         ) -> Result<(u64, Vec<Document>)> {
             match self {
                 MockIndex::Real(index) => {
-                    index.retrieve_documents(offset, limit, attributes_to_retrieve)
+                    index.retrieve_documents(offset, limit, attributes_to_retrieve, filter, sort)
+                }
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn retrieve_documents_after<S: AsRef<str>>(
+            &self,
+            after: Option<DocumentId>,
+            limit: usize,
+            attributes_to_retrieve: Option<Vec<S>>,
+            filter: Option<serde_json::Value>,
+        ) -> Result<(Vec<Document>, Option<DocumentId>)> {
+            match self {
+                MockIndex::Real(index) => {
+                    index.retrieve_documents_after(after, limit, attributes_to_retrieve, filter)
                 }
                 MockIndex::Mock(_) => todo!(),
             }
@@ -121,6 +184,61 @@ pub mod test {
             }
         }
 
+        pub fn retrieve_documents_by_ids<S: AsRef<str>>(
+            &self,
+            ids: Vec<String>,
+            attributes_to_retrieve: Option<Vec<S>>,
+        ) -> Result<(Vec<Document>, Vec<String>)> {
+            match self {
+                MockIndex::Real(index) => {
+                    index.retrieve_documents_by_ids(ids, attributes_to_retrieve)
+                }
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn edit_documents(
+            &self,
+            filter: Option<&serde_json::Value>,
+            function: &str,
+        ) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.edit_documents(filter, function),
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn document_changes(&self, since: u64, limit: usize) -> Result<DocumentChanges> {
+            match self {
+                MockIndex::Real(index) => index.document_changes(since, limit),
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn restore_documents(&self, ids: Option<&[String]>) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.restore_documents(ids),
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn expired_documents_ids(&self) -> Result<Vec<String>> {
+            match self {
+                MockIndex::Real(index) => index.expired_documents_ids(),
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn next_document_expiration(
+            &self,
+            now: OffsetDateTime,
+        ) -> Result<Option<OffsetDateTime>> {
+            match self {
+                MockIndex::Real(index) => index.next_document_expiration(now),
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
         pub fn size(&self) -> u64 {
             match self {
                 MockIndex::Real(index) => index.size(),
@@ -128,6 +246,13 @@ pub mod test {
             }
         }
 
+        pub fn copy_to(&self, dst: impl AsRef<Path>) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.copy_to(dst),
+                MockIndex::Mock(m) => unsafe { m.get("copy_to").call(dst.as_ref()) },
+            }
+        }
+
         pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.snapshot(path),
@@ -142,10 +267,41 @@ pub mod test {
             }
         }
 
-        pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
+        pub fn perform_search(
+            &self,
+            query: SearchQuery,
+            default_cutoff: Option<Duration>,
+        ) -> Result<SearchResult> {
             match self {
-                MockIndex::Real(index) => index.perform_search(query),
-                MockIndex::Mock(m) => unsafe { m.get("perform_search").call(query) },
+                MockIndex::Real(index) => index.perform_search(query, default_cutoff),
+                MockIndex::Mock(m) => unsafe {
+                    m.get("perform_search").call((query, default_cutoff))
+                },
+            }
+        }
+
+        pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_facet_search(query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_facet_search").call(query) },
+            }
+        }
+
+        pub fn perform_suggest(&self, query: SuggestQuery) -> Result<SuggestResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_suggest(query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_suggest").call(query) },
+            }
+        }
+
+        pub fn perform_similar(
+            &self,
+            doc_id: String,
+            query: SimilarQuery,
+        ) -> Result<SimilarResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_similar(doc_id, query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_similar").call((doc_id, query)) },
             }
         }
 
@@ -159,25 +315,40 @@ pub mod test {
         pub fn update_documents(
             &self,
             method: IndexDocumentsMethod,
+            merge_patch: bool,
             primary_key: Option<String>,
             file_store: UpdateFileStore,
             contents: impl Iterator<Item = Uuid>,
-        ) -> Result<DocumentAdditionResult> {
+            should_abort: impl Fn() -> bool,
+        ) -> Result<(DocumentAdditionResult, Vec<String>)> {
             match self {
-                MockIndex::Real(index) => {
-                    index.update_documents(method, primary_key, file_store, contents)
-                }
+                MockIndex::Real(index) => index.update_documents(
+                    method,
+                    merge_patch,
+                    primary_key,
+                    file_store,
+                    contents,
+                    should_abort,
+                ),
                 MockIndex::Mock(mocker) => unsafe {
-                    mocker
-                        .get("update_documents")
-                        .call((method, primary_key, file_store, contents))
+                    mocker.get("update_documents").call((
+                        method,
+                        merge_patch,
+                        primary_key,
+                        file_store,
+                        contents,
+                    ))
                 },
             }
         }
 
-        pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
+        pub fn update_settings(
+            &self,
+            settings: &Settings<Checked>,
+            should_abort: impl Fn() -> bool,
+        ) -> Result<()> {
             match self {
-                MockIndex::Real(index) => index.update_settings(settings),
+                MockIndex::Real(index) => index.update_settings(settings, should_abort),
                 MockIndex::Mock(m) => unsafe { m.get("update_settings").call(settings) },
             }
         }
@@ -196,6 +367,16 @@ pub mod test {
             }
         }
 
+        pub fn delete_documents_by_filter(
+            &self,
+            filter: &serde_json::Value,
+        ) -> Result<DocumentDeletionResult> {
+            match self {
+                MockIndex::Real(index) => index.delete_documents_by_filter(filter),
+                MockIndex::Mock(m) => unsafe { m.get("delete_documents_by_filter").call(filter) },
+            }
+        }
+
         pub fn clear_documents(&self) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.clear_documents(),
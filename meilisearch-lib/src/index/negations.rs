@@ -0,0 +1,158 @@
+use milli::heed::RoTxn;
+use milli::tokenizer::TokenizerBuilder;
+use roaring::RoaringBitmap;
+
+use super::error::Result;
+use super::index::Index;
+
+/// Splits the negated terms, `-term` and `-"exact phrase"`, out of the query `q`. Returns the
+/// rest of the query, given to the engine, and the negated terms.
+///
+/// A `-` only negates a term at the start of a word and outside of a phrase, so `spider-man` and
+/// `"well -known"` are left as written.
+pub(crate) fn split_negations(q: &str) -> (String, Vec<String>) {
+    let mut query = String::with_capacity(q.len());
+    let mut negated = Vec::new();
+    let mut in_phrase = false;
+    let mut word_start = true;
+    let mut rest = q;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '-' && word_start && !in_phrase {
+            let negation = &rest[1..];
+            if let Some(phrase) = negation.strip_prefix('"') {
+                // an unterminated phrase lasts until the end of the query.
+                let end = phrase.find('"').unwrap_or(phrase.len());
+                negated.push(phrase[..end].to_string());
+                rest = phrase.get(end + 1..).unwrap_or_default();
+                word_start = false;
+                continue;
+            }
+            let end = negation.find(char::is_whitespace).unwrap_or(negation.len());
+            if end > 0 && !negation.starts_with('-') {
+                negated.push(negation[..end].to_string());
+                rest = &negation[end..];
+                word_start = false;
+                continue;
+            }
+        }
+
+        if c == '"' {
+            in_phrase = !in_phrase;
+        }
+        word_start = c.is_whitespace();
+        query.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    (query, negated)
+}
+
+impl Index {
+    /// Returns the documents containing one of the `negated` terms. The words of a term made of
+    /// several words, like a phrase, must follow each other. The words are matched exactly,
+    /// without typos nor prefixes.
+    ///
+    /// When `fields` are given, the terms are only looked for in them, and all the words of a
+    /// term must appear in them.
+    pub(crate) fn negated_documents(
+        &self,
+        rtxn: &RoTxn,
+        negated: &[String],
+        fields: Option<&[String]>,
+    ) -> Result<RoaringBitmap> {
+        let tokenizer = TokenizerBuilder::default().build();
+        let fids = match fields {
+            Some(fields) => Some(self.attributes_fields_ids(rtxn, fields)?),
+            None => None,
+        };
+
+        let mut docids = RoaringBitmap::new();
+        for term in negated {
+            let words: Vec<_> = tokenizer
+                .tokenize(term)
+                .filter(|token| token.is_word())
+                .map(|token| token.lemma().to_string())
+                .collect();
+
+            match (words.as_slice(), &fids) {
+                ([], _) => (),
+                (words, Some(fids)) => {
+                    let mut term_docids: Option<RoaringBitmap> = None;
+                    for word in words {
+                        let word_docids = self.word_docids_in_fields(rtxn, word, fids)?;
+                        term_docids = Some(match term_docids {
+                            Some(term_docids) => term_docids & word_docids,
+                            None => word_docids,
+                        });
+                    }
+                    let mut term_docids = term_docids.unwrap_or_default();
+                    if words.len() > 1 {
+                        term_docids &=
+                            self.negated_documents(rtxn, std::slice::from_ref(term), None)?;
+                    }
+                    docids |= term_docids;
+                }
+                ([word], None) => {
+                    docids |= self.word_docids.get(rtxn, word)?.unwrap_or_default();
+                    docids |= self.exact_word_docids.get(rtxn, word)?.unwrap_or_default();
+                }
+                (words, None) => {
+                    // like for the phrases of the engine, the consecutive words are at a
+                    // proximity of 1.
+                    let mut phrase_docids: Option<RoaringBitmap> = None;
+                    for pair in words.windows(2) {
+                        let pair_docids = self
+                            .word_pair_proximity_docids
+                            .get(rtxn, &(pair[0].as_str(), pair[1].as_str(), 1))?
+                            .unwrap_or_default();
+                        let pair_docids = match phrase_docids {
+                            Some(phrase_docids) => phrase_docids & pair_docids,
+                            None => pair_docids,
+                        };
+                        let done = pair_docids.is_empty();
+                        phrase_docids = Some(pair_docids);
+                        if done {
+                            break;
+                        }
+                    }
+                    docids |= phrase_docids.unwrap_or_default();
+                }
+            }
+        }
+
+        Ok(docids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_negations() {
+        assert_eq!(
+            split_negations("captain -marvel"),
+            ("captain ".to_string(), vec!["marvel".to_string()])
+        );
+        assert_eq!(
+            split_negations("-\"hidden world\" dragon -glass"),
+            (
+                " dragon ".to_string(),
+                vec!["hidden world".to_string(), "glass".to_string()]
+            )
+        );
+        assert_eq!(
+            split_negations("spider-man \"well -known\" - -- a-"),
+            ("spider-man \"well -known\" - -- a-".to_string(), vec![])
+        );
+        assert_eq!(
+            split_negations("-\"unterminated phrase"),
+            (String::new(), vec!["unterminated phrase".to_string()])
+        );
+        assert_eq!(
+            split_negations("-shazam!"),
+            (String::new(), vec!["shazam!".to_string()])
+        );
+    }
+}
@@ -1,22 +1,27 @@
+use std::cell::Cell;
 use std::cmp::min;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use either::Either;
+use indexmap::IndexMap;
 use milli::tokenizer::TokenizerBuilder;
 use milli::{
-    AscDesc, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds, MatcherBuilder, SortError,
+    AscDesc, FieldId, FieldsIdsMap, FormatOptions, MatchBounds, MatcherBuilder, Member, SortError,
     DEFAULT_VALUES_PER_FACET,
 };
 use regex::Regex;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-
-use crate::index::error::FacetError;
+use time::OffsetDateTime;
 
 use super::error::{IndexError, Result};
+use super::facet_stats::FacetStats;
+use super::filter::{filter_attributes, parse_filter};
 use super::index::Index;
+use super::negations::split_negations;
+use super::updates::{FacetValuesSort, RankingProfile};
 
 pub type Document = serde_json::Map<String, Value>;
 type MatchesPosition = BTreeMap<String, Vec<MatchBounds>>;
@@ -26,6 +31,7 @@ pub const DEFAULT_CROP_LENGTH: fn() -> usize = || 10;
 pub const DEFAULT_CROP_MARKER: fn() -> String = || "…".to_string();
 pub const DEFAULT_HIGHLIGHT_PRE_TAG: fn() -> String = || "<em>".to_string();
 pub const DEFAULT_HIGHLIGHT_POST_TAG: fn() -> String = || "</em>".to_string();
+pub const DEFAULT_SEMANTIC_RATIO: fn() -> f32 = || 0.5;
 
 /// The maximimum number of results that the engine
 /// will be able to return in one search call.
@@ -38,6 +44,8 @@ pub struct SearchQuery {
     pub offset: Option<usize>,
     #[serde(default = "DEFAULT_SEARCH_LIMIT")]
     pub limit: usize,
+    pub page: Option<usize>,
+    pub hits_per_page: Option<usize>,
     pub attributes_to_retrieve: Option<BTreeSet<String>>,
     pub attributes_to_crop: Option<Vec<String>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
@@ -49,12 +57,202 @@ pub struct SearchQuery {
     pub filter: Option<Value>,
     pub sort: Option<Vec<String>>,
     pub facets: Option<Vec<String>>,
+    pub vector: Option<Vec<f32>>,
+    pub hybrid: Option<HybridQuery>,
+    #[serde(default)]
+    pub matching_strategy: MatchingStrategy,
+    pub attributes_to_search_on: Option<Vec<String>>,
+    pub distinct: Option<String>,
+    /// The weights of the searchable attributes for this query, the attributes without a weight
+    /// weigh `1`.
+    pub attribute_weights: Option<BTreeMap<String, f32>>,
+    /// The name of the ranking profile of the index whose parameters replace the ones of the query.
+    pub ranking_profile: Option<String>,
+    #[serde(default)]
+    pub show_suggestions: bool,
     #[serde(default = "DEFAULT_HIGHLIGHT_PRE_TAG")]
     pub highlight_pre_tag: String,
     #[serde(default = "DEFAULT_HIGHLIGHT_POST_TAG")]
     pub highlight_post_tag: String,
     #[serde(default = "DEFAULT_CROP_MARKER")]
     pub crop_marker: String,
+    /// The attributes the search may return, set from the search rules of the API key or the
+    /// tenant token rather than by the query.
+    #[serde(skip)]
+    pub attribute_restrictions: Option<AttributeRestrictions>,
+}
+
+/// Restricts the attributes returned by a search, in the hits, their formatted version, the
+/// matches positions and the facets. An attribute is matched by its name or by the name of an
+/// object containing it.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeRestrictions {
+    /// The only attributes that can be returned, all the displayed ones when `None`.
+    pub displayed_attributes: Option<BTreeSet<String>>,
+    /// The attributes that can never be returned, even when they are displayed.
+    pub forbidden_attributes: BTreeSet<String>,
+}
+
+impl AttributeRestrictions {
+    pub fn allows(&self, attribute: &str) -> bool {
+        let displayed = self
+            .displayed_attributes
+            .as_ref()
+            .map_or(true, |displayed| {
+                displayed
+                    .iter()
+                    .any(|allowed| milli::is_faceted_by(attribute, allowed))
+            });
+        displayed
+            && !self
+                .forbidden_attributes
+                .iter()
+                .any(|forbidden| milli::is_faceted_by(attribute, forbidden))
+    }
+
+    fn check(&self, attribute: &str, parameter: &'static str) -> Result<()> {
+        if self.allows(attribute) {
+            Ok(())
+        } else {
+            Err(IndexError::RestrictedSearchAttribute(
+                attribute.to_string(),
+                parameter,
+            ))
+        }
+    }
+
+    /// Returns an error if the `filter` uses a restricted attribute. It must be checked before
+    /// the filter of the search rules is added, which may use them.
+    pub fn check_filter(&self, filter: Option<&Value>) -> Result<()> {
+        match filter {
+            Some(filter) => filter_attributes(filter)
+                .iter()
+                .try_for_each(|attribute| self.check(attribute, "filter")),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error if the `query` filters, sorts, searches on, or deduplicates by a
+    /// restricted attribute. The query string is restricted when the search is performed.
+    pub fn check_query(&self, query: &SearchQuery) -> Result<()> {
+        self.check_filter(query.filter.as_ref())?;
+        for asc_desc in parse_sort(query.sort.as_deref().unwrap_or_default())? {
+            match asc_desc {
+                AscDesc::Asc(Member::Field(field)) | AscDesc::Desc(Member::Field(field)) => {
+                    self.check(&field, "sort")?
+                }
+                AscDesc::Asc(Member::Geo(_)) | AscDesc::Desc(Member::Geo(_)) => {
+                    self.check("_geo", "sort")?
+                }
+            }
+        }
+        for attribute in query.attributes_to_search_on.iter().flatten() {
+            self.check(attribute, "attributesToSearchOn")?;
+        }
+        if let Some(ref distinct) = query.distinct {
+            self.check(distinct, "distinct")?;
+        }
+        for attribute in query.attribute_weights.iter().flat_map(BTreeMap::keys) {
+            self.check(attribute, "attributeWeights")?;
+        }
+        Ok(())
+    }
+}
+
+/// The longest query string written in the slow query log, longer ones are truncated.
+const MAX_LOGGED_QUERY_LENGTH: usize = 256;
+
+impl SearchQuery {
+    /// Returns the parameters describing how the search is performed, for the slow query log.
+    /// The query string is truncated, and only the dimensions of the vector are kept.
+    pub fn sanitized(&self) -> Value {
+        let q = self
+            .q
+            .as_ref()
+            .map(|q| q.chars().take(MAX_LOGGED_QUERY_LENGTH).collect::<String>());
+        json!({
+            "q": q,
+            "offset": self.offset,
+            "limit": self.limit,
+            "page": self.page,
+            "hitsPerPage": self.hits_per_page,
+            "filter": self.filter,
+            "sort": self.sort,
+            "facets": self.facets,
+            "vectorDimensions": self.vector.as_ref().map(Vec::len),
+            "matchingStrategy": format!("{:?}", self.matching_strategy).to_lowercase(),
+            "attributesToSearchOn": self.attributes_to_search_on,
+            "distinct": self.distinct,
+            "attributeWeights": self.attribute_weights,
+            "rankingProfile": self.ranking_profile,
+        })
+    }
+
+    /// Returns the normalized form of the query, identical for two queries returning the same
+    /// results, to key the search cache.
+    pub fn cache_key(&self) -> String {
+        let attributes_to_highlight = self
+            .attributes_to_highlight
+            .as_ref()
+            .map(|attributes| attributes.iter().collect::<BTreeSet<_>>());
+        json!({
+            "q": self.q,
+            "offset": self.offset,
+            "limit": self.limit,
+            "page": self.page,
+            "hitsPerPage": self.hits_per_page,
+            "attributesToRetrieve": self.attributes_to_retrieve,
+            "attributesToCrop": self.attributes_to_crop,
+            "cropLength": self.crop_length,
+            "attributesToHighlight": attributes_to_highlight,
+            "showMatchesPosition": self.show_matches_position,
+            "filter": self.filter,
+            "sort": self.sort,
+            "facets": self.facets,
+            "vector": self.vector,
+            "semanticRatio": self.hybrid.map(|hybrid| hybrid.semantic_ratio),
+            "matchingStrategy": format!("{:?}", self.matching_strategy),
+            "attributesToSearchOn": self.attributes_to_search_on,
+            "distinct": self.distinct,
+            "attributeWeights": self.attribute_weights,
+            "rankingProfile": self.ranking_profile,
+            "showSuggestions": self.show_suggestions,
+            "highlightPreTag": self.highlight_pre_tag,
+            "highlightPostTag": self.highlight_post_tag,
+            "cropMarker": self.crop_marker,
+            "attributeRestrictions": self.attribute_restrictions,
+        })
+        .to_string()
+    }
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchingStrategy {
+    /// Remove the query words from last to first until enough documents are found.
+    Last,
+    /// All the query words are mandatory.
+    All,
+    /// Remove the query words from the most frequent to the least frequent until enough
+    /// documents are found.
+    Frequency,
+}
+
+impl Default for MatchingStrategy {
+    fn default() -> Self {
+        Self::Last
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HybridQuery {
+    /// The weight of the vector similarity in the final ranking, between `0.0` (keyword
+    /// ranking only) and `1.0` (vector ranking only).
+    #[serde(default = "DEFAULT_SEMANTIC_RATIO")]
+    pub semantic_ratio: f32,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -71,61 +269,408 @@ pub struct SearchHit {
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
     pub hits: Vec<SearchHit>,
-    pub estimated_total_hits: u64,
     pub query: String,
-    pub limit: usize,
-    pub offset: usize,
     pub processing_time_ms: u128,
+    #[serde(flatten)]
+    pub hits_info: HitsInfo,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub facet_distribution: Option<BTreeMap<String, BTreeMap<String, u64>>>,
+    pub facet_distribution: Option<BTreeMap<String, IndexMap<String, u64>>>,
+    /// The smallest and the largest values of the numeric facets, among the matching documents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_stats: Option<BTreeMap<String, FacetStats>>,
+    /// Whether the search cutoff was reached. The hits are then the best ones found until then,
+    /// their ranking may be partial and no suggestions are computed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+    /// Corrected versions of the query, computed on demand when the query matches few documents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestions: Option<Vec<String>>,
+    /// The time spent in each phase of the search, they are logged for the slow queries.
+    #[serde(skip)]
+    pub timings: SearchTimings,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchTimings {
+    /// Filtering and ranking the documents.
+    pub ranking: Duration,
+    /// Retrieving and formatting the hits.
+    pub formatting: Duration,
+    pub suggestions: Duration,
+    pub facets: Duration,
+}
+
+/// The pagination of a search result, depending on the pagination parameters of the query.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum HitsInfo {
+    /// The `page`/`hitsPerPage` mode, in which all the candidates of the search are counted, up
+    /// to the `maxTotalHits` of the index, rather than estimated from the ranked documents.
+    #[serde(rename_all = "camelCase")]
+    Pagination {
+        hits_per_page: usize,
+        page: usize,
+        total_pages: usize,
+        total_hits: u64,
+    },
+    /// The `offset`/`limit` mode, in which the number of matching documents is estimated.
+    #[serde(rename_all = "camelCase")]
+    OffsetLimit {
+        limit: usize,
+        offset: usize,
+        estimated_total_hits: u64,
+    },
+}
+
+impl HitsInfo {
+    /// Returns the exact or estimated number of documents matching the query.
+    pub fn total_hits(&self) -> u64 {
+        match self {
+            HitsInfo::Pagination { total_hits, .. } => *total_hits,
+            HitsInfo::OffsetLimit {
+                estimated_total_hits,
+                ..
+            } => *estimated_total_hits,
+        }
+    }
+}
+
+/// The instant after which a search stops refining its results and returns the best ones found
+/// so far. It remembers whether it was reached, the search result is then `degraded`.
+#[derive(Debug, Default)]
+pub(crate) struct Deadline {
+    at: Option<Instant>,
+    reached: Cell<bool>,
+}
+
+impl Deadline {
+    pub fn new(at: Option<Instant>) -> Self {
+        Self {
+            at,
+            reached: Cell::new(false),
+        }
+    }
+
+    /// Whether the deadline is reached now, or was reached before.
+    pub fn is_reached(&self) -> bool {
+        let reached = self.reached.get() || self.at.map_or(false, |at| Instant::now() >= at);
+        self.reached.set(reached);
+        reached
+    }
+
+    /// Whether the deadline was reached the last time it was checked.
+    pub fn was_reached(&self) -> bool {
+        self.reached.get()
+    }
 }
 
 impl Index {
-    pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
+    /// Performs the search described by the `query`.
+    ///
+    /// The search cutoff of the index, or the `default_cutoff` if the index doesn't define one,
+    /// bounds the time spent ranking the documents. The ranking done by the engine can't be
+    /// interrupted, but once the cutoff is reached the vector similarity ranking stops comparing
+    /// the documents, the attribute weights are no longer applied and no suggestions are
+    /// computed. The hits ranked so far are always returned, and the result is marked as
+    /// `degraded`.
+    pub fn perform_search(
+        &self,
+        mut query: SearchQuery,
+        default_cutoff: Option<Duration>,
+    ) -> Result<SearchResult> {
         let before_search = Instant::now();
         let rtxn = self.read_txn()?;
 
+        if let Some(name) = query.ranking_profile.clone() {
+            let mut profiles = self.ranking_profiles(&rtxn)?;
+            match profiles.remove(&name) {
+                Some(profile) => apply_ranking_profile(&mut query, profile),
+                None => {
+                    return Err(IndexError::InvalidSearchRankingProfile(
+                        name,
+                        profiles.into_keys().collect(),
+                    ))
+                }
+            }
+        }
+
+        let cutoff = self
+            .search_cutoff_ms(&rtxn)?
+            .map(Duration::from_millis)
+            .or(default_cutoff);
+        let deadline = Deadline::new(cutoff.map(|cutoff| before_search + cutoff));
+
+        let filter = match query.filter {
+            Some(ref filter) => parse_filter(filter)?,
+            None => None,
+        };
+
+        // The negated terms aren't given to the engine, the documents containing them are
+        // removed from the ranked documents.
+        let (q, negated) = match query.q {
+            Some(ref q) => {
+                let (q, negated) = split_negations(q);
+                (Some(q), negated)
+            }
+            None => (None, Vec::new()),
+        };
+        let q = match q {
+            Some(q) if query.matching_strategy == MatchingStrategy::Frequency => {
+                Some(self.order_terms_by_frequency(&rtxn, &q)?)
+            }
+            q => q,
+        };
+        // The query string can't match the documents through the restricted attributes.
+        let allowed_fields = match query.attribute_restrictions {
+            Some(ref restrictions) if query.q.is_some() => self.allowed_searched_fields(
+                &rtxn,
+                query.attributes_to_search_on.as_deref(),
+                restrictions,
+            )?,
+            _ => None,
+        };
+        let mut excluded = if negated.is_empty() {
+            None
+        } else {
+            Some(self.negated_documents(&rtxn, &negated, allowed_fields.as_deref())?)
+        };
+        // The negated terms can exclude any number of ranked documents, unlike the expired ones.
+        let excludes_terms = excluded.is_some();
+        // The expired documents are ignored until they are swept from the index.
+        let expired = self.expired_documents(OffsetDateTime::now_utc())?;
+        let expired_count = expired.len() as usize;
+        if !expired.is_empty() {
+            *excluded.get_or_insert_with(RoaringBitmap::new) |= expired;
+        }
+
         let mut search = self.search(&rtxn);
 
-        if let Some(ref query) = query.q {
-            search.query(query);
+        if let Some(ref q) = q {
+            search.query(q);
         }
 
+        search.optional_words(query.matching_strategy != MatchingStrategy::All);
+
         let pagination_limited_to = self
             .pagination_limited_to(&rtxn)?
             .unwrap_or(DEFAULT_PAGINATION_LIMITED_TO);
 
+        // The `page`/`hitsPerPage` parameters take precedence over `offset`/`limit`.
+        let is_finite_pagination = query.page.is_some() || query.hits_per_page.is_some();
+        let (offset, limit) = if is_finite_pagination {
+            let hits_per_page = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+            match query.page.unwrap_or(1).checked_sub(1) {
+                Some(page) => (page.saturating_mul(hits_per_page), hits_per_page),
+                // there are no hits before the first page.
+                None => (0, 0),
+            }
+        } else {
+            (query.offset.unwrap_or(0), query.limit)
+        };
+
         // Make sure that a user can't get more documents than the hard limit,
         // we align that on the offset too.
-        let offset = min(query.offset.unwrap_or(0), pagination_limited_to);
-        let limit = min(query.limit, pagination_limited_to.saturating_sub(offset));
+        let offset = min(offset, pagination_limited_to);
+        let limit = min(limit, pagination_limited_to.saturating_sub(offset));
 
         search.offset(offset);
         search.limit(limit);
 
-        if let Some(ref filter) = query.filter {
-            if let Some(facets) = parse_filter(filter)? {
-                search.filter(facets);
+        if let Some(ref filter) = filter {
+            if let Some(engine_filter) = filter.engine_filter()? {
+                search.filter(engine_filter);
             }
         }
 
         if let Some(ref sort) = query.sort {
-            let sort = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
-                Ok(sorts) => sorts,
-                Err(asc_desc_error) => {
-                    return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
+            search.sort_criteria(parse_sort(sort)?);
+        }
+
+        let semantic_ratio = match query.hybrid {
+            Some(HybridQuery { semantic_ratio }) => {
+                if query.vector.is_none() {
+                    return Err(IndexError::InvalidHybridQuery(
+                        "a `vector` must be provided to perform an hybrid search".to_string(),
+                    ));
                 }
-            };
+                if !(0.0..=1.0).contains(&semantic_ratio) {
+                    return Err(IndexError::InvalidHybridQuery(format!(
+                        "`semanticRatio` must be between 0.0 and 1.0, found `{}`",
+                        semantic_ratio
+                    )));
+                }
+                Some(semantic_ratio)
+            }
+            None => None,
+        };
+
+        if let Some(ref attributes) = query.attributes_to_search_on {
+            if let Some(searchable) = self.user_defined_searchable_fields(&rtxn)? {
+                if let Some(attribute) = attributes
+                    .iter()
+                    .find(|a| !searchable.contains(&a.as_str()))
+                {
+                    return Err(IndexError::InvalidAttributesToSearchOn(
+                        attribute.to_string(),
+                        searchable.into_iter().map(String::from).collect(),
+                    ));
+                }
+            }
+        }
 
-            search.sort_criteria(sort);
+        if let Some(ref distinct) = query.distinct {
+            let filterable = self.filterable_fields(&rtxn)?;
+            if !filterable.iter().any(|f| milli::is_faceted_by(distinct, f)) {
+                return Err(IndexError::InvalidSearchDistinct(
+                    distinct.to_string(),
+                    filterable.into_iter().collect(),
+                ));
+            }
+        }
+
+        if let Some(ref weights) = query.attribute_weights {
+            let searchable = self.user_defined_searchable_fields(&rtxn)?;
+            for (attribute, weight) in weights {
+                if !weight.is_finite() || *weight < 0.0 {
+                    return Err(IndexError::InvalidSearchAttributeWeights(format!(
+                        "the weight of `{}` must be a positive number, found `{}`",
+                        attribute, weight
+                    )));
+                }
+                if let Some(ref searchable) = searchable {
+                    if !searchable.contains(&attribute.as_str()) {
+                        return Err(IndexError::InvalidSearchAttributeWeights(format!(
+                            "attribute `{}` is not searchable. Available searchable attributes are: `{}`",
+                            attribute,
+                            searchable.join(", ")
+                        )));
+                    }
+                }
+            }
         }
 
+        // The engine can't weight the searchable attributes nor change the distinct attribute of
+        // a single query, so the ranked documents are processed afterward, on the whole
+        // pagination window. It has no effect on the vector ranking. The filters using operators
+        // the engine doesn't support, the negated terms and the restriction of the searchable
+        // attributes restrict all the ranked documents.
+        let searched_attributes = allowed_fields
+            .as_ref()
+            .or(query.attributes_to_search_on.as_ref());
+        let restricted = match (searched_attributes, &q) {
+            // like for the engine, a query without words is a placeholder search.
+            (Some(attributes), Some(q)) if !q.trim().is_empty() => {
+                Some(self.documents_matching_in_attributes(&rtxn, q, attributes)?)
+            }
+            _ => None,
+        };
+        let weighted_attributes = query.attribute_weights.as_ref().filter(|_| q.is_some());
+        let extended_filter = filter.as_ref().filter(|filter| filter.is_extended());
+        let post_process = query.vector.is_none()
+            && (restricted.is_some()
+                || weighted_attributes.is_some()
+                || query.distinct.is_some()
+                || extended_filter.is_some()
+                || excluded.is_some());
+        if post_process {
+            search.offset(0);
+            // at most `expired_count` of the ranked documents are dropped for having expired.
+            if extended_filter.is_some() || excludes_terms || restricted.is_some() {
+                search.limit(self.number_of_documents(&rtxn)? as usize);
+            } else if weighted_attributes.is_some() || query.distinct.is_some() {
+                search.limit(pagination_limited_to.saturating_add(expired_count));
+            } else {
+                search.limit(offset.saturating_add(limit).saturating_add(expired_count));
+            }
+        }
+
+        match (query.vector.is_some(), semantic_ratio) {
+            // When a vector is given, all the candidates are ranked by similarity
+            // and the pagination is applied afterward.
+            (true, None) => {
+                search.offset(0);
+                search.limit(0);
+            }
+            // An hybrid search blends the whole pagination window of both rankings.
+            (true, Some(_)) => {
+                search.offset(0);
+                search.limit(offset + limit);
+            }
+            (false, _) => (),
+        }
+
+        let mut timings = SearchTimings::default();
+        let before_ranking = Instant::now();
+
         let milli::SearchResult {
             documents_ids,
             matching_words,
             candidates,
             ..
-        } = search.execute()?;
+        } = tracing::info_span!("milli::search").in_scope(|| search.execute())?;
+
+        let (documents_ids, candidates) = match extended_filter {
+            Some(filter) => {
+                let matching = filter.evaluate(&rtxn, self)?;
+                let documents_ids = documents_ids
+                    .into_iter()
+                    .filter(|id| matching.contains(*id))
+                    .collect();
+                (documents_ids, candidates & matching)
+            }
+            None => (documents_ids, candidates),
+        };
+
+        let (documents_ids, candidates) = match excluded {
+            Some(ref excluded) => {
+                let documents_ids = documents_ids
+                    .into_iter()
+                    .filter(|id| !excluded.contains(*id))
+                    .collect();
+                (documents_ids, candidates - excluded)
+            }
+            None => (documents_ids, candidates),
+        };
+
+        let (documents_ids, candidates) = match restricted {
+            Some(ref restricted) => {
+                let documents_ids = documents_ids
+                    .into_iter()
+                    .filter(|id| restricted.contains(*id))
+                    .collect();
+                (documents_ids, candidates & restricted)
+            }
+            None => (documents_ids, candidates),
+        };
+
+        let (documents_ids, candidates) = match (&query.vector, semantic_ratio) {
+            (Some(vector), None) => {
+                let documents_ids =
+                    self.sort_by_vector_similarity(vector, &candidates, offset, limit, &deadline)?;
+                (documents_ids, candidates)
+            }
+            (Some(vector), Some(semantic_ratio)) => {
+                // the semantic part of the search is not restricted by the query terms.
+                let mut semantic_candidates = match filter {
+                    Some(ref filter) => filter.evaluate(&rtxn, self)?,
+                    None => self.documents_ids(&rtxn)?,
+                };
+                if let Some(ref excluded) = excluded {
+                    semantic_candidates -= excluded;
+                }
+                let documents_ids = self.hybrid_ranking(
+                    &documents_ids,
+                    vector,
+                    &semantic_candidates,
+                    semantic_ratio,
+                    offset,
+                    limit,
+                    &deadline,
+                )?;
+                (documents_ids, candidates | semantic_candidates)
+            }
+            (None, _) => (documents_ids, candidates),
+        };
 
         let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
 
@@ -133,6 +678,18 @@ impl Index {
             .displayed_fields_ids(&rtxn)?
             .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
             .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+        // the restricted attributes are handled as if they weren't displayed.
+        let displayed_ids: BTreeSet<_> = match query.attribute_restrictions {
+            Some(ref restrictions) => displayed_ids
+                .into_iter()
+                .filter(|id| {
+                    fields_ids_map
+                        .name(*id)
+                        .map_or(false, |name| restrictions.allows(name))
+                })
+                .collect(),
+            None => displayed_ids,
+        };
 
         let fids = |attrs: &BTreeSet<String>| {
             let mut ids = BTreeSet::new();
@@ -187,13 +744,57 @@ impl Index {
         formatter_builder.highlight_prefix(query.highlight_pre_tag);
         formatter_builder.highlight_suffix(query.highlight_post_tag);
 
+        let (documents_ids, candidates) = if post_process {
+            let mut documents_ids = documents_ids;
+            // once the cutoff is reached, the documents keep the ranking of the engine.
+            if let Some(weights) = weighted_attributes.filter(|_| !deadline.is_reached()) {
+                documents_ids = weight_documents(
+                    self.documents(&rtxn, documents_ids)?,
+                    weights,
+                    self.searchable_fields_ids(&rtxn)?.as_deref(),
+                    &fields_ids_map,
+                    &formatter_builder,
+                )?;
+            }
+            if let Some(ref distinct) = query.distinct {
+                documents_ids = distinct_documents(
+                    self.documents(&rtxn, documents_ids)?,
+                    distinct,
+                    &fields_ids_map,
+                )?;
+            }
+            // the candidates are already restricted like the ranked documents, only the distinct
+            // attribute changes their number.
+            let candidates = if query.distinct.is_some() {
+                documents_ids.iter().copied().collect()
+            } else {
+                candidates
+            };
+            let documents_ids = documents_ids.into_iter().skip(offset).take(limit).collect();
+            (documents_ids, candidates)
+        } else {
+            (documents_ids, candidates)
+        };
+
+        timings.ranking = before_ranking.elapsed();
+        // the ranked hits are returned even when the ranking took longer than the cutoff.
+        deadline.is_reached();
+        let before_formatting = Instant::now();
+
         let mut documents = Vec::new();
 
         let documents_iter = self.documents(&rtxn, documents_ids)?;
 
         for (_id, obkv) in documents_iter {
             // First generate a document with all the displayed fields
-            let displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
+            let mut displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
+            if let Some(ref restrictions) = query.attribute_restrictions {
+                remove_forbidden_attributes(
+                    &mut displayed_document,
+                    "",
+                    &restrictions.forbidden_attributes,
+                );
+            }
 
             // select the attributes to retrieve
             let attributes_to_retrieve = to_retrieve_ids
@@ -223,40 +824,172 @@ impl Index {
             documents.push(hit);
         }
 
-        let estimated_total_hits = candidates.len();
+        timings.formatting = before_formatting.elapsed();
+        let before_suggestions = Instant::now();
+
+        // The corrections are only worth it when the query doesn't fill the requested hits. They
+        // are taken from all the words of the index, so they could reveal the restricted
+        // attributes.
+        let suggestions = match q {
+            Some(ref q)
+                if query.show_suggestions
+                    && allowed_fields.is_none()
+                    && candidates.len() < limit as u64
+                    && !deadline.is_reached() =>
+            {
+                let mut filtered_candidates = match filter {
+                    Some(ref filter) => filter.evaluate(&rtxn, self)?,
+                    None => self.documents_ids(&rtxn)?,
+                };
+                if let Some(ref excluded) = excluded {
+                    filtered_candidates -= excluded;
+                }
+                Some(self.query_corrections(&rtxn, q, &filtered_candidates)?)
+            }
+            _ => None,
+        };
+        timings.suggestions = before_suggestions.elapsed();
+
+        let hits_info = if is_finite_pagination {
+            let hits_per_page = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+            // the hits are counted on the candidates, without ranking all of them.
+            let total_hits = match self.max_total_hits(&rtxn)? {
+                Some(max_total_hits) => candidates.len().min(max_total_hits as u64),
+                None => candidates.len(),
+            };
+            let total_pages = if hits_per_page == 0 {
+                0
+            } else {
+                (total_hits as usize).div_ceil(hits_per_page)
+            };
+            HitsInfo::Pagination {
+                hits_per_page,
+                page: query.page.unwrap_or(1),
+                total_pages,
+                total_hits,
+            }
+        } else {
+            HitsInfo::OffsetLimit {
+                limit: query.limit,
+                offset: query.offset.unwrap_or_default(),
+                estimated_total_hits: candidates.len(),
+            }
+        };
 
-        let facet_distribution = match query.facets {
+        let before_facets = Instant::now();
+        let (facet_distribution, facet_stats) = match query.facets {
             Some(ref fields) => {
                 let mut facet_distribution = self.facets_distribution(&rtxn);
 
                 let max_values_by_facet = self
                     .max_values_per_facet(&rtxn)?
                     .unwrap_or(DEFAULT_VALUES_PER_FACET);
-                facet_distribution.max_values_per_facet(max_values_by_facet);
+                let sort_facet_values_by = self.sort_facet_values_by(&rtxn)?;
+                let any_by_count = if fields.iter().any(|f| f == "*") {
+                    sort_facet_values_by
+                        .values()
+                        .any(|sort| *sort == FacetValuesSort::Count)
+                } else {
+                    fields.iter().any(|f| {
+                        facet_values_sort(&sort_facet_values_by, f) == FacetValuesSort::Count
+                    })
+                };
+                // the engine keeps the first values in lexicographic order, all of them are needed
+                // to keep the most frequent ones.
+                if any_by_count {
+                    facet_distribution.max_values_per_facet(usize::MAX);
+                } else {
+                    facet_distribution.max_values_per_facet(max_values_by_facet);
+                }
 
                 if fields.iter().all(|f| f != "*") {
                     facet_distribution.facets(fields);
                 }
-                let distribution = facet_distribution.candidates(candidates).execute()?;
-
-                Some(distribution)
+                let mut distribution = facet_distribution
+                    .candidates(candidates.clone())
+                    .execute()?;
+                if let Some(ref restrictions) = query.attribute_restrictions {
+                    distribution.retain(|field, _| restrictions.allows(field));
+                }
+                let stats = self.facet_stats(&rtxn, distribution.keys(), &candidates)?;
+
+                let distribution = distribution
+                    .into_iter()
+                    .map(|(field, values)| {
+                        let sort = facet_values_sort(&sort_facet_values_by, &field);
+                        let values = sort_facet_values(values, sort, max_values_by_facet);
+                        (field, values)
+                    })
+                    .collect();
+
+                (Some(distribution), Some(stats))
             }
-            None => None,
+            None => (None, None),
         };
+        timings.facets = before_facets.elapsed();
 
         let result = SearchResult {
             hits: documents,
-            estimated_total_hits,
             query: query.q.clone().unwrap_or_default(),
-            limit: query.limit,
-            offset: query.offset.unwrap_or_default(),
             processing_time_ms: before_search.elapsed().as_millis(),
+            hits_info,
             facet_distribution,
+            facet_stats,
+            degraded: deadline.was_reached(),
+            suggestions,
+            timings,
         };
         Ok(result)
     }
 }
 
+/// Returns the order of the values of the `field` facet.
+/// Replaces the parameters of the `query` with the ones set by the ranking `profile`.
+fn apply_ranking_profile(query: &mut SearchQuery, profile: RankingProfile) {
+    let RankingProfile {
+        sort,
+        matching_strategy,
+        attributes_to_search_on,
+        attribute_weights,
+    } = profile;
+
+    if sort.is_some() {
+        query.sort = sort;
+    }
+    if let Some(matching_strategy) = matching_strategy {
+        query.matching_strategy = matching_strategy;
+    }
+    if attributes_to_search_on.is_some() {
+        query.attributes_to_search_on = attributes_to_search_on;
+    }
+    if attribute_weights.is_some() {
+        query.attribute_weights = attribute_weights;
+    }
+}
+
+fn facet_values_sort(sort_by: &BTreeMap<String, FacetValuesSort>, field: &str) -> FacetValuesSort {
+    sort_by
+        .get(field)
+        .or_else(|| sort_by.get("*"))
+        .copied()
+        .unwrap_or(FacetValuesSort::Alpha)
+}
+
+/// Orders the values of a facet distribution and keeps the first `max_values` ones.
+fn sort_facet_values(
+    values: BTreeMap<String, u64>,
+    sort: FacetValuesSort,
+    max_values: usize,
+) -> IndexMap<String, u64> {
+    let mut values: Vec<_> = values.into_iter().collect();
+    if sort == FacetValuesSort::Count {
+        // `sort_by` is stable, values with the same count stay in lexicographic order.
+        values.sort_by(|(_, left), (_, right)| right.cmp(left));
+    }
+    values.truncate(max_values);
+    values.into_iter().collect()
+}
+
 fn insert_geo_distance(sorts: &[String], document: &mut Document) {
     lazy_static::lazy_static! {
         static ref GEO_REGEX: Regex =
@@ -391,6 +1124,89 @@ fn add_non_formatted_ids_to_formatted_options(
     }
 }
 
+/// Sorts the documents by the weight of their heaviest searchable attribute matching the query,
+/// the attributes without a weight weigh `1`. The documents whose heaviest attributes weigh the
+/// same are kept in the order they were given, the ranking of the engine.
+fn weight_documents<'a, A: AsRef<[u8]>>(
+    documents: Vec<(u32, obkv::KvReaderU16)>,
+    weights: &BTreeMap<String, f32>,
+    searchable: Option<&[FieldId]>,
+    field_ids_map: &FieldsIdsMap,
+    builder: &MatcherBuilder<'a, A>,
+) -> Result<Vec<u32>> {
+    let fids: Vec<_> = field_ids_map
+        .iter()
+        .filter(|(fid, _)| searchable.map_or(true, |searchable| searchable.contains(fid)))
+        .map(|(fid, name)| (fid, attribute_weight(name, weights)))
+        .collect();
+
+    let mut weighted = Vec::with_capacity(documents.len());
+    for (id, obkv) in documents {
+        let mut heaviest = 0.0;
+        for &(fid, weight) in &fids {
+            if weight <= heaviest {
+                continue;
+            }
+            if let Some(value) = obkv.get(fid) {
+                let value: Value = serde_json::from_slice(value)?;
+                if value_matches(&value, builder) {
+                    heaviest = weight;
+                }
+            }
+        }
+        weighted.push((id, heaviest));
+    }
+
+    weighted.sort_by(|(_, left), (_, right)| right.total_cmp(left));
+    Ok(weighted.into_iter().map(|(id, _)| id).collect())
+}
+
+/// Returns the weight of the attribute `name`, the one of the closest weighted attribute
+/// containing it for the nested attributes.
+fn attribute_weight(name: &str, weights: &BTreeMap<String, f32>) -> f32 {
+    weights
+        .iter()
+        .filter(|(attribute, _)| milli::is_faceted_by(name, attribute))
+        .max_by_key(|(attribute, _)| attribute.len())
+        .map_or(1.0, |(_, weight)| *weight)
+}
+
+/// Returns the ids of the documents, in the order they were given, keeping only the first
+/// document for each value of the `distinct` attribute. Documents without a value are kept.
+fn distinct_documents(
+    documents: Vec<(u32, obkv::KvReaderU16)>,
+    distinct: &str,
+    field_ids_map: &FieldsIdsMap,
+) -> Result<Vec<u32>> {
+    let fids: BTreeSet<_> = field_ids_map
+        .iter()
+        .filter(|(_, name)| milli::is_faceted_by(distinct, name))
+        .map(|(fid, _)| fid)
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut documents_ids = Vec::new();
+    for (id, obkv) in documents {
+        let document = make_document(&fids, field_ids_map, obkv)?;
+        let value = permissive_json_pointer::select_values(&document, std::iter::once(distinct));
+        if value.is_empty() || seen.insert(serde_json::to_string(&value)?) {
+            documents_ids.push(id);
+        }
+    }
+
+    Ok(documents_ids)
+}
+
+fn value_matches<'a, A: AsRef<[u8]>>(value: &Value, builder: &MatcherBuilder<'a, A>) -> bool {
+    match value {
+        Value::String(string) => !builder.build(string).matches().is_empty(),
+        Value::Number(number) => !builder.build(&number.to_string()).matches().is_empty(),
+        Value::Array(values) => values.iter().any(|v| value_matches(v, builder)),
+        Value::Object(object) => object.values().any(|v| value_matches(v, builder)),
+        _ => false,
+    }
+}
+
 fn make_document(
     displayed_attributes: &BTreeSet<FieldId>,
     field_ids_map: &FieldsIdsMap,
@@ -418,6 +1234,41 @@ fn make_document(
     Ok(document)
 }
 
+/// Removes the attributes of the `object` named `prefix` matched by the `forbidden` ones, the
+/// nested ones included.
+pub(crate) fn remove_forbidden_attributes(
+    object: &mut Document,
+    prefix: &str,
+    forbidden: &BTreeSet<String>,
+) {
+    let mut removed = Vec::new();
+    for (key, value) in object.iter_mut() {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if forbidden.iter().any(|f| milli::is_faceted_by(&name, f)) {
+            removed.push(key.clone());
+        } else {
+            remove_nested_forbidden_attributes(value, &name, forbidden);
+        }
+    }
+    for key in removed {
+        object.remove(&key);
+    }
+}
+
+fn remove_nested_forbidden_attributes(value: &mut Value, name: &str, forbidden: &BTreeSet<String>) {
+    match value {
+        Value::Object(object) => remove_forbidden_attributes(object, name, forbidden),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| remove_nested_forbidden_attributes(value, name, forbidden)),
+        _ => (),
+    }
+}
+
 fn format_fields<'a, A: AsRef<[u8]>>(
     document: &Document,
     field_ids_map: &FieldsIdsMap,
@@ -554,43 +1405,11 @@ fn format_value<'a, A: AsRef<[u8]>>(
     }
 }
 
-fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
-    match facets {
-        Value::String(expr) => {
-            let condition = Filter::from_str(expr)?;
-            Ok(condition)
-        }
-        Value::Array(arr) => parse_filter_array(arr),
-        v => Err(FacetError::InvalidExpression(&["Array"], v.clone()).into()),
-    }
-}
-
-fn parse_filter_array(arr: &[Value]) -> Result<Option<Filter>> {
-    let mut ands = Vec::new();
-    for value in arr {
-        match value {
-            Value::String(s) => ands.push(Either::Right(s.as_str())),
-            Value::Array(arr) => {
-                let mut ors = Vec::new();
-                for value in arr {
-                    match value {
-                        Value::String(s) => ors.push(s.as_str()),
-                        v => {
-                            return Err(FacetError::InvalidExpression(&["String"], v.clone()).into())
-                        }
-                    }
-                }
-                ands.push(Either::Left(ors));
-            }
-            v => {
-                return Err(
-                    FacetError::InvalidExpression(&["String", "[String]"], v.clone()).into(),
-                )
-            }
-        }
+pub(crate) fn parse_sort(sort: &[String]) -> Result<Vec<AscDesc>> {
+    match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+        Ok(sorts) => Ok(sorts),
+        Err(asc_desc_error) => Err(IndexError::Milli(SortError::from(asc_desc_error).into())),
     }
-
-    Ok(Filter::from_array(ands)?)
 }
 
 #[cfg(test)]
@@ -657,4 +1476,137 @@ mod test {
         insert_geo_distance(sorters, &mut document);
         assert_eq!(document.get("_geoDistance"), None);
     }
+
+    #[test]
+    fn test_sort_facet_values() {
+        let sort_by = BTreeMap::from([
+            ("*".to_string(), FacetValuesSort::Count),
+            ("genre".to_string(), FacetValuesSort::Alpha),
+        ]);
+        assert_eq!(facet_values_sort(&sort_by, "genre"), FacetValuesSort::Alpha);
+        assert_eq!(
+            facet_values_sort(&sort_by, "author"),
+            FacetValuesSort::Count
+        );
+        assert_eq!(
+            facet_values_sort(&BTreeMap::new(), "author"),
+            FacetValuesSort::Alpha
+        );
+
+        let values = BTreeMap::from([
+            ("action".to_string(), 2),
+            ("comedy".to_string(), 5),
+            ("drama".to_string(), 2),
+        ]);
+        let sorted = sort_facet_values(values.clone(), FacetValuesSort::Count, 2);
+        assert_eq!(
+            sorted.into_iter().collect::<Vec<_>>(),
+            vec![("comedy".to_string(), 5), ("action".to_string(), 2)]
+        );
+        let sorted = sort_facet_values(values, FacetValuesSort::Alpha, 10);
+        assert_eq!(
+            sorted.keys().collect::<Vec<_>>(),
+            vec!["action", "comedy", "drama"]
+        );
+    }
+
+    #[test]
+    fn test_attribute_weight() {
+        let weights = BTreeMap::from([("title".to_string(), 3.0), ("info".to_string(), 0.5)]);
+        assert_eq!(attribute_weight("title", &weights), 3.0);
+        assert_eq!(attribute_weight("info.author", &weights), 0.5);
+        assert_eq!(attribute_weight("overview", &weights), 1.0);
+        assert_eq!(attribute_weight("titles", &weights), 1.0);
+    }
+
+    #[test]
+    fn test_apply_ranking_profile() {
+        let mut query: SearchQuery = serde_json::from_value(json!({
+            "q": "hello",
+            "sort": ["price:asc"],
+            "attributesToSearchOn": ["title"],
+        }))
+        .unwrap();
+        let profile = RankingProfile {
+            sort: Some(vec!["rating:desc".to_string()]),
+            matching_strategy: Some(MatchingStrategy::All),
+            ..Default::default()
+        };
+
+        apply_ranking_profile(&mut query, profile);
+        assert_eq!(query.sort, Some(vec!["rating:desc".to_string()]));
+        assert_eq!(query.matching_strategy, MatchingStrategy::All);
+        // the parameters the profile doesn't set are kept.
+        assert_eq!(
+            query.attributes_to_search_on,
+            Some(vec!["title".to_string()])
+        );
+        assert_eq!(query.attribute_weights, None);
+    }
+
+    #[test]
+    fn test_attribute_restrictions() {
+        let restrictions = AttributeRestrictions {
+            displayed_attributes: Some(BTreeSet::from([
+                "title".to_string(),
+                "contact".to_string(),
+            ])),
+            forbidden_attributes: BTreeSet::from(["contact.email".to_string()]),
+        };
+        assert!(restrictions.allows("title"));
+        assert!(restrictions.allows("contact.phone"));
+        assert!(!restrictions.allows("contact.email"));
+        assert!(!restrictions.allows("internal_notes"));
+
+        let mut document = json!({
+            "title": "hello",
+            "contact": { "email": "a@b.c", "phone": "123" },
+            "reviews": [{ "contact": { "email": "d@e.f" } }],
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let forbidden =
+            BTreeSet::from(["contact.email".to_string(), "reviews.contact".to_string()]);
+        remove_forbidden_attributes(&mut document, "", &forbidden);
+        assert_eq!(
+            Value::Object(document),
+            json!({ "title": "hello", "contact": { "phone": "123" }, "reviews": [{}] })
+        );
+    }
+
+    #[test]
+    fn test_restricted_query_attributes() {
+        let restrictions = AttributeRestrictions {
+            displayed_attributes: None,
+            forbidden_attributes: BTreeSet::from(["contact.email".to_string(), "_geo".to_string()]),
+        };
+        let query = |query: Value| serde_json::from_value::<SearchQuery>(query).unwrap();
+
+        let allowed = query(json!({
+            "q": "hello",
+            "filter": [["title = hello", "contact.phone EXISTS"]],
+            "sort": ["title:asc"],
+            "attributesToSearchOn": ["title"],
+        }));
+        assert!(restrictions.check_query(&allowed).is_ok());
+
+        for (restricted, parameter) in [
+            (json!({"filter": "contact.email = a"}), "filter"),
+            (json!({"filter": "contact.email IS NOT EMPTY"}), "filter"),
+            (json!({"filter": "_geoRadius(45.4, 9.1, 2000)"}), "filter"),
+            (json!({"sort": ["contact.email:desc"]}), "sort"),
+            (json!({"sort": ["_geoPoint(45.4, 9.1):asc"]}), "sort"),
+            (
+                json!({"attributesToSearchOn": ["contact.email"]}),
+                "attributesToSearchOn",
+            ),
+            (json!({"distinct": "contact.email"}), "distinct"),
+        ] {
+            match restrictions.check_query(&query(restricted.clone())) {
+                Err(IndexError::RestrictedSearchAttribute(_, p)) => assert_eq!(p, parameter),
+                otherwise => panic!("{}: {:?}", restricted, otherwise),
+            }
+        }
+    }
 }
@@ -0,0 +1,144 @@
+use fst::automaton::{Automaton, Str};
+use fst::IntoStreamer;
+use milli::heed::RoTxn;
+use milli::tokenizer::TokenizerBuilder;
+use milli::FieldId;
+use roaring::RoaringBitmap;
+
+use super::did_you_mean::{levenshtein, same_first_letter};
+use super::error::Result;
+use super::index::Index;
+use super::search::AttributeRestrictions;
+
+impl Index {
+    /// Returns the ids of the fields matched by the `attributes`, by their name or by the name
+    /// of an object containing them.
+    pub(crate) fn attributes_fields_ids(
+        &self,
+        rtxn: &RoTxn,
+        attributes: &[String],
+    ) -> Result<Vec<FieldId>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        Ok(fields_ids_map
+            .iter()
+            .filter(|(_, name)| {
+                attributes
+                    .iter()
+                    .any(|attr| milli::is_faceted_by(name, attr))
+            })
+            .map(|(fid, _)| fid)
+            .collect())
+    }
+
+    /// Returns the fields the query string may match with the `restrictions`, among the
+    /// `searched` attributes or all the searchable ones, or `None` when none of them is
+    /// restricted. The fields are listed one by one, so that the restricted fields of an object
+    /// are left out.
+    pub(crate) fn allowed_searched_fields(
+        &self,
+        rtxn: &RoTxn,
+        searched: Option<&[String]>,
+        restrictions: &AttributeRestrictions,
+    ) -> Result<Option<Vec<String>>> {
+        let searchable = match searched {
+            Some(searched) => Some(searched.to_vec()),
+            None => self
+                .user_defined_searchable_fields(rtxn)?
+                .map(|fields| fields.into_iter().map(String::from).collect()),
+        };
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let fields: Vec<_> = fields_ids_map
+            .iter()
+            .map(|(_, name)| name)
+            .filter(|name| match searchable {
+                Some(ref searchable) => searchable
+                    .iter()
+                    .any(|attr| milli::is_faceted_by(name, attr)),
+                None => true,
+            })
+            .collect();
+
+        if fields.iter().all(|name| restrictions.allows(name)) {
+            return Ok(None);
+        }
+        Ok(Some(
+            fields
+                .into_iter()
+                .filter(|name| restrictions.allows(name))
+                .map(String::from)
+                .collect(),
+        ))
+    }
+
+    /// Returns the documents in which the `word` appears as is in one of the fields `fids`.
+    pub(crate) fn word_docids_in_fields(
+        &self,
+        rtxn: &RoTxn,
+        word: &str,
+        fids: &[FieldId],
+    ) -> Result<RoaringBitmap> {
+        let mut docids = RoaringBitmap::new();
+        for fid in fids {
+            // the engine stores the positions of the words with their attribute in the upper 16
+            // bits.
+            let start = (*fid as u32) << 16;
+            let range = (word, start)..=(word, start | 0xFFFF);
+            for entry in self.word_position_docids.range(rtxn, &range)? {
+                let ((position_word, _), position_docids) = entry?;
+                if position_word == word {
+                    docids |= position_docids;
+                }
+            }
+        }
+        Ok(docids)
+    }
+
+    /// Returns the documents in which one of the words of the query `q` appears in one of the
+    /// `attributes`. The words are matched within the typo tolerance of the index, and the last
+    /// one also as a prefix, like the engine does.
+    ///
+    /// The documents are computed before the ranking, so the restriction applies to all the
+    /// documents matching the query and not only to the ones the engine ranks first.
+    pub(crate) fn documents_matching_in_attributes(
+        &self,
+        rtxn: &RoTxn,
+        q: &str,
+        attributes: &[String],
+    ) -> Result<RoaringBitmap> {
+        let fids = self.attributes_fields_ids(rtxn, attributes)?;
+        if fids.is_empty() {
+            return Ok(RoaringBitmap::new());
+        }
+
+        let tokenizer = TokenizerBuilder::default().build();
+        let words: Vec<_> = tokenizer
+            .tokenize(q)
+            .filter(|token| token.is_word())
+            .map(|token| token.lemma().to_string())
+            .collect();
+        let words_fst = self.words_fst(rtxn)?;
+
+        let mut docids = RoaringBitmap::new();
+        for (i, word) in words.iter().enumerate() {
+            let max_typos = self.max_typos(rtxn, word)?;
+            let mut indexed_words: Vec<_> = same_first_letter(&words_fst, word)?
+                .into_iter()
+                .filter(|indexed| levenshtein(word, indexed) <= max_typos)
+                .collect();
+            if i == words.len() - 1 {
+                indexed_words.extend(
+                    words_fst
+                        .search(Str::new(word).starts_with())
+                        .into_stream()
+                        .into_strs()?,
+                );
+            }
+
+            for indexed in &indexed_words {
+                docids |= self.word_docids_in_fields(rtxn, indexed, &fids)?;
+            }
+        }
+
+        Ok(docids)
+    }
+}
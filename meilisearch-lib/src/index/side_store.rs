@@ -0,0 +1,85 @@
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use milli::heed::byteorder::BE;
+use milli::heed::types::{ByteSlice, OwnedType, SerdeJson, Str, Unit};
+use milli::heed::zerocopy::U32;
+use milli::heed::{CompactionOption, Database, Env, EnvOpenOptions};
+
+use super::error::Result;
+use super::index::Document;
+
+/// The directory of an index holding the environment of its side store.
+const SIDE_STORE_DIR: &str = "side-store";
+
+const DELETED_DOCUMENTS_DB_NAME: &str = "deleted-documents";
+const DELETION_DATES_DB_NAME: &str = "deletion-dates";
+const VECTORS_DB_NAME: &str = "vectors";
+const EXPIRATIONS_DB_NAME: &str = "expirations";
+const EXPIRATION_DATES_DB_NAME: &str = "expiration-dates";
+
+/// A key of the expirations database, see [`SideStore::expirations`].
+pub(crate) type ExpirationKey = [u8; 12];
+
+/// The databases meilisearch keeps next to the ones of the engine, for the data the engine
+/// doesn't know about and that is too large for its main database. They live in their own
+/// environment, since the one of the engine can't hold more databases.
+#[derive(Clone)]
+pub struct SideStore {
+    pub(crate) env: Env,
+    /// The deleted documents that can still be restored, by external id.
+    pub(crate) deleted_documents: Database<Str, SerdeJson<Document>>,
+    /// The unix timestamp each deleted document was deleted at, by external id. They are kept
+    /// apart from the documents so that the retention is applied without reading them.
+    pub(crate) deletion_dates: Database<Str, OwnedType<i64>>,
+    /// The embeddings of the documents, by internal id, encoded as the `vector` module does.
+    pub(crate) vectors: Database<OwnedType<U32<BE>>, ByteSlice>,
+    /// The documents with an expiry date, by expiry date then internal id, so that the expired
+    /// documents are read with a range, see the `expiration` module.
+    pub(crate) expirations: Database<OwnedType<ExpirationKey>, Unit>,
+    /// The unix timestamp each document with an expiry date expires at, by internal id, to find
+    /// its key in `expirations` once it changes.
+    pub(crate) expiration_dates: Database<OwnedType<U32<BE>>, OwnedType<i64>>,
+}
+
+impl SideStore {
+    /// Returns the path of the side store of the index stored in `index_path`.
+    pub fn path(index_path: impl AsRef<Path>) -> PathBuf {
+        index_path.as_ref().join(SIDE_STORE_DIR)
+    }
+
+    pub fn open(index_path: impl AsRef<Path>, size: usize) -> Result<Self> {
+        let path = Self::path(index_path);
+        create_dir_all(&path)?;
+        let mut options = EnvOpenOptions::new();
+        options.map_size(size);
+        options.max_dbs(6);
+        let env = options.open(path)?;
+
+        let mut wtxn = env.write_txn()?;
+        let deleted_documents = env.create_database(&mut wtxn, Some(DELETED_DOCUMENTS_DB_NAME))?;
+        let deletion_dates = env.create_database(&mut wtxn, Some(DELETION_DATES_DB_NAME))?;
+        let vectors = env.create_database(&mut wtxn, Some(VECTORS_DB_NAME))?;
+        let expirations = env.create_database(&mut wtxn, Some(EXPIRATIONS_DB_NAME))?;
+        let expiration_dates = env.create_database(&mut wtxn, Some(EXPIRATION_DATES_DB_NAME))?;
+        wtxn.commit()?;
+
+        Ok(Self {
+            env,
+            deleted_documents,
+            deletion_dates,
+            vectors,
+            expirations,
+            expiration_dates,
+        })
+    }
+
+    /// Copies the side store to the side store directory of the index stored in `index_path`.
+    pub fn copy_to(&self, index_path: impl AsRef<Path>) -> Result<()> {
+        let dst = Self::path(index_path);
+        create_dir_all(&dst)?;
+        self.env
+            .copy_to_path(dst.join("data.mdb"), CompactionOption::Enabled)?;
+        Ok(())
+    }
+}
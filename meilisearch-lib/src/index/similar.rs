@@ -0,0 +1,235 @@
+use std::cmp::{min, Ordering};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+use milli::obkv_to_json;
+use milli::tokenizer::TokenizerBuilder;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::error::{IndexError, Result};
+use super::filter::parse_filter;
+use super::index::Index;
+use super::search::{
+    remove_forbidden_attributes, AttributeRestrictions, Deadline, DEFAULT_PAGINATION_LIMITED_TO,
+};
+use super::Document;
+
+pub const DEFAULT_SIMILAR_LIMIT: fn() -> usize = || 20;
+
+/// The maximum number of distinct terms of the reference document used to find the similar ones.
+const MAX_SIMILARITY_TERMS: usize = 25;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SimilarQuery {
+    pub filter: Option<Value>,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default = "DEFAULT_SIMILAR_LIMIT")]
+    pub limit: usize,
+    pub attributes_to_retrieve: Option<BTreeSet<String>>,
+    /// The attributes the similar documents may return, set from the search rules.
+    #[serde(skip)]
+    pub attribute_restrictions: Option<AttributeRestrictions>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarResult {
+    pub id: String,
+    pub hits: Vec<Document>,
+    pub offset: usize,
+    pub limit: usize,
+    pub estimated_total_hits: u64,
+    pub processing_time_ms: u128,
+}
+
+impl Index {
+    /// Ranks the documents matching the filter by similarity with the document `doc_id`, which
+    /// is never part of the results.
+    ///
+    /// When the reference document was sent with an embedding, the documents are ranked by cosine similarity with it and the ones without a comparable embedding are left
+    /// out. Otherwise the most significant terms of its searchable attributes are weighted by
+    /// tf-idf, and the documents are ranked by the total weight of the terms they share with it.
+    pub fn perform_similar(&self, doc_id: String, query: SimilarQuery) -> Result<SimilarResult> {
+        let before_similar = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let internal_id = self
+            .external_documents_ids(&rtxn)?
+            .get(doc_id.as_bytes())
+            .ok_or_else(|| IndexError::DocumentNotFound(doc_id.clone()))?;
+
+        let fields_ids_map = self.fields_ids_map(&rtxn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let reference = self
+            .documents(&rtxn, std::iter::once(internal_id))?
+            .into_iter()
+            .next()
+            .map(|(_, obkv)| obkv_to_json(&all_fields, &fields_ids_map, obkv))
+            .transpose()?
+            .ok_or_else(|| IndexError::DocumentNotFound(doc_id.clone()))?;
+
+        let mut candidates = match query.filter {
+            Some(ref filter) => match parse_filter(filter)? {
+                Some(filter) => filter.evaluate(&rtxn, self)?,
+                None => self.documents_ids(&rtxn)?,
+            },
+            None => self.documents_ids(&rtxn)?,
+        };
+        candidates.remove(internal_id);
+
+        let pagination_limited_to = self
+            .pagination_limited_to(&rtxn)?
+            .unwrap_or(DEFAULT_PAGINATION_LIMITED_TO);
+        let offset = min(query.offset, pagination_limited_to);
+        let limit = min(query.limit, pagination_limited_to.saturating_sub(offset));
+
+        let embedding = self
+            .document_embeddings(&self.side.env.read_txn()?, internal_id)?
+            .into_iter()
+            .next();
+        let (ranked, estimated_total_hits) = match embedding {
+            Some(embedding) => {
+                let (similarities, compared) = self.vector_similarities(
+                    &embedding,
+                    &candidates,
+                    offset + limit,
+                    &Deadline::default(),
+                )?;
+                let ranked: Vec<_> = similarities.into_iter().map(|(id, _)| id).collect();
+                (ranked, compared.len())
+            }
+            None => {
+                let searchable = self.searchable_fields_ids(&rtxn)?;
+                let mut text = Vec::new();
+                for (fid, name) in fields_ids_map.iter() {
+                    let is_searchable = searchable
+                        .as_ref()
+                        .map_or(true, |searchable| searchable.contains(&fid));
+                    if let Some(value) = reference.get(name).filter(|_| is_searchable) {
+                        collect_strings(value, &mut text);
+                    }
+                }
+                let ranked = self.term_similarities(&rtxn, &text, &candidates)?;
+                let estimated_total_hits = ranked.len() as u64;
+                (ranked, estimated_total_hits)
+            }
+        };
+
+        let mut displayed_ids: Vec<_> = self
+            .displayed_fields_ids(&rtxn)?
+            .unwrap_or_else(|| all_fields.clone());
+        if let Some(ref restrictions) = query.attribute_restrictions {
+            displayed_ids.retain(|id| {
+                fields_ids_map
+                    .name(*id)
+                    .map_or(false, |name| restrictions.allows(name))
+            });
+        }
+
+        let page: Vec<_> = ranked.into_iter().skip(offset).take(limit).collect();
+        let mut hits = Vec::with_capacity(page.len());
+        for (_, obkv) in self.documents(&rtxn, page)? {
+            let mut document = obkv_to_json(&displayed_ids, &fields_ids_map, obkv)?;
+            if let Some(ref restrictions) = query.attribute_restrictions {
+                remove_forbidden_attributes(&mut document, "", &restrictions.forbidden_attributes);
+            }
+            let document = match query.attributes_to_retrieve {
+                Some(ref attributes) if !attributes.contains("*") => {
+                    permissive_json_pointer::select_values(
+                        &document,
+                        attributes.iter().map(String::as_str),
+                    )
+                }
+                _ => document,
+            };
+            hits.push(document);
+        }
+
+        Ok(SimilarResult {
+            id: doc_id,
+            hits,
+            offset,
+            limit,
+            estimated_total_hits,
+            processing_time_ms: before_similar.elapsed().as_millis(),
+        })
+    }
+
+    /// Returns the `candidates` sharing terms with the `text`, sorted by decreasing similarity.
+    fn term_similarities(
+        &self,
+        rtxn: &milli::heed::RoTxn,
+        text: &[&str],
+        candidates: &RoaringBitmap,
+    ) -> Result<Vec<u32>> {
+        let tokenizer = TokenizerBuilder::default().build();
+        let mut frequencies: HashMap<String, usize> = HashMap::new();
+        for text in text {
+            for token in tokenizer.tokenize(text).filter(|token| token.is_word()) {
+                *frequencies.entry(token.lemma().to_string()).or_default() += 1;
+            }
+        }
+
+        let number_of_documents = self.number_of_documents(rtxn)? as f32;
+        let mut terms = Vec::with_capacity(frequencies.len());
+        for (term, frequency) in frequencies {
+            let docids = match self.word_docids.get(rtxn, &term)? {
+                Some(docids) => docids,
+                None => continue,
+            };
+            let idf = (number_of_documents / docids.len() as f32).ln();
+            terms.push((frequency as f32 * idf, docids));
+        }
+        // only the heaviest terms of the reference document are kept.
+        terms.sort_by(|(left, _), (right, _)| right.partial_cmp(left).unwrap_or(Ordering::Equal));
+        terms.truncate(MAX_SIMILARITY_TERMS);
+
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        for (weight, docids) in terms.into_iter().filter(|(weight, _)| *weight > 0.0) {
+            for id in &docids & candidates {
+                *scores.entry(id).or_default() += weight;
+            }
+        }
+
+        let mut scores: Vec<_> = scores.into_iter().collect();
+        // documents with the same score are ordered by their ids to keep the ranking stable.
+        scores.sort_by(|(left_id, left), (right_id, right)| {
+            right
+                .partial_cmp(left)
+                .unwrap_or(Ordering::Equal)
+                .then(left_id.cmp(right_id))
+        });
+
+        Ok(scores.into_iter().map(|(id, _)| id).collect())
+    }
+}
+
+/// Collects the strings contained in a value, whatever their depth in the objects and arrays.
+fn collect_strings<'a>(value: &'a Value, strings: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => strings.push(s),
+        Value::Array(values) => values.iter().for_each(|v| collect_strings(v, strings)),
+        Value::Object(object) => object.values().for_each(|v| collect_strings(v, strings)),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_collect_strings() {
+        let value = json!({ "title": "Carol", "tags": ["drama", { "lang": "en" }], "year": 2015 });
+        let mut strings = Vec::new();
+        collect_strings(&value, &mut strings);
+        strings.sort_unstable();
+        assert_eq!(strings, vec!["Carol", "drama", "en"]);
+    }
+}
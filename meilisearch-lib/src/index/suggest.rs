@@ -0,0 +1,103 @@
+use std::time::Instant;
+
+use fst::automaton::{Automaton, Str};
+use fst::IntoStreamer;
+use milli::tokenizer::TokenizerBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::error::Result;
+use super::filter::parse_filter;
+use super::index::Index;
+
+pub const DEFAULT_SUGGEST_LIMIT: fn() -> usize = || 5;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SuggestQuery {
+    pub q: String,
+    #[serde(default = "DEFAULT_SUGGEST_LIMIT")]
+    pub limit: usize,
+    pub filter: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub suggestion: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestResult {
+    pub suggestions: Vec<Suggestion>,
+    pub query: String,
+    pub processing_time_ms: u128,
+}
+
+impl Index {
+    /// Completes the last word of the query with the indexed words starting with it.
+    ///
+    /// A completion is weighted by the number of documents matching the filter that contain both
+    /// the completed word and all the previous words of the query, completions that don't appear
+    /// in any of these documents are dropped. The most popular completions are returned first.
+    pub fn perform_suggest(&self, query: SuggestQuery) -> Result<SuggestResult> {
+        let before_suggest = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let tokenizer = TokenizerBuilder::default().build();
+        let mut words: Vec<_> = tokenizer
+            .tokenize(&query.q)
+            .filter(|token| token.is_word())
+            .map(|token| token.lemma().to_string())
+            .collect();
+
+        let mut suggestions = Vec::new();
+
+        if let Some(prefix) = words.pop() {
+            let mut candidates = match query.filter {
+                Some(ref filter) => match parse_filter(filter)? {
+                    Some(filter) => filter.evaluate(&rtxn, self)?,
+                    None => self.documents_ids(&rtxn)?,
+                },
+                None => self.documents_ids(&rtxn)?,
+            };
+            for word in &words {
+                candidates &= self.word_docids.get(&rtxn, word)?.unwrap_or_default();
+            }
+
+            let completions = self
+                .words_fst(&rtxn)?
+                .search(Str::new(&prefix).starts_with())
+                .into_stream()
+                .into_strs()?;
+
+            for completion in completions {
+                let count = match self.word_docids.get(&rtxn, &completion)? {
+                    Some(docids) => (&docids & &candidates).len(),
+                    None => 0,
+                };
+                if count > 0 {
+                    let suggestion = words
+                        .iter()
+                        .map(String::as_str)
+                        .chain(std::iter::once(completion.as_str()))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    suggestions.push(Suggestion { suggestion, count });
+                }
+            }
+
+            // `sort_by` is stable, completions with the same count stay in lexicographic order.
+            suggestions.sort_by(|left, right| right.count.cmp(&left.count));
+            suggestions.truncate(query.limit);
+        }
+
+        Ok(SuggestResult {
+            suggestions,
+            query: query.q,
+            processing_time_ms: before_suggest.elapsed().as_millis(),
+        })
+    }
+}
@@ -0,0 +1,182 @@
+use milli::heed::RoTxn;
+use milli::obkv_to_json;
+use roaring::RoaringBitmap;
+use serde_json::json;
+use time::{Duration, OffsetDateTime};
+
+use super::batch_file::BatchFile;
+use super::changes::document_id;
+use super::error::Result;
+use super::expiration::document_expirations;
+use super::index::Index;
+use super::vector::VECTORS_FIELD_NAME;
+use super::Document;
+
+impl Index {
+    /// Returns how long the deleted documents can be restored, `None` when the deletions are
+    /// definitive.
+    fn soft_deletion_retention(&self) -> Option<Duration> {
+        self.indexer_config
+            .opts()
+            .soft_deletion_retention_sec
+            .map(|secs| Duration::seconds(secs as i64))
+    }
+
+    /// Calls `f` with the deleted documents that can still be restored, only the ones whose ids
+    /// are in `ids` if given.
+    fn for_each_deleted_document(
+        &self,
+        ids: Option<&[String]>,
+        mut f: impl FnMut(Document) -> Result<()>,
+    ) -> Result<()> {
+        let retention = match self.soft_deletion_retention() {
+            Some(retention) => retention,
+            None => return Ok(()),
+        };
+        let deleted_after = (OffsetDateTime::now_utc() - retention).unix_timestamp();
+
+        let txn = self.side.env.read_txn()?;
+        let mut restore = |id: &str, deleted_at: i64| -> Result<()> {
+            if deleted_at > deleted_after {
+                if let Some(document) = self.side.deleted_documents.get(&txn, id)? {
+                    f(document)?;
+                }
+            }
+            Ok(())
+        };
+
+        match ids {
+            Some(ids) => {
+                for id in ids {
+                    if let Some(deleted_at) = self.side.deletion_dates.get(&txn, id)? {
+                        restore(id, deleted_at)?;
+                    }
+                }
+            }
+            None => {
+                for entry in self.side.deletion_dates.iter(&txn)? {
+                    let (id, deleted_at) = entry?;
+                    restore(id, deleted_at)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds back the deleted documents that can still be restored, only the ones whose ids are in
+    /// `ids` if given, and returns how many documents were restored.
+    pub fn restore_documents(&self, ids: Option<&[String]>) -> Result<u64> {
+        let primary_key = self.primary_key(&self.read_txn()?)?.map(String::from);
+        let mut restored = BatchFile::new()?;
+        let mut restored_ids = Vec::new();
+        self.for_each_deleted_document(ids, |document| {
+            let id = primary_key
+                .as_ref()
+                .and_then(|primary_key| document.get(primary_key).cloned());
+            restored_ids.extend(document_id(id));
+            restored.push(&document)
+        })?;
+
+        let (count, reader) = restored.finish()?;
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let mut txn = self.write_txn()?;
+        let (addition, vectors) = self.replace_documents_txn(&mut txn, reader)?;
+        let expirations = document_expirations(self, &txn, &restored_ids)?;
+        self.record_upserted_ids(&mut txn, restored_ids)?;
+        txn.commit()?;
+        vectors.store(self, &self.side)?;
+        expirations.store(&self.side)?;
+        // the restored documents are back in the index, they are forgotten.
+        self.retain_trash()?;
+
+        Ok(addition.indexed_documents)
+    }
+
+    /// Keeps aside the documents `docids` of `txn` that are about to be deleted, so they can be
+    /// restored until the retention elapses. Nothing is kept when the deletions are definitive.
+    ///
+    /// The documents are kept in the side store before the deletion is committed: if it fails,
+    /// they are forgotten by the next call to `retain_trash` since they are still in the index.
+    pub(crate) fn trash_documents(&self, txn: &RoTxn, docids: &RoaringBitmap) -> Result<()> {
+        if self.soft_deletion_retention().is_none() || docids.is_empty() {
+            return Ok(());
+        }
+
+        let primary_key = match self.primary_key(txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return Ok(()),
+        };
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let deleted_at = OffsetDateTime::now_utc().unix_timestamp();
+
+        let mut side_txn = self.side.env.write_txn()?;
+        for docid in docids {
+            let (_, obkv) = match self.documents(txn, std::iter::once(docid))?.pop() {
+                Some(document) => document,
+                None => continue,
+            };
+            let mut document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            // the document is restored with its embeddings.
+            let embeddings = self.document_embeddings(&side_txn, docid)?;
+            if !embeddings.is_empty() {
+                document.insert(VECTORS_FIELD_NAME.to_string(), json!(embeddings));
+            }
+            let id = match document_id(document.get(&primary_key).cloned()) {
+                Some(id) => id,
+                None => continue,
+            };
+            self.side
+                .deleted_documents
+                .put(&mut side_txn, &id, &document)?;
+            self.side
+                .deletion_dates
+                .put(&mut side_txn, &id, &deleted_at)?;
+        }
+        side_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Forgets the deleted documents whose retention elapsed, and the ones that were added back
+    /// to the index, restored or not, so they can't replace a newer version. It must be called
+    /// once the deletion or the addition is committed.
+    pub(crate) fn retain_trash(&self) -> Result<()> {
+        let side_txn = self.side.env.read_txn()?;
+        if self.side.deletion_dates.is_empty(&side_txn)? {
+            return Ok(());
+        }
+
+        let deleted_after = self
+            .soft_deletion_retention()
+            .map(|retention| (OffsetDateTime::now_utc() - retention).unix_timestamp());
+        let txn = self.read_txn()?;
+        let external_ids = self.external_documents_ids(&txn)?;
+
+        let mut forgotten = Vec::new();
+        for entry in self.side.deletion_dates.iter(&side_txn)? {
+            let (id, deleted_at) = entry?;
+            let expired = deleted_after.map_or(true, |after| deleted_at <= after);
+            if expired || external_ids.get(id.as_bytes()).is_some() {
+                forgotten.push(id.to_string());
+            }
+        }
+        drop(side_txn);
+        if forgotten.is_empty() {
+            return Ok(());
+        }
+
+        let mut side_txn = self.side.env.write_txn()?;
+        for id in forgotten {
+            self.side.deleted_documents.delete(&mut side_txn, &id)?;
+            self.side.deletion_dates.delete(&mut side_txn, &id)?;
+        }
+        side_txn.commit()?;
+
+        Ok(())
+    }
+}
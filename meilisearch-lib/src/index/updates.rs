@@ -1,18 +1,33 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 
 use log::{debug, info, trace};
 use milli::documents::DocumentBatchReader;
+use milli::heed::types::{SerdeJson, Str};
+use milli::heed::RwTxn;
 use milli::update::{
     DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
     Setting,
 };
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 use uuid::Uuid;
 
-use super::error::Result;
-use super::index::{Index, IndexMeta};
+use super::error::{IndexError, Result};
+use super::expiration::all_document_expirations;
+use super::facet_trigrams::index_facet_trigrams;
+use super::field_presence::{index_field_presence, retain_field_presence};
+use super::filter::parse_filter;
+use super::index::{
+    Index, IndexMeta, EXPIRY_ATTRIBUTE_KEY, MAX_TOTAL_HITS_KEY, RANKING_PROFILES_KEY,
+    SEARCH_CUTOFF_MS_KEY, SORT_FACET_VALUES_BY_KEY, VERSION_ATTRIBUTE_KEY,
+};
+use super::vector::{infer_primary_key, take_vectors, AddedVectors};
+use super::versioning::VersionedDocuments;
+use super::MatchingStrategy;
 use crate::update_file_store::UpdateFileStore;
 
 fn serialize_with_wildcard<S>(
@@ -77,6 +92,21 @@ pub struct FacetingSettings {
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     pub max_values_per_facet: Setting<usize>,
+    /// The order of the values of each facet, `*` applies to the facets not listed.
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub sort_facet_values_by: Setting<BTreeMap<String, FacetValuesSort>>,
+}
+
+/// The order of the values of a facet in the facet distribution.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FacetValuesSort {
+    /// In lexicographic order.
+    Alpha,
+    /// By decreasing number of documents.
+    Count,
 }
 
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
@@ -87,6 +117,29 @@ pub struct PaginationSettings {
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     pub limited_to: Setting<usize>,
+    /// The largest `totalHits` the `page`/`hitsPerPage` pagination reports, unbounded when reset.
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub max_total_hits: Setting<usize>,
+}
+
+/// A named set of search parameters, selected with the `rankingProfile` search parameter to
+/// compare the relevance of different configurations. The parameters set by the profile replace
+/// the ones of the query.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching_strategy: Option<MatchingStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes_to_search_on: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, proptest(strategy = "test::attribute_weights_strategy()"))]
+    pub attribute_weights: Option<BTreeMap<String, f32>>,
 }
 
 /// Holds all the settings for an index. `T` can either be `Checked` if they represents settings
@@ -141,6 +194,18 @@ pub struct Settings<T> {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub pagination: Setting<PaginationSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub search_cutoff_ms: Setting<u64>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub ranking_profiles: Setting<BTreeMap<String, RankingProfile>>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub expiry_attribute: Setting<String>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub version_attribute: Setting<String>,
 
     #[serde(skip)]
     pub _kind: PhantomData<T>,
@@ -160,6 +225,10 @@ impl Settings<Checked> {
             typo_tolerance: Setting::Reset,
             faceting: Setting::Reset,
             pagination: Setting::Reset,
+            search_cutoff_ms: Setting::Reset,
+            ranking_profiles: Setting::Reset,
+            expiry_attribute: Setting::Reset,
+            version_attribute: Setting::Reset,
             _kind: PhantomData,
         }
     }
@@ -177,6 +246,10 @@ impl Settings<Checked> {
             typo_tolerance,
             faceting,
             pagination,
+            search_cutoff_ms,
+            ranking_profiles,
+            expiry_attribute,
+            version_attribute,
             ..
         } = self;
 
@@ -192,6 +265,10 @@ impl Settings<Checked> {
             typo_tolerance,
             faceting,
             pagination,
+            search_cutoff_ms,
+            ranking_profiles,
+            expiry_attribute,
+            version_attribute,
             _kind: PhantomData,
         }
     }
@@ -233,6 +310,10 @@ impl Settings<Unchecked> {
             typo_tolerance: self.typo_tolerance,
             faceting: self.faceting,
             pagination: self.pagination,
+            search_cutoff_ms: self.search_cutoff_ms,
+            ranking_profiles: self.ranking_profiles,
+            expiry_attribute: self.expiry_attribute,
+            version_attribute: self.version_attribute,
             _kind: PhantomData,
         }
     }
@@ -252,7 +333,8 @@ impl Index {
         txn: &mut milli::heed::RwTxn<'a, 'b>,
         primary_key: String,
     ) -> Result<IndexMeta> {
-        let mut builder = milli::update::Settings::new(txn, self, self.indexer_config.as_ref());
+        let indexer_config = self.indexer_config.config();
+        let mut builder = milli::update::Settings::new(txn, self, &indexer_config);
         builder.set_primary_key(primary_key);
         builder.execute(|_| ())?;
         let meta = IndexMeta::new_txn(self, txn)?;
@@ -271,6 +353,16 @@ impl Index {
     /// Deletes `ids` from the index, and returns how many documents were deleted.
     pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
         let mut txn = self.write_txn()?;
+        let external_ids = self.external_documents_ids(&txn)?;
+        let docids: RoaringBitmap = ids
+            .iter()
+            .filter_map(|id| external_ids.get(id.as_bytes()))
+            .collect();
+        self.trash_documents(&txn, &docids)?;
+        self.forget_vectors(&docids)?;
+        self.forget_expirations(&docids)?;
+        self.record_deletions(&mut txn, &docids)?;
+
         let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
 
         // We ignore unexisting document ids
@@ -279,27 +371,67 @@ impl Index {
         });
 
         let deleted = builder.execute()?;
+        retain_field_presence(self, &mut txn)?;
+
+        txn.commit()?;
+        self.retain_trash()?;
+
+        Ok(deleted)
+    }
+
+    /// Deletes the documents matching `filter` from the index, and returns how many documents
+    /// were deleted.
+    pub fn delete_documents_by_filter(&self, filter: &Value) -> Result<DocumentDeletionResult> {
+        let mut txn = self.write_txn()?;
+        let candidates = match parse_filter(filter)? {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => RoaringBitmap::new(),
+        };
+        self.trash_documents(&txn, &candidates)?;
+        self.forget_vectors(&candidates)?;
+        self.forget_expirations(&candidates)?;
+        self.record_deletions(&mut txn, &candidates)?;
+
+        let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
+        builder.delete_documents(&candidates);
+        let deleted = builder.execute()?;
+        retain_field_presence(self, &mut txn)?;
 
         txn.commit()?;
+        self.retain_trash()?;
 
         Ok(deleted)
     }
 
     pub fn clear_documents(&self) -> Result<()> {
         let mut txn = self.write_txn()?;
+        let docids = self.documents_ids(&txn)?;
+        self.trash_documents(&txn, &docids)?;
+        self.forget_vectors(&docids)?;
+        self.forget_expirations(&docids)?;
+        self.record_deletions(&mut txn, &docids)?;
         milli::update::ClearDocuments::new(&mut txn, self).execute()?;
+        retain_field_presence(self, &mut txn)?;
         txn.commit()?;
+        self.retain_trash()?;
 
         Ok(())
     }
 
+    /// Adds the documents of the update files `contents`, and returns the ids of the documents
+    /// left unchanged because they weren't newer than the stored ones, when the index has a
+    /// version attribute. When `merge_patch` is set, the documents are JSON Merge Patches applied
+    /// to the stored documents. `should_abort` is checked before each update file is read and
+    /// before the documents are committed.
     pub fn update_documents(
         &self,
         method: IndexDocumentsMethod,
+        merge_patch: bool,
         primary_key: Option<String>,
         file_store: UpdateFileStore,
         contents: impl IntoIterator<Item = Uuid>,
-    ) -> Result<DocumentAdditionResult> {
+        should_abort: impl Fn() -> bool,
+    ) -> Result<(DocumentAdditionResult, Vec<String>)> {
         trace!("performing document addition");
         let mut txn = self.write_txn()?;
 
@@ -309,46 +441,179 @@ impl Index {
             }
         }
 
+        // the patched documents are complete, they replace the stored ones.
         let config = IndexDocumentsConfig {
-            update_method: method,
+            update_method: if merge_patch {
+                IndexDocumentsMethod::ReplaceDocuments
+            } else {
+                method
+            },
             ..Default::default()
         };
 
+        let contents: Vec<_> = contents.into_iter().collect();
+        // the patches are merged in the transaction of the addition, with the documents as the
+        // previous tasks left them. The documents that aren't newer than the stored ones are
+        // left out of the addition.
+        let (readers, rejected, vectors) = if merge_patch {
+            let (batch, rejected, vectors) = self.merge_patches(&txn, &file_store, &contents)?;
+            let (count, reader) = batch.finish()?;
+            let readers = if count > 0 { vec![reader] } else { Vec::new() };
+            (readers, rejected, vectors)
+        } else {
+            let partial = matches!(method, IndexDocumentsMethod::UpdateDocuments);
+            let (readers, rejected) =
+                match self.newer_documents(&txn, &file_store, &contents, partial)? {
+                    Some(VersionedDocuments {
+                        count,
+                        reader,
+                        rejected,
+                    }) => {
+                        let readers = if count > 0 { vec![reader] } else { Vec::new() };
+                        (readers, rejected)
+                    }
+                    None => {
+                        let mut readers = Vec::new();
+                        // the parts of a payload are added in order, the last version of a
+                        // document wins.
+                        for content_uuid in contents.iter().copied() {
+                            if should_abort() {
+                                return Err(IndexError::Aborted);
+                            }
+                            for content_file in file_store.get_update(content_uuid)? {
+                                readers.push(DocumentBatchReader::from_reader(content_file)?);
+                            }
+                        }
+                        (readers, Vec::new())
+                    }
+                };
+
+            // the embeddings are taken out of the documents, which are indexed without them.
+            if self.changes_vectors(&readers, partial)? {
+                if self.primary_key(&txn)?.is_none() {
+                    if let Some(primary_key) = infer_primary_key(&readers) {
+                        self.update_primary_key_txn(&mut txn, primary_key)?;
+                    }
+                }
+                let primary_key = self.primary_key(&txn)?.map(String::from);
+                let (readers, vectors) = take_vectors(primary_key.as_deref(), readers, partial)?;
+                (readers, rejected, vectors)
+            } else {
+                (readers, rejected, AddedVectors::default())
+            }
+        };
+
         let indexing_callback = |indexing_step| debug!("update: {:?}", indexing_step);
+        let indexer_config = self.indexer_config.config();
         let mut builder = milli::update::IndexDocuments::new(
             &mut txn,
             self,
-            self.indexer_config.as_ref(),
+            &indexer_config,
             config,
             indexing_callback,
         )?;
 
-        for content_uuid in contents.into_iter() {
-            let content_file = file_store.get_update(content_uuid)?;
-            let reader = DocumentBatchReader::from_reader(content_file)?;
+        for reader in readers {
             builder.add_documents(reader)?;
         }
 
-        let addition = builder.execute()?;
+        let addition =
+            tracing::info_span!("milli::index_documents").in_scope(|| builder.execute())?;
+        index_field_presence(self, &mut txn)?;
+        index_facet_trigrams(self, &mut txn)?;
+        // only the documents of the addition are read, the rejected ones are left unchanged.
+        let expirations = self.content_expirations(&txn, &file_store, &contents)?;
+        self.record_upserts(&mut txn, &file_store, &contents, &rejected)?;
 
+        if should_abort() {
+            return Err(IndexError::Aborted);
+        }
         txn.commit()?;
+        vectors.store(self, &self.side)?;
+        expirations.store(&self.side)?;
+        self.retain_trash()?;
 
         info!("document addition done: {:?}", addition);
 
-        Ok(addition)
+        Ok((addition, rejected))
+    }
+
+    /// Replaces the stored documents with the ones of `reader` in `txn`, along with the data
+    /// meilisearch derives from them. The documents are derived from the stored ones, they are
+    /// never checked against the version attribute of the index, and keep their embeddings
+    /// unless they have a `_vectors` field. The returned embeddings must be stored once `txn` is
+    /// committed, like the expiry dates the caller reads with the ids of the documents.
+    pub(crate) fn replace_documents_txn(
+        &self,
+        txn: &mut RwTxn,
+        reader: DocumentBatchReader<File>,
+    ) -> Result<(DocumentAdditionResult, AddedVectors)> {
+        let readers = vec![reader];
+        let (readers, vectors) = if self.changes_vectors(&readers, true)? {
+            let primary_key = self.primary_key(txn)?.map(String::from);
+            take_vectors(primary_key.as_deref(), readers, true)?
+        } else {
+            (readers, AddedVectors::default())
+        };
+
+        let config = IndexDocumentsConfig {
+            update_method: IndexDocumentsMethod::ReplaceDocuments,
+            ..Default::default()
+        };
+        let indexing_callback = |indexing_step| debug!("update: {:?}", indexing_step);
+        let indexer_config = self.indexer_config.config();
+        let mut builder = milli::update::IndexDocuments::new(
+            txn,
+            self,
+            &indexer_config,
+            config,
+            indexing_callback,
+        )?;
+        for reader in readers {
+            builder.add_documents(reader)?;
+        }
+
+        let addition =
+            tracing::info_span!("milli::index_documents").in_scope(|| builder.execute())?;
+        index_field_presence(self, txn)?;
+        index_facet_trigrams(self, txn)?;
+
+        Ok((addition, vectors))
     }
 
-    pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
+    /// Applies the `settings` to the index, `should_abort` is checked before they are committed.
+    pub fn update_settings(
+        &self,
+        settings: &Settings<Checked>,
+        should_abort: impl Fn() -> bool,
+    ) -> Result<()> {
         // We must use the write transaction of the update here.
         let mut txn = self.write_txn()?;
-        let mut builder =
-            milli::update::Settings::new(&mut txn, self, self.indexer_config.as_ref());
+        let indexer_config = self.indexer_config.config();
+        let mut builder = milli::update::Settings::new(&mut txn, self, &indexer_config);
 
         apply_settings_to_builder(settings, &mut builder);
 
         builder.execute(|indexing_step| debug!("update: {:?}", indexing_step))?;
 
+        let expiry_attribute = self.expiry_attribute(&txn)?;
+        apply_settings_to_index(settings, self, &mut txn)?;
+        index_field_presence(self, &mut txn)?;
+        index_facet_trigrams(self, &mut txn)?;
+        // the expiry dates are only read again when they are read from another attribute.
+        let expirations = if self.expiry_attribute(&txn)? != expiry_attribute {
+            Some(all_document_expirations(self, &txn)?)
+        } else {
+            None
+        };
+
+        if should_abort() {
+            return Err(IndexError::Aborted);
+        }
         txn.commit()?;
+        if let Some(expirations) = expirations {
+            expirations.store(&self.side)?;
+        }
 
         Ok(())
     }
@@ -484,6 +749,98 @@ pub fn apply_settings_to_builder(
     }
 }
 
+/// Applies the settings that are not handled by the engine, and thus stored by meilisearch
+/// directly in the main database of the index.
+pub fn apply_settings_to_index(
+    settings: &Settings<Checked>,
+    index: &milli::Index,
+    txn: &mut RwTxn,
+) -> Result<()> {
+    match settings.search_cutoff_ms {
+        Setting::Set(cutoff) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<u64>>(txn, SEARCH_CUTOFF_MS_KEY, &cutoff)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, SEARCH_CUTOFF_MS_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    let sort_facet_values_by = match settings.faceting {
+        Setting::Set(ref faceting) => faceting.sort_facet_values_by.as_ref(),
+        Setting::Reset => Setting::Reset,
+        Setting::NotSet => Setting::NotSet,
+    };
+    match sort_facet_values_by {
+        Setting::Set(sort_by) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<_>>(txn, SORT_FACET_VALUES_BY_KEY, sort_by)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, SORT_FACET_VALUES_BY_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    let max_total_hits = match settings.pagination {
+        Setting::Set(ref pagination) => pagination.max_total_hits.as_ref(),
+        Setting::Reset => Setting::Reset,
+        Setting::NotSet => Setting::NotSet,
+    };
+    match max_total_hits {
+        Setting::Set(max_total_hits) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<usize>>(txn, MAX_TOTAL_HITS_KEY, max_total_hits)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, MAX_TOTAL_HITS_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.ranking_profiles {
+        Setting::Set(ref profiles) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<_>>(txn, RANKING_PROFILES_KEY, profiles)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, RANKING_PROFILES_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.expiry_attribute {
+        Setting::Set(ref attribute) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<_>>(txn, EXPIRY_ATTRIBUTE_KEY, attribute)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, EXPIRY_ATTRIBUTE_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.version_attribute {
+        Setting::Set(ref attribute) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<_>>(txn, VERSION_ATTRIBUTE_KEY, attribute)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, VERSION_ATTRIBUTE_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 pub(crate) mod test {
     use proptest::prelude::*;
@@ -498,6 +855,16 @@ pub(crate) mod test {
         ]
     }
 
+    /// The weights are kept finite, so that they survive a serialization.
+    pub(super) fn attribute_weights_strategy(
+    ) -> impl Strategy<Value = Option<BTreeMap<String, f32>>> {
+        proptest::option::of(proptest::collection::btree_map(
+            any::<String>(),
+            0.0f32..10.0,
+            0..4,
+        ))
+    }
+
     #[test]
     fn test_setting_check() {
         // test no changes
@@ -513,6 +880,10 @@ pub(crate) mod test {
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
+            ranking_profiles: Setting::NotSet,
+            expiry_attribute: Setting::NotSet,
+            version_attribute: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -537,6 +908,10 @@ pub(crate) mod test {
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
+            ranking_profiles: Setting::NotSet,
+            expiry_attribute: Setting::NotSet,
+            version_attribute: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
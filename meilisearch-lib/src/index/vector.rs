@@ -0,0 +1,457 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek};
+
+use milli::documents::DocumentBatchReader;
+use milli::heed::byteorder::BE;
+use milli::heed::zerocopy::U32;
+use milli::heed::RoTxn;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::batch_file::{for_each_document_of, BatchFile};
+use super::changes::document_id;
+use super::error::Result;
+use super::index::{Document, Index};
+use super::search::Deadline;
+use super::side_store::SideStore;
+
+type BEU32 = U32<BE>;
+
+/// The name of the reserved field in which the documents are sent with their embeddings. The
+/// embeddings are taken out of the documents before they are indexed, and kept in the side store
+/// so they are neither searchable nor returned.
+pub const VECTORS_FIELD_NAME: &str = "_vectors";
+
+/// The embeddings taken out of the `_vectors` field of the documents of an addition, by document
+/// id, `None` when the stored embeddings of the document must be forgotten.
+#[derive(Default)]
+pub(crate) struct AddedVectors {
+    embeddings: HashMap<String, Option<Vec<Vec<f32>>>>,
+}
+
+impl AddedVectors {
+    /// Takes the embeddings out of the `_vectors` field of the `document` whose id is `id`. When
+    /// the document has no `_vectors` field, its stored embeddings are kept if the addition is
+    /// `partial` and forgotten otherwise.
+    pub fn take(&mut self, id: Option<String>, document: &mut Document, partial: bool) {
+        let vectors = document.remove(VECTORS_FIELD_NAME);
+        // the documents without a valid id are rejected by the engine.
+        let id = match id {
+            Some(id) => id,
+            None => return,
+        };
+        match vectors {
+            Some(vectors) => {
+                let embeddings = parse_embeddings(&vectors);
+                self.embeddings
+                    .insert(id, Some(embeddings).filter(|e| !e.is_empty()));
+            }
+            None if !partial => {
+                self.embeddings.insert(id, None);
+            }
+            None => (),
+        }
+    }
+
+    /// Stores the embeddings of the documents of the addition, once it is committed to `index`.
+    pub fn store(self, index: &milli::Index, side: &SideStore) -> Result<()> {
+        if self.embeddings.is_empty() {
+            return Ok(());
+        }
+
+        let rtxn = index.read_txn()?;
+        let external_ids = index.external_documents_ids(&rtxn)?;
+        let mut side_txn = side.env.write_txn()?;
+        for (id, embeddings) in self.embeddings {
+            let docid = match external_ids.get(id.as_bytes()) {
+                Some(docid) => BEU32::new(docid),
+                None => continue,
+            };
+            match embeddings {
+                Some(embeddings) => {
+                    side.vectors
+                        .put(&mut side_txn, &docid, &encode_embeddings(&embeddings))?;
+                }
+                None => {
+                    side.vectors.delete(&mut side_txn, &docid)?;
+                }
+            }
+        }
+        side_txn.commit()?;
+
+        Ok(())
+    }
+}
+
+/// Returns the field the engine infers as the primary key of the documents of `readers`: the
+/// first one whose name contains `id`.
+pub(crate) fn infer_primary_key<R: Read + Seek>(
+    readers: &[DocumentBatchReader<R>],
+) -> Option<String> {
+    let mut fields: Vec<_> = readers.first()?.index().iter().collect();
+    fields.sort_by_key(|(id, _)| **id);
+    fields
+        .into_iter()
+        .map(|(_, name)| name)
+        .find(|name| name.to_lowercase().contains("id"))
+        .cloned()
+}
+
+/// Takes the embeddings out of the documents of `readers`, whose ids are in their `primary_key`
+/// field, and returns the documents left to index along with the embeddings.
+pub(crate) fn take_vectors<R: Read + Seek>(
+    primary_key: Option<&str>,
+    readers: Vec<DocumentBatchReader<R>>,
+    partial: bool,
+) -> Result<(Vec<DocumentBatchReader<File>>, AddedVectors)> {
+    let mut vectors = AddedVectors::default();
+    let mut batch = BatchFile::new()?;
+    for reader in readers {
+        for_each_document_of(reader, |mut document| {
+            let id =
+                primary_key.and_then(|primary_key| document_id(document.get(primary_key).cloned()));
+            vectors.take(id, &mut document, partial);
+            batch.push(&document)
+        })?;
+    }
+
+    let (count, reader) = batch.finish()?;
+    let readers = if count > 0 { vec![reader] } else { Vec::new() };
+    Ok((readers, vectors))
+}
+
+impl Index {
+    /// Returns whether adding the documents of `readers` changes the stored embeddings: when one
+    /// of them has a `_vectors` field, or when they replace documents whose embeddings must be
+    /// forgotten.
+    pub(crate) fn changes_vectors<R: Read + Seek>(
+        &self,
+        readers: &[DocumentBatchReader<R>],
+        partial: bool,
+    ) -> Result<bool> {
+        let has_vectors = readers.iter().any(|reader| {
+            reader
+                .index()
+                .iter()
+                .any(|(_, name)| name == VECTORS_FIELD_NAME)
+        });
+        if has_vectors || partial {
+            return Ok(has_vectors);
+        }
+
+        let side_txn = self.side.env.read_txn()?;
+        Ok(!self.side.vectors.is_empty(&side_txn)?)
+    }
+
+    /// Returns the embeddings of the document `docid` stored in `side_txn`, a transaction of the
+    /// side store.
+    pub(crate) fn document_embeddings(
+        &self,
+        side_txn: &RoTxn,
+        docid: u32,
+    ) -> Result<Vec<Vec<f32>>> {
+        Ok(self
+            .side
+            .vectors
+            .get(side_txn, &BEU32::new(docid))?
+            .map(decode_embeddings)
+            .unwrap_or_default())
+    }
+
+    /// Forgets the embeddings of the documents `docids`, which are about to be deleted. They are
+    /// forgotten before the deletion is committed, so that the ids the engine reuses never get
+    /// the embeddings of a deleted document.
+    pub(crate) fn forget_vectors(&self, docids: &RoaringBitmap) -> Result<()> {
+        let mut side_txn = self.side.env.write_txn()?;
+        if self.side.vectors.is_empty(&side_txn)? {
+            return Ok(());
+        }
+        for docid in docids {
+            self.side
+                .vectors
+                .delete(&mut side_txn, &BEU32::new(docid))?;
+        }
+        side_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Sorts the `candidates` by decreasing cosine similarity between their embeddings and the
+    /// query `vector`, and returns the ids of the documents in the `offset`/`limit` window.
+    ///
+    /// A document sent with several embeddings is ranked by the most similar one. Documents
+    /// without an embedding of the same dimensions as the query vector are ranked after all the
+    /// others, like the ones left uncompared once the `deadline` is reached.
+    pub(crate) fn sort_by_vector_similarity(
+        &self,
+        vector: &[f32],
+        candidates: &RoaringBitmap,
+        offset: usize,
+        limit: usize,
+        deadline: &Deadline,
+    ) -> Result<Vec<u32>> {
+        let window = offset + limit;
+        let (similarities, compared) =
+            self.vector_similarities(vector, candidates, window, deadline)?;
+        // the documents without a comparable embedding follow in the order of their ids.
+        let others = window.saturating_sub(similarities.len());
+        Ok(similarities
+            .into_iter()
+            .map(|(id, _)| id)
+            .chain((candidates - &compared).into_iter().take(others))
+            .skip(offset)
+            .take(limit)
+            .collect())
+    }
+
+    /// Blends the keyword ranking of a search with the vector similarity ranking of the
+    /// `semantic_candidates`, and returns the ids of the documents in the `offset`/`limit` window.
+    ///
+    /// Both rankings are normalized between `0.0` and `1.0`: the keyword score decreases linearly
+    /// with the rank of the document in `keyword_ids`, and the cosine similarity is brought back
+    /// from `[-1.0, 1.0]`. The final score of a document is the `semantic_ratio` weighted average
+    /// of both scores, a document that is missing from one ranking scores `0.0` in it.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn hybrid_ranking(
+        &self,
+        keyword_ids: &[u32],
+        vector: &[f32],
+        semantic_candidates: &RoaringBitmap,
+        semantic_ratio: f32,
+        offset: usize,
+        limit: usize,
+        deadline: &Deadline,
+    ) -> Result<Vec<u32>> {
+        let window = offset + limit;
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+
+        for (rank, id) in keyword_ids.iter().take(window).enumerate() {
+            let keyword_score = 1.0 - rank as f32 / window.max(1) as f32;
+            *scores.entry(*id).or_default() += (1.0 - semantic_ratio) * keyword_score;
+        }
+
+        let (similarities, _) =
+            self.vector_similarities(vector, semantic_candidates, window, deadline)?;
+        for (id, similarity) in similarities {
+            let semantic_score = (similarity + 1.0) / 2.0;
+            *scores.entry(id).or_default() += semantic_ratio * semantic_score;
+        }
+
+        Ok(top_scores(scores, window)
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    /// Returns the `limit` most similar `candidates` to the `vector`, by decreasing similarity,
+    /// along with all the candidates that have an embedding of the same dimensions as it.
+    ///
+    /// Once the `deadline` is reached and `limit` candidates were compared, the most similar
+    /// among the ones compared so far are returned.
+    pub(crate) fn vector_similarities(
+        &self,
+        vector: &[f32],
+        candidates: &RoaringBitmap,
+        limit: usize,
+        deadline: &Deadline,
+    ) -> Result<(Vec<(u32, f32)>, RoaringBitmap)> {
+        let side_txn = self.side.env.read_txn()?;
+        let mut compared = RoaringBitmap::new();
+        let mut similarities = Vec::new();
+        for entry in self.side.vectors.iter(&side_txn)? {
+            if similarities.len() >= limit && deadline.is_reached() {
+                break;
+            }
+
+            let (docid, bytes) = entry?;
+            let docid = docid.get();
+            if !candidates.contains(docid) {
+                continue;
+            }
+            if let Some(similarity) = best_similarity(vector, &decode_embeddings(bytes)) {
+                compared.insert(docid);
+                similarities.push((docid, similarity));
+            }
+        }
+
+        Ok((top_scores(similarities, limit), compared))
+    }
+}
+
+/// A document along with its score, ordered so that the best documents are the greatest: the
+/// documents with the same score are ordered by their ids to keep the ranking stable.
+#[derive(PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .partial_cmp(&other.0)
+            .unwrap_or(Ordering::Equal)
+            .then(other.1.cmp(&self.1))
+    }
+}
+
+/// Returns the `limit` documents with the best `scores`, by decreasing score. Only the best
+/// documents seen so far are kept in a heap, the scores are never sorted as a whole.
+fn top_scores(scores: impl IntoIterator<Item = (u32, f32)>, limit: usize) -> Vec<(u32, f32)> {
+    let mut heap = BinaryHeap::with_capacity(limit + 1);
+    for (id, score) in scores {
+        heap.push(Reverse(Scored(score, id)));
+        if heap.len() > limit {
+            heap.pop();
+        }
+    }
+
+    // the heap is sorted by increasing `Reverse`, the best document first.
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(Scored(score, id))| (id, score))
+        .collect()
+}
+
+/// Returns the embeddings of a `_vectors` field, which contains either one embedding or an array
+/// of embeddings. The values that aren't embeddings are ignored.
+fn parse_embeddings(vectors: &Value) -> Vec<Vec<f32>> {
+    let values = match vectors {
+        Value::Array(values) => values,
+        _ => return Vec::new(),
+    };
+
+    if values.iter().all(Value::is_array) {
+        values.iter().flat_map(parse_embeddings).collect()
+    } else {
+        values
+            .iter()
+            .map(|v| v.as_f64().map(|v| v as f32))
+            .collect::<Option<Vec<_>>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Encodes embeddings as they are stored in the side store: the dimensions of each one followed
+/// by its values, all in little endian.
+fn encode_embeddings(embeddings: &[Vec<f32>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for embedding in embeddings {
+        bytes.extend_from_slice(&(embedding.len() as u32).to_le_bytes());
+        for value in embedding {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn decode_embeddings(mut bytes: &[u8]) -> Vec<Vec<f32>> {
+    let mut embeddings = Vec::new();
+    while bytes.len() >= 4 {
+        let (dimensions, rest) = bytes.split_at(4);
+        let dimensions = u32::from_le_bytes(dimensions.try_into().unwrap()) as usize;
+        let (values, rest) = rest.split_at((dimensions * 4).min(rest.len()));
+        embeddings.push(
+            values
+                .chunks_exact(4)
+                .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+                .collect(),
+        );
+        bytes = rest;
+    }
+    embeddings
+}
+
+/// Returns the best similarity between the `vector` and the `embeddings` of a document.
+fn best_similarity(vector: &[f32], embeddings: &[Vec<f32>]) -> Option<f32> {
+    embeddings
+        .iter()
+        .filter_map(|embedding| cosine_similarity(vector, embedding))
+        .reduce(f32::max)
+}
+
+/// Returns the cosine similarity between two vectors, or `None` if their dimensions differ
+/// or one of them is null.
+fn cosine_similarity(left: &[f32], right: &[f32]) -> Option<f32> {
+    if left.len() != right.len() || left.is_empty() {
+        return None;
+    }
+
+    let dot: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+    let left_norm: f32 = left.iter().map(|l| l * l).sum::<f32>().sqrt();
+    let right_norm: f32 = right.iter().map(|r| r * r).sum::<f32>().sqrt();
+
+    if left_norm == 0.0 || right_norm == 0.0 {
+        None
+    } else {
+        Some(dot / (left_norm * right_norm))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[-1.0, 0.0]), Some(-1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_parse_embeddings() {
+        assert_eq!(parse_embeddings(&json!([1.0, 0.0])), vec![vec![1.0, 0.0]]);
+        assert_eq!(
+            parse_embeddings(&json!([[0.0, 1.0], [1.0, 0.0, 0.0]])),
+            vec![vec![0.0, 1.0], vec![1.0, 0.0, 0.0]]
+        );
+        assert!(parse_embeddings(&json!("hello")).is_empty());
+        assert!(parse_embeddings(&json!([1.0, "a"])).is_empty());
+        assert!(parse_embeddings(&json!(null)).is_empty());
+    }
+
+    #[test]
+    fn test_best_similarity() {
+        let embeddings = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        assert_eq!(best_similarity(&[1.0, 0.0], &embeddings), Some(1.0));
+        let embeddings = vec![vec![0.0, 1.0], vec![1.0, 0.0, 0.0]];
+        assert_eq!(best_similarity(&[1.0, 0.0], &embeddings), Some(0.0));
+        assert_eq!(best_similarity(&[1.0, 0.0], &[]), None);
+    }
+
+    #[test]
+    fn test_top_scores() {
+        let scores = vec![(4, 0.5), (1, 0.9), (3, 0.5), (2, -0.1), (5, 0.7)];
+        assert_eq!(
+            top_scores(scores.clone(), 3),
+            vec![(1, 0.9), (5, 0.7), (3, 0.5)]
+        );
+        assert_eq!(top_scores(scores.clone(), 10).len(), 5);
+        assert!(top_scores(scores, 0).is_empty());
+    }
+
+    #[test]
+    fn test_embeddings_encoding() {
+        let embeddings = vec![vec![0.5, -1.0], vec![], vec![3.0, 2.0, 1.0]];
+        assert_eq!(
+            decode_embeddings(&encode_embeddings(&embeddings)),
+            embeddings
+        );
+        assert!(decode_embeddings(&[]).is_empty());
+    }
+}
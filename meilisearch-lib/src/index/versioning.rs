@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+
+use milli::documents::DocumentBatchReader;
+use milli::heed::RoTxn;
+use milli::{ExternalDocumentsIds, FieldId};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::batch_file::{for_each_document, BatchFile};
+use super::changes::document_id;
+use super::error::Result;
+use super::index::{Document, Index};
+use crate::update_file_store::UpdateFileStore;
+
+/// The documents of an addition newer than the stored ones, and the ids of the documents left
+/// unchanged because none of their versions in the addition was newer.
+pub(crate) struct VersionedDocuments {
+    pub count: usize,
+    pub reader: DocumentBatchReader<File>,
+    pub rejected: Vec<String>,
+}
+
+/// Returns the version held by the version attribute of a document, only numbers are versions.
+fn document_version(value: Option<Value>) -> Option<f64> {
+    value?.as_f64()
+}
+
+/// Returns whether a document of version `new` may replace the document of version `current`.
+/// A document without a version replaces the ones without a version only.
+fn is_newer(new: Option<f64>, current: Option<f64>) -> bool {
+    match (new, current) {
+        (_, None) => true,
+        (Some(new), Some(current)) => new > current,
+        (None, Some(_)) => false,
+    }
+}
+
+/// Decides, in the order they were sent, which documents of an addition are newer than the
+/// stored ones according to the version attribute of the index.
+///
+/// The partial updates, the `UpdateDocuments` additions and the JSON Merge Patches, that don't
+/// set the version attribute keep the stored version and are always accepted. The ones that set
+/// it, and the documents replacing the stored ones, must bump it.
+pub(crate) struct VersionCheck<'t> {
+    index: &'t Index,
+    txn: &'t RoTxn<'t>,
+    external_ids: ExternalDocumentsIds<'t>,
+    primary_key: String,
+    version_attribute: String,
+    field_id: Option<FieldId>,
+    partial: bool,
+    // the version of each document once the previous documents of the addition are applied.
+    versions: HashMap<String, Option<f64>>,
+    accepted: HashSet<String>,
+    rejected: Vec<String>,
+}
+
+impl<'t> VersionCheck<'t> {
+    /// Returns whether `document` may be added. The documents without a valid id are accepted,
+    /// they are left to the engine which rejects them.
+    pub fn accept(&mut self, document: &Document) -> Result<bool> {
+        let id = match document_id(document.get(&self.primary_key).cloned()) {
+            Some(id) => id,
+            None => return Ok(true),
+        };
+        let current = match self.versions.get(&id) {
+            Some(version) => *version,
+            None => self
+                .index
+                .stored_version(self.txn, &self.external_ids, self.field_id, &id)?,
+        };
+
+        let version = match document.get(&self.version_attribute) {
+            None if self.partial => current,
+            version => {
+                let version = document_version(version.cloned());
+                if !is_newer(version, current) {
+                    self.rejected.push(id);
+                    return Ok(false);
+                }
+                version.or(current)
+            }
+        };
+        self.versions.insert(id.clone(), version);
+        self.accepted.insert(id);
+        Ok(true)
+    }
+
+    /// Returns the ids of the documents left unchanged because none of their versions was
+    /// accepted.
+    pub fn into_rejected(self) -> Vec<String> {
+        let Self {
+            accepted,
+            mut rejected,
+            ..
+        } = self;
+        let mut seen = HashSet::new();
+        rejected.retain(|id| !accepted.contains(id) && seen.insert(id.clone()));
+        rejected
+    }
+}
+
+impl Index {
+    /// Returns the version of the stored document `id`, held by the field `field_id`, if it
+    /// exists and has one.
+    fn stored_version(
+        &self,
+        txn: &RoTxn,
+        external_ids: &ExternalDocumentsIds,
+        field_id: Option<FieldId>,
+        id: &str,
+    ) -> Result<Option<f64>> {
+        let (field_id, docid) = match (field_id, external_ids.get(id.as_bytes())) {
+            (Some(field_id), Some(docid)) => (field_id, docid),
+            _ => return Ok(None),
+        };
+
+        let version = self
+            .documents(txn, std::iter::once(docid))?
+            .into_iter()
+            .next()
+            .and_then(|(_, obkv)| obkv.get(field_id))
+            .and_then(|bytes| serde_json::from_slice(bytes).ok());
+        Ok(document_version(version))
+    }
+
+    /// Returns the check of the versions of the documents added in `txn`, whether they are
+    /// `partial` updates or not. Returns `None` when the index has no version attribute, or no
+    /// primary key yet.
+    pub(crate) fn version_check<'t>(
+        &'t self,
+        txn: &'t RoTxn<'t>,
+        partial: bool,
+    ) -> Result<Option<VersionCheck<'t>>> {
+        let version_attribute = match self.version_attribute(txn)? {
+            Some(attribute) => attribute,
+            None => return Ok(None),
+        };
+        let primary_key = match self.primary_key(txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return Ok(None),
+        };
+
+        Ok(Some(VersionCheck {
+            index: self,
+            txn,
+            external_ids: self.external_documents_ids(txn)?,
+            primary_key,
+            field_id: self.fields_ids_map(txn)?.id(&version_attribute),
+            version_attribute,
+            partial,
+            versions: HashMap::new(),
+            accepted: HashSet::new(),
+            rejected: Vec::new(),
+        }))
+    }
+
+    /// Keeps the documents of the update files `contents` that are newer than the stored ones,
+    /// according to the version attribute of the index, in the order they were sent. Returns
+    /// `None` when the index has no version attribute, or no primary key yet.
+    pub(crate) fn newer_documents(
+        &self,
+        txn: &RoTxn,
+        file_store: &UpdateFileStore,
+        contents: &[Uuid],
+        partial: bool,
+    ) -> Result<Option<VersionedDocuments>> {
+        let mut check = match self.version_check(txn, partial)? {
+            Some(check) => check,
+            None => return Ok(None),
+        };
+
+        let mut batch = BatchFile::new()?;
+        for_each_document(file_store, contents, |document| {
+            if check.accept(&document)? {
+                batch.push(&document)?;
+            }
+            Ok(())
+        })?;
+        let (count, reader) = batch.finish()?;
+
+        Ok(Some(VersionedDocuments {
+            count,
+            reader,
+            rejected: check.into_rejected(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer(Some(2.0), Some(1.0)));
+        assert!(!is_newer(Some(1.0), Some(1.0)));
+        assert!(!is_newer(Some(1.0), Some(2.0)));
+        assert!(is_newer(Some(1.0), None));
+        assert!(is_newer(None, None));
+        assert!(!is_newer(None, Some(1.0)));
+    }
+}
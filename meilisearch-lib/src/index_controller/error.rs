@@ -4,12 +4,14 @@ use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::index_uid::IndexUidFormatError;
 use meilisearch_types::internal_error;
 use tokio::task::JoinError;
+use uuid::Uuid;
 
 use super::DocumentAdditionFormat;
 use crate::document_formats::DocumentFormatError;
 use crate::dump::error::DumpError;
 use crate::index::error::IndexError;
 use crate::tasks::error::TaskError;
+use crate::tasks::task::BatchSeq;
 use crate::update_file_store::UpdateFileStoreError;
 
 use crate::index_resolver::error::IndexResolverError;
@@ -36,9 +38,25 @@ pub enum IndexControllerError {
     MissingPayload(DocumentAdditionFormat),
     #[error("The provided payload reached the size limit.")]
     PayloadTooLarge,
+    #[error("Snapshots are disabled, a snapshot directory must be provided.")]
+    SnapshotsDisabled,
+    #[error("{0}")]
+    InvalidSwapIndexes(String),
+    #[error("The replication is disabled, a replication log retention must be provided.")]
+    ReplicationDisabled,
+    #[error("The update file `{0}` doesn't exist, it was processed and its retention elapsed.")]
+    UpdateFileNotFound(Uuid),
+    #[error("A task of the batch `{0}` of the replication log was deleted, the follower must be restored from a new snapshot of the primary instance.")]
+    ReplicationPositionUnavailable(BatchSeq),
 }
 
-internal_error!(IndexControllerError: JoinError, UpdateFileStoreError);
+internal_error!(
+    IndexControllerError: JoinError,
+    UpdateFileStoreError,
+    serde_json::Error,
+    std::io::Error,
+    reqwest::Error
+);
 
 impl From<actix_web::error::PayloadError> for IndexControllerError {
     fn from(other: actix_web::error::PayloadError) -> Self {
@@ -60,7 +78,14 @@ impl ErrorCode for IndexControllerError {
             IndexControllerError::DocumentFormatError(e) => e.error_code(),
             IndexControllerError::MissingPayload(_) => Code::MissingPayload,
             IndexControllerError::PayloadTooLarge => Code::PayloadTooLarge,
+            IndexControllerError::SnapshotsDisabled => Code::BadRequest,
             IndexControllerError::DumpError(e) => e.error_code(),
+            IndexControllerError::InvalidSwapIndexes(_) => Code::InvalidSwapIndexes,
+            IndexControllerError::ReplicationDisabled => Code::BadRequest,
+            IndexControllerError::UpdateFileNotFound(_) => Code::UpdateFileNotFound,
+            IndexControllerError::ReplicationPositionUnavailable(_) => {
+                Code::ReplicationPositionUnavailable
+            }
         }
     }
 }
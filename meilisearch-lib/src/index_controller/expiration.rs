@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::{error, info};
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
+
+use super::error::Result;
+use super::{MeiliSearch, Update};
+use crate::tasks::task::TaskId;
+
+/// How often the indexes are checked for expired documents. The expired documents are ignored by
+/// the searches in the meantime.
+const EXPIRATION_CHECK_PERIOD: Duration = Duration::from_secs(60);
+
+/// Periodically deletes the documents whose expiry date is reached, in the indexes having an
+/// expiry attribute.
+pub struct DocumentExpirationService {
+    pub(crate) meilisearch: MeiliSearch,
+    /// The last deletion registered for each index, no other is registered until it finishes.
+    pub(crate) pending: HashMap<String, TaskId>,
+}
+
+impl DocumentExpirationService {
+    pub async fn run(mut self) {
+        loop {
+            sleep(EXPIRATION_CHECK_PERIOD).await;
+            if let Err(e) = self.delete_expired_documents().await {
+                error!(
+                    "an error occured while deleting the expired documents: {}",
+                    e
+                );
+            }
+        }
+    }
+
+    async fn delete_expired_documents(&mut self) -> Result<()> {
+        // the followers delete the expired documents along with their primary.
+        if self.meilisearch.is_read_only() || self.meilisearch.is_replica() {
+            return Ok(());
+        }
+
+        for (uid, index) in self.meilisearch.index_resolver.list().await? {
+            if let Some(id) = self.pending.get(&uid) {
+                match self.meilisearch.get_task(*id, None).await {
+                    Ok(task) if !task.is_finished() => continue,
+                    _ => {
+                        self.pending.remove(&uid);
+                    }
+                }
+            }
+
+            let ids = spawn_blocking(move || index.expired_documents_ids()).await??;
+            // Don't register a deletion task every period when there is nothing to delete.
+            if !ids.is_empty() {
+                let count = ids.len();
+                let task = self
+                    .meilisearch
+                    .register_update(uid.clone(), Update::DeleteDocuments(ids))
+                    .await?;
+                info!(
+                    "Deleting {} expired documents from `{}` with the task {}.",
+                    count, uid, task.id
+                );
+                self.pending.insert(uid, task.id);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use meilisearch_auth::SearchRules;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
-use std::io::Cursor;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -15,35 +15,63 @@ use meilisearch_types::index_uid::IndexUid;
 use milli::update::IndexDocumentsMethod;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tokio::sync::RwLock;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use uuid::Uuid;
 
-use crate::document_formats::{read_csv, read_json, read_ndjson};
+use crate::document_formats::{
+    read_cbor, read_csv, read_json, read_msgpack, read_ndjson_parallel, ChunksReader, CsvOptions,
+    DocumentFormatError, NdjsonPipelineOptions,
+};
 use crate::dump::{self, load_dump, DumpHandler};
+pub use crate::dump::{DumpImportSource, DumpSource, ImportConflictPolicy, ImportedIndex};
+use crate::experimental_features::{FeatureStore, RuntimeFeatures, RuntimeFeaturesUpdate};
 use crate::index::{
-    Checked, Document, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
+    check_edition_function, Checked, Document, DocumentChanges, FacetSearchQuery,
+    FacetSearchResult, IndexMeta, IndexStats, SearchQuery, SearchResult, SearchTimings, Settings,
+    SimilarQuery, SimilarResult, SuggestQuery, SuggestResult, Unchecked,
 };
 use crate::index_resolver::error::IndexResolverError;
-use crate::options::{IndexerOpts, SchedulerConfig};
-use crate::snapshot::{load_snapshot, SnapshotService};
+pub use crate::index_resolver::template::IndexTemplate;
+use crate::options::{IndexerConfigHandle, IndexerOpts, SchedulerConfig};
+use crate::snapshot::{
+    load_snapshot, CronSchedule, SnapshotConfig, SnapshotRetention, SnapshotSchedule,
+    SnapshotService,
+};
+use crate::storage::{ArchiveKind, ObjectStorage};
 use crate::tasks::error::TaskError;
-use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId};
+use crate::tasks::task::{DocumentDeletion, GroupUid, Task, TaskContent, TaskId, TaskPriority};
 use crate::tasks::{
-    BatchHandler, EmptyBatchHandler, Scheduler, SnapshotHandler, TaskFilter, TaskStore,
+    BatchHandler, EmptyBatchHandler, Scheduler, SnapshotHandler, TaskFilter, TaskRetentionService,
+    TaskStore, TaskWebhook,
 };
 use error::Result;
 
 use self::error::IndexControllerError;
+use self::expiration::DocumentExpirationService;
+use self::replication::{
+    ReplicationLogService, ReplicationService, REPLICATION_POSITION_FILE_NAME,
+};
+use self::search_analytics::SearchAnalytics;
+use self::search_cache::SearchCache;
 use crate::index_resolver::index_store::{IndexStore, MapIndexStore};
 use crate::index_resolver::meta_store::{HeedMetaStore, IndexMetaStore};
 use crate::index_resolver::{create_index_resolver, IndexResolver};
 use crate::update_file_store::UpdateFileStore;
 
 pub mod error;
+mod expiration;
+mod replication;
+mod search_analytics;
+mod search_cache;
 pub mod versioning;
 
+pub(crate) use replication::copy_replication_position;
+pub use replication::{ReplicaConfig, ReplicatedTask, ReplicationLog};
+pub use search_analytics::{ProfileStat, QueryStat, SearchAnalyticsReport};
+
 /// Concrete implementation of the IndexController, exposed by meilisearch-lib
 pub type MeiliSearch = IndexController<HeedMetaStore, MapIndexStore>;
 
@@ -68,6 +96,14 @@ pub struct IndexMetadata {
     pub meta: IndexMeta,
 }
 
+/// A name resolving to the index `index_uid` in the search, document and settings operations.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Alias {
+    pub alias: String,
+    pub index_uid: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct IndexSettings {
     pub uid: Option<String>,
@@ -79,6 +115,36 @@ pub struct IndexController<U, I> {
     scheduler: Arc<RwLock<Scheduler>>,
     task_store: TaskStore,
     pub update_file_store: UpdateFileStore,
+    /// The search cutoff applied to the indexes that don't define their own, it can be changed
+    /// at runtime.
+    search_cutoff: Arc<RwLock<Option<Duration>>>,
+    /// The searches taking longer are logged with their timings.
+    slow_query_threshold: Option<Duration>,
+    /// The results of the latest searches, `None` if the cache is disabled.
+    search_cache: Option<Arc<SearchCache>>,
+    /// The endpoint notified when tasks are finished, it can be changed at runtime.
+    webhook: Arc<RwLock<Option<TaskWebhook>>>,
+    /// How to perform a snapshot on demand, `None` if no snapshot directory was provided.
+    snapshot: Option<SnapshotConfig>,
+    /// Whether the instance is in read-only mode, see [`IndexController::set_read_only`].
+    read_only: Arc<AtomicBool>,
+    /// The configuration of the indexer, it can be changed at runtime.
+    indexer_config: IndexerConfigHandle,
+    /// The experimental features enabled at runtime.
+    features: Arc<FeatureStore>,
+    /// The searches counted by index, when enabled at startup or at runtime.
+    search_analytics: Arc<SearchAnalytics>,
+    /// Whether the search analytics were enabled at startup.
+    search_analytics_enabled: bool,
+    /// How long the update files are kept for the followers, `None` if the replication log
+    /// isn't served.
+    replication_log_retention: Option<Duration>,
+    /// The URL of the primary instance, if this one is a follower.
+    replica_of: Option<String>,
+    /// The directory of the dumps, the only one the dumps can be imported from.
+    dump_path: PathBuf,
+    /// The hosts the imported dumps can be downloaded from.
+    dump_import_allowed_hosts: Vec<String>,
 }
 
 /// Need a custom implementation for clone because deriving require that U and I are clone.
@@ -89,6 +155,20 @@ impl<U, I> Clone for IndexController<U, I> {
             scheduler: self.scheduler.clone(),
             update_file_store: self.update_file_store.clone(),
             task_store: self.task_store.clone(),
+            search_cutoff: self.search_cutoff.clone(),
+            slow_query_threshold: self.slow_query_threshold,
+            search_cache: self.search_cache.clone(),
+            webhook: self.webhook.clone(),
+            snapshot: self.snapshot.clone(),
+            read_only: self.read_only.clone(),
+            indexer_config: self.indexer_config.clone(),
+            features: self.features.clone(),
+            search_analytics: self.search_analytics.clone(),
+            search_analytics_enabled: self.search_analytics_enabled,
+            replication_log_retention: self.replication_log_retention,
+            replica_of: self.replica_of.clone(),
+            dump_path: self.dump_path.clone(),
+            dump_import_allowed_hosts: self.dump_import_allowed_hosts.clone(),
         }
     }
 }
@@ -96,8 +176,10 @@ impl<U, I> Clone for IndexController<U, I> {
 #[derive(Debug)]
 pub enum DocumentAdditionFormat {
     Json,
-    Csv,
+    Csv(CsvOptions),
     Ndjson,
+    MessagePack,
+    Cbor,
 }
 
 impl fmt::Display for DocumentAdditionFormat {
@@ -105,7 +187,9 @@ impl fmt::Display for DocumentAdditionFormat {
         match self {
             DocumentAdditionFormat::Json => write!(f, "json"),
             DocumentAdditionFormat::Ndjson => write!(f, "ndjson"),
-            DocumentAdditionFormat::Csv => write!(f, "csv"),
+            DocumentAdditionFormat::Csv(_) => write!(f, "csv"),
+            DocumentAdditionFormat::MessagePack => write!(f, "msgpack"),
+            DocumentAdditionFormat::Cbor => write!(f, "cbor"),
         }
     }
 }
@@ -124,6 +208,17 @@ pub struct Stats {
 #[derivative(Debug)]
 pub enum Update {
     DeleteDocuments(Vec<String>),
+    DeleteDocumentsByFilter(serde_json::Value),
+    /// Applies the rhai `function` to the documents matching `filter`.
+    EditDocuments {
+        filter: Option<serde_json::Value>,
+        function: String,
+    },
+    /// Adds back the deleted documents that can still be restored, only the ones whose ids are
+    /// in `ids` if given.
+    RestoreDocuments {
+        ids: Option<Vec<String>>,
+    },
     ClearDocuments,
     Settings {
         settings: Settings<Unchecked>,
@@ -136,6 +231,8 @@ pub enum Update {
         payload: Payload,
         primary_key: Option<String>,
         method: IndexDocumentsMethod,
+        /// Whether the documents are JSON Merge Patches applied to the stored documents.
+        merge_patch: bool,
         format: DocumentAdditionFormat,
         allow_index_creation: bool,
     },
@@ -146,6 +243,10 @@ pub enum Update {
     UpdateIndex {
         primary_key: Option<String>,
     },
+    CloneIndex {
+        destination: String,
+        settings_only: bool,
+    },
 }
 
 #[derive(Default, Debug)]
@@ -158,10 +259,22 @@ pub struct IndexControllerBuilder {
     ignore_snapshot_if_db_exists: bool,
     ignore_missing_snapshot: bool,
     schedule_snapshot: bool,
+    snapshot_schedule: Option<CronSchedule>,
+    snapshot_retention: SnapshotRetention,
     dump_src: Option<PathBuf>,
+    incremental_dumps: Vec<PathBuf>,
     dump_dst: Option<PathBuf>,
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
+    search_cutoff: Option<Duration>,
+    slow_query_threshold: Option<Duration>,
+    search_cache_size: Option<usize>,
+    search_analytics: bool,
+    webhook: Option<TaskWebhook>,
+    object_storage: Option<ObjectStorage>,
+    replication_log_retention: Option<Duration>,
+    replica: Option<ReplicaConfig>,
+    dump_import_allowed_hosts: Vec<String>,
 }
 
 impl IndexControllerBuilder {
@@ -178,18 +291,42 @@ impl IndexControllerBuilder {
             .max_task_store_size
             .ok_or_else(|| anyhow::anyhow!("Missing update database size"))?;
 
+        // The archives to import missing from the local filesystem are fetched from the object
+        // storage, they are kept in temporary files until they are loaded.
+        let mut fetched = Vec::new();
+        let mut fetch = |path: &Path, kind| -> anyhow::Result<PathBuf> {
+            match self.object_storage {
+                Some(ref storage) if !path.exists() => match storage.fetch(kind, path)? {
+                    Some(file) => {
+                        let fetched_path = file.path().to_path_buf();
+                        fetched.push(file);
+                        Ok(fetched_path)
+                    }
+                    None => Ok(path.to_path_buf()),
+                },
+                _ => Ok(path.to_path_buf()),
+            }
+        };
+
         if let Some(ref path) = self.import_snapshot {
             log::info!("Loading from snapshot {:?}", path);
             load_snapshot(
                 db_path.as_ref(),
-                path,
+                fetch(path, ArchiveKind::Snapshot)?,
                 self.ignore_snapshot_if_db_exists,
                 self.ignore_missing_snapshot,
             )?;
         } else if let Some(ref src_path) = self.dump_src {
+            let src_path = fetch(src_path, ArchiveKind::Dump)?;
+            let incremental_dumps = self
+                .incremental_dumps
+                .iter()
+                .map(|path| fetch(path, ArchiveKind::Dump))
+                .collect::<anyhow::Result<Vec<_>>>()?;
             load_dump(
                 db_path.as_ref(),
                 src_path,
+                &incremental_dumps,
                 self.ignore_dump_if_db_exists,
                 self.ignore_missing_dump,
                 index_size,
@@ -211,27 +348,33 @@ impl IndexControllerBuilder {
         let update_file_store = UpdateFileStore::new(&db_path)?;
         // Create or overwrite the version file for this DB
         versioning::create_version_file(db_path.as_ref())?;
+        let features = Arc::new(FeatureStore::new(db_path.as_ref())?);
 
-        let index_resolver = Arc::new(create_index_resolver(
+        let indexer_config = IndexerConfigHandle::new(indexer_options.clone())?;
+        let mut index_resolver = create_index_resolver(
             &db_path,
             index_size,
-            &indexer_options,
+            indexer_config.clone(),
             meta_env.clone(),
             update_file_store.clone(),
-        )?);
+        )?;
+        index_resolver.set_keep_update_files(self.replication_log_retention.is_some());
+        let index_resolver = Arc::new(index_resolver);
 
         let dump_path = self
             .dump_dst
             .ok_or_else(|| anyhow::anyhow!("Missing dump directory path"))?;
 
         let dump_handler = Arc::new(DumpHandler::new(
-            dump_path,
+            dump_path.clone(),
             db_path.as_ref().into(),
             update_file_store.clone(),
             task_store_size,
             index_size,
             meta_env.clone(),
             index_resolver.clone(),
+            self.object_storage.clone(),
+            self.dump_import_allowed_hosts.clone(),
         ));
         let task_store = TaskStore::new(meta_env)?;
 
@@ -243,34 +386,113 @@ impl IndexControllerBuilder {
             // dummy handler to catch all empty batches
             Arc::new(EmptyBatchHandler),
         ];
+        let task_retention_days = scheduler_config.task_retention_days;
         let scheduler = Scheduler::new(task_store.clone(), handlers, scheduler_config)?;
 
-        if self.schedule_snapshot {
-            let snapshot_period = self
-                .snapshot_interval
-                .ok_or_else(|| anyhow::anyhow!("Snapshot interval not provided."))?;
-            let snapshot_path = self
-                .snapshot_dir
+        if let Some(days) = task_retention_days {
+            let retention_service = TaskRetentionService {
+                scheduler: scheduler.clone(),
+                retention: Duration::from_secs(days.saturating_mul(24 * 60 * 60)),
+            };
+
+            tokio::task::spawn_local(retention_service.run());
+        }
+
+        let snapshot = self.snapshot_dir.map(|snapshot_path| SnapshotConfig {
+            db_path: db_path.as_ref().to_path_buf(),
+            snapshot_path,
+            index_size,
+            meta_env_size: task_store_size,
+            retention: self.snapshot_retention,
+            object_storage: self.object_storage.clone(),
+        });
+
+        if self.schedule_snapshot || self.snapshot_schedule.is_some() {
+            let schedule = match self.snapshot_schedule {
+                Some(schedule) => SnapshotSchedule::Cron(schedule),
+                None => SnapshotSchedule::Interval(
+                    self.snapshot_interval
+                        .ok_or_else(|| anyhow::anyhow!("Snapshot interval not provided."))?,
+                ),
+            };
+            let config = snapshot
+                .clone()
                 .ok_or_else(|| anyhow::anyhow!("Snapshot path not provided."))?;
 
             let snapshot_service = SnapshotService {
-                db_path: db_path.as_ref().to_path_buf(),
-                snapshot_period,
-                snapshot_path,
-                index_size,
-                meta_env_size: task_store_size,
+                config,
+                schedule,
                 scheduler: scheduler.clone(),
             };
 
             tokio::task::spawn_local(snapshot_service.run());
         }
 
-        Ok(IndexController {
+        let search_cache = match self.search_cache_size {
+            Some(size) if size > 0 => {
+                let cache = Arc::new(SearchCache::new(size));
+                let invalidated = cache.clone();
+                // the update loop hasn't started yet, no task can finish before the hook is set.
+                scheduler
+                    .try_write()?
+                    .on_finished(move |tasks| invalidated.invalidate_tasks(tasks));
+                Some(cache)
+            }
+            _ => None,
+        };
+
+        let search_analytics = Arc::new(SearchAnalytics::default());
+        let deleted = search_analytics.clone();
+        scheduler
+            .try_write()?
+            .on_finished(move |tasks| deleted.remove_deleted_indexes(tasks));
+
+        let meilisearch = IndexController {
             index_resolver,
             scheduler,
             update_file_store,
             task_store,
-        })
+            search_cutoff: Arc::new(RwLock::new(self.search_cutoff)),
+            slow_query_threshold: self.slow_query_threshold,
+            search_cache,
+            webhook: Arc::new(RwLock::new(self.webhook)),
+            snapshot,
+            read_only: Arc::default(),
+            indexer_config,
+            features,
+            search_analytics,
+            search_analytics_enabled: self.search_analytics,
+            replication_log_retention: self.replication_log_retention,
+            replica_of: self.replica.as_ref().map(|r| r.primary_url.clone()),
+            dump_path,
+            dump_import_allowed_hosts: self.dump_import_allowed_hosts,
+        };
+
+        let expiration_service = DocumentExpirationService {
+            meilisearch: meilisearch.clone(),
+            pending: HashMap::new(),
+        };
+        tokio::task::spawn_local(expiration_service.run());
+
+        if let Some(retention) = self.replication_log_retention {
+            let log_service = ReplicationLogService {
+                meilisearch: meilisearch.clone(),
+                retention,
+            };
+            tokio::task::spawn_local(log_service.run());
+        }
+
+        if let Some(config) = self.replica {
+            let replication_service = ReplicationService {
+                meilisearch: meilisearch.clone(),
+                config,
+                position_path: db_path.as_ref().join(REPLICATION_POSITION_FILE_NAME),
+                client: reqwest::Client::new(),
+            };
+            tokio::task::spawn_local(replication_service.run());
+        }
+
+        Ok(meilisearch)
     }
 
     /// Set the index controller builder's max update store size.
@@ -323,12 +545,31 @@ impl IndexControllerBuilder {
         self
     }
 
+    /// Set the cron-like schedule of the snapshots, it replaces the snapshot interval and
+    /// enables the snapshot scheduling.
+    pub fn set_snapshot_schedule(&mut self, schedule: CronSchedule) -> &mut Self {
+        self.snapshot_schedule = Some(schedule);
+        self
+    }
+
+    /// Set the index controller builder's snapshot retention.
+    pub fn set_snapshot_retention(&mut self, retention: SnapshotRetention) -> &mut Self {
+        self.snapshot_retention = retention;
+        self
+    }
+
     /// Set the index controller builder's dump src.
     pub fn set_dump_src(&mut self, dump_src: PathBuf) -> &mut Self {
         self.dump_src.replace(dump_src);
         self
     }
 
+    /// Set the incremental dumps replayed, in order, on top of the imported dump.
+    pub fn set_incremental_dumps(&mut self, incremental_dumps: Vec<PathBuf>) -> &mut Self {
+        self.incremental_dumps = incremental_dumps;
+        self
+    }
+
     /// Set the index controller builder's dump dst.
     pub fn set_dump_dst(&mut self, dump_dst: PathBuf) -> &mut Self {
         self.dump_dst.replace(dump_dst);
@@ -346,6 +587,61 @@ impl IndexControllerBuilder {
         self.ignore_missing_dump = ignore_missing_dump;
         self
     }
+
+    /// Set the search cutoff applied to the indexes that don't define their own.
+    pub fn set_search_cutoff(&mut self, search_cutoff: Duration) -> &mut Self {
+        self.search_cutoff.replace(search_cutoff);
+        self
+    }
+
+    /// Set the duration above which the searches are logged as slow queries.
+    pub fn set_slow_query_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.slow_query_threshold.replace(threshold);
+        self
+    }
+
+    /// Set the number of search results kept in the search cache, `0` disables it.
+    pub fn set_search_cache_size(&mut self, size: usize) -> &mut Self {
+        self.search_cache_size.replace(size);
+        self
+    }
+
+    /// Set whether the searches are counted by index, they can also be enabled at runtime.
+    pub fn set_search_analytics(&mut self, enabled: bool) -> &mut Self {
+        self.search_analytics = enabled;
+        self
+    }
+
+    /// Set the endpoint notified when tasks are finished.
+    pub fn set_webhook(&mut self, webhook: TaskWebhook) -> &mut Self {
+        self.webhook.replace(webhook);
+        self
+    }
+
+    /// Set the object storage the dumps and snapshots are uploaded to.
+    pub fn set_object_storage(&mut self, object_storage: ObjectStorage) -> &mut Self {
+        self.object_storage.replace(object_storage);
+        self
+    }
+
+    /// Set how long the update files are kept once processed, for the followers replaying the
+    /// tasks. The replication log is only served when it is set.
+    pub fn set_replication_log_retention(&mut self, retention: Duration) -> &mut Self {
+        self.replication_log_retention.replace(retention);
+        self
+    }
+
+    /// Set the primary instance replicated by this one, which then rejects the write requests.
+    pub fn set_replica(&mut self, replica: ReplicaConfig) -> &mut Self {
+        self.replica.replace(replica);
+        self
+    }
+
+    /// Set the hosts the dumps imported at runtime can be downloaded from, none by default.
+    pub fn set_dump_import_allowed_hosts(&mut self, hosts: Vec<String>) -> &mut Self {
+        self.dump_import_allowed_hosts = hosts;
+        self
+    }
 }
 
 impl<U, I> IndexController<U, I>
@@ -358,12 +654,103 @@ where
     }
 
     pub async fn register_update(&self, uid: String, update: Update) -> Result<Task> {
+        let content = self.task_content(uid, update).await?;
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
+
+        Ok(task)
+    }
+
+    /// Registers the `updates` as consecutive tasks of a group, in a single transaction: either
+    /// all the tasks are enqueued or none of them is. Returns the uid of the group and its tasks.
+    ///
+    /// The consecutive document additions, or updates, of the group on the same index are
+    /// processed together in a single write transaction, so either all of them are applied or
+    /// none is. The other tasks are processed in order but independently, a failed task doesn't
+    /// revert the previous ones.
+    pub async fn register_group(
+        &self,
+        updates: Vec<(String, Update)>,
+    ) -> Result<(GroupUid, Vec<Task>)> {
+        let mut contents = Vec::with_capacity(updates.len());
+        for (uid, update) in updates {
+            match self.task_content(uid, update).await {
+                Ok(content) => contents.push(content),
+                Err(e) => {
+                    let content_uuids = contents.iter().filter_map(TaskContent::get_content_uuid);
+                    self.delete_update_files(content_uuids).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        let content_uuids: Vec<_> = contents
+            .iter()
+            .filter_map(TaskContent::get_content_uuid)
+            .collect();
+        match self.task_store.register_group(contents).await {
+            Ok(result) => {
+                self.scheduler.read().await.notify();
+                Ok(result)
+            }
+            Err(e) => {
+                self.delete_update_files(content_uuids).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Deletes the update files of tasks that will never be processed.
+    async fn delete_update_files(&self, content_uuids: impl IntoIterator<Item = Uuid>) {
+        for content_uuid in content_uuids {
+            if let Err(e) = self.update_file_store.delete(content_uuid).await {
+                log::error!("error deleting update file: {}", e);
+            }
+        }
+    }
+
+    pub async fn get_group(&self, uid: GroupUid, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
+        let tasks = self.task_store.get_group(uid).await?;
+        match filter {
+            // The group can't be seen if one of its tasks targets an unauthorized index.
+            Some(filter) if !tasks.iter().all(|task| filter.pass(task)) => {
+                Err(TaskError::UnexistingGroup(uid).into())
+            }
+            _ => Ok(tasks),
+        }
+    }
+
+    /// Converts an update to the content of the task registering it. The payload of the
+    /// document additions is written to the update file store.
+    async fn task_content(&self, uid: String, update: Update) -> Result<TaskContent> {
+        // The aliases only resolve in the document and settings operations, the operations on
+        // the indexes themselves always target an index uid.
+        let uid = match update {
+            Update::DeleteIndex
+            | Update::CreateIndex { .. }
+            | Update::UpdateIndex { .. }
+            | Update::CloneIndex { .. } => uid,
+            _ => self.index_resolver.resolve_alias(uid).await?,
+        };
         let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
         let content = match update {
             Update::DeleteDocuments(ids) => TaskContent::DocumentDeletion {
                 index_uid,
                 deletion: DocumentDeletion::Ids(ids),
             },
+            Update::DeleteDocumentsByFilter(filter) => TaskContent::DocumentDeletion {
+                index_uid,
+                deletion: DocumentDeletion::Filter(filter),
+            },
+            Update::EditDocuments { filter, function } => {
+                check_edition_function(&function)?;
+                TaskContent::DocumentEdition {
+                    index_uid,
+                    filter,
+                    function,
+                }
+            }
+            Update::RestoreDocuments { ids } => TaskContent::DocumentRestoration { index_uid, ids },
             Update::ClearDocuments => TaskContent::DocumentDeletion {
                 index_uid,
                 deletion: DocumentDeletion::Clear,
@@ -383,36 +770,81 @@ where
                 primary_key,
                 format,
                 method,
+                merge_patch,
                 allow_index_creation,
             } => {
-                let mut buffer = Vec::new();
-                while let Some(bytes) = payload.next().await {
-                    let bytes = bytes?;
-                    buffer.extend_from_slice(&bytes);
-                }
+                // The payload is streamed to the update file store as it is received, so that
+                // it never has to be fully loaded in memory.
+                let (sender, receiver) = mpsc::channel(1);
                 let (content_uuid, mut update_file) = self.update_file_store.new_update()?;
-                let documents_count = tokio::task::spawn_blocking(move || -> Result<_> {
+                // The ndjson payloads are split into batches written in parallel.
+                let file_store = self.update_file_store.clone();
+                let new_part = move || {
+                    file_store
+                        .new_update_part()
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+                };
+                let ndjson_options = NdjsonPipelineOptions {
+                    threads: *self.indexer_config.opts().max_indexing_threads,
+                    ..Default::default()
+                };
+                let handle = tokio::task::spawn_blocking(move || -> Result<_> {
+                    let mut reader = ChunksReader::new(receiver);
                     // check if the payload is empty, and return an error
-                    if buffer.is_empty() {
+                    if reader.is_empty().map_err(DocumentFormatError::from)? {
                         return Err(IndexControllerError::MissingPayload(format));
                     }
 
-                    let reader = Cursor::new(buffer);
                     let count = match format {
                         DocumentAdditionFormat::Json => read_json(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Csv => read_csv(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Ndjson => read_ndjson(reader, &mut *update_file)?,
+                        DocumentAdditionFormat::Csv(options) => {
+                            read_csv(reader, &mut *update_file, options)?
+                        }
+                        DocumentAdditionFormat::Ndjson => {
+                            let (count, parts) =
+                                read_ndjson_parallel(reader, new_part, ndjson_options)?;
+                            update_file.set_parts(parts);
+                            count
+                        }
+                        DocumentAdditionFormat::MessagePack => {
+                            read_msgpack(reader, &mut *update_file)?
+                        }
+                        DocumentAdditionFormat::Cbor => read_cbor(reader, &mut *update_file)?,
                     };
 
                     update_file.persist()?;
 
                     Ok(count)
-                })
-                .await??;
+                });
+
+                let mut payload_error = None;
+                while let Some(bytes) = payload.next().await {
+                    match bytes {
+                        Ok(bytes) => {
+                            // the reader stops early when the payload is malformed.
+                            if sender.send(Ok(bytes)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            let _ = sender.send(Err(())).await;
+                            payload_error = Some(e);
+                            break;
+                        }
+                    }
+                }
+                drop(sender);
+
+                let result = handle.await?;
+                if let Some(e) = payload_error {
+                    return Err(e.into());
+                }
+                let documents_count = result?;
 
                 TaskContent::DocumentAddition {
                     content_uuid,
                     merge_strategy: method,
+                    merge_patch,
                     primary_key,
                     documents_count,
                     allow_index_creation,
@@ -428,22 +860,311 @@ where
                 primary_key,
                 index_uid,
             },
+            Update::CloneIndex {
+                destination,
+                settings_only,
+            } => TaskContent::IndexClone {
+                destination: IndexUid::from_str(&destination).map_err(IndexResolverError::from)?,
+                settings_only,
+                index_uid,
+            },
         };
 
+        Ok(content)
+    }
+
+    /// Registers a dump. When `incremental_from` is set, the dump only contains the indexes
+    /// modified since the dump `incremental_from`, which must have succeeded.
+    pub async fn register_dump_task(&self, incremental_from: Option<String>) -> Result<Task> {
+        if let Some(ref base) = incremental_from {
+            dump::find_dump_start(&self.task_store, base).await?;
+        }
+
+        let uid = dump::generate_uid();
+        let content = TaskContent::Dump {
+            uid,
+            incremental_from,
+        };
         let task = self.task_store.register(content).await?;
         self.scheduler.read().await.notify();
-
         Ok(task)
     }
 
-    pub async fn register_dump_task(&self) -> Result<Task> {
-        let uid = dump::generate_uid();
-        let content = TaskContent::Dump { uid };
+    /// Registers a task exchanging the two indexes of each pair of `swaps`. An index can only
+    /// appear in one pair, and can't be swapped with itself.
+    pub async fn register_index_swap(&self, swaps: Vec<(String, String)>) -> Result<Task> {
+        let mut seen = HashSet::new();
+        let mut index_swaps = Vec::with_capacity(swaps.len());
+        for (left, right) in swaps {
+            if left == right {
+                return Err(IndexControllerError::InvalidSwapIndexes(format!(
+                    "The index `{}` can't be swapped with itself.",
+                    left
+                )));
+            }
+            for uid in [&left, &right] {
+                if !seen.insert(uid.clone()) {
+                    return Err(IndexControllerError::InvalidSwapIndexes(format!(
+                        "The index `{}` is present in several swaps.",
+                        uid
+                    )));
+                }
+            }
+            let left = IndexUid::from_str(&left)?;
+            let right = IndexUid::from_str(&right)?;
+            index_swaps.push((left, right));
+        }
+
+        if index_swaps.is_empty() {
+            return Err(IndexControllerError::InvalidSwapIndexes(
+                "At least one pair of indexes must be swapped.".to_string(),
+            ));
+        }
+
+        let content = TaskContent::IndexSwap { swaps: index_swaps };
         let task = self.task_store.register(content).await?;
         self.scheduler.read().await.notify();
         Ok(task)
     }
 
+    /// Registers the import of the dump read from `source`, the tasks recreating its indexes
+    /// are registered when the import is processed. The indexes conflicting with an existing
+    /// index are handled according to the `conflict` policy. Only the dumps of version V5 can
+    /// be imported.
+    pub async fn register_dump_import(
+        &self,
+        source: DumpSource,
+        conflict: ImportConflictPolicy,
+    ) -> Result<Task> {
+        let source = match source {
+            DumpSource::Path(path) => {
+                DumpImportSource::Path(dump::resolve_dump_path(&self.dump_path, &path)?)
+            }
+            DumpSource::Url(url) => {
+                dump::check_dump_url(&url, &self.dump_import_allowed_hosts)?;
+                DumpImportSource::Url(url)
+            }
+            DumpSource::Payload(mut payload) => {
+                // The archive is kept in the dump directory until the import is processed.
+                let uuid = Uuid::new_v4();
+                let path = dump::upload_path(&self.dump_path, uuid);
+                let written = async {
+                    if let Some(dir) = path.parent() {
+                        tokio::fs::create_dir_all(dir).await?;
+                    }
+                    let mut file = tokio::fs::File::create(&path).await?;
+                    while let Some(bytes) = payload.next().await {
+                        file.write_all(&bytes?).await?;
+                    }
+                    file.flush().await?;
+                    Ok::<_, IndexControllerError>(())
+                }
+                .await;
+                if written.is_err() {
+                    let _ = tokio::fs::remove_file(&path).await;
+                }
+                written?;
+                DumpImportSource::Upload(uuid)
+            }
+        };
+
+        let upload = match source {
+            DumpImportSource::Upload(uuid) => Some(uuid),
+            _ => None,
+        };
+        let content = TaskContent::DumpImport { source, conflict };
+        match self.task_store.register(content).await {
+            Ok(task) => {
+                self.scheduler.read().await.notify();
+                Ok(task)
+            }
+            Err(e) => {
+                if let Some(uuid) = upload {
+                    let _ = tokio::fs::remove_file(dump::upload_path(&self.dump_path, uuid)).await;
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Schedules a snapshot, it is performed before the next task is processed.
+    pub async fn register_snapshot(&self) -> Result<()> {
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .ok_or(IndexControllerError::SnapshotsDisabled)?;
+        self.scheduler
+            .write()
+            .await
+            .schedule_snapshot(snapshot.job());
+        Ok(())
+    }
+
+    /// Cancels the unfinished tasks matching the `filter`, and returns the `TaskCancelation` task
+    /// recording them. The `query` is the original query of the cancelation. Canceling a task
+    /// being processed stops its whole batch before its changes are committed.
+    pub async fn cancel_tasks(&self, query: String, filter: TaskFilter) -> Result<Task> {
+        let (task, canceled) = self
+            .scheduler
+            .write()
+            .await
+            .cancel_tasks(query, filter)
+            .await?;
+
+        self.delete_update_files(canceled.iter().filter_map(Task::get_content_uuid))
+            .await;
+
+        Ok(task)
+    }
+
+    /// Deletes the finished tasks matching the `filter` from the task history, and returns the
+    /// `TaskDeletion` task recording them. The `query` is the original query of the deletion.
+    pub async fn delete_tasks(&self, query: String, filter: TaskFilter) -> Result<Task> {
+        let scheduler = self.scheduler.read().await;
+        let ids = scheduler.finished_tasks(filter).await?;
+        let task = scheduler.delete_tasks(query, ids).await?;
+        Ok(task)
+    }
+
+    /// Changes the priority of the enqueued task `id`, and returns the task.
+    pub async fn set_task_priority(
+        &self,
+        id: TaskId,
+        priority: TaskPriority,
+        filter: Option<TaskFilter>,
+    ) -> Result<Task> {
+        let task = self.get_task(id, filter).await?;
+
+        if !self
+            .scheduler
+            .write()
+            .await
+            .set_priority(id, priority)
+            .await?
+        {
+            return Err(TaskError::TaskNotEnqueued(id).into());
+        }
+
+        Ok(task)
+    }
+
+    /// Returns a receiver of the tasks that reach a terminal state, either succeeded, failed or
+    /// canceled, from now on.
+    pub async fn subscribe_finished_tasks(&self) -> broadcast::Receiver<Vec<Task>> {
+        self.scheduler.read().await.subscribe_finished_tasks()
+    }
+
+    pub async fn webhook(&self) -> Option<TaskWebhook> {
+        self.webhook.read().await.clone()
+    }
+
+    /// Replaces the endpoint notified when tasks are finished, `None` disables the notifications.
+    pub async fn set_webhook(&self, webhook: Option<TaskWebhook>) {
+        *self.webhook.write().await = webhook;
+    }
+
+    pub fn experimental_features(&self) -> RuntimeFeatures {
+        self.features.get()
+    }
+
+    /// Toggles the experimental features of the `update`, the change is persisted.
+    pub async fn update_experimental_features(
+        &self,
+        update: RuntimeFeaturesUpdate,
+    ) -> Result<RuntimeFeatures> {
+        let features = self.features.clone();
+        let features = spawn_blocking(move || features.update(update)).await??;
+        self.clear_search_cache();
+        Ok(features)
+    }
+
+    /// Whether the searches are counted, see [`IndexController::search_analytics`].
+    pub fn is_search_analytics_enabled(&self) -> bool {
+        self.search_analytics_enabled || self.features.get().search_analytics
+    }
+
+    /// Returns the `limit` most frequent queries made on the index `uid` between `from` and
+    /// `to`, among the ones counted while the search analytics were enabled.
+    pub async fn search_analytics(
+        &self,
+        uid: String,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        limit: usize,
+    ) -> Result<SearchAnalyticsReport> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        // the analytics of the unknown indexes are an error, not an empty report.
+        self.index_resolver.get_index(uid.clone()).await?;
+        Ok(self.search_analytics.report(&uid, from, to, limit))
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    /// Puts the instance in read-only mode, or back in read-write mode. In read-only mode the
+    /// scheduler finishes the batch it is processing and doesn't start a new one, the enqueued
+    /// tasks are processed once the read-write mode is restored. The mode isn't persisted, an
+    /// instance always starts in read-write mode.
+    pub async fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+        self.scheduler.write().await.set_paused(read_only);
+    }
+
+    /// Stops starting new batches of tasks and waits for the batch being processed to finish, for
+    /// at most `timeout`. Returns whether the batch finished in time, otherwise its tasks are
+    /// processed again when the instance restarts since the changes of a batch are committed at
+    /// once. The scheduler isn't resumed, this is meant to be called before exiting.
+    pub async fn drain_tasks(&self, timeout: Duration) -> Result<bool> {
+        self.scheduler.write().await.set_paused(true);
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.is_processing().await? {
+            if tokio::time::Instant::now() >= deadline {
+                return Ok(false);
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        Ok(true)
+    }
+
+    /// Returns whether the scheduler is processing a batch of tasks.
+    pub async fn is_processing(&self) -> Result<bool> {
+        let processing = self.scheduler.read().await.get_processing_tasks().await?;
+        Ok(!processing.is_empty())
+    }
+
+    pub fn indexer_options(&self) -> IndexerOpts {
+        self.indexer_config.opts()
+    }
+
+    /// Replaces the memory and threads limits of the indexer, the batch being processed keeps
+    /// the limits it started with.
+    pub fn set_indexer_options(&self, opts: IndexerOpts) -> Result<()> {
+        self.indexer_config
+            .set_opts(opts)
+            .map_err(|e| IndexControllerError::Internal(e.into()))
+    }
+
+    pub async fn search_cutoff(&self) -> Option<Duration> {
+        *self.search_cutoff.read().await
+    }
+
+    /// Replaces the search cutoff applied to the indexes that don't define their own, `None`
+    /// removes it.
+    pub async fn set_search_cutoff(&self, search_cutoff: Option<Duration>) {
+        *self.search_cutoff.write().await = search_cutoff;
+        self.clear_search_cache();
+    }
+
+    /// Forgets the cached search results, once an option changing the searches changed.
+    fn clear_search_cache(&self) {
+        if let Some(cache) = &self.search_cache {
+            cache.clear();
+        }
+    }
+
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
         let task = self.scheduler.read().await.get_task(id, filter).await?;
         Ok(task)
@@ -530,7 +1251,34 @@ where
         Ok(ret)
     }
 
+    /// Returns the documents `ids` of the index `uid` in the order they are requested, and the
+    /// ids of the ones that don't exist.
+    pub async fn documents_by_ids(
+        &self,
+        uid: String,
+        ids: Vec<String>,
+        attributes_to_retrieve: Option<Vec<String>>,
+    ) -> Result<(Vec<Document>, Vec<String>)> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index = self.index_resolver.get_index(uid).await?;
+        let result =
+            spawn_blocking(move || index.retrieve_documents_by_ids(ids, attributes_to_retrieve))
+                .await??;
+        Ok(result)
+    }
+
+    /// Returns the uuid of the index `uid` and the date of its last update, they change whenever
+    /// its documents or its settings change.
+    pub async fn index_version(&self, uid: String) -> Result<(Uuid, OffsetDateTime)> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index = self.index_resolver.get_index(uid).await?;
+        let uuid = index.uuid();
+        let meta = spawn_blocking(move || index.meta()).await??;
+        Ok((uuid, meta.updated_at))
+    }
+
     pub async fn settings(&self, uid: String) -> Result<Settings<Checked>> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
         let index = self.index_resolver.get_index(uid).await?;
         let settings = spawn_blocking(move || index.settings()).await??;
         Ok(settings)
@@ -543,11 +1291,32 @@ where
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<String>>,
+        filter: Option<serde_json::Value>,
+        sort: Option<Vec<String>>,
     ) -> Result<(u64, Vec<Document>)> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
         let index = self.index_resolver.get_index(uid).await?;
-        let result =
-            spawn_blocking(move || index.retrieve_documents(offset, limit, attributes_to_retrieve))
-                .await??;
+        let result = spawn_blocking(move || {
+            index.retrieve_documents(offset, limit, attributes_to_retrieve, filter, sort)
+        })
+        .await??;
+        Ok(result)
+    }
+
+    pub async fn documents_after(
+        &self,
+        uid: String,
+        after: Option<milli::DocumentId>,
+        limit: usize,
+        attributes_to_retrieve: Option<Vec<String>>,
+        filter: Option<serde_json::Value>,
+    ) -> Result<(Vec<Document>, Option<milli::DocumentId>)> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index = self.index_resolver.get_index(uid).await?;
+        let result = spawn_blocking(move || {
+            index.retrieve_documents_after(after, limit, attributes_to_retrieve, filter)
+        })
+        .await??;
         Ok(result)
     }
 
@@ -557,6 +1326,7 @@ where
         doc_id: String,
         attributes_to_retrieve: Option<Vec<String>>,
     ) -> Result<Document> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
         let index = self.index_resolver.get_index(uid).await?;
         let document =
             spawn_blocking(move || index.retrieve_document(doc_id, attributes_to_retrieve))
@@ -565,8 +1335,137 @@ where
     }
 
     pub async fn search(&self, uid: String, query: SearchQuery) -> Result<SearchResult> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        // the span of the request is carried to the blocking thread performing the search.
+        let span = tracing::info_span!("search", index = %uid);
+        // the query is moved to the search, it is only described when it may be logged.
+        let sanitized_query = self.slow_query_threshold.map(|_| query.sanitized());
+        let analytics_query = self.is_search_analytics_enabled().then(|| {
+            (
+                query.q.clone().unwrap_or_default(),
+                query.ranking_profile.clone(),
+            )
+        });
+        let index = self.index_resolver.get_index(uid.clone()).await?;
+
+        // the version is read before the search, so an outdated result isn't cached.
+        let cached = self
+            .search_cache
+            .as_ref()
+            .map(|cache| (cache, query.cache_key(), cache.version(&uid)));
+        if let Some((cache, key, _)) = &cached {
+            let before_lookup = std::time::Instant::now();
+            if let Some(mut result) = cache.get(&uid, key) {
+                result.processing_time_ms = before_lookup.elapsed().as_millis();
+                if let Some((q, profile)) = analytics_query {
+                    self.record_search(&uid, &q, profile.as_deref(), &result);
+                }
+                return Ok(result);
+            }
+        }
+
+        let search_cutoff = *self.search_cutoff.read().await;
+        let cacheable = cached.is_some();
+        let (result, expires_at) = spawn_blocking(move || {
+            span.in_scope(|| -> Result<_> {
+                // read before the search, an expiry added meanwhile is covered by its task.
+                let expires_at = if cacheable {
+                    index.next_document_expiration(OffsetDateTime::now_utc())?
+                } else {
+                    None
+                };
+                Ok((index.perform_search(query, search_cutoff)?, expires_at))
+            })
+        })
+        .await??;
+
+        // the results interrupted by the cutoff are incomplete.
+        if let Some((cache, key, version)) = cached {
+            if !result.degraded {
+                cache.insert(&uid, version, key, result.clone(), expires_at);
+            }
+        }
+
+        if let Some((q, profile)) = analytics_query {
+            self.record_search(&uid, &q, profile.as_deref(), &result);
+        }
+
+        if let (Some(threshold), Some(query)) = (self.slow_query_threshold, sanitized_query) {
+            let processing_time = Duration::from_millis(result.processing_time_ms as u64);
+            if processing_time >= threshold {
+                let SearchTimings {
+                    ranking,
+                    formatting,
+                    suggestions,
+                    facets,
+                } = &result.timings;
+                log::warn!(
+                    "Slow search on the index `{}` in {:?} (ranking: {:?}, formatting: {:?}, suggestions: {:?}, facets: {:?}): {}",
+                    uid,
+                    processing_time,
+                    ranking,
+                    formatting,
+                    suggestions,
+                    facets,
+                    query
+                );
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn record_search(&self, uid: &str, q: &str, profile: Option<&str>, result: &SearchResult) {
+        self.search_analytics.record(
+            uid,
+            q,
+            profile,
+            result.hits_info.total_hits(),
+            result.processing_time_ms,
+        );
+    }
+
+    pub async fn facet_search(
+        &self,
+        uid: String,
+        query: FacetSearchQuery,
+    ) -> Result<FacetSearchResult> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
         let index = self.index_resolver.get_index(uid).await?;
-        let result = spawn_blocking(move || index.perform_search(query)).await??;
+        let result = spawn_blocking(move || index.perform_facet_search(query)).await??;
+        Ok(result)
+    }
+
+    pub async fn suggest(&self, uid: String, query: SuggestQuery) -> Result<SuggestResult> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index = self.index_resolver.get_index(uid).await?;
+        let result = spawn_blocking(move || index.perform_suggest(query)).await??;
+        Ok(result)
+    }
+
+    /// Returns the documents of the index `uid` that are the most similar to the document `doc_id`.
+    /// Returns the changes of the documents of the index `uid` made after the change `since`.
+    pub async fn document_changes(
+        &self,
+        uid: String,
+        since: u64,
+        limit: usize,
+    ) -> Result<DocumentChanges> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index = self.index_resolver.get_index(uid).await?;
+        let changes = spawn_blocking(move || index.document_changes(since, limit)).await??;
+        Ok(changes)
+    }
+
+    pub async fn similar(
+        &self,
+        uid: String,
+        doc_id: String,
+        query: SimilarQuery,
+    ) -> Result<SimilarResult> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index = self.index_resolver.get_index(uid).await?;
+        let result = spawn_blocking(move || index.perform_similar(doc_id, query)).await??;
         Ok(result)
     }
 
@@ -578,7 +1477,8 @@ where
         Ok(meta)
     }
 
-    pub async fn get_index_stats(&self, uid: String) -> Result<IndexStats> {
+    /// Returns the stats of the index `uid`, along with its `detailed` stats if requested.
+    pub async fn get_index_stats(&self, uid: String, detailed: bool) -> Result<IndexStats> {
         let processing_tasks = self.scheduler.read().await.get_processing_tasks().await?;
         // Check if the currently indexing update is from our index.
         let is_indexing = processing_tasks
@@ -586,12 +1486,89 @@ where
             .map_or(false, |task| task.index_uid().map_or(false, |u| u == uid));
 
         let index = self.index_resolver.get_index(uid).await?;
-        let mut stats = spawn_blocking(move || index.stats()).await??;
+        let mut stats = spawn_blocking(move || -> Result<_> {
+            let mut stats = index.stats()?;
+            if detailed {
+                stats.details = Some(index.detailed_stats()?);
+            }
+            Ok(stats)
+        })
+        .await??;
         stats.is_indexing = Some(is_indexing);
 
         Ok(stats)
     }
 
+    /// Returns the uid of the index the alias `uid` points to, or `uid` itself if it isn't an
+    /// alias.
+    pub async fn resolve_alias(&self, uid: String) -> Result<String> {
+        Ok(self.index_resolver.resolve_alias(uid).await?)
+    }
+
+    pub async fn get_alias(&self, alias: String) -> Result<Alias> {
+        let index_uid = self.index_resolver.get_alias(alias.clone()).await?;
+        Ok(Alias { alias, index_uid })
+    }
+
+    pub async fn list_aliases(&self) -> Result<Vec<Alias>> {
+        let aliases = self.index_resolver.list_aliases().await?;
+        Ok(aliases
+            .into_iter()
+            .map(|(alias, index_uid)| Alias { alias, index_uid })
+            .collect())
+    }
+
+    /// Points `alias` to the index `index_uid`, creating the alias if it doesn't exist. The
+    /// alias is retargeted atomically: an operation either resolves to the previous index or to
+    /// the new one.
+    pub async fn set_alias(&self, alias: String, index_uid: String) -> Result<Alias> {
+        self.index_resolver
+            .put_alias(alias.clone(), index_uid.clone())
+            .await?;
+        Ok(Alias { alias, index_uid })
+    }
+
+    pub async fn delete_alias(&self, alias: String) -> Result<()> {
+        Ok(self.index_resolver.delete_alias(alias).await?)
+    }
+
+    pub async fn get_template(&self, name: String) -> Result<IndexTemplate> {
+        Ok(self.index_resolver.get_template(name).await?)
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+        Ok(self.index_resolver.list_templates().await?)
+    }
+
+    /// Creates or replaces an index template. The settings of the template are applied to the
+    /// indexes created afterward whose uid matches its pattern, the existing indexes are left
+    /// untouched.
+    pub async fn set_template(&self, template: IndexTemplate) -> Result<IndexTemplate> {
+        self.index_resolver.put_template(template.clone()).await?;
+        Ok(template)
+    }
+
+    pub async fn delete_template(&self, name: String) -> Result<()> {
+        Ok(self.index_resolver.delete_template(name).await?)
+    }
+
+    /// Checks that the databases can be read: the task store and every index are opened and
+    /// read from.
+    pub async fn check_databases(&self) -> Result<()> {
+        self.task_store.list_tasks(None, None, Some(1)).await?;
+        for (_, index) in self.index_resolver.list().await? {
+            spawn_blocking(move || index.meta()).await??;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the scheduler is still processing the tasks, it stops if its update loop
+    /// panics.
+    pub async fn is_scheduler_running(&self) -> bool {
+        self.scheduler.read().await.is_running()
+    }
+
     pub async fn get_all_stats(&self, search_rules: &SearchRules) -> Result<Stats> {
         let mut last_task: Option<OffsetDateTime> = None;
         let mut indexes = BTreeMap::new();
@@ -654,7 +1631,7 @@ mod test {
     use crate::index::error::Result as IndexResult;
     use crate::index::Index;
     use crate::index::{
-        DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+        HitsInfo, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
     };
     use crate::index_resolver::index_store::MockIndexStore;
     use crate::index_resolver::meta_store::MockIndexMetaStore;
@@ -674,6 +1651,20 @@ mod test {
                 task_store,
                 update_file_store,
                 scheduler,
+                search_cutoff: Default::default(),
+                slow_query_threshold: None,
+                search_cache: None,
+                webhook: Default::default(),
+                snapshot: None,
+                read_only: Default::default(),
+                indexer_config: IndexerConfigHandle::new(Default::default()).unwrap(),
+                features: Arc::new(FeatureStore::new(tempfile::tempdir().unwrap().path()).unwrap()),
+                search_analytics: Default::default(),
+                search_analytics_enabled: false,
+                replication_log_retention: None,
+                replica_of: None,
+                dump_path: PathBuf::from("dumps"),
+                dump_import_allowed_hosts: Vec::new(),
             }
         }
     }
@@ -686,6 +1677,8 @@ mod test {
             q: Some(String::from("hello world")),
             offset: Some(10),
             limit: 0,
+            page: None,
+            hits_per_page: None,
             attributes_to_retrieve: Some(vec!["string".to_owned()].into_iter().collect()),
             attributes_to_crop: None,
             crop_length: 18,
@@ -694,22 +1687,41 @@ mod test {
             filter: None,
             sort: None,
             facets: None,
+            vector: None,
+            hybrid: None,
+            matching_strategy: Default::default(),
+            attributes_to_search_on: None,
+            distinct: None,
+            attribute_weights: None,
+            ranking_profile: None,
+            show_suggestions: false,
             highlight_pre_tag: DEFAULT_HIGHLIGHT_PRE_TAG(),
             highlight_post_tag: DEFAULT_HIGHLIGHT_POST_TAG(),
             crop_marker: DEFAULT_CROP_MARKER(),
+            attribute_restrictions: None,
         };
 
         let result = SearchResult {
             hits: vec![],
-            estimated_total_hits: 29,
             query: "hello world".to_string(),
-            limit: 24,
-            offset: 0,
             processing_time_ms: 50,
+            hits_info: HitsInfo::OffsetLimit {
+                limit: 24,
+                offset: 0,
+                estimated_total_hits: 29,
+            },
             facet_distribution: None,
+            facet_stats: None,
+            degraded: false,
+            suggestions: None,
+            timings: Default::default(),
         };
 
         let mut uuid_store = MockIndexMetaStore::new();
+        uuid_store
+            .expect_get_alias()
+            .with(eq(index_uid.to_owned()))
+            .returning(|_| Box::pin(ok(None)));
         uuid_store
             .expect_get()
             .with(eq(index_uid.to_owned()))
@@ -734,10 +1746,13 @@ mod test {
                 let query = query_clone.clone();
                 let mocker = Mocker::default();
                 mocker
-                    .when::<SearchQuery, IndexResult<SearchResult>>("perform_search")
+                    .when::<(SearchQuery, Option<Duration>), IndexResult<SearchResult>>(
+                        "perform_search",
+                    )
                     .once()
-                    .then(move |q| {
+                    .then(move |(q, cutoff)| {
                         assert_eq!(&q, &query);
+                        assert_eq!(cutoff, None);
                         Ok(result.clone())
                     });
                 let index = Index::mock(mocker);
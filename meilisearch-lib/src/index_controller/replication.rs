@@ -0,0 +1,454 @@
+//! The replication of a primary instance by read-only followers. The primary serves the log of
+//! its processed batches, the followers replay the tasks that succeeded in the order they were
+//! processed, which the priorities make different from the order of the task uids, so they end
+//! up holding the same indexes with some delay.
+//!
+//! A follower can't replay the tasks deleted from the primary, nor the document additions whose
+//! update file retention elapsed: it stops replicating and must be restored from a new snapshot
+//! of the primary.
+
+use std::fs;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use log::{error, info};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use super::error::{IndexControllerError, Result};
+use super::{IndexController, MeiliSearch};
+use crate::document_formats::read_ndjson;
+use crate::index_resolver::index_store::IndexStore;
+use crate::index_resolver::meta_store::IndexMetaStore;
+use crate::tasks::task::{BatchSeq, Task, TaskContent, TaskEvent, TaskId};
+use crate::tasks::TaskFilter;
+
+/// The file of the database directory holding the seq of the last batch of the primary a follower
+/// is up to date with.
+pub const REPLICATION_POSITION_FILE_NAME: &str = "replication-position";
+
+/// How often a follower asks the primary for new tasks once it is up to date.
+const REPLICATION_POLL_PERIOD: Duration = Duration::from_secs(1);
+
+/// The number of batches a follower asks the primary for at once.
+const REPLICATION_PAGE_SIZE: usize = 100;
+
+/// How often a follower checks whether the tasks it registered before an ordering point are
+/// finished.
+const ORDERING_POINT_POLL_PERIOD: Duration = Duration::from_millis(100);
+
+/// How often the primary checks for the update files whose replication retention elapsed.
+const UPDATE_FILES_CHECK_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// The size of the chunks of the update files sent to the followers.
+const UPDATE_FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ReplicaConfig {
+    /// The URL of the primary instance, e.g. `http://primary:7700`.
+    pub primary_url: String,
+    /// The API key sent to the primary, it must grant the `replication.get` action.
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicatedTask {
+    pub uid: TaskId,
+    pub batch_seq: BatchSeq,
+    pub content: TaskContent,
+}
+
+/// A page of the replication log: the tasks to replay, in order, and the seq of the last batch
+/// of the primary the follower is up to date with once they are replayed.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationLog {
+    pub tasks: Vec<ReplicatedTask>,
+    pub last_batch_seq: Option<BatchSeq>,
+}
+
+/// Returns whether the followers replay the `task`: the ones that failed didn't change anything
+/// and the dumps and the task deletions only concern the primary. The task cancelations are only
+/// replayed as ordering points.
+fn is_replicated(task: &Task) -> bool {
+    let succeeded = matches!(task.events.last(), Some(TaskEvent::Succeeded { .. }));
+    let replicated_content = !matches!(
+        task.content,
+        TaskContent::Dump { .. }
+            | TaskContent::DumpImport { .. }
+            | TaskContent::TaskDeletion { .. }
+    );
+    succeeded && replicated_content
+}
+
+/// Returns whether the `content` is an ordering point of the replication log: a follower replays
+/// it once all the tasks it replayed before are finished, so that its scheduler doesn't reorder
+/// the tasks around it. The barriers change several indexes at once, and a task cancelation
+/// separates the tasks canceled on the primary from the ones registered after it.
+fn is_ordering_point(content: &TaskContent) -> bool {
+    matches!(
+        content,
+        TaskContent::IndexSwap { .. }
+            | TaskContent::IndexClone { .. }
+            | TaskContent::TaskCancelation { .. }
+    )
+}
+
+impl<U, I> IndexController<U, I>
+where
+    U: IndexMetaStore,
+    I: IndexStore,
+{
+    /// Returns whether the instance is a follower replicating a primary instance, it doesn't
+    /// accept the write requests.
+    pub fn is_replica(&self) -> bool {
+        self.replica_of.is_some()
+    }
+
+    /// Returns the URL of the primary instance replicated by this one, if it is a follower.
+    pub fn replica_of(&self) -> Option<&str> {
+        self.replica_of.as_deref()
+    }
+
+    /// Returns the tasks of at most `limit` batches processed after the batch `after`, or from
+    /// the first batch, in the order they were processed.
+    ///
+    /// Returns [`IndexControllerError::ReplicationPositionUnavailable`] if a task of a batch
+    /// following `after` was deleted, the follower can't catch up anymore.
+    pub async fn replication_log(
+        &self,
+        after: Option<BatchSeq>,
+        limit: usize,
+    ) -> Result<ReplicationLog> {
+        if self.replication_log_retention.is_none() {
+            return Err(IndexControllerError::ReplicationDisabled);
+        }
+
+        let batches = self.task_store.list_batches(after, limit.max(1)).await?;
+        let mut log = ReplicationLog {
+            tasks: Vec::new(),
+            last_batch_seq: after,
+        };
+
+        for (seq, tasks) in batches {
+            for task in tasks {
+                let task = task.ok_or(IndexControllerError::ReplicationPositionUnavailable(seq))?;
+                if is_replicated(&task) {
+                    log.tasks.push(ReplicatedTask {
+                        uid: task.id,
+                        batch_seq: seq,
+                        content: task.content,
+                    });
+                }
+            }
+            log.last_batch_seq = Some(seq);
+        }
+
+        Ok(log)
+    }
+
+    /// Returns the documents of the update file `uuid` as a stream of NDJSON chunks, for the
+    /// followers replaying its document addition. The file is read as the chunks are consumed.
+    pub fn replication_update_file(&self, uuid: Uuid) -> Result<impl Stream<Item = Result<Bytes>>> {
+        if self.replication_log_retention.is_none() {
+            return Err(IndexControllerError::ReplicationDisabled);
+        }
+        if !self.update_file_store.contains(uuid) {
+            return Err(IndexControllerError::UpdateFileNotFound(uuid));
+        }
+
+        let (sender, receiver) = mpsc::channel(1);
+        let update_file_store = self.update_file_store.clone();
+        spawn_blocking(move || {
+            let mut writer =
+                BufWriter::with_capacity(UPDATE_FILE_CHUNK_SIZE, ChunkSender(sender.clone()));
+            let written = update_file_store
+                .write_ndjson(uuid, &mut writer)
+                .map_err(IndexControllerError::from)
+                .and_then(|()| writer.flush().map_err(IndexControllerError::from));
+            if let Err(e) = written {
+                // the follower is already gone if the chunks can't be sent.
+                let _ = sender.blocking_send(Err(e));
+            }
+        });
+
+        Ok(futures::stream::unfold(
+            receiver,
+            |mut receiver| async move {
+                let chunk = receiver.recv().await?;
+                Some((chunk, receiver))
+            },
+        ))
+    }
+}
+
+/// Sends the bytes written to it as chunks of an update file.
+struct ChunkSender(mpsc::Sender<Result<Bytes>>);
+
+impl Write for ChunkSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "the follower is gone"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Copies the replication position of the database at `src` to `dst`, if it is a follower, so
+/// that a follower restored from a snapshot resumes where it was.
+pub fn copy_replication_position(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let src = src.join(REPLICATION_POSITION_FILE_NAME);
+    match fs::copy(src, dst.join(REPLICATION_POSITION_FILE_NAME)) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Periodically deletes, on the primary, the update files of the document additions processed
+/// before the replication retention. The followers lagging further behind can't replay them.
+pub struct ReplicationLogService {
+    pub(crate) meilisearch: MeiliSearch,
+    pub(crate) retention: Duration,
+}
+
+impl ReplicationLogService {
+    pub async fn run(self) {
+        info!(
+            "The update files are kept for {}s for the followers.",
+            self.retention.as_secs()
+        );
+        loop {
+            if let Err(e) = self.delete_expired_update_files().await {
+                error!(
+                    "an error occured while deleting the replicated update files: {}",
+                    e
+                );
+            }
+            sleep(UPDATE_FILES_CHECK_PERIOD).await;
+        }
+    }
+
+    async fn delete_expired_update_files(&self) -> Result<()> {
+        let before = OffsetDateTime::now_utc() - self.retention;
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(move |task| {
+            task.get_content_uuid().is_some() && task.finished_at().map_or(false, |at| at < before)
+        });
+
+        let tasks = self
+            .meilisearch
+            .task_store
+            .list_tasks(None, Some(filter), None)
+            .await?;
+        let update_file_store = &self.meilisearch.update_file_store;
+        for uuid in tasks.iter().filter_map(Task::get_content_uuid) {
+            // the files checked by the previous runs are already deleted.
+            if update_file_store.contains(uuid) {
+                update_file_store.delete(uuid).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Replays, on a follower, the tasks of the primary as soon as they are finished.
+pub struct ReplicationService {
+    pub(crate) meilisearch: MeiliSearch,
+    pub(crate) config: ReplicaConfig,
+    /// The file holding the seq of the last batch of the primary the follower is up to date with.
+    pub(crate) position_path: PathBuf,
+    pub(crate) client: Client,
+}
+
+impl ReplicationService {
+    pub async fn run(self) {
+        info!(
+            "Replicating the primary instance at {}.",
+            self.config.primary_url
+        );
+        let mut position = match self.read_position().await {
+            Ok(position) => position,
+            Err(e) => {
+                error!("the replication position can't be read: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            match self.replicate(&mut position).await {
+                // the follower is catching up, the next tasks are fetched at once.
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(
+                    e @ (IndexControllerError::ReplicationPositionUnavailable(_)
+                    | IndexControllerError::UpdateFileNotFound(_)),
+                ) => {
+                    error!(
+                        "the replication is stopped, the follower must be restored from a new snapshot of the primary instance: {}",
+                        e
+                    );
+                    return;
+                }
+                Err(e) => error!(
+                    "an error occured while replicating the primary instance: {}",
+                    e
+                ),
+            }
+            sleep(REPLICATION_POLL_PERIOD).await;
+        }
+    }
+
+    async fn read_position(&self) -> Result<Option<BatchSeq>> {
+        match fs::read_to_string(&self.position_path) {
+            Ok(content) => content
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|e| IndexControllerError::Internal(Box::new(e))),
+            // a new follower either is empty, or was restored from a snapshot of the primary and
+            // holds its batches, it resumes after the last one.
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Ok(self.meilisearch.task_store.last_batch_seq().await?)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_position(&self, position: BatchSeq) -> Result<()> {
+        // the file is replaced at once, to not be left half written.
+        let temp = self.position_path.with_extension("tmp");
+        fs::write(&temp, position.to_string())?;
+        fs::rename(&temp, &self.position_path)?;
+        Ok(())
+    }
+
+    fn get(&self, route: &str) -> RequestBuilder {
+        let url = format!("{}{}", self.config.primary_url.trim_end_matches('/'), route);
+        let request = self.client.get(url);
+        match self.config.api_key {
+            Some(ref api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    /// Replays the next page of the replication log, returns whether the position moved.
+    ///
+    /// The position moves once all the replicated tasks of a batch are registered, a follower
+    /// stopped in the middle of a batch registers its tasks again.
+    async fn replicate(&self, position: &mut Option<BatchSeq>) -> Result<bool> {
+        let mut route = format!("/replication/tasks?limit={}", REPLICATION_PAGE_SIZE);
+        if let Some(after) = position {
+            route.push_str(&format!("&after={}", after));
+        }
+        let response = self.get(&route).send().await?;
+        if response.status() == StatusCode::GONE {
+            let first = position.map_or(0, |after| after.saturating_add(1));
+            return Err(IndexControllerError::ReplicationPositionUnavailable(first));
+        }
+        let log: ReplicationLog = response.error_for_status()?.json().await?;
+
+        let previous = *position;
+        for task in log.tasks {
+            // the tasks of the batches before the one of `task` are all registered.
+            if let Some(done) = task.batch_seq.checked_sub(1) {
+                if Some(done) > *position {
+                    *position = Some(done);
+                    self.write_position(done)?;
+                }
+            }
+
+            if is_ordering_point(&task.content) {
+                self.wait_for_registered_tasks().await?;
+            }
+
+            let mut content = task.content;
+            match content {
+                // the canceled tasks never succeeded on the primary, they weren't replayed.
+                TaskContent::TaskCancelation { .. } => continue,
+                TaskContent::DocumentAddition {
+                    ref mut content_uuid,
+                    ..
+                } => *content_uuid = self.fetch_update_file(*content_uuid).await?,
+                _ => (),
+            }
+
+            self.meilisearch.task_store.register(content).await?;
+            self.meilisearch.scheduler.read().await.notify();
+        }
+
+        if let Some(last_batch_seq) = log.last_batch_seq {
+            if *position != Some(last_batch_seq) {
+                *position = Some(last_batch_seq);
+                self.write_position(last_batch_seq)?;
+            }
+        }
+
+        Ok(*position != previous)
+    }
+
+    /// Waits for all the tasks registered on the follower to be finished.
+    async fn wait_for_registered_tasks(&self) -> Result<()> {
+        let mut from = None;
+        loop {
+            let unfinished = self
+                .meilisearch
+                .task_store
+                .fetch_unfinished_tasks(from)
+                .await?;
+            match unfinished.first() {
+                // the tasks before the first unfinished one are never checked again.
+                Some(task) => from = Some(task.id),
+                None => return Ok(()),
+            }
+            sleep(ORDERING_POINT_POLL_PERIOD).await;
+        }
+    }
+
+    /// Downloads the update file `uuid` of the primary to a new update file, returns its uuid.
+    /// The documents are written to a temporary file as they are received, and read from it
+    /// once they are all there.
+    async fn fetch_update_file(&self, uuid: Uuid) -> Result<Uuid> {
+        let response = self
+            .get(&format!("/replication/update-files/{}", uuid))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            // the follower must be restored from a new snapshot of the primary.
+            return Err(IndexControllerError::UpdateFileNotFound(uuid));
+        }
+
+        let content = NamedTempFile::new()?;
+        let mut file = tokio::fs::File::from_std(content.reopen()?);
+        let mut stream = response.error_for_status()?.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.flush().await?;
+
+        let update_file_store = self.meilisearch.update_file_store.clone();
+        spawn_blocking(move || -> Result<_> {
+            let (uuid, mut update_file) = update_file_store.new_update()?;
+            read_ndjson(BufReader::new(content.reopen()?), &mut *update_file)?;
+            update_file.persist()?;
+            Ok(uuid)
+        })
+        .await?
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::tasks::task::{Task, TaskContent, TaskEvent};
+
+/// The duration covered by a bucket of searches, in seconds.
+const BUCKET_DURATION: i64 = 60 * 60;
+/// How long the searches are remembered, in buckets.
+const RETENTION: i64 = 7 * 24;
+/// The maximum number of distinct queries counted by bucket for an index, the following ones are
+/// only counted in the totals.
+const MAX_QUERIES_PER_BUCKET: usize = 10_000;
+
+/// The searches made on each index, counted by hour in memory. They are lost on restart, and the
+/// ones older than a week are forgotten.
+#[derive(Default)]
+pub struct SearchAnalytics {
+    /// The buckets of each index uid, by starting timestamp.
+    buckets: Mutex<HashMap<String, BTreeMap<i64, Bucket>>>,
+}
+
+#[derive(Default)]
+struct Bucket {
+    searches: u64,
+    zero_result_searches: u64,
+    processing_time_ms: u128,
+    queries: HashMap<String, QueryCount>,
+    profiles: HashMap<String, ProfileCount>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct QueryCount {
+    searches: u64,
+    zero_result_searches: u64,
+}
+
+#[derive(Default, Clone, Copy)]
+struct ProfileCount {
+    searches: u64,
+    zero_result_searches: u64,
+    processing_time_ms: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchAnalyticsReport {
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    pub total_searches: u64,
+    pub zero_result_searches: u64,
+    /// `None` when there were no searches.
+    pub average_processing_time_ms: Option<f64>,
+    /// The most frequent queries, the placeholder searches aren't counted.
+    pub top_queries: Vec<QueryStat>,
+    /// The most frequent queries that matched no documents.
+    pub zero_result_queries: Vec<QueryStat>,
+    /// The searches made with each ranking profile, to compare their relevance.
+    pub profiles: BTreeMap<String, ProfileStat>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryStat {
+    pub query: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileStat {
+    pub total_searches: u64,
+    pub zero_result_searches: u64,
+    pub average_processing_time_ms: f64,
+}
+
+impl SearchAnalytics {
+    /// Counts a search of `query` on the index `uid` with the ranking `profile`, which matched
+    /// `hits` documents.
+    pub fn record(
+        &self,
+        uid: &str,
+        query: &str,
+        profile: Option<&str>,
+        hits: u64,
+        processing_time_ms: u128,
+    ) {
+        self.record_at(
+            OffsetDateTime::now_utc(),
+            uid,
+            query,
+            profile,
+            hits,
+            processing_time_ms,
+        )
+    }
+
+    fn record_at(
+        &self,
+        at: OffsetDateTime,
+        uid: &str,
+        query: &str,
+        profile: Option<&str>,
+        hits: u64,
+        processing_time_ms: u128,
+    ) {
+        let start = bucket_start(at);
+        let mut buckets = self.buckets.lock().unwrap();
+        let index_buckets = buckets.entry(uid.to_string()).or_default();
+        // the buckets are forgotten once they are older than the retention.
+        while let Some(oldest) = index_buckets.keys().next().copied() {
+            if oldest > start - RETENTION * BUCKET_DURATION {
+                break;
+            }
+            index_buckets.remove(&oldest);
+        }
+
+        let bucket = index_buckets.entry(start).or_default();
+        let zero_result = hits == 0;
+        bucket.searches += 1;
+        bucket.zero_result_searches += zero_result as u64;
+        bucket.processing_time_ms += processing_time_ms;
+
+        if let Some(profile) = profile {
+            let count = bucket.profiles.entry(profile.to_string()).or_default();
+            count.searches += 1;
+            count.zero_result_searches += zero_result as u64;
+            count.processing_time_ms += processing_time_ms;
+        }
+
+        let query = normalize_query(query);
+        if query.is_empty() {
+            return;
+        }
+        let full = bucket.queries.len() >= MAX_QUERIES_PER_BUCKET;
+        let count = match bucket.queries.get_mut(&query) {
+            Some(count) => count,
+            None if full => return,
+            None => bucket.queries.entry(query).or_default(),
+        };
+        count.searches += 1;
+        count.zero_result_searches += zero_result as u64;
+    }
+
+    /// Returns the `limit` most frequent queries made on the index `uid` between `from` and `to`.
+    /// The searches are counted by hour, all the searches of the hours overlapping the window
+    /// are counted.
+    pub fn report(
+        &self,
+        uid: &str,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        limit: usize,
+    ) -> SearchAnalyticsReport {
+        let mut total_searches = 0;
+        let mut zero_result_searches = 0;
+        let mut processing_time_ms = 0;
+        let mut queries: HashMap<&str, QueryCount> = HashMap::new();
+        let mut profiles: BTreeMap<&str, ProfileCount> = BTreeMap::new();
+
+        let buckets = self.buckets.lock().unwrap();
+        let start = bucket_start(from);
+        let range = start..to.unix_timestamp().max(start);
+        for (_, bucket) in buckets
+            .get(uid)
+            .into_iter()
+            .flat_map(|index_buckets| index_buckets.range(range.clone()))
+        {
+            total_searches += bucket.searches;
+            zero_result_searches += bucket.zero_result_searches;
+            processing_time_ms += bucket.processing_time_ms;
+            for (query, count) in &bucket.queries {
+                let total = queries.entry(query).or_default();
+                total.searches += count.searches;
+                total.zero_result_searches += count.zero_result_searches;
+            }
+            for (profile, count) in &bucket.profiles {
+                let total = profiles.entry(profile).or_default();
+                total.searches += count.searches;
+                total.zero_result_searches += count.zero_result_searches;
+                total.processing_time_ms += count.processing_time_ms;
+            }
+        }
+
+        let most_frequent = |count: fn(&QueryCount) -> u64| {
+            let mut stats: Vec<_> = queries
+                .iter()
+                .filter(|(_, c)| count(c) > 0)
+                .map(|(query, c)| QueryStat {
+                    query: query.to_string(),
+                    count: count(c),
+                })
+                .collect();
+            stats.sort_by(|l, r| r.count.cmp(&l.count).then_with(|| l.query.cmp(&r.query)));
+            stats.truncate(limit);
+            stats
+        };
+
+        SearchAnalyticsReport {
+            from,
+            to,
+            total_searches,
+            zero_result_searches,
+            average_processing_time_ms: (total_searches > 0)
+                .then(|| processing_time_ms as f64 / total_searches as f64),
+            top_queries: most_frequent(|c| c.searches),
+            zero_result_queries: most_frequent(|c| c.zero_result_searches),
+            profiles: profiles
+                .into_iter()
+                .map(|(profile, count)| {
+                    let stat = ProfileStat {
+                        total_searches: count.searches,
+                        zero_result_searches: count.zero_result_searches,
+                        average_processing_time_ms: count.processing_time_ms as f64
+                            / count.searches as f64,
+                    };
+                    (profile.to_string(), stat)
+                })
+                .collect(),
+        }
+    }
+
+    /// Forgets the searches of the indexes deleted by the finished `tasks`.
+    pub fn remove_deleted_indexes(&self, tasks: &[Task]) {
+        let mut buckets = self.buckets.lock().unwrap();
+        for task in tasks {
+            if let (TaskContent::IndexDeletion { index_uid }, Some(TaskEvent::Succeeded { .. })) =
+                (&task.content, task.events.last())
+            {
+                buckets.remove(index_uid.as_str());
+            }
+        }
+    }
+}
+
+fn bucket_start(at: OffsetDateTime) -> i64 {
+    at.unix_timestamp().div_euclid(BUCKET_DURATION) * BUCKET_DURATION
+}
+
+/// The queries differing only by their case or spacing are counted together.
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use time::macros::datetime;
+    use time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn count_the_searches_by_hour() {
+        let analytics = SearchAnalytics::default();
+        let at = datetime!(2022-06-01 10:30 UTC);
+        analytics.record_at(at, "movies", "Star  Wars", None, 12, 4);
+        analytics.record_at(at, "movies", "star wars", None, 10, 2);
+        analytics.record_at(at, "movies", "strar wars", None, 0, 3);
+        analytics.record_at(at, "movies", "", None, 100, 1);
+        analytics.record_at(at + Duration::hours(2), "movies", "dune", None, 0, 10);
+        analytics.record_at(at, "books", "dune", None, 3, 1);
+
+        let report = analytics.report("movies", at, at + Duration::minutes(10), 10);
+        assert_eq!(report.total_searches, 4);
+        assert_eq!(report.zero_result_searches, 1);
+        assert_eq!(report.average_processing_time_ms, Some(2.5));
+        assert_eq!(
+            report.top_queries,
+            vec![
+                QueryStat {
+                    query: "star wars".to_string(),
+                    count: 2
+                },
+                QueryStat {
+                    query: "strar wars".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(
+            report.zero_result_queries,
+            vec![QueryStat {
+                query: "strar wars".to_string(),
+                count: 1
+            }]
+        );
+
+        let report = analytics.report("movies", at, at + Duration::hours(3), 1);
+        assert_eq!(report.total_searches, 5);
+        assert_eq!(report.top_queries.len(), 1);
+        assert_eq!(report.zero_result_queries[0].query, "dune");
+
+        assert!(report.profiles.is_empty());
+
+        let report = analytics.report("shows", at, at + Duration::hours(3), 10);
+        assert_eq!(report.total_searches, 0);
+        assert_eq!(report.average_processing_time_ms, None);
+
+        // the searches older than the retention are forgotten.
+        analytics.record_at(at + Duration::days(8), "movies", "dune", None, 1, 1);
+        let report = analytics.report("movies", at, at + Duration::days(9), 10);
+        assert_eq!(report.total_searches, 1);
+    }
+
+    #[test]
+    fn count_the_searches_by_ranking_profile() {
+        let analytics = SearchAnalytics::default();
+        let at = datetime!(2022-06-01 10:30 UTC);
+        analytics.record_at(at, "movies", "dune", Some("baseline"), 3, 4);
+        analytics.record_at(at, "movies", "dnue", Some("baseline"), 0, 2);
+        analytics.record_at(at, "movies", "dnue", Some("typos"), 2, 5);
+        analytics.record_at(at, "movies", "dune", None, 3, 1);
+
+        let report = analytics.report("movies", at, at + Duration::minutes(10), 10);
+        assert_eq!(report.total_searches, 4);
+        assert_eq!(
+            report.profiles,
+            BTreeMap::from([
+                (
+                    "baseline".to_string(),
+                    ProfileStat {
+                        total_searches: 2,
+                        zero_result_searches: 1,
+                        average_processing_time_ms: 3.0,
+                    }
+                ),
+                (
+                    "typos".to_string(),
+                    ProfileStat {
+                        total_searches: 1,
+                        zero_result_searches: 0,
+                        average_processing_time_ms: 5.0,
+                    }
+                ),
+            ])
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use time::OffsetDateTime;
+
+use crate::index::SearchResult;
+use crate::tasks::task::{Task, TaskContent};
+
+/// A least recently used cache of the search results, to absorb the identical searches sent
+/// repeatedly, as with search-as-you-type.
+///
+/// The results of an index are removed as soon as a task touching it is finished, before the
+/// task is seen as finished. Each index
+/// also has a version, changed at each invalidation, so a search started before a task
+/// finished can't put its outdated result in the cache.
+///
+/// All the results are removed when an option changing the searches is changed at runtime, and
+/// a result is only kept until the next document of its index expires.
+pub struct SearchCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The cached results by index uid and normalized query.
+    entries: HashMap<(String, String), Entry>,
+    /// The keys of the entries by time of last use, the oldest first.
+    by_last_use: BTreeMap<u64, (String, String)>,
+    /// The time of the last invalidation of each index, its version.
+    invalidated_at: HashMap<String, u64>,
+    /// The time of the last invalidation of all the indexes.
+    cleared_at: u64,
+    clock: u64,
+}
+
+struct Entry {
+    result: SearchResult,
+    last_use: u64,
+    /// When the next document of the index expires, the result is outdated from then on.
+    expires_at: Option<OffsetDateTime>,
+}
+
+impl SearchCache {
+    /// Creates a cache holding at most `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// Returns the version of the index `uid`, to give back to [`SearchCache::insert`].
+    pub fn version(&self, uid: &str) -> u64 {
+        self.inner.lock().unwrap().version(uid)
+    }
+
+    pub fn get(&self, uid: &str, query: &str) -> Option<SearchResult> {
+        let mut inner = self.inner.lock().unwrap();
+        let key = (uid.to_string(), query.to_string());
+        let clock = inner.tick();
+        let entry = inner.entries.get_mut(&key)?;
+        if matches!(entry.expires_at, Some(expires_at) if expires_at <= OffsetDateTime::now_utc()) {
+            let last_use = entry.last_use;
+            inner.entries.remove(&key);
+            inner.by_last_use.remove(&last_use);
+            return None;
+        }
+        let last_use = std::mem::replace(&mut entry.last_use, clock);
+        let result = entry.result.clone();
+        inner.by_last_use.remove(&last_use);
+        inner.by_last_use.insert(clock, key);
+        Some(result)
+    }
+
+    /// Caches the `result` of the `query` until `expires_at`, unless the index has been
+    /// invalidated since `version` was read.
+    pub fn insert(
+        &self,
+        uid: &str,
+        version: u64,
+        query: String,
+        result: SearchResult,
+        expires_at: Option<OffsetDateTime>,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.version(uid) != version {
+            return;
+        }
+
+        let key = (uid.to_string(), query);
+        let last_use = inner.tick();
+        let entry = Entry {
+            result,
+            last_use,
+            expires_at,
+        };
+        if let Some(previous) = inner.entries.insert(key.clone(), entry) {
+            inner.by_last_use.remove(&previous.last_use);
+        }
+        inner.by_last_use.insert(last_use, key);
+
+        while inner.entries.len() > self.capacity {
+            let oldest = match inner.by_last_use.keys().next() {
+                Some(&oldest) => oldest,
+                None => break,
+            };
+            if let Some(key) = inner.by_last_use.remove(&oldest) {
+                inner.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Removes the results of the index `uid`.
+    pub fn invalidate(&self, uid: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        let now = inner.tick();
+        inner.invalidated_at.insert(uid.to_string(), now);
+        let Inner {
+            entries,
+            by_last_use,
+            ..
+        } = &mut *inner;
+        entries.retain(|(index, _), entry| {
+            let keep = index != uid;
+            if !keep {
+                by_last_use.remove(&entry.last_use);
+            }
+            keep
+        });
+    }
+
+    /// Removes the results of all the indexes.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.cleared_at = inner.tick();
+        inner.entries.clear();
+        inner.by_last_use.clear();
+    }
+
+    /// Removes the results of the indexes touched by the finished `tasks`.
+    pub fn invalidate_tasks(&self, tasks: &[Task]) {
+        for task in tasks.iter().filter(|task| task.is_finished()) {
+            match (&task.content, task.index_uid()) {
+                (_, Some(uid)) => self.invalidate(uid),
+                (TaskContent::IndexSwap { swaps }, None) => {
+                    for (lhs, rhs) in swaps {
+                        self.invalidate(lhs.as_str());
+                        self.invalidate(rhs.as_str());
+                    }
+                }
+                // the other tasks don't touch the indexes.
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Inner {
+    fn version(&self, uid: &str) -> u64 {
+        let invalidated_at = self.invalidated_at.get(uid).copied().unwrap_or_default();
+        invalidated_at.max(self.cleared_at)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::index::HitsInfo;
+
+    use super::*;
+
+    fn result(query: &str) -> SearchResult {
+        SearchResult {
+            hits: Vec::new(),
+            query: query.to_string(),
+            processing_time_ms: 0,
+            hits_info: HitsInfo::OffsetLimit {
+                limit: 20,
+                offset: 0,
+                estimated_total_hits: 0,
+            },
+            facet_distribution: None,
+            facet_stats: None,
+            degraded: false,
+            suggestions: None,
+            timings: Default::default(),
+        }
+    }
+
+    fn insert(cache: &SearchCache, uid: &str, query: &str) {
+        let version = cache.version(uid);
+        cache.insert(uid, version, query.to_string(), result(query), None);
+    }
+
+    #[test]
+    fn evict_the_least_recently_used_result() {
+        let cache = SearchCache::new(2);
+        insert(&cache, "movies", "a");
+        insert(&cache, "movies", "b");
+        assert!(cache.get("movies", "a").is_some());
+        insert(&cache, "movies", "c");
+
+        assert!(cache.get("movies", "a").is_some());
+        assert!(cache.get("movies", "b").is_none());
+        assert!(cache.get("movies", "c").is_some());
+    }
+
+    #[test]
+    fn invalidate_the_results_of_an_index() {
+        let cache = SearchCache::new(10);
+        insert(&cache, "movies", "a");
+        insert(&cache, "books", "a");
+        cache.invalidate("movies");
+
+        assert!(cache.get("movies", "a").is_none());
+        assert!(cache.get("books", "a").is_some());
+    }
+
+    #[test]
+    fn ignore_the_results_computed_before_an_invalidation() {
+        let cache = SearchCache::new(10);
+        let version = cache.version("movies");
+        cache.invalidate("movies");
+        cache.insert("movies", version, "a".to_string(), result("a"), None);
+        assert!(cache.get("movies", "a").is_none());
+    }
+
+    #[test]
+    fn clear_the_results_of_all_the_indexes() {
+        let cache = SearchCache::new(10);
+        let version = cache.version("books");
+        insert(&cache, "movies", "a");
+        cache.clear();
+
+        assert!(cache.get("movies", "a").is_none());
+        cache.insert("books", version, "a".to_string(), result("a"), None);
+        assert!(cache.get("books", "a").is_none());
+    }
+
+    #[test]
+    fn forget_the_results_once_a_document_expired() {
+        let cache = SearchCache::new(10);
+        let now = OffsetDateTime::now_utc();
+        let version = cache.version("movies");
+        cache.insert(
+            "movies",
+            version,
+            "a".to_string(),
+            result("a"),
+            Some(now - time::Duration::seconds(1)),
+        );
+        cache.insert(
+            "movies",
+            version,
+            "b".to_string(),
+            result("b"),
+            Some(now + time::Duration::hours(1)),
+        );
+
+        assert!(cache.get("movies", "a").is_none());
+        assert!(cache.get("movies", "b").is_some());
+    }
+}
@@ -29,6 +29,20 @@ pub enum IndexResolverError {
     Milli(#[from] milli::Error),
     #[error("{0}")]
     BadlyFormatted(#[from] IndexUidFormatError),
+    #[error("Alias `{0}` not found.")]
+    AliasNotFound(String),
+    #[error("`{0}` is not a valid alias. An alias can be an integer or a string containing only alphanumeric characters, hyphens (-) and underscores (_).")]
+    InvalidAlias(String),
+    #[error("An index named `{0}` already exists, it can't be used as an alias.")]
+    AliasConflict(String),
+    #[error("`{0}` is an alias, it can't be used as the uid of an index.")]
+    NameUsedByAlias(String),
+    #[error("Index template `{0}` not found.")]
+    TemplateNotFound(String),
+    #[error("`{0}` is not a valid index template name. A template name can be an integer or a string containing only alphanumeric characters, hyphens (-) and underscores (_).")]
+    InvalidTemplateName(String),
+    #[error("`{0}` is not a valid index template pattern. A pattern can only contain alphanumeric characters, hyphens (-), underscores (_) and wildcards (*).")]
+    InvalidTemplatePattern(String),
 }
 
 impl<T> From<MpscSendError<T>> for IndexResolverError
@@ -66,6 +80,14 @@ impl ErrorCode for IndexResolverError {
             IndexResolverError::UuidAlreadyExists(_) => Code::CreateIndex,
             IndexResolverError::Milli(e) => MilliError(e).error_code(),
             IndexResolverError::BadlyFormatted(_) => Code::InvalidIndexUid,
+            IndexResolverError::AliasNotFound(_) => Code::AliasNotFound,
+            IndexResolverError::InvalidAlias(_) | IndexResolverError::AliasConflict(_) => {
+                Code::InvalidAlias
+            }
+            IndexResolverError::NameUsedByAlias(_) => Code::IndexAlreadyExists,
+            IndexResolverError::TemplateNotFound(_) => Code::IndexTemplateNotFound,
+            IndexResolverError::InvalidTemplateName(_)
+            | IndexResolverError::InvalidTemplatePattern(_) => Code::InvalidIndexTemplate,
         }
     }
 }
@@ -1,9 +1,7 @@
 use std::collections::HashMap;
-use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use milli::update::IndexerConfig;
 use tokio::fs;
 use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
@@ -11,7 +9,7 @@ use uuid::Uuid;
 
 use super::error::{IndexResolverError, Result};
 use crate::index::Index;
-use crate::options::IndexerOpts;
+use crate::options::IndexerConfigHandle;
 
 type AsyncMap<K, V> = Arc<RwLock<HashMap<K, V>>>;
 
@@ -19,6 +17,8 @@ type AsyncMap<K, V> = Arc<RwLock<HashMap<K, V>>>;
 #[cfg_attr(test, mockall::automock)]
 pub trait IndexStore {
     async fn create(&self, uuid: Uuid) -> Result<Index>;
+    /// Creates the index `uuid` with a copy of the documents and settings of `source`.
+    async fn create_copy(&self, source: Index, uuid: Uuid) -> Result<Index>;
     async fn get(&self, uuid: Uuid) -> Result<Option<Index>>;
     async fn delete(&self, uuid: Uuid) -> Result<Option<Index>>;
 }
@@ -27,16 +27,15 @@ pub struct MapIndexStore {
     index_store: AsyncMap<Uuid, Index>,
     path: PathBuf,
     index_size: usize,
-    indexer_config: Arc<IndexerConfig>,
+    indexer_config: IndexerConfigHandle,
 }
 
 impl MapIndexStore {
     pub fn new(
         path: impl AsRef<Path>,
         index_size: usize,
-        indexer_opts: &IndexerOpts,
+        indexer_config: IndexerConfigHandle,
     ) -> anyhow::Result<Self> {
-        let indexer_config = Arc::new(IndexerConfig::try_from(indexer_opts)?);
         let path = path.as_ref().join("indexes/");
         let index_store = Arc::new(RwLock::new(HashMap::new()));
         Ok(Self {
@@ -76,6 +75,29 @@ impl IndexStore for MapIndexStore {
         Ok(index)
     }
 
+    async fn create_copy(&self, source: Index, uuid: Uuid) -> Result<Index> {
+        let mut lock = self.index_store.write().await;
+
+        let path = self.path.join(format!("{}", uuid));
+        if lock.contains_key(&uuid) || path.exists() {
+            return Err(IndexResolverError::UuidAlreadyExists(uuid));
+        }
+
+        let index_size = self.index_size;
+        let update_handler = self.indexer_config.clone();
+        let index = spawn_blocking(move || -> Result<Index> {
+            std::fs::create_dir_all(&path)?;
+            source.copy_to(&path)?;
+            let index = Index::open(path, index_size, uuid, update_handler)?;
+            Ok(index)
+        })
+        .await??;
+
+        lock.insert(uuid, index.clone());
+
+        Ok(index)
+    }
+
     async fn get(&self, uuid: Uuid) -> Result<Option<Index>> {
         let guard = self.index_store.read().await;
         match guard.get(&uuid) {
@@ -4,12 +4,13 @@ use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use milli::heed::types::{SerdeBincode, Str};
+use milli::heed::types::{SerdeBincode, SerdeJson, Str};
 use milli::heed::{CompactionOption, Database, Env};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::error::{IndexResolverError, Result};
+use super::template::IndexTemplate;
 use crate::tasks::task::TaskId;
 use crate::EnvSizer;
 
@@ -19,7 +20,15 @@ pub struct DumpEntry {
     pub index_meta: IndexMeta,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct AliasDumpEntry {
+    pub alias: String,
+    pub index_uid: String,
+}
+
 const UUIDS_DB_PATH: &str = "index_uuids";
+const ALIASES_DUMP_FILE: &str = "aliases.jsonl";
+const TEMPLATES_DUMP_FILE: &str = "templates.jsonl";
 
 #[async_trait::async_trait]
 #[cfg_attr(test, mockall::automock)]
@@ -30,6 +39,18 @@ pub trait IndexMetaStore: Sized {
     async fn delete(&self, uid: String) -> Result<Option<IndexMeta>>;
     async fn list(&self) -> Result<Vec<(String, IndexMeta)>>;
     async fn insert(&self, name: String, meta: IndexMeta) -> Result<()>;
+    /// Exchanges the indexes of each pair, in a single transaction.
+    async fn swap(&self, swaps: Vec<(String, String)>) -> Result<()>;
+    async fn get_alias(&self, alias: String) -> Result<Option<String>>;
+    async fn list_aliases(&self) -> Result<Vec<(String, String)>>;
+    /// Points `alias` to the index `index_uid`, which must exist.
+    async fn put_alias(&self, alias: String, index_uid: String) -> Result<()>;
+    async fn delete_alias(&self, alias: String) -> Result<Option<String>>;
+    async fn get_template(&self, name: String) -> Result<Option<IndexTemplate>>;
+    /// Returns the templates, ordered by name.
+    async fn list_templates(&self) -> Result<Vec<IndexTemplate>>;
+    async fn put_template(&self, template: IndexTemplate) -> Result<()>;
+    async fn delete_template(&self, name: String) -> Result<Option<IndexTemplate>>;
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>>;
     async fn get_size(&self) -> Result<u64>;
     async fn dump(&self, path: PathBuf) -> Result<()>;
@@ -45,6 +66,11 @@ pub struct IndexMeta {
 pub struct HeedMetaStore {
     env: Arc<Env>,
     db: Database<Str, SerdeBincode<IndexMeta>>,
+    /// Maps the aliases to the uid of the index they resolve to.
+    aliases: Database<Str, Str>,
+    /// The index templates, by name. Their settings are stored as JSON, since the unset fields
+    /// are skipped when serializing them.
+    templates: Database<Str, SerdeJson<IndexTemplate>>,
 }
 
 impl Drop for HeedMetaStore {
@@ -58,7 +84,14 @@ impl Drop for HeedMetaStore {
 impl HeedMetaStore {
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let db = env.create_database(Some("uuids"))?;
-        Ok(Self { env, db })
+        let aliases = env.create_database(Some("aliases"))?;
+        let templates = env.create_database(Some("index-templates"))?;
+        Ok(Self {
+            env,
+            db,
+            aliases,
+            templates,
+        })
     }
 
     fn get(&self, name: &str) -> Result<Option<IndexMeta>> {
@@ -105,12 +138,107 @@ impl HeedMetaStore {
         if db.get(&txn, &name)?.is_some() {
             return Err(IndexResolverError::IndexAlreadyExists(name));
         }
+        if self.aliases.get(&txn, &name)?.is_some() {
+            return Err(IndexResolverError::NameUsedByAlias(name));
+        }
 
         db.put(&mut txn, &name, &meta)?;
         txn.commit()?;
         Ok(())
     }
 
+    fn swap(&self, swaps: Vec<(String, String)>) -> Result<()> {
+        let env = self.env.clone();
+        let db = self.db;
+        let mut txn = env.write_txn()?;
+
+        for (left, right) in swaps {
+            let left_meta = db
+                .get(&txn, &left)?
+                .ok_or_else(|| IndexResolverError::UnexistingIndex(left.clone()))?;
+            let right_meta = db
+                .get(&txn, &right)?
+                .ok_or_else(|| IndexResolverError::UnexistingIndex(right.clone()))?;
+            db.put(&mut txn, &left, &right_meta)?;
+            db.put(&mut txn, &right, &left_meta)?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn get_alias(&self, alias: &str) -> Result<Option<String>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.aliases.get(&txn, alias)?.map(String::from))
+    }
+
+    fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+        let txn = self.env.read_txn()?;
+        let mut entries = Vec::new();
+        for entry in self.aliases.iter(&txn)? {
+            let (alias, index_uid) = entry?;
+            entries.push((alias.to_string(), index_uid.to_string()));
+        }
+        Ok(entries)
+    }
+
+    fn put_alias(&self, alias: String, index_uid: String) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+
+        if self.db.get(&txn, &alias)?.is_some() {
+            return Err(IndexResolverError::AliasConflict(alias));
+        }
+        if self.db.get(&txn, &index_uid)?.is_none() {
+            return Err(IndexResolverError::UnexistingIndex(index_uid));
+        }
+
+        self.aliases.put(&mut txn, &alias, &index_uid)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn delete_alias(&self, alias: String) -> Result<Option<String>> {
+        let mut txn = self.env.write_txn()?;
+        let index_uid = self.aliases.get(&txn, &alias)?.map(String::from);
+        if index_uid.is_some() {
+            self.aliases.delete(&mut txn, &alias)?;
+            txn.commit()?;
+        }
+        Ok(index_uid)
+    }
+
+    fn get_template(&self, name: &str) -> Result<Option<IndexTemplate>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.templates.get(&txn, name)?)
+    }
+
+    fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+        let txn = self.env.read_txn()?;
+        let mut templates = Vec::new();
+        for entry in self.templates.iter(&txn)? {
+            let (_, template) = entry?;
+            templates.push(template);
+        }
+        Ok(templates)
+    }
+
+    fn put_template(&self, template: IndexTemplate) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        self.templates.put(&mut txn, &template.name, &template)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn delete_template(&self, name: String) -> Result<Option<IndexTemplate>> {
+        let mut txn = self.env.write_txn()?;
+        let template = self.templates.get(&txn, &name)?;
+        if template.is_some() {
+            self.templates.delete(&mut txn, &name)?;
+            txn.commit()?;
+        }
+        Ok(template)
+    }
+
     fn snapshot(&self, mut path: PathBuf) -> Result<HashSet<Uuid>> {
         // Write transaction to acquire a lock on the database.
         let txn = self.env.write_txn()?;
@@ -150,9 +278,49 @@ impl HeedMetaStore {
             dump_file.write_all(b"\n").unwrap();
         }
 
+        let mut aliases_file = File::create(dump_path.join(ALIASES_DUMP_FILE))?;
+        for entry in self.aliases.iter(&txn)? {
+            let (alias, index_uid) = entry?;
+            let entry = AliasDumpEntry {
+                alias: alias.to_string(),
+                index_uid: index_uid.to_string(),
+            };
+            serde_json::to_writer(&mut aliases_file, &entry)?;
+            aliases_file.write_all(b"\n")?;
+        }
+
+        let mut templates_file = File::create(dump_path.join(TEMPLATES_DUMP_FILE))?;
+        for entry in self.templates.iter(&txn)? {
+            let (_, template) = entry?;
+            serde_json::to_writer(&mut templates_file, &template)?;
+            templates_file.write_all(b"\n")?;
+        }
+
         Ok(())
     }
 
+    /// Returns the indexes listed in the dump at `src`.
+    pub fn dumped_indexes(src: impl AsRef<Path>) -> Result<Vec<DumpEntry>> {
+        let src_indexes = src.as_ref().join(UUIDS_DB_PATH).join("data.jsonl");
+        let indexes = BufReader::new(File::open(&src_indexes)?);
+
+        let mut entries = Vec::new();
+        for line in indexes.lines() {
+            entries.push(serde_json::from_str(&line?)?);
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the uuids of the indexes listed in the dump at `src`.
+    pub fn dumped_uuids(src: impl AsRef<Path>) -> Result<HashSet<Uuid>> {
+        let entries = Self::dumped_indexes(src)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| entry.index_meta.uuid)
+            .collect())
+    }
+
     pub fn load_dump(src: impl AsRef<Path>, env: Arc<milli::heed::Env>) -> Result<()> {
         let src_indexes = src.as_ref().join(UUIDS_DB_PATH).join("data.jsonl");
         let indexes = File::open(&src_indexes)?;
@@ -174,6 +342,22 @@ impl HeedMetaStore {
 
             line.clear();
         }
+
+        // The dumps created before the aliases and the templates don't contain any.
+        let src_aliases = src.as_ref().join(UUIDS_DB_PATH).join(ALIASES_DUMP_FILE);
+        if src_aliases.exists() {
+            for line in BufReader::new(File::open(&src_aliases)?).lines() {
+                let AliasDumpEntry { alias, index_uid } = serde_json::from_str(&line?)?;
+                db.aliases.put(&mut txn, &alias, &index_uid)?;
+            }
+        }
+        let src_templates = src.as_ref().join(UUIDS_DB_PATH).join(TEMPLATES_DUMP_FILE);
+        if src_templates.exists() {
+            for line in BufReader::new(File::open(&src_templates)?).lines() {
+                let template: IndexTemplate = serde_json::from_str(&line?)?;
+                db.templates.put(&mut txn, &template.name, &template)?;
+            }
+        }
         txn.commit()?;
 
         Ok(())
@@ -202,6 +386,51 @@ impl IndexMetaStore for HeedMetaStore {
         tokio::task::spawn_blocking(move || this.insert(name, meta)).await?
     }
 
+    async fn swap(&self, swaps: Vec<(String, String)>) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.swap(swaps)).await?
+    }
+
+    async fn get_alias(&self, alias: String) -> Result<Option<String>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_alias(&alias)).await?
+    }
+
+    async fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.list_aliases()).await?
+    }
+
+    async fn put_alias(&self, alias: String, index_uid: String) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.put_alias(alias, index_uid)).await?
+    }
+
+    async fn delete_alias(&self, alias: String) -> Result<Option<String>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.delete_alias(alias)).await?
+    }
+
+    async fn get_template(&self, name: String) -> Result<Option<IndexTemplate>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_template(&name)).await?
+    }
+
+    async fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.list_templates()).await?
+    }
+
+    async fn put_template(&self, template: IndexTemplate) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.put_template(template)).await?
+    }
+
+    async fn delete_template(&self, name: String) -> Result<Option<IndexTemplate>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.delete_template(name)).await?
+    }
+
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>> {
         let this = self.clone();
         tokio::task::spawn_blocking(move || this.snapshot(path)).await?
@@ -1,9 +1,12 @@
 pub mod error;
 pub mod index_store;
 pub mod meta_store;
+pub mod template;
 
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use error::{IndexResolverError, Result};
@@ -17,12 +20,15 @@ use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
-use crate::index::{error::Result as IndexResult, Index};
-use crate::options::IndexerOpts;
+use crate::index::error::{IndexError, Result as IndexResult};
+use crate::index::Index;
+use crate::options::{IndexerConfigHandle, IndexerOpts};
+use crate::tasks::batch::Cancelation;
 use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult};
 use crate::update_file_store::UpdateFileStore;
 
 use self::meta_store::IndexMeta;
+use self::template::IndexTemplate;
 
 pub type HardStateIndexResolver = IndexResolver<HeedMetaStore, MapIndexStore>;
 
@@ -35,12 +41,12 @@ pub use test::MockIndexResolver as IndexResolver;
 pub fn create_index_resolver(
     path: impl AsRef<Path>,
     index_size: usize,
-    indexer_opts: &IndexerOpts,
+    indexer_config: IndexerConfigHandle,
     meta_env: Arc<milli::heed::Env>,
     file_store: UpdateFileStore,
 ) -> anyhow::Result<HardStateIndexResolver> {
     let uuid_store = HeedMetaStore::new(meta_env)?;
-    let index_store = MapIndexStore::new(&path, index_size, indexer_opts)?;
+    let index_store = MapIndexStore::new(&path, index_size, indexer_config)?;
     Ok(IndexResolver::new(uuid_store, index_store, file_store))
 }
 
@@ -51,6 +57,8 @@ mod real {
         pub(super) index_uuid_store: U,
         pub(super) index_store: I,
         pub(super) file_store: UpdateFileStore,
+        /// Whether the update files are kept once processed, for the replication.
+        pub(super) keep_update_files: bool,
     }
 
     impl IndexResolver<HeedMetaStore, MapIndexStore> {
@@ -83,10 +91,32 @@ mod real {
                 index_uuid_store,
                 index_store,
                 file_store,
+                keep_update_files: false,
             }
         }
 
-        pub async fn process_document_addition_batch(&self, tasks: &mut [Task]) {
+        /// Keeps the update files of the document additions once they are processed, they are
+        /// deleted by the replication log service instead.
+        pub fn set_keep_update_files(&mut self, keep: bool) {
+            self.keep_update_files = keep;
+        }
+
+        pub fn keeps_update_files(&self) -> bool {
+            self.keep_update_files
+        }
+
+        pub async fn process_document_addition_batch(
+            &self,
+            tasks: &mut [Task],
+            cancelation: &Cancelation,
+        ) {
+            if let Some(event) = cancelation.event() {
+                for task in tasks.iter_mut() {
+                    task.events.push(event.clone());
+                }
+                return;
+            }
+
             fn get_content_uuid(task: &Task) -> Uuid {
                 match task {
                     Task {
@@ -105,6 +135,7 @@ mod real {
                     content:
                         TaskContent::DocumentAddition {
                             merge_strategy,
+                            merge_patch,
                             primary_key,
                             allow_index_creation,
                             index_uid,
@@ -114,6 +145,7 @@ mod real {
                 }) => {
                     let primary_key = primary_key.clone();
                     let method = *merge_strategy;
+                    let merge_patch = *merge_patch;
 
                     let index = if *allow_index_creation {
                         self.get_or_create_index(index_uid.clone(), *id).await
@@ -140,23 +172,33 @@ mod real {
                     };
 
                     let file_store = self.file_store.clone();
+                    let span = tracing::Span::current();
+                    let should_abort = cancelation.clone();
                     let result = spawn_blocking(move || {
-                        index.update_documents(
-                            method,
-                            primary_key,
-                            file_store,
-                            content_uuids.into_iter(),
-                        )
+                        span.in_scope(|| {
+                            index.update_documents(
+                                method,
+                                merge_patch,
+                                primary_key,
+                                file_store,
+                                content_uuids.into_iter(),
+                                || should_abort.is_canceled(),
+                            )
+                        })
                     })
                     .await;
 
                     let event = match result {
-                        Ok(Ok(result)) => TaskEvent::Succeeded {
+                        Ok(Ok((result, rejected_documents))) => TaskEvent::Succeeded {
                             timestamp: OffsetDateTime::now_utc(),
                             result: TaskResult::DocumentAddition {
                                 indexed_documents: result.indexed_documents,
+                                rejected_documents,
                             },
                         },
+                        Ok(Err(IndexError::Aborted)) => cancelation
+                            .event()
+                            .expect("the document addition is only aborted by a cancelation"),
                         Ok(Err(e)) => TaskEvent::Failed {
                             timestamp: OffsetDateTime::now_utc(),
                             error: e.into(),
@@ -180,7 +222,11 @@ mod real {
             Ok(())
         }
 
-        async fn process_task_inner(&self, task: &Task) -> Result<TaskResult> {
+        async fn process_task_inner(
+            &self,
+            task: &Task,
+            cancelation: &Cancelation,
+        ) -> Result<TaskResult> {
             match &task.content {
                 TaskContent::DocumentAddition { .. } => {
                     panic!("updates should be handled by batch")
@@ -198,6 +244,49 @@ mod real {
 
                     Ok(TaskResult::DocumentDeletion { deleted_documents })
                 }
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Filter(filter),
+                    index_uid,
+                } => {
+                    let filter = filter.clone();
+                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+
+                    let DocumentDeletionResult {
+                        deleted_documents, ..
+                    } = spawn_blocking(move || index.delete_documents_by_filter(&filter)).await??;
+
+                    Ok(TaskResult::DocumentDeletion { deleted_documents })
+                }
+                TaskContent::DocumentEdition {
+                    index_uid,
+                    filter,
+                    function,
+                } => {
+                    let filter = filter.clone();
+                    let function = function.clone();
+                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+
+                    let indexed_documents =
+                        spawn_blocking(move || index.edit_documents(filter.as_ref(), &function))
+                            .await??;
+
+                    Ok(TaskResult::DocumentAddition {
+                        indexed_documents,
+                        rejected_documents: Vec::new(),
+                    })
+                }
+                TaskContent::DocumentRestoration { index_uid, ids } => {
+                    let ids = ids.clone();
+                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+
+                    let indexed_documents =
+                        spawn_blocking(move || index.restore_documents(ids.as_deref())).await??;
+
+                    Ok(TaskResult::DocumentAddition {
+                        indexed_documents,
+                        rejected_documents: Vec::new(),
+                    })
+                }
                 TaskContent::DocumentDeletion {
                     deletion: DocumentDeletion::Clear,
                     index_uid,
@@ -225,7 +314,11 @@ mod real {
                     };
 
                     let settings = settings.clone();
-                    spawn_blocking(move || index.update_settings(&settings.check())).await??;
+                    let should_abort = cancelation.clone();
+                    spawn_blocking(move || {
+                        index.update_settings(&settings.check(), || should_abort.is_canceled())
+                    })
+                    .await??;
 
                     Ok(TaskResult::Other)
                 }
@@ -265,13 +358,51 @@ mod real {
 
                     Ok(TaskResult::Other)
                 }
+                TaskContent::IndexSwap { swaps } => {
+                    let swaps = swaps
+                        .iter()
+                        .map(|(left, right)| (left.to_string(), right.to_string()))
+                        .collect();
+                    self.index_uuid_store.swap(swaps).await?;
+
+                    Ok(TaskResult::Other)
+                }
+                TaskContent::IndexClone {
+                    index_uid,
+                    destination,
+                    settings_only,
+                } => {
+                    let source = self.get_index(index_uid.clone().into_inner()).await?;
+                    if *settings_only {
+                        let index = self.create_index(destination.clone(), task.id).await?;
+                        spawn_blocking(move || -> IndexResult<()> {
+                            index.update_settings(&source.settings()?, || false)
+                        })
+                        .await??;
+                    } else {
+                        self.copy_index(source, destination.clone(), task.id)
+                            .await?;
+                    }
+
+                    Ok(TaskResult::Other)
+                }
                 _ => unreachable!("Invalid task for index resolver"),
             }
         }
 
-        pub async fn process_task(&self, task: &mut Task) {
-            match self.process_task_inner(task).await {
+        pub async fn process_task(&self, task: &mut Task, cancelation: &Cancelation) {
+            if let Some(event) = cancelation.event() {
+                task.events.push(event);
+                return;
+            }
+
+            match self.process_task_inner(task, cancelation).await {
                 Ok(res) => task.events.push(TaskEvent::succeeded(res)),
+                Err(IndexResolverError::IndexError(IndexError::Aborted)) => task.events.push(
+                    cancelation
+                        .event()
+                        .expect("the task is only aborted by a cancelation"),
+                ),
                 Err(e) => task.events.push(TaskEvent::failed(e)),
             }
         }
@@ -284,23 +415,43 @@ mod real {
             Ok(())
         }
 
+        /// Dumps the indexes among `uids`, along with the list of every index. The other indexes
+        /// are expected to be found in a previous dump.
+        pub async fn dump_indexes(
+            &self,
+            path: impl AsRef<Path>,
+            uids: &HashSet<String>,
+        ) -> Result<()> {
+            for (uid, index) in self.list().await? {
+                if uids.contains(&uid) {
+                    index.dump(&path)?;
+                }
+            }
+            self.index_uuid_store.dump(path.as_ref().to_owned()).await?;
+            Ok(())
+        }
+
         async fn create_index(&self, uid: IndexUid, creation_task_id: TaskId) -> Result<Index> {
             match self.index_uuid_store.get(uid.into_inner()).await? {
                 (uid, Some(_)) => Err(IndexResolverError::IndexAlreadyExists(uid)),
                 (uid, None) => {
                     let uuid = Uuid::new_v4();
                     let index = self.index_store.create(uuid).await?;
-                    match self
-                        .index_uuid_store
-                        .insert(
-                            uid,
-                            IndexMeta {
-                                uuid,
-                                creation_task_id,
-                            },
-                        )
-                        .await
-                    {
+                    let result = match self.apply_template(&uid, index.clone()).await {
+                        Ok(()) => {
+                            self.index_uuid_store
+                                .insert(
+                                    uid,
+                                    IndexMeta {
+                                        uuid,
+                                        creation_task_id,
+                                    },
+                                )
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    match result {
                         Err(e) => {
                             match self.index_store.delete(uuid).await {
                                 Ok(Some(index)) => {
@@ -317,6 +468,53 @@ mod real {
             }
         }
 
+        /// Creates the index `uid` with a copy of the documents and settings of `source`.
+        async fn copy_index(
+            &self,
+            source: Index,
+            uid: IndexUid,
+            creation_task_id: TaskId,
+        ) -> Result<Index> {
+            match self.index_uuid_store.get(uid.into_inner()).await? {
+                (uid, Some(_)) => Err(IndexResolverError::IndexAlreadyExists(uid)),
+                (uid, None) => {
+                    let uuid = Uuid::new_v4();
+                    let index = self.index_store.create_copy(source, uuid).await?;
+                    let meta = IndexMeta {
+                        uuid,
+                        creation_task_id,
+                    };
+                    match self.index_uuid_store.insert(uid, meta).await {
+                        Err(e) => {
+                            match self.index_store.delete(uuid).await {
+                                Ok(Some(index)) => index.close(),
+                                Ok(None) => (),
+                                Err(e) => log::error!("Error while deleting index: {:?}", e),
+                            }
+                            Err(e)
+                        }
+                        Ok(()) => Ok(index),
+                    }
+                }
+            }
+        }
+
+        /// Applies the settings of the template matching `uid`, if any, to the newly created
+        /// `index`.
+        async fn apply_template(&self, uid: &str, index: Index) -> Result<()> {
+            let templates = self.index_uuid_store.list_templates().await?;
+            if let Some(template) = template::find_template(&templates, uid) {
+                log::info!(
+                    "Applying the template `{}` to the index `{}`.",
+                    template.name,
+                    uid
+                );
+                let settings = template.settings.clone().check();
+                spawn_blocking(move || index.update_settings(&settings, || false)).await??;
+            }
+            Ok(())
+        }
+
         /// Get or create an index with name `uid`.
         pub async fn get_or_create_index(&self, uid: IndexUid, task_id: TaskId) -> Result<Index> {
             match self.create_index(uid, task_id).await {
@@ -372,6 +570,71 @@ mod real {
             }
         }
 
+        /// Returns the uid of the index the alias `uid` resolves to, or `uid` itself if it isn't
+        /// an alias.
+        pub async fn resolve_alias(&self, uid: String) -> Result<String> {
+            match self.index_uuid_store.get_alias(uid.clone()).await? {
+                Some(index_uid) => Ok(index_uid),
+                None => Ok(uid),
+            }
+        }
+
+        pub async fn get_alias(&self, alias: String) -> Result<String> {
+            self.index_uuid_store
+                .get_alias(alias.clone())
+                .await?
+                .ok_or(IndexResolverError::AliasNotFound(alias))
+        }
+
+        pub async fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+            self.index_uuid_store.list_aliases().await
+        }
+
+        /// Points `alias` to the index `index_uid`. An existing alias is retargeted atomically.
+        pub async fn put_alias(&self, alias: String, index_uid: String) -> Result<()> {
+            if IndexUid::from_str(&alias).is_err() {
+                return Err(IndexResolverError::InvalidAlias(alias));
+            }
+            self.index_uuid_store.put_alias(alias, index_uid).await
+        }
+
+        pub async fn delete_alias(&self, alias: String) -> Result<()> {
+            match self.index_uuid_store.delete_alias(alias.clone()).await? {
+                Some(_) => Ok(()),
+                None => Err(IndexResolverError::AliasNotFound(alias)),
+            }
+        }
+
+        pub async fn get_template(&self, name: String) -> Result<IndexTemplate> {
+            self.index_uuid_store
+                .get_template(name.clone())
+                .await?
+                .ok_or(IndexResolverError::TemplateNotFound(name))
+        }
+
+        pub async fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+            self.index_uuid_store.list_templates().await
+        }
+
+        /// Creates or replaces the template `template.name`. It only applies to the indexes
+        /// created afterward.
+        pub async fn put_template(&self, template: IndexTemplate) -> Result<()> {
+            if IndexUid::from_str(&template.name).is_err() {
+                return Err(IndexResolverError::InvalidTemplateName(template.name));
+            }
+            if !template::is_valid_pattern(&template.pattern) {
+                return Err(IndexResolverError::InvalidTemplatePattern(template.pattern));
+            }
+            self.index_uuid_store.put_template(template).await
+        }
+
+        pub async fn delete_template(&self, name: String) -> Result<()> {
+            match self.index_uuid_store.delete_template(name.clone()).await? {
+                Some(_) => Ok(()),
+                None => Err(IndexResolverError::TemplateNotFound(name)),
+            }
+        }
+
         pub async fn get_index_creation_task_id(&self, index_uid: String) -> Result<TaskId> {
             let (uid, meta) = self.index_uuid_store.get(index_uid).await?;
             meta.map(
@@ -391,8 +654,9 @@ mod test {
     use super::index_store::MockIndexStore;
     use super::meta_store::MockIndexMetaStore;
     use super::*;
+    use crate::tasks::task::TaskPriority;
 
-    use futures::future::ok;
+    use futures::future::{err, ok};
     use milli::FieldDistribution;
     use nelson::Mocker;
 
@@ -423,6 +687,7 @@ mod test {
                 index_uuid_store,
                 index_store,
                 file_store,
+                keep_update_files: false,
             })
         }
 
@@ -430,18 +695,38 @@ mod test {
             Self::Mock(mocker)
         }
 
-        pub async fn process_document_addition_batch(&self, tasks: &mut [Task]) {
+        pub fn set_keep_update_files(&mut self, keep: bool) {
+            match self {
+                IndexResolver::Real(r) => r.set_keep_update_files(keep),
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn keeps_update_files(&self) -> bool {
             match self {
-                IndexResolver::Real(r) => r.process_document_addition_batch(tasks).await,
+                IndexResolver::Real(r) => r.keeps_update_files(),
+                IndexResolver::Mock(_) => false,
+            }
+        }
+
+        pub async fn process_document_addition_batch(
+            &self,
+            tasks: &mut [Task],
+            cancelation: &Cancelation,
+        ) {
+            match self {
+                IndexResolver::Real(r) => {
+                    r.process_document_addition_batch(tasks, cancelation).await
+                }
                 IndexResolver::Mock(m) => unsafe {
                     m.get("process_document_addition_batch").call(tasks)
                 },
             }
         }
 
-        pub async fn process_task(&self, task: &mut Task) {
+        pub async fn process_task(&self, task: &mut Task, cancelation: &Cancelation) {
             match self {
-                IndexResolver::Real(r) => r.process_task(task).await,
+                IndexResolver::Real(r) => r.process_task(task, cancelation).await,
                 IndexResolver::Mock(m) => unsafe { m.get("process_task").call(task) },
             }
         }
@@ -453,6 +738,17 @@ mod test {
             }
         }
 
+        pub async fn dump_indexes(
+            &self,
+            path: impl AsRef<Path>,
+            uids: &HashSet<String>,
+        ) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.dump_indexes(path, uids).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         /// Get or create an index with name `uid`.
         pub async fn get_or_create_index(&self, uid: IndexUid, task_id: TaskId) -> Result<Index> {
             match self {
@@ -482,6 +778,69 @@ mod test {
             }
         }
 
+        pub async fn resolve_alias(&self, uid: String) -> Result<String> {
+            match self {
+                IndexResolver::Real(r) => r.resolve_alias(uid).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn get_alias(&self, alias: String) -> Result<String> {
+            match self {
+                IndexResolver::Real(r) => r.get_alias(alias).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+            match self {
+                IndexResolver::Real(r) => r.list_aliases().await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn put_alias(&self, alias: String, index_uid: String) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.put_alias(alias, index_uid).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn delete_alias(&self, alias: String) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.delete_alias(alias).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn get_template(&self, name: String) -> Result<IndexTemplate> {
+            match self {
+                IndexResolver::Real(r) => r.get_template(name).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+            match self {
+                IndexResolver::Real(r) => r.list_templates().await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn put_template(&self, template: IndexTemplate) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.put_template(template).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn delete_template(&self, name: String) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.delete_template(name).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn get_index_creation_task_id(&self, index_uid: String) -> Result<TaskId> {
             match self {
                 IndexResolver::Real(r) => r.get_index_creation_task_id(index_uid).await,
@@ -520,9 +879,15 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Cancelation::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Failed { .. }));
     }
@@ -549,6 +914,7 @@ mod test {
                         number_of_documents: 10,
                         is_indexing: None,
                         field_distribution: FieldDistribution::default(),
+                        details: None,
                     })
                 });
             Box::pin(ok(Some(Index::mock(mocker))))
@@ -565,9 +931,15 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Cancelation::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
@@ -601,6 +973,7 @@ mod test {
                         number_of_documents: 10,
                         is_indexing: None,
                         field_distribution: FieldDistribution::default(),
+                        details: None,
                     })
                 });
             Box::pin(ok(Some(Index::mock(mocker))))
@@ -618,9 +991,15 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Cancelation::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
@@ -667,10 +1046,96 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Cancelation::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
+
+    #[actix_rt::test]
+    async fn test_swap_unknown_index() {
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store
+            .expect_swap()
+            .once()
+            .returning(|_| Box::pin(err(IndexResolverError::UnexistingIndex("test".to_string()))));
+
+        let index_store = MockIndexStore::new();
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        let mut task = Task {
+            id: 1,
+            content: TaskContent::IndexSwap {
+                swaps: vec![(
+                    IndexUid::new_unchecked("test"),
+                    IndexUid::new_unchecked("other"),
+                )],
+            },
+            events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
+        };
+
+        index_resolver
+            .process_task(&mut task, &Cancelation::default())
+            .await;
+
+        assert!(matches!(task.events[0], TaskEvent::Failed { .. }));
+    }
+
+    #[actix_rt::test]
+    async fn test_canceled_batch() {
+        // the stores are never reached, the batch stops before its first task.
+        let meta_store = MockIndexMetaStore::new();
+        let index_store = MockIndexStore::new();
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        let cancelation = Cancelation::default();
+        cancelation.cancel(3);
+        cancelation.cancel(4);
+
+        let mut tasks = vec![Task {
+            id: 1,
+            content: TaskContent::DocumentAddition {
+                content_uuid: Uuid::new_v4(),
+                merge_strategy: milli::update::IndexDocumentsMethod::ReplaceDocuments,
+                merge_patch: false,
+                primary_key: None,
+                documents_count: 1,
+                allow_index_creation: true,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
+        }];
+
+        index_resolver
+            .process_document_addition_batch(&mut tasks, &cancelation)
+            .await;
+
+        // the first cancelation is the one recorded.
+        assert!(matches!(
+            tasks[0].events[0],
+            TaskEvent::Canceled { canceled_by: 3, .. }
+        ));
+    }
 }
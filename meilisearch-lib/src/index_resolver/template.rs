@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Settings, Unchecked};
+
+/// Settings applied to the indexes whose uid matches `pattern` when they are created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexTemplate {
+    pub name: String,
+    /// An index uid where `*` matches any sequence of characters, e.g. `logs-*`.
+    pub pattern: String,
+    pub settings: Settings<Unchecked>,
+}
+
+/// Returns whether `pattern` is made of the characters allowed in an index uid and of `*`.
+pub fn is_valid_pattern(pattern: &str) -> bool {
+    !pattern.is_empty()
+        && pattern.len() <= 400
+        && pattern
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '*'))
+}
+
+/// Returns whether the index uid `uid` matches `pattern`.
+pub fn matches(pattern: &str, uid: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // there is always a first part, possibly empty.
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match uid.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let parts: Vec<_> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some((last, middle)) => (*last, middle),
+        // no wildcard, the whole uid must be matched.
+        None => return rest.is_empty(),
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// Returns the template applied to a new index `uid`. When several templates match, the one
+/// with the most specific pattern, i.e. with the most characters besides the wildcards, is
+/// applied, then the first one by name.
+pub fn find_template<'a>(templates: &'a [IndexTemplate], uid: &str) -> Option<&'a IndexTemplate> {
+    let specificity = |template: &IndexTemplate| template.pattern.replace('*', "").len();
+
+    templates
+        .iter()
+        .filter(|template| matches(&template.pattern, uid))
+        .fold(None, |best: Option<&IndexTemplate>, template| match best {
+            Some(best) if specificity(best) >= specificity(template) => Some(best),
+            _ => Some(template),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn template(name: &str, pattern: &str) -> IndexTemplate {
+        IndexTemplate {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            settings: Settings::default(),
+        }
+    }
+
+    #[test]
+    fn match_patterns() {
+        assert!(matches("logs-*", "logs-2022"));
+        assert!(matches("logs-*", "logs-"));
+        assert!(!matches("logs-*", "log"));
+        assert!(matches("*-staging", "movies-staging"));
+        assert!(!matches("*-staging", "movies-production"));
+        assert!(matches("logs-*-eu", "logs-2022-06-eu"));
+        assert!(!matches("logs-*-eu", "logs-eu"));
+        assert!(matches("*", "movies"));
+        assert!(matches("movies", "movies"));
+        assert!(!matches("movies", "movies-1"));
+
+        assert!(is_valid_pattern("logs-*"));
+        assert!(!is_valid_pattern("logs.*"));
+        assert!(!is_valid_pattern(""));
+    }
+
+    #[test]
+    fn most_specific_template() {
+        let templates = vec![
+            template("all", "*"),
+            template("logs", "logs-*"),
+            template("eu-logs", "logs-*-eu"),
+            template("other-logs", "logs-*"),
+        ];
+
+        let name = |uid| find_template(&templates, uid).map(|t| t.name.as_str());
+        assert_eq!(name("movies"), Some("all"));
+        assert_eq!(name("logs-2022"), Some("logs"));
+        assert_eq!(name("logs-2022-eu"), Some("eu-logs"));
+        assert_eq!(
+            find_template(&templates[1..2], "movies").map(|t| &t.name),
+            None
+        );
+    }
+}
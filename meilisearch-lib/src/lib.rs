@@ -4,10 +4,14 @@ pub mod options;
 
 mod analytics;
 mod dump;
+pub mod experimental_features;
 pub mod index;
 pub mod index_controller;
 mod index_resolver;
+pub mod request_id;
 mod snapshot;
+mod storage;
+pub mod task_priority;
 pub mod tasks;
 mod update_file_store;
 
@@ -16,6 +20,8 @@ use std::path::Path;
 pub use index_controller::MeiliSearch;
 pub use milli;
 pub use milli::heed;
+pub use snapshot::{CronSchedule, CronScheduleError, SnapshotRetention};
+pub use storage::{ObjectStorage, ObjectStorageError};
 
 mod compression;
 pub mod document_formats;
@@ -1,4 +1,5 @@
 use core::fmt;
+use std::sync::{Arc, RwLock};
 use std::{convert::TryFrom, num::ParseIntError, ops::Deref, str::FromStr};
 
 use byte_unit::{Byte, ByteError};
@@ -7,6 +8,8 @@ use milli::update::IndexerConfig;
 use serde::Serialize;
 use sysinfo::{RefreshKind, System, SystemExt};
 
+use crate::storage::{ObjectStorage, ObjectStorageError};
+
 #[derive(Debug, Clone, Parser, Serialize)]
 pub struct IndexerOpts {
     /// The amount of documents to skip before printing
@@ -37,6 +40,21 @@ pub struct IndexerOpts {
     /// It defaults to half of the available threads.
     #[clap(long, env = "MEILI_MAX_INDEXING_THREADS", default_value_t)]
     pub max_indexing_threads: MaxThreads,
+
+    /// Number of seconds the deleted documents can be restored for.
+    ///
+    /// When set, the deletions are soft: the deleted documents disappear from the index but are
+    /// kept aside, and can be restored with the `/indexes/{index_uid}/documents/restore` route
+    /// until the retention elapses. By default the deletions are definitive. The deletion of an
+    /// index is always definitive, its deleted documents are dropped along with it.
+    #[clap(long, env = "MEILI_SOFT_DELETION_RETENTION_SEC")]
+    pub soft_deletion_retention_sec: Option<u64>,
+
+    /// Record the changes of the documents, listed in order by the
+    /// `/indexes/{index_uid}/changes` route to keep external systems in sync. Only the latest
+    /// change of each document is kept.
+    #[clap(long, env = "MEILI_EXPERIMENTAL_ENABLE_CHANGES_FEED")]
+    pub experimental_enable_changes_feed: bool,
 }
 
 #[derive(Debug, Clone, Parser, Default, Serialize)]
@@ -62,6 +80,92 @@ pub struct SchedulerConfig {
     /// starting to process a batch of updates.
     #[clap(long, requires = "enable-auto-batching", hide = true)]
     pub debounce_duration_sec: Option<u64>,
+
+    /// Number of days the finished tasks are kept in the task history.
+    ///
+    /// When set, the tasks that finished more than `task_retention_days` days ago are
+    /// periodically deleted. By default the task history is never pruned.
+    #[clap(long, env = "MEILI_TASK_RETENTION_DAYS")]
+    pub task_retention_days: Option<u64>,
+}
+
+/// The S3-compatible object storage the dumps and snapshots are uploaded to.
+#[derive(Debug, Clone, Parser, Serialize)]
+pub struct ObjectStorageOpts {
+    /// The endpoint of an S3-compatible object storage, e.g. `https://s3.eu-west-1.amazonaws.com`
+    /// or `http://localhost:9000` for MinIO. When set, the dumps and snapshots are uploaded to the
+    /// storage and removed locally, and the dumps and snapshots to import that don't exist
+    /// locally are fetched from the storage.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OBJECT_STORAGE_ENDPOINT",
+        requires_all = &["object-storage-bucket", "object-storage-access-key", "object-storage-secret-key"]
+    )]
+    pub object_storage_endpoint: Option<String>,
+
+    /// The bucket the dumps and snapshots are stored in.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OBJECT_STORAGE_BUCKET",
+        requires = "object-storage-endpoint"
+    )]
+    pub object_storage_bucket: Option<String>,
+
+    /// The region of the bucket, used to sign the requests.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_OBJECT_STORAGE_REGION", default_value = "us-east-1")]
+    pub object_storage_region: String,
+
+    /// The access key of the object storage.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OBJECT_STORAGE_ACCESS_KEY",
+        requires = "object-storage-endpoint"
+    )]
+    pub object_storage_access_key: Option<String>,
+
+    /// The secret key of the object storage.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_OBJECT_STORAGE_SECRET_KEY",
+        requires = "object-storage-endpoint"
+    )]
+    pub object_storage_secret_key: Option<String>,
+
+    /// Prepended to the key of the stored objects, e.g. `production/`. The dumps are stored
+    /// under `<prefix>dumps/` and the snapshots under `<prefix>snapshots/`.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_OBJECT_STORAGE_PREFIX", default_value = "")]
+    pub object_storage_prefix: String,
+}
+
+impl ObjectStorageOpts {
+    /// Returns the configured object storage, if any.
+    pub fn storage(&self) -> Result<Option<ObjectStorage>, ObjectStorageError> {
+        match (
+            &self.object_storage_endpoint,
+            &self.object_storage_bucket,
+            &self.object_storage_access_key,
+            &self.object_storage_secret_key,
+        ) {
+            (Some(endpoint), Some(bucket), Some(access_key), Some(secret_key)) => {
+                ObjectStorage::new(
+                    endpoint,
+                    bucket.clone(),
+                    self.object_storage_region.clone(),
+                    access_key.clone(),
+                    secret_key.clone(),
+                    self.object_storage_prefix.clone(),
+                )
+                .map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 impl TryFrom<&IndexerOpts> for IndexerConfig {
@@ -83,6 +187,38 @@ impl TryFrom<&IndexerOpts> for IndexerConfig {
     }
 }
 
+/// The configuration of the indexer shared by all the indexes, it can be replaced at runtime.
+/// The batches being processed keep using the configuration they started with.
+#[derive(Clone)]
+pub struct IndexerConfigHandle {
+    inner: Arc<RwLock<(IndexerOpts, Arc<IndexerConfig>)>>,
+}
+
+impl IndexerConfigHandle {
+    pub fn new(opts: IndexerOpts) -> anyhow::Result<Self> {
+        let config = Arc::new(IndexerConfig::try_from(&opts)?);
+        Ok(Self {
+            inner: Arc::new(RwLock::new((opts, config))),
+        })
+    }
+
+    /// Returns the configuration the next batches are processed with.
+    pub fn config(&self) -> Arc<IndexerConfig> {
+        self.inner.read().unwrap().1.clone()
+    }
+
+    pub fn opts(&self) -> IndexerOpts {
+        self.inner.read().unwrap().0.clone()
+    }
+
+    /// Replaces the configuration, a new thread pool is built for the next batches.
+    pub fn set_opts(&self, opts: IndexerOpts) -> anyhow::Result<()> {
+        let config = Arc::new(IndexerConfig::try_from(&opts)?);
+        *self.inner.write().unwrap() = (opts, config);
+        Ok(())
+    }
+}
+
 impl Default for IndexerOpts {
     fn default() -> Self {
         Self {
@@ -90,6 +226,8 @@ impl Default for IndexerOpts {
             max_nb_chunks: None,
             max_indexing_memory: MaxMemory::default(),
             max_indexing_threads: MaxThreads::default(),
+            soft_deletion_retention_sec: None,
+            experimental_enable_changes_feed: false,
         }
     }
 }
@@ -134,6 +272,10 @@ impl Deref for MaxMemory {
 }
 
 impl MaxMemory {
+    pub fn new(memory: Byte) -> Self {
+        Self(Some(memory))
+    }
+
     pub fn unlimited() -> Self {
         Self(None)
     }
@@ -162,6 +304,12 @@ impl FromStr for MaxThreads {
     }
 }
 
+impl MaxThreads {
+    pub fn new(threads: usize) -> Self {
+        Self(threads)
+    }
+}
+
 impl Default for MaxThreads {
     fn default() -> Self {
         MaxThreads(num_cpus::get() / 2)
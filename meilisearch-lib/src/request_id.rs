@@ -0,0 +1,19 @@
+//! The id of the HTTP request being processed. It is set by the HTTP layer while a request is
+//! processed, and recorded by the tasks registered in the meantime.
+
+use std::future::Future;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Runs `fut` with `id` as the id of the current request.
+pub async fn scope<F: Future>(id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(id, fut).await
+}
+
+/// Returns the id of the request being processed, if any. It isn't available on the blocking
+/// threads.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
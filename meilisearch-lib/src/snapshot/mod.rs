@@ -0,0 +1,395 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::bail;
+use fs_extra::dir::{self, CopyOptions};
+use log::{error, info, trace};
+use meilisearch_auth::open_auth_store_env;
+use time::macros::format_description;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+use walkdir::WalkDir;
+
+use crate::compression::from_tar_gz;
+use crate::experimental_features;
+use crate::index::SideStore;
+use crate::index_controller::versioning::VERSION_FILE_NAME;
+use crate::index_controller::{copy_replication_position, open_meta_env};
+use crate::storage::{ArchiveKind, ObjectStorage};
+use crate::tasks::Scheduler;
+
+pub use schedule::{CronSchedule, CronScheduleError};
+
+mod schedule;
+
+/// When the scheduled snapshots are performed.
+#[derive(Debug, Clone)]
+pub enum SnapshotSchedule {
+    /// A snapshot is performed at startup, and then after each period.
+    Interval(Duration),
+    /// A snapshot is performed at each occurrence of the schedule.
+    Cron(CronSchedule),
+}
+
+/// Which snapshots are kept in the snapshot directory.
+///
+/// Without retention, a single snapshot is kept and overwritten by each new snapshot. Otherwise
+/// each snapshot is stored in its own timestamped file, and the oldest ones are removed once
+/// the retention is exceeded. The most recent snapshot is never removed.
+///
+/// The retention only applies to the snapshot directory: with an object storage, the snapshots
+/// are removed locally once uploaded, and their retention must be configured on the bucket.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SnapshotRetention {
+    /// The number of snapshots to keep.
+    pub keep_last: Option<usize>,
+    /// The number of days a snapshot is kept for.
+    pub keep_days: Option<u64>,
+}
+
+impl SnapshotRetention {
+    fn is_enabled(&self) -> bool {
+        self.keep_last.is_some() || self.keep_days.is_some()
+    }
+
+    /// Removes the snapshots of `db_name` in `dir` exceeding the retention.
+    fn apply(&self, dir: &Path, db_name: &str) -> anyhow::Result<()> {
+        let prefix = format!("{}-", db_name);
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = match entry.file_name().into_string() {
+                Ok(name) if name.starts_with(&prefix) && name.ends_with(".snapshot") => name,
+                _ => continue,
+            };
+            let modified = entry.metadata()?.modified()?;
+            snapshots.push((name, entry.path(), modified));
+        }
+
+        // The names of the snapshots end with their creation date, most recent ones first.
+        snapshots.sort_unstable_by(|(left, ..), (right, ..)| right.cmp(left));
+
+        let now = SystemTime::now();
+        let max_age = self
+            .keep_days
+            .map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60)));
+        for (position, (_, path, modified)) in snapshots.into_iter().enumerate().skip(1) {
+            let too_many = self.keep_last.map_or(false, |keep| position >= keep);
+            let too_old = max_age.map_or(false, |max_age| {
+                now.duration_since(modified)
+                    .map_or(false, |age| age > max_age)
+            });
+
+            if too_many || too_old {
+                trace!("Removing snapshot {:?}.", path);
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything needed to perform a snapshot of the database.
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    pub(crate) db_path: PathBuf,
+    pub(crate) snapshot_path: PathBuf,
+    pub(crate) index_size: usize,
+    pub(crate) meta_env_size: usize,
+    pub(crate) retention: SnapshotRetention,
+    /// When set, the snapshots are uploaded to the object storage and removed locally.
+    pub(crate) object_storage: Option<ObjectStorage>,
+}
+
+impl SnapshotConfig {
+    pub fn job(&self) -> SnapshotJob {
+        SnapshotJob {
+            dest_path: self.snapshot_path.clone(),
+            src_path: self.db_path.clone(),
+            meta_env_size: self.meta_env_size,
+            index_size: self.index_size,
+            retention: self.retention,
+            object_storage: self.object_storage.clone(),
+        }
+    }
+}
+
+pub struct SnapshotService {
+    pub(crate) config: SnapshotConfig,
+    pub(crate) schedule: SnapshotSchedule,
+    pub(crate) scheduler: Arc<RwLock<Scheduler>>,
+}
+
+impl SnapshotService {
+    pub async fn run(self) {
+        match self.schedule {
+            SnapshotSchedule::Interval(period) => {
+                info!("Snapshot scheduled every {}s.", period.as_secs());
+                loop {
+                    self.schedule_snapshot().await;
+                    sleep(period).await;
+                }
+            }
+            SnapshotSchedule::Cron(ref schedule) => {
+                info!("Snapshot scheduled with `{}`.", schedule);
+                loop {
+                    let now = OffsetDateTime::now_utc();
+                    match schedule.next_after(now) {
+                        Some(next) => {
+                            sleep((next - now).try_into().unwrap_or_default()).await;
+                            self.schedule_snapshot().await;
+                        }
+                        None => {
+                            error!("The snapshot schedule `{}` never occurs.", schedule);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn schedule_snapshot(&self) {
+        let job = self.config.job();
+        self.scheduler.write().await.schedule_snapshot(job);
+    }
+}
+
+pub fn load_snapshot(
+    db_path: impl AsRef<Path>,
+    snapshot_path: impl AsRef<Path>,
+    ignore_snapshot_if_db_exists: bool,
+    ignore_missing_snapshot: bool,
+) -> anyhow::Result<()> {
+    let empty_db = crate::is_empty_db(&db_path);
+    let snapshot_path_exists = snapshot_path.as_ref().exists();
+
+    if empty_db && snapshot_path_exists {
+        match from_tar_gz(snapshot_path, &db_path) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                //clean created db folder
+                std::fs::remove_dir_all(&db_path)?;
+                Err(e)
+            }
+        }
+    } else if !empty_db && !ignore_snapshot_if_db_exists {
+        bail!(
+            "database already exists at {:?}, try to delete it or rename it",
+            db_path
+                .as_ref()
+                .canonicalize()
+                .unwrap_or_else(|_| db_path.as_ref().to_owned())
+        )
+    } else if !snapshot_path_exists && !ignore_missing_snapshot {
+        bail!("snapshot doesn't exist at {:?}", snapshot_path.as_ref())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct SnapshotJob {
+    dest_path: PathBuf,
+    src_path: PathBuf,
+
+    meta_env_size: usize,
+    index_size: usize,
+    retention: SnapshotRetention,
+    object_storage: Option<ObjectStorage>,
+}
+
+impl SnapshotJob {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let object_storage = self.object_storage.clone();
+        let snapshot_path = tokio::task::spawn_blocking(|| self.run_sync()).await??;
+
+        if let Some(storage) = object_storage {
+            let name = snapshot_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            storage
+                .upload(&storage.key(ArchiveKind::Snapshot, &name), &snapshot_path)
+                .await?;
+            tokio::fs::remove_file(&snapshot_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Performs the snapshot, and returns the path of the created archive.
+    fn run_sync(self) -> anyhow::Result<PathBuf> {
+        trace!("Performing snapshot.");
+
+        let snapshot_dir = self.dest_path.clone();
+        std::fs::create_dir_all(&snapshot_dir)?;
+        let temp_snapshot_dir = tempfile::tempdir()?;
+        let temp_snapshot_path = temp_snapshot_dir.path();
+
+        self.snapshot_version_file(temp_snapshot_path)?;
+        self.snapshot_meta_env(temp_snapshot_path)?;
+        self.snapshot_file_store(temp_snapshot_path)?;
+        self.snapshot_indexes(temp_snapshot_path)?;
+        self.snapshot_auth(temp_snapshot_path)?;
+        experimental_features::copy_features(&self.src_path, temp_snapshot_path)?;
+        copy_replication_position(&self.src_path, temp_snapshot_path)?;
+
+        let db_name = self
+            .src_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("data.ms")
+            .to_string();
+
+        let snapshot_path = if self.retention.is_enabled() {
+            let format = format_description!("[year][month][day]-[hour][minute][second]");
+            let date = OffsetDateTime::now_utc().format(format)?;
+            self.dest_path
+                .join(format!("{}-{}.snapshot", db_name, date))
+        } else {
+            self.dest_path.join(format!("{}.snapshot", db_name))
+        };
+        let temp_snapshot_file = tempfile::NamedTempFile::new_in(&snapshot_dir)?;
+        let temp_snapshot_file_path = temp_snapshot_file.path().to_owned();
+        crate::compression::to_tar_gz(temp_snapshot_path, temp_snapshot_file_path)?;
+        let _file = temp_snapshot_file.persist(&snapshot_path)?;
+
+        #[cfg(unix)]
+        {
+            use std::fs::Permissions;
+            use std::os::unix::fs::PermissionsExt;
+
+            let perm = Permissions::from_mode(0o644);
+            _file.set_permissions(perm)?;
+        }
+
+        trace!("Created snapshot in {:?}.", snapshot_path);
+
+        if self.retention.is_enabled() {
+            self.retention.apply(&self.dest_path, &db_name)?;
+        }
+
+        Ok(snapshot_path)
+    }
+
+    fn snapshot_version_file(&self, path: &Path) -> anyhow::Result<()> {
+        let dst = path.join(VERSION_FILE_NAME);
+        let src = self.src_path.join(VERSION_FILE_NAME);
+
+        fs::copy(src, dst)?;
+
+        Ok(())
+    }
+
+    fn snapshot_meta_env(&self, path: &Path) -> anyhow::Result<()> {
+        let env = open_meta_env(&self.src_path, self.meta_env_size)?;
+
+        let dst = path.join("data.mdb");
+        env.copy_to_path(dst, milli::heed::CompactionOption::Enabled)?;
+
+        Ok(())
+    }
+
+    fn snapshot_file_store(&self, path: &Path) -> anyhow::Result<()> {
+        // for now we simply copy the updates/updates_files
+        // FIXME(marin): We may copy more files than necessary, if new files are added while we are
+        // performing the snapshop. We need a way to filter them out.
+
+        let dst = path.join("updates");
+        fs::create_dir_all(&dst)?;
+        let options = CopyOptions::default();
+        dir::copy(self.src_path.join("updates/updates_files"), dst, &options)?;
+
+        Ok(())
+    }
+
+    fn snapshot_indexes(&self, path: &Path) -> anyhow::Result<()> {
+        let indexes_path = self.src_path.join("indexes/");
+        let dst = path.join("indexes/");
+
+        for entry in WalkDir::new(indexes_path).max_depth(1).into_iter().skip(1) {
+            let entry = entry?;
+            let name = entry.file_name();
+            let dst = dst.join(name);
+
+            std::fs::create_dir_all(&dst)?;
+
+            let mut options = milli::heed::EnvOpenOptions::new();
+            options.map_size(self.index_size);
+            let index = milli::Index::new(options, entry.path())?;
+            index
+                .env
+                .copy_to_path(dst.join("data.mdb"), milli::heed::CompactionOption::Enabled)?;
+            SideStore::open(entry.path(), self.index_size)?.copy_to(&dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn snapshot_auth(&self, path: &Path) -> anyhow::Result<()> {
+        let auth_path = self.src_path.join("auth");
+        let dst = path.join("auth");
+        std::fs::create_dir_all(&dst)?;
+        let dst = dst.join("data.mdb");
+
+        let env = open_auth_store_env(&auth_path)?;
+        env.copy_to_path(dst, milli::heed::CompactionOption::Enabled)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = [
+            "data.ms-20220601-000000.snapshot",
+            "data.ms-20220602-000000.snapshot",
+            "data.ms-20220603-000000.snapshot",
+            "data.ms.snapshot",
+            "other.ms-20220601-000000.snapshot",
+        ];
+        for name in names {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        let retention = SnapshotRetention {
+            keep_last: Some(2),
+            keep_days: None,
+        };
+        retention.apply(dir.path(), "data.ms").unwrap();
+
+        let mut remaining: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            [
+                "data.ms-20220602-000000.snapshot",
+                "data.ms-20220603-000000.snapshot",
+                "data.ms.snapshot",
+                "other.ms-20220601-000000.snapshot",
+            ]
+        );
+
+        // The most recent snapshot is always kept.
+        let retention = SnapshotRetention {
+            keep_last: Some(0),
+            keep_days: Some(0),
+        };
+        retention.apply(dir.path(), "data.ms").unwrap();
+        assert!(dir.path().join("data.ms-20220603-000000.snapshot").exists());
+        assert!(!dir.path().join("data.ms-20220602-000000.snapshot").exists());
+    }
+}
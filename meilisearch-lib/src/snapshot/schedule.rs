@@ -0,0 +1,228 @@
+use std::fmt;
+use std::str::FromStr;
+
+use time::{Date, OffsetDateTime, Time};
+
+/// How far in the future the next occurrence of a schedule is looked for. Eight years are
+/// enough to find any satisfiable date, including the 29th of February on a given weekday.
+const MAX_LOOKUP_DAYS: usize = 8 * 366;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid snapshot schedule `{schedule}`: {reason}.")]
+pub struct CronScheduleError {
+    schedule: String,
+    reason: String,
+}
+
+/// A cron-like schedule, made of the five `minute hour day-of-month month day-of-week` fields.
+///
+/// Each field accepts `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`, and a comma separated
+/// list of those. The days of the week go from `0` (Sunday) to `6`, `7` is also accepted for
+/// Sunday. The schedule is always evaluated in UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// As in cron, when both the day of the month and the day of the week are restricted, a day
+    /// matching either of them is a match.
+    days_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Returns the first date strictly after `after` matching the schedule, or `None` if the
+    /// schedule never matches.
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        let after = after.to_offset(time::UtcOffset::UTC);
+        let mut date = after.date();
+        // the next occurrence starts at the next minute at the earliest.
+        let mut from = Some((after.hour(), after.minute() + 1));
+
+        for _ in 0..MAX_LOOKUP_DAYS {
+            if self.matches_date(date) {
+                let (from_hour, from_minute) = from.unwrap_or((0, 0));
+                if let Some(time) = self.first_time_from(from_hour, from_minute) {
+                    return Some(date.with_time(time).assume_utc());
+                }
+            }
+            date = date.next_day()?;
+            from = None;
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        let month = contains(self.months, u8::from(date.month()));
+        let day_of_month = contains(self.days_of_month, date.day());
+        let day_of_week = contains(self.days_of_week, date.weekday().number_days_from_sunday());
+
+        let day = if self.days_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        };
+
+        month && day
+    }
+
+    fn first_time_from(&self, from_hour: u8, from_minute: u8) -> Option<Time> {
+        for hour in from_hour..24 {
+            if !contains(self.hours, hour) {
+                continue;
+            }
+            let first_minute = if hour == from_hour { from_minute } else { 0 };
+            if let Some(minute) = (first_minute..60).find(|m| contains(self.minutes, *m)) {
+                return Time::from_hms(hour, minute, 0).ok();
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |reason: String| CronScheduleError {
+            schedule: s.to_string(),
+            reason,
+        };
+
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = match fields[..] {
+            [a, b, c, d, e] => [a, b, c, d, e],
+            _ => return Err(error(format!("expected 5 fields, found {}", fields.len()))),
+        };
+
+        // `7` is an alias for Sunday.
+        let mut days_of_week_set = parse_field(days_of_week, 0, 7).map_err(error)?;
+        if contains(days_of_week_set, 7u8) {
+            days_of_week_set |= 1;
+        }
+
+        Ok(Self {
+            source: s.to_string(),
+            minutes: parse_field(minutes, 0, 59).map_err(error)?,
+            hours: parse_field(hours, 0, 23).map_err(error)?,
+            days_of_month: parse_field(days_of_month, 1, 31).map_err(error)?,
+            months: parse_field(months, 1, 12).map_err(error)?,
+            days_of_week: days_of_week_set,
+            days_restricted: !days_of_month.starts_with('*') && !days_of_week.starts_with('*'),
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn contains(set: u64, value: u8) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parses a field of a schedule into the set of its values, stored as a bitset.
+fn parse_field(field: &str, min: u8, max: u8) -> Result<u64, String> {
+    let mut set = 0;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u8>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("`{}` is not a valid step", step)),
+            },
+            None => (part, 1),
+        };
+
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (parse_value(start, min, max)?, parse_value(end, min, max)?),
+            // a single value with a step goes until the end of the field.
+            None if step > 1 => (parse_value(range, min, max)?, max),
+            None => {
+                let value = parse_value(range, min, max)?;
+                (value, value)
+            }
+        };
+
+        if start > end {
+            return Err(format!("`{}` is not a valid range", range));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+
+    Ok(set)
+}
+
+fn parse_value(value: &str, min: u8, max: u8) -> Result<u8, String> {
+    match value.parse::<u8>() {
+        Ok(v) if (min..=max).contains(&v) => Ok(v),
+        _ => Err(format!(
+            "`{}` is not a value between {} and {}",
+            value, min, max
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn next(schedule: &str, after: OffsetDateTime) -> OffsetDateTime {
+        schedule
+            .parse::<CronSchedule>()
+            .unwrap()
+            .next_after(after)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_invalid_schedules() {
+        assert!("* * * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("* * 0 * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert!("10-5 * * * *".parse::<CronSchedule>().is_err());
+        assert!("a * * * *".parse::<CronSchedule>().is_err());
+    }
+
+    #[test]
+    fn next_occurrence() {
+        let after = datetime!(2022-06-15 10:30:45 UTC);
+
+        assert_eq!(next("* * * * *", after), datetime!(2022-06-15 10:31 UTC));
+        assert_eq!(next("0 3 * * *", after), datetime!(2022-06-16 03:00 UTC));
+        assert_eq!(next("*/20 * * * *", after), datetime!(2022-06-15 10:40 UTC));
+        assert_eq!(
+            next("15,45 10 * * *", after),
+            datetime!(2022-06-15 10:45 UTC)
+        );
+        assert_eq!(next("0 0 1 * *", after), datetime!(2022-07-01 00:00 UTC));
+        assert_eq!(next("0 0 * 1 *", after), datetime!(2023-01-01 00:00 UTC));
+        // 2022-06-15 is a Wednesday.
+        assert_eq!(next("0 12 * * 0", after), datetime!(2022-06-19 12:00 UTC));
+        assert_eq!(next("0 12 * * 7", after), datetime!(2022-06-19 12:00 UTC));
+        assert_eq!(next("0 9 * * 1-5", after), datetime!(2022-06-16 09:00 UTC));
+        // restricting both days matches either of them.
+        assert_eq!(next("0 0 20 * 5", after), datetime!(2022-06-17 00:00 UTC));
+        assert_eq!(next("0 0 29 2 *", after), datetime!(2024-02-29 00:00 UTC));
+    }
+
+    #[test]
+    fn unsatisfiable_schedule() {
+        let schedule: CronSchedule = "0 0 31 2 *".parse().unwrap();
+        assert!(schedule
+            .next_after(datetime!(2022-06-15 10:30 UTC))
+            .is_none());
+    }
+}
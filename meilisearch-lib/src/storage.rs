@@ -0,0 +1,545 @@
+//! Storage of the dump and snapshot archives in an S3-compatible object storage.
+//!
+//! The archives are still created on the local filesystem, they are then uploaded to the bucket
+//! and removed locally. The requests are signed with the AWS signature version 4, which is
+//! supported by AWS S3, MinIO and the XML API of Google Cloud Storage. Objects are addressed with
+//! path-style URLs: `<endpoint>/<bucket>/<key>`.
+//!
+//! The archives larger than `MULTIPART_THRESHOLD` are uploaded in parts, since a single upload
+//! can't exceed 5 GiB.
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use reqwest::header::{AUTHORIZATION, CONTENT_LENGTH, ETAG};
+use reqwest::{Client, Method, RequestBuilder, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use time::macros::format_description;
+use time::OffsetDateTime;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// The payload hash sent with the uploads, whose body is streamed instead of being hashed.
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// The size above which the archives are uploaded in parts.
+const MULTIPART_THRESHOLD: u64 = 256 * 1024 * 1024;
+/// The smallest size of the parts of an upload, they are larger when an archive would need more
+/// than `MAX_PARTS` parts.
+const MIN_PART_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_PARTS: u64 = 10_000;
+
+/// How long connecting to the object storage can take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the object storage can take to answer a request, or to send the next chunk of a
+/// downloaded object.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long sending an object, or a part of an object, and getting the answer can take.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+pub type Result<T> = std::result::Result<T, ObjectStorageError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ObjectStorageError {
+    #[error(
+        "The object storage endpoint `{0}` is invalid. Expected an absolute `http` or `https` URL."
+    )]
+    InvalidEndpoint(String),
+    #[error("The request to the object storage failed: {0}.")]
+    Request(#[from] reqwest::Error),
+    #[error("The object storage answered `{status}` for the object `{key}`: {body}")]
+    UnexpectedStatus {
+        key: String,
+        status: StatusCode,
+        body: String,
+    },
+    #[error("The object storage didn't answer in time for the object `{0}`.")]
+    Timeout(String),
+    #[error("The object storage answered with an invalid response for the object `{key}`: {body}")]
+    InvalidResponse { key: String, body: String },
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The kinds of archives kept in the object storage, each kind is stored under its own prefix.
+#[derive(Debug, Clone, Copy)]
+pub enum ArchiveKind {
+    Dump,
+    Snapshot,
+}
+
+impl ArchiveKind {
+    fn prefix(&self) -> &'static str {
+        match self {
+            ArchiveKind::Dump => "dumps/",
+            ArchiveKind::Snapshot => "snapshots/",
+        }
+    }
+}
+
+/// A bucket of an S3-compatible object storage.
+#[derive(Clone)]
+pub struct ObjectStorage {
+    client: Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    /// Prepended to the key of every object, e.g. `production/`.
+    prefix: String,
+}
+
+/// The credentials are left out of the debug representation, which ends up in the logs.
+impl fmt::Debug for ObjectStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectStorage")
+            .field("endpoint", &self.endpoint.as_str())
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl ObjectStorage {
+    pub fn new(
+        endpoint: &str,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+        prefix: String,
+    ) -> Result<Self> {
+        let endpoint = match Url::parse(endpoint) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => url,
+            _ => return Err(ObjectStorageError::InvalidEndpoint(endpoint.to_string())),
+        };
+
+        let client = Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?;
+
+        Ok(Self {
+            client,
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            prefix,
+        })
+    }
+
+    /// Returns the key of the archive `name` of the given `kind`.
+    pub fn key(&self, kind: ArchiveKind, name: &str) -> String {
+        format!("{}{}{}", self.prefix, kind.prefix(), name)
+    }
+
+    /// Uploads the file at `path` as the object `key`, replacing any existing object.
+    pub async fn upload(&self, key: &str, path: &Path) -> Result<()> {
+        let file = tokio::fs::File::open(path).await?;
+        let len = file.metadata().await?.len();
+
+        if len > MULTIPART_THRESHOLD {
+            self.upload_parts(key, file, len).await?;
+        } else {
+            let response = self
+                .request(Method::PUT, key, &[], UNSIGNED_PAYLOAD)
+                .header(CONTENT_LENGTH, len)
+                .body(file)
+                .timeout(UPLOAD_TIMEOUT)
+                .send()
+                .await?;
+            check_status(key, response).await?;
+        }
+
+        info!("Uploaded {:?} to the object storage as `{}`.", path, key);
+        Ok(())
+    }
+
+    /// Uploads the `len` bytes of `file` as the object `key` with a multipart upload. The upload
+    /// is aborted if a part can't be uploaded, so that the storage doesn't keep its parts.
+    async fn upload_parts(&self, key: &str, mut file: tokio::fs::File, len: u64) -> Result<()> {
+        let response = self
+            .request(Method::POST, key, &[("uploads", "")], &hex_sha256(b""))
+            .timeout(READ_TIMEOUT)
+            .send()
+            .await?;
+        let body = check_status(key, response).await?.text().await?;
+        let upload_id = match xml_element(&body, "UploadId") {
+            Some(upload_id) => upload_id.to_string(),
+            None => {
+                return Err(ObjectStorageError::InvalidResponse {
+                    key: key.to_string(),
+                    body,
+                })
+            }
+        };
+
+        let part_size = MIN_PART_SIZE.max((len + MAX_PARTS - 1) / MAX_PARTS);
+        let mut etags = Vec::new();
+        let mut uploaded = async {
+            let mut remaining = len;
+            while remaining > 0 {
+                let size = remaining.min(part_size);
+                let mut part = vec![0; size as usize];
+                file.read_exact(&mut part).await?;
+                remaining -= size;
+
+                let number = (etags.len() + 1).to_string();
+                let query = [
+                    ("partNumber", number.as_str()),
+                    ("uploadId", upload_id.as_str()),
+                ];
+                let response = self
+                    .request(Method::PUT, key, &query, UNSIGNED_PAYLOAD)
+                    .header(CONTENT_LENGTH, size)
+                    .body(part)
+                    .timeout(UPLOAD_TIMEOUT)
+                    .send()
+                    .await?;
+                let response = check_status(key, response).await?;
+                match response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                {
+                    Some(etag) => etags.push(etag.to_string()),
+                    None => {
+                        return Err(ObjectStorageError::InvalidResponse {
+                            key: key.to_string(),
+                            body: "missing `ETag` header".to_string(),
+                        })
+                    }
+                }
+            }
+            Ok(())
+        }
+        .await;
+
+        if uploaded.is_ok() {
+            uploaded = self.complete_upload(key, &upload_id, &etags).await;
+        }
+        if uploaded.is_err() {
+            let query = [("uploadId", upload_id.as_str())];
+            let aborted = self
+                .request(Method::DELETE, key, &query, &hex_sha256(b""))
+                .timeout(READ_TIMEOUT)
+                .send()
+                .await;
+            if let Err(e) = aborted {
+                warn!("The upload of `{}` couldn't be aborted: {}.", key, e);
+            }
+        }
+
+        uploaded
+    }
+
+    /// Completes the multipart upload `upload_id` of the object `key` with the parts `etags`.
+    async fn complete_upload(&self, key: &str, upload_id: &str, etags: &[String]) -> Result<()> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in etags.iter().enumerate() {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                etag
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+
+        let response = self
+            .request(
+                Method::POST,
+                key,
+                &[("uploadId", upload_id)],
+                &hex_sha256(body.as_bytes()),
+            )
+            .body(body)
+            .timeout(UPLOAD_TIMEOUT)
+            .send()
+            .await?;
+        // the completion can fail after the storage answered with a success.
+        let body = check_status(key, response).await?.text().await?;
+        if xml_element(&body, "Code").is_some() {
+            return Err(ObjectStorageError::InvalidResponse {
+                key: key.to_string(),
+                body,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the object `key` to the file at `path`. Returns `false` if the object doesn't
+    /// exist.
+    pub async fn download(&self, key: &str, path: &Path) -> Result<bool> {
+        let response = tokio::time::timeout(
+            READ_TIMEOUT,
+            self.request(Method::GET, key, &[], &hex_sha256(b"")).send(),
+        )
+        .await
+        .map_err(|_| ObjectStorageError::Timeout(key.to_string()))??;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let response = check_status(key, response).await?;
+
+        let mut file = tokio::fs::File::create(path).await?;
+        let mut stream = response.bytes_stream();
+        // the download can be long, only the delay between two chunks is limited.
+        loop {
+            let chunk = tokio::time::timeout(READ_TIMEOUT, stream.next())
+                .await
+                .map_err(|_| ObjectStorageError::Timeout(key.to_string()))?;
+            match chunk {
+                Some(chunk) => file.write_all(&chunk?).await?,
+                None => break,
+            }
+        }
+        file.flush().await?;
+
+        info!("Downloaded `{}` from the object storage.", key);
+        Ok(true)
+    }
+
+    /// Fetches the archive of the given `kind` named after the file `path` into a temporary
+    /// file. Returns `None` if the archive isn't in the object storage either.
+    ///
+    /// It is used at startup, before the runtime is available to the engine, so the download is
+    /// performed on a dedicated thread.
+    pub fn fetch(
+        &self,
+        kind: ArchiveKind,
+        path: &Path,
+    ) -> anyhow::Result<Option<tempfile::NamedTempFile>> {
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let key = self.key(kind, name);
+        let file = tempfile::NamedTempFile::new()?;
+        let file_path = file.path().to_path_buf();
+
+        let storage = self.clone();
+        let found = std::thread::spawn(move || -> anyhow::Result<bool> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            Ok(runtime.block_on(storage.download(&key, &file_path))?)
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("The download of the archive `{}` panicked.", name))??;
+
+        Ok(found.then(|| file))
+    }
+
+    /// Builds a request on the object `key` with the parameters `query`, signed with the AWS
+    /// signature version 4.
+    fn request(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        payload_hash: &str,
+    ) -> RequestBuilder {
+        let path = format!("/{}/{}", self.bucket, key);
+        let canonical_uri = uri_encode(&format!(
+            "{}{}",
+            self.endpoint.path().trim_end_matches('/'),
+            path
+        ));
+        let mut url = self.endpoint.clone();
+        url.set_path(&canonical_uri);
+
+        let mut query: Vec<_> = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", query_encode(name), query_encode(value)))
+            .collect();
+        query.sort();
+        let canonical_query = query.join("&");
+        url.set_query((!canonical_query.is_empty()).then(|| canonical_query.as_str()));
+
+        // `has_host` is checked when the storage is created.
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let amz_date = now
+            .format(format_description!(
+                "[year][month][day]T[hour][minute][second]Z"
+            ))
+            .unwrap();
+        let date = &amz_date[..8];
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            canonical_uri,
+            canonical_query,
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex_sha256(canonical_request.as_bytes())
+        );
+        let key = signing_key(&self.secret_key, date, &self.region, "s3");
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        );
+
+        self.client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(AUTHORIZATION, authorization)
+    }
+}
+
+async fn check_status(key: &str, response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let body = response.text().await.unwrap_or_default();
+        Err(ObjectStorageError::UnexpectedStatus {
+            key: key.to_string(),
+            status,
+            body,
+        })
+    }
+}
+
+/// Derives the key signing the requests of a given day, region and service.
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any size.
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Percent-encodes every byte of `path` but the unreserved characters and the `/` separators.
+fn uri_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Percent-encodes every byte of a query parameter but the unreserved characters.
+fn query_encode(value: &str) -> String {
+    uri_encode(value).replace('/', "%2F")
+}
+
+/// Returns the text of the first element `name` of the XML `body`.
+fn xml_element<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&close)? + start;
+    Some(&body[start..end])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn derive_signing_key() {
+        // The example of the AWS documentation on the derivation of a signing key.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn encode_uri() {
+        assert_eq!(
+            uri_encode("/bucket/dumps/20220615-103045123.dump"),
+            "/bucket/dumps/20220615-103045123.dump"
+        );
+        assert_eq!(uri_encode("/bucket/my dump+1"), "/bucket/my%20dump%2B1");
+        assert_eq!(query_encode("a/b=c"), "a%2Fb%3Dc");
+    }
+
+    #[test]
+    fn read_xml_element() {
+        let body = "<InitiateMultipartUploadResult><Bucket>bucket</Bucket>\
+            <UploadId>VXBsb2FkIElE</UploadId></InitiateMultipartUploadResult>";
+        assert_eq!(xml_element(body, "UploadId"), Some("VXBsb2FkIElE"));
+        assert_eq!(xml_element(body, "Code"), None);
+    }
+
+    #[test]
+    fn archive_keys() {
+        let storage = ObjectStorage::new(
+            "http://localhost:9000",
+            "bucket".to_string(),
+            "us-east-1".to_string(),
+            "access".to_string(),
+            "secret".to_string(),
+            "production/".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            storage.key(ArchiveKind::Dump, "20220615-103045123.dump"),
+            "production/dumps/20220615-103045123.dump"
+        );
+        assert_eq!(
+            storage.key(ArchiveKind::Snapshot, "data.ms.snapshot"),
+            "production/snapshots/data.ms.snapshot"
+        );
+        assert!(ObjectStorage::new(
+            "localhost:9000",
+            "bucket".to_string(),
+            "us-east-1".to_string(),
+            "access".to_string(),
+            "secret".to_string(),
+            String::new(),
+        )
+        .is_err());
+    }
+}
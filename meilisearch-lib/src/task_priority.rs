@@ -0,0 +1,24 @@
+//! The priority given to the tasks registered by the HTTP request being processed. It is set by
+//! the HTTP layer while a request is processed, and recorded by the tasks registered in the
+//! meantime.
+
+use std::future::Future;
+
+use crate::tasks::task::TaskPriority;
+
+tokio::task_local! {
+    static TASK_PRIORITY: TaskPriority;
+}
+
+/// Runs `fut` with `priority` as the priority of the tasks it registers.
+pub async fn scope<F: Future>(priority: TaskPriority, fut: F) -> F::Output {
+    TASK_PRIORITY.scope(priority, fut).await
+}
+
+/// Returns the priority of the tasks registered by the current request, the default one when
+/// none was given. It isn't available on the blocking threads.
+pub fn current() -> TaskPriority {
+    TASK_PRIORITY
+        .try_with(|priority| *priority)
+        .unwrap_or_default()
+}
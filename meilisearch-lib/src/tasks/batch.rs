@@ -1,8 +1,10 @@
+use std::sync::{Arc, Mutex};
+
 use time::OffsetDateTime;
 
 use crate::snapshot::SnapshotJob;
 
-use super::task::{Task, TaskEvent};
+use super::task::{Task, TaskEvent, TaskId};
 
 pub type BatchId = u32;
 
@@ -25,6 +27,14 @@ impl BatchContent {
         }
     }
 
+    pub fn tasks(&self) -> &[Task] {
+        match self {
+            BatchContent::DocumentsAdditionBatch(ts) => ts,
+            BatchContent::Dump(t) | BatchContent::IndexUpdate(t) => std::slice::from_ref(t),
+            BatchContent::Snapshot(_) | BatchContent::Empty => &[],
+        }
+    }
+
     pub fn push_event(&mut self, event: TaskEvent) {
         match self {
             BatchContent::DocumentsAdditionBatch(ts) => {
@@ -36,6 +46,35 @@ impl BatchContent {
     }
 }
 
+/// Set when the batch is canceled while it is processed. The handlers check it before each task
+/// of the batch and before committing their changes, the tasks they didn't commit are then
+/// marked as canceled.
+#[derive(Debug, Clone, Default)]
+pub struct Cancelation(Arc<Mutex<Option<TaskId>>>);
+
+impl Cancelation {
+    /// Cancels the batch on behalf of the `TaskCancelation` task `canceled_by`.
+    pub fn cancel(&self, canceled_by: TaskId) {
+        self.0.lock().unwrap().get_or_insert(canceled_by);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    /// Returns the event of the tasks left uncommitted by the cancelation, if the batch was
+    /// canceled.
+    pub fn event(&self) -> Option<TaskEvent> {
+        self.0
+            .lock()
+            .unwrap()
+            .map(|canceled_by| TaskEvent::Canceled {
+                canceled_by,
+                timestamp: OffsetDateTime::now_utc(),
+            })
+    }
+}
+
 #[derive(Debug)]
 pub struct Batch {
     // Only batches that contains a persistant tasks are given an id. Snapshot batches don't have
@@ -43,6 +82,7 @@ pub struct Batch {
     pub id: Option<BatchId>,
     pub created_at: OffsetDateTime,
     pub content: BatchContent,
+    pub cancelation: Cancelation,
 }
 
 impl Batch {
@@ -51,6 +91,7 @@ impl Batch {
             id,
             created_at: OffsetDateTime::now_utc(),
             content,
+            cancelation: Cancelation::default(),
         }
     }
     pub fn len(&self) -> usize {
@@ -70,6 +111,7 @@ impl Batch {
             id: None,
             created_at: OffsetDateTime::now_utc(),
             content: BatchContent::Empty,
+            cancelation: Cancelation::default(),
         }
     }
 }
@@ -4,7 +4,7 @@ use tokio::task::JoinError;
 
 use crate::update_file_store::UpdateFileStoreError;
 
-use super::task::TaskId;
+use super::task::{GroupUid, TaskId};
 
 pub type Result<T> = std::result::Result<T, TaskError>;
 
@@ -12,6 +12,12 @@ pub type Result<T> = std::result::Result<T, TaskError>;
 pub enum TaskError {
     #[error("Task `{0}` not found.")]
     UnexistingTask(TaskId),
+    #[error("Group `{0}` not found.")]
+    UnexistingGroup(GroupUid),
+    #[error("Task `{0}` can't be prioritized because it isn't enqueued anymore.")]
+    TaskNotEnqueued(TaskId),
+    #[error("Task `{0}` can't be canceled because it is being processed and its changes can't be rolled back.")]
+    TaskNotCancelable(TaskId),
     #[error("Internal error: {0}")]
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
@@ -21,6 +27,7 @@ internal_error!(
     JoinError,
     std::io::Error,
     serde_json::Error,
+    time::error::Format,
     UpdateFileStoreError
 );
 
@@ -28,6 +35,9 @@ impl ErrorCode for TaskError {
     fn error_code(&self) -> Code {
         match self {
             TaskError::UnexistingTask(_) => Code::TaskNotFound,
+            TaskError::TaskNotEnqueued(_) => Code::TaskNotEnqueued,
+            TaskError::TaskNotCancelable(_) => Code::TaskNotCancelable,
+            TaskError::UnexistingGroup(_) => Code::GroupNotFound,
             TaskError::Internal(_) => Code::Internal,
         }
     }
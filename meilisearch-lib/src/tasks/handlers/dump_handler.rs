@@ -18,10 +18,14 @@ where
     async fn process_batch(&self, mut batch: Batch) -> Batch {
         match &batch.content {
             BatchContent::Dump(Task {
-                content: TaskContent::Dump { uid },
+                content:
+                    TaskContent::Dump {
+                        uid,
+                        incremental_from,
+                    },
                 ..
             }) => {
-                match self.run(uid.clone()).await {
+                match self.run(uid.clone(), incremental_from.clone()).await {
                     Ok(_) => {
                         batch
                             .content
@@ -31,6 +35,16 @@ where
                 }
                 batch
             }
+            BatchContent::Dump(Task {
+                content: TaskContent::DumpImport { source, conflict },
+                ..
+            }) => {
+                match self.import(source, *conflict).await {
+                    Ok(result) => batch.content.push_event(TaskEvent::succeeded(result)),
+                    Err(e) => batch.content.push_event(TaskEvent::failed(e)),
+                }
+                batch
+            }
             _ => unreachable!("invalid batch content for dump"),
         }
     }
@@ -41,6 +55,7 @@ where
 #[cfg(test)]
 mod test {
     use crate::dump::error::{DumpError, Result as DumpResult};
+    use crate::dump::{DumpImportSource, ImportConflictPolicy};
     use crate::index_resolver::{index_store::MockIndexStore, meta_store::MockIndexMetaStore};
     use crate::tasks::handlers::test::task_to_batch;
 
@@ -49,6 +64,16 @@ mod test {
     use nelson::Mocker;
     use proptest::prelude::*;
 
+    fn is_import(batch: &Batch) -> bool {
+        matches!(
+            batch.content,
+            BatchContent::Dump(Task {
+                content: TaskContent::DumpImport { .. },
+                ..
+            })
+        )
+    }
+
     proptest! {
         #[test]
         fn finish_does_nothing(
@@ -77,8 +102,12 @@ mod test {
                 let should_accept = matches!(batch.content, BatchContent::Dump { .. });
 
                 let mocker = Mocker::default();
-                if should_accept {
-                    mocker.when::<String, DumpResult<()>>("run")
+                if should_accept && is_import(&batch) {
+                    mocker.when::<(DumpImportSource, ImportConflictPolicy), DumpResult<TaskResult>>("import")
+                    .once()
+                    .then(|_| Ok(TaskResult::Other));
+                } else if should_accept {
+                    mocker.when::<(String, Option<String>), DumpResult<()>>("run")
                     .once()
                     .then(|_| Ok(()));
                 }
@@ -108,8 +137,12 @@ mod test {
                 let should_accept = matches!(batch.content, BatchContent::Dump { .. });
 
                 let mocker = Mocker::default();
-                if should_accept {
-                    mocker.when::<String, DumpResult<()>>("run")
+                if should_accept && is_import(&batch) {
+                    mocker.when::<(DumpImportSource, ImportConflictPolicy), DumpResult<TaskResult>>("import")
+                    .once()
+                    .then(|_| Err(DumpError::Internal("error".into())));
+                } else if should_accept {
+                    mocker.when::<(String, Option<String>), DumpResult<()>>("run")
                     .once()
                     .then(|_| Err(DumpError::Internal("error".into())));
                 }
@@ -19,10 +19,11 @@ where
     async fn process_batch(&self, mut batch: Batch) -> Batch {
         match batch.content {
             BatchContent::DocumentsAdditionBatch(ref mut tasks) => {
-                self.process_document_addition_batch(tasks).await;
+                self.process_document_addition_batch(tasks, &batch.cancelation)
+                    .await;
             }
             BatchContent::IndexUpdate(ref mut task) => {
-                self.process_task(task).await;
+                self.process_task(task, &batch.cancelation).await;
             }
             _ => unreachable!(),
         }
@@ -31,6 +32,11 @@ where
     }
 
     async fn finish(&self, batch: &Batch) {
+        // the followers replaying the additions download their update files.
+        if self.keeps_update_files() {
+            return;
+        }
+
         if let BatchContent::DocumentsAdditionBatch(ref tasks) = batch.content {
             for task in tasks {
                 if let Some(content_uuid) = task.get_content_uuid() {
@@ -52,7 +58,7 @@ mod test {
     };
     use crate::tasks::{
         handlers::test::task_to_batch,
-        task::{Task, TaskContent},
+        task::{Task, TaskContent, TaskPriority},
     };
     use crate::update_file_store::{Result as FileStoreResult, UpdateFileStore};
 
@@ -107,12 +113,17 @@ mod test {
             content: TaskContent::DocumentAddition {
                 content_uuid,
                 merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                merge_patch: false,
                 primary_key: None,
                 documents_count: 100,
                 allow_index_creation: true,
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
         let batch = task_to_batch(task);
@@ -133,8 +144,13 @@ mod test {
             id: 1,
             content: TaskContent::Dump {
                 uid: String::from("hello"),
+                incremental_from: None,
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
         let batch = task_to_batch(task);
@@ -174,7 +190,7 @@ mod test {
                     TaskContent::DocumentAddition { .. } => {
                         mocker.when::<&mut [Task], ()>("process_document_addition_batch").then(|_| ());
                     }
-                    TaskContent::Dump { .. } => (),
+                    TaskContent::Dump { .. } | TaskContent::DumpImport { .. } => (),
                     _ => {
                         mocker.when::<&mut Task, ()>("process_task").then(|_| ());
                     }
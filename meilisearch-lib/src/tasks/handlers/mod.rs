@@ -18,17 +18,25 @@ mod test {
                 BatchContent::DocumentsAdditionBatch(vec![task])
             }
             TaskContent::DocumentDeletion { .. }
+            | TaskContent::DocumentEdition { .. }
+            | TaskContent::DocumentRestoration { .. }
             | TaskContent::SettingsUpdate { .. }
             | TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
-            | TaskContent::IndexUpdate { .. } => BatchContent::IndexUpdate(task),
-            TaskContent::Dump { .. } => BatchContent::Dump(task),
+            | TaskContent::IndexUpdate { .. }
+            | TaskContent::IndexSwap { .. }
+            | TaskContent::IndexClone { .. } => BatchContent::IndexUpdate(task),
+            TaskContent::Dump { .. } | TaskContent::DumpImport { .. } => BatchContent::Dump(task),
+            TaskContent::TaskCancelation { .. } | TaskContent::TaskDeletion { .. } => {
+                BatchContent::Empty
+            }
         };
 
         Batch {
             id: Some(1),
             created_at: OffsetDateTime::now_utc(),
             content,
+            cancelation: Default::default(),
         }
     }
 }
@@ -2,8 +2,10 @@ use async_trait::async_trait;
 
 pub use handlers::empty_handler::EmptyBatchHandler;
 pub use handlers::snapshot_handler::SnapshotHandler;
+pub use retention::TaskRetentionService;
 pub use scheduler::Scheduler;
 pub use task_store::TaskFilter;
+pub use webhook::TaskWebhook;
 
 #[cfg(test)]
 pub use task_store::test::MockTaskStore as TaskStore;
@@ -16,10 +18,12 @@ use error::Result;
 pub mod batch;
 pub mod error;
 mod handlers;
+mod retention;
 mod scheduler;
 pub mod task;
 mod task_store;
 pub mod update_loop;
+mod webhook;
 
 #[cfg_attr(test, mockall::automock(type Error=test::DebugError;))]
 #[async_trait]
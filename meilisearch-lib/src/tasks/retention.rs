@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+use super::error::Result;
+use super::{Scheduler, TaskFilter};
+
+/// How often the task history is checked for tasks to delete.
+const RETENTION_CHECK_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically deletes the tasks that finished before the retention period.
+pub struct TaskRetentionService {
+    pub(crate) scheduler: Arc<RwLock<Scheduler>>,
+    pub(crate) retention: Duration,
+}
+
+impl TaskRetentionService {
+    pub async fn run(self) {
+        info!("Finished tasks are kept for {}s.", self.retention.as_secs());
+        loop {
+            if let Err(e) = self.delete_expired_tasks().await {
+                error!("an error occured while deleting the expired tasks: {}", e);
+            }
+            sleep(RETENTION_CHECK_PERIOD).await;
+        }
+    }
+
+    async fn delete_expired_tasks(&self) -> Result<()> {
+        let before = OffsetDateTime::now_utc() - self.retention;
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(move |task| task.finished_at().map_or(false, |at| at < before));
+
+        let scheduler = self.scheduler.read().await;
+        let ids = scheduler.finished_tasks(filter).await?;
+        // Don't register a deletion task every period when there is nothing to delete.
+        if !ids.is_empty() {
+            let query = format!("beforeFinishedAt={}", before.format(&Rfc3339)?);
+            let task = scheduler.delete_tasks(query, ids).await?;
+            info!("Deleted the tasks that expired with the task {}.", task.id);
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque};
+use std::collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::ops::{Deref, DerefMut};
 use std::slice;
 use std::sync::Arc;
@@ -8,22 +8,36 @@ use std::time::Duration;
 use atomic_refcell::AtomicRefCell;
 use milli::update::IndexDocumentsMethod;
 use time::OffsetDateTime;
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{broadcast, watch, RwLock};
 
 use crate::options::SchedulerConfig;
 use crate::snapshot::SnapshotJob;
 
-use super::batch::{Batch, BatchContent};
-use super::error::Result;
-use super::task::{Task, TaskContent, TaskEvent, TaskId};
+use super::batch::{Batch, BatchContent, Cancelation};
+use super::error::{Result, TaskError};
+use super::task::{GroupUid, Task, TaskContent, TaskEvent, TaskId, TaskPriority};
 use super::update_loop::UpdateLoop;
 use super::{BatchHandler, TaskFilter, TaskStore};
 
+/// The number of finished tasks notifications a subscriber can lag behind.
+const FINISHED_TASKS_CHANNEL_CAPACITY: usize = 1024;
+
 #[derive(Eq, Debug, Clone, Copy)]
 enum TaskType {
-    DocumentAddition { number: usize },
-    DocumentUpdate { number: usize },
+    DocumentAddition {
+        number: usize,
+    },
+    DocumentUpdate {
+        number: usize,
+    },
+    DocumentMergePatch {
+        number: usize,
+    },
     IndexUpdate,
+    /// A task changing several indexes at once, such as an index swap, is a barrier: it is in the
+    /// task lists of all its indexes, and is only scheduled once it is the first task of each of
+    /// them, whatever the priority of the tasks registered after it.
+    Barrier,
     Dump,
 }
 
@@ -34,6 +48,10 @@ impl PartialEq for TaskType {
             (self, other),
             (Self::DocumentAddition { .. }, Self::DocumentAddition { .. })
                 | (Self::DocumentUpdate { .. }, Self::DocumentUpdate { .. })
+                | (
+                    Self::DocumentMergePatch { .. },
+                    Self::DocumentMergePatch { .. }
+                )
         )
     }
 }
@@ -42,6 +60,8 @@ impl PartialEq for TaskType {
 struct PendingTask {
     kind: TaskType,
     id: TaskId,
+    priority: TaskPriority,
+    group: Option<GroupUid>,
 }
 
 impl PartialEq for PendingTask {
@@ -66,6 +86,8 @@ impl Ord for PendingTask {
 struct TaskList {
     id: TaskListIdentifier,
     tasks: BinaryHeap<PendingTask>,
+    /// The highest priority of the tasks in the list.
+    priority: TaskPriority,
 }
 
 impl Deref for TaskList {
@@ -87,8 +109,20 @@ impl TaskList {
         Self {
             id,
             tasks: Default::default(),
+            priority: TaskPriority::Low,
         }
     }
+
+    /// Recomputes the priority of the list from the tasks it contains, returns whether it changed.
+    fn update_priority(&mut self) -> bool {
+        let priority = self
+            .tasks
+            .iter()
+            .map(|task| task.priority)
+            .max()
+            .unwrap_or(TaskPriority::Low);
+        std::mem::replace(&mut self.priority, priority) != priority
+    }
 }
 
 impl PartialEq for TaskList {
@@ -102,14 +136,15 @@ impl Eq for TaskList {}
 impl Ord for TaskList {
     fn cmp(&self, other: &Self) -> Ordering {
         match (&self.id, &other.id) {
-            (TaskListIdentifier::Index(_), TaskListIdentifier::Index(_)) => {
-                match (self.peek(), other.peek()) {
+            (TaskListIdentifier::Index(_), TaskListIdentifier::Index(_)) => self
+                .priority
+                .cmp(&other.priority)
+                .then_with(|| match (self.peek(), other.peek()) {
                     (None, None) => Ordering::Equal,
                     (None, Some(_)) => Ordering::Less,
                     (Some(_), None) => Ordering::Greater,
                     (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
-                }
-            }
+                }),
             (TaskListIdentifier::Index(_), TaskListIdentifier::Dump) => Ordering::Less,
             (TaskListIdentifier::Dump, TaskListIdentifier::Index(_)) => Ordering::Greater,
             (TaskListIdentifier::Dump, TaskListIdentifier::Dump) => {
@@ -136,13 +171,21 @@ impl From<&Task> for TaskListIdentifier {
         match &task.content {
             TaskContent::DocumentAddition { index_uid, .. }
             | TaskContent::DocumentDeletion { index_uid, .. }
+            | TaskContent::DocumentEdition { index_uid, .. }
+            | TaskContent::DocumentRestoration { index_uid, .. }
             | TaskContent::SettingsUpdate { index_uid, .. }
             | TaskContent::IndexDeletion { index_uid }
             | TaskContent::IndexCreation { index_uid, .. }
             | TaskContent::IndexUpdate { index_uid, .. } => {
                 TaskListIdentifier::Index(index_uid.as_str().to_string())
             }
-            TaskContent::Dump { .. } => TaskListIdentifier::Dump,
+            TaskContent::IndexSwap { .. } | TaskContent::IndexClone { .. } => {
+                unreachable!("the barriers are in the task lists of their indexes")
+            }
+            TaskContent::Dump { .. } | TaskContent::DumpImport { .. } => TaskListIdentifier::Dump,
+            TaskContent::TaskCancelation { .. } | TaskContent::TaskDeletion { .. } => {
+                unreachable!("task cancelations and deletions are never enqueued")
+            }
         }
     }
 }
@@ -158,9 +201,21 @@ struct TaskQueue {
 impl TaskQueue {
     fn insert(&mut self, task: Task) {
         let id = task.id;
-        let uid = TaskListIdentifier::from(&task);
+        let priority = task.priority;
+        let group = task.group_uid;
+        let uids = match barrier_indexes(&task.content) {
+            Some(indexes) => indexes.into_iter().map(TaskListIdentifier::Index).collect(),
+            None => vec![TaskListIdentifier::from(&task)],
+        };
 
         let kind = match task.content {
+            TaskContent::DocumentAddition {
+                documents_count,
+                merge_patch: true,
+                ..
+            } => TaskType::DocumentMergePatch {
+                number: documents_count,
+            },
             TaskContent::DocumentAddition {
                 documents_count,
                 merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
@@ -175,16 +230,32 @@ impl TaskQueue {
             } => TaskType::DocumentUpdate {
                 number: documents_count,
             },
-            TaskContent::Dump { .. } => TaskType::Dump,
+            TaskContent::Dump { .. } | TaskContent::DumpImport { .. } => TaskType::Dump,
             TaskContent::DocumentDeletion { .. }
+            | TaskContent::DocumentEdition { .. }
+            | TaskContent::DocumentRestoration { .. }
             | TaskContent::SettingsUpdate { .. }
             | TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
             | TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
+            TaskContent::IndexSwap { .. } | TaskContent::IndexClone { .. } => TaskType::Barrier,
             _ => unreachable!("unhandled task type"),
         };
-        let task = PendingTask { kind, id };
+        for uid in uids {
+            self.insert_pending(
+                uid,
+                PendingTask {
+                    kind,
+                    id,
+                    priority,
+                    group,
+                },
+            );
+        }
+    }
 
+    fn insert_pending(&mut self, uid: TaskListIdentifier, task: PendingTask) {
+        let id = task.id;
         match self.index_tasks.entry(uid) {
             Entry::Occupied(entry) => {
                 // A task list already exists for this index, all we have to to is to push the new
@@ -197,10 +268,16 @@ impl TaskQueue {
                 assert!(list.peek().map(|old_id| id >= old_id.id).unwrap_or(true));
 
                 list.push(task);
+                // A higher priority moves the list in the queue.
+                if list.update_priority() {
+                    drop(list);
+                    self.reorder();
+                }
             }
             Entry::Vacant(entry) => {
                 let mut task_list = TaskList::new(entry.key().clone());
                 task_list.push(task);
+                task_list.update_priority();
                 let task_list = Arc::new(AtomicRefCell::new(task_list));
                 entry.insert(task_list.clone());
                 self.queue.push(task_list);
@@ -208,13 +285,129 @@ impl TaskQueue {
         }
     }
 
+    /// Changes the priority of the enqueued task `id`, returns false if the task isn't in the queue.
+    fn set_priority(&mut self, id: TaskId, priority: TaskPriority) -> bool {
+        // a barrier is in the task lists of all its indexes.
+        let lists: Vec<_> = self
+            .index_tasks
+            .values()
+            .filter(|list| list.borrow().tasks.iter().any(|task| task.id == id))
+            .cloned()
+            .collect();
+        if lists.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        for list in lists {
+            let mut list = list.borrow_mut();
+            let mut tasks = std::mem::take(&mut list.tasks).into_vec();
+            tasks
+                .iter_mut()
+                .filter(|task| task.id == id)
+                .for_each(|task| task.priority = priority);
+            list.tasks = tasks.into();
+            changed |= list.update_priority();
+        }
+        if changed {
+            self.reorder();
+        }
+        true
+    }
+
+    /// Rebuilds the queue after the priority of one of its task lists changed.
+    fn reorder(&mut self) {
+        let lists = std::mem::take(&mut self.queue).into_vec();
+        self.queue = lists.into();
+    }
+
+    /// Removes the tasks with the given ids from the queue.
+    fn remove(&mut self, ids: &HashSet<TaskId>) {
+        let mut remaining: Vec<_> = self
+            .index_tasks
+            .drain()
+            .flat_map(|(uid, list)| {
+                let tasks = std::mem::take(&mut list.borrow_mut().tasks);
+                tasks
+                    .into_vec()
+                    .into_iter()
+                    .filter(|task| !ids.contains(&task.id))
+                    .map(move |task| (uid.clone(), task))
+            })
+            .collect();
+        self.queue.clear();
+
+        // The task lists must be rebuilt in increasing id order to keep the queue ordered.
+        remaining.sort_unstable_by_key(|(_, task)| task.id);
+        for (uid, task) in remaining {
+            self.insert_pending(uid, task);
+        }
+    }
+
+    /// Returns the task list to schedule next: the head of the queue, unless its first task is a
+    /// barrier preceded by pending tasks on another of its indexes. The list of the earliest of
+    /// these tasks is scheduled first, so that the barrier is processed after all the tasks
+    /// registered before it on its indexes, and before all the ones registered after it.
+    fn next(&self) -> Option<Arc<AtomicRefCell<TaskList>>> {
+        let mut next = self.queue.peek()?.clone();
+        loop {
+            let barrier = next
+                .borrow()
+                .peek()
+                .filter(|task| matches!(task.kind, TaskType::Barrier))
+                .map(|task| task.id);
+            let barrier = match barrier {
+                Some(barrier) => barrier,
+                None => return Some(next),
+            };
+            let earliest = self
+                .index_tasks
+                .values()
+                .filter(|list| list.borrow().iter().any(|task| task.id == barrier))
+                .min_by_key(|list| list.borrow().peek().map(|task| task.id))
+                .cloned();
+            match earliest {
+                Some(list) if list.borrow().peek().map(|task| task.id) != Some(barrier) => {
+                    next = list
+                }
+                _ => return Some(next),
+            }
+        }
+    }
+
+    /// Removes the scheduled barrier `id` from the task lists of its indexes, where it is the
+    /// first task.
+    fn pop_barrier(&mut self, id: TaskId) {
+        self.index_tasks.retain(|_, list| {
+            let mut list = list.borrow_mut();
+            if list.peek().map_or(false, |task| task.id == id) {
+                list.pop();
+                list.update_priority();
+            }
+            !list.is_empty()
+        });
+        self.queue = self.index_tasks.values().cloned().collect();
+    }
+
     /// Passes a context with a view to the task list of the next index to schedule. It is
-    /// guaranteed that the first id from task list will be the lowest pending task id.
+    /// guaranteed that the first id from task list will be the lowest pending task id of the
+    /// indexes it concerns.
     fn head_mut<R>(&mut self, mut f: impl FnMut(&mut TaskList) -> R) -> Option<R> {
-        let head = self.queue.pop()?;
+        let head = self.next()?;
+        if Arc::ptr_eq(self.queue.peek()?, &head) {
+            self.queue.pop();
+        } else {
+            let lists = std::mem::take(&mut self.queue).into_vec();
+            self.queue = lists
+                .into_iter()
+                .filter(|list| !Arc::ptr_eq(list, &head))
+                .collect();
+        }
         let result = {
             let mut ref_head = head.borrow_mut();
-            f(&mut *ref_head)
+            let result = f(&mut *ref_head);
+            ref_head.update_priority();
+            result
         };
         if !head.borrow().tasks.is_empty() {
             // After being mutated, the head is reinserted to the correct position.
@@ -238,10 +431,18 @@ pub struct Scheduler {
 
     store: TaskStore,
     processing: Processing,
+    /// The cancelation of the batch being processed
+    cancelation: Cancelation,
     next_fetched_task_id: TaskId,
     config: SchedulerConfig,
+    /// When paused, no new batch of tasks is started.
+    paused: bool,
     /// Notifies the update loop that a new task was received
     notifier: watch::Sender<()>,
+    /// Broadcasts the tasks that reached a terminal state
+    finished_tasks: broadcast::Sender<Vec<Task>>,
+    /// Called with the tasks of each processed batch, before they are stored
+    finished_hooks: Vec<Box<dyn Fn(&[Task]) + Send + Sync>>,
 }
 
 impl Scheduler {
@@ -251,6 +452,7 @@ impl Scheduler {
         mut config: SchedulerConfig,
     ) -> Result<Arc<RwLock<Self>>> {
         let (notifier, rcv) = watch::channel(());
+        let (finished_tasks, _) = broadcast::channel(FINISHED_TASKS_CHANNEL_CAPACITY);
 
         let debounce_time = config.debounce_duration_sec;
 
@@ -265,9 +467,13 @@ impl Scheduler {
 
             store,
             processing: Processing::Nothing,
+            cancelation: Cancelation::default(),
             next_fetched_task_id: 0,
             config,
+            paused: false,
             notifier,
+            finished_tasks,
+            finished_hooks: Vec::new(),
         };
 
         // Notify update loop to start processing pending updates immediately after startup.
@@ -295,12 +501,72 @@ impl Scheduler {
     /// Clears the processing list, this method should be called when the processing of a batch is finished.
     pub fn finish(&mut self) {
         self.processing = Processing::Nothing;
+        self.cancelation = Cancelation::default();
     }
 
     pub fn notify(&self) {
         let _ = self.notifier.send(());
     }
 
+    /// Stops starting new batches of tasks, the batch being processed is finished. The snapshots
+    /// are still performed.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        if !paused {
+            self.notify();
+        }
+    }
+
+    /// Returns whether the update loop is running, the loop holds the only receiver of the
+    /// notifier.
+    pub fn is_running(&self) -> bool {
+        !self.notifier.is_closed()
+    }
+
+    /// Changes the priority of the enqueued task `id`, and stores it with the task so it is kept
+    /// when the engine restarts. Returns false if the task isn't enqueued, either because it
+    /// doesn't exist, it is being processed or it is already finished.
+    pub async fn set_priority(&mut self, id: TaskId, priority: TaskPriority) -> Result<bool> {
+        // The task may have been registered after the last fetch.
+        self.fetch_pending_tasks().await?;
+        if !self.tasks.set_priority(id, priority) {
+            return Ok(false);
+        }
+
+        // The task can't be picked by the update loop while the scheduler is borrowed.
+        let mut task = self.store.get_task(id, None).await?;
+        task.priority = priority;
+        self.store.update_tasks(vec![task]).await?;
+
+        Ok(true)
+    }
+
+    /// Returns a receiver of the tasks that reach a terminal state from now on. A receiver that
+    /// doesn't keep up misses the oldest notifications.
+    pub fn subscribe_finished_tasks(&self) -> broadcast::Receiver<Vec<Task>> {
+        self.finished_tasks.subscribe()
+    }
+
+    /// Registers a `hook` called with the tasks of each processed batch, before their new state
+    /// is stored, so the hook is done when the tasks are seen as finished.
+    pub fn on_finished(&mut self, hook: impl Fn(&[Task]) + Send + Sync + 'static) {
+        self.finished_hooks.push(Box::new(hook));
+    }
+
+    pub fn run_finished_hooks(&self, tasks: &[Task]) {
+        for hook in &self.finished_hooks {
+            hook(tasks);
+        }
+    }
+
+    /// Broadcasts the finished tasks among `tasks` to the subscribers, if any.
+    pub fn notify_finished(&self, tasks: &[Task]) {
+        let finished: Vec<_> = tasks.iter().filter(|t| t.is_finished()).cloned().collect();
+        if !finished.is_empty() && self.finished_tasks.receiver_count() > 0 {
+            let _ = self.finished_tasks.send(finished);
+        }
+    }
+
     fn notify_if_not_empty(&self) {
         if !self.snapshots.is_empty() || !self.tasks.is_empty() {
             self.notify();
@@ -349,6 +615,75 @@ impl Scheduler {
         Ok(tasks)
     }
 
+    /// Cancels the unfinished tasks matching the `filter`, and returns the `TaskCancelation` task
+    /// along with the canceled enqueued tasks.
+    ///
+    /// The tasks of a batch are committed together, canceling one of the tasks being processed
+    /// cancels the whole batch, which stops before committing its changes. Only the document
+    /// additions and the settings updates can be stopped this way.
+    pub async fn cancel_tasks(
+        &mut self,
+        query: String,
+        filter: TaskFilter,
+    ) -> Result<(Task, Vec<Task>)> {
+        // Make sure all the tasks that could be canceled are in the queue before removing them.
+        self.fetch_pending_tasks().await?;
+
+        let processing: HashSet<_> = self.processing.ids().collect();
+        let (selected_processing, enqueued): (Vec<_>, Vec<_>) = self
+            .store
+            .list_tasks(None, Some(filter), None)
+            .await?
+            .into_iter()
+            .filter(|task| !task.is_finished())
+            .partition(|task| processing.contains(&task.id));
+
+        if let Some(task) = selected_processing.iter().find(|task| !is_abortable(task)) {
+            return Err(TaskError::TaskNotCancelable(task.id));
+        }
+        let processing = if selected_processing.is_empty() {
+            Vec::new()
+        } else {
+            self.processing.ids().collect()
+        };
+
+        let ids = enqueued.into_iter().map(|task| task.id).collect();
+        let (task, canceled) = self
+            .store
+            .cancel_tasks(query, ids, processing.clone())
+            .await?;
+        if !processing.is_empty() {
+            self.cancelation.cancel(task.id);
+        }
+        let ids = canceled.iter().map(|task| task.id).collect();
+        self.tasks.remove(&ids);
+
+        let mut finished = canceled.clone();
+        finished.push(task.clone());
+        self.notify_finished(&finished);
+
+        Ok((task, canceled))
+    }
+
+    /// Returns the ids of the finished tasks matching the `filter`.
+    pub async fn finished_tasks(&self, filter: TaskFilter) -> Result<Vec<TaskId>> {
+        Ok(self
+            .store
+            .list_tasks(None, Some(filter), None)
+            .await?
+            .into_iter()
+            .filter(Task::is_finished)
+            .map(|task| task.id)
+            .collect())
+    }
+
+    /// Deletes the finished tasks among `ids`, and returns the `TaskDeletion` task recording them.
+    pub async fn delete_tasks(&self, query: String, ids: Vec<TaskId>) -> Result<Task> {
+        let task = self.store.delete_tasks(query, ids).await?;
+        self.notify_finished(slice::from_ref(&task));
+        Ok(task)
+    }
+
     pub fn schedule_snapshot(&mut self, job: SnapshotJob) {
         self.snapshots.push_back(job);
         self.notify();
@@ -377,6 +712,10 @@ impl Scheduler {
             return Ok(batch);
         }
 
+        if self.paused {
+            return Ok(Batch::empty());
+        }
+
         // Try to fill the queue with pending tasks.
         self.fetch_pending_tasks().await?;
 
@@ -405,6 +744,7 @@ impl Scheduler {
             self.processing = processing;
 
             let batch = Batch::new(Some(id), content);
+            self.cancelation = batch.cancelation.clone();
 
             // There is more work to do, notify the update loop
             self.notify_if_not_empty();
@@ -473,13 +813,61 @@ impl Processing {
     }
 }
 
+/// Returns the indexes of the tasks that are barriers across several indexes, `None` for the
+/// tasks of a single task list.
+///
+/// The snapshots and the dumps aren't barriers: they are scheduled alone, between two batches,
+/// and cover all the indexes at once, the tasks still enqueued being saved along with them.
+fn barrier_indexes(content: &TaskContent) -> Option<Vec<String>> {
+    match content {
+        TaskContent::IndexSwap { swaps } => Some(
+            swaps
+                .iter()
+                .flat_map(|(left, right)| [left, right])
+                .map(|uid| uid.as_str().to_string())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect(),
+        ),
+        // the destination must not be changed by the tasks registered before the clone.
+        TaskContent::IndexClone {
+            index_uid,
+            destination,
+            ..
+        } => Some(vec![
+            index_uid.as_str().to_string(),
+            destination.as_str().to_string(),
+        ]),
+        _ => None,
+    }
+}
+
+/// Whether the processing of `task` can be stopped before its changes are committed.
+fn is_abortable(task: &Task) -> bool {
+    matches!(
+        task.content,
+        TaskContent::DocumentAddition { .. } | TaskContent::SettingsUpdate { .. }
+    )
+}
+
 fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
     let mut doc_count = 0;
-    tasks
+    let mut barrier = None;
+    let processing = tasks
         .head_mut(|list| match list.peek().copied() {
+            Some(PendingTask {
+                kind: TaskType::Barrier,
+                id,
+                ..
+            }) => {
+                list.pop();
+                barrier = Some(id);
+                Processing::IndexUpdate(id)
+            }
             Some(PendingTask {
                 kind: TaskType::IndexUpdate,
                 id,
+                ..
             }) => {
                 list.pop();
                 Processing::IndexUpdate(id)
@@ -487,15 +875,24 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             Some(PendingTask {
                 kind: TaskType::Dump,
                 id,
+                ..
             }) => {
                 list.pop();
                 Processing::Dump(id)
             }
-            Some(PendingTask { kind, .. }) => {
+            Some(PendingTask { kind, group, .. }) => {
                 let mut task_list = Vec::new();
                 loop {
                     match list.peek() {
-                        Some(pending) if pending.kind == kind => {
+                        // The document operations of a group are processed together, so they are
+                        // applied in the same write transaction regardless of the limits, and
+                        // never along the tasks of another group.
+                        Some(pending) if pending.kind == kind && pending.group == group => {
+                            if group.is_some() {
+                                task_list.push(list.pop().unwrap().id);
+                                continue;
+                            }
+
                             // We always need to process at least one task for the scheduler to make progress.
                             if task_list.len() >= config.max_batch_size.unwrap_or(usize::MAX).max(1)
                             {
@@ -510,7 +907,8 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
                             // We check that bound only after adding the current task to the batch, so that a batch contains at least one task.
                             match pending.kind {
                                 TaskType::DocumentUpdate { number }
-                                | TaskType::DocumentAddition { number } => {
+                                | TaskType::DocumentAddition { number }
+                                | TaskType::DocumentMergePatch { number } => {
                                     doc_count += number;
 
                                     if doc_count
@@ -529,7 +927,14 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             }
             None => Processing::Nothing,
         })
-        .unwrap_or(Processing::Nothing)
+        .unwrap_or(Processing::Nothing);
+
+    // The barrier is also the first task of the lists of its other indexes.
+    if let Some(id) = barrier {
+        tasks.pop_barrier(id);
+    }
+
+    processing
 }
 
 #[cfg(test)]
@@ -547,6 +952,10 @@ mod test {
             id,
             content,
             events: vec![],
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         }
     }
 
@@ -578,10 +987,94 @@ mod test {
         assert!(queue.queue.is_empty());
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn remove_tasks_from_queue() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
+        queue.insert(gen_task(1, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
+        queue.insert(gen_task(3, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+
+        queue.remove(&HashSet::from([0, 3]));
+
+        let test2_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test2_tasks, &[1]);
+
+        let test1_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test1_tasks, &[2]);
+
+        queue.remove(&HashSet::new());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn prioritize_tasks_in_queue() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
+        queue.insert(gen_task(1, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test3") }));
+        queue.insert(gen_task(3, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+
+        assert!(queue.set_priority(2, TaskPriority::High));
+        assert!(queue.set_priority(3, TaskPriority::Urgent));
+        assert!(!queue.set_priority(4, TaskPriority::Urgent));
+
+        // The tasks of an index keep their order, even when a later one is prioritized.
+        let test2_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test2_tasks, &[1, 3]);
+
+        let test3_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test3_tasks, &[2]);
+
+        // Lowering the priority of a task moves its index after the others.
+        queue.insert(gen_task(4, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test4") }));
+        queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test4") }));
+        assert!(queue.set_priority(5, TaskPriority::High));
+        queue.head_mut(|tasks| tasks.pop()).unwrap();
+        assert!(queue.set_priority(5, TaskPriority::Low));
+
+        let test1_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test1_tasks, &[0]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn register_tasks_with_their_priority() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
+        let mut task = gen_task(1, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") });
+        task.priority = TaskPriority::High;
+        queue.insert(task);
+
+        // The priority registered with the task is kept by the queue.
+        let test2_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test2_tasks, &[1]);
+
+        let test1_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test1_tasks, &[0]);
+    }
+
     fn gen_doc_addition_task_content(index_uid: &str) -> TaskContent {
         TaskContent::DocumentAddition {
             content_uuid: Uuid::new_v4(),
             merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+            merge_patch: false,
             primary_key: Some("test".to_string()),
             documents_count: 0,
             allow_index_creation: true,
@@ -601,7 +1094,10 @@ mod test {
         queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1")}));
         queue.insert(gen_task(6, gen_doc_addition_task_content("test2")));
         queue.insert(gen_task(7, gen_doc_addition_task_content("test1")));
-        queue.insert(gen_task(8, TaskContent::Dump { uid: "adump".to_owned() }));
+        queue.insert(gen_task(8, TaskContent::Dump {
+                uid: "adump".to_owned(),
+                incremental_from: None,
+            }));
 
         let config = SchedulerConfig::default();
 
@@ -629,4 +1125,116 @@ mod test {
 
         assert!(queue.is_empty());
     }
+
+    #[test]
+    fn make_batch_of_group() {
+        let grouped = |id, group| Task {
+            group_uid: Some(group),
+            ..gen_task(id, gen_doc_addition_task_content("test1"))
+        };
+
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(grouped(1, 0));
+        queue.insert(grouped(2, 0));
+        queue.insert(grouped(3, 0));
+        queue.insert(grouped(4, 1));
+        queue.insert(gen_task(5, gen_doc_addition_task_content("test1")));
+
+        let config = SchedulerConfig {
+            max_batch_size: Some(2),
+            ..Default::default()
+        };
+
+        // The tasks of a group are processed together, beyond the limits of the batches, and
+        // apart from the others.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![1, 2, 3]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![4]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![5]));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn make_batch_around_index_swap() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(
+            2,
+            TaskContent::IndexSwap {
+                swaps: vec![(
+                    IndexUid::new_unchecked("test1"),
+                    IndexUid::new_unchecked("test2"),
+                )],
+            },
+        ));
+        queue.insert(Task {
+            priority: TaskPriority::High,
+            ..gen_task(3, gen_doc_addition_task_content("test1"))
+        });
+        queue.insert(gen_task(4, gen_doc_addition_task_content("test2")));
+
+        let config = SchedulerConfig::default();
+
+        // The tasks registered before the swap on both its indexes are processed before it, and
+        // aren't batched with the ones registered after it, whatever their priority.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![1]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(2));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![3]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![4]));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn make_batch_around_index_clone() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("staging")));
+        queue.insert(gen_task(
+            1,
+            TaskContent::IndexClone {
+                index_uid: IndexUid::new_unchecked("products"),
+                destination: IndexUid::new_unchecked("staging"),
+                settings_only: false,
+            },
+        ));
+        queue.insert(Task {
+            priority: TaskPriority::High,
+            ..gen_task(2, gen_doc_addition_task_content("staging"))
+        });
+
+        let config = SchedulerConfig::default();
+
+        // The clone waits for the tasks registered before it on its destination, and the ones
+        // registered after it on the destination wait for the clone.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(1));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![2]));
+
+        assert!(queue.is_empty());
+    }
 }
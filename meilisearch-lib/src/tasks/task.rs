@@ -6,16 +6,69 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use super::batch::BatchId;
+use crate::dump::{DumpImportSource, ImportConflictPolicy, ImportedIndex};
 use crate::index::{Settings, Unchecked};
 
 pub type TaskId = u32;
 
+/// The uid of a group of tasks registered together through the batch route. Not to be confused
+/// with the `BatchId` of the scheduler, which identifies the tasks processed together: the
+/// consecutive document operations of a group on the same index end up in the same batch.
+pub type GroupUid = u32;
+
+/// The position of a processed batch in the processing order of the tasks, it grows with each
+/// batch whatever the ids and the priorities of its tasks.
+pub type BatchSeq = u32;
+
+/// The priority of an enqueued task.
+///
+/// The tasks of an index are always processed in the order they were registered, the priority
+/// decides which index is scheduled next: the queue of an index containing a task with a higher
+/// priority is processed before the others. The priority is given when the task is registered
+/// and stored with it, so it is kept when the engine restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum TaskPriority {
+    Low,
+    Normal,
+    High,
+    /// Only given to the tasks that are explicitly moved to the front of the queue.
+    Urgent,
+}
+
+impl Default for TaskPriority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub enum TaskResult {
-    DocumentAddition { indexed_documents: u64 },
-    DocumentDeletion { deleted_documents: u64 },
-    ClearAll { deleted_documents: u64 },
+    DocumentAddition {
+        indexed_documents: u64,
+        /// The documents left unchanged because they weren't newer than the stored ones.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        rejected_documents: Vec<String>,
+    },
+    DocumentDeletion {
+        deleted_documents: u64,
+    },
+    ClearAll {
+        deleted_documents: u64,
+    },
+    TaskCancelation {
+        canceled_tasks: u64,
+    },
+    TaskDeletion {
+        deleted_tasks: u64,
+    },
+    DumpImport {
+        /// The group of the tasks recreating the indexes, `None` if every index was skipped.
+        group_uid: Option<GroupUid>,
+        indexes: Vec<ImportedIndex>,
+    },
     Other,
 }
 
@@ -23,6 +76,7 @@ impl From<DocumentAdditionResult> for TaskResult {
     fn from(other: DocumentAdditionResult) -> Self {
         Self::DocumentAddition {
             indexed_documents: other.indexed_documents,
+            rejected_documents: Vec::new(),
         }
     }
 }
@@ -58,6 +112,14 @@ pub enum TaskEvent {
         #[serde(with = "time::serde::rfc3339")]
         timestamp: OffsetDateTime,
     },
+    /// The task was canceled by the `TaskCancelation` task `canceled_by` before its changes were
+    /// committed.
+    Canceled {
+        canceled_by: TaskId,
+        #[cfg_attr(test, proptest(strategy = "test::datetime_strategy()"))]
+        #[serde(with = "time::serde::rfc3339")]
+        timestamp: OffsetDateTime,
+    },
 }
 
 impl TaskEvent {
@@ -90,40 +152,75 @@ pub struct Task {
     // the TaskContent.
     pub content: TaskContent,
     pub events: Vec<TaskEvent>,
+    /// The id of the HTTP request that registered the task.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The priority of the task while it is enqueued, the tasks stored before the priorities
+    /// were persisted are `Normal`.
+    #[serde(default)]
+    pub priority: TaskPriority,
+    /// The group the task was registered with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_uid: Option<GroupUid>,
+    /// The batch that finished the task, once it is finished. The tasks finished before the
+    /// batches were recorded don't have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_seq: Option<BatchSeq>,
 }
 
 impl Task {
     /// Return true when a task is finished.
-    /// A task is finished when its last state is either `Succeeded` or `Failed`.
+    /// A task is finished when its last state is either `Succeeded`, `Failed` or `Canceled`.
     pub fn is_finished(&self) -> bool {
         self.events.last().map_or(false, |event| {
             matches!(
                 event,
-                TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. }
+                TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. } | TaskEvent::Canceled { .. }
             )
         })
     }
 
-    /// Return the content_uuid of the `Task` if there is one.
-    pub fn get_content_uuid(&self) -> Option<Uuid> {
-        match self {
-            Task {
-                content: TaskContent::DocumentAddition { content_uuid, .. },
-                ..
-            } => Some(*content_uuid),
+    /// Returns the date at which the task finished, if it is finished.
+    pub fn finished_at(&self) -> Option<OffsetDateTime> {
+        match self.events.last() {
+            Some(
+                TaskEvent::Succeeded { timestamp, .. }
+                | TaskEvent::Failed { timestamp, .. }
+                | TaskEvent::Canceled { timestamp, .. },
+            ) => Some(*timestamp),
             _ => None,
         }
     }
 
+    /// Returns the date at which the processing of the task started, if it was processed.
+    pub fn started_at(&self) -> Option<OffsetDateTime> {
+        self.events.iter().find_map(|event| match event {
+            TaskEvent::Processing(timestamp) => Some(*timestamp),
+            _ => None,
+        })
+    }
+
+    /// Return the content_uuid of the `Task` if there is one.
+    pub fn get_content_uuid(&self) -> Option<Uuid> {
+        self.content.get_content_uuid()
+    }
+
     pub fn index_uid(&self) -> Option<&str> {
         match &self.content {
             TaskContent::DocumentAddition { index_uid, .. }
             | TaskContent::DocumentDeletion { index_uid, .. }
+            | TaskContent::DocumentEdition { index_uid, .. }
+            | TaskContent::DocumentRestoration { index_uid, .. }
             | TaskContent::SettingsUpdate { index_uid, .. }
             | TaskContent::IndexDeletion { index_uid }
             | TaskContent::IndexCreation { index_uid, .. }
-            | TaskContent::IndexUpdate { index_uid, .. } => Some(index_uid.as_str()),
-            TaskContent::Dump { .. } => None,
+            | TaskContent::IndexUpdate { index_uid, .. }
+            | TaskContent::IndexClone { index_uid, .. } => Some(index_uid.as_str()),
+            TaskContent::IndexSwap { .. }
+            | TaskContent::Dump { .. }
+            | TaskContent::DumpImport { .. }
+            | TaskContent::TaskCancelation { .. }
+            | TaskContent::TaskDeletion { .. } => None,
         }
     }
 }
@@ -133,6 +230,7 @@ impl Task {
 pub enum DocumentDeletion {
     Clear,
     Ids(Vec<String>),
+    Filter(#[cfg_attr(test, proptest(value = "serde_json::Value::Null"))] serde_json::Value),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -145,6 +243,9 @@ pub enum TaskContent {
         content_uuid: Uuid,
         #[cfg_attr(test, proptest(strategy = "test::index_document_method_strategy()"))]
         merge_strategy: IndexDocumentsMethod,
+        /// Whether the documents are JSON Merge Patches applied to the stored documents.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        merge_patch: bool,
         primary_key: Option<String>,
         documents_count: usize,
         allow_index_creation: bool,
@@ -153,6 +254,19 @@ pub enum TaskContent {
         index_uid: IndexUid,
         deletion: DocumentDeletion,
     },
+    /// Applies the rhai `function` to the documents of the index `index_uid` matching `filter`.
+    DocumentEdition {
+        index_uid: IndexUid,
+        #[cfg_attr(test, proptest(value = "None"))]
+        filter: Option<serde_json::Value>,
+        function: String,
+    },
+    /// Adds back the deleted documents of the index `index_uid` that can still be restored, only
+    /// the ones whose ids are in `ids` if given.
+    DocumentRestoration {
+        index_uid: IndexUid,
+        ids: Option<Vec<String>>,
+    },
     SettingsUpdate {
         index_uid: IndexUid,
         settings: Settings<Unchecked>,
@@ -171,9 +285,53 @@ pub enum TaskContent {
         index_uid: IndexUid,
         primary_key: Option<String>,
     },
+    /// Exchanges the two indexes of each pair, along with their documents and settings. All the
+    /// pairs are swapped at once, or none of them if one index is missing.
+    IndexSwap {
+        swaps: Vec<(IndexUid, IndexUid)>,
+    },
+    /// Creates the index `destination` with the settings and, unless `settings_only` is set, the
+    /// documents of the index `index_uid`.
+    IndexClone {
+        index_uid: IndexUid,
+        destination: IndexUid,
+        settings_only: bool,
+    },
     Dump {
         uid: String,
+        /// The uid of the dump an incremental dump is based on, `None` for a full dump.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        incremental_from: Option<String>,
+    },
+    /// Registers, in a single group, the tasks recreating the indexes of the dump read from
+    /// `source`. The indexes conflicting with an existing index are handled according to the
+    /// `conflict` policy.
+    DumpImport {
+        source: DumpImportSource,
+        conflict: ImportConflictPolicy,
     },
+    /// Cancels the enqueued tasks matching the `query`. This task is never enqueued, it is
+    /// registered already processed along with the cancelation of the `tasks`.
+    TaskCancelation {
+        query: String,
+        tasks: Vec<TaskId>,
+    },
+    /// Deletes the finished tasks matching the `query` from the task history. Like the
+    /// cancelations, this task is registered already processed.
+    TaskDeletion {
+        query: String,
+        tasks: Vec<TaskId>,
+    },
+}
+
+impl TaskContent {
+    /// Return the content_uuid of the `TaskContent` if there is one.
+    pub fn get_content_uuid(&self) -> Option<Uuid> {
+        match self {
+            TaskContent::DocumentAddition { content_uuid, .. } => Some(*content_uuid),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
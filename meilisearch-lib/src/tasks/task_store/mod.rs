@@ -6,16 +6,18 @@ use std::path::Path;
 use std::sync::Arc;
 
 use log::debug;
+use meilisearch_types::index_uid_pattern;
 use milli::heed::{Env, RwTxn};
 use time::OffsetDateTime;
 
 use super::batch::BatchContent;
 use super::error::TaskError;
 use super::scheduler::Processing;
-use super::task::{Task, TaskContent, TaskId};
+use super::task::{BatchSeq, GroupUid, Task, TaskContent, TaskId, TaskPriority, TaskResult};
 use super::Result;
 use crate::tasks::task::TaskEvent;
 use crate::update_file_store::UpdateFileStore;
+use crate::{request_id, task_priority};
 
 #[cfg(test)]
 pub use store::test::MockStore as Store;
@@ -26,12 +28,23 @@ pub use store::Store;
 #[derive(Default)]
 pub struct TaskFilter {
     indexes: Option<HashSet<String>>,
+    /// The index patterns like `products*`, the tasks of the indexes they match also pass.
+    index_patterns: Vec<String>,
     filter_fn: Option<Box<dyn Fn(&Task) -> bool + Sync + Send + 'static>>,
 }
 
 impl TaskFilter {
-    fn pass(&self, task: &Task) -> bool {
+    pub(crate) fn pass(&self, task: &Task) -> bool {
         match task.index_uid() {
+            Some(index_uid) if self.has_index_patterns() => {
+                self.indexes
+                    .as_ref()
+                    .map_or(false, |indexes| indexes.contains(index_uid))
+                    || self
+                        .index_patterns
+                        .iter()
+                        .any(|pattern| index_uid_pattern::matches(pattern, index_uid))
+            }
             Some(index_uid) => self
                 .indexes
                 .as_ref()
@@ -40,15 +53,30 @@ impl TaskFilter {
         }
     }
 
+    /// Returns the indexes the tasks must belong to. The tasks of the indexes matching a pattern
+    /// can't be listed from the indexes, so there are no such indexes when the filter has patterns.
     fn filtered_indexes(&self) -> Option<&HashSet<String>> {
-        self.indexes.as_ref()
+        if self.has_index_patterns() {
+            None
+        } else {
+            self.indexes.as_ref()
+        }
     }
 
-    /// Adds an index to the filter, so the filter must match this index.
+    fn has_index_patterns(&self) -> bool {
+        !self.index_patterns.is_empty()
+    }
+
+    /// Adds an index to the filter, so the filter must match this index. The index may be a
+    /// pattern like `products*`.
     pub fn filter_index(&mut self, index: String) {
-        self.indexes
-            .get_or_insert_with(Default::default)
-            .insert(index);
+        if index.ends_with('*') {
+            self.index_patterns.push(index);
+        } else {
+            self.indexes
+                .get_or_insert_with(Default::default)
+                .insert(index);
+        }
     }
 
     pub fn filter_fn(&mut self, f: impl Fn(&Task) -> bool + Sync + Send + 'static) {
@@ -60,6 +88,30 @@ pub struct TaskStore {
     store: Arc<Store>,
 }
 
+/// Records the tasks finished among `tasks` that aren't recorded yet as the next processed batch,
+/// before they are stored.
+fn record_batch<'a>(
+    store: &Store,
+    txn: &mut RwTxn,
+    tasks: impl IntoIterator<Item = &'a mut Task>,
+) -> Result<()> {
+    let mut finished: Vec<_> = tasks
+        .into_iter()
+        .filter(|task| task.is_finished() && task.batch_seq.is_none())
+        .collect();
+    if finished.is_empty() {
+        return Ok(());
+    }
+
+    let seq = store.next_batch_seq(txn)?;
+    for task in &mut finished {
+        task.batch_seq = Some(seq);
+    }
+    store.put_batch(txn, seq, &finished.iter().map(|task| task.id).collect())?;
+
+    Ok(())
+}
+
 impl Clone for TaskStore {
     fn clone(&self) -> Self {
         Self {
@@ -77,6 +129,8 @@ impl TaskStore {
     pub async fn register(&self, content: TaskContent) -> Result<Task> {
         debug!("registering update: {:?}", content);
         let store = self.store.clone();
+        let request_id = request_id::current();
+        let priority = task_priority::current();
         let task = tokio::task::spawn_blocking(move || -> Result<Task> {
             let mut txn = store.wtxn()?;
             let next_task_id = store.next_task_id(&mut txn)?;
@@ -85,6 +139,10 @@ impl TaskStore {
                 id: next_task_id,
                 content,
                 events: vec![created_at],
+                request_id,
+                priority,
+                group_uid: None,
+                batch_seq: None,
             };
 
             store.put(&mut txn, &task)?;
@@ -97,11 +155,204 @@ impl TaskStore {
         Ok(task)
     }
 
+    /// Registers a task for each of the `contents`, in the same transaction and in order, and
+    /// records them as a group. Returns the uid of the group and its tasks.
+    pub async fn register_group(
+        &self,
+        contents: Vec<TaskContent>,
+    ) -> Result<(GroupUid, Vec<Task>)> {
+        debug!("registering group: {:?}", contents);
+        let store = self.store.clone();
+        let request_id = request_id::current();
+        let priority = task_priority::current();
+        let result = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut txn = store.wtxn()?;
+            let first_task_id = store.next_task_id(&mut txn)?;
+            let uid = store.next_group_uid(&mut txn)?;
+            let created_at = OffsetDateTime::now_utc();
+
+            let tasks: Vec<_> = contents
+                .into_iter()
+                .zip(first_task_id..)
+                .map(|(content, id)| Task {
+                    id,
+                    content,
+                    events: vec![TaskEvent::Created(created_at)],
+                    request_id: request_id.clone(),
+                    priority,
+                    group_uid: Some(uid),
+                    batch_seq: None,
+                })
+                .collect();
+
+            for task in &tasks {
+                store.put(&mut txn, task)?;
+            }
+
+            store.put_group(&mut txn, uid, &tasks.iter().map(|task| task.id).collect())?;
+            txn.commit()?;
+
+            Ok((uid, tasks))
+        })
+        .await??;
+
+        Ok(result)
+    }
+
+    /// Returns the tasks of the registered group `uid` that weren't deleted since.
+    pub async fn get_group(&self, uid: GroupUid) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || -> Result<_> {
+            let txn = store.rtxn()?;
+            let ids = store
+                .get_group(&txn, uid)?
+                .ok_or(TaskError::UnexistingGroup(uid))?;
+
+            let mut tasks = Vec::new();
+            for id in ids {
+                if let Some(task) = store.get(&txn, id)? {
+                    tasks.push(task);
+                }
+            }
+
+            Ok(tasks)
+        })
+        .await?
+    }
+
     pub fn register_raw_update(&self, wtxn: &mut RwTxn, task: &Task) -> Result<()> {
         self.store.put(wtxn, task)?;
         Ok(())
     }
 
+    /// Registers a `TaskCancelation` task canceling the unfinished tasks among `ids`. The
+    /// cancelation task is registered already succeeded, in the same transaction as the canceled
+    /// tasks, so it is never picked by the scheduler. It is recorded as a processed batch along
+    /// with the enqueued tasks it canceled.
+    ///
+    /// The `processing` tasks are counted by the cancelation, but they are marked as canceled by
+    /// the batch processing them, which stops before committing their changes.
+    pub async fn cancel_tasks(
+        &self,
+        query: String,
+        ids: Vec<TaskId>,
+        processing: Vec<TaskId>,
+    ) -> Result<(Task, Vec<Task>)> {
+        let store = self.store.clone();
+        let request_id = request_id::current();
+        let result = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut txn = store.wtxn()?;
+            let next_task_id = store.next_task_id(&mut txn)?;
+            let now = OffsetDateTime::now_utc();
+
+            let mut canceled = Vec::new();
+            for id in ids {
+                match store.get(&txn, id)? {
+                    Some(mut task) if !task.is_finished() => {
+                        task.events.push(TaskEvent::Canceled {
+                            canceled_by: next_task_id,
+                            timestamp: now,
+                        });
+                        canceled.push(task);
+                    }
+                    _ => (),
+                }
+            }
+
+            let mut tasks: Vec<_> = canceled.iter().map(|task| task.id).collect();
+            tasks.extend(processing);
+            tasks.sort_unstable();
+
+            let mut task = Task {
+                id: next_task_id,
+                content: TaskContent::TaskCancelation {
+                    query,
+                    tasks: tasks.clone(),
+                },
+                events: vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::Processing(now),
+                    TaskEvent::Succeeded {
+                        result: TaskResult::TaskCancelation {
+                            canceled_tasks: tasks.len() as u64,
+                        },
+                        timestamp: now,
+                    },
+                ],
+                request_id,
+                priority: TaskPriority::default(),
+                group_uid: None,
+                batch_seq: None,
+            };
+
+            record_batch(&store, &mut txn, canceled.iter_mut().chain(Some(&mut task)))?;
+            for task in canceled.iter().chain(Some(&task)) {
+                store.put(&mut txn, task)?;
+            }
+            txn.commit()?;
+
+            Ok((task, canceled))
+        })
+        .await??;
+
+        Ok(result)
+    }
+
+    /// Deletes the finished tasks among `ids` and registers a `TaskDeletion` task recording them,
+    /// in the same transaction. The deletion task always gets a higher id than the deleted
+    /// tasks, so the task ids are never reused.
+    pub async fn delete_tasks(&self, query: String, ids: Vec<TaskId>) -> Result<Task> {
+        let store = self.store.clone();
+        let request_id = request_id::current();
+        let task = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut txn = store.wtxn()?;
+            let next_task_id = store.next_task_id(&mut txn)?;
+            let now = OffsetDateTime::now_utc();
+
+            let mut deleted = Vec::new();
+            for id in ids {
+                match store.get(&txn, id)? {
+                    Some(task) if task.is_finished() => {
+                        store.delete(&mut txn, &task)?;
+                        deleted.push(task.id);
+                    }
+                    _ => (),
+                }
+            }
+
+            let mut task = Task {
+                id: next_task_id,
+                events: vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::Processing(now),
+                    TaskEvent::Succeeded {
+                        result: TaskResult::TaskDeletion {
+                            deleted_tasks: deleted.len() as u64,
+                        },
+                        timestamp: now,
+                    },
+                ],
+                content: TaskContent::TaskDeletion {
+                    query,
+                    tasks: deleted,
+                },
+                request_id,
+                priority: TaskPriority::default(),
+                group_uid: None,
+                batch_seq: None,
+            };
+
+            record_batch(&store, &mut txn, Some(&mut task))?;
+            store.put(&mut txn, &task)?;
+            txn.commit()?;
+
+            Ok(task)
+        })
+        .await??;
+
+        Ok(task)
+    }
+
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
         let store = self.store.clone();
         let task = tokio::task::spawn_blocking(move || -> Result<_> {
@@ -152,7 +403,10 @@ impl TaskStore {
                 }
                 Processing::Dump(id) => {
                     let task = store.get(&txn, id)?.ok_or(TaskError::UnexistingTask(id))?;
-                    debug_assert!(matches!(task.content, TaskContent::Dump { .. }));
+                    debug_assert!(matches!(
+                        task.content,
+                        TaskContent::Dump { .. } | TaskContent::DumpImport { .. }
+                    ));
                     BatchContent::Dump(task)
                 }
                 Processing::Nothing => BatchContent::Empty,
@@ -165,12 +419,15 @@ impl TaskStore {
         Ok(tasks)
     }
 
-    pub async fn update_tasks(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
+    /// Stores the new state of the `tasks` of a batch. The ones it finished are recorded as the
+    /// next processed batch.
+    pub async fn update_tasks(&self, mut tasks: Vec<Task>) -> Result<Vec<Task>> {
         let store = self.store.clone();
 
         let tasks = tokio::task::spawn_blocking(move || -> Result<_> {
             let mut txn = store.wtxn()?;
 
+            record_batch(&store, &mut txn, &mut tasks)?;
             for task in &tasks {
                 store.put(&mut txn, task)?;
             }
@@ -184,6 +441,41 @@ impl TaskStore {
         Ok(tasks)
     }
 
+    /// Returns at most `limit` processed batches following the batch `after`, or from the first
+    /// one, with their tasks in the order they were processed. The deleted tasks are `None`.
+    pub async fn list_batches(
+        &self,
+        after: Option<BatchSeq>,
+        limit: usize,
+    ) -> Result<Vec<(BatchSeq, Vec<Option<Task>>)>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            let mut batches = Vec::new();
+            for (seq, ids) in store.list_batches(&txn, after, limit)? {
+                let tasks = ids
+                    .into_iter()
+                    .map(|id| store.get(&txn, id))
+                    .collect::<Result<_>>()?;
+                batches.push((seq, tasks));
+            }
+            Ok(batches)
+        })
+        .await?
+    }
+
+    /// Returns the seq of the last processed batch, if any.
+    pub async fn last_batch_seq(&self) -> Result<Option<BatchSeq>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.last_batch_seq(&txn)
+        })
+        .await?
+    }
+
     pub async fn fetch_unfinished_tasks(&self, offset: Option<TaskId>) -> Result<Vec<Task>> {
         let store = self.store.clone();
 
@@ -257,7 +549,10 @@ impl TaskStore {
 
         let mut wtxn = env.write_txn()?;
         for entry in stream {
-            store.register_raw_update(&mut wtxn, &entry?)?;
+            let mut task = entry?;
+            // the batches of the dumped instance aren't imported.
+            task.batch_seq = None;
+            store.register_raw_update(&mut wtxn, &task)?;
         }
         wtxn.commit()?;
 
@@ -318,6 +613,13 @@ pub mod test {
             }
         }
 
+        pub async fn delete_tasks(&self, query: String, ids: Vec<TaskId>) -> Result<Task> {
+            match self {
+                Self::Real(s) => s.delete_tasks(query, ids).await,
+                Self::Mock(m) => unsafe { m.get("delete_tasks").call((query, ids)) },
+            }
+        }
+
         pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
             match self {
                 Self::Real(s) => s.get_task(id, filter).await,
@@ -335,6 +637,24 @@ pub mod test {
             }
         }
 
+        pub async fn list_batches(
+            &self,
+            after: Option<BatchSeq>,
+            limit: usize,
+        ) -> Result<Vec<(BatchSeq, Vec<Option<Task>>)>> {
+            match self {
+                Self::Real(s) => s.list_batches(after, limit).await,
+                Self::Mock(m) => unsafe { m.get("list_batches").call((after, limit)) },
+            }
+        }
+
+        pub async fn last_batch_seq(&self) -> Result<Option<BatchSeq>> {
+            match self {
+                Self::Real(s) => s.last_batch_seq().await,
+                Self::Mock(m) => unsafe { m.get("last_batch_seq").call(()) },
+            }
+        }
+
         pub async fn fetch_unfinished_tasks(&self, from: Option<TaskId>) -> Result<Vec<Task>> {
             match self {
                 Self::Real(s) => s.fetch_unfinished_tasks(from).await,
@@ -361,6 +681,23 @@ pub mod test {
             }
         }
 
+        pub async fn register_group(
+            &self,
+            contents: Vec<TaskContent>,
+        ) -> Result<(GroupUid, Vec<Task>)> {
+            match self {
+                Self::Real(s) => s.register_group(contents).await,
+                Self::Mock(_m) => todo!(),
+            }
+        }
+
+        pub async fn get_group(&self, uid: GroupUid) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.get_group(uid).await,
+                Self::Mock(m) => unsafe { m.get("get_group").call(uid) },
+            }
+        }
+
         pub fn register_raw_update(&self, wtxn: &mut RwTxn, task: &Task) -> Result<()> {
             match self {
                 Self::Real(s) => s.register_raw_update(wtxn, task),
@@ -368,6 +705,18 @@ pub mod test {
             }
         }
 
+        pub async fn cancel_tasks(
+            &self,
+            query: String,
+            ids: Vec<TaskId>,
+            processing: Vec<TaskId>,
+        ) -> Result<(Task, Vec<Task>)> {
+            match self {
+                Self::Real(s) => s.cancel_tasks(query, ids, processing).await,
+                Self::Mock(m) => unsafe { m.get("cancel_tasks").call((query, ids, processing)) },
+            }
+        }
+
         pub fn load_dump(path: impl AsRef<Path>, env: Arc<Env>) -> anyhow::Result<()> {
             TaskStore::load_dump(path, env)
         }
@@ -389,6 +738,10 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
         let mut runner = TestRunner::new(Config::default());
@@ -415,4 +768,101 @@ pub mod test {
             })
             .unwrap();
     }
+
+    #[actix_rt::test]
+    async fn test_register_group() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let content = |index_uid: &str| TaskContent::IndexCreation {
+            primary_key: None,
+            index_uid: IndexUid::new_unchecked(index_uid),
+        };
+
+        store.register(content("test0")).await.unwrap();
+        let (uid, tasks) = store
+            .register_group(vec![content("test1"), content("test2")])
+            .await
+            .unwrap();
+        assert_eq!(uid, 0);
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[1, 2]);
+
+        let (uid, _) = store.register_group(vec![content("test3")]).await.unwrap();
+        assert_eq!(uid, 1);
+
+        let tasks = store.get_group(0).await.unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[1, 2]);
+        assert_eq!(tasks[1].index_uid(), Some("test2"));
+        assert!(tasks.iter().all(|t| t.group_uid == Some(0)));
+
+        assert!(matches!(
+            store.get_group(2).await,
+            Err(TaskError::UnexistingGroup(2))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_record_batches() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let content = |index_uid: &str| TaskContent::IndexCreation {
+            primary_key: None,
+            index_uid: IndexUid::new_unchecked(index_uid),
+        };
+        let succeed = |mut task: Task| {
+            task.events.push(TaskEvent::Succeeded {
+                result: TaskResult::Other,
+                timestamp: OffsetDateTime::now_utc(),
+            });
+            task
+        };
+
+        let first = store.register(content("test0")).await.unwrap();
+        let second = store.register(content("test1")).await.unwrap();
+        let third = store.register(content("test2")).await.unwrap();
+
+        // the unfinished tasks aren't recorded.
+        let mut processing = third.clone();
+        processing
+            .events
+            .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+        store.update_tasks(vec![processing]).await.unwrap();
+        assert_eq!(store.last_batch_seq().await.unwrap(), None);
+
+        // the batches are recorded in the order they are processed, whatever the task ids.
+        let tasks = store.update_tasks(vec![succeed(third)]).await.unwrap();
+        assert_eq!(tasks[0].batch_seq, Some(0));
+        let tasks = store
+            .update_tasks(vec![succeed(first), succeed(second)])
+            .await
+            .unwrap();
+        assert!(tasks.iter().all(|t| t.batch_seq == Some(1)));
+
+        let (canceled, _) = store
+            .cancel_tasks("uids=1".to_string(), vec![1], Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(canceled.batch_seq, Some(2));
+        assert_eq!(store.last_batch_seq().await.unwrap(), Some(2));
+
+        let ids =
+            |tasks: &[Option<Task>]| -> Vec<_> { tasks.iter().flatten().map(|t| t.id).collect() };
+        let batches = store.list_batches(None, 10).await.unwrap();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].0, 0);
+        assert_eq!(ids(&batches[0].1), &[2]);
+        assert_eq!(ids(&batches[1].1), &[0, 1]);
+        assert_eq!(ids(&batches[2].1), &[3]);
+
+        store
+            .delete_tasks("uids=0".to_string(), vec![0])
+            .await
+            .unwrap();
+        let batches = store.list_batches(Some(0), 1).await.unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0, 1);
+        assert!(batches[0].1[0].is_none());
+        assert_eq!(ids(&batches[0].1), &[1]);
+    }
 }
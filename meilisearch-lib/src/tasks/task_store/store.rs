@@ -4,6 +4,8 @@ type BEU32 = milli::heed::zerocopy::U32<milli::heed::byteorder::BE>;
 
 const INDEX_UIDS_TASK_IDS: &str = "index-uids-task-ids";
 const TASKS: &str = "tasks";
+const GROUPS: &str = "groups";
+const BATCHES: &str = "batches";
 
 use std::collections::HashSet;
 use std::ops::Bound::{Excluded, Unbounded};
@@ -15,7 +17,7 @@ use milli::heed::{Database, Env, RoTxn, RwTxn};
 use milli::heed_codec::RoaringBitmapCodec;
 use roaring::RoaringBitmap;
 
-use crate::tasks::task::{Task, TaskId};
+use crate::tasks::task::{BatchSeq, GroupUid, Task, TaskId};
 
 use super::super::Result;
 use super::TaskFilter;
@@ -25,6 +27,11 @@ pub struct Store {
     /// Maps an index uid to the set of tasks ids associated to it.
     index_uid_task_ids: Database<Str, RoaringBitmapCodec>,
     tasks: Database<OwnedType<BEU32>, SerdeJson<Task>>,
+    /// Maps the uid of a group registered through the batch route to the ids of its tasks.
+    groups: Database<OwnedType<BEU32>, RoaringBitmapCodec>,
+    /// Maps the seq of each processed batch to the ids of the tasks it finished, in the order
+    /// the batches were processed.
+    batches: Database<OwnedType<BEU32>, RoaringBitmapCodec>,
 }
 
 impl Drop for Store {
@@ -44,11 +51,15 @@ impl Store {
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let index_uid_task_ids = env.create_database(Some(INDEX_UIDS_TASK_IDS))?;
         let tasks = env.create_database(Some(TASKS))?;
+        let groups = env.create_database(Some(GROUPS))?;
+        let batches = env.create_database(Some(BATCHES))?;
 
         Ok(Self {
             env,
             index_uid_task_ids,
             tasks,
+            groups,
+            batches,
         })
     }
 
@@ -91,11 +102,98 @@ impl Store {
         Ok(())
     }
 
+    /// Removes the task from the store and from the tasks of its index.
+    pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        self.tasks.delete(txn, &BEU32::new(task.id))?;
+        if let Some(index_uid) = task.index_uid() {
+            if let Some(mut tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
+                tasks_set.remove(task.id);
+                if tasks_set.is_empty() {
+                    self.index_uid_task_ids.delete(txn, index_uid)?;
+                } else {
+                    self.index_uid_task_ids.put(txn, index_uid, &tasks_set)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn get(&self, txn: &RoTxn, id: TaskId) -> Result<Option<Task>> {
         let task = self.tasks.get(txn, &BEU32::new(id))?;
         Ok(task)
     }
 
+    /// Returns the uid for the next registered group, see `next_task_id`.
+    pub fn next_group_uid(&self, txn: &mut RwTxn) -> Result<GroupUid> {
+        let uid = self
+            .groups
+            .lazily_decode_data()
+            .last(txn)?
+            .map(|(uid, _)| uid.get() + 1)
+            .unwrap_or(0);
+        Ok(uid)
+    }
+
+    pub fn put_group(&self, txn: &mut RwTxn, uid: GroupUid, tasks: &RoaringBitmap) -> Result<()> {
+        self.groups.put(txn, &BEU32::new(uid), tasks)?;
+        Ok(())
+    }
+
+    pub fn get_group(&self, txn: &RoTxn, uid: GroupUid) -> Result<Option<RoaringBitmap>> {
+        let tasks = self.groups.get(txn, &BEU32::new(uid))?;
+        Ok(tasks)
+    }
+
+    /// Returns the seq for the next processed batch, see `next_task_id`.
+    pub fn next_batch_seq(&self, txn: &mut RwTxn) -> Result<BatchSeq> {
+        let seq = self
+            .batches
+            .lazily_decode_data()
+            .last(txn)?
+            .map(|(seq, _)| seq.get() + 1)
+            .unwrap_or(0);
+        Ok(seq)
+    }
+
+    pub fn put_batch(&self, txn: &mut RwTxn, seq: BatchSeq, tasks: &RoaringBitmap) -> Result<()> {
+        self.batches.put(txn, &BEU32::new(seq), tasks)?;
+        Ok(())
+    }
+
+    /// Returns the seq of the last processed batch, if any.
+    pub fn last_batch_seq(&self, txn: &RoTxn) -> Result<Option<BatchSeq>> {
+        let seq = self
+            .batches
+            .lazily_decode_data()
+            .last(txn)?
+            .map(|(seq, _)| seq.get());
+        Ok(seq)
+    }
+
+    /// Returns at most `limit` processed batches following the batch `after`, or from the first
+    /// one, along with the ids of the tasks they finished. The batches are never deleted, unlike
+    /// their tasks.
+    pub fn list_batches(
+        &self,
+        txn: &RoTxn,
+        after: Option<BatchSeq>,
+        limit: usize,
+    ) -> Result<Vec<(BatchSeq, RoaringBitmap)>> {
+        let range = match after {
+            Some(after) => (Excluded(BEU32::new(after)), Unbounded),
+            None => (Unbounded, Unbounded),
+        };
+        let result: StdResult<Vec<_>, milli::heed::Error> = self
+            .batches
+            .range(txn, &range)?
+            .map(|r| r.map(|(seq, tasks)| (seq.get(), tasks)))
+            .take(limit)
+            .collect();
+
+        result.map_err(Into::into)
+    }
+
     /// Returns the unfinished tasks starting from the given taskId in ascending order.
     pub fn fetch_unfinished_tasks(&self, txn: &RoTxn, from: Option<TaskId>) -> Result<Vec<Task>> {
         // We must NEVER re-enqueue an already processed task! It's content uuid would point to an unexisting file.
@@ -127,10 +225,11 @@ impl Store {
         };
 
         let filter_fn = |task: &Task| {
-            filter
-                .as_ref()
-                .and_then(|f| f.filter_fn.as_ref())
-                .map_or(true, |f| f(task))
+            filter.as_ref().map_or(true, |filter| {
+                // the index patterns are only checked here, the tasks are not listed by index.
+                (!filter.has_index_patterns() || filter.pass(task))
+                    && filter.filter_fn.as_ref().map_or(true, |f| f(task))
+            })
         };
 
         let result: Result<Vec<_>> = match filter.as_ref().and_then(|f| f.filtered_indexes()) {
@@ -184,7 +283,7 @@ pub mod test {
     use nelson::Mocker;
     use tempfile::TempDir;
 
-    use crate::tasks::task::TaskContent;
+    use crate::tasks::task::{TaskContent, TaskPriority};
 
     use super::*;
 
@@ -247,6 +346,13 @@ pub mod test {
             }
         }
 
+        pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.delete(txn, task),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn get(&self, txn: &RoTxn, id: TaskId) -> Result<Option<Task>> {
             match self {
                 MockStore::Real(index) => index.get(txn, id),
@@ -254,6 +360,70 @@ pub mod test {
             }
         }
 
+        pub fn next_group_uid(&self, txn: &mut RwTxn) -> Result<GroupUid> {
+            match self {
+                MockStore::Real(index) => index.next_group_uid(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn put_group(
+            &self,
+            txn: &mut RwTxn,
+            uid: GroupUid,
+            tasks: &RoaringBitmap,
+        ) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.put_group(txn, uid, tasks),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn get_group(&self, txn: &RoTxn, uid: GroupUid) -> Result<Option<RoaringBitmap>> {
+            match self {
+                MockStore::Real(index) => index.get_group(txn, uid),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn next_batch_seq(&self, txn: &mut RwTxn) -> Result<BatchSeq> {
+            match self {
+                MockStore::Real(index) => index.next_batch_seq(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn put_batch(
+            &self,
+            txn: &mut RwTxn,
+            seq: BatchSeq,
+            tasks: &RoaringBitmap,
+        ) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.put_batch(txn, seq, tasks),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn last_batch_seq(&self, txn: &RoTxn) -> Result<Option<BatchSeq>> {
+            match self {
+                MockStore::Real(index) => index.last_batch_seq(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn list_batches(
+            &self,
+            txn: &RoTxn,
+            after: Option<BatchSeq>,
+            limit: usize,
+        ) -> Result<Vec<(BatchSeq, RoaringBitmap)>> {
+            match self {
+                MockStore::Real(index) => index.list_batches(txn, after, limit),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn fetch_unfinished_tasks(
             &self,
             txn: &RoTxn,
@@ -291,6 +461,10 @@ pub mod test {
                     index_uid: IndexUid::new_unchecked("test"),
                 },
                 events: vec![],
+                request_id: None,
+                priority: TaskPriority::default(),
+                group_uid: None,
+                batch_seq: None,
             })
             .collect::<Vec<_>>();
 
@@ -323,6 +497,10 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: vec![],
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
         let task_2 = Task {
@@ -331,6 +509,10 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test1"),
             },
             events: vec![],
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
         let mut txn = store.wtxn().unwrap();
@@ -353,6 +535,10 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: vec![],
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
         let task_2 = Task {
             id: 1,
@@ -360,6 +546,10 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test1"),
             },
             events: vec![],
+            request_id: None,
+            priority: TaskPriority::default(),
+            group_uid: None,
+            batch_seq: None,
         };
 
         let mut txn = store.wtxn().unwrap();
@@ -4,11 +4,12 @@ use std::time::Duration;
 use time::OffsetDateTime;
 use tokio::sync::{watch, RwLock};
 use tokio::time::interval_at;
+use tracing::Instrument;
 
 use super::batch::Batch;
 use super::error::Result;
 use super::{BatchHandler, Scheduler};
-use crate::tasks::task::TaskEvent;
+use crate::tasks::task::{TaskContent, TaskEvent};
 
 /// The update loop sequentially performs batches of updates by asking the scheduler for a batch,
 /// and handing it to the `TaskPerformer`.
@@ -75,9 +76,15 @@ impl UpdateLoop {
                 .await?
         };
 
-        let batch = performer.process_batch(batch).await;
+        let span = tracing::info_span!("process_batch", batch_id = ?batch.id, tasks = batch.len());
+        let batch = performer
+            .process_batch(batch)
+            .instrument(span.clone())
+            .await;
 
-        self.handle_batch_result(batch, performer).await?;
+        self.handle_batch_result(batch, performer)
+            .instrument(span)
+            .await?;
 
         Ok(())
     }
@@ -93,8 +100,18 @@ impl UpdateLoop {
         performer: Arc<dyn BatchHandler + Sync + Send + 'static>,
     ) -> Result<()> {
         let mut scheduler = self.scheduler.write().await;
+        scheduler.run_finished_hooks(batch.content.tasks());
         let content = scheduler.update_tasks(batch.content).await?;
         scheduler.finish();
+        scheduler.notify_finished(content.tasks());
+        // the tasks registered by a dump import are fetched by the next batch.
+        let imported = content
+            .tasks()
+            .iter()
+            .any(|task| matches!(task.content, TaskContent::DumpImport { .. }));
+        if imported {
+            scheduler.notify();
+        }
         drop(scheduler);
         batch.content = content;
         performer.finish(&batch).await;
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// The endpoint notified with the tasks that reach a terminal state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TaskWebhook {
+    pub url: String,
+    /// The value of the `Authorization` header sent with the notifications.
+    #[serde(default)]
+    pub authorization: Option<String>,
+}
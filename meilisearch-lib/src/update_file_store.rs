@@ -17,9 +17,13 @@ const UPDATE_FILES_PATH: &str = "updates/updates_files";
 
 use crate::document_formats::read_ndjson;
 
+/// The content of an update file can be written to several batches, its parts, that are read in
+/// order. The first part is stored at the path of the update file, the following ones next to it
+/// with a numbered extension.
 pub struct UpdateFile {
     path: PathBuf,
     file: NamedTempFile,
+    parts: Vec<NamedTempFile>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -48,12 +52,36 @@ into_update_store_error!(
 );
 
 impl UpdateFile {
+    /// Replaces the content of the update file by `parts`, created with
+    /// [`UpdateFileStore::new_update_part`].
+    pub fn set_parts(&mut self, parts: Vec<NamedTempFile>) {
+        self.parts = parts;
+    }
+
     pub fn persist(self) -> Result<()> {
-        self.file.persist(&self.path)?;
+        let mut parts = self.parts.into_iter();
+        match parts.next() {
+            Some(first) => {
+                first.persist(&self.path)?;
+                for (part, file) in parts.enumerate() {
+                    file.persist(part_path(&self.path, part + 1))?;
+                }
+            }
+            None => {
+                self.file.persist(&self.path)?;
+            }
+        }
         Ok(())
     }
 }
 
+/// Returns the path of the nth part of the update file stored at `path`.
+fn part_path(path: &Path, part: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{}", part));
+    PathBuf::from(path)
+}
+
 impl Deref for UpdateFile {
     type Target = NamedTempFile;
 
@@ -117,68 +145,112 @@ mod store {
             let file = NamedTempFile::new_in(&self.path)?;
             let uuid = Uuid::new_v4();
             let path = self.path.join(uuid.to_string());
-            let update_file = UpdateFile { file, path };
+            let update_file = UpdateFile {
+                file,
+                path,
+                parts: Vec::new(),
+            };
 
             Ok((uuid, update_file))
         }
 
-        /// Returns the file corresponding to the requested uuid.
-        pub fn get_update(&self, uuid: Uuid) -> Result<File> {
+        /// Creates a temporary file for a part of an update file, see [`UpdateFile::set_parts`].
+        pub fn new_update_part(&self) -> Result<NamedTempFile> {
+            Ok(NamedTempFile::new_in(&self.path)?)
+        }
+
+        /// Returns the paths of the parts of the update file pointed to by `uuid`, in order.
+        fn part_paths(&self, uuid: Uuid) -> Vec<PathBuf> {
             let path = self.path.join(uuid.to_string());
-            let file = File::open(path)?;
-            Ok(file)
+            let mut paths = vec![path.clone()];
+            for part in 1.. {
+                let part_path = part_path(&path, part);
+                if !part_path.exists() {
+                    break;
+                }
+                paths.push(part_path);
+            }
+            paths
+        }
+
+        /// Returns the parts of the file corresponding to the requested uuid, in order.
+        pub fn get_update(&self, uuid: Uuid) -> Result<Vec<File>> {
+            self.part_paths(uuid)
+                .into_iter()
+                .map(|path| Ok(File::open(path)?))
+                .collect()
         }
 
         /// Copies the content of the update file pointed to by `uuid` to the `dst` directory.
         pub fn snapshot(&self, uuid: Uuid, dst: impl AsRef<Path>) -> Result<()> {
-            let src = self.path.join(uuid.to_string());
-            let mut dst = dst.as_ref().join(UPDATE_FILES_PATH);
+            let dst = dst.as_ref().join(UPDATE_FILES_PATH);
             std::fs::create_dir_all(&dst)?;
-            dst.push(uuid.to_string());
-            std::fs::copy(src, dst)?;
+            for src in self.part_paths(uuid) {
+                // the part paths always end with a file name.
+                let name = src.file_name().unwrap();
+                std::fs::copy(&src, dst.join(name))?;
+            }
             Ok(())
         }
 
         /// Peforms a dump of the given update file uuid into the provided dump path.
         pub fn dump(&self, uuid: Uuid, dump_path: impl AsRef<Path>) -> Result<()> {
             let uuid_string = uuid.to_string();
-            let update_file_path = self.path.join(&uuid_string);
             let mut dst = dump_path.as_ref().join(UPDATE_FILES_PATH);
             std::fs::create_dir_all(&dst)?;
             dst.push(&uuid_string);
 
-            let update_file = File::open(update_file_path)?;
             let mut dst_file = NamedTempFile::new_in(&dump_path)?;
-            let mut document_reader = DocumentBatchReader::from_reader(update_file)?;
+            // The parts are dumped to a single file.
+            self.write_ndjson(uuid, &mut dst_file)?;
+            dst_file.persist(dst)?;
 
+            Ok(())
+        }
+
+        /// Writes the documents of the update file pointed to by `uuid` to `writer`, as NDJSON.
+        pub fn write_ndjson(&self, uuid: Uuid, mut writer: impl Write) -> Result<()> {
             let mut document_buffer = Map::new();
-            // TODO: we need to find a way to do this more efficiently. (create a custom serializer
-            // for jsonl for example...)
-            while let Some((index, document)) = document_reader.next_document_with_index()? {
-                for (field_id, content) in document.iter() {
-                    if let Some(field_name) = index.name(field_id) {
-                        let content = serde_json::from_slice(content)?;
-                        document_buffer.insert(field_name.to_string(), content);
+
+            for update_file in self.get_update(uuid)? {
+                let mut document_reader = DocumentBatchReader::from_reader(update_file)?;
+
+                // TODO: we need to find a way to do this more efficiently. (create a custom serializer
+                // for jsonl for example...)
+                while let Some((index, document)) = document_reader.next_document_with_index()? {
+                    for (field_id, content) in document.iter() {
+                        if let Some(field_name) = index.name(field_id) {
+                            let content = serde_json::from_slice(content)?;
+                            document_buffer.insert(field_name.to_string(), content);
+                        }
                     }
-                }
 
-                serde_json::to_writer(&mut dst_file, &document_buffer)?;
-                dst_file.write_all(b"\n")?;
-                document_buffer.clear();
+                    serde_json::to_writer(&mut writer, &document_buffer)?;
+                    writer.write_all(b"\n")?;
+                    document_buffer.clear();
+                }
             }
 
-            dst_file.persist(dst)?;
-
             Ok(())
         }
 
+        /// Returns whether the update file pointed to by `uuid` exists.
+        pub fn contains(&self, uuid: Uuid) -> bool {
+            self.path.join(uuid.to_string()).exists()
+        }
+
         pub fn get_size(&self, uuid: Uuid) -> Result<u64> {
-            Ok(self.get_update(uuid)?.metadata()?.len())
+            let mut size = 0;
+            for file in self.get_update(uuid)? {
+                size += file.metadata()?.len();
+            }
+            Ok(size)
         }
 
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
-            let path = self.path.join(uuid.to_string());
-            tokio::fs::remove_file(path).await?;
+            for path in self.part_paths(uuid) {
+                tokio::fs::remove_file(path).await?;
+            }
             Ok(())
         }
     }
@@ -218,7 +290,14 @@ mod test {
             }
         }
 
-        pub fn get_update(&self, uuid: Uuid) -> Result<File> {
+        pub fn new_update_part(&self) -> Result<NamedTempFile> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.new_update_part(),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn get_update(&self, uuid: Uuid) -> Result<Vec<File>> {
             match self {
                 MockUpdateFileStore::Real(s) => s.get_update(uuid),
                 MockUpdateFileStore::Mock(_) => todo!(),
@@ -239,6 +318,20 @@ mod test {
             }
         }
 
+        pub fn write_ndjson(&self, uuid: Uuid, writer: impl Write) -> Result<()> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.write_ndjson(uuid, writer),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn contains(&self, uuid: Uuid) -> bool {
+            match self {
+                MockUpdateFileStore::Real(s) => s.contains(uuid),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
         pub fn get_size(&self, uuid: Uuid) -> Result<u64> {
             match self {
                 MockUpdateFileStore::Real(s) => s.get_size(uuid),
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use actix_web::{self as aweb, http::StatusCode, HttpResponseBuilder};
+use enum_iterator::IntoEnumIterator;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -113,7 +114,7 @@ impl fmt::Display for ErrorType {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, IntoEnumIterator)]
 pub enum Code {
     // index related error
     CreateIndex,
@@ -154,6 +155,7 @@ pub enum Code {
 
     DumpAlreadyInProgress,
     DumpProcessFailed,
+    InvalidDump,
 
     InvalidContentType,
     MissingContentType,
@@ -168,11 +170,48 @@ pub enum Code {
     InvalidApiKeyDescription,
     InvalidApiKeyName,
     InvalidApiKeyUid,
+    InvalidApiKeyGracePeriod,
+    InvalidApiKeyAllowedIps,
+    InvalidApiKeyAllowedReferers,
+    InvalidApiKeyRateLimit,
+    InvalidApiKeyDisplayedAttributes,
+    InvalidApiKeyForbiddenAttributes,
     ImmutableField,
     ApiKeyAlreadyExists,
+
+    InvalidDocumentEditionFunction,
+    InvalidFacetSearchFacetName,
+    InvalidSearchHybridQuery,
+    InvalidSearchAttributesToSearchOn,
+    InvalidSearchDistinct,
+    InvalidSearchAttributeWeights,
+    InvalidSearchRankingProfile,
+    InvalidDocumentCursor,
+    MissingTaskFilters,
+    InvalidWebhookUrl,
+    InvalidLogLevel,
+    TaskNotEnqueued,
+    TaskNotCancelable,
+    GroupNotFound,
+    InvalidBatchOperation,
+    InvalidSwapIndexes,
+    AliasNotFound,
+    InvalidAlias,
+    IndexTemplateNotFound,
+    InvalidIndexTemplate,
+    TooManyRequests,
+    FeatureNotEnabled,
+    ReadOnlyMode,
+    UpdateFileNotFound,
+    ReplicationPositionUnavailable,
 }
 
 impl Code {
+    /// return all the `Code` variants, e.g. to document them
+    pub fn all() -> impl Iterator<Item = Code> {
+        Code::into_enum_iter()
+    }
+
     /// associate a `Code` variant to the actual ErrCode
     fn err_code(&self) -> ErrCode {
         use Code::*;
@@ -252,6 +291,7 @@ impl Code {
             DumpProcessFailed => {
                 ErrCode::internal("dump_process_failed", StatusCode::INTERNAL_SERVER_ERROR)
             }
+            InvalidDump => ErrCode::invalid("invalid_dump", StatusCode::BAD_REQUEST),
             MissingContentType => {
                 ErrCode::invalid("missing_content_type", StatusCode::UNSUPPORTED_MEDIA_TYPE)
             }
@@ -278,26 +318,96 @@ impl Code {
             }
             InvalidApiKeyName => ErrCode::invalid("invalid_api_key_name", StatusCode::BAD_REQUEST),
             InvalidApiKeyUid => ErrCode::invalid("invalid_api_key_uid", StatusCode::BAD_REQUEST),
+            InvalidApiKeyGracePeriod => {
+                ErrCode::invalid("invalid_api_key_grace_period", StatusCode::BAD_REQUEST)
+            }
+            InvalidApiKeyAllowedIps => {
+                ErrCode::invalid("invalid_api_key_allowed_ips", StatusCode::BAD_REQUEST)
+            }
+            InvalidApiKeyAllowedReferers => {
+                ErrCode::invalid("invalid_api_key_allowed_referers", StatusCode::BAD_REQUEST)
+            }
+            InvalidApiKeyRateLimit => {
+                ErrCode::invalid("invalid_api_key_rate_limit", StatusCode::BAD_REQUEST)
+            }
+            InvalidApiKeyDisplayedAttributes => ErrCode::invalid(
+                "invalid_api_key_displayed_attributes",
+                StatusCode::BAD_REQUEST,
+            ),
+            InvalidApiKeyForbiddenAttributes => ErrCode::invalid(
+                "invalid_api_key_forbidden_attributes",
+                StatusCode::BAD_REQUEST,
+            ),
             ApiKeyAlreadyExists => ErrCode::invalid("api_key_already_exists", StatusCode::CONFLICT),
             ImmutableField => ErrCode::invalid("immutable_field", StatusCode::BAD_REQUEST),
             InvalidMinWordLengthForTypo => {
                 ErrCode::invalid("invalid_min_word_length_for_typo", StatusCode::BAD_REQUEST)
             }
+            InvalidDocumentEditionFunction => {
+                ErrCode::invalid("invalid_document_edition_function", StatusCode::BAD_REQUEST)
+            }
+            InvalidFacetSearchFacetName => {
+                ErrCode::invalid("invalid_facet_search_facet_name", StatusCode::BAD_REQUEST)
+            }
+            InvalidSearchHybridQuery => {
+                ErrCode::invalid("invalid_search_hybrid_query", StatusCode::BAD_REQUEST)
+            }
+            InvalidSearchAttributesToSearchOn => ErrCode::invalid(
+                "invalid_search_attributes_to_search_on",
+                StatusCode::BAD_REQUEST,
+            ),
+            InvalidSearchDistinct => {
+                ErrCode::invalid("invalid_search_distinct", StatusCode::BAD_REQUEST)
+            }
+            InvalidSearchAttributeWeights => {
+                ErrCode::invalid("invalid_search_attribute_weights", StatusCode::BAD_REQUEST)
+            }
+            InvalidSearchRankingProfile => {
+                ErrCode::invalid("invalid_search_ranking_profile", StatusCode::BAD_REQUEST)
+            }
+            InvalidDocumentCursor => {
+                ErrCode::invalid("invalid_document_cursor", StatusCode::BAD_REQUEST)
+            }
+            MissingTaskFilters => ErrCode::invalid("missing_task_filters", StatusCode::BAD_REQUEST),
+            InvalidWebhookUrl => ErrCode::invalid("invalid_webhook_url", StatusCode::BAD_REQUEST),
+            InvalidLogLevel => ErrCode::invalid("invalid_log_level", StatusCode::BAD_REQUEST),
+            TaskNotEnqueued => ErrCode::invalid("task_not_enqueued", StatusCode::BAD_REQUEST),
+            TaskNotCancelable => ErrCode::invalid("task_not_cancelable", StatusCode::BAD_REQUEST),
+            GroupNotFound => ErrCode::invalid("group_not_found", StatusCode::NOT_FOUND),
+            InvalidBatchOperation => {
+                ErrCode::invalid("invalid_batch_operation", StatusCode::BAD_REQUEST)
+            }
+            InvalidSwapIndexes => ErrCode::invalid("invalid_swap_indexes", StatusCode::BAD_REQUEST),
+            AliasNotFound => ErrCode::invalid("alias_not_found", StatusCode::NOT_FOUND),
+            InvalidAlias => ErrCode::invalid("invalid_alias", StatusCode::BAD_REQUEST),
+            IndexTemplateNotFound => {
+                ErrCode::invalid("index_template_not_found", StatusCode::NOT_FOUND)
+            }
+            InvalidIndexTemplate => {
+                ErrCode::invalid("invalid_index_template", StatusCode::BAD_REQUEST)
+            }
+            TooManyRequests => ErrCode::invalid("too_many_requests", StatusCode::TOO_MANY_REQUESTS),
+            FeatureNotEnabled => ErrCode::invalid("feature_not_enabled", StatusCode::BAD_REQUEST),
+            ReadOnlyMode => ErrCode::invalid("read_only_mode", StatusCode::SERVICE_UNAVAILABLE),
+            UpdateFileNotFound => ErrCode::invalid("update_file_not_found", StatusCode::NOT_FOUND),
+            ReplicationPositionUnavailable => {
+                ErrCode::invalid("replication_position_unavailable", StatusCode::GONE)
+            }
         }
     }
 
     /// return the HTTP status code associated with the `Code`
-    fn http(&self) -> StatusCode {
+    pub fn http(&self) -> StatusCode {
         self.err_code().status_code
     }
 
     /// return error name, used as error code
-    fn name(&self) -> String {
+    pub fn name(&self) -> String {
         self.err_code().error_name.to_string()
     }
 
     /// return the error type
-    fn type_(&self) -> String {
+    pub fn type_(&self) -> String {
         self.err_code().error_type.to_string()
     }
 
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::index_uid::IndexUid;
+
+/// An index uid, optionally ending with a `*` that matches the index uids starting with the
+/// rest of the pattern, e.g. `products*`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IndexUidPattern(String);
+
+impl IndexUidPattern {
+    pub fn new_unchecked(s: impl AsRef<str>) -> Self {
+        Self(s.as_ref().to_string())
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    /// Returns whether the pattern matches several indexes.
+    pub fn is_pattern(&self) -> bool {
+        self.0.ends_with('*')
+    }
+
+    /// Returns whether the index uid `uid` matches this pattern.
+    pub fn matches_str(&self, uid: &str) -> bool {
+        matches(&self.0, uid)
+    }
+}
+
+/// Returns whether the index uid `uid` matches the index uid pattern `pattern`.
+pub fn matches(pattern: &str, uid: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => uid.starts_with(prefix),
+        None => pattern == uid,
+    }
+}
+
+impl std::ops::Deref for IndexUidPattern {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for IndexUidPattern {
+    type Error = IndexUidPatternFormatError;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        let uid = pattern.strip_suffix('*').unwrap_or(&pattern);
+        match IndexUid::from_str(uid) {
+            Ok(_) => Ok(IndexUidPattern(pattern)),
+            Err(_) => Err(IndexUidPatternFormatError {
+                invalid_pattern: pattern,
+            }),
+        }
+    }
+}
+
+impl FromStr for IndexUidPattern {
+    type Err = IndexUidPatternFormatError;
+
+    fn from_str(pattern: &str) -> Result<IndexUidPattern, IndexUidPatternFormatError> {
+        pattern.to_string().try_into()
+    }
+}
+
+impl From<IndexUidPattern> for String {
+    fn from(pattern: IndexUidPattern) -> Self {
+        pattern.into_inner()
+    }
+}
+
+#[derive(Debug)]
+pub struct IndexUidPatternFormatError {
+    pub invalid_pattern: String,
+}
+
+impl fmt::Display for IndexUidPatternFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid index uid pattern `{}`, the pattern must be an index uid \
+            optionally followed by a `*`.",
+            self.invalid_pattern,
+        )
+    }
+}
+
+impl Error for IndexUidPatternFormatError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_match_patterns() {
+        let pattern = IndexUidPattern::from_str("products*").unwrap();
+        assert!(pattern.is_pattern());
+        assert!(pattern.matches_str("products"));
+        assert!(pattern.matches_str("products-eu"));
+        assert!(!pattern.matches_str("sales"));
+
+        let exact = IndexUidPattern::from_str("products").unwrap();
+        assert!(!exact.is_pattern());
+        assert!(exact.matches_str("products"));
+        assert!(!exact.matches_str("products-eu"));
+
+        assert!(IndexUidPattern::from_str("pro*ducts").is_err());
+        assert!(IndexUidPattern::from_str("*").is_err());
+        assert!(IndexUidPattern::from_str("products**").is_err());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod error;
 pub mod index_uid;
+pub mod index_uid_pattern;
 pub mod star_or;